@@ -53,6 +53,11 @@ pub async fn diagnostics(matches: &ArgMatches, env: Option<&shared::env::Env>) -
         "sqlx migrations",
         &env.database_migrate.to_string(),
     );
+    write_line(
+        &mut output,
+        "metrics enabled",
+        &env.metrics_token.is_some().to_string(),
+    );
     writeln!(output).unwrap();
     write_line(
         &mut output,
@@ -555,7 +555,7 @@ pub async fn import(matches: &ArgMatches, env: Option<&shared::env::Env>) -> i32
 
                 let row = sqlx::query(
                     r#"
-                    INSERT INTO locations (name, description, created)
+                    INSERT INTO locations (short_name, name, description, created)
                     VALUES ($1, $1, $2, $3)
                     ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name
                     RETURNING uuid
@@ -252,9 +252,16 @@ async fn main() {
         extension_internal_list::list(),
     ));
     let settings = Arc::new(shared::settings::Settings::new(database.clone()).await);
-    let storage = Arc::new(shared::storage::Storage::new(settings.clone()));
+    let storage = Arc::new(shared::storage::Storage::new(
+        settings.clone(),
+        database.clone(),
+    ));
     let captcha = Arc::new(shared::captcha::Captcha::new(settings.clone()));
-    let mail = Arc::new(shared::mail::Mail::new(settings.clone()));
+    let mail = Arc::new(shared::mail::Mail::new(
+        settings.clone(),
+        database.clone(),
+        env.clone(),
+    ));
 
     let state = Arc::new(shared::AppState {
         start_time: Instant::now(),
@@ -276,6 +283,53 @@ async fn main() {
         env,
     });
 
+    tokio::spawn(shared::jobs::work(state.clone()));
+
+    tokio::spawn({
+        let state = state.clone();
+
+        async move {
+            loop {
+                tokio::time::sleep(std::time::Duration::from_secs(60 * 60)).await;
+
+                if let Err(err) = shared::jobs::enqueue_unique(
+                    &state.database,
+                    &shared::jobs::server::PruneServerBackups {},
+                )
+                .await
+                {
+                    tracing::error!("failed to queue server backup pruning: {:#?}", err);
+                }
+
+                if let Err(err) = shared::jobs::enqueue_unique(
+                    &state.database,
+                    &shared::jobs::webhook::PruneWebhookDeliveries {},
+                )
+                .await
+                {
+                    tracing::error!("failed to queue webhook delivery pruning: {:#?}", err);
+                }
+            }
+        }
+    });
+
+    tokio::spawn({
+        let state = state.clone();
+
+        async move {
+            loop {
+                tokio::time::sleep(std::time::Duration::from_secs(5 * 60)).await;
+
+                if let Err(err) =
+                    shared::models::nest_egg_source::NestEggSource::enqueue_due(&state.database)
+                        .await
+                {
+                    tracing::error!("failed to queue egg source syncs: {:#?}", err);
+                }
+            }
+        }
+    });
+
     let routes = extensions.init(state.clone()).await;
     let mut extension_router = OpenApiRouter::new().with_state(state.clone());
 
@@ -400,6 +454,7 @@ async fn main() {
                 },
             ),
         )
+        .route("/metrics", get(panel_rs::routes::metrics::route))
         .fallback(|req: Request<Body>| async move {
             if !req.uri().path().starts_with("/api") {
                 let path = &req.uri().path()[1..];
@@ -450,6 +505,7 @@ async fn main() {
         .layer(axum::middleware::from_fn(handle_request))
         .layer(CookieManagerLayer::new())
         .route_layer(axum::middleware::from_fn(handle_postprocessing))
+        .route_layer(axum::middleware::from_fn(panel_rs::routes::metrics::handle))
         .route_layer(SentryHttpLayer::new().enable_transaction())
         .with_state(state.clone());
 
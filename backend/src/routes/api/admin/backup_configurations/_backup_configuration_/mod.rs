@@ -155,7 +155,10 @@ mod patch {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
-        models::{admin_activity::GetAdminActivityLogger, user::GetPermissionManager},
+        models::{
+            admin_activity::GetAdminActivityLogger, backup_configurations::BackupConfiguration,
+            user::GetPermissionManager,
+        },
         response::{ApiResponse, ApiResponseResult},
     };
     use utoipa::ToSchema;
@@ -172,6 +175,8 @@ mod patch {
 
         backup_disk: Option<shared::models::server_backup::BackupDisk>,
         backup_configs: Option<shared::models::backup_configurations::BackupConfigs>,
+        #[validate(nested)]
+        retention: Option<shared::models::backup_retention::BackupRetention>,
     }
 
     #[derive(ToSchema, Serialize)]
@@ -250,6 +255,15 @@ mod patch {
             }
         }
 
+        if let Some(retention) = data.retention {
+            BackupConfiguration::update_retention(
+                &state.database,
+                backup_configuration.uuid,
+                retention,
+            )
+            .await?;
+        }
+
         activity_logger
             .log(
                 "backup-configuration:update",
@@ -105,6 +105,9 @@ mod post {
         backup_disk: shared::models::server_backup::BackupDisk,
         #[serde(default)]
         backup_configs: shared::models::backup_configurations::BackupConfigs,
+        #[validate(nested)]
+        #[serde(default)]
+        retention: shared::models::backup_retention::BackupRetention,
     }
 
     #[derive(ToSchema, Serialize)]
@@ -137,6 +140,7 @@ mod post {
             data.description.as_deref(),
             data.backup_disk,
             data.backup_configs,
+            data.retention,
         )
         .await
         {
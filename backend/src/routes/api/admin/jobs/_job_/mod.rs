@@ -0,0 +1,152 @@
+use super::State;
+use axum::{
+    extract::{Path, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use shared::{
+    GetState,
+    models::{queued_job::QueuedJob, user::GetPermissionManager},
+    response::ApiResponse,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod retry;
+
+pub async fn auth(
+    state: GetState,
+    permissions: GetPermissionManager,
+    Path(job): Path<Vec<String>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let job = match job.first().map(|s| s.parse::<uuid::Uuid>()) {
+        Some(Ok(id)) => id,
+        _ => {
+            return Ok(ApiResponse::error("invalid job uuid")
+                .with_status(StatusCode::BAD_REQUEST)
+                .into_response());
+        }
+    };
+
+    if let Err(err) = permissions.has_admin_permission("jobs.read") {
+        return Ok(err.into_response());
+    }
+
+    let job = QueuedJob::by_uuid(&state.database, job).await;
+    let job = match job {
+        Ok(Some(job)) => job,
+        Ok(None) => {
+            return Ok(ApiResponse::error("job not found")
+                .with_status(StatusCode::NOT_FOUND)
+                .into_response());
+        }
+        Err(err) => return Ok(ApiResponse::from(err).into_response()),
+    };
+
+    req.extensions_mut().insert(job);
+
+    Ok(next.run(req).await)
+}
+
+mod get {
+    use serde::Serialize;
+    use shared::{
+        ApiError,
+        models::{queued_job::GetQueuedJob, user::GetPermissionManager},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        job: shared::models::queued_job::AdminApiQueuedJob,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "job" = uuid::Uuid,
+            description = "The job ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(permissions: GetPermissionManager, job: GetQueuedJob) -> ApiResponseResult {
+        permissions.has_admin_permission("jobs.read")?;
+
+        ApiResponse::json(Response {
+            job: job.0.into_admin_api_object(),
+        })
+        .ok()
+    }
+}
+
+mod delete {
+    use axum::http::StatusCode;
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger,
+            queued_job::{GetQueuedJob, QueuedJob, QueuedJobStatus},
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(delete, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+    ), params(
+        (
+            "job" = uuid::Uuid,
+            description = "The job ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        job: GetQueuedJob,
+        activity_logger: GetAdminActivityLogger,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("jobs.delete")?;
+
+        if job.status == QueuedJobStatus::Running {
+            return ApiResponse::error("job is currently running")
+                .with_status(StatusCode::CONFLICT)
+                .ok();
+        }
+
+        QueuedJob::delete_by_uuid(&state.database, job.uuid).await?;
+
+        activity_logger
+            .log(
+                "job:delete",
+                serde_json::json!({
+                    "uuid": job.uuid,
+                    "kind": job.kind,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .routes(routes!(delete::route))
+        .nest("/retry", retry::router(state))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
+        .with_state(state.clone())
+}
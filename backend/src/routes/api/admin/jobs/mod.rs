@@ -0,0 +1,97 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod _job_;
+
+mod get {
+    use axum::{extract::Query, http::StatusCode};
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{
+            Pagination, PaginationParamsWithSearch,
+            queued_job::{QueuedJob, QueuedJobStatus},
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(Deserialize)]
+    pub struct Params {
+        status: Option<QueuedJobStatus>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        #[schema(inline)]
+        jobs: Pagination<shared::models::queued_job::AdminApiQueuedJob>,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ), params(
+        (
+            "page" = i64, Query,
+            description = "The page number",
+            example = "1",
+        ),
+        (
+            "per_page" = i64, Query,
+            description = "The number of items per page",
+            example = "10",
+        ),
+        (
+            "search" = Option<String>, Query,
+            description = "Search term for the job kind",
+        ),
+        (
+            "status" = Option<QueuedJobStatus>, Query,
+            description = "Only list jobs with this status",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        Query(params): Query<PaginationParamsWithSearch>,
+        Query(filter): Query<Params>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&params) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("jobs.read")?;
+
+        let jobs = QueuedJob::all_with_pagination(
+            &state.database,
+            params.page,
+            params.per_page,
+            params.search.as_deref(),
+            filter.status,
+        )
+        .await?;
+
+        ApiResponse::json(Response {
+            jobs: Pagination {
+                total: jobs.total,
+                per_page: jobs.per_page,
+                page: jobs.page,
+                data: jobs
+                    .data
+                    .into_iter()
+                    .map(|job| job.into_admin_api_object())
+                    .collect(),
+            },
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .nest("/{job}", _job_::router(state))
+        .with_state(state.clone())
+}
@@ -0,0 +1,190 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod put {
+    use axum::{extract::Path, http::StatusCode};
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        mail::template::MailTemplateKind,
+        models::{
+            admin_activity::GetAdminActivityLogger,
+            mail_template::MailTemplate,
+            user::{GetPermissionManager, LANGUAGE_REGEX},
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        #[validate(length(min = 1, max = 255))]
+        #[schema(min_length = 1, max_length = 255)]
+        subject: String,
+        #[validate(length(min = 1, max = 65535))]
+        #[schema(min_length = 1, max_length = 65535)]
+        html: String,
+        #[validate(length(min = 1, max = 65535))]
+        #[schema(min_length = 1, max_length = 65535)]
+        text: String,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        mail_template: shared::models::mail_template::AdminApiMailTemplate,
+    }
+
+    #[utoipa::path(put, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "mail_template" = String,
+            description = "The mail template name",
+            example = "password_reset",
+        ),
+        (
+            "language" = String,
+            description = "The language of the override",
+            example = "en",
+        ),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        activity_logger: GetAdminActivityLogger,
+        Path((mail_template, language)): Path<(String, String)>,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("mail-templates.update")?;
+
+        let kind = match MailTemplateKind::from_name(&mail_template) {
+            Some(kind) => kind,
+            None => {
+                return ApiResponse::error("mail template not found")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+        };
+
+        if language.len() > 15 || !LANGUAGE_REGEX.is_match(&language) {
+            return ApiResponse::error("invalid language")
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        for (field, source) in [
+            ("subject", &data.subject),
+            ("html", &data.html),
+            ("text", &data.text),
+        ] {
+            if let Err(err) = kind.validate(source) {
+                return ApiResponse::error(&format!("{field}: {err}"))
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+        }
+
+        let mail_template = MailTemplate::upsert(
+            &state.database,
+            kind.name(),
+            &language,
+            &data.subject,
+            &data.html,
+            &data.text,
+        )
+        .await?;
+
+        activity_logger
+            .log(
+                "mail-template:update",
+                serde_json::json!({
+                    "name": mail_template.name,
+                    "language": mail_template.language,
+                    "subject": mail_template.subject,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {
+            mail_template: mail_template.into_admin_api_object(),
+        })
+        .ok()
+    }
+}
+
+mod delete {
+    use axum::{extract::Path, http::StatusCode};
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, mail_template::MailTemplate,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(delete, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "mail_template" = String,
+            description = "The mail template name",
+            example = "password_reset",
+        ),
+        (
+            "language" = String,
+            description = "The language of the override",
+            example = "en",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        activity_logger: GetAdminActivityLogger,
+        Path((mail_template, language)): Path<(String, String)>,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("mail-templates.update")?;
+
+        if !MailTemplate::delete_by_name_language(&state.database, &mail_template, &language)
+            .await?
+        {
+            return ApiResponse::error("mail template override not found")
+                .with_status(StatusCode::NOT_FOUND)
+                .ok();
+        }
+
+        activity_logger
+            .log(
+                "mail-template:delete",
+                serde_json::json!({
+                    "name": mail_template,
+                    "language": language,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(put::route))
+        .routes(routes!(delete::route))
+        .with_state(state.clone())
+}
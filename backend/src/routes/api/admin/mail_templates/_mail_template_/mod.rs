@@ -0,0 +1,88 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod _language_;
+
+mod get {
+    use axum::{extract::Path, http::StatusCode};
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        mail::template::{COMMON_VARIABLES, MailTemplateKind},
+        models::{mail_template::MailTemplate, user::GetPermissionManager},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct ResponseDefault {
+        subject: &'static str,
+        html: &'static str,
+        text: &'static str,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        name: &'static str,
+        variables: Vec<&'static str>,
+        #[schema(inline)]
+        default: ResponseDefault,
+        overrides: Vec<shared::models::mail_template::AdminApiMailTemplate>,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "mail_template" = String,
+            description = "The mail template name",
+            example = "password_reset",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        Path(mail_template): Path<String>,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("mail-templates.read")?;
+
+        let kind = match MailTemplateKind::from_name(&mail_template) {
+            Some(kind) => kind,
+            None => {
+                return ApiResponse::error("mail template not found")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+        };
+
+        let (subject, html, text) = kind.default_content();
+
+        ApiResponse::json(Response {
+            name: kind.name(),
+            variables: COMMON_VARIABLES
+                .iter()
+                .chain(kind.variables())
+                .copied()
+                .collect(),
+            default: ResponseDefault {
+                subject,
+                html,
+                text,
+            },
+            overrides: MailTemplate::all_by_name(&state.database, kind.name())
+                .await?
+                .into_iter()
+                .map(|mail_template| mail_template.into_admin_api_object())
+                .collect(),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .nest("/{language}", _language_::router(state))
+        .with_state(state.clone())
+}
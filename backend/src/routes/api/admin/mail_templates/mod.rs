@@ -0,0 +1,62 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod _mail_template_;
+
+mod get {
+    use serde::Serialize;
+    use shared::{
+        GetState,
+        mail::template::{COMMON_VARIABLES, MailTemplateKind},
+        models::{mail_template::MailTemplate, user::GetPermissionManager},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct ResponseMailTemplate {
+        name: &'static str,
+        variables: Vec<&'static str>,
+        /// Languages with an override of the built in template.
+        languages: Vec<String>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        #[schema(inline)]
+        mail_templates: Vec<ResponseMailTemplate>,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ))]
+    pub async fn route(state: GetState, permissions: GetPermissionManager) -> ApiResponseResult {
+        permissions.has_admin_permission("mail-templates.read")?;
+
+        let mut mail_templates = Vec::with_capacity(MailTemplateKind::ALL.len());
+        for kind in MailTemplateKind::ALL {
+            mail_templates.push(ResponseMailTemplate {
+                name: kind.name(),
+                variables: COMMON_VARIABLES
+                    .iter()
+                    .chain(kind.variables())
+                    .copied()
+                    .collect(),
+                languages: MailTemplate::all_by_name(&state.database, kind.name())
+                    .await?
+                    .into_iter()
+                    .map(|mail_template| mail_template.language)
+                    .collect(),
+            });
+        }
+
+        ApiResponse::json(Response { mail_templates }).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .nest("/{mail_template}", _mail_template_::router(state))
+        .with_state(state.clone())
+}
@@ -19,14 +19,18 @@ use utoipa_axum::router::OpenApiRouter;
 mod activity;
 mod backup_configurations;
 mod database_hosts;
+mod jobs;
 mod locations;
+mod mail_templates;
 mod mounts;
 mod nests;
 mod nodes;
 mod roles;
+mod server_templates;
 mod servers;
 mod settings;
 mod users;
+mod webhooks;
 
 pub async fn auth(
     state: GetState,
@@ -60,8 +64,10 @@ pub async fn auth(
 pub fn router(state: &State) -> OpenApiRouter<State> {
     OpenApiRouter::new()
         .nest("/settings", settings::router(state))
+        .nest("/mail-templates", mail_templates::router(state))
         .nest("/locations", locations::router(state))
         .nest("/servers", servers::router(state))
+        .nest("/server-templates", server_templates::router(state))
         .nest("/nodes", nodes::router(state))
         .nest("/nests", nests::router(state))
         .nest("/database-hosts", database_hosts::router(state))
@@ -72,6 +78,8 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
         .nest("/mounts", mounts::router(state))
         .nest("/users", users::router(state))
         .nest("/roles", roles::router(state))
+        .nest("/webhooks", webhooks::router(state))
+        .nest("/jobs", jobs::router(state))
         .nest("/activity", activity::router(state))
         .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
         .route_layer(axum::middleware::from_fn_with_state(
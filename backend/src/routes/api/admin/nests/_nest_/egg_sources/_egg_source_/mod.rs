@@ -0,0 +1,279 @@
+use crate::routes::api::admin::nests::_nest_::GetNest;
+
+use super::State;
+use axum::{
+    extract::{Path, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use shared::{
+    GetState,
+    models::{nest_egg_source::NestEggSource, user::GetPermissionManager},
+    response::ApiResponse,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod sync;
+
+pub type GetNestEggSource = shared::extract::ConsumingExtension<NestEggSource>;
+
+pub async fn auth(
+    state: GetState,
+    permissions: GetPermissionManager,
+    nest: GetNest,
+    Path(egg_source): Path<Vec<String>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let egg_source = match egg_source.get(1).map(|s| s.parse::<uuid::Uuid>()) {
+        Some(Ok(id)) => id,
+        _ => {
+            return Ok(ApiResponse::error("invalid egg source id")
+                .with_status(StatusCode::BAD_REQUEST)
+                .into_response());
+        }
+    };
+
+    if let Err(err) = permissions.has_admin_permission("eggs.sources") {
+        return Ok(err.into_response());
+    }
+
+    let egg_source = NestEggSource::by_nest_uuid_uuid(&state.database, nest.uuid, egg_source).await;
+    let egg_source = match egg_source {
+        Ok(Some(egg_source)) => egg_source,
+        Ok(None) => {
+            return Ok(ApiResponse::error("egg source not found")
+                .with_status(StatusCode::NOT_FOUND)
+                .into_response());
+        }
+        Err(err) => return Ok(ApiResponse::from(err).into_response()),
+    };
+
+    req.extensions_mut().insert(nest.0);
+    req.extensions_mut().insert(egg_source);
+
+    Ok(next.run(req).await)
+}
+
+mod get {
+    use crate::routes::api::admin::nests::_nest_::egg_sources::_egg_source_::GetNestEggSource;
+    use serde::Serialize;
+    use shared::{
+        ApiError,
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        egg_source: shared::models::nest_egg_source::AdminApiNestEggSource,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "egg_source" = uuid::Uuid,
+            description = "The egg source ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(egg_source: GetNestEggSource) -> ApiResponseResult {
+        ApiResponse::json(Response {
+            egg_source: egg_source.0.into_admin_api_object(),
+        })
+        .ok()
+    }
+}
+
+mod delete {
+    use crate::routes::api::admin::nests::_nest_::{
+        GetNest, egg_sources::_egg_source_::GetNestEggSource,
+    };
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, nest_egg_source::NestEggSource,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(delete, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "egg_source" = uuid::Uuid,
+            description = "The egg source ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        nest: GetNest,
+        egg_source: GetNestEggSource,
+        activity_logger: GetAdminActivityLogger,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("eggs.sources")?;
+
+        NestEggSource::delete_by_uuid(&state.database, egg_source.uuid).await?;
+
+        activity_logger
+            .log(
+                "nest:egg-source.delete",
+                serde_json::json!({
+                    "uuid": egg_source.uuid,
+                    "nest_uuid": nest.uuid,
+
+                    "name": egg_source.name,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+mod patch {
+    use crate::routes::api::admin::nests::_nest_::{
+        GetNest, egg_sources::_egg_source_::GetNestEggSource,
+    };
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{admin_activity::GetAdminActivityLogger, user::GetPermissionManager},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        #[validate(length(min = 3, max = 255))]
+        #[schema(min_length = 3, max_length = 255)]
+        name: Option<String>,
+        #[validate(length(min = 1, max = 2048))]
+        #[schema(min_length = 1, max_length = 2048)]
+        url: Option<String>,
+        /// The sync interval in minutes, `0` disables scheduled syncs.
+        #[validate(range(min = 0))]
+        #[schema(minimum = 0)]
+        sync_interval: Option<i32>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(patch, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "egg_source" = uuid::Uuid,
+            description = "The egg source ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        nest: GetNest,
+        mut egg_source: GetNestEggSource,
+        activity_logger: GetAdminActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("eggs.sources")?;
+
+        if let Some(name) = data.name {
+            egg_source.name = name;
+        }
+        if let Some(url) = data.url {
+            egg_source.url = url;
+        }
+        match data.sync_interval {
+            Some(0) => egg_source.sync_interval = None,
+            Some(1..5) => {
+                return ApiResponse::error("sync_interval: must be at least 5 minutes")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+            Some(sync_interval) => egg_source.sync_interval = Some(sync_interval),
+            None => {}
+        }
+
+        match egg_source.update(&state.database).await {
+            Ok(_) => {}
+            Err(err) if err.to_string().contains("unique constraint") => {
+                return ApiResponse::error("egg source with name already exists")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Err(err) => {
+                tracing::error!("failed to update egg source: {:#?}", err);
+
+                return ApiResponse::error("failed to update egg source")
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
+        }
+
+        activity_logger
+            .log(
+                "nest:egg-source.update",
+                serde_json::json!({
+                    "uuid": egg_source.uuid,
+                    "nest_uuid": nest.uuid,
+
+                    "name": egg_source.name,
+                    "url": egg_source.url,
+                    "sync_interval": egg_source.sync_interval,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .routes(routes!(delete::route))
+        .routes(routes!(patch::route))
+        .nest("/sync", sync::router(state))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
+        .with_state(state.clone())
+}
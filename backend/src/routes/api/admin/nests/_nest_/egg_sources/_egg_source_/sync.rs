@@ -0,0 +1,118 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use crate::routes::api::admin::nests::_nest_::{
+        GetNest, egg_sources::_egg_source_::GetNestEggSource,
+    };
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        jobs::nest::SyncNestEggSource,
+        models::{
+            admin_activity::GetAdminActivityLogger,
+            nest_egg_source::{NestEggSource, NestEggSourceSyncReport},
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct Payload {
+        /// Only report what a sync would change.
+        #[serde(default)]
+        dry_run: bool,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        job_uuid: Option<uuid::Uuid>,
+        report: Option<NestEggSourceSyncReport>,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+        (status = EXPECTATION_FAILED, body = ApiError),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "egg_source" = uuid::Uuid,
+            description = "The egg source ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        nest: GetNest,
+        egg_source: GetNestEggSource,
+        activity_logger: GetAdminActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("eggs.sources")?;
+
+        if data.dry_run {
+            let report = match egg_source.sync(&state.database, &state.env, true).await {
+                Ok(report) => report,
+                Err(err) => {
+                    return ApiResponse::error(&format!("failed to read egg source: {err}"))
+                        .with_status(StatusCode::EXPECTATION_FAILED)
+                        .ok();
+                }
+            };
+
+            return ApiResponse::json(Response {
+                job_uuid: None,
+                report: Some(report),
+            })
+            .ok();
+        }
+
+        if NestEggSource::is_syncing(&state.database, egg_source.uuid).await? {
+            return ApiResponse::error("egg source is already syncing")
+                .with_status(StatusCode::CONFLICT)
+                .ok();
+        }
+
+        let job_uuid = shared::jobs::enqueue(
+            &state.database,
+            &SyncNestEggSource {
+                source_uuid: egg_source.uuid,
+            },
+        )
+        .await?;
+
+        activity_logger
+            .log(
+                "nest:egg-source.sync",
+                serde_json::json!({
+                    "uuid": egg_source.uuid,
+                    "nest_uuid": nest.uuid,
+
+                    "name": egg_source.name,
+                    "job_uuid": job_uuid,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {
+            job_uuid: Some(job_uuid),
+            report: None,
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
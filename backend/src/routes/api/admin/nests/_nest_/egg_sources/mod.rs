@@ -0,0 +1,198 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod _egg_source_;
+
+mod get {
+    use crate::routes::api::admin::nests::_nest_::GetNest;
+    use axum::{extract::Query, http::StatusCode};
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            Pagination, PaginationParamsWithSearch, nest_egg_source::NestEggSource,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        #[schema(inline)]
+        egg_sources: Pagination<shared::models::nest_egg_source::AdminApiNestEggSource>,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "page" = i64, Query,
+            description = "The page number",
+            example = "1",
+        ),
+        (
+            "per_page" = i64, Query,
+            description = "The number of items per page",
+            example = "10",
+        ),
+        (
+            "search" = Option<String>, Query,
+            description = "Search term for items",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        nest: GetNest,
+        Query(params): Query<PaginationParamsWithSearch>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&params) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("eggs.sources")?;
+
+        let egg_sources = NestEggSource::by_nest_uuid_with_pagination(
+            &state.database,
+            nest.uuid,
+            params.page,
+            params.per_page,
+            params.search.as_deref(),
+        )
+        .await?;
+
+        ApiResponse::json(Response {
+            egg_sources: Pagination {
+                total: egg_sources.total,
+                per_page: egg_sources.per_page,
+                page: egg_sources.page,
+                data: egg_sources
+                    .data
+                    .into_iter()
+                    .map(|egg_source| egg_source.into_admin_api_object())
+                    .collect(),
+            },
+        })
+        .ok()
+    }
+}
+
+mod post {
+    use crate::routes::api::admin::nests::_nest_::GetNest;
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, nest_egg_source::NestEggSource,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        #[validate(length(min = 3, max = 255))]
+        #[schema(min_length = 3, max_length = 255)]
+        name: String,
+        #[validate(length(min = 1, max = 2048))]
+        #[schema(min_length = 1, max_length = 2048)]
+        url: String,
+        #[validate(range(min = 5))]
+        #[schema(minimum = 5)]
+        sync_interval: Option<i32>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        egg_source: shared::models::nest_egg_source::AdminApiNestEggSource,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        )
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        nest: GetNest,
+        activity_logger: GetAdminActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("eggs.sources")?;
+
+        let egg_source = match NestEggSource::create(
+            &state.database,
+            nest.uuid,
+            &data.name,
+            &data.url,
+            data.sync_interval,
+        )
+        .await
+        {
+            Ok(egg_source) => egg_source,
+            Err(err) if err.to_string().contains("unique constraint") => {
+                return ApiResponse::error("egg source with name already exists")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Err(err) => {
+                tracing::error!("failed to create egg source: {:#?}", err);
+
+                return ApiResponse::error("failed to create egg source")
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
+        };
+
+        activity_logger
+            .log(
+                "nest:egg-source.create",
+                serde_json::json!({
+                    "uuid": egg_source.uuid,
+                    "nest_uuid": nest.uuid,
+
+                    "name": egg_source.name,
+                    "url": egg_source.url,
+                    "sync_interval": egg_source.sync_interval,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {
+            egg_source: egg_source.into_admin_api_object(),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .routes(routes!(post::route))
+        .nest("/{egg_source}", _egg_source_::router(state))
+        .with_state(state.clone())
+}
@@ -0,0 +1,71 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod get {
+    use crate::routes::api::admin::nests::_nest_::eggs::_egg_::GetNestEgg;
+    use axum::extract::Query;
+    use serde::Deserialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            nest_egg::NestEggExportFormat, nest_egg_variable::NestEggVariable,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+
+    #[derive(Deserialize)]
+    pub struct Params {
+        #[serde(default)]
+        format: NestEggExportFormat,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = serde_json::Value),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "egg" = uuid::Uuid,
+            description = "The egg ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "format" = Option<NestEggExportFormat>, Query,
+            description = "The format of the exported egg, defaults to ptdl_v2",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        egg: GetNestEgg,
+        Query(params): Query<Params>,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("eggs.read")?;
+
+        let variables = NestEggVariable::all_by_egg_uuid(state.database.read(), egg.uuid).await?;
+        let file_name: String = egg
+            .name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+
+        ApiResponse::json(egg.export(&variables, params.format))
+            .with_header(
+                "Content-Disposition",
+                &format!("attachment; filename=\"egg-{file_name}.json\""),
+            )
+            .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .with_state(state.clone())
+}
@@ -14,7 +14,9 @@ use shared::{
 };
 use utoipa_axum::{router::OpenApiRouter, routes};
 
+mod export;
 mod mounts;
+mod revisions;
 mod variables;
 
 pub type GetNestEgg = shared::extract::ConsumingExtension<NestEgg>;
@@ -170,7 +172,10 @@ mod patch {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
-        models::{admin_activity::GetAdminActivityLogger, user::GetPermissionManager},
+        models::{
+            admin_activity::GetAdminActivityLogger, nest_egg::NestEgg,
+            nest_egg_revision::NestEggRevision, user::GetPermissionManager,
+        },
         response::{ApiResponse, ApiResponseResult},
     };
     use utoipa::ToSchema;
@@ -204,6 +209,7 @@ mod patch {
         #[schema(min_length = 1, max_length = 255)]
         startup: Option<String>,
         force_outgoing_ip: Option<bool>,
+        deployable: Option<bool>,
 
         features: Option<Vec<String>>,
         docker_images: Option<IndexMap<String, String>>,
@@ -286,6 +292,9 @@ mod patch {
         if let Some(force_outgoing_ip) = data.force_outgoing_ip {
             egg.force_outgoing_ip = force_outgoing_ip;
         }
+        if let Some(deployable) = data.deployable {
+            egg.deployable = deployable;
+        }
         if let Some(features) = data.features {
             egg.features = features;
         }
@@ -296,6 +305,8 @@ mod patch {
             egg.file_denylist = file_denylist;
         }
 
+        let mut transaction = state.database.write().begin().await?;
+
         match sqlx::query!(
             "UPDATE nest_eggs
             SET
@@ -320,7 +331,7 @@ mod patch {
             &egg.file_denylist,
             egg.uuid,
         )
-        .execute(state.database.write())
+        .execute(&mut *transaction)
         .await
         {
             Ok(_) => {}
@@ -338,6 +349,14 @@ mod patch {
             }
         }
 
+        if data.deployable.is_some() {
+            NestEgg::update_deployable(&mut *transaction, egg.uuid, egg.deployable).await?;
+        }
+
+        NestEggRevision::record(&mut transaction, egg.uuid, Some(activity_logger.user_uuid))
+            .await?;
+        transaction.commit().await?;
+
         activity_logger
             .log(
                 "nest:egg.update",
@@ -357,6 +376,7 @@ mod patch {
 
                     "startup": egg.startup,
                     "force_outgoing_ip": egg.force_outgoing_ip,
+                    "deployable": egg.deployable,
 
                     "features": egg.features,
                     "docker_images": egg.docker_images,
@@ -376,6 +396,8 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
         .routes(routes!(patch::route))
         .nest("/variables", variables::router(state))
         .nest("/mounts", mounts::router(state))
+        .nest("/export", export::router(state))
+        .nest("/revisions", revisions::router(state))
         .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
         .with_state(state.clone())
 }
@@ -0,0 +1,109 @@
+use crate::routes::api::admin::nests::_nest_::{GetNest, eggs::_egg_::GetNestEgg};
+
+use super::State;
+use axum::{
+    extract::{Path, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use shared::{
+    GetState,
+    models::{nest_egg_revision::NestEggRevision, user::GetPermissionManager},
+    response::ApiResponse,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod rollback;
+
+pub type GetNestEggRevision = shared::extract::ConsumingExtension<NestEggRevision>;
+
+pub async fn auth(
+    state: GetState,
+    permissions: GetPermissionManager,
+    nest: GetNest,
+    egg: GetNestEgg,
+    Path(revision): Path<Vec<String>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let revision = match revision.get(2).map(|s| s.parse::<i32>()) {
+        Some(Ok(revision)) => revision,
+        _ => {
+            return Ok(ApiResponse::error("invalid revision")
+                .with_status(StatusCode::BAD_REQUEST)
+                .into_response());
+        }
+    };
+
+    if let Err(err) = permissions.has_admin_permission("eggs.read") {
+        return Ok(err.into_response());
+    }
+
+    let revision = NestEggRevision::by_egg_uuid_revision(&state.database, egg.uuid, revision).await;
+    let revision = match revision {
+        Ok(Some(revision)) => revision,
+        Ok(None) => {
+            return Ok(ApiResponse::error("revision not found")
+                .with_status(StatusCode::NOT_FOUND)
+                .into_response());
+        }
+        Err(err) => return Ok(ApiResponse::from(err).into_response()),
+    };
+
+    req.extensions_mut().insert(nest.0);
+    req.extensions_mut().insert(egg.0);
+    req.extensions_mut().insert(revision);
+
+    Ok(next.run(req).await)
+}
+
+mod get {
+    use crate::routes::api::admin::nests::_nest_::eggs::_egg_::revisions::_revision_::GetNestEggRevision;
+    use serde::Serialize;
+    use shared::{
+        ApiError,
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        revision: shared::models::nest_egg_revision::AdminApiNestEggRevision,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "egg" = uuid::Uuid,
+            description = "The egg ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "revision" = i32,
+            description = "The revision number",
+            example = "1",
+        ),
+    ))]
+    pub async fn route(revision: GetNestEggRevision) -> ApiResponseResult {
+        ApiResponse::json(Response {
+            revision: revision.0.into_admin_api_object(true),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .nest("/rollback", rollback::router(state))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
+        .with_state(state.clone())
+}
@@ -0,0 +1,124 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use crate::routes::api::admin::nests::_nest_::{
+        GetNest,
+        eggs::_egg_::{GetNestEgg, revisions::_revision_::GetNestEggRevision},
+    };
+    use axum::http::StatusCode;
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, nest_egg::NestEggImport,
+            nest_egg_revision::NestEggRevision, nest_egg_variable::NestEggVariable,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        revision: Option<shared::models::nest_egg_revision::AdminApiNestEggRevision>,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "egg" = uuid::Uuid,
+            description = "The egg ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "revision" = i32,
+            description = "The revision number",
+            example = "1",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        nest: GetNest,
+        egg: GetNestEgg,
+        revision: GetNestEggRevision,
+        activity_logger: GetAdminActivityLogger,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("eggs.update")?;
+
+        let import: NestEggImport = match serde_json::from_value(revision.data.clone()) {
+            Ok(import) => import,
+            Err(err) => {
+                tracing::error!("failed to parse egg revision: {:#?}", err);
+
+                return ApiResponse::error("failed to parse egg revision")
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
+        };
+
+        let mut transaction = state.database.write().begin().await?;
+
+        let variables = NestEggVariable::all_by_egg_uuid(&mut *transaction, egg.uuid).await?;
+        match egg
+            .0
+            .update_from_import(&state.database, &mut transaction, variables, import)
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.to_string().contains("unique constraint") => {
+                return ApiResponse::error("egg with name already exists")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Err(err) => {
+                tracing::error!("failed to roll back egg: {:#?}", err);
+
+                return ApiResponse::error("failed to roll back egg")
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
+        }
+
+        let new_revision = NestEggRevision::record(
+            &mut transaction,
+            revision.egg_uuid,
+            Some(activity_logger.user_uuid),
+        )
+        .await?;
+        transaction.commit().await?;
+
+        activity_logger
+            .log(
+                "nest:egg.rollback",
+                serde_json::json!({
+                    "uuid": revision.egg_uuid,
+                    "nest_uuid": nest.uuid,
+
+                    "revision": revision.revision,
+                    "new_revision": new_revision.as_ref().map(|revision| revision.revision),
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {
+            revision: new_revision.map(|revision| revision.into_admin_api_object(false)),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
@@ -0,0 +1,80 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod get {
+    use crate::routes::api::admin::nests::_nest_::eggs::_egg_::GetNestEgg;
+    use axum::{extract::Query, http::StatusCode};
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{nest_egg_revision::NestEggRevision, user::GetPermissionManager},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(Deserialize)]
+    pub struct Params {
+        from: i32,
+        to: i32,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        changes: Vec<shared::models::nest_egg_revision::NestEggRevisionChange>,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "egg" = uuid::Uuid,
+            description = "The egg ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "from" = i32, Query,
+            description = "The revision to compare from",
+            example = "1",
+        ),
+        (
+            "to" = i32, Query,
+            description = "The revision to compare to",
+            example = "2",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        egg: GetNestEgg,
+        Query(params): Query<Params>,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("eggs.read")?;
+
+        let (from, to) = tokio::try_join!(
+            NestEggRevision::by_egg_uuid_revision(&state.database, egg.uuid, params.from),
+            NestEggRevision::by_egg_uuid_revision(&state.database, egg.uuid, params.to),
+        )?;
+        let (Some(from), Some(to)) = (from, to) else {
+            return ApiResponse::error("revision not found")
+                .with_status(StatusCode::NOT_FOUND)
+                .ok();
+        };
+
+        ApiResponse::json(Response {
+            changes: from.diff(&to),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .with_state(state.clone())
+}
@@ -0,0 +1,95 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod _revision_;
+mod diff;
+
+mod get {
+    use crate::routes::api::admin::nests::_nest_::eggs::_egg_::GetNestEgg;
+    use axum::{extract::Query, http::StatusCode};
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            Pagination, PaginationParams, nest_egg_revision::NestEggRevision,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        #[schema(inline)]
+        revisions: Pagination<shared::models::nest_egg_revision::AdminApiNestEggRevision>,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ), params(
+        (
+            "nest" = uuid::Uuid,
+            description = "The nest ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "egg" = uuid::Uuid,
+            description = "The egg ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "page" = i64, Query,
+            description = "The page number",
+            example = "1",
+        ),
+        (
+            "per_page" = i64, Query,
+            description = "The number of items per page",
+            example = "10",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        egg: GetNestEgg,
+        Query(params): Query<PaginationParams>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&params) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("eggs.read")?;
+
+        let revisions = NestEggRevision::by_egg_uuid_with_pagination(
+            &state.database,
+            egg.uuid,
+            params.page,
+            params.per_page,
+        )
+        .await?;
+
+        ApiResponse::json(Response {
+            revisions: Pagination {
+                total: revisions.total,
+                per_page: revisions.per_page,
+                page: revisions.page,
+                data: revisions
+                    .data
+                    .into_iter()
+                    .map(|revision| revision.into_admin_api_object(false))
+                    .collect(),
+            },
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .nest("/diff", diff::router(state))
+        .nest("/{revision}", _revision_::router(state))
+        .with_state(state.clone())
+}
@@ -8,8 +8,8 @@ mod delete {
     use shared::{
         ApiError, GetState,
         models::{
-            admin_activity::GetAdminActivityLogger, nest_egg_variable::NestEggVariable,
-            user::GetPermissionManager,
+            admin_activity::GetAdminActivityLogger, nest_egg_revision::NestEggRevision,
+            nest_egg_variable::NestEggVariable, user::GetPermissionManager,
         },
         response::{ApiResponse, ApiResponseResult},
     };
@@ -58,7 +58,13 @@ mod delete {
                 }
             };
 
-        NestEggVariable::delete_by_uuid(&state.database, egg_variable.uuid).await?;
+        let mut transaction = state.database.write().begin().await?;
+
+        NestEggVariable::delete_by_uuid(&mut *transaction, egg_variable.uuid).await?;
+
+        NestEggRevision::record(&mut transaction, egg.uuid, Some(activity_logger.user_uuid))
+            .await?;
+        transaction.commit().await?;
 
         activity_logger
             .log(
@@ -85,8 +91,8 @@ mod patch {
     use shared::{
         ApiError, GetState,
         models::{
-            admin_activity::GetAdminActivityLogger, nest_egg_variable::NestEggVariable,
-            user::GetPermissionManager,
+            admin_activity::GetAdminActivityLogger, nest_egg_revision::NestEggRevision,
+            nest_egg_variable::NestEggVariable, user::GetPermissionManager,
         },
         response::{ApiResponse, ApiResponseResult},
     };
@@ -114,6 +120,11 @@ mod patch {
         user_editable: Option<bool>,
         #[validate(custom(function = "rule_validator::validate_rules"))]
         rules: Option<Vec<String>>,
+        kind: Option<shared::models::nest_egg_variable::NestEggVariableKind>,
+        options: Option<Vec<String>>,
+        #[validate(nested)]
+        #[schema(inline)]
+        ui_hints: Option<shared::models::nest_egg_variable::NestEggVariableUiHints>,
     }
 
     #[derive(ToSchema, Serialize)]
@@ -200,6 +211,26 @@ mod patch {
         if let Some(rules) = data.rules {
             egg_variable.rules = rules;
         }
+        if let Some(kind) = data.kind {
+            egg_variable.kind = kind;
+        }
+        if let Some(options) = data.options {
+            egg_variable.options = options;
+        }
+        if let Some(ui_hints) = data.ui_hints {
+            egg_variable.ui_hints = Some(ui_hints);
+        }
+
+        if let Err(error) = egg_variable
+            .kind
+            .validate_definition(&egg_variable.options, egg_variable.default_value.as_deref())
+        {
+            return ApiResponse::error(error)
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        let mut transaction = state.database.write().begin().await?;
 
         match sqlx::query!(
             "UPDATE nest_egg_variables
@@ -217,7 +248,7 @@ mod patch {
             &egg_variable.rules,
             egg_variable.uuid,
         )
-        .execute(state.database.write())
+        .execute(&mut *transaction)
         .await
         {
             Ok(_) => {}
@@ -235,6 +266,20 @@ mod patch {
             }
         }
 
+        NestEggVariable::update_kind(
+            &state.database,
+            &mut transaction,
+            egg_variable.uuid,
+            egg_variable.kind,
+            &egg_variable.options,
+            egg_variable.ui_hints.as_ref(),
+        )
+        .await?;
+
+        NestEggRevision::record(&mut transaction, egg.uuid, Some(activity_logger.user_uuid))
+            .await?;
+        transaction.commit().await?;
+
         activity_logger
             .log(
                 "nest:egg.variable.update",
@@ -253,6 +298,9 @@ mod patch {
                     "user_viewable": egg_variable.user_viewable,
                     "user_editable": egg_variable.user_editable,
                     "rules": egg_variable.rules,
+                    "kind": egg_variable.kind,
+                    "options": egg_variable.options,
+                    "ui_hints": egg_variable.ui_hints,
                 }),
             )
             .await;
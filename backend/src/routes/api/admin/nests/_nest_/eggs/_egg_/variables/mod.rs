@@ -40,7 +40,7 @@ mod get {
     ) -> ApiResponseResult {
         permissions.has_admin_permission("eggs.read")?;
 
-        let variables = NestEggVariable::all_by_egg_uuid(&state.database, egg.uuid).await?;
+        let variables = NestEggVariable::all_by_egg_uuid(state.database.read(), egg.uuid).await?;
 
         ApiResponse::json(Response {
             variables: variables
@@ -59,8 +59,8 @@ mod post {
     use shared::{
         ApiError, GetState,
         models::{
-            admin_activity::GetAdminActivityLogger, nest_egg_variable::NestEggVariable,
-            user::GetPermissionManager,
+            admin_activity::GetAdminActivityLogger, nest_egg_revision::NestEggRevision,
+            nest_egg_variable::NestEggVariable, user::GetPermissionManager,
         },
         response::{ApiResponse, ApiResponseResult},
     };
@@ -88,6 +88,13 @@ mod post {
         user_editable: bool,
         #[validate(custom(function = "rule_validator::validate_rules"))]
         rules: Vec<String>,
+        #[serde(default)]
+        kind: shared::models::nest_egg_variable::NestEggVariableKind,
+        #[serde(default)]
+        options: Vec<String>,
+        #[validate(nested)]
+        #[schema(inline)]
+        ui_hints: Option<shared::models::nest_egg_variable::NestEggVariableUiHints>,
     }
 
     #[derive(ToSchema, Serialize)]
@@ -127,8 +134,19 @@ mod post {
 
         permissions.has_admin_permission("eggs.update")?;
 
+        if let Err(error) = data
+            .kind
+            .validate_definition(&data.options, data.default_value.as_deref())
+        {
+            return ApiResponse::error(error)
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        let mut transaction = state.database.write().begin().await?;
+
         let egg_variable = match NestEggVariable::create(
-            &state.database,
+            &mut *transaction,
             egg.uuid,
             &data.name,
             data.description.as_deref(),
@@ -138,6 +156,9 @@ mod post {
             data.user_viewable,
             data.user_editable,
             &data.rules,
+            data.kind,
+            &data.options,
+            data.ui_hints.as_ref(),
         )
         .await
         {
@@ -156,6 +177,10 @@ mod post {
             }
         };
 
+        NestEggRevision::record(&mut transaction, egg.uuid, Some(activity_logger.user_uuid))
+            .await?;
+        transaction.commit().await?;
+
         activity_logger
             .log(
                 "nest:egg.variable.create",
@@ -174,6 +199,9 @@ mod post {
                     "user_viewable": egg_variable.user_viewable,
                     "user_editable": egg_variable.user_editable,
                     "rules": egg_variable.rules,
+                    "kind": egg_variable.kind,
+                    "options": egg_variable.options,
+                    "ui_hints": egg_variable.ui_hints,
                 }),
             )
             .await;
@@ -4,80 +4,18 @@ use utoipa_axum::{router::OpenApiRouter, routes};
 mod post {
     use crate::routes::api::admin::nests::_nest_::GetNest;
     use axum::http::StatusCode;
-    use indexmap::IndexMap;
-    use serde::{Deserialize, Serialize};
+    use serde::Serialize;
     use shared::{
         ApiError, GetState,
         models::{
             admin_activity::GetAdminActivityLogger,
-            nest_egg::{NestEgg, NestEggConfigScript},
-            nest_egg_variable::NestEggVariable,
+            nest_egg::{NestEgg, NestEggImport},
+            nest_egg_revision::NestEggRevision,
             user::GetPermissionManager,
         },
         response::{ApiResponse, ApiResponseResult},
     };
-    use std::collections::HashMap;
     use utoipa::ToSchema;
-    use validator::Validate;
-
-    #[derive(ToSchema, Validate, Deserialize)]
-    pub struct PayloadVariable {
-        #[validate(length(min = 1, max = 255))]
-        #[schema(min_length = 1, max_length = 255)]
-        name: String,
-        #[validate(length(max = 1024))]
-        #[schema(max_length = 1024)]
-        description: Option<String>,
-
-        #[validate(length(min = 1, max = 255))]
-        #[schema(min_length = 1, max_length = 255)]
-        env_variable: String,
-        #[validate(length(max = 1024))]
-        #[schema(max_length = 1024)]
-        default_value: Option<String>,
-
-        user_viewable: bool,
-        user_editable: bool,
-        rules: Option<String>,
-    }
-
-    #[derive(ToSchema, Validate, Deserialize)]
-    pub struct PayloadScripts {
-        #[schema(inline)]
-        installation: NestEggConfigScript,
-    }
-
-    #[derive(ToSchema, Validate, Deserialize)]
-    pub struct Payload {
-        #[validate(length(min = 2, max = 255))]
-        #[schema(min_length = 2, max_length = 255)]
-        author: String,
-        #[validate(length(min = 3, max = 255))]
-        #[schema(min_length = 3, max_length = 255)]
-        name: String,
-        #[validate(length(max = 1024))]
-        #[schema(max_length = 1024)]
-        description: Option<String>,
-
-        config: HashMap<String, String>,
-        #[schema(inline)]
-        scripts: PayloadScripts,
-
-        #[validate(length(min = 1, max = 255))]
-        #[schema(min_length = 1, max_length = 255)]
-        startup: String,
-        #[serde(default)]
-        force_outgoing_ip: bool,
-
-        #[serde(default)]
-        features: Vec<String>,
-        docker_images: IndexMap<String, String>,
-        #[serde(default)]
-        file_denylist: Vec<String>,
-
-        #[schema(inline)]
-        variables: Vec<PayloadVariable>,
-    }
 
     #[derive(ToSchema, Serialize)]
     struct Response {
@@ -95,13 +33,13 @@ mod post {
             description = "The nest ID",
             example = "123e4567-e89b-12d3-a456-426614174000",
         ),
-    ), request_body = inline(Payload))]
+    ), request_body = inline(NestEggImport))]
     pub async fn route(
         state: GetState,
         permissions: GetPermissionManager,
         nest: GetNest,
         activity_logger: GetAdminActivityLogger,
-        axum::Json(data): axum::Json<Payload>,
+        axum::Json(data): axum::Json<NestEggImport>,
     ) -> ApiResponseResult {
         if let Err(errors) = shared::utils::validate_data(&data) {
             return ApiResponse::json(ApiError::new_strings_value(errors))
@@ -111,45 +49,9 @@ mod post {
 
         permissions.has_admin_permission("eggs.create")?;
 
-        let config_files =
-            serde_json::from_str(data.config.get("files").unwrap_or(&"[]".to_string()))
-                .unwrap_or_default();
-        let config_startup =
-            serde_json::from_str(data.config.get("startup").unwrap_or(&"{}".to_string()))
-                .unwrap_or_default();
-        let config_stop =
-            serde_json::from_str(data.config.get("stop").unwrap_or(&"{}".to_string()))
-                .unwrap_or_default();
+        let mut transaction = state.database.write().begin().await?;
 
-        let egg = match NestEgg::create(
-            &state.database,
-            nest.uuid,
-            &data.author,
-            &data.name,
-            if let Some(description) = &data.description {
-                if description.is_empty() {
-                    None
-                } else {
-                    Some(description)
-                }
-            } else {
-                None
-            },
-            config_files,
-            config_startup,
-            config_stop,
-            data.scripts.installation,
-            shared::models::nest_egg::NestEggConfigAllocations {
-                user_self_assign: Default::default(),
-            },
-            &data.startup,
-            data.force_outgoing_ip,
-            &data.features,
-            data.docker_images,
-            &data.file_denylist,
-        )
-        .await
-        {
+        let egg = match NestEgg::import(&mut transaction, nest.uuid, data).await {
             Ok(egg) => egg,
             Err(err) if err.to_string().contains("unique constraint") => {
                 return ApiResponse::error("egg with name already exists")
@@ -165,32 +67,9 @@ mod post {
             }
         };
 
-        for variable in data.variables {
-            let rules = variable
-                .rules
-                .as_ref()
-                .map(|v| v.split('|').map(String::from).collect::<Vec<String>>())
-                .unwrap_or_default();
-
-            if rule_validator::validate_rules(&rules).is_err() {
-                continue;
-            }
-
-            NestEggVariable::create(
-                &state.database,
-                egg.uuid,
-                &variable.name,
-                variable.description.as_deref(),
-                0,
-                &variable.env_variable,
-                variable.default_value.as_deref(),
-                variable.user_viewable,
-                variable.user_editable,
-                &rules,
-            )
-            .await
-            .ok();
-        }
+        NestEggRevision::record(&mut transaction, egg.uuid, Some(activity_logger.user_uuid))
+            .await?;
+        transaction.commit().await?;
 
         activity_logger
             .log(
@@ -93,7 +93,8 @@ mod post {
     use shared::{
         ApiError, GetState,
         models::{
-            admin_activity::GetAdminActivityLogger, nest_egg::NestEgg, user::GetPermissionManager,
+            admin_activity::GetAdminActivityLogger, nest_egg::NestEgg,
+            nest_egg_revision::NestEggRevision, user::GetPermissionManager,
         },
         response::{ApiResponse, ApiResponseResult},
     };
@@ -170,8 +171,10 @@ mod post {
                 .ok();
         }
 
+        let mut transaction = state.database.write().begin().await?;
+
         let egg = match NestEgg::create(
-            &state.database,
+            &mut *transaction,
             nest.uuid,
             &data.author,
             &data.name,
@@ -204,6 +207,10 @@ mod post {
             }
         };
 
+        NestEggRevision::record(&mut transaction, egg.uuid, Some(activity_logger.user_uuid))
+            .await?;
+        transaction.commit().await?;
+
         activity_logger
             .log(
                 "nest:egg.create",
@@ -12,6 +12,7 @@ use shared::{
 };
 use utoipa_axum::{router::OpenApiRouter, routes};
 
+mod egg_sources;
 mod eggs;
 
 pub type GetNest = shared::extract::ConsumingExtension<Nest>;
@@ -263,6 +264,7 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
         .routes(routes!(delete::route))
         .routes(routes!(patch::route))
         .nest("/eggs", eggs::router(state))
+        .nest("/egg-sources", egg_sources::router(state))
         .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
         .with_state(state.clone())
 }
@@ -13,6 +13,7 @@ use shared::{
 use utoipa_axum::{router::OpenApiRouter, routes};
 
 mod download;
+mod restore;
 
 pub type GetServerBackup = shared::extract::ConsumingExtension<ServerBackup>;
 
@@ -179,6 +180,7 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
         .routes(routes!(get::route))
         .routes(routes!(delete::route))
         .nest("/download", download::router(state))
+        .nest("/restore", restore::router(state))
         .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
         .with_state(state.clone())
 }
@@ -0,0 +1,157 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use crate::routes::api::admin::nodes::_node_::backups::_backup_::GetServerBackup;
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, node::GetNode, server::Server,
+            server_activity::ServerActivity, server_backup::BackupRestoreIntoStatus,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct Payload {
+        server_uuid: uuid::Uuid,
+        truncate_directory: bool,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+        (status = EXPECTATION_FAILED, body = ApiError),
+    ), params(
+        (
+            "node" = uuid::Uuid,
+            description = "The node ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "backup" = uuid::Uuid,
+            description = "The backup ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        node: GetNode,
+        activity_logger: GetAdminActivityLogger,
+        backup: GetServerBackup,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("nodes.backups")?;
+        permissions.has_admin_permission("servers.update")?;
+
+        if backup.completed.is_none() {
+            return ApiResponse::error("backup has not been completed yet")
+                .with_status(StatusCode::EXPECTATION_FAILED)
+                .ok();
+        }
+
+        let server = match Server::by_uuid(&state.database, data.server_uuid).await? {
+            Some(server) => server,
+            None => {
+                return ApiResponse::error("server not found")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+        };
+
+        let backup_uuid = backup.uuid;
+        let backup_name = backup.name.clone();
+
+        let server_uuid = server.uuid;
+        match backup
+            .0
+            .restore_into(&state.database, &state.jwt, server, data.truncate_directory)
+            .await
+        {
+            Ok(BackupRestoreIntoStatus::Started) => {}
+            Ok(BackupRestoreIntoStatus::ServerTransferring) => {
+                return ApiResponse::error("server is being transferred")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Ok(BackupRestoreIntoStatus::NotRestorable) => {
+                return ApiResponse::error(
+                    "backup is stored on another node and cannot be restored into the server",
+                )
+                .with_status(StatusCode::EXPECTATION_FAILED)
+                .ok();
+            }
+            Ok(BackupRestoreIntoStatus::ServerBusy) => {
+                return ApiResponse::error("server is not in a valid state to restore backup.")
+                    .with_status(StatusCode::EXPECTATION_FAILED)
+                    .ok();
+            }
+            Ok(BackupRestoreIntoStatus::BackupBusy) => {
+                return ApiResponse::error("backup is already being restored into another server")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Err(err) => {
+                tracing::error!(server = %server_uuid, backup = %backup_uuid, "failed to restore detached backup: {:#?}", err);
+
+                return ApiResponse::error("failed to restore detached backup")
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
+        }
+
+        activity_logger
+            .log(
+                "node:backup.restore",
+                serde_json::json!({
+                    "uuid": backup_uuid,
+                    "node_uuid": node.uuid,
+                    "server_uuid": server_uuid,
+
+                    "name": backup_name,
+                    "truncate_directory": data.truncate_directory,
+                }),
+            )
+            .await;
+
+        if let Err(err) = ServerActivity::log(
+            &state.database,
+            server_uuid,
+            Some(activity_logger.user_uuid),
+            activity_logger.api_key_uuid,
+            "server:backup.restore",
+            Some(activity_logger.ip.into()),
+            serde_json::json!({
+                "uuid": backup_uuid,
+                "name": backup_name,
+                "truncate_directory": data.truncate_directory,
+            }),
+        )
+        .await
+        {
+            tracing::warn!(
+                backup = %backup_uuid,
+                "failed to log server activity: {:#?}",
+                err
+            );
+        }
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
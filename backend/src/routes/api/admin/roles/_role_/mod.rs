@@ -131,7 +131,7 @@ mod patch {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
-        models::{admin_activity::GetAdminActivityLogger, user::GetPermissionManager},
+        models::{admin_activity::GetAdminActivityLogger, role::Role, user::GetPermissionManager},
         response::{ApiResponse, ApiResponseResult},
     };
     use std::sync::Arc;
@@ -153,6 +153,8 @@ mod patch {
         admin_permissions: Option<Vec<String>>,
         #[validate(custom(function = "shared::permissions::validate_server_permissions"))]
         server_permissions: Option<Vec<String>>,
+        #[validate(nested)]
+        quotas: Option<shared::models::quota::Quotas>,
     }
 
     #[derive(ToSchema, Serialize)]
@@ -201,6 +203,9 @@ mod patch {
         if let Some(server_permissions) = data.server_permissions {
             role.server_permissions = Arc::new(server_permissions);
         }
+        if let Some(quotas) = data.quotas {
+            role.quotas = quotas;
+        }
 
         match sqlx::query!(
             "UPDATE roles
@@ -230,6 +235,10 @@ mod patch {
             }
         }
 
+        if data.quotas.is_some() {
+            Role::update_quotas(&state.database, role.uuid, &role.quotas).await?;
+        }
+
         activity_logger
             .log(
                 "role:update",
@@ -239,6 +248,7 @@ mod patch {
                     "description": role.description,
                     "admin_permissions": role.admin_permissions,
                     "server_permissions": role.server_permissions,
+                    "quotas": role.quotas,
                 }),
             )
             .await;
@@ -0,0 +1,61 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use crate::routes::api::admin::server_templates::_server_template_::GetServerTemplate;
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{admin_activity::GetAdminActivityLogger, user::GetPermissionManager},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        servers: Vec<uuid::Uuid>,
+        /// Servers created from the template that use a different egg by now.
+        skipped: i64,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "server_template" = uuid::Uuid,
+            description = "The server template ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        activity_logger: GetAdminActivityLogger,
+        server_template: GetServerTemplate,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("server-templates.apply")?;
+
+        let servers = server_template.apply(&state.database).await?;
+
+        activity_logger
+            .log(
+                "server-template:apply",
+                serde_json::json!({
+                    "uuid": server_template.uuid,
+                    "servers": servers,
+                }),
+            )
+            .await;
+
+        let skipped = server_template.servers - servers.len() as i64;
+
+        ApiResponse::json(Response { servers, skipped }).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
@@ -0,0 +1,395 @@
+use super::State;
+use axum::{
+    extract::{Path, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use shared::{
+    GetState,
+    models::{server_template::ServerTemplate, user::GetPermissionManager},
+    response::ApiResponse,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod apply;
+
+pub type GetServerTemplate = shared::extract::ConsumingExtension<ServerTemplate>;
+
+pub async fn auth(
+    state: GetState,
+    permissions: GetPermissionManager,
+    Path(server_template): Path<Vec<String>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let server_template = match server_template.first().map(|s| s.parse::<uuid::Uuid>()) {
+        Some(Ok(id)) => id,
+        _ => {
+            return Ok(ApiResponse::error("invalid server template uuid")
+                .with_status(StatusCode::BAD_REQUEST)
+                .into_response());
+        }
+    };
+
+    if let Err(err) = permissions.has_admin_permission("server-templates.read") {
+        return Ok(err.into_response());
+    }
+
+    let server_template = ServerTemplate::by_uuid(&state.database, server_template).await;
+    let server_template = match server_template {
+        Ok(Some(server_template)) => server_template,
+        Ok(None) => {
+            return Ok(ApiResponse::error("server template not found")
+                .with_status(StatusCode::NOT_FOUND)
+                .into_response());
+        }
+        Err(err) => return Ok(ApiResponse::from(err).into_response()),
+    };
+
+    req.extensions_mut().insert(server_template);
+
+    Ok(next.run(req).await)
+}
+
+mod get {
+    use crate::routes::api::admin::server_templates::_server_template_::GetServerTemplate;
+    use serde::Serialize;
+    use shared::{
+        ApiError,
+        models::user::GetPermissionManager,
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        server_template: shared::models::server_template::AdminApiServerTemplate,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "server_template" = uuid::Uuid,
+            description = "The server template ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        permissions: GetPermissionManager,
+        server_template: GetServerTemplate,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("server-templates.read")?;
+
+        ApiResponse::json(Response {
+            server_template: server_template.0.into_admin_api_object(),
+        })
+        .ok()
+    }
+}
+
+mod delete {
+    use crate::routes::api::admin::server_templates::_server_template_::GetServerTemplate;
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, server_template::ServerTemplate,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(delete, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "server_template" = uuid::Uuid,
+            description = "The server template ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        activity_logger: GetAdminActivityLogger,
+        server_template: GetServerTemplate,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("server-templates.delete")?;
+
+        // servers created from the template keep their configuration
+        ServerTemplate::delete_by_uuid(&state.database, server_template.uuid).await?;
+
+        activity_logger
+            .log(
+                "server-template:delete",
+                serde_json::json!({
+                    "uuid": server_template.uuid,
+                    "name": server_template.name,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+mod patch {
+    use crate::routes::api::admin::server_templates::_server_template_::GetServerTemplate;
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, mount::Mount, nest_egg::NestEgg,
+            nest_egg_variable::NestEggVariable, server_template::ServerTemplate,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use std::{collections::BTreeMap, str::FromStr};
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        egg_uuid: Option<uuid::Uuid>,
+
+        #[validate(length(min = 3, max = 255))]
+        #[schema(min_length = 3, max_length = 255)]
+        name: Option<String>,
+        #[validate(length(max = 1024))]
+        #[schema(max_length = 1024)]
+        description: Option<String>,
+
+        limits: Option<shared::models::server::ApiServerLimits>,
+        pinned_cpus: Option<Vec<i16>>,
+
+        #[validate(length(min = 1, max = 1024))]
+        #[schema(min_length = 1, max_length = 1024)]
+        startup: Option<String>,
+        #[validate(length(min = 2, max = 255))]
+        #[schema(min_length = 2, max_length = 255)]
+        image: Option<String>,
+        #[validate(length(max = 255))]
+        #[schema(max_length = 255)]
+        timezone: Option<String>,
+
+        feature_limits: Option<shared::models::server::ApiServerFeatureLimits>,
+
+        variables: Option<BTreeMap<String, String>>,
+        mounts: Option<Vec<uuid::Uuid>>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(patch, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+    ), params(
+        (
+            "server_template" = uuid::Uuid,
+            description = "The server template ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        mut server_template: GetServerTemplate,
+        activity_logger: GetAdminActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("server-templates.update")?;
+
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        if let Some(egg_uuid) = data.egg_uuid {
+            let egg = match NestEgg::by_uuid(&state.database, egg_uuid).await? {
+                Some(egg) => egg,
+                None => {
+                    return ApiResponse::error("egg not found")
+                        .with_status(StatusCode::NOT_FOUND)
+                        .ok();
+                }
+            };
+
+            server_template.egg_uuid = egg.uuid;
+        }
+        if let Some(name) = data.name {
+            server_template.name = name;
+        }
+        if let Some(description) = data.description {
+            if description.is_empty() {
+                server_template.description = None;
+            } else {
+                server_template.description = Some(description);
+            }
+        }
+        if let Some(limits) = &data.limits {
+            server_template.cpu = limits.cpu;
+            server_template.memory = limits.memory;
+            server_template.swap = limits.swap;
+            server_template.disk = limits.disk;
+            server_template.io_weight = limits.io_weight;
+        }
+        if let Some(pinned_cpus) = data.pinned_cpus {
+            server_template.pinned_cpus = pinned_cpus;
+        }
+        if let Some(startup) = data.startup {
+            server_template.startup = startup;
+        }
+        if let Some(image) = data.image {
+            server_template.image = image;
+        }
+        if let Some(timezone) = data.timezone {
+            if timezone.is_empty() {
+                server_template.timezone = None;
+            } else {
+                if chrono_tz::Tz::from_str(&timezone).is_err() {
+                    return ApiResponse::error("invalid timezone")
+                        .with_status(StatusCode::BAD_REQUEST)
+                        .ok();
+                }
+
+                server_template.timezone = Some(timezone);
+            }
+        }
+        if let Some(feature_limits) = &data.feature_limits {
+            server_template.allocation_limit = feature_limits.allocations;
+            server_template.database_limit = feature_limits.databases;
+            server_template.backup_limit = feature_limits.backups;
+            server_template.schedule_limit = feature_limits.schedules;
+        }
+        let variables = match data.variables {
+            Some(variables) => variables,
+            None => server_template.decrypted_variables(&state.database)?,
+        };
+        if let Some(mounts) = data.mounts {
+            for mount_uuid in &mounts {
+                if Mount::by_uuid(&state.database, *mount_uuid)
+                    .await?
+                    .is_none()
+                {
+                    return ApiResponse::error("mount not found")
+                        .with_status(StatusCode::NOT_FOUND)
+                        .ok();
+                }
+            }
+
+            server_template.mounts = mounts;
+        }
+
+        // a new egg may not define the variables of the old one
+        let egg_variables =
+            NestEggVariable::all_by_egg_uuid(state.database.read(), server_template.egg_uuid)
+                .await?;
+        if let Err(error) = super::super::validate_variables(&egg_variables, &variables) {
+            return ApiResponse::error(&error)
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        (
+            server_template.variables,
+            server_template.encrypted_variables,
+        ) = ServerTemplate::encrypt_variables(&state.database, &egg_variables, &variables)?;
+
+        match sqlx::query(
+            r#"
+            UPDATE server_templates
+            SET
+                egg_uuid = $1, name = $2, description = $3,
+                memory = $4, swap = $5, disk = $6, io_weight = $7, cpu = $8,
+                pinned_cpus = $9, startup = $10, image = $11, timezone = $12,
+                allocation_limit = $13, database_limit = $14, backup_limit = $15,
+                schedule_limit = $16, variables = $17, encrypted_variables = $18, mounts = $19
+            WHERE server_templates.uuid = $20
+            "#,
+        )
+        .bind(server_template.egg_uuid)
+        .bind(&server_template.name)
+        .bind(&server_template.description)
+        .bind(server_template.memory)
+        .bind(server_template.swap)
+        .bind(server_template.disk)
+        .bind(server_template.io_weight)
+        .bind(server_template.cpu)
+        .bind(&server_template.pinned_cpus)
+        .bind(&server_template.startup)
+        .bind(&server_template.image)
+        .bind(&server_template.timezone)
+        .bind(server_template.allocation_limit)
+        .bind(server_template.database_limit)
+        .bind(server_template.backup_limit)
+        .bind(server_template.schedule_limit)
+        .bind(serde_json::to_value(&server_template.variables)?)
+        .bind(&server_template.encrypted_variables)
+        .bind(&server_template.mounts)
+        .bind(server_template.uuid)
+        .execute(state.database.write())
+        .await
+        {
+            Ok(_) => {}
+            Err(err) if err.to_string().contains("unique constraint") => {
+                return ApiResponse::error("server template with name already exists")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Err(err) => {
+                tracing::error!("failed to update server template: {:#?}", err);
+
+                return ApiResponse::error("failed to update server template")
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
+        }
+
+        activity_logger
+            .log(
+                "server-template:update",
+                serde_json::json!({
+                    "uuid": server_template.uuid,
+                    "egg_uuid": server_template.egg_uuid,
+                    "name": server_template.name,
+                    "description": server_template.description,
+
+                    "limits": data.limits,
+                    "pinned_cpus": server_template.pinned_cpus,
+                    "startup": server_template.startup,
+                    "image": server_template.image,
+                    "timezone": server_template.timezone,
+                    "feature_limits": data.feature_limits,
+                    "variables": NestEggVariable::mask_values(&egg_variables, &server_template.variables),
+                    "mounts": server_template.mounts,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .routes(routes!(delete::route))
+        .routes(routes!(patch::route))
+        .nest("/apply", apply::router(state))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
+        .with_state(state.clone())
+}
@@ -0,0 +1,276 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        deployment::{DeploymentRequest, NoDeploymentAvailable},
+        models::{
+            admin_activity::GetAdminActivityLogger,
+            node::Node,
+            quota::{QuotaExceeded, QuotaUsage},
+            server::{GetServer, Server, ServerCloneOptions, ServerPlacement},
+            server_backup::ServerBackup,
+            user::{GetPermissionManager, User},
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        node_uuid: Option<uuid::Uuid>,
+        allocation_uuid: Option<uuid::Uuid>,
+        #[serde(default)]
+        allocation_uuids: Vec<uuid::Uuid>,
+        /// Picks the node and allocations automatically instead of `node_uuid` and `allocation_uuid(s)`.
+        #[validate(nested)]
+        deploy: Option<DeploymentRequest>,
+
+        /// Defaults to the owner of the server.
+        owner_uuid: Option<uuid::Uuid>,
+        #[validate(length(min = 3, max = 255))]
+        #[schema(min_length = 3, max_length = 255)]
+        name: Option<String>,
+
+        #[serde(default)]
+        subusers: bool,
+        /// Creates empty databases with new credentials on the same database hosts.
+        #[serde(default)]
+        databases: bool,
+        /// Seeds the files from this backup instead of a fresh one.
+        backup_uuid: Option<uuid::Uuid>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        server: shared::models::server::AdminApiServer,
+        backup_uuid: uuid::Uuid,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+        (status = EXPECTATION_FAILED, body = ApiError),
+    ), params(
+        (
+            "server" = uuid::Uuid,
+            description = "The server ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        server: GetServer,
+        activity_logger: GetAdminActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("servers.clone")?;
+
+        let node = match (data.node_uuid, &data.deploy) {
+            (Some(node_uuid), None) => match Node::by_uuid(&state.database, node_uuid).await? {
+                Some(node) => Some(node),
+                None => {
+                    return ApiResponse::error("node not found")
+                        .with_status(StatusCode::NOT_FOUND)
+                        .ok();
+                }
+            },
+            (None, Some(_))
+                if data.allocation_uuid.is_none() && data.allocation_uuids.is_empty() =>
+            {
+                None
+            }
+            (None, None) => {
+                return ApiResponse::error("either node_uuid or deploy is required")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+            _ => {
+                return ApiResponse::error(
+                    "deploy cannot be combined with node_uuid or allocation_uuid(s)",
+                )
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+            }
+        };
+
+        let owner = match User::by_uuid(
+            &state.database,
+            data.owner_uuid.unwrap_or(server.owner.uuid),
+        )
+        .await?
+        {
+            Some(owner) => owner,
+            None => {
+                return ApiResponse::error("owner not found")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+        };
+        let owner_uuid = owner.uuid;
+
+        let quotas = owner.effective_quotas();
+        if quotas != Default::default() {
+            let usage = QuotaUsage::by_user_uuid(&state.database, owner.uuid).await?;
+
+            if let Err(err) =
+                quotas.check_server(&usage, &server.limits(), &server.feature_limits())
+            {
+                return ApiResponse::error(&err.to_string())
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+        }
+
+        let (disk, backup_node_uuid) = match data.backup_uuid {
+            Some(backup_uuid) => {
+                match ServerBackup::by_server_uuid_uuid(&state.database, server.uuid, backup_uuid)
+                    .await?
+                {
+                    Some(backup) if backup.completed.is_some() && backup.successful => {
+                        (backup.disk, backup.node_uuid)
+                    }
+                    Some(_) => {
+                        return ApiResponse::error("backup has not been completed successfully")
+                            .with_status(StatusCode::EXPECTATION_FAILED)
+                            .ok();
+                    }
+                    None => {
+                        return ApiResponse::error("backup not found")
+                            .with_status(StatusCode::NOT_FOUND)
+                            .ok();
+                    }
+                }
+            }
+            None => match server.backup_configuration() {
+                Some(backup_configuration) => (backup_configuration.backup_disk, server.node.uuid),
+                None => {
+                    return ApiResponse::error("server does not have a backup configuration")
+                        .with_status(StatusCode::EXPECTATION_FAILED)
+                        .ok();
+                }
+            },
+        };
+
+        if !disk.is_portable()
+            && node
+                .as_ref()
+                .is_none_or(|node| node.uuid != backup_node_uuid)
+        {
+            return ApiResponse::error(
+                "backups of this server are stored on its node, the clone has to be placed on the same node",
+            )
+            .with_status(StatusCode::EXPECTATION_FAILED)
+            .ok();
+        }
+
+        let placement = match (&node, &data.deploy) {
+            (Some(node), _) => ServerPlacement::Node {
+                node,
+                allocation_uuid: data.allocation_uuid,
+                allocation_uuids: &data.allocation_uuids,
+            },
+            (None, Some(deploy)) => ServerPlacement::Deploy(deploy),
+            (None, None) => unreachable!(),
+        };
+
+        let name = data.name.unwrap_or_else(|| {
+            format!("{} (clone)", server.name)
+                .chars()
+                .take(255)
+                .collect()
+        });
+
+        let (clone_uuid, backup_uuid) = match server
+            .create_clone(
+                &state.database,
+                placement,
+                &ServerCloneOptions {
+                    owner_uuid,
+                    name: &name,
+                    subusers: data.subusers,
+                    databases: data.databases,
+                    backup_uuid: data.backup_uuid,
+                    quotas: (quotas != Default::default()).then_some(quotas),
+                },
+            )
+            .await
+        {
+            Ok(uuids) => uuids,
+            Err(err) if err.is::<QuotaExceeded>() => {
+                return ApiResponse::error(&err.to_string())
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+            Err(err) if err.is::<NoDeploymentAvailable>() => {
+                return ApiResponse::error(&err.to_string())
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Err(err) if err.to_string().contains("unique constraint") => {
+                return ApiResponse::error("server with allocation(s) already exists")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Err(err) => {
+                tracing::error!(server = %server.uuid, "failed to clone server: {:#?}", err);
+
+                return ApiResponse::error(&format!("failed to clone server: {err}"))
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
+        };
+
+        let clone = Server::by_uuid(&state.database, clone_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("server not found after cloning"))?;
+
+        activity_logger
+            .log(
+                "server:clone",
+                serde_json::json!({
+                    "uuid": server.uuid,
+                    "clone_uuid": clone.uuid,
+                    "node_uuid": clone.node.uuid,
+                    "owner_uuid": owner_uuid,
+                    "backup_uuid": backup_uuid,
+
+                    "allocation_uuid": data.allocation_uuid,
+                    "allocation_uuids": data.allocation_uuids,
+                    "deploy": data.deploy,
+
+                    "name": name,
+                    "subusers": data.subusers,
+                    "databases": data.databases,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {
+            server: clone
+                .into_admin_api_object(&state.database, &state.storage.retrieve_urls().await),
+            backup_uuid,
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
@@ -0,0 +1,89 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod put {
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, nest_egg_revision::NestEggRevision,
+            server::GetServer, user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct Payload {
+        /// The revision of the server's egg to pin the server to, `null` follows the egg.
+        egg_revision_uuid: Option<uuid::Uuid>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(put, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "server" = uuid::Uuid,
+            description = "The server ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        server: GetServer,
+        activity_logger: GetAdminActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("servers.update")?;
+
+        let revision = match data.egg_revision_uuid {
+            Some(egg_revision_uuid) => {
+                match NestEggRevision::by_egg_uuid_uuid(
+                    &state.database,
+                    server.egg.uuid,
+                    egg_revision_uuid,
+                )
+                .await?
+                {
+                    Some(revision) => Some(revision),
+                    None => {
+                        return ApiResponse::error("egg revision not found")
+                            .with_status(StatusCode::NOT_FOUND)
+                            .ok();
+                    }
+                }
+            }
+            None => None,
+        };
+
+        server
+            .update_egg_revision(&state.database, data.egg_revision_uuid)
+            .await?;
+
+        activity_logger
+            .log(
+                "server:egg-revision.update",
+                serde_json::json!({
+                    "uuid": server.uuid,
+                    "egg_uuid": server.egg.uuid,
+                    "egg_revision_uuid": data.egg_revision_uuid,
+                    "revision": revision.map(|revision| revision.revision),
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(put::route))
+        .with_state(state.clone())
+}
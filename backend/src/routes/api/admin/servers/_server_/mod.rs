@@ -13,6 +13,9 @@ use shared::{
 use utoipa_axum::{router::OpenApiRouter, routes};
 
 mod allocations;
+mod backup_retention;
+mod clone;
+mod egg_revision;
 mod mounts;
 mod transfer;
 mod variables;
@@ -181,6 +184,7 @@ mod patch {
             nest_egg::NestEgg,
             server::GetServer,
             user::{GetPermissionManager, User},
+            user_notification::{Notification, UserNotification},
         },
         response::{ApiResponse, ApiResponseResult},
     };
@@ -274,7 +278,12 @@ mod patch {
                 }
             };
 
-            server.egg = Box::new(egg);
+            if egg.uuid != server.egg.uuid && server.egg_revision_uuid.is_some() {
+                server.update_egg_revision(&state.database, None).await?;
+                server.egg_revision_uuid = None;
+            }
+
+            *server.egg = egg;
         }
         if let Some(backup_configuration_uuid) = data.backup_configuration_uuid {
             if backup_configuration_uuid.is_nil() {
@@ -295,6 +304,7 @@ mod patch {
                 server.backup_configuration = Some(Box::new(backup_configuration));
             }
         }
+        let was_suspended = server.suspended;
         if let Some(suspended) = data.suspended {
             server.suspended = suspended;
         }
@@ -420,11 +430,22 @@ mod patch {
             )
             .await;
 
-        tokio::spawn(async move {
-            if let Err(err) = server.0.sync(&state.database).await {
-                tracing::error!("failed to sync server on node: {:#?}", err);
-            }
-        });
+        shared::jobs::enqueue(
+            &state.database,
+            &shared::jobs::server::SyncServer {
+                server_uuid: server.uuid,
+            },
+        )
+        .await?;
+
+        if server.suspended && !was_suspended {
+            UserNotification::notify(
+                &state.database,
+                server.owner.uuid,
+                Notification::server_suspended(&server),
+            )
+            .await;
+        }
 
         ApiResponse::json(Response {}).ok()
     }
@@ -438,6 +459,9 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
         .nest("/variables", variables::router(state))
         .nest("/mounts", mounts::router(state))
         .nest("/transfer", transfer::router(state))
+        .nest("/clone", clone::router(state))
+        .nest("/backup-retention", backup_retention::router(state))
+        .nest("/egg-revision", egg_revision::router(state))
         .nest("/allocations", allocations::router(state))
         .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
         .with_state(state.clone())
@@ -6,7 +6,6 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
-        jwt::BasePayload,
         models::{
             admin_activity::GetAdminActivityLogger, node::Node, server::GetServer,
             user::GetPermissionManager,
@@ -119,51 +118,21 @@ mod post {
             .await?;
         }
 
-        let token = destination_node.create_jwt(
-            &state.database,
-            &state.jwt,
-            &BasePayload {
-                issuer: "panel".into(),
-                subject: Some(server.uuid.to_string()),
-                audience: Vec::new(),
-                expiration_time: Some(chrono::Utc::now().timestamp() + 600),
-                not_before: None,
-                issued_at: Some(chrono::Utc::now().timestamp()),
-                jwt_id: server.node.uuid.to_string(),
+        shared::jobs::enqueue_with(
+            &mut *transaction,
+            &shared::jobs::server::TransferServer {
+                server_uuid: server.uuid,
+                destination_node_uuid: destination_node.uuid,
+                backups: data.backups,
+                delete_backups: data.delete_source_backups,
+                archive_format: data.archive_format,
+                compression_level: data.compression_level,
             },
-        )?;
-
-        let mut url = destination_node.url;
-        url.set_path("/api/transfers");
-
-        match server
-            .node
-            .api_client(&state.database)
-            .post_servers_server_transfer(
-                server.uuid,
-                &wings_api::servers_server_transfer::post::RequestBody {
-                    url: url.to_string(),
-                    token: format!("Bearer {token}"),
-                    backups: data.backups,
-                    delete_backups: data.delete_source_backups,
-                    archive_format: data.archive_format,
-                    compression_level: data.compression_level,
-                },
-            )
-            .await
-        {
-            Ok(_) => {
-                transaction.commit().await?;
-            }
-            Err(err) => {
-                tracing::error!("failed to transfer server to node: {:#?}", err);
-                transaction.rollback().await?;
+            std::time::Duration::ZERO,
+        )
+        .await?;
 
-                return ApiResponse::error("failed to transfer server to node")
-                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .ok();
-            }
-        }
+        transaction.commit().await?;
 
         activity_logger
             .log(
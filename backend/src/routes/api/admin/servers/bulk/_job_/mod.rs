@@ -0,0 +1,59 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod results;
+
+mod get {
+    use axum::{extract::Path, http::StatusCode};
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{server_bulk_job::ServerBulkJob, user::GetPermissionManager},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        job: shared::models::server_bulk_job::AdminApiServerBulkJob,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "job" = uuid::Uuid,
+            description = "The bulk job ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        Path(job): Path<uuid::Uuid>,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("servers.bulk")?;
+
+        let job = match ServerBulkJob::by_uuid(&state.database, job).await? {
+            Some(job) => job,
+            None => {
+                return ApiResponse::error("bulk job not found")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+        };
+
+        ApiResponse::json(Response {
+            job: job.into_admin_api_object(),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .nest("/results", results::router(state))
+        .with_state(state.clone())
+}
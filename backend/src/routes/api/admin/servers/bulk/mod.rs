@@ -0,0 +1,231 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod _job_;
+
+mod get {
+    use axum::{extract::Query, http::StatusCode};
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            Pagination, PaginationParams, server_bulk_job::ServerBulkJob,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        #[schema(inline)]
+        jobs: Pagination<shared::models::server_bulk_job::AdminApiServerBulkJob>,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ), params(
+        (
+            "page" = i64, Query,
+            description = "The page number",
+            example = "1",
+        ),
+        (
+            "per_page" = i64, Query,
+            description = "The number of items per page",
+            example = "10",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        Query(params): Query<PaginationParams>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&params) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("servers.bulk")?;
+
+        let jobs =
+            ServerBulkJob::all_with_pagination(&state.database, params.page, params.per_page)
+                .await?;
+
+        ApiResponse::json(Response {
+            jobs: Pagination {
+                total: jobs.total,
+                per_page: jobs.per_page,
+                page: jobs.page,
+                data: jobs
+                    .data
+                    .into_iter()
+                    .map(|job| job.into_admin_api_object())
+                    .collect(),
+            },
+        })
+        .ok()
+    }
+}
+
+mod post {
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger,
+            mount::Mount,
+            server_bulk_job::{ServerBulkAction, ServerBulkFilter, ServerBulkJob},
+            user::{GetPermissionManager, GetUser},
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    fn default_concurrency() -> i32 {
+        4
+    }
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        #[validate(length(min = 1, max = 10000))]
+        #[schema(min_items = 1, max_items = 10000)]
+        server_uuids: Option<Vec<uuid::Uuid>>,
+        filter: Option<ServerBulkFilter>,
+
+        action: ServerBulkAction,
+        /// The maximum amount of servers of the same node that are worked on at once.
+        #[validate(range(min = 1, max = 32))]
+        #[schema(minimum = 1, maximum = 32)]
+        #[serde(default = "default_concurrency")]
+        concurrency: i32,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        job: shared::models::server_bulk_job::AdminApiServerBulkJob,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+        activity_logger: GetAdminActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("servers.bulk")?;
+
+        match &data.action {
+            ServerBulkAction::Image { image } if !(2..=255).contains(&image.len()) => {
+                return ApiResponse::error("image must be between 2 and 255 characters")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+            ServerBulkAction::Variable {
+                env_variable,
+                value,
+            } if env_variable.is_empty() || env_variable.len() > 255 || value.len() > 1024 => {
+                return ApiResponse::error("invalid variable")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+            ServerBulkAction::Mount { mount_uuid }
+                if Mount::by_uuid(&state.database, *mount_uuid)
+                    .await?
+                    .is_none() =>
+            {
+                return ApiResponse::error("mount not found")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+            _ => {}
+        }
+
+        let server_uuids = match (data.server_uuids, &data.filter) {
+            (Some(mut server_uuids), None) => {
+                server_uuids.sort_unstable();
+                server_uuids.dedup();
+
+                let found: i64 =
+                    sqlx::query_scalar("SELECT COUNT(*) FROM servers WHERE servers.uuid = ANY($1)")
+                        .bind(&server_uuids)
+                        .fetch_one(state.database.read())
+                        .await?;
+
+                if found != server_uuids.len() as i64 {
+                    return ApiResponse::error("server not found")
+                        .with_status(StatusCode::NOT_FOUND)
+                        .ok();
+                }
+
+                server_uuids
+            }
+            (None, Some(filter)) => filter.server_uuids(&state.database).await?,
+            _ => {
+                return ApiResponse::error("exactly one of server_uuids and filter is required")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+        };
+
+        if server_uuids.is_empty() {
+            return ApiResponse::error("no servers matched the filter")
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        let job = ServerBulkJob::create(
+            &state.database,
+            Some(user.uuid),
+            &data.action,
+            data.concurrency,
+            &server_uuids,
+        )
+        .await?;
+
+        activity_logger
+            .log(
+                "server:bulk",
+                serde_json::json!({
+                    "uuid": job.uuid,
+                    "action": job.masked_action(),
+                    "filter": data.filter,
+                    "servers": job.total,
+                }),
+            )
+            .await;
+
+        shared::jobs::enqueue(
+            &state.database,
+            &shared::jobs::server::RunServerBulkJob { job_uuid: job.uuid },
+        )
+        .await?;
+
+        ApiResponse::json(Response {
+            job: job.into_admin_api_object(),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .routes(routes!(post::route))
+        .nest("/{job}", _job_::router(state))
+        .with_state(state.clone())
+}
@@ -0,0 +1,81 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        deployment::{DeploymentRequest, NoDeploymentAvailable},
+        models::user::GetPermissionManager,
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        #[validate(nested)]
+        deploy: DeploymentRequest,
+
+        #[validate(range(min = 0))]
+        #[schema(minimum = 0)]
+        memory: i64,
+        #[validate(range(min = 0))]
+        #[schema(minimum = 0)]
+        disk: i64,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        node: shared::models::node::AdminApiNode,
+        allocations: Vec<shared::models::node_allocation::AdminApiNodeAllocation>,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("servers.create")?;
+
+        let deployment =
+            match shared::deployment::plan(&state.database, &data.deploy, data.memory, data.disk)
+                .await?
+            {
+                Some(deployment) => deployment,
+                None => {
+                    return ApiResponse::error(&NoDeploymentAvailable.to_string())
+                        .with_status(StatusCode::CONFLICT)
+                        .ok();
+                }
+            };
+
+        ApiResponse::json(Response {
+            node: deployment.node.into_admin_api_object(&state.database),
+            allocations: deployment
+                .allocations
+                .into_iter()
+                .map(|allocation| allocation.into_admin_api_object())
+                .collect(),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
@@ -2,6 +2,8 @@ use super::State;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
 mod _server_;
+mod bulk;
+mod deploy_dry_run;
 mod external;
 
 mod get {
@@ -86,28 +88,39 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
+        deployment::{DeploymentRequest, NoDeploymentAvailable},
         models::{
             admin_activity::GetAdminActivityLogger,
             backup_configurations::BackupConfiguration,
             nest_egg::NestEgg,
+            nest_egg_variable::NestEggVariable,
             node::Node,
-            server::Server,
+            quota::{QuotaExceeded, QuotaUsage},
+            server::{Server, ServerPlacement},
+            server_template::ServerTemplate,
             user::{GetPermissionManager, User},
         },
         response::{ApiResponse, ApiResponseResult},
     };
+    use std::collections::BTreeMap;
     use utoipa::ToSchema;
     use validator::Validate;
 
     #[derive(ToSchema, Validate, Deserialize)]
     pub struct Payload {
-        node_uuid: uuid::Uuid,
+        node_uuid: Option<uuid::Uuid>,
         owner_uuid: uuid::Uuid,
-        egg_uuid: uuid::Uuid,
+        /// Fills every omitted egg, limit, startup, image, timezone, variable and mount field.
+        template_uuid: Option<uuid::Uuid>,
+        egg_uuid: Option<uuid::Uuid>,
         backup_configuration_uuid: Option<uuid::Uuid>,
 
         allocation_uuid: Option<uuid::Uuid>,
+        #[serde(default)]
         allocation_uuids: Vec<uuid::Uuid>,
+        /// Picks the node and allocations automatically instead of `node_uuid` and `allocation_uuid(s)`.
+        #[validate(nested)]
+        deploy: Option<DeploymentRequest>,
 
         start_on_completion: bool,
         skip_scripts: bool,
@@ -122,19 +135,25 @@ mod post {
         #[schema(max_length = 1024)]
         description: Option<String>,
 
-        limits: shared::models::server::ApiServerLimits,
-        pinned_cpus: Vec<i16>,
+        limits: Option<shared::models::server::ApiServerLimits>,
+        pinned_cpus: Option<Vec<i16>>,
 
         #[validate(length(min = 1, max = 1024))]
         #[schema(min_length = 1, max_length = 1024)]
-        startup: String,
+        startup: Option<String>,
         #[validate(length(min = 2, max = 255))]
         #[schema(min_length = 2, max_length = 255)]
-        image: String,
+        image: Option<String>,
         #[schema(min_length = 3, max_length = 255, value_type = String)]
         timezone: Option<chrono_tz::Tz>,
 
-        feature_limits: shared::models::server::ApiServerFeatureLimits,
+        feature_limits: Option<shared::models::server::ApiServerFeatureLimits>,
+
+        /// Merged over the variables of the template.
+        #[serde(default)]
+        variables: BTreeMap<String, String>,
+        /// Replaces the mounts of the template.
+        mount_uuids: Option<Vec<uuid::Uuid>>,
     }
 
     #[derive(ToSchema, Serialize)]
@@ -162,14 +181,88 @@ mod post {
 
         permissions.has_admin_permission("servers.create")?;
 
-        let node = match Node::by_uuid(&state.database, data.node_uuid).await? {
-            Some(node) => node,
-            None => {
-                return ApiResponse::error("node not found")
-                    .with_status(StatusCode::NOT_FOUND)
+        let node = match (data.node_uuid, &data.deploy) {
+            (Some(node_uuid), None) => match Node::by_uuid(&state.database, node_uuid).await? {
+                Some(node) => Some(node),
+                None => {
+                    return ApiResponse::error("node not found")
+                        .with_status(StatusCode::NOT_FOUND)
+                        .ok();
+                }
+            },
+            (None, Some(_))
+                if data.allocation_uuid.is_none() && data.allocation_uuids.is_empty() =>
+            {
+                None
+            }
+            (None, None) => {
+                return ApiResponse::error("either node_uuid or deploy is required")
+                    .with_status(StatusCode::BAD_REQUEST)
                     .ok();
             }
+            _ => {
+                return ApiResponse::error(
+                    "deploy cannot be combined with node_uuid or allocation_uuid(s)",
+                )
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+            }
+        };
+
+        let template = match data.template_uuid {
+            Some(template_uuid) => {
+                match ServerTemplate::by_uuid(&state.database, template_uuid).await? {
+                    Some(template) => Some(template),
+                    None => {
+                        return ApiResponse::error("server template not found")
+                            .with_status(StatusCode::NOT_FOUND)
+                            .ok();
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let (Some(egg_uuid), Some(limits), Some(startup), Some(image), Some(feature_limits)) = (
+            data.egg_uuid
+                .or(template.as_ref().map(|template| template.egg_uuid)),
+            data.limits
+                .or_else(|| template.as_ref().map(|template| template.limits())),
+            data.startup
+                .or_else(|| template.as_ref().map(|template| template.startup.clone())),
+            data.image
+                .or_else(|| template.as_ref().map(|template| template.image.clone())),
+            data.feature_limits
+                .or_else(|| template.as_ref().map(|template| template.feature_limits())),
+        ) else {
+            return ApiResponse::error(
+                "egg_uuid, limits, startup, image and feature_limits are required without template_uuid",
+            )
+            .with_status(StatusCode::BAD_REQUEST)
+            .ok();
+        };
+        let pinned_cpus = data
+            .pinned_cpus
+            .or_else(|| {
+                template
+                    .as_ref()
+                    .map(|template| template.pinned_cpus.clone())
+            })
+            .unwrap_or_default();
+        let timezone = data.timezone.map(|tz| tz.name().to_string()).or_else(|| {
+            template
+                .as_ref()
+                .and_then(|template| template.timezone.clone())
+        });
+        let mut variables = match &template {
+            Some(template) => template.decrypted_variables(&state.database)?,
+            None => Default::default(),
         };
+        variables.extend(data.variables);
+        let mount_uuids = data
+            .mount_uuids
+            .or_else(|| template.as_ref().map(|template| template.mounts.clone()))
+            .unwrap_or_default();
 
         let owner = match User::by_uuid(&state.database, data.owner_uuid).await? {
             Some(user) => user,
@@ -180,7 +273,18 @@ mod post {
             }
         };
 
-        let egg = match NestEgg::by_uuid(&state.database, data.egg_uuid).await? {
+        let quotas = owner.effective_quotas();
+        if quotas != Default::default() {
+            let usage = QuotaUsage::by_user_uuid(&state.database, owner.uuid).await?;
+
+            if let Err(err) = quotas.check_server(&usage, &limits, &feature_limits) {
+                return ApiResponse::error(&err.to_string())
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+        }
+
+        let egg = match NestEgg::by_uuid(&state.database, egg_uuid).await? {
             Some(egg) => egg,
             None => {
                 return ApiResponse::error("egg not found")
@@ -189,6 +293,17 @@ mod post {
             }
         };
 
+        let egg_variables =
+            NestEggVariable::all_by_egg_uuid(state.database.read(), egg.uuid).await?;
+        if !variables.is_empty()
+            && let Err(error) =
+                super::super::server_templates::validate_variables(&egg_variables, &variables)
+        {
+            return ApiResponse::error(&error)
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
         let backup_configuration = if let Some(backup_configuration_uuid) =
             data.backup_configuration_uuid
         {
@@ -204,31 +319,57 @@ mod post {
             None
         };
 
+        let placement = match (&node, &data.deploy) {
+            (Some(node), _) => ServerPlacement::Node {
+                node,
+                allocation_uuid: data.allocation_uuid,
+                allocation_uuids: &data.allocation_uuids,
+            },
+            (None, Some(deploy)) => ServerPlacement::Deploy(deploy),
+            (None, None) => {
+                return ApiResponse::error("either node_uuid or deploy is required")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+        };
+
         let server = match Server::create(
             &state.database,
-            &node,
+            placement,
             owner.uuid,
+            (quotas != Default::default()).then_some(&quotas),
             egg.uuid,
             backup_configuration.map(|backup_configuration| backup_configuration.uuid),
-            data.allocation_uuid,
-            &data.allocation_uuids,
+            template.as_ref().map(|template| template.uuid),
             data.external_id.as_deref(),
             data.start_on_completion,
             data.skip_scripts,
             &data.name,
             data.description.as_deref(),
-            &data.limits,
-            &data.pinned_cpus,
-            &data.startup,
-            &data.image,
-            data.timezone.as_ref().map(|tz| tz.name()),
-            &data.feature_limits,
+            &limits,
+            &pinned_cpus,
+            &startup,
+            &image,
+            timezone.as_deref(),
+            &feature_limits,
+            &variables,
+            &mount_uuids,
         )
         .await
         {
             Ok(server_uuid) => Server::by_uuid(&state.database, server_uuid)
                 .await?
                 .ok_or_else(|| anyhow::anyhow!("server not found after creation"))?,
+            Err(err) if err.is::<QuotaExceeded>() => {
+                return ApiResponse::error(&err.to_string())
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+            Err(err) if err.is::<NoDeploymentAvailable>() => {
+                return ApiResponse::error(&err.to_string())
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
             Err(err) if err.to_string().contains("unique constraint") => {
                 return ApiResponse::error("server with allocation(s) already exists")
                     .with_status(StatusCode::CONFLICT)
@@ -248,12 +389,14 @@ mod post {
                 "server:create",
                 serde_json::json!({
                     "uuid": server.uuid,
-                    "node_uuid": node.uuid,
+                    "node_uuid": server.node.uuid,
                     "owner_uuid": owner.uuid,
+                    "template_uuid": data.template_uuid,
                     "egg_uuid": egg.uuid,
 
                     "allocation_uuid": data.allocation_uuid,
                     "allocation_uuids": data.allocation_uuids,
+                    "deploy": data.deploy,
                     "external_id": data.external_id,
 
                     "start_on_completion": data.start_on_completion,
@@ -261,12 +404,14 @@ mod post {
 
                     "name": data.name,
                     "description": data.description,
-                    "limits": data.limits,
-                    "pinned_cpus": data.pinned_cpus,
-                    "startup": data.startup,
-                    "image": data.image,
-                    "timezone": data.timezone,
-                    "feature_limits": data.feature_limits,
+                    "limits": limits,
+                    "pinned_cpus": pinned_cpus,
+                    "startup": startup,
+                    "image": image,
+                    "timezone": timezone,
+                    "feature_limits": feature_limits,
+                    "variables": NestEggVariable::mask_values(&egg_variables, &variables),
+                    "mount_uuids": mount_uuids,
                 }),
             )
             .await;
@@ -283,6 +428,8 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
     OpenApiRouter::new()
         .routes(routes!(get::route))
         .routes(routes!(post::route))
+        .nest("/bulk", bulk::router(state))
+        .nest("/deploy/dry-run", deploy_dry_run::router(state))
         .nest("/{server}", _server_::router(state))
         .nest("/external", external::router(state))
         .with_state(state.clone())
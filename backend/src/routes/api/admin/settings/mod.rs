@@ -0,0 +1,338 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod test_mail;
+
+mod get {
+    use serde::Serialize;
+    use shared::{
+        GetState,
+        models::user::GetPermissionManager,
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response<'a> {
+        #[schema(inline)]
+        settings: &'a shared::settings::AppSettings,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ))]
+    pub async fn route(state: GetState, permissions: GetPermissionManager) -> ApiResponseResult {
+        permissions.has_admin_permission("settings.read")?;
+
+        let settings = state.settings.get().await;
+
+        ApiResponse::json(Response {
+            settings: &settings,
+        })
+        .ok()
+    }
+}
+
+mod put {
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        GetState,
+        models::{admin_activity::GetAdminActivityLogger, user::GetPermissionManager},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct PayloadApp {
+        name: Option<String>,
+        url: Option<String>,
+        telemetry_enabled: Option<bool>,
+        registration_enabled: Option<bool>,
+    }
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct PayloadWebauthn {
+        rp_id: Option<String>,
+        rp_origin: Option<String>,
+    }
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct PayloadServer {
+        max_file_manager_view_size: Option<u64>,
+        max_schedules_step_count: Option<u64>,
+
+        allow_overwriting_custom_docker_image: Option<bool>,
+        allow_editing_startup_command: Option<bool>,
+
+        verify_backups_automatically: Option<bool>,
+    }
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct PayloadSecurity {
+        new_device_notifications: Option<bool>,
+        new_device_verification: Option<shared::settings::NewDeviceVerification>,
+        /// Only set this when a reverse proxy in front of every request overwrites the header,
+        /// clients could otherwise fake a known network. Empty to unset.
+        asn_header: Option<String>,
+
+        login_delay_after: Option<u32>,
+        login_max_delay: Option<u64>,
+        login_lockout_after: Option<u32>,
+        login_lockout_duration: Option<u64>,
+    }
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct PayloadOidc {
+        enabled: Option<bool>,
+        name: Option<String>,
+
+        issuer: Option<String>,
+        client_id: Option<String>,
+        client_secret: Option<String>,
+        scopes: Option<String>,
+
+        username_claim: Option<String>,
+        email_claim: Option<String>,
+        name_first_claim: Option<String>,
+        name_last_claim: Option<String>,
+        groups_claim: Option<String>,
+        role_mappings: Option<Vec<shared::settings::RoleMapping>>,
+
+        auto_provision: Option<bool>,
+    }
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct PayloadLdap {
+        enabled: Option<bool>,
+
+        url: Option<String>,
+        starttls: Option<bool>,
+        bind_dn: Option<String>,
+        search_base: Option<String>,
+        search_filter: Option<String>,
+
+        email_attribute: Option<String>,
+        name_first_attribute: Option<String>,
+        name_last_attribute: Option<String>,
+        groups_attribute: Option<String>,
+        role_mappings: Option<Vec<shared::settings::RoleMapping>>,
+    }
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct Payload {
+        storage_driver: Option<shared::settings::StorageDriver>,
+        mail_mode: Option<shared::settings::MailMode>,
+        captcha_provider: Option<shared::settings::CaptchaProvider>,
+
+        #[schema(inline)]
+        app: Option<PayloadApp>,
+        #[schema(inline)]
+        webauthn: Option<PayloadWebauthn>,
+        #[schema(inline)]
+        server: Option<PayloadServer>,
+        #[schema(inline)]
+        security: Option<PayloadSecurity>,
+        #[schema(inline)]
+        oidc: Option<PayloadOidc>,
+        #[schema(inline)]
+        ldap: Option<PayloadLdap>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(put, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        activity_logger: GetAdminActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("settings.update")?;
+
+        let mut settings = state.settings.get_mut().await;
+
+        if let Some(storage_driver) = data.storage_driver {
+            settings.storage_driver = storage_driver;
+        }
+        if let Some(mail_mode) = data.mail_mode {
+            settings.mail_mode = mail_mode;
+        }
+        if let Some(captcha_provider) = data.captcha_provider {
+            settings.captcha_provider = captcha_provider;
+        }
+        if let Some(app) = data.app {
+            if let Some(name) = app.name {
+                settings.app.name = name;
+            }
+            if let Some(url) = app.url {
+                settings.app.url = url;
+            }
+            if let Some(telemetry_enabled) = app.telemetry_enabled {
+                settings.app.telemetry_enabled = telemetry_enabled;
+            }
+            if let Some(registration_enabled) = app.registration_enabled {
+                settings.app.registration_enabled = registration_enabled;
+            }
+        }
+        if let Some(webauthn) = data.webauthn {
+            if let Some(rp_id) = webauthn.rp_id {
+                settings.webauthn.rp_id = rp_id;
+            }
+            if let Some(rp_origin) = webauthn.rp_origin {
+                settings.webauthn.rp_origin = rp_origin;
+            }
+        }
+        if let Some(server) = data.server {
+            if let Some(max_file_manager_view_size) = server.max_file_manager_view_size {
+                settings.server.max_file_manager_view_size = max_file_manager_view_size;
+            }
+            if let Some(max_schedules_step_count) = server.max_schedules_step_count {
+                settings.server.max_schedules_step_count = max_schedules_step_count;
+            }
+            if let Some(allow_overwriting_custom_docker_image) =
+                server.allow_overwriting_custom_docker_image
+            {
+                settings.server.allow_overwriting_custom_docker_image =
+                    allow_overwriting_custom_docker_image;
+            }
+            if let Some(allow_editing_startup_command) = server.allow_editing_startup_command {
+                settings.server.allow_editing_startup_command = allow_editing_startup_command;
+            }
+            if let Some(verify_backups_automatically) = server.verify_backups_automatically {
+                settings.server.verify_backups_automatically = verify_backups_automatically;
+            }
+        }
+
+        if let Some(security) = data.security {
+            if let Some(new_device_notifications) = security.new_device_notifications {
+                settings.security.new_device_notifications = new_device_notifications;
+            }
+            if let Some(new_device_verification) = security.new_device_verification {
+                settings.security.new_device_verification = new_device_verification;
+            }
+            if let Some(asn_header) = security.asn_header {
+                if asn_header.is_empty() {
+                    settings.security.asn_header = None;
+                } else {
+                    settings.security.asn_header = Some(asn_header);
+                }
+            }
+            if let Some(login_delay_after) = security.login_delay_after {
+                settings.security.login_delay_after = login_delay_after;
+            }
+            if let Some(login_max_delay) = security.login_max_delay {
+                settings.security.login_max_delay = login_max_delay;
+            }
+            if let Some(login_lockout_after) = security.login_lockout_after {
+                settings.security.login_lockout_after = login_lockout_after;
+            }
+            if let Some(login_lockout_duration) = security.login_lockout_duration {
+                settings.security.login_lockout_duration = login_lockout_duration;
+            }
+        }
+
+        if let Some(oidc) = data.oidc {
+            if let Some(enabled) = oidc.enabled {
+                settings.oidc.enabled = enabled;
+            }
+            if let Some(name) = oidc.name {
+                settings.oidc.name = name;
+            }
+            if let Some(issuer) = oidc.issuer {
+                settings.oidc.issuer = issuer;
+            }
+            if let Some(client_id) = oidc.client_id {
+                settings.oidc.client_id = client_id;
+            }
+            if let Some(client_secret) = oidc.client_secret {
+                settings.oidc.client_secret = client_secret;
+            }
+            if let Some(scopes) = oidc.scopes {
+                settings.oidc.scopes = scopes;
+            }
+            if let Some(username_claim) = oidc.username_claim {
+                settings.oidc.username_claim = username_claim;
+            }
+            if let Some(email_claim) = oidc.email_claim {
+                settings.oidc.email_claim = email_claim;
+            }
+            if let Some(name_first_claim) = oidc.name_first_claim {
+                settings.oidc.name_first_claim = name_first_claim;
+            }
+            if let Some(name_last_claim) = oidc.name_last_claim {
+                settings.oidc.name_last_claim = name_last_claim;
+            }
+            if let Some(groups_claim) = oidc.groups_claim {
+                if groups_claim.is_empty() {
+                    settings.oidc.groups_claim = None;
+                } else {
+                    settings.oidc.groups_claim = Some(groups_claim);
+                }
+            }
+            if let Some(role_mappings) = oidc.role_mappings {
+                settings.oidc.role_mappings = role_mappings;
+            }
+            if let Some(auto_provision) = oidc.auto_provision {
+                settings.oidc.auto_provision = auto_provision;
+            }
+        }
+
+        if let Some(ldap) = data.ldap {
+            if let Some(enabled) = ldap.enabled {
+                settings.ldap.enabled = enabled;
+            }
+            if let Some(url) = ldap.url {
+                settings.ldap.url = url;
+            }
+            if let Some(starttls) = ldap.starttls {
+                settings.ldap.starttls = starttls;
+            }
+            if let Some(bind_dn) = ldap.bind_dn {
+                settings.ldap.bind_dn = bind_dn;
+            }
+            if let Some(search_base) = ldap.search_base {
+                settings.ldap.search_base = search_base;
+            }
+            if let Some(search_filter) = ldap.search_filter {
+                settings.ldap.search_filter = search_filter;
+            }
+            if let Some(email_attribute) = ldap.email_attribute {
+                settings.ldap.email_attribute = email_attribute;
+            }
+            if let Some(name_first_attribute) = ldap.name_first_attribute {
+                settings.ldap.name_first_attribute = name_first_attribute;
+            }
+            if let Some(name_last_attribute) = ldap.name_last_attribute {
+                settings.ldap.name_last_attribute = name_last_attribute;
+            }
+            if let Some(groups_attribute) = ldap.groups_attribute {
+                if groups_attribute.is_empty() {
+                    settings.ldap.groups_attribute = None;
+                } else {
+                    settings.ldap.groups_attribute = Some(groups_attribute);
+                }
+            }
+            if let Some(role_mappings) = ldap.role_mappings {
+                settings.ldap.role_mappings = role_mappings;
+            }
+        }
+
+        let settings_json = settings.censored();
+        settings.save().await?;
+
+        activity_logger.log("settings:update", settings_json).await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .routes(routes!(put::route))
+        .nest("/test-mail", test_mail::router(state))
+        .with_state(state.clone())
+}
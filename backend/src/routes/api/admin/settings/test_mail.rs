@@ -0,0 +1,88 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger,
+            user::{GetPermissionManager, GetUser},
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        /// Defaults to the email of the current user.
+        #[validate(email)]
+        #[schema(format = "email")]
+        destination: Option<String>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = EXPECTATION_FAILED, body = ApiError),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+        activity_logger: GetAdminActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("settings.update")?;
+
+        let app_name = state.settings.get().await.app.name.clone();
+        let destination = data.destination.unwrap_or_else(|| user.email.clone());
+
+        if let Err(err) = state
+            .mail
+            .deliver(
+                &destination,
+                &format!("{app_name} - Test Email"),
+                format!("<p>This is a test email from {app_name}, your mail settings work.</p>"),
+                Some(format!(
+                    "This is a test email from {app_name}, your mail settings work."
+                )),
+            )
+            .await
+        {
+            tracing::error!("failed to send test email: {:#?}", err);
+
+            return ApiResponse::error("failed to send test email, check the panel logs")
+                .with_status(StatusCode::EXPECTATION_FAILED)
+                .ok();
+        }
+
+        activity_logger
+            .log(
+                "settings:test-mail",
+                serde_json::json!({
+                    "destination": destination,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
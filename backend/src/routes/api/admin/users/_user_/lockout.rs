@@ -0,0 +1,57 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod delete {
+    use crate::routes::api::admin::users::_user_::GetParamUser;
+    use axum::http::StatusCode;
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, user::GetPermissionManager,
+            user_login_failure::UserLoginFailure,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(delete, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetParamUser,
+        activity_logger: GetAdminActivityLogger,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("users.clear-lockout")?;
+
+        if !UserLoginFailure::delete_by_user_uuid(&state.database, user.uuid).await? {
+            return ApiResponse::error("user has no failed login attempts")
+                .with_status(StatusCode::NOT_FOUND)
+                .ok();
+        }
+
+        activity_logger
+            .log(
+                "user:lockout.clear",
+                serde_json::json!({
+                    "uuid": user.uuid,
+                    "username": user.username,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(delete::route))
+        .with_state(state.clone())
+}
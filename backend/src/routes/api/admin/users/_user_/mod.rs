@@ -13,6 +13,7 @@ use shared::{
 use std::ops::{Deref, DerefMut};
 use utoipa_axum::{router::OpenApiRouter, routes};
 
+mod lockout;
 mod servers;
 mod two_factor;
 
@@ -163,6 +164,7 @@ mod delete {
                     "name_first": user.name_first,
                     "name_last": user.name_last,
                     "admin": user.admin,
+                    "quotas": user.quotas,
                 }),
             )
             .await;
@@ -177,7 +179,10 @@ mod patch {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
-        models::{admin_activity::GetAdminActivityLogger, user::GetPermissionManager},
+        models::{
+            admin_activity::GetAdminActivityLogger,
+            user::{GetPermissionManager, User},
+        },
         response::{ApiResponse, ApiResponseResult},
     };
     use utoipa::ToSchema;
@@ -206,6 +211,8 @@ mod patch {
         password: Option<String>,
 
         admin: Option<bool>,
+        #[validate(nested)]
+        quotas: Option<shared::models::quota::Quotas>,
     }
 
     #[derive(ToSchema, Serialize)]
@@ -265,6 +272,9 @@ mod patch {
         if let Some(admin) = data.admin {
             user.admin = admin;
         }
+        if let Some(quotas) = data.quotas {
+            user.quotas = quotas;
+        }
 
         match sqlx::query!(
             "UPDATE users
@@ -295,6 +305,10 @@ mod patch {
             }
         }
 
+        if data.quotas.is_some() {
+            User::update_quotas(&state.database, user.uuid, &user.quotas).await?;
+        }
+
         activity_logger
             .log(
                 "user:update",
@@ -305,6 +319,7 @@ mod patch {
                     "name_first": user.name_first,
                     "name_last": user.name_last,
                     "admin": user.admin,
+                    "quotas": user.quotas,
                 }),
             )
             .await;
@@ -319,6 +334,7 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
         .routes(routes!(delete::route))
         .routes(routes!(patch::route))
         .nest("/two-factor", two_factor::router(state))
+        .nest("/lockout", lockout::router(state))
         .nest("/servers", servers::router(state))
         .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
         .with_state(state.clone())
@@ -0,0 +1,96 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod get {
+    use axum::{extract::Query, http::StatusCode};
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{
+            Pagination, PaginationParamsWithSearch, user::GetPermissionManager,
+            user_login_failure::UserLoginFailure,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(Deserialize)]
+    pub struct Params {
+        #[serde(default)]
+        locked: bool,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        #[schema(inline)]
+        lockouts: Pagination<shared::models::user_login_failure::AdminApiUserLoginFailure>,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ), params(
+        (
+            "page" = i64, Query,
+            description = "The page number",
+            example = "1",
+        ),
+        (
+            "per_page" = i64, Query,
+            description = "The number of items per page",
+            example = "10",
+        ),
+        (
+            "search" = Option<String>, Query,
+            description = "Search term for the username or email",
+        ),
+        (
+            "locked" = Option<bool>, Query,
+            description = "Only list accounts that are currently locked",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        Query(params): Query<PaginationParamsWithSearch>,
+        Query(filter): Query<Params>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&params) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("users.read")?;
+
+        let lockouts = UserLoginFailure::all_with_pagination(
+            &state.database,
+            params.page,
+            params.per_page,
+            params.search.as_deref(),
+            filter.locked,
+        )
+        .await?;
+
+        let storage_url_retriever = state.storage.retrieve_urls().await;
+
+        ApiResponse::json(Response {
+            lockouts: Pagination {
+                total: lockouts.total,
+                per_page: lockouts.per_page,
+                page: lockouts.page,
+                data: lockouts
+                    .data
+                    .into_iter()
+                    .map(|lockout| lockout.into_admin_api_object(&storage_url_retriever))
+                    .collect(),
+            },
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .with_state(state.clone())
+}
@@ -2,6 +2,7 @@ use super::State;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
 mod _user_;
+mod lockouts;
 
 mod get {
     use axum::{extract::Query, http::StatusCode};
@@ -193,6 +194,7 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
     OpenApiRouter::new()
         .routes(routes!(get::route))
         .routes(routes!(post::route))
+        .nest("/lockouts", lockouts::router(state))
         .nest("/{user}", _user_::router(state))
         .with_state(state.clone())
 }
@@ -0,0 +1,76 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use crate::routes::api::admin::webhooks::_webhook_::deliveries::_delivery_::GetWebhookDelivery;
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, user::GetPermissionManager,
+            webhook::GetWebhook, webhook_delivery::WebhookDelivery,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        delivery: shared::models::webhook_delivery::AdminApiWebhookDelivery,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "webhook" = uuid::Uuid,
+            description = "The webhook ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "delivery" = uuid::Uuid,
+            description = "The delivery ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        webhook: GetWebhook,
+        delivery: GetWebhookDelivery,
+        activity_logger: GetAdminActivityLogger,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("webhooks.redeliver")?;
+
+        let redelivery = WebhookDelivery::create(
+            &state.database,
+            webhook.uuid,
+            &delivery.event,
+            delivery.payload.clone(),
+        )
+        .await?;
+
+        activity_logger
+            .log(
+                "webhook:delivery.redeliver",
+                serde_json::json!({
+                    "uuid": webhook.uuid,
+                    "delivery_uuid": delivery.uuid,
+                    "redelivery_uuid": redelivery.uuid,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {
+            delivery: redelivery.into_admin_api_object(),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
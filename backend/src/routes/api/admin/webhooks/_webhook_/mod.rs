@@ -0,0 +1,293 @@
+use super::State;
+use axum::{
+    extract::{Path, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use shared::{
+    GetState,
+    models::{user::GetPermissionManager, webhook::Webhook},
+    response::ApiResponse,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod deliveries;
+
+pub async fn auth(
+    state: GetState,
+    permissions: GetPermissionManager,
+    Path(webhook): Path<Vec<String>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let webhook = match webhook.first().map(|s| s.parse::<uuid::Uuid>()) {
+        Some(Ok(id)) => id,
+        _ => {
+            return Ok(ApiResponse::error("invalid webhook uuid")
+                .with_status(StatusCode::BAD_REQUEST)
+                .into_response());
+        }
+    };
+
+    if let Err(err) = permissions.has_admin_permission("webhooks.read") {
+        return Ok(err.into_response());
+    }
+
+    let webhook = Webhook::by_uuid(&state.database, webhook).await;
+    let webhook = match webhook {
+        Ok(Some(webhook)) => webhook,
+        Ok(None) => {
+            return Ok(ApiResponse::error("webhook not found")
+                .with_status(StatusCode::NOT_FOUND)
+                .into_response());
+        }
+        Err(err) => return Ok(ApiResponse::from(err).into_response()),
+    };
+
+    req.extensions_mut().insert(webhook);
+
+    Ok(next.run(req).await)
+}
+
+mod get {
+    use serde::Serialize;
+    use shared::{
+        ApiError,
+        models::{user::GetPermissionManager, webhook::GetWebhook},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        webhook: shared::models::webhook::AdminApiWebhook,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "webhook" = uuid::Uuid,
+            description = "The webhook ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        permissions: GetPermissionManager,
+        webhook: GetWebhook,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("webhooks.read")?;
+
+        ApiResponse::json(Response {
+            webhook: webhook.0.into_admin_api_object(),
+        })
+        .ok()
+    }
+}
+
+mod delete {
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger,
+            user::GetPermissionManager,
+            webhook::{GetWebhook, Webhook},
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(delete, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "webhook" = uuid::Uuid,
+            description = "The webhook ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        webhook: GetWebhook,
+        activity_logger: GetAdminActivityLogger,
+    ) -> ApiResponseResult {
+        permissions.has_admin_permission("webhooks.delete")?;
+
+        Webhook::delete_by_uuid(&state.database, webhook.uuid).await?;
+
+        activity_logger
+            .log(
+                "webhook:delete",
+                serde_json::json!({
+                    "uuid": webhook.uuid,
+                    "name": webhook.name,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+mod patch {
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{
+            admin_activity::GetAdminActivityLogger, user::GetPermissionManager, webhook::GetWebhook,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        #[validate(length(min = 3, max = 255))]
+        #[schema(min_length = 3, max_length = 255)]
+        name: Option<String>,
+        #[validate(length(max = 1024))]
+        #[schema(max_length = 1024)]
+        description: Option<String>,
+
+        #[validate(length(min = 3, max = 255), url)]
+        #[schema(min_length = 3, max_length = 255, format = "uri")]
+        url: Option<String>,
+        #[validate(length(min = 16, max = 255))]
+        #[schema(min_length = 16, max_length = 255)]
+        secret: Option<String>,
+        #[validate(
+            length(min = 1),
+            custom(function = "shared::models::webhook::validate_event_filters")
+        )]
+        #[schema(min_items = 1)]
+        events: Option<Vec<String>>,
+
+        enabled: Option<bool>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(patch, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+    ), params(
+        (
+            "webhook" = uuid::Uuid,
+            description = "The webhook ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        mut webhook: GetWebhook,
+        activity_logger: GetAdminActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_admin_permission("webhooks.update")?;
+
+        if let Some(name) = data.name {
+            webhook.name = name;
+        }
+        if let Some(description) = data.description {
+            if description.is_empty() {
+                webhook.description = None;
+            } else {
+                webhook.description = Some(description);
+            }
+        }
+        if let Some(url) = data.url {
+            webhook.url = url;
+        }
+        if let Some(secret) = data.secret {
+            webhook.secret = state
+                .database
+                .encrypt(secret)
+                .ok_or_else(|| sqlx::Error::Encode("failed to encrypt webhook secret".into()))?;
+        }
+        if let Some(events) = data.events {
+            webhook.events = events;
+        }
+        if let Some(enabled) = data.enabled {
+            webhook.enabled = enabled;
+        }
+
+        match sqlx::query(
+            r#"
+            UPDATE webhooks
+            SET name = $1, description = $2, url = $3, secret = $4, events = $5, enabled = $6
+            WHERE webhooks.uuid = $7
+            "#,
+        )
+        .bind(&webhook.name)
+        .bind(&webhook.description)
+        .bind(&webhook.url)
+        .bind(&webhook.secret)
+        .bind(&webhook.events)
+        .bind(webhook.enabled)
+        .bind(webhook.uuid)
+        .execute(state.database.write())
+        .await
+        {
+            Ok(_) => {}
+            Err(err) if err.to_string().contains("unique constraint") => {
+                return ApiResponse::error("webhook with name already exists")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Err(err) => {
+                tracing::error!("failed to update webhook: {:#?}", err);
+
+                return ApiResponse::error("failed to update webhook")
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
+        }
+
+        activity_logger
+            .log(
+                "webhook:update",
+                serde_json::json!({
+                    "uuid": webhook.uuid,
+                    "name": webhook.name,
+                    "description": webhook.description,
+
+                    "url": webhook.url,
+                    "events": webhook.events,
+                    "enabled": webhook.enabled,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .routes(routes!(delete::route))
+        .routes(routes!(patch::route))
+        .nest("/deliveries", deliveries::router(state))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
+        .with_state(state.clone())
+}
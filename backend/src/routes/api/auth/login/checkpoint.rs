@@ -9,16 +9,18 @@ pub struct TwoFactorRequiredJwt {
     pub base: BasePayload,
 
     pub user_uuid: uuid::Uuid,
-    pub user_totp_secret: String,
 }
 
+/// Carries the confirmation token of sign ins that end in a redirect, like OIDC.
+pub const COOKIE: &str = "checkpoint_token";
+
 mod post {
     use axum::http::StatusCode;
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
         models::{
-            user::User, user_activity::UserActivity, user_recovery_code::UserRecoveryCode,
+            user::User, user_login_failure::UserLoginFailure, user_recovery_code::UserRecoveryCode,
             user_session::UserSession,
         },
         response::{ApiResponse, ApiResponseResult},
@@ -27,7 +29,10 @@ mod post {
     use utoipa::ToSchema;
     use validator::Validate;
 
-    use crate::routes::api::auth::login::checkpoint::TwoFactorRequiredJwt;
+    use crate::routes::api::auth::{
+        Login,
+        login::checkpoint::{COOKIE, TwoFactorRequiredJwt},
+    };
 
     #[derive(ToSchema, Validate, Deserialize)]
     pub struct Payload {
@@ -35,18 +40,27 @@ mod post {
         #[schema(min_length = 6, max_length = 10)]
         code: String,
 
-        confirmation_token: String,
+        /// Falls back to the checkpoint cookie when omitted.
+        #[serde(default)]
+        confirmation_token: Option<String>,
     }
 
     #[derive(ToSchema, Serialize)]
-    struct Response {
-        user: shared::models::user::ApiFullUser,
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum Response {
+        Completed {
+            user: Box<shared::models::user::ApiFullUser>,
+        },
+        DeviceVerificationRequired {
+            token: String,
+        },
     }
 
     #[utoipa::path(post, path = "/", responses(
         (status = OK, body = inline(Response)),
         (status = BAD_REQUEST, body = ApiError),
         (status = NOT_FOUND, body = ApiError),
+        (status = TOO_MANY_REQUESTS, body = ApiError),
     ), request_body = inline(Payload))]
     pub async fn route(
         state: GetState,
@@ -55,7 +69,19 @@ mod post {
         cookies: Cookies,
         axum::Json(data): axum::Json<Payload>,
     ) -> ApiResponseResult {
-        let payload: TwoFactorRequiredJwt = match state.jwt.verify(&data.confirmation_token) {
+        let confirmation_token = match data
+            .confirmation_token
+            .or_else(|| cookies.get(COOKIE).map(|cookie| cookie.value().to_string()))
+        {
+            Some(confirmation_token) => confirmation_token,
+            None => {
+                return ApiResponse::error("invalid confirmation token")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+        };
+
+        let payload: TwoFactorRequiredJwt = match state.jwt.verify(&confirmation_token) {
             Ok(payload) => payload,
             Err(_) => {
                 return ApiResponse::error("invalid confirmation token")
@@ -70,40 +96,58 @@ mod post {
                 .ok();
         }
 
-        match data.code.len() {
+        let login = Login::detect(&state, payload.user_uuid, ip.0, &headers).await?;
+
+        if let Some(throttle) =
+            UserLoginFailure::check(&state.database, &state.settings, payload.user_uuid).await?
+        {
+            return throttle.into_response().ok();
+        }
+
+        let user = match User::by_uuid(&state.database, payload.user_uuid).await? {
+            Some(user) => user,
+            None => {
+                return ApiResponse::error("user not found")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+        };
+
+        let (using, activity) = match data.code.len() {
             6 => {
+                let secret = match &user.totp_secret {
+                    Some(secret) if user.totp_enabled => secret.clone(),
+                    _ => {
+                        return ApiResponse::error("invalid confirmation token")
+                            .with_status(StatusCode::BAD_REQUEST)
+                            .ok();
+                    }
+                };
+
                 let totp = totp_rs::TOTP::new(
                     totp_rs::Algorithm::SHA1,
                     6,
                     1,
                     30,
-                    totp_rs::Secret::Encoded(payload.user_totp_secret).to_bytes()?,
+                    totp_rs::Secret::Encoded(secret).to_bytes()?,
                 )?;
 
                 if !totp.check_current(&data.code).is_ok_and(|valid| valid) {
+                    UserLoginFailure::record(
+                        &state.database,
+                        &state.settings,
+                        payload.user_uuid,
+                        Some(ip.0),
+                        serde_json::json!({ "using": "two_factor" }),
+                    )
+                    .await?;
+
                     return ApiResponse::error("invalid confirmation code")
                         .with_status(StatusCode::BAD_REQUEST)
                         .ok();
                 }
 
-                if let Err(err) = UserActivity::log(
-                    &state.database,
-                    payload.user_uuid,
-                    None,
-                    "auth:success",
-                    ip.0.into(),
-                    serde_json::json!({
-                        "using": "two_factor",
-                    }),
-                )
-                .await
-                {
-                    tracing::warn!(
-                        user = %payload.user_uuid,
-                        "failed to log user activity: {:#?}",
-                        err
-                    );
-                }
+                ("two_factor", serde_json::json!({ "using": "two_factor" }))
             }
             10 => {
                 if let Some(code) = UserRecoveryCode::delete_by_user_uuid_code(
@@ -113,26 +157,23 @@ mod post {
                 )
                 .await?
                 {
-                    if let Err(err) = UserActivity::log(
-                        &state.database,
-                        payload.user_uuid,
-                        None,
-                        "auth:success",
-                        ip.0.into(),
+                    (
+                        "recovery_code",
                         serde_json::json!({
                             "using": "recovery_code",
                             "code": code,
                         }),
                     )
-                    .await
-                    {
-                        tracing::warn!(
-                            user = %payload.user_uuid,
-                            "failed to log user activity: {:#?}",
-                            err
-                        );
-                    }
                 } else {
+                    UserLoginFailure::record(
+                        &state.database,
+                        &state.settings,
+                        payload.user_uuid,
+                        Some(ip.0),
+                        serde_json::json!({ "using": "recovery_code" }),
+                    )
+                    .await?;
+
                     return ApiResponse::error("invalid recovery code")
                         .with_status(StatusCode::BAD_REQUEST)
                         .ok();
@@ -143,25 +184,21 @@ mod post {
                     .with_status(StatusCode::BAD_REQUEST)
                     .ok();
             }
-        }
-
-        let user = match User::by_uuid(&state.database, payload.user_uuid).await? {
-            Some(user) => user,
-            None => {
-                return ApiResponse::error("user not found")
-                    .with_status(StatusCode::NOT_FOUND)
-                    .ok();
-            }
         };
 
+        cookies.remove(Cookie::build(COOKIE).path("/api/auth/login").build());
+
+        if login.requires_verification(&state, true).await {
+            let token = login.start_verification(&state, &user, using).await?;
+
+            return ApiResponse::json(Response::DeviceVerificationRequired { token }).ok();
+        }
+
         let key = UserSession::create(
             &state.database,
             user.uuid,
             ip.0.into(),
-            headers
-                .get("User-Agent")
-                .map(|ua| shared::utils::slice_up_to(ua.to_str().unwrap_or("unknown"), 255))
-                .unwrap_or("unknown"),
+            &login.fingerprint.user_agent,
         )
         .await?;
 
@@ -179,9 +216,12 @@ mod post {
                 )
                 .build(),
         );
+        drop(settings);
+
+        login.complete(&state, user.uuid, activity, false).await;
 
-        ApiResponse::json(Response {
-            user: user.into_api_full_object(&state.storage.retrieve_urls().await),
+        ApiResponse::json(Response::Completed {
+            user: Box::new(user.into_api_full_object(&state.storage.retrieve_urls().await)),
         })
         .ok()
     }
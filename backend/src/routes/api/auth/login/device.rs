@@ -0,0 +1,183 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+/// Carries the device verification token of sign ins that end in a redirect, like OIDC.
+pub const COOKIE: &str = "device_token";
+
+mod post {
+    use axum::http::StatusCode;
+    use base64::Engine;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{user::User, user_login_failure::UserLoginFailure, user_session::UserSession},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use tower_cookies::{Cookie, Cookies};
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    use crate::routes::api::auth::{DeviceVerificationJwt, Login, login::device::COOKIE};
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        #[validate(length(equal = 8))]
+        #[schema(min_length = 8, max_length = 8)]
+        code: String,
+
+        /// Falls back to the device verification cookie when omitted.
+        #[serde(default)]
+        confirmation_token: Option<String>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        user: shared::models::user::ApiFullUser,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = TOO_MANY_REQUESTS, body = ApiError),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        ip: shared::GetIp,
+        headers: axum::http::HeaderMap,
+        cookies: Cookies,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        let confirmation_token = match data
+            .confirmation_token
+            .or_else(|| cookies.get(COOKIE).map(|cookie| cookie.value().to_string()))
+        {
+            Some(confirmation_token) => confirmation_token,
+            None => {
+                return ApiResponse::error("invalid confirmation token")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+        };
+
+        let payload: DeviceVerificationJwt = match state.jwt.verify(&confirmation_token) {
+            Ok(payload) => payload,
+            Err(_) => {
+                return ApiResponse::error("invalid confirmation token")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+        };
+
+        if !payload.base.validate() {
+            return ApiResponse::error("invalid confirmation token")
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        if let Some(throttle) =
+            UserLoginFailure::check(&state.database, &state.settings, payload.user_uuid).await?
+        {
+            return throttle.into_response().ok();
+        }
+
+        let code = base64::engine::general_purpose::STANDARD
+            .decode(&payload.code)
+            .ok()
+            .and_then(|code| state.database.decrypt(code));
+        if code.is_none_or(|code| !code.eq_ignore_ascii_case(&data.code)) {
+            UserLoginFailure::record(
+                &state.database,
+                &state.settings,
+                payload.user_uuid,
+                Some(ip.0),
+                serde_json::json!({ "using": "device_verification" }),
+            )
+            .await?;
+
+            return ApiResponse::error("invalid confirmation code")
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        // the token outlives this request, so only its first successful use counts
+        if !state
+            .cache
+            .claim(
+                &format!("device_verification::{}", payload.base.jwt_id),
+                600,
+            )
+            .await?
+        {
+            return ApiResponse::error("invalid confirmation token")
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        cookies.remove(Cookie::build(COOKIE).path("/api/auth/login").build());
+
+        let user = match User::by_uuid(&state.database, payload.user_uuid).await? {
+            Some(user) => user,
+            None => {
+                return ApiResponse::error("user not found")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+        };
+
+        let login = Login::detect(&state, user.uuid, ip.0, &headers).await?;
+
+        let key = UserSession::create(
+            &state.database,
+            user.uuid,
+            ip.0.into(),
+            &login.fingerprint.user_agent,
+        )
+        .await?;
+
+        let settings = state.settings.get().await;
+
+        cookies.add(
+            Cookie::build(("session", key))
+                .http_only(true)
+                .same_site(tower_cookies::cookie::SameSite::Strict)
+                .secure(settings.app.url.starts_with("https://"))
+                .path("/")
+                .expires(
+                    tower_cookies::cookie::time::OffsetDateTime::now_utc()
+                        + tower_cookies::cookie::time::Duration::days(30),
+                )
+                .build(),
+        );
+        drop(settings);
+
+        login
+            .complete(
+                &state,
+                user.uuid,
+                serde_json::json!({
+                    "using": payload.using,
+                    "verified": "email",
+                }),
+                true,
+            )
+            .await;
+
+        ApiResponse::json(Response {
+            user: user.into_api_full_object(&state.storage.retrieve_urls().await),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
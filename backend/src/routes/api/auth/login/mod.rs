@@ -1,23 +1,44 @@
 use super::State;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
-mod checkpoint;
+pub mod checkpoint;
+pub mod device;
 mod security_key;
 
+/// Hands the confirmation token of a sign in that ends in a redirect to the login page,
+/// without exposing it in the url.
+pub fn confirmation_cookie(
+    name: &'static str,
+    token: String,
+    app_url: &str,
+    max_age: tower_cookies::cookie::time::Duration,
+) -> tower_cookies::Cookie<'static> {
+    tower_cookies::Cookie::build((name, token))
+        .http_only(true)
+        .same_site(tower_cookies::cookie::SameSite::Lax)
+        .secure(app_url.starts_with("https://"))
+        .path("/api/auth/login")
+        .max_age(max_age)
+        .build()
+}
+
 mod post {
     use axum::http::StatusCode;
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
         jwt::BasePayload,
-        models::{user::User, user_activity::UserActivity, user_session::UserSession},
+        models::{
+            user::User, user_activity::UserActivity, user_login_failure::UserLoginFailure,
+            user_session::UserSession,
+        },
         response::{ApiResponse, ApiResponseResult},
     };
     use tower_cookies::{Cookie, Cookies};
     use utoipa::ToSchema;
     use validator::Validate;
 
-    use crate::routes::api::auth::login::checkpoint::TwoFactorRequiredJwt;
+    use crate::routes::api::auth::{Login, login::checkpoint::TwoFactorRequiredJwt};
 
     #[derive(ToSchema, Validate, Deserialize)]
     pub struct Payload {
@@ -38,11 +59,15 @@ mod post {
         TwoFactorRequired {
             token: String,
         },
+        DeviceVerificationRequired {
+            token: String,
+        },
     }
 
     #[utoipa::path(post, path = "/", responses(
         (status = OK, body = inline(Response)),
         (status = BAD_REQUEST, body = ApiError),
+        (status = TOO_MANY_REQUESTS, body = ApiError),
     ), request_body = inline(Payload))]
     pub async fn route(
         state: GetState,
@@ -63,29 +88,54 @@ mod post {
                 .ok();
         }
 
-        let user = if data.user.contains('@') {
-            match User::by_email_password(&state.database, &data.user, &data.password).await? {
-                Some(user) => user,
-                None => {
-                    return ApiResponse::error("invalid username or password")
-                        .with_status(StatusCode::BAD_REQUEST)
-                        .ok();
-                }
+        let user_uuid = User::uuid_by_username_or_email(&state.database, &data.user).await?;
+        let throttle = match user_uuid {
+            Some(user_uuid) => {
+                UserLoginFailure::check(&state.database, &state.settings, user_uuid).await?
             }
+            None => {
+                UserLoginFailure::check_unknown(&state.cache, &state.settings, &data.user).await?
+            }
+        };
+        if let Some(throttle) = throttle {
+            return throttle.into_response().ok();
+        }
+
+        let user = if data.user.contains('@') {
+            User::by_email_password(&state.database, &data.user, &data.password).await?
         } else {
-            match User::by_username_password(&state.database, &data.user, &data.password).await? {
-                Some(user) => user,
-                None => {
-                    return ApiResponse::error("invalid username or password")
-                        .with_status(StatusCode::BAD_REQUEST)
-                        .ok();
+            User::by_username_password(&state.database, &state.settings, &data.user, &data.password)
+                .await?
+        };
+        let user = match user {
+            Some(user) => user,
+            None => {
+                match user_uuid {
+                    Some(user_uuid) => {
+                        UserLoginFailure::record(
+                            &state.database,
+                            &state.settings,
+                            user_uuid,
+                            Some(ip.0),
+                            serde_json::json!({
+                                "using": "password",
+                            }),
+                        )
+                        .await?
+                    }
+                    None => {
+                        UserLoginFailure::record_unknown(&state.cache, &state.settings, &data.user)
+                            .await?
+                    }
                 }
+
+                return ApiResponse::error("invalid username or password")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
             }
         };
 
-        if user.totp_enabled
-            && let Some(secret) = &user.totp_secret
-        {
+        if user.totp_enabled && user.totp_secret.is_some() {
             let token = state.jwt.create(&TwoFactorRequiredJwt {
                 base: BasePayload {
                     issuer: "panel".into(),
@@ -97,7 +147,6 @@ mod post {
                     jwt_id: user.uuid.to_string(),
                 },
                 user_uuid: user.uuid,
-                user_totp_secret: secret.clone(),
             })?;
 
             if let Err(err) = UserActivity::log(
@@ -115,14 +164,19 @@ mod post {
 
             ApiResponse::json(Response::TwoFactorRequired { token }).ok()
         } else {
+            let login = Login::detect(&state, user.uuid, ip.0, &headers).await?;
+
+            if login.requires_verification(&state, false).await {
+                let token = login.start_verification(&state, &user, "password").await?;
+
+                return ApiResponse::json(Response::DeviceVerificationRequired { token }).ok();
+            }
+
             let key = UserSession::create(
                 &state.database,
                 user.uuid,
                 ip.0.into(),
-                headers
-                    .get("User-Agent")
-                    .map(|ua| shared::utils::slice_up_to(ua.to_str().unwrap_or("unknown"), 255))
-                    .unwrap_or("unknown"),
+                &login.fingerprint.user_agent,
             )
             .await?;
 
@@ -141,20 +195,17 @@ mod post {
                     .build(),
             );
 
-            if let Err(err) = UserActivity::log(
-                &state.database,
-                user.uuid,
-                None,
-                "auth:success",
-                ip.0.into(),
-                serde_json::json!({
-                    "using": "password",
-                }),
-            )
-            .await
-            {
-                tracing::warn!(user = %user.uuid, "failed to log user activity: {:#?}", err);
-            }
+            drop(settings);
+            login
+                .complete(
+                    &state,
+                    user.uuid,
+                    serde_json::json!({
+                        "using": "password",
+                    }),
+                    false,
+                )
+                .await;
 
             ApiResponse::json(Response::Completed {
                 user: Box::new(user.into_api_full_object(&state.storage.retrieve_urls().await)),
@@ -169,5 +220,6 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
         .routes(routes!(post::route))
         .nest("/security-key", security_key::router(state))
         .nest("/checkpoint", checkpoint::router(state))
+        .nest("/device", device::router(state))
         .with_state(state.clone())
 }
@@ -83,13 +83,15 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
-        models::{user::User, user_activity::UserActivity, user_session::UserSession},
+        models::{user::User, user_session::UserSession},
         response::{ApiResponse, ApiResponseResult},
     };
     use tower_cookies::{Cookie, Cookies};
     use utoipa::ToSchema;
     use webauthn_rs::prelude::{PasskeyAuthentication, PublicKeyCredential};
 
+    use crate::routes::api::auth::Login;
+
     #[derive(ToSchema, Deserialize)]
     pub struct Payload {
         uuid: uuid::Uuid,
@@ -98,8 +100,14 @@ mod post {
     }
 
     #[derive(ToSchema, Serialize)]
-    struct Response {
-        user: shared::models::user::ApiFullUser,
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum Response {
+        Completed {
+            user: Box<shared::models::user::ApiFullUser>,
+        },
+        DeviceVerificationRequired {
+            token: String,
+        },
     }
 
     #[utoipa::path(post, path = "/", responses(
@@ -177,14 +185,22 @@ mod post {
             .await?;
         }
 
+        let login = Login::detect(&state, user.uuid, ip.0, &headers).await?;
+
+        // a passkey proves possession of the key, like completing two-factor authentication
+        if login.requires_verification(&state, true).await {
+            let token = login
+                .start_verification(&state, &user, "security-key")
+                .await?;
+
+            return ApiResponse::json(Response::DeviceVerificationRequired { token }).ok();
+        }
+
         let key = UserSession::create(
             &state.database,
             user.uuid,
             ip.0.into(),
-            headers
-                .get("User-Agent")
-                .map(|ua| shared::utils::slice_up_to(ua.to_str().unwrap_or("unknown"), 255))
-                .unwrap_or("unknown"),
+            &login.fingerprint.user_agent,
         )
         .await?;
 
@@ -203,24 +219,21 @@ mod post {
                 .build(),
         );
 
-        if let Err(err) = UserActivity::log(
-            &state.database,
-            user.uuid,
-            None,
-            "auth:success",
-            ip.0.into(),
-            serde_json::json!({
-                "using": "security-key",
-                "uuid": security_key.uuid,
-            }),
-        )
-        .await
-        {
-            tracing::warn!(user = %user.uuid, "failed to log user activity: {:#?}", err);
-        }
+        drop(settings);
+        login
+            .complete(
+                &state,
+                user.uuid,
+                serde_json::json!({
+                    "using": "security-key",
+                    "uuid": security_key.uuid,
+                }),
+                false,
+            )
+            .await;
 
-        ApiResponse::json(Response {
-            user: user.into_api_full_object(&state.storage.retrieve_urls().await),
+        ApiResponse::json(Response::Completed {
+            user: Box::new(user.into_api_full_object(&state.storage.retrieve_urls().await)),
         })
         .ok()
     }
@@ -1,13 +1,222 @@
 use super::State;
+use base64::Engine;
+use rand::distr::SampleString;
+use serde::{Deserialize, Serialize};
+use shared::{
+    jwt::BasePayload,
+    mail::template::MailTemplateKind,
+    models::{
+        user::User,
+        user_activity::UserActivity,
+        user_login_failure::UserLoginFailure,
+        user_notification::{Notification, NotificationCategory, UserNotification},
+        user_session::{DeviceFingerprint, NewDevice, UserSession},
+    },
+    settings::NewDeviceVerification,
+};
+use std::collections::BTreeMap;
 use utoipa_axum::router::OpenApiRouter;
 
 mod login;
+pub mod oidc;
 mod password;
 mod register;
 
+#[derive(Deserialize, Serialize)]
+pub struct DeviceVerificationJwt {
+    #[serde(flatten)]
+    pub base: BasePayload,
+
+    pub user_uuid: uuid::Uuid,
+    /// The encrypted code mailed to the user.
+    pub code: String,
+    pub using: String,
+}
+
+/// A sign in that passed its credential checks, compared with the earlier sign ins of the user.
+pub struct Login {
+    pub fingerprint: DeviceFingerprint,
+    pub new_device: Option<NewDevice>,
+}
+
+impl Login {
+    /// Has to run before the session of the sign in is created.
+    pub async fn detect(
+        state: &State,
+        user_uuid: uuid::Uuid,
+        ip: std::net::IpAddr,
+        headers: &axum::http::HeaderMap,
+    ) -> Result<Self, sqlx::Error> {
+        let asn_header = state.settings.get().await.security.asn_header.clone();
+        let fingerprint = DeviceFingerprint::new(ip, headers, asn_header.as_deref());
+        let new_device =
+            UserSession::detect_new_device(&state.database, user_uuid, &fingerprint).await?;
+
+        Ok(Self {
+            fingerprint,
+            new_device,
+        })
+    }
+
+    /// Whether the new device has to be confirmed by email before a session is created.
+    pub async fn requires_verification(&self, state: &State, two_factor: bool) -> bool {
+        self.new_device.is_some()
+            && match state.settings.get().await.security.new_device_verification {
+                NewDeviceVerification::None => false,
+                NewDeviceVerification::Email => true,
+                NewDeviceVerification::TwoFactor => !two_factor,
+            }
+    }
+
+    /// Mails a code to confirm the new device with and returns the token to confirm it against.
+    pub async fn start_verification(
+        &self,
+        state: &State,
+        user: &User,
+        using: &str,
+    ) -> Result<String, anyhow::Error> {
+        let code = rand::distr::Alphanumeric
+            .sample_string(&mut rand::rng(), 8)
+            .to_uppercase();
+        let encrypted = state
+            .database
+            .encrypt(&code)
+            .ok_or_else(|| anyhow::anyhow!("failed to encrypt device verification code"))?;
+
+        let token = state.jwt.create(&DeviceVerificationJwt {
+            base: BasePayload {
+                issuer: "panel".into(),
+                subject: None,
+                audience: Vec::new(),
+                expiration_time: Some(chrono::Utc::now().timestamp() + 600),
+                not_before: None,
+                issued_at: Some(chrono::Utc::now().timestamp()),
+                jwt_id: uuid::Uuid::new_v4().to_string(),
+            },
+            user_uuid: user.uuid,
+            code: base64::engine::general_purpose::STANDARD.encode(encrypted),
+            using: using.to_string(),
+        })?;
+
+        self.log_new_device(state, user.uuid, NewDeviceVerification::Email)
+            .await;
+
+        state
+            .mail
+            .send_template(
+                user,
+                MailTemplateKind::NewDeviceVerification,
+                BTreeMap::from([
+                    ("ip", self.fingerprint.ip.to_string()),
+                    ("user_agent", self.fingerprint.user_agent.clone()),
+                    ("code", code),
+                ]),
+            )
+            .await;
+
+        Ok(token)
+    }
+
+    /// Logs the sign in, resets the failed attempts of the account and notifies about a new
+    /// device unless it was confirmed by email.
+    pub async fn complete(
+        &self,
+        state: &State,
+        user_uuid: uuid::Uuid,
+        mut data: serde_json::Value,
+        verified: bool,
+    ) {
+        if !verified && self.new_device.is_some() {
+            self.log_new_device(state, user_uuid, NewDeviceVerification::None)
+                .await;
+
+            if state.settings.get().await.security.new_device_notifications {
+                self.notify(state, user_uuid).await;
+            }
+        }
+
+        if let Err(err) = UserLoginFailure::delete_by_user_uuid(&state.database, user_uuid).await {
+            tracing::warn!(user = %user_uuid, "failed to reset failed logins: {:#?}", err);
+        }
+
+        data["user_agent"] = self.fingerprint.user_agent.clone().into();
+        if let Some(asn) = self.fingerprint.asn {
+            data["asn"] = asn.into();
+        }
+
+        if let Err(err) = UserActivity::log(
+            &state.database,
+            user_uuid,
+            None,
+            "auth:success",
+            self.fingerprint.ip.into(),
+            data,
+        )
+        .await
+        {
+            tracing::warn!(user = %user_uuid, "failed to log user activity: {:#?}", err);
+        }
+    }
+
+    async fn log_new_device(
+        &self,
+        state: &State,
+        user_uuid: uuid::Uuid,
+        verification: NewDeviceVerification,
+    ) {
+        if let Err(err) = UserActivity::log(
+            &state.database,
+            user_uuid,
+            None,
+            "auth:login.new-device",
+            self.fingerprint.ip.into(),
+            serde_json::json!({
+                "user_agent": self.fingerprint.user_agent,
+                "asn": self.fingerprint.asn,
+                "changes": self.new_device,
+                "verification": verification,
+            }),
+        )
+        .await
+        {
+            tracing::warn!(user = %user_uuid, "failed to log user activity: {:#?}", err);
+        }
+    }
+
+    async fn notify(&self, state: &State, user_uuid: uuid::Uuid) {
+        UserNotification::notify(
+            &state.database,
+            user_uuid,
+            Notification {
+                category: NotificationCategory::Account,
+                event: "account:login.new-device",
+                server_uuid: None,
+                title: "New device signed in".into(),
+                body: format!(
+                    "Your account was signed in to from a new device at {}.",
+                    self.fingerprint.ip
+                ),
+                data: serde_json::json!({
+                    "ip": self.fingerprint.ip.to_string(),
+                    "user_agent": self.fingerprint.user_agent,
+                }),
+                mail: Some((
+                    MailTemplateKind::NewLogin,
+                    BTreeMap::from([
+                        ("ip", self.fingerprint.ip.to_string()),
+                        ("user_agent", self.fingerprint.user_agent.clone()),
+                    ]),
+                )),
+            },
+        )
+        .await;
+    }
+}
+
 pub fn router(state: &State) -> OpenApiRouter<State> {
     OpenApiRouter::new()
         .nest("/login", login::router(state))
+        .nest("/oidc", oidc::router(state))
         .nest("/register", register::router(state))
         .nest("/password", password::router(state))
         .with_state(state.clone())
@@ -0,0 +1,361 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod get {
+    use crate::routes::api::auth::{
+        Login,
+        login::{checkpoint, checkpoint::TwoFactorRequiredJwt, confirmation_cookie, device},
+        oidc::{OidcStateJwt, redirect_uri},
+    };
+    use axum::{extract::Query, http::StatusCode};
+    use rand::distr::SampleString;
+    use serde::Deserialize;
+    use shared::{
+        GetState,
+        jwt::BasePayload,
+        models::{
+            user::User, user_activity::UserActivity, user_external_identity::UserExternalIdentity,
+            user_session::UserSession,
+        },
+        oidc::{OidcClaims, OidcClient},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use tower_cookies::{Cookie, Cookies};
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Deserialize)]
+    pub struct Params {
+        code: Option<String>,
+        state: Option<String>,
+        error: Option<String>,
+    }
+
+    #[inline]
+    fn redirect(url: &str) -> ApiResponseResult {
+        ApiResponse::new(axum::body::Body::empty())
+            .with_status(StatusCode::FOUND)
+            .with_header("Location", url)
+            .ok()
+    }
+
+    /// Derives a valid, unused username from the configured claim (or the email local part).
+    async fn provision_username(
+        database: &shared::database::Database,
+        claims: &OidcClaims,
+        username_claim: &str,
+        email: &str,
+    ) -> Result<String, sqlx::Error> {
+        let base = claims
+            .get_str(username_claim)
+            .unwrap_or_else(|| email.split('@').next().unwrap_or_default());
+        let mut base: String = base
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .take(15)
+            .collect();
+        while base.len() < 3 {
+            base.push('_');
+        }
+
+        let mut username = base.clone();
+        for _ in 0..5 {
+            let taken = sqlx::query("SELECT 1 FROM users WHERE users.username = $1")
+                .bind(&username)
+                .fetch_optional(database.read())
+                .await?
+                .is_some();
+            if !taken {
+                break;
+            }
+
+            let suffix = rand::distr::Alphanumeric.sample_string(&mut rand::rng(), 4);
+            username = format!("{}{suffix}", &base[..base.len().min(11)]);
+        }
+
+        Ok(username)
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = FOUND, description = "Redirects back to the panel"),
+    ), params(
+        ("code" = Option<String>, Query, description = "The authorization code"),
+        ("state" = Option<String>, Query, description = "The authorization state"),
+        ("error" = Option<String>, Query, description = "The error returned by the provider"),
+    ))]
+    pub async fn route(
+        state: GetState,
+        ip: shared::GetIp,
+        headers: axum::http::HeaderMap,
+        cookies: Cookies,
+        Query(params): Query<Params>,
+    ) -> ApiResponseResult {
+        let settings = state.settings.get().await;
+        let app_url = settings.app.url.trim_end_matches('/').to_string();
+        let oidc_settings = settings.oidc.clone();
+        drop(settings);
+
+        let payload = cookies
+            .get("oidc_state")
+            .and_then(|cookie| state.jwt.verify::<OidcStateJwt>(cookie.value()).ok())
+            .filter(|payload| payload.base.validate());
+        cookies.remove(Cookie::build("oidc_state").path("/api/auth/oidc").build());
+
+        let payload = match payload {
+            Some(payload) if params.state.as_deref() == Some(payload.state.as_str()) => payload,
+            _ => return redirect(&format!("{app_url}/auth/login?oidc_error=invalid_state")),
+        };
+
+        let error_url = |error: &str| match payload.link_user_uuid {
+            Some(_) => format!("{app_url}/account?oidc_error={error}"),
+            None => format!("{app_url}/auth/login?oidc_error={error}"),
+        };
+
+        let code = match (params.code, params.error) {
+            (Some(code), None) => code,
+            _ => return redirect(&error_url("access_denied")),
+        };
+
+        let client = match OidcClient::discover(oidc_settings).await {
+            Ok(client) => client,
+            Err(err) => {
+                tracing::warn!("failed to discover oidc provider: {:#?}", err);
+
+                return redirect(&error_url("provider_unavailable"));
+            }
+        };
+
+        let claims = match client
+            .exchange_code(
+                &redirect_uri(&app_url),
+                &code,
+                &payload.code_verifier,
+                &payload.nonce,
+            )
+            .await
+        {
+            Ok(claims) => claims,
+            Err(err) => {
+                tracing::warn!("failed to exchange oidc authorization code: {:#?}", err);
+
+                return redirect(&error_url("invalid_response"));
+            }
+        };
+
+        let subject = claims.subject();
+        if subject.len() > 255 {
+            return redirect(&error_url("invalid_response"));
+        }
+
+        let provider = client.settings().identity_provider();
+        if provider.len() > 255 {
+            return redirect(&error_url("provider_unavailable"));
+        }
+
+        let existing_user = User::by_external_identity(&state.database, provider, subject).await?;
+
+        if let Some(link_user_uuid) = payload.link_user_uuid {
+            if existing_user.is_some_and(|user| user.uuid != link_user_uuid) {
+                return redirect(&error_url("identity_in_use"));
+            }
+
+            match UserExternalIdentity::create(
+                state.database.write(),
+                link_user_uuid,
+                provider,
+                subject,
+            )
+            .await
+            {
+                Ok(()) => {}
+                Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+                    return redirect(&error_url("identity_in_use"));
+                }
+                Err(err) => return Err(err.into()),
+            }
+
+            if let Err(err) = UserActivity::log(
+                &state.database,
+                link_user_uuid,
+                None,
+                "user:account.oidc-link",
+                ip.0.into(),
+                serde_json::json!({
+                    "subject": claims.subject(),
+                }),
+            )
+            .await
+            {
+                tracing::warn!(user = %link_user_uuid, "failed to log user activity: {:#?}", err);
+            }
+
+            return redirect(&format!("{app_url}/account"));
+        }
+
+        let oidc_settings = client.settings();
+        let (user_uuid, provisioned) = match existing_user {
+            Some(user) => (user.uuid, false),
+            None => {
+                if !oidc_settings.auto_provision {
+                    return redirect(&error_url("account_not_linked"));
+                }
+
+                let email = match claims.get_str(&oidc_settings.email_claim) {
+                    Some(email) if email.contains('@') => email,
+                    _ => return redirect(&error_url("missing_email")),
+                };
+                if claims.get("email_verified").and_then(|v| v.as_bool()) == Some(false) {
+                    return redirect(&error_url("email_not_verified"));
+                }
+
+                // existing accounts have to link their identity themselves
+                if User::by_email(&state.database, email).await?.is_some() {
+                    return redirect(&error_url("email_in_use"));
+                }
+
+                let username = provision_username(
+                    &state.database,
+                    &claims,
+                    &oidc_settings.username_claim,
+                    email,
+                )
+                .await?;
+
+                let password = rand::distr::Alphanumeric.sample_string(&mut rand::rng(), 64);
+                let user_uuid = User::create(
+                    &state.database,
+                    &username,
+                    email,
+                    claims
+                        .get_str(&oidc_settings.name_first_claim)
+                        .unwrap_or(&username),
+                    claims
+                        .get_str(&oidc_settings.name_last_claim)
+                        .unwrap_or(&username),
+                    &password,
+                    false,
+                )
+                .await?;
+
+                UserExternalIdentity::create(state.database.write(), user_uuid, provider, subject)
+                    .await?;
+
+                (user_uuid, true)
+            }
+        };
+
+        if let Some(groups_claim) = &oidc_settings.groups_claim
+            && !oidc_settings.role_mappings.is_empty()
+        {
+            let groups = claims.get_strings(groups_claim);
+
+            User::update_mapped_role(
+                &state.database,
+                user_uuid,
+                &oidc_settings.role_mappings,
+                |group| groups.contains(&group),
+            )
+            .await?;
+        }
+
+        let user = match User::by_uuid(&state.database, user_uuid).await? {
+            Some(user) => user,
+            None => return redirect(&error_url("account_not_linked")),
+        };
+
+        // the identity provider does not replace the second factor of the panel
+        if user.totp_enabled && user.totp_secret.is_some() {
+            let token = state.jwt.create(&TwoFactorRequiredJwt {
+                base: BasePayload {
+                    issuer: "panel".into(),
+                    subject: None,
+                    audience: Vec::new(),
+                    expiration_time: Some(chrono::Utc::now().timestamp() + 300),
+                    not_before: None,
+                    issued_at: Some(chrono::Utc::now().timestamp()),
+                    jwt_id: user.uuid.to_string(),
+                },
+                user_uuid: user.uuid,
+            })?;
+
+            if let Err(err) = UserActivity::log(
+                &state.database,
+                user.uuid,
+                None,
+                "auth:checkpoint",
+                ip.0.into(),
+                serde_json::json!({
+                    "using": "oidc",
+                }),
+            )
+            .await
+            {
+                tracing::warn!(user = %user.uuid, "failed to log user activity: {:#?}", err);
+            }
+
+            cookies.add(confirmation_cookie(
+                checkpoint::COOKIE,
+                token,
+                &app_url,
+                tower_cookies::cookie::time::Duration::minutes(5),
+            ));
+
+            return redirect(&format!("{app_url}/auth/login?step=totp"));
+        }
+
+        let login = Login::detect(&state, user_uuid, ip.0, &headers).await?;
+
+        if login.requires_verification(&state, false).await {
+            let token = login.start_verification(&state, &user, "oidc").await?;
+
+            cookies.add(confirmation_cookie(
+                device::COOKIE,
+                token,
+                &app_url,
+                tower_cookies::cookie::time::Duration::minutes(10),
+            ));
+
+            return redirect(&format!("{app_url}/auth/login?step=device"));
+        }
+
+        let key = UserSession::create(
+            &state.database,
+            user_uuid,
+            ip.0.into(),
+            &login.fingerprint.user_agent,
+        )
+        .await?;
+
+        cookies.add(
+            Cookie::build(("session", key))
+                .http_only(true)
+                .same_site(tower_cookies::cookie::SameSite::Strict)
+                .secure(app_url.starts_with("https://"))
+                .path("/")
+                .expires(
+                    tower_cookies::cookie::time::OffsetDateTime::now_utc()
+                        + tower_cookies::cookie::time::Duration::days(30),
+                )
+                .build(),
+        );
+
+        login
+            .complete(
+                &state,
+                user_uuid,
+                serde_json::json!({
+                    "using": "oidc",
+                    "provisioned": provisioned,
+                }),
+                false,
+            )
+            .await;
+
+        redirect(&format!("{app_url}/"))
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .with_state(state.clone())
+}
@@ -0,0 +1,83 @@
+use super::State;
+use rand::distr::SampleString;
+use serde::{Deserialize, Serialize};
+use shared::{jwt::BasePayload, oidc::OidcClient};
+use tower_cookies::{Cookie, Cookies};
+use utoipa_axum::router::OpenApiRouter;
+
+mod callback;
+mod redirect;
+
+#[derive(Deserialize, Serialize)]
+pub struct OidcStateJwt {
+    #[serde(flatten)]
+    pub base: BasePayload,
+
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub link_user_uuid: Option<uuid::Uuid>,
+}
+
+#[inline]
+pub fn redirect_uri(app_url: &str) -> String {
+    format!("{}/api/auth/oidc/callback", app_url.trim_end_matches('/'))
+}
+
+/// Starts an authorization request with the configured provider and stores its state in a cookie.
+/// When `link_user_uuid` is set, the callback links the identity to that user instead of logging in.
+pub async fn start_authorization(
+    state: &shared::State,
+    cookies: &Cookies,
+    link_user_uuid: Option<uuid::Uuid>,
+) -> Result<String, anyhow::Error> {
+    let settings = state.settings.get().await;
+    let app_url = settings.app.url.clone();
+    let oidc_settings = settings.oidc.clone();
+    drop(settings);
+
+    let client = OidcClient::discover(oidc_settings).await?;
+
+    let payload = OidcStateJwt {
+        base: BasePayload {
+            issuer: "panel".into(),
+            subject: None,
+            audience: Vec::new(),
+            expiration_time: Some(chrono::Utc::now().timestamp() + 600),
+            not_before: None,
+            issued_at: Some(chrono::Utc::now().timestamp()),
+            jwt_id: uuid::Uuid::new_v4().to_string(),
+        },
+        state: rand::distr::Alphanumeric.sample_string(&mut rand::rng(), 32),
+        nonce: rand::distr::Alphanumeric.sample_string(&mut rand::rng(), 32),
+        code_verifier: rand::distr::Alphanumeric.sample_string(&mut rand::rng(), 64),
+        link_user_uuid,
+    };
+
+    let url = client.authorization_url(
+        &redirect_uri(&app_url),
+        &payload.state,
+        &payload.nonce,
+        &payload.code_verifier,
+    )?;
+
+    // the callback is a cross-site navigation, so this can not be a strict cookie
+    cookies.add(
+        Cookie::build(("oidc_state", state.jwt.create(&payload)?))
+            .http_only(true)
+            .same_site(tower_cookies::cookie::SameSite::Lax)
+            .secure(app_url.starts_with("https://"))
+            .path("/api/auth/oidc")
+            .max_age(tower_cookies::cookie::time::Duration::minutes(10))
+            .build(),
+    );
+
+    Ok(url)
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .nest("/redirect", redirect::router(state))
+        .nest("/callback", callback::router(state))
+        .with_state(state.clone())
+}
@@ -0,0 +1,45 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod get {
+    use axum::http::StatusCode;
+    use shared::{
+        ApiError, GetState,
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use tower_cookies::Cookies;
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = FOUND, description = "Redirects to the OpenID Connect provider"),
+        (status = BAD_REQUEST, body = ApiError),
+    ))]
+    pub async fn route(state: GetState, cookies: Cookies) -> ApiResponseResult {
+        if !state.settings.get().await.oidc.enabled {
+            return ApiResponse::error("single sign-on is not enabled")
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        let url = match super::super::start_authorization(&state, &cookies, None).await {
+            Ok(url) => url,
+            Err(err) => {
+                tracing::warn!("failed to start oidc authorization: {:#?}", err);
+
+                return ApiResponse::error("single sign-on provider is unavailable")
+                    .with_status(StatusCode::BAD_GATEWAY)
+                    .ok();
+            }
+        };
+
+        ApiResponse::new(axum::body::Body::empty())
+            .with_status(StatusCode::FOUND)
+            .with_header("Location", &url)
+            .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .with_state(state.clone())
+}
@@ -6,9 +6,11 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
+        mail::template::MailTemplateKind,
         models::{user::User, user_password_reset::UserPasswordReset},
         response::{ApiResponse, ApiResponseResult},
     };
+    use std::collections::BTreeMap;
     use utoipa::ToSchema;
     use validator::Validate;
 
@@ -65,26 +67,18 @@ mod post {
                 }
             };
 
-            let settings = state.settings.get().await;
-
-            let mail = shared::mail::MAIL_PASSWORD_RESET
-                .replace("{{app_name}}", &settings.app.name)
-                .replace("{{user_username}}", &user.username)
-                .replace(
-                    "{{reset_link}}",
-                    &format!(
-                        "{}/auth/reset-password?token={}",
-                        settings.app.url,
-                        urlencoding::encode(&token),
-                    ),
-                );
+            let reset_link = format!(
+                "{}/auth/reset-password?token={}",
+                state.settings.get().await.app.url,
+                urlencoding::encode(&token),
+            );
 
             state
                 .mail
-                .send(
-                    user.email,
-                    format!("{} - Password Reset", settings.app.name),
-                    mail,
+                .send_template(
+                    &user,
+                    MailTemplateKind::PasswordReset,
+                    BTreeMap::from([("reset_link", reset_link)]),
                 )
                 .await;
         });
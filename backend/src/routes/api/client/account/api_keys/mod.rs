@@ -85,13 +85,16 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
+        mail::template::MailTemplateKind,
         models::{
             user::{AuthMethod, GetAuthMethod, GetPermissionManager, GetUser},
             user_activity::GetUserActivityLogger,
             user_api_key::UserApiKey,
+            user_notification::{Notification, NotificationCategory, UserNotification},
         },
         response::{ApiResponse, ApiResponseResult},
     };
+    use std::collections::BTreeMap;
     use utoipa::ToSchema;
     use validator::Validate;
 
@@ -195,6 +198,24 @@ mod post {
             )
             .await;
 
+        UserNotification::notify(
+            &state.database,
+            user.uuid,
+            Notification {
+                category: NotificationCategory::Account,
+                event: "account:api-key.created",
+                server_uuid: None,
+                title: "API key created".into(),
+                body: format!("The API key {} was created on your account.", api_key.name),
+                data: serde_json::json!({ "uuid": api_key.uuid }),
+                mail: Some((
+                    MailTemplateKind::ApiKeyCreated,
+                    BTreeMap::from([("api_key_name", api_key.name.clone())]),
+                )),
+            },
+        )
+        .await;
+
         ApiResponse::json(Response {
             api_key: api_key.into_api_object(),
             key,
@@ -6,7 +6,10 @@ mod api_keys;
 mod avatar;
 mod email;
 mod logout;
+mod notifications;
+mod oidc;
 mod password;
+mod quotas;
 mod security_keys;
 mod sessions;
 mod ssh_keys;
@@ -65,6 +68,14 @@ mod patch {
         #[validate(length(min = 2, max = 255))]
         #[schema(min_length = 2, max_length = 255)]
         name_last: Option<String>,
+        /// Used for mails sent to the user.
+        #[validate(
+            length(min = 2, max = 15),
+            regex(path = "*shared::models::user::LANGUAGE_REGEX")
+        )]
+        #[schema(min_length = 2, max_length = 15)]
+        #[schema(pattern = "^[a-z]{2,3}(-[A-Za-z0-9]{2,8})*$")]
+        language: Option<String>,
     }
 
     #[derive(ToSchema, Serialize)]
@@ -94,6 +105,9 @@ mod patch {
         if let Some(name_last) = data.name_last {
             user.name_last = name_last;
         }
+        if let Some(language) = data.language {
+            user.language = language;
+        }
 
         sqlx::query!(
             "UPDATE users
@@ -107,6 +121,12 @@ mod patch {
         .execute(state.database.write())
         .await?;
 
+        sqlx::query("UPDATE users SET language = $2 WHERE users.uuid = $1")
+            .bind(user.uuid)
+            .bind(&user.language)
+            .execute(state.database.write())
+            .await?;
+
         activity_logger
             .log(
                 "user:account.update",
@@ -114,6 +134,7 @@ mod patch {
                     "username": user.username,
                     "name_first": user.name_first,
                     "name_last": user.name_last,
+                    "language": user.language,
                 }),
             )
             .await;
@@ -130,11 +151,14 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
         .nest("/avatar", avatar::router(state))
         .nest("/email", email::router(state))
         .nest("/password", password::router(state))
+        .nest("/oidc", oidc::router(state))
+        .nest("/quotas", quotas::router(state))
         .nest("/two-factor", two_factor::router(state))
         .nest("/security-keys", security_keys::router(state))
         .nest("/api-keys", api_keys::router(state))
         .nest("/ssh-keys", ssh_keys::router(state))
         .nest("/sessions", sessions::router(state))
+        .nest("/notifications", notifications::router(state))
         .nest("/activity", activity::router(state))
         .with_state(state.clone())
 }
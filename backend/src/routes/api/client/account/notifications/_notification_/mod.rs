@@ -0,0 +1,75 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod read;
+
+mod delete {
+    use axum::{extract::Path, http::StatusCode};
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            user::{GetPermissionManager, GetUser},
+            user_activity::GetUserActivityLogger,
+            user_notification::UserNotification,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(delete, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "notification" = uuid::Uuid,
+            description = "The notification ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+        activity_logger: GetUserActivityLogger,
+        Path(notification): Path<uuid::Uuid>,
+    ) -> ApiResponseResult {
+        permissions.has_user_permission("notifications.delete")?;
+
+        let notification =
+            match UserNotification::by_user_uuid_uuid(&state.database, user.uuid, notification)
+                .await?
+            {
+                Some(notification) => notification,
+                None => {
+                    return ApiResponse::error("notification not found")
+                        .with_status(StatusCode::NOT_FOUND)
+                        .ok();
+                }
+            };
+
+        UserNotification::delete_by_uuid(&state.database, notification.uuid).await?;
+
+        activity_logger
+            .log(
+                "user:notification.delete",
+                serde_json::json!({
+                    "uuid": notification.uuid,
+                    "event": notification.event,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(delete::route))
+        .nest("/read", read::router(state))
+        .with_state(state.clone())
+}
@@ -0,0 +1,65 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use axum::{extract::Path, http::StatusCode};
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            user::{GetPermissionManager, GetUser},
+            user_notification::UserNotification,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        notification: shared::models::user_notification::ApiUserNotification,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ), params(
+        (
+            "notification" = uuid::Uuid,
+            description = "The notification ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+        Path(notification): Path<uuid::Uuid>,
+    ) -> ApiResponseResult {
+        permissions.has_user_permission("notifications.update")?;
+
+        UserNotification::mark_read(&state.database, user.uuid, Some(notification)).await?;
+
+        let notification =
+            match UserNotification::by_user_uuid_uuid(&state.database, user.uuid, notification)
+                .await?
+            {
+                Some(notification) => notification,
+                None => {
+                    return ApiResponse::error("notification not found")
+                        .with_status(StatusCode::NOT_FOUND)
+                        .ok();
+                }
+            };
+
+        ApiResponse::json(Response {
+            notification: notification.into_api_object(),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
@@ -0,0 +1,103 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod _notification_;
+mod preferences;
+mod read;
+
+mod get {
+    use axum::{extract::Query, http::StatusCode};
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        models::{
+            Pagination, PaginationParams,
+            user::{GetPermissionManager, GetUser},
+            user_notification::UserNotification,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(Deserialize)]
+    pub struct Params {
+        #[serde(default)]
+        unread: bool,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        unread: i64,
+        #[schema(inline)]
+        notifications: Pagination<shared::models::user_notification::ApiUserNotification>,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ), params(
+        (
+            "page" = i64, Query,
+            description = "The page number",
+            example = "1",
+        ),
+        (
+            "per_page" = i64, Query,
+            description = "The number of items per page",
+            example = "10",
+        ),
+        (
+            "unread" = Option<bool>, Query,
+            description = "Only list notifications that were not read yet",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+        Query(params): Query<PaginationParams>,
+        Query(filter): Query<Params>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&params) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_user_permission("notifications.read")?;
+
+        let (notifications, unread) = tokio::try_join!(
+            UserNotification::by_user_uuid_with_pagination(
+                &state.database,
+                user.uuid,
+                params.page,
+                params.per_page,
+                filter.unread,
+            ),
+            UserNotification::count_unread_by_user_uuid(&state.database, user.uuid),
+        )?;
+
+        ApiResponse::json(Response {
+            unread,
+            notifications: Pagination {
+                total: notifications.total,
+                per_page: notifications.per_page,
+                page: notifications.page,
+                data: notifications
+                    .data
+                    .into_iter()
+                    .map(|notification| notification.into_api_object())
+                    .collect(),
+            },
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .nest("/read", read::router(state))
+        .nest("/preferences", preferences::router(state))
+        .nest("/{notification}", _notification_::router(state))
+        .with_state(state.clone())
+}
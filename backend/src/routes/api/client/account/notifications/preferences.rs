@@ -0,0 +1,87 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod get {
+    use serde::Serialize;
+    use shared::{
+        GetState,
+        models::{
+            user::{GetPermissionManager, GetUser},
+            user_notification::NotificationPreferences,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        preferences: NotificationPreferences,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+    ) -> ApiResponseResult {
+        permissions.has_user_permission("notifications.read")?;
+
+        let preferences = NotificationPreferences::by_user_uuid(&state.database, user.uuid)
+            .await?
+            .unwrap_or_default();
+
+        ApiResponse::json(Response { preferences }).ok()
+    }
+}
+
+mod put {
+    use serde::Serialize;
+    use shared::{
+        GetState,
+        models::{
+            user::{GetPermissionManager, GetUser},
+            user_activity::GetUserActivityLogger,
+            user_notification::NotificationPreferences,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        preferences: NotificationPreferences,
+    }
+
+    #[utoipa::path(put, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ), request_body = NotificationPreferences)]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+        activity_logger: GetUserActivityLogger,
+        axum::Json(preferences): axum::Json<NotificationPreferences>,
+    ) -> ApiResponseResult {
+        permissions.has_user_permission("notifications.update")?;
+
+        preferences.save(&state.database, user.uuid).await?;
+
+        activity_logger
+            .log(
+                "user:notification.preferences.update",
+                serde_json::to_value(preferences)?,
+            )
+            .await;
+
+        ApiResponse::json(Response { preferences }).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .routes(routes!(put::route))
+        .with_state(state.clone())
+}
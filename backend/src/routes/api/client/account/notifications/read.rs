@@ -0,0 +1,41 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use serde::Serialize;
+    use shared::{
+        GetState,
+        models::{
+            user::{GetPermissionManager, GetUser},
+            user_notification::UserNotification,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        read: u64,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+    ) -> ApiResponseResult {
+        permissions.has_user_permission("notifications.update")?;
+
+        let read = UserNotification::mark_read(&state.database, user.uuid, None).await?;
+
+        ApiResponse::json(Response { read }).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
@@ -0,0 +1,184 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod get {
+    use serde::Serialize;
+    use shared::{
+        GetState,
+        models::{user::GetUser, user_external_identity::UserExternalIdentity},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        enabled: bool,
+        linked: bool,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ))]
+    pub async fn route(state: GetState, user: GetUser) -> ApiResponseResult {
+        let settings = state.settings.get().await;
+        let enabled = settings.oidc.enabled;
+        let provider = settings.oidc.identity_provider().to_string();
+        drop(settings);
+
+        ApiResponse::json(Response {
+            enabled,
+            linked: UserExternalIdentity::by_user_uuid_provider(
+                &state.database,
+                user.uuid,
+                &provider,
+            )
+            .await?
+            .is_some(),
+        })
+        .ok()
+    }
+}
+
+mod post {
+    use axum::http::StatusCode;
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            user::{GetPermissionManager, GetUser},
+            user_external_identity::UserExternalIdentity,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use tower_cookies::Cookies;
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        url: String,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+        cookies: Cookies,
+    ) -> ApiResponseResult {
+        permissions.has_user_permission("account.oidc")?;
+
+        let settings = state.settings.get().await;
+        if !settings.oidc.enabled {
+            return ApiResponse::error("single sign-on is not enabled")
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+        let provider = settings.oidc.identity_provider().to_string();
+        drop(settings);
+
+        if UserExternalIdentity::by_user_uuid_provider(&state.database, user.uuid, &provider)
+            .await?
+            .is_some()
+        {
+            return ApiResponse::error("a single sign-on identity is already linked")
+                .with_status(StatusCode::CONFLICT)
+                .ok();
+        }
+
+        let url = match crate::routes::api::auth::oidc::start_authorization(
+            &state,
+            &cookies,
+            Some(user.uuid),
+        )
+        .await
+        {
+            Ok(url) => url,
+            Err(err) => {
+                tracing::warn!("failed to start oidc authorization: {:#?}", err);
+
+                return ApiResponse::error("single sign-on provider is unavailable")
+                    .with_status(StatusCode::BAD_GATEWAY)
+                    .ok();
+            }
+        };
+
+        ApiResponse::json(Response { url }).ok()
+    }
+}
+
+mod delete {
+    use axum::http::StatusCode;
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            user::{GetPermissionManager, GetUser},
+            user_activity::GetUserActivityLogger,
+            user_external_identity::UserExternalIdentity,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    #[utoipa::path(delete, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+        activity_logger: GetUserActivityLogger,
+    ) -> ApiResponseResult {
+        permissions.has_user_permission("account.oidc")?;
+
+        let provider = state
+            .settings
+            .get()
+            .await
+            .oidc
+            .identity_provider()
+            .to_string();
+
+        let identity = match UserExternalIdentity::delete_by_user_uuid_provider(
+            &state.database,
+            user.uuid,
+            &provider,
+        )
+        .await?
+        {
+            Some(identity) => identity,
+            None => {
+                return ApiResponse::error("no single sign-on identity is linked")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+        };
+
+        activity_logger
+            .log(
+                "user:account.oidc-unlink",
+                serde_json::json!({
+                    "subject": identity.subject,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .routes(routes!(post::route))
+        .routes(routes!(delete::route))
+        .with_state(state.clone())
+}
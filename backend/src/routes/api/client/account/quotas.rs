@@ -0,0 +1,38 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod get {
+    use serde::Serialize;
+    use shared::{
+        GetState,
+        models::{
+            quota::{QuotaUsage, Quotas},
+            user::GetUser,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        limits: Quotas,
+        usage: QuotaUsage,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ))]
+    pub async fn route(state: GetState, user: GetUser) -> ApiResponseResult {
+        ApiResponse::json(Response {
+            limits: user.effective_quotas(),
+            usage: QuotaUsage::by_user_uuid(&state.database, user.uuid).await?,
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .with_state(state.clone())
+}
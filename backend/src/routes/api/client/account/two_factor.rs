@@ -69,13 +69,16 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
+        mail::template::MailTemplateKind,
         models::{
             user::{GetPermissionManager, GetUser},
             user_activity::GetUserActivityLogger,
+            user_notification::{Notification, NotificationCategory, UserNotification},
             user_recovery_code::UserRecoveryCode,
         },
         response::{ApiResponse, ApiResponseResult},
     };
+    use std::collections::BTreeMap;
     use utoipa::ToSchema;
     use validator::Validate;
 
@@ -168,6 +171,21 @@ mod post {
             .log("user:account.two-factor.enable", serde_json::json!({}))
             .await;
 
+        UserNotification::notify(
+            &state.database,
+            user.uuid,
+            Notification {
+                category: NotificationCategory::Account,
+                event: "account:two-factor.enabled",
+                server_uuid: None,
+                title: "Two-factor authentication enabled".into(),
+                body: "Two-factor authentication has been enabled on your account.".into(),
+                data: serde_json::json!({}),
+                mail: Some((MailTemplateKind::TwoFactorEnabled, BTreeMap::new())),
+            },
+        )
+        .await;
+
         ApiResponse::json(Response { recovery_codes }).ok()
     }
 }
@@ -177,13 +195,16 @@ mod delete {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
+        mail::template::MailTemplateKind,
         models::{
             user::{GetPermissionManager, GetUser},
             user_activity::GetUserActivityLogger,
+            user_notification::{Notification, NotificationCategory, UserNotification},
             user_recovery_code::UserRecoveryCode,
         },
         response::{ApiResponse, ApiResponseResult},
     };
+    use std::collections::BTreeMap;
     use utoipa::ToSchema;
     use validator::Validate;
 
@@ -288,6 +309,21 @@ mod delete {
             .log("user:account.two-factor.disable", serde_json::json!({}))
             .await;
 
+        UserNotification::notify(
+            &state.database,
+            user.uuid,
+            Notification {
+                category: NotificationCategory::Account,
+                event: "account:two-factor.disabled",
+                server_uuid: None,
+                title: "Two-factor authentication disabled".into(),
+                body: "Two-factor authentication has been disabled on your account.".into(),
+                data: serde_json::json!({}),
+                mail: Some((MailTemplateKind::TwoFactorDisabled, BTreeMap::new())),
+            },
+        )
+        .await;
+
         ApiResponse::json(Response {}).ok()
     }
 }
@@ -14,6 +14,7 @@ use utoipa_axum::{router::OpenApiRouter, routes};
 
 mod download;
 mod restore;
+mod verify;
 
 pub type GetServerBackup = shared::extract::ConsumingExtension<ServerBackup>;
 
@@ -106,7 +107,7 @@ mod delete {
     use shared::{
         ApiError, GetState,
         models::{
-            server::{GetServer, GetServerActivityLogger},
+            server::GetServerActivityLogger, server_backup::ServerBackup,
             user::GetPermissionManager,
         },
         response::{ApiResponse, ApiResponseResult},
@@ -122,6 +123,7 @@ mod delete {
         (status = OK, body = inline(Response)),
         (status = UNAUTHORIZED, body = ApiError),
         (status = NOT_FOUND, body = ApiError),
+        (status = CONFLICT, body = ApiError),
         (status = EXPECTATION_FAILED, body = ApiError),
     ), params(
         (
@@ -138,7 +140,6 @@ mod delete {
     pub async fn route(
         state: GetState,
         permissions: GetPermissionManager,
-        server: GetServer,
         activity_logger: GetServerActivityLogger,
         backup: GetServerBackup,
     ) -> ApiResponseResult {
@@ -159,11 +160,9 @@ mod delete {
         let backup_uuid = backup.uuid;
         let backup_name = backup.name.clone();
 
-        if let Err(err) = backup.0.delete(&state.database, &server).await {
-            tracing::error!(server = %server.uuid, backup = %backup_uuid, "failed to delete backup: {:#?}", err);
-
-            return ApiResponse::error("failed to delete backup")
-                .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+        if !ServerBackup::queue_deletion(&state.database, backup_uuid).await? {
+            return ApiResponse::error("backup has already been deleted")
+                .with_status(StatusCode::CONFLICT)
                 .ok();
         }
 
@@ -283,6 +282,7 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
         .routes(routes!(patch::route))
         .nest("/download", download::router(state))
         .nest("/restore", restore::router(state))
+        .nest("/verify", verify::router(state))
         .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
         .with_state(state.clone())
 }
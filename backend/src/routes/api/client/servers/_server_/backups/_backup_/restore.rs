@@ -7,8 +7,9 @@ mod post {
     use shared::{
         ApiError, GetState,
         models::{
-            server::{GetServer, GetServerActivityLogger},
-            user::GetPermissionManager,
+            server::{GetServer, GetServerActivityLogger, Server, ServerActivityLogger},
+            server_backup::BackupRestoreIntoStatus,
+            user::{GetPermissionManager, GetUser},
         },
         response::{ApiResponse, ApiResponseResult},
     };
@@ -19,6 +20,10 @@ mod post {
     #[derive(ToSchema, Deserialize)]
     pub struct Payload {
         truncate_directory: bool,
+        /// Restores the backup into another server, requires ownership of both servers and
+        /// the `backups.restore` permission on both.
+        #[serde(default)]
+        target_server_uuid: Option<uuid::Uuid>,
     }
 
     #[derive(ToSchema, Serialize)]
@@ -27,8 +32,11 @@ mod post {
     #[utoipa::path(post, path = "/", responses(
         (status = OK, body = inline(Response)),
         (status = UNAUTHORIZED, body = ApiError),
+        (status = FORBIDDEN, body = ApiError),
         (status = NOT_FOUND, body = ApiError),
         (status = BAD_REQUEST, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+        (status = EXPECTATION_FAILED, body = ApiError),
     ), params(
         (
             "server" = uuid::Uuid,
@@ -44,6 +52,7 @@ mod post {
     pub async fn route(
         state: GetState,
         permissions: GetPermissionManager,
+        user: GetUser,
         server: GetServer,
         activity_logger: GetServerActivityLogger,
         backup: GetServerBackup,
@@ -57,45 +66,163 @@ mod post {
                 .ok();
         }
 
-        let mut transaction = state.database.write().begin().await?;
-
-        let rows_affected = sqlx::query!(
-            "UPDATE servers
-            SET status = 'RESTORING_BACKUP'
-            WHERE servers.uuid = $1 AND servers.status IS NULL",
-            server.uuid
-        )
-        .execute(&mut *transaction)
-        .await?
-        .rows_affected();
-
-        if rows_affected == 0 {
-            transaction.rollback().await?;
-
-            return ApiResponse::error("server is not in a valid state to restore backup.")
-                .with_status(StatusCode::EXPECTATION_FAILED)
+        if let Some(target_server_uuid) = data.target_server_uuid
+            && target_server_uuid != server.uuid
+        {
+            if !user.admin && server.owner.uuid != user.uuid {
+                return ApiResponse::error(
+                    "only the owner of the server can restore its backups into another server",
+                )
+                .with_status(StatusCode::FORBIDDEN)
                 .ok();
+            }
+
+            let target_server = match Server::by_user_identifier(
+                &state.database,
+                &user,
+                &target_server_uuid.to_string(),
+            )
+            .await?
+            {
+                Some(target_server) if user.admin || target_server.owner.uuid == user.uuid => {
+                    target_server
+                }
+                _ => {
+                    return ApiResponse::error("target server not found")
+                        .with_status(StatusCode::NOT_FOUND)
+                        .ok();
+                }
+            };
+
+            permissions
+                .0
+                .clone()
+                .add_subuser_permissions(target_server.subuser_permissions.clone())
+                .has_server_permission("backups.restore")?;
+
+            if target_server.suspended && !user.admin {
+                return ApiResponse::error("target server is suspended")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+
+            let backup_uuid = backup.uuid;
+            let backup_name = backup.name.clone();
+
+            let target_logger = ServerActivityLogger {
+                state: activity_logger.state.clone(),
+                server_uuid: target_server.uuid,
+                user_uuid: activity_logger.user_uuid,
+                api_key_uuid: activity_logger.api_key_uuid,
+                ip: activity_logger.ip,
+            };
+
+            match backup
+                .0
+                .restore_into(
+                    &state.database,
+                    &state.jwt,
+                    target_server,
+                    data.truncate_directory,
+                )
+                .await
+            {
+                Ok(BackupRestoreIntoStatus::Started) => {}
+                Ok(BackupRestoreIntoStatus::ServerTransferring) => {
+                    return ApiResponse::error("target server is being transferred")
+                        .with_status(StatusCode::CONFLICT)
+                        .ok();
+                }
+                Ok(BackupRestoreIntoStatus::NotRestorable) => {
+                    return ApiResponse::error(
+                        "backup is stored on another node and cannot be restored into the target server",
+                    )
+                    .with_status(StatusCode::EXPECTATION_FAILED)
+                    .ok();
+                }
+                Ok(BackupRestoreIntoStatus::ServerBusy) => {
+                    return ApiResponse::error(
+                        "target server is not in a valid state to restore backup.",
+                    )
+                    .with_status(StatusCode::EXPECTATION_FAILED)
+                    .ok();
+                }
+                Ok(BackupRestoreIntoStatus::BackupBusy) => {
+                    return ApiResponse::error(
+                        "backup is already being restored into another server",
+                    )
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+                }
+                Err(err) => {
+                    tracing::error!(server = %target_server_uuid, backup = %backup_uuid, "failed to restore backup: {:#?}", err);
+
+                    return ApiResponse::error("failed to restore backup")
+                        .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .ok();
+                }
+            }
+
+            activity_logger
+                .log(
+                    "server:backup.restore",
+                    serde_json::json!({
+                        "uuid": backup_uuid,
+                        "name": backup_name,
+                        "truncate_directory": data.truncate_directory,
+                        "target_server_uuid": target_server_uuid,
+                    }),
+                )
+                .await;
+            target_logger
+                .log(
+                    "server:backup.restore",
+                    serde_json::json!({
+                        "uuid": backup_uuid,
+                        "name": backup_name,
+                        "truncate_directory": data.truncate_directory,
+                        "source_server_uuid": server.uuid,
+                    }),
+                )
+                .await;
+
+            return ApiResponse::json(Response {}).ok();
         }
 
         let backup_uuid = backup.uuid;
         let backup_name = backup.name.clone();
 
         let uuid = server.uuid;
-        if let Err(err) = backup
+        match backup
             .0
-            .restore(&state.database, server.0, data.truncate_directory)
+            .restore_into(
+                &state.database,
+                &state.jwt,
+                server.0,
+                data.truncate_directory,
+            )
             .await
         {
-            transaction.rollback().await?;
-            tracing::error!(server = %uuid, backup = %backup_uuid, "failed to restore backup: {:#?}", err);
-
-            return ApiResponse::error("failed to restore backup")
-                .with_status(StatusCode::INTERNAL_SERVER_ERROR)
-                .ok();
+            Ok(BackupRestoreIntoStatus::Started) => {}
+            Ok(BackupRestoreIntoStatus::BackupBusy) => {
+                return ApiResponse::error("backup is already being restored into another server")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Ok(_) => {
+                return ApiResponse::error("server is not in a valid state to restore backup.")
+                    .with_status(StatusCode::EXPECTATION_FAILED)
+                    .ok();
+            }
+            Err(err) => {
+                tracing::error!(server = %uuid, backup = %backup_uuid, "failed to restore backup: {:#?}", err);
+
+                return ApiResponse::error("failed to restore backup")
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
         }
 
-        transaction.commit().await?;
-
         activity_logger
             .log(
                 "server:backup.restore",
@@ -0,0 +1,89 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use crate::routes::api::client::servers::_server_::backups::_backup_::GetServerBackup;
+    use axum::http::StatusCode;
+    use serde::Serialize;
+    use shared::{
+        ApiError, GetState,
+        models::{
+            server::{GetServer, GetServerActivityLogger},
+            server_backup::ServerBackup,
+            user::GetPermissionManager,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {}
+
+    /// Queues a verification of the backup.
+    ///
+    /// Only S3 backups can be verified, wings has no endpoint to check restic repositories
+    /// yet. Restic and local backups are refused with `417 Expectation Failed`.
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+        (status = EXPECTATION_FAILED, body = ApiError),
+    ), params(
+        (
+            "server" = uuid::Uuid,
+            description = "The server ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        (
+            "backup" = uuid::Uuid,
+            description = "The backup ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        server: GetServer,
+        activity_logger: GetServerActivityLogger,
+        backup: GetServerBackup,
+    ) -> ApiResponseResult {
+        permissions.has_server_permission("backups.verify")?;
+
+        if !backup.is_verifiable() {
+            return ApiResponse::error("only S3 backups can be verified")
+                .with_status(StatusCode::EXPECTATION_FAILED)
+                .ok();
+        }
+
+        if backup.completed.is_none() || !backup.successful {
+            return ApiResponse::error("backup has not been completed successfully")
+                .with_status(StatusCode::EXPECTATION_FAILED)
+                .ok();
+        }
+
+        if !ServerBackup::queue_verification(&state.database, server.uuid, backup.uuid).await? {
+            return ApiResponse::error("backup is already being verified")
+                .with_status(StatusCode::CONFLICT)
+                .ok();
+        }
+
+        activity_logger
+            .log(
+                "server:backup.verify",
+                serde_json::json!({
+                    "uuid": backup.uuid,
+                    "name": backup.name,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {}).ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
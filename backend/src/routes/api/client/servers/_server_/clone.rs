@@ -0,0 +1,233 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod post {
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        deployment::{DeploymentRequest, DeploymentStrategy, NoDeploymentAvailable},
+        models::{
+            location::Location,
+            quota::{QuotaExceeded, QuotaUsage},
+            server::{
+                GetServer, GetServerActivityLogger, Server, ServerCloneOptions, ServerPlacement,
+            },
+            server_backup::ServerBackup,
+            user::{GetPermissionManager, GetUser},
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        /// Defaults to the location of the server.
+        location_uuid: Option<uuid::Uuid>,
+
+        #[validate(length(min = 3, max = 255))]
+        #[schema(min_length = 3, max_length = 255)]
+        name: String,
+
+        #[serde(default)]
+        subusers: bool,
+        #[serde(default)]
+        databases: bool,
+        backup_uuid: Option<uuid::Uuid>,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        server: shared::models::server::ApiServer,
+        backup_uuid: uuid::Uuid,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = FORBIDDEN, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+        (status = EXPECTATION_FAILED, body = ApiError),
+    ), params(
+        (
+            "server" = uuid::Uuid,
+            description = "The server ID",
+            example = "123e4567-e89b-12d3-a456-426614174000",
+        ),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+        server: GetServer,
+        activity_logger: GetServerActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        if !user.admin && server.owner.uuid != user.uuid {
+            return ApiResponse::error("only the owner of the server can clone it")
+                .with_status(StatusCode::FORBIDDEN)
+                .ok();
+        }
+
+        permissions.has_user_permission("servers.create")?;
+
+        let limits = server.limits();
+        let feature_limits = server.feature_limits();
+
+        let quotas = (!user.admin).then(|| user.effective_quotas());
+        if let Some(quotas) = quotas {
+            if quotas.servers.is_none() || quotas.memory.is_none() || quotas.disk.is_none() {
+                return ApiResponse::error("server creation is not enabled for this account")
+                    .with_status(StatusCode::FORBIDDEN)
+                    .ok();
+            }
+
+            let usage = QuotaUsage::by_user_uuid(&state.database, user.uuid).await?;
+            if let Err(err) = quotas.check_server(&usage, &limits, &feature_limits) {
+                return ApiResponse::error(&err.to_string())
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+        }
+
+        let location_uuid = match data.location_uuid {
+            Some(location_uuid) => match Location::by_uuid(&state.database, location_uuid).await? {
+                Some(location) => location.uuid,
+                None => {
+                    return ApiResponse::error("location not found")
+                        .with_status(StatusCode::NOT_FOUND)
+                        .ok();
+                }
+            },
+            None => server.node.location.uuid,
+        };
+
+        // without a backup to clone from, a fresh one is created like through the backups route
+        if data.backup_uuid.is_none()
+            && ServerBackup::count_by_server_uuid(&state.database, server.uuid).await
+                >= server.backup_limit as i64
+        {
+            return ApiResponse::error("maximum number of backups reached")
+                .with_status(StatusCode::EXPECTATION_FAILED)
+                .ok();
+        }
+
+        let disk = match data.backup_uuid {
+            Some(backup_uuid) => {
+                match ServerBackup::by_server_uuid_uuid(&state.database, server.uuid, backup_uuid)
+                    .await?
+                {
+                    Some(backup) if backup.completed.is_some() && backup.successful => backup.disk,
+                    Some(_) => {
+                        return ApiResponse::error("backup has not been completed successfully")
+                            .with_status(StatusCode::EXPECTATION_FAILED)
+                            .ok();
+                    }
+                    None => {
+                        return ApiResponse::error("backup not found")
+                            .with_status(StatusCode::NOT_FOUND)
+                            .ok();
+                    }
+                }
+            }
+            None => match server.backup_configuration() {
+                Some(backup_configuration) => backup_configuration.backup_disk,
+                None => {
+                    return ApiResponse::error("server does not have a backup configuration")
+                        .with_status(StatusCode::EXPECTATION_FAILED)
+                        .ok();
+                }
+            },
+        };
+
+        if !disk.is_portable() {
+            return ApiResponse::error(
+                "backups of this server are stored on its node and cannot be restored elsewhere",
+            )
+            .with_status(StatusCode::EXPECTATION_FAILED)
+            .ok();
+        }
+
+        let deploy = DeploymentRequest {
+            location_uuids: vec![location_uuid],
+            port_ranges: Vec::new(),
+            strategy: DeploymentStrategy::LeastMemoryUsed,
+            additional_allocations: 0,
+        };
+
+        let (clone_uuid, backup_uuid) = match server
+            .create_clone(
+                &state.database,
+                ServerPlacement::Deploy(&deploy),
+                &ServerCloneOptions {
+                    owner_uuid: user.uuid,
+                    name: &data.name,
+                    subusers: data.subusers,
+                    databases: data.databases,
+                    backup_uuid: data.backup_uuid,
+                    quotas,
+                },
+            )
+            .await
+        {
+            Ok(uuids) => uuids,
+            Err(err) if err.is::<QuotaExceeded>() => {
+                return ApiResponse::error(&err.to_string())
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+            Err(err) if err.is::<NoDeploymentAvailable>() => {
+                return ApiResponse::error("no node in this location has enough free resources")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Err(err) => {
+                tracing::error!(server = %server.uuid, "failed to clone server: {:#?}", err);
+
+                return ApiResponse::error("failed to clone server")
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
+        };
+
+        let clone = Server::by_uuid(&state.database, clone_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("server not found after cloning"))?;
+
+        activity_logger
+            .log(
+                "server:clone",
+                serde_json::json!({
+                    "clone_uuid": clone.uuid,
+                    "name": clone.name,
+                    "location_uuid": location_uuid,
+                    "node_uuid": clone.node.uuid,
+                    "backup_uuid": backup_uuid,
+                    "subusers": data.subusers,
+                    "databases": data.databases,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {
+            server: clone.into_api_object(&user),
+            backup_uuid,
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(post::route))
+        .with_state(state.clone())
+}
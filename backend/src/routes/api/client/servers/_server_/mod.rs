@@ -19,6 +19,7 @@ use utoipa_axum::{router::OpenApiRouter, routes};
 mod activity;
 mod allocations;
 mod backups;
+mod clone;
 mod command;
 mod databases;
 mod files;
@@ -152,6 +153,7 @@ pub fn router(state: &State) -> OpenApiRouter<State> {
         .nest("/databases", databases::router(state))
         .nest("/mounts", mounts::router(state))
         .nest("/schedules", schedules::router(state))
+        .nest("/clone", clone::router(state))
         .route_layer(axum::middleware::from_fn_with_state(state.clone(), auth))
         .with_state(state.clone())
 }
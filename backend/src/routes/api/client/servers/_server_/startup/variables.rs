@@ -112,7 +112,7 @@ mod put {
         )
         .await?;
 
-        let variable_uuid = if let Some(variable) = variables
+        let target = if let Some(variable) = variables
             .iter()
             .find(|variable| variable.variable.env_variable == data.env_variable)
         {
@@ -122,7 +122,17 @@ mod put {
                     .ok();
             }
 
-            variable.variable.uuid
+            if let Err(error) = variable
+                .variable
+                .kind
+                .validate(&data.value, &variable.variable.options)
+            {
+                return ApiResponse::error(error)
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+
+            variable.variable.clone()
         } else {
             return ApiResponse::error("variable not found")
                 .with_status(StatusCode::NOT_FOUND)
@@ -133,16 +143,15 @@ mod put {
         validator_variables.reserve(variables.len());
 
         for variable in variables {
+            let value = if variable.variable.env_variable == data.env_variable {
+                data.value.clone()
+            } else {
+                variable.decrypted_value(&state.database)?
+            };
+
             validator_variables.insert(
                 variable.variable.env_variable.clone(),
-                (
-                    variable.variable.rules,
-                    if variable.variable.env_variable == data.env_variable {
-                        data.value.clone()
-                    } else {
-                        variable.value
-                    },
-                ),
+                (variable.variable.rules, value),
             );
         }
 
@@ -161,14 +170,14 @@ mod put {
                 .ok();
         }
 
-        ServerVariable::create(&state.database, server.uuid, variable_uuid, &data.value).await?;
+        ServerVariable::set(&state.database, server.uuid, &target, &data.value).await?;
 
         activity_logger
             .log(
                 "server:startup.variable",
                 serde_json::json!({
                     "env_variable": data.env_variable,
-                    "value": data.value,
+                    "value": (!target.is_secret()).then_some(data.value),
                 }),
             )
             .await;
@@ -13,7 +13,6 @@ mod delete {
         },
         response::{ApiResponse, ApiResponseResult},
     };
-    use std::sync::Arc;
     use utoipa::ToSchema;
 
     #[derive(ToSchema, Serialize)]
@@ -68,45 +67,14 @@ mod delete {
             )
             .await;
 
-        tokio::spawn({
-            let database = Arc::clone(&state.database);
-
-            async move {
-                tracing::debug!(server = %server.uuid, "removing subuser permissions in wings");
-
-                if let Err(err) = server
-                    .node
-                    .api_client(&database)
-                    .post_servers_server_ws_permissions(
-                        server.uuid,
-                        &wings_api::servers_server_ws_permissions::post::RequestBody {
-                            user_permissions: vec![wings_api::servers_server_ws_permissions::post::RequestBodyUserPermissions {
-                                user: subuser.user.uuid,
-                                permissions: Vec::new(),
-                                ignored_files: Vec::new(),
-                            }]
-                        }
-                    )
-                    .await
-                {
-                    tracing::error!(server = %server.uuid, "failed to remove subuser permissions in wings: {:#?}", err);
-                }
-
-                if let Err(err) = server
-                    .node
-                    .api_client(&database)
-                    .post_servers_server_ws_deny(
-                        server.uuid,
-                        &wings_api::servers_server_ws_deny::post::RequestBody {
-                            jtis: vec![subuser.user.uuid.to_string()],
-                        },
-                    )
-                    .await
-                {
-                    tracing::error!(server = %server.uuid, "failed to remove subuser permissions in wings: {:#?}", err);
-                }
-            }
-        });
+        shared::jobs::enqueue(
+            &state.database,
+            &shared::jobs::server::SyncSubuserPermissions {
+                server_uuid: server.uuid,
+                user_uuid: subuser.user.uuid,
+            },
+        )
+        .await?;
 
         ApiResponse::json(Response {}).ok()
     }
@@ -124,7 +92,6 @@ mod patch {
         },
         response::{ApiResponse, ApiResponseResult},
     };
-    use std::sync::Arc;
     use utoipa::ToSchema;
     use validator::Validate;
 
@@ -231,31 +198,14 @@ mod patch {
             )
             .await;
 
-        tokio::spawn({
-            let database = Arc::clone(&state.database);
-
-            async move {
-                tracing::debug!(server = %server.uuid, "updating subuser permissions in wings");
-
-                if let Err(err) = server
-                    .node
-                    .api_client(&database)
-                    .post_servers_server_ws_permissions(
-                        server.uuid,
-                        &wings_api::servers_server_ws_permissions::post::RequestBody {
-                            user_permissions: vec![wings_api::servers_server_ws_permissions::post::RequestBodyUserPermissions {
-                                user: subuser.user.uuid,
-                                permissions: server.wings_permissions(&subuser.user).into_iter().map(String::from).collect(),
-                                ignored_files: subuser.ignored_files,
-                            }]
-                        }
-                    )
-                    .await
-                {
-                    tracing::error!(server = %server.uuid, "failed to update subuser permissions in wings: {:#?}", err);
-                }
-            }
-        });
+        shared::jobs::enqueue(
+            &state.database,
+            &shared::jobs::server::SyncSubuserPermissions {
+                server_uuid: server.uuid,
+                user_uuid: subuser.user.uuid,
+            },
+        )
+        .await?;
 
         ApiResponse::json(Response {}).ok()
     }
@@ -0,0 +1,43 @@
+use super::State;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+mod get {
+    use serde::Serialize;
+    use shared::{
+        GetState,
+        models::{location::Location, nest_egg::NestEgg},
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        eggs: Vec<shared::models::nest_egg::ApiNestEgg>,
+        locations: Vec<shared::models::location::ApiLocation>,
+    }
+
+    #[utoipa::path(get, path = "/", responses(
+        (status = OK, body = inline(Response)),
+    ))]
+    pub async fn route(state: GetState) -> ApiResponseResult {
+        let (eggs, locations) = tokio::try_join!(
+            NestEgg::deployable(&state.database),
+            Location::deployable(&state.database),
+        )?;
+
+        ApiResponse::json(Response {
+            eggs: eggs.into_iter().map(|egg| egg.into_api_object()).collect(),
+            locations: locations
+                .into_iter()
+                .map(|location| location.into_api_object())
+                .collect(),
+        })
+        .ok()
+    }
+}
+
+pub fn router(state: &State) -> OpenApiRouter<State> {
+    OpenApiRouter::new()
+        .routes(routes!(get::route))
+        .with_state(state.clone())
+}
@@ -2,6 +2,7 @@ use super::State;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
 pub mod _server_;
+mod deployable;
 
 mod get {
     use axum::{extract::Query, http::StatusCode};
@@ -109,9 +110,234 @@ mod get {
     }
 }
 
+mod post {
+    use axum::http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use shared::{
+        ApiError, GetState,
+        deployment::{DeploymentRequest, DeploymentStrategy, NoDeploymentAvailable},
+        models::{
+            location::Location,
+            nest_egg::NestEgg,
+            quota::{QuotaExceeded, QuotaUsage},
+            server::{ApiServerFeatureLimits, ApiServerLimits, Server, ServerPlacement},
+            user::{GetPermissionManager, GetUser},
+            user_activity::GetUserActivityLogger,
+        },
+        response::{ApiResponse, ApiResponseResult},
+    };
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(ToSchema, Validate, Deserialize)]
+    pub struct Payload {
+        egg_uuid: uuid::Uuid,
+        location_uuid: uuid::Uuid,
+
+        #[validate(length(min = 3, max = 255))]
+        #[schema(min_length = 3, max_length = 255)]
+        name: String,
+        #[validate(length(max = 1024))]
+        #[schema(max_length = 1024)]
+        description: Option<String>,
+        #[validate(length(min = 2, max = 255))]
+        #[schema(min_length = 2, max_length = 255)]
+        image: Option<String>,
+
+        #[validate(range(min = 1))]
+        #[schema(minimum = 1)]
+        memory: i64,
+        #[validate(range(min = 1))]
+        #[schema(minimum = 1)]
+        disk: i64,
+        #[validate(range(min = 0))]
+        #[schema(minimum = 0)]
+        #[serde(default)]
+        cpu: i32,
+
+        #[validate(range(min = 0))]
+        #[schema(minimum = 0)]
+        #[serde(default)]
+        databases: i32,
+        #[validate(range(min = 0))]
+        #[schema(minimum = 0)]
+        #[serde(default)]
+        backups: i32,
+    }
+
+    #[derive(ToSchema, Serialize)]
+    struct Response {
+        server: shared::models::server::ApiServer,
+    }
+
+    #[utoipa::path(post, path = "/", responses(
+        (status = OK, body = inline(Response)),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = FORBIDDEN, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = CONFLICT, body = ApiError),
+    ), request_body = inline(Payload))]
+    pub async fn route(
+        state: GetState,
+        permissions: GetPermissionManager,
+        user: GetUser,
+        activity_logger: GetUserActivityLogger,
+        axum::Json(data): axum::Json<Payload>,
+    ) -> ApiResponseResult {
+        if let Err(errors) = shared::utils::validate_data(&data) {
+            return ApiResponse::json(ApiError::new_strings_value(errors))
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        permissions.has_user_permission("servers.create")?;
+
+        // without server, memory and disk quotas there is nothing bounding self-service creation
+        let quotas = user.effective_quotas();
+        if quotas.servers.is_none() || quotas.memory.is_none() || quotas.disk.is_none() {
+            return ApiResponse::error("server creation is not enabled for this account")
+                .with_status(StatusCode::FORBIDDEN)
+                .ok();
+        }
+
+        let egg = match NestEgg::by_uuid(&state.database, data.egg_uuid).await? {
+            Some(egg) if egg.deployable => egg,
+            _ => {
+                return ApiResponse::error("egg not found")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+        };
+
+        let image = match data.image {
+            Some(image) if egg.docker_images.values().any(|i| *i == image) => image,
+            Some(_) => {
+                return ApiResponse::error("image is not available for this egg")
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+            None => match egg.docker_images.values().next() {
+                Some(image) => image.clone(),
+                None => {
+                    return ApiResponse::error("egg has no docker images")
+                        .with_status(StatusCode::BAD_REQUEST)
+                        .ok();
+                }
+            },
+        };
+
+        let location = match Location::by_uuid(&state.database, data.location_uuid).await? {
+            Some(location) => location,
+            None => {
+                return ApiResponse::error("location not found")
+                    .with_status(StatusCode::NOT_FOUND)
+                    .ok();
+            }
+        };
+
+        let limits = ApiServerLimits {
+            cpu: data.cpu,
+            memory: data.memory,
+            swap: 0,
+            disk: data.disk,
+            io_weight: None,
+        };
+        let feature_limits = ApiServerFeatureLimits {
+            allocations: 1,
+            databases: data.databases,
+            backups: data.backups,
+            schedules: 0,
+        };
+
+        let usage = QuotaUsage::by_user_uuid(&state.database, user.uuid).await?;
+        if let Err(err) = quotas.check_server(&usage, &limits, &feature_limits) {
+            return ApiResponse::error(&err.to_string())
+                .with_status(StatusCode::BAD_REQUEST)
+                .ok();
+        }
+
+        let deploy = DeploymentRequest {
+            location_uuids: vec![location.uuid],
+            port_ranges: Vec::new(),
+            strategy: DeploymentStrategy::LeastMemoryUsed,
+            additional_allocations: 0,
+        };
+
+        let server_uuid = match Server::create(
+            &state.database,
+            ServerPlacement::Deploy(&deploy),
+            user.uuid,
+            Some(&quotas),
+            egg.uuid,
+            None,
+            None,
+            None,
+            true,
+            false,
+            &data.name,
+            data.description.as_deref(),
+            &limits,
+            &[],
+            &egg.startup,
+            &image,
+            None,
+            &feature_limits,
+            &Default::default(),
+            &[],
+        )
+        .await
+        {
+            Ok(server_uuid) => server_uuid,
+            Err(err) if err.is::<QuotaExceeded>() => {
+                return ApiResponse::error(&err.to_string())
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+            Err(err) if err.is::<NoDeploymentAvailable>() => {
+                return ApiResponse::error("no node in this location has enough free resources")
+                    .with_status(StatusCode::CONFLICT)
+                    .ok();
+            }
+            Err(err) => {
+                tracing::error!("failed to create server: {:#?}", err);
+
+                return ApiResponse::error("failed to create server")
+                    .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .ok();
+            }
+        };
+
+        let server = Server::by_uuid(&state.database, server_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("server not found after creation"))?;
+
+        activity_logger
+            .log(
+                "user:server.create",
+                serde_json::json!({
+                    "uuid": server.uuid,
+                    "name": server.name,
+                    "egg_uuid": egg.uuid,
+                    "location_uuid": location.uuid,
+                    "node_uuid": server.node.uuid,
+                    "limits": limits,
+                    "feature_limits": feature_limits,
+                }),
+            )
+            .await;
+
+        ApiResponse::json(Response {
+            server: server.into_api_object(&user),
+        })
+        .ok()
+    }
+}
+
 pub fn router(state: &State) -> OpenApiRouter<State> {
     OpenApiRouter::new()
         .routes(routes!(get::route))
+        .routes(routes!(post::route))
+        .nest("/deployable", deployable::router(state))
         .nest("/{server}", _server_::router(state))
         .with_state(state.clone())
 }
@@ -24,7 +24,8 @@ pub async fn auth(
     mut req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    let backup = ServerBackup::by_node_uuid_uuid(&state.database, node.uuid, backup).await;
+    let backup =
+        ServerBackup::by_node_uuid_uuid_or_restoring(&state.database, node.uuid, backup).await;
     let backup = match backup {
         Ok(Some(backup)) => backup,
         Ok(None) => {
@@ -70,6 +71,7 @@ mod get {
 
     #[utoipa::path(get, path = "/", responses(
         (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
         (status = EXPECTATION_FAILED, body = ApiError),
     ), params(
         (
@@ -89,6 +91,13 @@ mod get {
         backup: GetBackup,
         Query(params): Query<Params>,
     ) -> ApiResponseResult {
+        // backups of other nodes are only visible for restoring them
+        if backup.node_uuid != node.uuid {
+            return ApiResponse::error("backup not found")
+                .with_status(StatusCode::NOT_FOUND)
+                .ok();
+        }
+
         if backup.disk != BackupDisk::S3 {
             return ApiResponse::error("backup is not stored on S3")
                 .with_status(StatusCode::EXPECTATION_FAILED)
@@ -221,14 +230,17 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
+        mail::template::MailTemplateKind,
         models::{
             node::GetNode,
             server::Server,
             server_activity::ServerActivity,
-            server_backup::{BackupDisk, ServerBackup},
+            server_backup::{BackupDisk, BackupVerificationStatus, ServerBackup},
+            user_notification::{Notification, NotificationCategory, UserNotification},
         },
         response::{ApiResponse, ApiResponseResult},
     };
+    use std::collections::BTreeMap;
     use utoipa::ToSchema;
 
     #[derive(Debug, ToSchema, Deserialize)]
@@ -253,6 +265,7 @@ mod post {
 
     #[utoipa::path(post, path = "/", responses(
         (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
         (status = EXPECTATION_FAILED, body = ApiError),
     ), params(
         (
@@ -267,6 +280,14 @@ mod post {
         backup: GetBackup,
         axum::Json(mut data): axum::Json<Payload>,
     ) -> ApiResponseResult {
+        if backup.node_uuid != node.uuid {
+            return ApiResponse::error("backup not found")
+                .with_status(StatusCode::NOT_FOUND)
+                .ok();
+        }
+
+        let verifiable = backup.0.is_verifiable();
+
         if backup.disk == BackupDisk::S3 {
             let upload_id = match backup.0.upload_id {
                 Some(id) => id,
@@ -440,6 +461,76 @@ mod post {
             );
         }
 
+        if let Some(server_uuid) = backup.0.server_uuid
+            && let Some(server) = Server::by_uuid(&state.database, server_uuid).await?
+        {
+            UserNotification::notify(
+                &state.database,
+                server.owner.uuid,
+                if data.successful {
+                    Notification {
+                        category: NotificationCategory::Backup,
+                        event: "server:backup.complete",
+                        server_uuid: Some(server.uuid),
+                        title: "Backup completed".into(),
+                        body: format!(
+                            "The backup {} of {} has completed.",
+                            backup.0.name, server.name
+                        ),
+                        data: serde_json::json!({ "backup_uuid": backup.0.uuid }),
+                        mail: None,
+                    }
+                } else {
+                    Notification {
+                        category: NotificationCategory::Backup,
+                        event: "server:backup.fail",
+                        server_uuid: Some(server.uuid),
+                        title: "Backup failed".into(),
+                        body: format!(
+                            "The backup {} of {} has failed.",
+                            backup.0.name, server.name
+                        ),
+                        data: serde_json::json!({ "backup_uuid": backup.0.uuid }),
+                        mail: Some((
+                            MailTemplateKind::BackupFailed,
+                            BTreeMap::from([("backup_name", backup.0.name.clone())]),
+                        )),
+                    }
+                },
+            )
+            .await;
+        }
+
+        ServerBackup::resume_pending_restores(&state.database, backup.0.uuid, data.successful)
+            .await?;
+
+        if data.successful
+            && verifiable
+            && let Some(server_uuid) = backup.0.server_uuid
+            && state
+                .settings
+                .get()
+                .await
+                .server
+                .verify_backups_automatically
+        {
+            ServerBackup::update_verification(
+                &state.database,
+                backup.0.uuid,
+                BackupVerificationStatus::Pending,
+                None,
+            )
+            .await?;
+            shared::jobs::enqueue(
+                &state.database,
+                &shared::jobs::server::VerifyServerBackup {
+                    server_uuid,
+                    backup_uuid: backup.0.uuid,
+                },
+            )
+            .await?;
+        }
+
         ApiResponse::json(Response {}).ok()
     }
 }
@@ -7,7 +7,7 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
-        models::server_activity::ServerActivity,
+        models::{node::GetNode, server_activity::ServerActivity},
         response::{ApiResponse, ApiResponseResult},
     };
     use utoipa::ToSchema;
@@ -22,6 +22,7 @@ mod post {
 
     #[utoipa::path(post, path = "/", responses(
         (status = OK, body = inline(Response)),
+        (status = NOT_FOUND, body = ApiError),
         (status = EXPECTATION_FAILED, body = ApiError),
     ), params(
         (
@@ -32,34 +33,59 @@ mod post {
     ), request_body = inline(Payload))]
     pub async fn route(
         state: GetState,
+        node: GetNode,
         backup: GetBackup,
         axum::Json(data): axum::Json<Payload>,
     ) -> ApiResponseResult {
-        let server_uuid = match backup.server_uuid {
+        // the restoring server is tracked with the backup, a unique index keeps it to one
+        // server per node, restores started before that fall back to the backup's server
+        let restoring_server_uuid: Option<uuid::Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE servers
+            SET status = NULL, restoring_backup_uuid = NULL
+            WHERE servers.node_uuid = $1 AND servers.restoring_backup_uuid = $2 AND servers.status = 'RESTORING_BACKUP'
+            RETURNING servers.uuid
+            "#,
+        )
+        .bind(node.uuid)
+        .bind(backup.uuid)
+        .fetch_optional(state.database.write())
+        .await?;
+
+        let server_uuid = match restoring_server_uuid {
             Some(id) => id,
             None => {
-                return ApiResponse::error("server uuid not found")
-                    .with_status(StatusCode::NOT_FOUND)
-                    .ok();
+                let server_uuid = match backup.server_uuid {
+                    Some(id) if backup.node_uuid == node.uuid => id,
+                    _ => {
+                        return ApiResponse::error("server uuid not found")
+                            .with_status(StatusCode::NOT_FOUND)
+                            .ok();
+                    }
+                };
+
+                if sqlx::query(
+                    r#"
+                    UPDATE servers
+                    SET status = NULL
+                    WHERE servers.uuid = $1 AND servers.status = 'RESTORING_BACKUP'
+                    "#,
+                )
+                .bind(server_uuid)
+                .execute(state.database.write())
+                .await?
+                .rows_affected()
+                    == 0
+                {
+                    return ApiResponse::error("server is not restoring a backup")
+                        .with_status(StatusCode::EXPECTATION_FAILED)
+                        .ok();
+                }
+
+                server_uuid
             }
         };
 
-        if sqlx::query!(
-            "UPDATE servers
-            SET status = NULL
-            WHERE servers.uuid = $1 AND servers.status = 'RESTORING_BACKUP'",
-            server_uuid
-        )
-        .execute(state.database.write())
-        .await?
-        .rows_affected()
-            == 0
-        {
-            return ApiResponse::error("server is not restoring a backup")
-                .with_status(StatusCode::EXPECTATION_FAILED)
-                .ok();
-        }
-
         if let Err(err) = ServerActivity::log(
             &state.database,
             server_uuid,
@@ -4,6 +4,7 @@ use utoipa_axum::{router::OpenApiRouter, routes};
 mod get {
     use serde::Serialize;
     use shared::{
+        GetState,
         models::server::GetServer,
         response::{ApiResponse, ApiResponseResult},
     };
@@ -25,7 +26,9 @@ mod get {
             example = "123e4567-e89b-12d3-a456-426614174000",
         ),
     ))]
-    pub async fn route(server: GetServer) -> ApiResponseResult {
+    pub async fn route(state: GetState, mut server: GetServer) -> ApiResponseResult {
+        server.apply_egg_revision(&state.database).await?;
+
         ApiResponse::json(Response {
             container_image: server.0.egg.config_script.container,
             entrypoint: server.0.egg.config_script.entrypoint,
@@ -39,7 +42,10 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         GetState,
-        models::server::{GetServer, ServerStatus},
+        models::{
+            server::{GetServer, ServerStatus},
+            server_activity::ServerActivity,
+        },
         response::{ApiResponse, ApiResponseResult},
     };
     use utoipa::ToSchema;
@@ -87,6 +93,27 @@ mod post {
         .execute(state.database.write())
         .await?;
 
+        if !data.successful
+            && let Err(err) = ServerActivity::log_remote(
+                &state.database,
+                server.uuid,
+                None,
+                "server:install.fail",
+                None,
+                serde_json::json!({
+                    "reinstall": data.reinstall,
+                }),
+                chrono::Utc::now(),
+            )
+            .await
+        {
+            tracing::warn!(
+                server = %server.uuid,
+                "failed to log server activity: {:#?}",
+                err
+            );
+        }
+
         ApiResponse::json(Response {}).ok()
     }
 }
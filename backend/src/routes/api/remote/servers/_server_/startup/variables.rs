@@ -57,7 +57,7 @@ mod put {
         )
         .await?;
 
-        let variable_uuid = if let Some(variable) = variables
+        let target = if let Some(variable) = variables
             .iter()
             .find(|variable| variable.variable.env_variable == data.env_variable)
         {
@@ -67,7 +67,17 @@ mod put {
                     .ok();
             }
 
-            variable.variable.uuid
+            if let Err(error) = variable
+                .variable
+                .kind
+                .validate(&data.value, &variable.variable.options)
+            {
+                return ApiResponse::error(error)
+                    .with_status(StatusCode::BAD_REQUEST)
+                    .ok();
+            }
+
+            variable.variable.clone()
         } else {
             return ApiResponse::error("variable not found")
                 .with_status(StatusCode::NOT_FOUND)
@@ -78,16 +88,15 @@ mod put {
         validator_variables.reserve(variables.len());
 
         for variable in variables {
+            let value = if variable.variable.env_variable == data.env_variable {
+                data.value.clone()
+            } else {
+                variable.decrypted_value(&state.database)?
+            };
+
             validator_variables.insert(
                 variable.variable.env_variable.clone(),
-                (
-                    variable.variable.rules,
-                    if variable.variable.env_variable == data.env_variable {
-                        data.value.clone()
-                    } else {
-                        variable.value
-                    },
-                ),
+                (variable.variable.rules, value),
             );
         }
 
@@ -106,7 +115,7 @@ mod put {
                 .ok();
         }
 
-        ServerVariable::create(&state.database, server.uuid, variable_uuid, &data.value).await?;
+        ServerVariable::set(&state.database, server.uuid, &target, &data.value).await?;
 
         if let Err(err) = ServerActivity::log_remote(
             &state.database,
@@ -116,7 +125,7 @@ mod put {
             None,
             serde_json::json!({
                 "env_variable": data.env_variable,
-                "value": data.value,
+                "value": (!target.is_secret()).then_some(data.value),
             }),
             chrono::Utc::now(),
         )
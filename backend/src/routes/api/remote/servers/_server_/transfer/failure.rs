@@ -6,9 +6,15 @@ mod post {
     use serde::Serialize;
     use shared::{
         ApiError, GetState,
-        models::{node::GetNode, server::GetServer},
+        mail::template::MailTemplateKind,
+        models::{
+            node::GetNode,
+            server::GetServer,
+            user_notification::{Notification, NotificationCategory, UserNotification},
+        },
         response::{ApiResponse, ApiResponseResult},
     };
+    use std::collections::BTreeMap;
     use utoipa::ToSchema;
 
     #[derive(ToSchema, Serialize)]
@@ -38,39 +44,7 @@ mod post {
                 .ok();
         }
 
-        let mut transaction = state.database.write().begin().await?;
-
-        let (allocations, _) = tokio::try_join!(
-            sqlx::query!(
-                r#"
-                SELECT server_allocations.uuid FROM server_allocations
-                JOIN node_allocations ON node_allocations.uuid = server_allocations.allocation_uuid
-                WHERE server_allocations.server_uuid = $1 AND node_allocations.node_uuid != $2
-                "#,
-                server.uuid,
-                server.node.uuid
-            )
-            .fetch_all(state.database.read()),
-            sqlx::query!(
-                r#"
-                UPDATE servers
-                SET destination_allocation_uuid = NULL, destination_node_uuid = NULL
-                WHERE servers.uuid = $1
-                "#,
-                server.uuid
-            )
-            .execute(&mut *transaction)
-        )?;
-
-        sqlx::query!(
-            r#"
-            DELETE FROM server_allocations
-            WHERE server_allocations.uuid = ANY($1)
-            "#,
-            &allocations.into_iter().map(|a| a.uuid).collect::<Vec<_>>()
-        )
-        .execute(&mut *transaction)
-        .await?;
+        server.cancel_transfer(&state.database).await?;
 
         if let Ok(Some(destination_node)) = server.destination_node(&state.database).await
             && let Err(err) = destination_node
@@ -81,7 +55,23 @@ mod post {
             tracing::error!("failed to delete server on destination node: {:#?}", err);
         }
 
-        transaction.commit().await?;
+        UserNotification::notify(
+            &state.database,
+            server.owner.uuid,
+            Notification {
+                category: NotificationCategory::Transfer,
+                event: "server:transfer.fail",
+                server_uuid: Some(server.uuid),
+                title: "Server transfer failed".into(),
+                body: format!(
+                    "The transfer of your server {} to a new node has failed.",
+                    server.name
+                ),
+                data: serde_json::json!({}),
+                mail: Some((MailTemplateKind::TransferFailed, BTreeMap::new())),
+            },
+        )
+        .await;
 
         ApiResponse::json(Response {}).ok()
     }
@@ -6,9 +6,15 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
-        models::{node::GetNode, server::GetServer},
+        mail::template::MailTemplateKind,
+        models::{
+            node::GetNode,
+            server::GetServer,
+            user_notification::{Notification, NotificationCategory, UserNotification},
+        },
         response::{ApiResponse, ApiResponseResult},
     };
+    use std::collections::BTreeMap;
     use utoipa::ToSchema;
 
     #[derive(ToSchema, Deserialize)]
@@ -110,6 +116,24 @@ mod post {
             tracing::error!("failed to delete server on source node: {:#?}", err);
         }
 
+        UserNotification::notify(
+            &state.database,
+            server.owner.uuid,
+            Notification {
+                category: NotificationCategory::Transfer,
+                event: "server:transfer.complete",
+                server_uuid: Some(server.uuid),
+                title: "Server transfer completed".into(),
+                body: format!(
+                    "Your server {} has been transferred to a new node.",
+                    server.name
+                ),
+                data: serde_json::json!({}),
+                mail: Some((MailTemplateKind::TransferCompleted, BTreeMap::new())),
+            },
+        )
+        .await;
+
         ApiResponse::json(Response {}).ok()
     }
 }
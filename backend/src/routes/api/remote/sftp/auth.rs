@@ -6,7 +6,7 @@ mod post {
     use serde::{Deserialize, Serialize};
     use shared::{
         ApiError, GetState,
-        models::{server::Server, user::User},
+        models::{server::Server, user::User, user_login_failure::UserLoginFailure},
         response::{ApiResponse, ApiResponseResult},
     };
     use utoipa::ToSchema;
@@ -23,6 +23,11 @@ mod post {
         r#type: AuthenticationType,
         username: String,
         password: String,
+
+        /// The address of the SFTP client, as seen by wings.
+        #[serde(default)]
+        #[schema(value_type = Option<String>)]
+        ip: Option<std::net::IpAddr>,
     }
 
     #[derive(ToSchema, Serialize)]
@@ -37,6 +42,7 @@ mod post {
     #[utoipa::path(post, path = "/", responses(
         (status = OK, body = inline(Response)),
         (status = EXPECTATION_FAILED, body = ApiError),
+        (status = TOO_MANY_REQUESTS, body = ApiError),
     ), request_body = inline(Payload))]
     pub async fn route(
         state: GetState,
@@ -62,9 +68,50 @@ mod post {
 
         let user = match data.r#type {
             AuthenticationType::Password => {
-                match User::by_username_password(&state.database, user, &data.password).await? {
+                let user_uuid = User::uuid_by_username_or_email(&state.database, user).await?;
+                let throttle = match user_uuid {
+                    Some(user_uuid) => {
+                        UserLoginFailure::check(&state.database, &state.settings, user_uuid).await?
+                    }
+                    None => {
+                        UserLoginFailure::check_unknown(&state.cache, &state.settings, user).await?
+                    }
+                };
+                if let Some(throttle) = throttle {
+                    return throttle.into_response().ok();
+                }
+
+                match User::by_username_password(
+                    &state.database,
+                    &state.settings,
+                    user,
+                    &data.password,
+                )
+                .await?
+                {
                     Some(user) => user,
                     None => {
+                        match user_uuid {
+                            Some(user_uuid) => {
+                                UserLoginFailure::record(
+                                    &state.database,
+                                    &state.settings,
+                                    user_uuid,
+                                    data.ip,
+                                    serde_json::json!({ "using": "sftp" }),
+                                )
+                                .await?
+                            }
+                            None => {
+                                UserLoginFailure::record_unknown(
+                                    &state.cache,
+                                    &state.settings,
+                                    user,
+                                )
+                                .await?
+                            }
+                        }
+
                         return ApiResponse::error("user not found")
                             .with_status(StatusCode::EXPECTATION_FAILED)
                             .ok();
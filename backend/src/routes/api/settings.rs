@@ -24,6 +24,12 @@ mod get {
         allow_editing_startup_command: bool,
     }
 
+    #[derive(ToSchema, Serialize)]
+    struct ResponseOidc<'a> {
+        enabled: bool,
+        name: &'a str,
+    }
+
     #[derive(ToSchema, Serialize)]
     struct Response<'a> {
         version: &'a str,
@@ -34,6 +40,8 @@ mod get {
         app: ResponseApp<'a>,
         #[schema(inline)]
         server: ResponseServer,
+        #[schema(inline)]
+        oidc: ResponseOidc<'a>,
     }
 
     #[utoipa::path(get, path = "/", responses(
@@ -58,6 +66,10 @@ mod get {
                     .allow_overwriting_custom_docker_image,
                 allow_editing_startup_command: settings.server.allow_editing_startup_command,
             },
+            oidc: ResponseOidc {
+                enabled: settings.oidc.enabled,
+                name: &settings.oidc.name,
+            },
         })
         .ok()
     }
@@ -0,0 +1,61 @@
+use axum::{
+    body::Body,
+    extract::{MatchedPath, Request},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use shared::{
+    GetState,
+    response::{ApiResponse, ApiResponseResult},
+};
+use std::time::Instant;
+
+/// Records the method, matched path, status and duration of every routed request.
+pub async fn handle(
+    matched_path: Option<MatchedPath>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let method = req.method().clone();
+    let start = Instant::now();
+
+    let response = next.run(req).await;
+
+    if let Some(matched_path) = matched_path {
+        shared::metrics::METRICS.observe_http_request(
+            method.as_str(),
+            matched_path.as_str(),
+            response.status().as_u16(),
+            start.elapsed(),
+        );
+    }
+
+    Ok(response)
+}
+
+/// Serves the metrics in the Prometheus text format, only if `METRICS_TOKEN` is set.
+pub async fn route(state: GetState, headers: HeaderMap) -> ApiResponseResult {
+    if state.env.metrics_token.is_none() {
+        return ApiResponse::error("route not found")
+            .with_status(StatusCode::NOT_FOUND)
+            .ok();
+    }
+
+    if !shared::metrics::Metrics::is_authorized(
+        &state.env,
+        headers
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok()),
+    ) {
+        return ApiResponse::error("unauthorized")
+            .with_status(StatusCode::UNAUTHORIZED)
+            .ok();
+    }
+
+    let metrics = shared::metrics::METRICS.render(&state).await?;
+
+    ApiResponse::new(Body::from(metrics))
+        .with_header("Content-Type", "text/plain; version=0.0.4")
+        .ok()
+}
@@ -1,6 +1,7 @@
 use utoipa_axum::router::OpenApiRouter;
 
 pub mod api;
+pub mod metrics;
 pub use shared::{GetState, State};
 
 pub fn router(state: &State) -> OpenApiRouter<State> {
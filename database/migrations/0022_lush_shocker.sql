@@ -0,0 +1,30 @@
+CREATE TABLE "webhook_deliveries" (
+	"uuid" uuid PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+	"webhook_uuid" uuid NOT NULL,
+	"event" varchar(255) NOT NULL,
+	"payload" jsonb NOT NULL,
+	"attempts" integer DEFAULT 0 NOT NULL,
+	"successful" boolean DEFAULT false NOT NULL,
+	"response_status" integer,
+	"response_body" text,
+	"error" text,
+	"next_attempt" timestamp DEFAULT now(),
+	"last_attempt" timestamp,
+	"created" timestamp DEFAULT now() NOT NULL
+);
+
+CREATE TABLE "webhooks" (
+	"uuid" uuid PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+	"name" varchar(1020) NOT NULL,
+	"description" text,
+	"url" text NOT NULL,
+	"secret" "bytea" NOT NULL,
+	"events" varchar(255)[] NOT NULL,
+	"enabled" boolean DEFAULT true NOT NULL,
+	"created" timestamp DEFAULT now() NOT NULL
+);
+
+ALTER TABLE "webhook_deliveries" ADD CONSTRAINT "webhook_deliveries_webhook_uuid_webhooks_uuid_fk" FOREIGN KEY ("webhook_uuid") REFERENCES "public"."webhooks"("uuid") ON DELETE cascade ON UPDATE no action;
+CREATE INDEX "webhook_deliveries_webhook_uuid_idx" ON "webhook_deliveries" USING btree ("webhook_uuid");
+CREATE INDEX "webhook_deliveries_next_attempt_idx" ON "webhook_deliveries" USING btree ("next_attempt");
+CREATE UNIQUE INDEX "webhooks_name_idx" ON "webhooks" USING btree ("name");
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE "locations" ALTER COLUMN "short_name" DROP NOT NULL;
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE TABLE "user_external_identities" (
+	"user_uuid" uuid NOT NULL,
+	"provider" varchar(255) NOT NULL,
+	"subject" varchar(255) NOT NULL,
+	"created" timestamp DEFAULT now() NOT NULL,
+	CONSTRAINT "user_external_identities_provider_subject_pk" PRIMARY KEY("provider","subject")
+);
+
+ALTER TABLE "user_external_identities" ADD CONSTRAINT "user_external_identities_user_uuid_users_uuid_fk" FOREIGN KEY ("user_uuid") REFERENCES "public"."users"("uuid") ON DELETE cascade ON UPDATE no action;
+CREATE UNIQUE INDEX "user_external_identities_user_uuid_provider_idx" ON "user_external_identities" USING btree ("user_uuid","provider");
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE "users" ADD COLUMN "quotas" jsonb DEFAULT '{}'::jsonb NOT NULL;
+ALTER TABLE "roles" ADD COLUMN "quotas" jsonb DEFAULT '{}'::jsonb NOT NULL;
\ No newline at end of file
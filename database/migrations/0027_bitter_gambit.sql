@@ -0,0 +1,28 @@
+CREATE TABLE "server_templates" (
+	"uuid" uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+	"egg_uuid" uuid NOT NULL,
+	"name" varchar(1020) NOT NULL,
+	"description" text,
+	"memory" bigint NOT NULL,
+	"swap" bigint NOT NULL,
+	"disk" bigint NOT NULL,
+	"io_weight" smallint,
+	"cpu" integer NOT NULL,
+	"pinned_cpus" smallint[] NOT NULL,
+	"startup" text NOT NULL,
+	"image" varchar(255) NOT NULL,
+	"timezone" varchar(255),
+	"allocation_limit" integer DEFAULT 0 NOT NULL,
+	"database_limit" integer DEFAULT 0 NOT NULL,
+	"backup_limit" integer DEFAULT 0 NOT NULL,
+	"schedule_limit" integer DEFAULT 0 NOT NULL,
+	"variables" jsonb DEFAULT '{}'::jsonb NOT NULL,
+	"mounts" uuid[] NOT NULL,
+	"created" timestamp DEFAULT now() NOT NULL
+);
+
+ALTER TABLE "servers" ADD COLUMN "server_template_uuid" uuid;
+ALTER TABLE "server_templates" ADD CONSTRAINT "server_templates_egg_uuid_nest_eggs_uuid_fk" FOREIGN KEY ("egg_uuid") REFERENCES "public"."nest_eggs"("uuid") ON DELETE cascade ON UPDATE no action;
+ALTER TABLE "servers" ADD CONSTRAINT "servers_server_template_uuid_server_templates_uuid_fk" FOREIGN KEY ("server_template_uuid") REFERENCES "public"."server_templates"("uuid") ON DELETE set null ON UPDATE no action;
+CREATE UNIQUE INDEX "server_templates_name_idx" ON "server_templates" USING btree ("name");
+CREATE INDEX "servers_server_template_uuid_idx" ON "servers" USING btree ("server_template_uuid");
\ No newline at end of file
@@ -0,0 +1,22 @@
+CREATE TABLE "server_bulk_jobs" (
+	"uuid" uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+	"user_uuid" uuid,
+	"action" jsonb NOT NULL,
+	"concurrency" integer NOT NULL,
+	"finished" timestamp,
+	"created" timestamp DEFAULT now() NOT NULL
+);
+
+CREATE TABLE "server_bulk_job_results" (
+	"job_uuid" uuid NOT NULL,
+	"server_uuid" uuid NOT NULL,
+	"successful" boolean,
+	"error" text,
+	"finished" timestamp,
+	CONSTRAINT "server_bulk_job_results_pk" PRIMARY KEY("job_uuid","server_uuid")
+);
+
+ALTER TABLE "server_bulk_jobs" ADD CONSTRAINT "server_bulk_jobs_user_uuid_users_uuid_fk" FOREIGN KEY ("user_uuid") REFERENCES "public"."users"("uuid") ON DELETE set null ON UPDATE no action;
+ALTER TABLE "server_bulk_job_results" ADD CONSTRAINT "server_bulk_job_results_job_uuid_server_bulk_jobs_uuid_fk" FOREIGN KEY ("job_uuid") REFERENCES "public"."server_bulk_jobs"("uuid") ON DELETE cascade ON UPDATE no action;
+ALTER TABLE "server_bulk_job_results" ADD CONSTRAINT "server_bulk_job_results_server_uuid_servers_uuid_fk" FOREIGN KEY ("server_uuid") REFERENCES "public"."servers"("uuid") ON DELETE cascade ON UPDATE no action;
+CREATE INDEX "server_bulk_jobs_created_idx" ON "server_bulk_jobs" USING btree ("created");
\ No newline at end of file
@@ -0,0 +1,16 @@
+CREATE TYPE "public"."queued_job_status" AS ENUM('PENDING', 'RUNNING', 'FAILED');
+CREATE TABLE "queued_jobs" (
+	"uuid" uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+	"kind" varchar(255) NOT NULL,
+	"payload" jsonb NOT NULL,
+	"status" "queued_job_status" DEFAULT 'PENDING' NOT NULL,
+	"attempts" integer DEFAULT 0 NOT NULL,
+	"max_attempts" integer NOT NULL,
+	"error" text,
+	"run_at" timestamp DEFAULT now() NOT NULL,
+	"locked_until" timestamp,
+	"created" timestamp DEFAULT now() NOT NULL
+);
+
+CREATE INDEX "queued_jobs_status_run_at_idx" ON "queued_jobs" USING btree ("status","run_at");
+CREATE INDEX "queued_jobs_kind_idx" ON "queued_jobs" USING btree ("kind");
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE "backup_configurations" ADD COLUMN "retention" jsonb DEFAULT '{}'::jsonb NOT NULL;
+ALTER TABLE "servers" ADD COLUMN "backup_retention" jsonb;
\ No newline at end of file
@@ -0,0 +1,4 @@
+CREATE TYPE "public"."backup_verification_status" AS ENUM('PENDING', 'SUCCESSFUL', 'FAILED');
+ALTER TABLE "server_backups" ADD COLUMN "verification_status" "backup_verification_status";
+ALTER TABLE "server_backups" ADD COLUMN "verification_error" text;
+ALTER TABLE "server_backups" ADD COLUMN "verified" timestamp;
\ No newline at end of file
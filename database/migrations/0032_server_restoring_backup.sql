@@ -0,0 +1,3 @@
+ALTER TABLE "servers" ADD COLUMN "restoring_backup_uuid" uuid;
+ALTER TABLE "servers" ADD CONSTRAINT "servers_restoring_backup_uuid_server_backups_uuid_fk" FOREIGN KEY ("restoring_backup_uuid") REFERENCES "public"."server_backups"("uuid") ON DELETE set null ON UPDATE no action;
+CREATE UNIQUE INDEX "servers_node_uuid_restoring_backup_uuid_idx" ON "servers" USING btree ("node_uuid","restoring_backup_uuid");
\ No newline at end of file
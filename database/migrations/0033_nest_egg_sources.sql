@@ -0,0 +1,21 @@
+CREATE TABLE "nest_egg_sources" (
+	"uuid" uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+	"nest_uuid" uuid NOT NULL,
+	"name" varchar(255) NOT NULL,
+	"url" text NOT NULL,
+	"sync_interval" integer,
+	"last_synced" timestamp,
+	"last_sync_report" jsonb,
+	"last_sync_error" text,
+	"created" timestamp DEFAULT now() NOT NULL
+);
+
+ALTER TABLE "nest_eggs" ADD COLUMN "source_uuid" uuid;
+ALTER TABLE "nest_eggs" ADD COLUMN "source_path" text;
+ALTER TABLE "nest_eggs" ADD COLUMN "upstream_hash" varchar(64);
+ALTER TABLE "nest_eggs" ADD COLUMN "synced_hash" varchar(64);
+ALTER TABLE "nest_egg_sources" ADD CONSTRAINT "nest_egg_sources_nest_uuid_nests_uuid_fk" FOREIGN KEY ("nest_uuid") REFERENCES "public"."nests"("uuid") ON DELETE cascade ON UPDATE no action;
+ALTER TABLE "nest_eggs" ADD CONSTRAINT "nest_eggs_source_uuid_nest_egg_sources_uuid_fk" FOREIGN KEY ("source_uuid") REFERENCES "public"."nest_egg_sources"("uuid") ON DELETE set null ON UPDATE no action;
+CREATE INDEX "nest_egg_sources_nest_uuid_idx" ON "nest_egg_sources" USING btree ("nest_uuid");
+CREATE UNIQUE INDEX "nest_egg_sources_nest_uuid_name_idx" ON "nest_egg_sources" USING btree ("nest_uuid","name");
+CREATE INDEX "nest_eggs_source_uuid_idx" ON "nest_eggs" USING btree ("source_uuid");
\ No newline at end of file
@@ -0,0 +1,15 @@
+CREATE TABLE "nest_egg_revisions" (
+	"uuid" uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+	"egg_uuid" uuid NOT NULL,
+	"user_uuid" uuid,
+	"revision" integer NOT NULL,
+	"data" jsonb NOT NULL,
+	"hash" varchar(64) NOT NULL,
+	"created" timestamp DEFAULT now() NOT NULL
+);
+
+ALTER TABLE "servers" ADD COLUMN "egg_revision_uuid" uuid;
+ALTER TABLE "nest_egg_revisions" ADD CONSTRAINT "nest_egg_revisions_egg_uuid_nest_eggs_uuid_fk" FOREIGN KEY ("egg_uuid") REFERENCES "public"."nest_eggs"("uuid") ON DELETE cascade ON UPDATE no action;
+ALTER TABLE "nest_egg_revisions" ADD CONSTRAINT "nest_egg_revisions_user_uuid_users_uuid_fk" FOREIGN KEY ("user_uuid") REFERENCES "public"."users"("uuid") ON DELETE set null ON UPDATE no action;
+ALTER TABLE "servers" ADD CONSTRAINT "servers_egg_revision_uuid_nest_egg_revisions_uuid_fk" FOREIGN KEY ("egg_revision_uuid") REFERENCES "public"."nest_egg_revisions"("uuid") ON DELETE set null ON UPDATE no action;
+CREATE UNIQUE INDEX "nest_egg_revisions_egg_uuid_revision_idx" ON "nest_egg_revisions" USING btree ("egg_uuid","revision");
\ No newline at end of file
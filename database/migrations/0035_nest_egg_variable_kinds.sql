@@ -0,0 +1,5 @@
+CREATE TYPE "public"."nest_egg_variable_kind" AS ENUM('STRING', 'INTEGER', 'BOOLEAN', 'SELECT', 'SECRET', 'PORT', 'FILE_PATH', 'MULTILINE');
+ALTER TABLE "nest_egg_variables" ADD COLUMN "kind" "nest_egg_variable_kind" DEFAULT 'STRING' NOT NULL;
+ALTER TABLE "nest_egg_variables" ADD COLUMN "options" text[] DEFAULT '{}' NOT NULL;
+ALTER TABLE "nest_egg_variables" ADD COLUMN "ui_hints" jsonb;
+ALTER TABLE "server_variables" ADD COLUMN "encrypted_value" "bytea";
\ No newline at end of file
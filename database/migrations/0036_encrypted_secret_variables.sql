@@ -0,0 +1,2 @@
+ALTER TABLE "server_templates" ADD COLUMN "encrypted_variables" "bytea";
+ALTER TABLE "server_bulk_jobs" ADD COLUMN "encrypted_value" "bytea";
\ No newline at end of file
@@ -0,0 +1,13 @@
+CREATE TABLE "mail_templates" (
+	"uuid" uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+	"name" varchar(63) NOT NULL,
+	"language" varchar(15) NOT NULL,
+	"subject" varchar(255) NOT NULL,
+	"html" text NOT NULL,
+	"text" text NOT NULL,
+	"created" timestamp DEFAULT now() NOT NULL,
+	"updated" timestamp DEFAULT now() NOT NULL
+);
+
+ALTER TABLE "users" ADD COLUMN "language" varchar(15) DEFAULT 'en' NOT NULL;
+CREATE UNIQUE INDEX "mail_templates_name_language_idx" ON "mail_templates" USING btree ("name","language");
\ No newline at end of file
@@ -0,0 +1,18 @@
+CREATE TYPE "public"."notification_category" AS ENUM('ACCOUNT', 'SERVER', 'BACKUP', 'TRANSFER');
+CREATE TABLE "user_notifications" (
+	"uuid" uuid PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+	"user_uuid" uuid NOT NULL,
+	"server_uuid" uuid,
+	"category" "notification_category" NOT NULL,
+	"event" varchar(255) NOT NULL,
+	"title" varchar(255) NOT NULL,
+	"body" text NOT NULL,
+	"data" jsonb DEFAULT '{}'::jsonb NOT NULL,
+	"read" timestamp,
+	"created" timestamp DEFAULT now() NOT NULL
+);
+
+ALTER TABLE "users" ADD COLUMN "notification_preferences" jsonb DEFAULT '{}'::jsonb NOT NULL;
+ALTER TABLE "user_notifications" ADD CONSTRAINT "user_notifications_user_uuid_users_uuid_fk" FOREIGN KEY ("user_uuid") REFERENCES "public"."users"("uuid") ON DELETE cascade ON UPDATE no action;
+ALTER TABLE "user_notifications" ADD CONSTRAINT "user_notifications_server_uuid_servers_uuid_fk" FOREIGN KEY ("server_uuid") REFERENCES "public"."servers"("uuid") ON DELETE cascade ON UPDATE no action;
+CREATE INDEX "user_notifications_user_uuid_created_idx" ON "user_notifications" USING btree ("user_uuid","created");
\ No newline at end of file
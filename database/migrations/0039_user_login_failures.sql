@@ -0,0 +1,9 @@
+CREATE TABLE "user_login_failures" (
+	"user_uuid" uuid PRIMARY KEY NOT NULL,
+	"failures" integer DEFAULT 0 NOT NULL,
+	"last_ip" "inet",
+	"last_failure" timestamp DEFAULT now() NOT NULL,
+	"locked_until" timestamp
+);
+
+ALTER TABLE "user_login_failures" ADD CONSTRAINT "user_login_failures_user_uuid_users_uuid_fk" FOREIGN KEY ("user_uuid") REFERENCES "public"."users"("uuid") ON DELETE cascade ON UPDATE no action;
\ No newline at end of file
@@ -43,6 +43,36 @@ export const backupDiskEnum = pgEnum('backup_disk', [
 	'RESTIC'
 ])
 
+export const backupVerificationStatusEnum = pgEnum('backup_verification_status', [
+	'PENDING',
+	'SUCCESSFUL',
+	'FAILED'
+])
+
+export const queuedJobStatusEnum = pgEnum('queued_job_status', [
+	'PENDING',
+	'RUNNING',
+	'FAILED'
+])
+
+export const nestEggVariableKindEnum = pgEnum('nest_egg_variable_kind', [
+	'STRING',
+	'INTEGER',
+	'BOOLEAN',
+	'SELECT',
+	'SECRET',
+	'PORT',
+	'FILE_PATH',
+	'MULTILINE'
+])
+
+export const notificationCategoryEnum = pgEnum('notification_category', [
+	'ACCOUNT',
+	'SERVER',
+	'BACKUP',
+	'TRANSFER'
+])
+
 export const settings = pgTable('settings', {
 	key: varchar('key', { length: 255 }).primaryKey().notNull(),
 	value: text('value').notNull(),
@@ -50,6 +80,22 @@ export const settings = pgTable('settings', {
 	uniqueIndex('settings_key_idx').on(settings.key)
 ])
 
+export const mailTemplates = pgTable('mail_templates', {
+	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
+
+	name: varchar('name', { length: 63 }).notNull(),
+	language: varchar('language', { length: 15 }).notNull(),
+
+	subject: varchar('subject', { length: 255 }).notNull(),
+	html: text('html').notNull(),
+	text: text('text').notNull(),
+
+	created: timestamp('created').default(sql`now()`).notNull(),
+	updated: timestamp('updated').default(sql`now()`).notNull()
+}, (mailTemplates) => [
+	uniqueIndex('mail_templates_name_language_idx').on(mailTemplates.name, mailTemplates.language)
+])
+
 export const adminActivities = pgTable('admin_activities', {
 	userUuid: uuid('user_uuid').references(() => users.uuid, { onDelete: 'set null' }),
 	apiKeyUuid: uuid('api_key_uuid').references(() => userApiKeys.uuid, { onDelete: 'set null' }),
@@ -76,6 +122,7 @@ export const users = pgTable('users', {
 
 	nameFirst: varchar('name_first', { length: 255 }).notNull(),
 	nameLast: varchar('name_last', { length: 255 }).notNull(),
+	language: varchar('language', { length: 15 }).default('en').notNull(),
 
 	password: text('password').notNull(),
 
@@ -83,6 +130,9 @@ export const users = pgTable('users', {
 	totp_enabled: boolean('totp_enabled').default(false).notNull(),
 	totp_secret: char('totp_secret', { length: 32 }),
 
+	quotas: jsonb('quotas').default({}).notNull(),
+	notificationPreferences: jsonb('notification_preferences').default({}).notNull(),
+
 	created: timestamp('created').default(sql`now()`).notNull()
 }, (users) => [
 	uniqueIndex('users_external_id_idx').on(users.externalId),
@@ -131,6 +181,29 @@ export const userRecoveryCodes = pgTable('user_recovery_codes', {
 	uniqueIndex('user_recovery_codes_user_uuid_code_idx').on(userRecoveryCodes.userUuid, userRecoveryCodes.code)
 ])
 
+export const userExternalIdentities = pgTable('user_external_identities', {
+	userUuid: uuid('user_uuid').references(() => users.uuid, { onDelete: 'cascade' }).notNull(),
+
+	provider: varchar('provider', { length: 255 }).notNull(),
+	subject: varchar('subject', { length: 255 }).notNull(),
+
+	created: timestamp('created').default(sql`now()`).notNull()
+}, (userExternalIdentities) => [
+	primaryKey({ name: 'user_external_identities_provider_subject_pk', columns: [userExternalIdentities.provider, userExternalIdentities.subject] }),
+
+	uniqueIndex('user_external_identities_user_uuid_provider_idx').on(userExternalIdentities.userUuid, userExternalIdentities.provider)
+])
+
+export const userLoginFailures = pgTable('user_login_failures', {
+	userUuid: uuid('user_uuid').references(() => users.uuid, { onDelete: 'cascade' }).primaryKey().notNull(),
+
+	failures: integer('failures').default(0).notNull(),
+	lastIp: inet('last_ip'),
+
+	lastFailure: timestamp('last_failure').default(sql`now()`).notNull(),
+	lockedUntil: timestamp('locked_until')
+})
+
 export const userPasswordResets = pgTable('user_password_resets', {
 	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
 	userUuid: uuid('user_uuid').references(() => users.uuid, { onDelete: 'cascade' }).notNull(),
@@ -177,6 +250,23 @@ export const userSshKeys = pgTable('user_ssh_keys', {
 	uniqueIndex('user_ssh_keys_user_uuid_fingerprint_idx').on(userSshKeys.userUuid, userSshKeys.fingerprint)
 ])
 
+export const userNotifications = pgTable('user_notifications', {
+	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
+	userUuid: uuid('user_uuid').references(() => users.uuid, { onDelete: 'cascade' }).notNull(),
+	serverUuid: uuid('server_uuid').references((): PgColumn => servers.uuid, { onDelete: 'cascade' }),
+
+	category: notificationCategoryEnum('category').notNull(),
+	event: varchar('event', { length: 255 }).notNull(),
+	title: varchar('title', { length: 255 }).notNull(),
+	body: text('body').notNull(),
+	data: jsonb('data').default({}).notNull(),
+
+	read: timestamp('read'),
+	created: timestamp('created').default(sql`now()`).notNull()
+}, (userNotifications) => [
+	index('user_notifications_user_uuid_created_idx').on(userNotifications.userUuid, userNotifications.created)
+])
+
 export const userApiKeys = pgTable('user_api_keys', {
 	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
 	userUuid: uuid('user_uuid').references(() => users.uuid, { onDelete: 'cascade' }).notNull(),
@@ -207,6 +297,8 @@ export const roles = pgTable('roles', {
 	admin_permissions: varchar('admin_permissions', { length: 64 }).array().notNull(),
 	server_permissions: varchar('server_permissions', { length: 64 }).array().notNull(),
 
+	quotas: jsonb('quotas').default({}).notNull(),
+
 	created: timestamp('created').default(sql`now()`).notNull()
 }, (roles) => [
 	uniqueIndex('roles_name_idx').on(roles.name)
@@ -238,6 +330,7 @@ export const backupConfigurations = pgTable('backup_configurations', {
 
 	backup_disk: backupDiskEnum('backup_disk').default('LOCAL').notNull(),
 	backup_configs: jsonb('backup_configs').default({}).notNull(),
+	retention: jsonb('retention').default({}).notNull(),
 
 	created: timestamp('created').default(sql`now()`).notNull()
 }, (backupConfigurations) => [
@@ -248,7 +341,7 @@ export const locations = pgTable('locations', {
 	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
 	backupConfigurationUuid: uuid('backup_configuration_uuid').references(() => backupConfigurations.uuid, { onDelete: 'set null' }),
 
-	shortName: varchar('short_name', { length: 31 * UTF8_MAX_SCALAR_SIZE }).notNull(),
+	shortName: varchar('short_name', { length: 31 * UTF8_MAX_SCALAR_SIZE }),
 	name: varchar('name', { length: 255 * UTF8_MAX_SCALAR_SIZE }).notNull(),
 	description: text('description'),
 
@@ -335,6 +428,24 @@ export const nests = pgTable('nests', {
 	uniqueIndex('nests_name_idx').on(nests.name)
 ])
 
+export const nestEggSources = pgTable('nest_egg_sources', {
+	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
+	nestUuid: uuid('nest_uuid').references(() => nests.uuid, { onDelete: 'cascade' }).notNull(),
+
+	name: varchar('name', { length: 255 }).notNull(),
+	url: text('url').notNull(),
+	syncInterval: integer('sync_interval'),
+
+	lastSynced: timestamp('last_synced'),
+	lastSyncReport: jsonb('last_sync_report'),
+	lastSyncError: text('last_sync_error'),
+
+	created: timestamp('created').default(sql`now()`).notNull()
+}, (nestEggSources) => [
+	index('nest_egg_sources_nest_uuid_idx').on(nestEggSources.nestUuid),
+	uniqueIndex('nest_egg_sources_nest_uuid_name_idx').on(nestEggSources.nestUuid, nestEggSources.name)
+])
+
 export const nestEggs = pgTable('nest_eggs', {
 	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
 	nestUuid: uuid('nest_uuid').references(() => nests.uuid).notNull(),
@@ -351,14 +462,21 @@ export const nestEggs = pgTable('nest_eggs', {
 
 	startup: text('startup').notNull(),
 	forceOutgoingIp: boolean('force_outgoing_ip').default(false).notNull(),
+	deployable: boolean('deployable').default(false).notNull(),
 
 	features: text('features').array().notNull(),
 	docker_images: json('docker_images').$type<Record<string, string>>().notNull(),
 	file_denylist: text('file_denylist').array().notNull(),
 
+	sourceUuid: uuid('source_uuid').references(() => nestEggSources.uuid, { onDelete: 'set null' }),
+	sourcePath: text('source_path'),
+	upstreamHash: varchar('upstream_hash', { length: 64 }),
+	syncedHash: varchar('synced_hash', { length: 64 }),
+
 	created: timestamp('created').default(sql`now()`).notNull()
 }, (eggs) => [
 	index('eggs_nest_uuid_idx').on(eggs.nestUuid),
+	index('nest_eggs_source_uuid_idx').on(eggs.sourceUuid),
 	uniqueIndex('eggs_nest_uuid_name_idx').on(eggs.nestUuid, eggs.name),
 ])
 
@@ -387,6 +505,9 @@ export const nestEggVariables = pgTable('nest_egg_variables', {
 	userViewable: boolean('user_viewable').default(true).notNull(),
 	userEditable: boolean('user_editable').default(false).notNull(),
 	rules: text('rules').array().notNull(),
+	kind: nestEggVariableKindEnum('kind').default('STRING').notNull(),
+	options: text('options').array().default([]).notNull(),
+	uiHints: jsonb('ui_hints'),
 
 	created: timestamp('created').default(sql`now()`).notNull()
 }, (eggVariables) => [
@@ -395,6 +516,20 @@ export const nestEggVariables = pgTable('nest_egg_variables', {
 	uniqueIndex('egg_variables_egg_uuid_env_variable_idx').on(eggVariables.eggUuid, eggVariables.envVariable)
 ])
 
+export const nestEggRevisions = pgTable('nest_egg_revisions', {
+	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
+	eggUuid: uuid('egg_uuid').references(() => nestEggs.uuid, { onDelete: 'cascade' }).notNull(),
+	userUuid: uuid('user_uuid').references(() => users.uuid, { onDelete: 'set null' }),
+
+	revision: integer('revision').notNull(),
+	data: jsonb('data').notNull(),
+	hash: varchar('hash', { length: 64 }).notNull(),
+
+	created: timestamp('created').default(sql`now()`).notNull()
+}, (nestEggRevisions) => [
+	uniqueIndex('nest_egg_revisions_egg_uuid_revision_idx').on(nestEggRevisions.eggUuid, nestEggRevisions.revision)
+])
+
 export const databaseHosts = pgTable('database_hosts', {
 	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
 
@@ -416,6 +551,38 @@ export const databaseHosts = pgTable('database_hosts', {
 	uniqueIndex('database_hosts_host_port_idx').on(databaseHosts.host, databaseHosts.port)
 ])
 
+export const serverTemplates = pgTable('server_templates', {
+	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
+	eggUuid: uuid('egg_uuid').references(() => nestEggs.uuid, { onDelete: 'cascade' }).notNull(),
+
+	name: varchar('name', { length: 255 * UTF8_MAX_SCALAR_SIZE }).notNull(),
+	description: text('description'),
+
+	memory: bigint('memory', { mode: 'number' }).notNull(),
+	swap: bigint('swap', { mode: 'number' }).notNull(),
+	disk: bigint('disk', { mode: 'number' }).notNull(),
+	ioWeight: smallint('io_weight'),
+	cpu: integer('cpu').notNull(),
+	pinnedCpus: smallint('pinned_cpus').array().notNull(),
+
+	startup: text('startup').notNull(),
+	image: varchar('image', { length: 255 }).notNull(),
+	timezone: varchar('timezone', { length: 255 }),
+
+	allocationLimit: integer('allocation_limit').default(0).notNull(),
+	databaseLimit: integer('database_limit').default(0).notNull(),
+	backupLimit: integer('backup_limit').default(0).notNull(),
+	scheduleLimit: integer('schedule_limit').default(0).notNull(),
+
+	variables: jsonb('variables').default({}).notNull(),
+	encryptedVariables: bytea('encrypted_variables'),
+	mounts: uuid('mounts').array().notNull(),
+
+	created: timestamp('created').default(sql`now()`).notNull()
+}, (serverTemplates) => [
+	uniqueIndex('server_templates_name_idx').on(serverTemplates.name)
+])
+
 export const servers = pgTable('servers', {
 	uuid: uuid('uuid').primaryKey().notNull(),
 	uuidShort: integer('uuid_short').notNull(),
@@ -426,12 +593,15 @@ export const servers = pgTable('servers', {
 	destinationNodeUuid: uuid('destination_node_uuid').references(() => nodes.uuid),
 	ownerUuid: uuid('owner_uuid').references(() => users.uuid).notNull(),
 	eggUuid: uuid('egg_uuid').references(() => nestEggs.uuid).notNull(),
+	eggRevisionUuid: uuid('egg_revision_uuid').references(() => nestEggRevisions.uuid, { onDelete: 'set null' }),
 	backupConfigurationUuid: uuid('backup_configuration_uuid').references(() => backupConfigurations.uuid, { onDelete: 'set null' }),
+	serverTemplateUuid: uuid('server_template_uuid').references(() => serverTemplates.uuid, { onDelete: 'set null' }),
 
 	name: varchar('name', { length: 255 * UTF8_MAX_SCALAR_SIZE }).notNull(),
 	description: text('description'),
 
 	status: serverStatusEnum('status'),
+	restoringBackupUuid: uuid('restoring_backup_uuid').references((): PgColumn => serverBackups.uuid, { onDelete: 'set null' }),
 	suspended: boolean('suspended').default(false).notNull(),
 
 	memory: bigint('memory', { mode: 'number' }).notNull(),
@@ -450,6 +620,7 @@ export const servers = pgTable('servers', {
 	databaseLimit: integer('database_limit').default(0).notNull(),
 	backupLimit: integer('backup_limit').default(0).notNull(),
 	scheduleLimit: integer('schedule_limit').default(0).notNull(),
+	backupRetention: jsonb('backup_retention'),
 
 	created: timestamp('created').default(sql`now()`).notNull()
 }, (servers) => [
@@ -457,6 +628,8 @@ export const servers = pgTable('servers', {
 	uniqueIndex('servers_uuid_short_idx').on(servers.uuidShort),
 	index('servers_external_id_idx').on(servers.externalId),
 	index('servers_owner_uuid_idx').on(servers.ownerUuid),
+	index('servers_server_template_uuid_idx').on(servers.serverTemplateUuid),
+	uniqueIndex('servers_node_uuid_restoring_backup_uuid_idx').on(servers.nodeUuid, servers.restoringBackupUuid),
 ])
 
 export const serverAllocations = pgTable('server_allocations', {
@@ -509,6 +682,7 @@ export const serverVariables = pgTable('server_variables', {
 	variableUuid: uuid('variable_uuid').references(() => nestEggVariables.uuid, { onDelete: 'cascade' }).notNull(),
 
 	value: text('value').notNull(),
+	encryptedValue: bytea('encrypted_value'),
 
 	created: timestamp('created').default(sql`now()`).notNull()
 }, (serverVariables) => [
@@ -549,7 +723,11 @@ export const serverBackups = pgTable('server_backups', {
 	uploadId: text('upload_id'),
 	uploadPath: text('upload_path'),
 
+	verificationStatus: backupVerificationStatusEnum('verification_status'),
+	verificationError: text('verification_error'),
+
 	completed: timestamp('completed'),
+	verified: timestamp('verified'),
 	deleted: timestamp('deleted'),
 	created: timestamp('created').default(sql`now()`).notNull()
 }, (serverBackups) => [
@@ -605,3 +783,85 @@ export const serverScheduleSteps = pgTable('server_schedule_steps', {
 }, (serverScheduleSteps) => [
 	index('server_schedule_steps_schedule_uuid_idx').on(serverScheduleSteps.scheduleUuid)
 ])
+
+export const serverBulkJobs = pgTable('server_bulk_jobs', {
+	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
+	userUuid: uuid('user_uuid').references(() => users.uuid, { onDelete: 'set null' }),
+
+	action: jsonb('action').notNull(),
+	encryptedValue: bytea('encrypted_value'),
+	concurrency: integer('concurrency').notNull(),
+
+	finished: timestamp('finished'),
+	created: timestamp('created').default(sql`now()`).notNull()
+}, (serverBulkJobs) => [
+	index('server_bulk_jobs_created_idx').on(serverBulkJobs.created)
+])
+
+export const serverBulkJobResults = pgTable('server_bulk_job_results', {
+	jobUuid: uuid('job_uuid').references(() => serverBulkJobs.uuid, { onDelete: 'cascade' }).notNull(),
+	serverUuid: uuid('server_uuid').references(() => servers.uuid, { onDelete: 'cascade' }).notNull(),
+
+	successful: boolean('successful'),
+	error: text('error'),
+
+	finished: timestamp('finished')
+}, (serverBulkJobResults) => [
+	primaryKey({ name: 'server_bulk_job_results_pk', columns: [serverBulkJobResults.jobUuid, serverBulkJobResults.serverUuid] })
+])
+
+export const webhooks = pgTable('webhooks', {
+	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
+
+	name: varchar('name', { length: 255 * UTF8_MAX_SCALAR_SIZE }).notNull(),
+	description: text('description'),
+
+	url: text('url').notNull(),
+	secret: bytea('secret').notNull(),
+	events: varchar('events', { length: 255 }).array().notNull(),
+	enabled: boolean('enabled').default(true).notNull(),
+
+	created: timestamp('created').default(sql`now()`).notNull()
+}, (webhooks) => [
+	uniqueIndex('webhooks_name_idx').on(webhooks.name)
+])
+
+export const webhookDeliveries = pgTable('webhook_deliveries', {
+	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
+	webhookUuid: uuid('webhook_uuid').references(() => webhooks.uuid, { onDelete: 'cascade' }).notNull(),
+
+	event: varchar('event', { length: 255 }).notNull(),
+	payload: jsonb('payload').notNull(),
+
+	attempts: integer('attempts').default(0).notNull(),
+	successful: boolean('successful').default(false).notNull(),
+	responseStatus: integer('response_status'),
+	responseBody: text('response_body'),
+	error: text('error'),
+
+	nextAttempt: timestamp('next_attempt').default(sql`now()`),
+	lastAttempt: timestamp('last_attempt'),
+	created: timestamp('created').default(sql`now()`).notNull()
+}, (webhookDeliveries) => [
+	index('webhook_deliveries_webhook_uuid_idx').on(webhookDeliveries.webhookUuid),
+	index('webhook_deliveries_next_attempt_idx').on(webhookDeliveries.nextAttempt)
+])
+
+export const queuedJobs = pgTable('queued_jobs', {
+	uuid: uuid('uuid').default(sql`gen_random_uuid()`).primaryKey().notNull(),
+
+	kind: varchar('kind', { length: 255 }).notNull(),
+	payload: jsonb('payload').notNull(),
+
+	status: queuedJobStatusEnum('status').default('PENDING').notNull(),
+	attempts: integer('attempts').default(0).notNull(),
+	maxAttempts: integer('max_attempts').notNull(),
+	error: text('error'),
+
+	runAt: timestamp('run_at').default(sql`now()`).notNull(),
+	lockedUntil: timestamp('locked_until'),
+	created: timestamp('created').default(sql`now()`).notNull()
+}, (queuedJobs) => [
+	index('queued_jobs_status_run_at_idx').on(queuedJobs.status, queuedJobs.runAt),
+	index('queued_jobs_kind_idx').on(queuedJobs.kind)
+])
@@ -2,11 +2,14 @@ import { axiosInstance } from '@/api/axios';
 
 interface Data {
   code: string;
-  confirmation_token: string;
+  // omitted after an oidc redirect, the token is then read from a cookie
+  confirmation_token?: string;
 }
 
 interface Response {
-  user: User;
+  user?: User;
+  token?: string;
+  type: 'completed' | 'device_verification_required';
 }
 
 export default async ({ code, confirmation_token }: Data): Promise<Response> => {
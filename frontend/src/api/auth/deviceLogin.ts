@@ -0,0 +1,20 @@
+import { axiosInstance } from '@/api/axios';
+
+interface Data {
+  code: string;
+  // omitted after an oidc redirect, the token is then read from a cookie
+  confirmation_token?: string;
+}
+
+interface Response {
+  user: User;
+}
+
+export default async ({ code, confirmation_token }: Data): Promise<Response> => {
+  return new Promise((resolve, reject) => {
+    axiosInstance
+      .post('/api/auth/login/device', { code, confirmation_token })
+      .then(({ data }) => resolve(data))
+      .catch(reject);
+  });
+};
@@ -9,7 +9,7 @@ interface Data {
 interface Response {
   user?: User;
   token?: string;
-  type: 'completed' | 'two_factor_required';
+  type: 'completed' | 'two_factor_required' | 'device_verification_required';
 }
 
 export default async ({ user, password, captcha }: Data): Promise<Response> => {
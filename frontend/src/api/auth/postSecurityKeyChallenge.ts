@@ -2,7 +2,9 @@ import { axiosInstance } from '@/api/axios';
 import { prepareCredentialForTransport } from '../me/security-keys/postSecurityKeyChallenge';
 
 interface Response {
-  user: User;
+  user?: User;
+  token?: string;
+  type: 'completed' | 'device_verification_required';
 }
 
 export default async (uuid: string, challenge: PublicKeyCredential): Promise<Response> => {
@@ -1,5 +1,5 @@
 import { useRef, useState } from 'react';
-import { useNavigate } from 'react-router';
+import { useNavigate, useSearchParams } from 'react-router';
 import AuthWrapper from './AuthWrapper';
 import { useAuth } from '@/providers/AuthProvider';
 import Captcha from '@/elements/Captcha';
@@ -16,6 +16,7 @@ import PasswordInput from '@/elements/input/PasswordInput';
 import login from '@/api/auth/login';
 import PinInput from '@/elements/input/PinInput';
 import checkpointLogin from '@/api/auth/checkpointLogin';
+import deviceLogin from '@/api/auth/deviceLogin';
 import { load } from '@/lib/debounce';
 import { useGlobalStore } from '@/stores/global';
 
@@ -23,10 +24,13 @@ export default () => {
   const { doLogin } = useAuth();
   const { settings } = useGlobalStore();
   const navigate = useNavigate();
+  const [searchParams] = useSearchParams();
 
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState('');
-  const [step, setStep] = useState<'username' | 'passkey' | 'password' | 'totp' | 'totp-recovery'>('username');
+  const [step, setStep] = useState<'username' | 'passkey' | 'password' | 'totp' | 'totp-recovery' | 'device'>(
+    searchParams.get('step') === 'device' ? 'device' : searchParams.get('step') === 'totp' ? 'totp' : 'username',
+  );
   const [username, setUsername] = useState('');
   const [passkeyUuid, setPasskeyUuid] = useState('');
   const [passkeyOptions, setPasskeyOptions] = useState<CredentialRequestOptions>(null);
@@ -34,6 +38,14 @@ export default () => {
   const captchaRef = useRef(null);
   const [twoFactorToken, setTwoFactorToken] = useState('');
   const [totpCode, setTotpCode] = useState('');
+  const [deviceToken, setDeviceToken] = useState('');
+  const [deviceCode, setDeviceCode] = useState('');
+
+  const requireDeviceVerification = (token: string) => {
+    setDeviceToken(token);
+    setDeviceCode('');
+    setStep('device');
+  };
 
   const doSubmitUsername = () => {
     if (!username) {
@@ -75,6 +87,11 @@ export default () => {
       .then((credential) => {
         postSecurityKeyChallenge(passkeyUuid, credential as PublicKeyCredential)
           .then((response) => {
+            if (response.type === 'device_verification_required') {
+              requireDeviceVerification(response.token!);
+              return;
+            }
+
             doLogin(response.user);
           })
           .catch((msg) => {
@@ -130,6 +147,10 @@ export default () => {
             setStep('totp');
             return;
           }
+          if (response.type === 'device_verification_required') {
+            requireDeviceVerification(response.token!);
+            return;
+          }
 
           doLogin(response.user);
         })
@@ -143,7 +164,25 @@ export default () => {
   const doSubmitTotp = () => {
     load(true, setLoading);
 
-    checkpointLogin({ code: totpCode, confirmation_token: twoFactorToken })
+    checkpointLogin({ code: totpCode, confirmation_token: twoFactorToken || undefined })
+      .then((response) => {
+        if (response.type === 'device_verification_required') {
+          requireDeviceVerification(response.token!);
+          return;
+        }
+
+        doLogin(response.user);
+      })
+      .catch((msg) => {
+        setError(httpErrorToHuman(msg));
+      })
+      .finally(() => load(false, setLoading));
+  };
+
+  const doSubmitDevice = () => {
+    load(true, setLoading);
+
+    deviceLogin({ code: deviceCode, confirmation_token: deviceToken || undefined })
       .then((response) => {
         doLogin(response.user);
       })
@@ -333,6 +372,48 @@ export default () => {
                 Use TOTP
               </Button>
             </Stack>
+          ) : step === 'device' ? (
+            <Stack>
+              <Title order={2} ta={'center'}>
+                Confirm New Device
+              </Title>
+              <Text c={'dimmed'} ta={'center'}>
+                Enter the code we sent to your email address to confirm this device
+              </Text>
+
+              <TextInput
+                label={'Confirmation Code'}
+                placeholder={'Enter the confirmation code'}
+                value={deviceCode}
+                onChange={(e) => setDeviceCode(e.target.value)}
+                onKeyDown={(e) => e.key === 'Enter' && deviceCode.length === 8 && doSubmitDevice()}
+                size={'md'}
+              />
+
+              <Button
+                onClick={doSubmitDevice}
+                loading={loading}
+                disabled={deviceCode.length !== 8}
+                size={'md'}
+                fullWidth
+              >
+                Confirm Device
+              </Button>
+
+              <Divider label={'OR'} labelPosition={'center'} />
+
+              <Button
+                variant={'light'}
+                onClick={() => {
+                  setDeviceToken('');
+                  setStep('username');
+                }}
+                size={'md'}
+                fullWidth
+              >
+                Back to Login
+              </Button>
+            </Stack>
           ) : null}
         </Card>
       </Stack>
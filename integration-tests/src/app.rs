@@ -0,0 +1,348 @@
+use crate::wings::MockWings;
+use axum::{
+    body::Body,
+    http::{HeaderMap, Method, Request, StatusCode},
+};
+use rand::distr::SampleString;
+use shared::State;
+use sqlx::{Connection, Executor, postgres::PgConnection};
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    sync::{Arc, LazyLock},
+    time::Instant,
+};
+use tower::ServiceExt;
+
+/// Every app owns a connection pool with a large minimum size, so apps are booted one at a time.
+static SERIAL: LazyLock<Arc<tokio::sync::Mutex<()>>> =
+    LazyLock::new(|| Arc::new(tokio::sync::Mutex::new(())));
+
+/// The token `/metrics` is served with.
+pub const METRICS_TOKEN: &str = "integration-tests";
+
+/// A fully booted panel backed by a fresh database and a [MockWings] instance.
+///
+/// The database is created from `TEST_DATABASE_URL` (PostgreSQL 13+) and dropped again when
+/// the app goes out of scope, the cache connects to `TEST_REDIS_URL`.
+pub struct TestApp {
+    pub state: State,
+    pub wings: MockWings,
+    pub ip: IpAddr,
+
+    router: axum::Router,
+    worker: tokio::task::JoinHandle<()>,
+    admin_url: String,
+    database_name: String,
+    _serial: tokio::sync::OwnedMutexGuard<()>,
+}
+
+impl TestApp {
+    /// Boots a new app, panicking when the test environment is not configured.
+    pub async fn new() -> Self {
+        let admin_url = std::env::var("TEST_DATABASE_URL")
+            .expect("TEST_DATABASE_URL is required to run integration tests");
+        let redis_url = std::env::var("TEST_REDIS_URL")
+            .expect("TEST_REDIS_URL is required to run integration tests");
+
+        let serial = SERIAL.clone().lock_owned().await;
+
+        let database_name = format!("panel_test_{}", uuid::Uuid::new_v4().simple());
+        let mut connection = PgConnection::connect(&admin_url).await.unwrap();
+        connection
+            .execute(format!(r#"CREATE DATABASE "{database_name}""#).as_str())
+            .await
+            .unwrap();
+        connection.close().await.unwrap();
+
+        let (base, query) = match admin_url.split_once('?') {
+            Some((base, query)) => (base, format!("?{query}")),
+            None => (admin_url.as_str(), String::new()),
+        };
+        let (base, _) = base.rsplit_once('/').unwrap();
+
+        let env = Arc::new(shared::env::Env {
+            redis_mode: shared::env::RedisMode::Redis { redis_url },
+            sentry_url: None,
+            database_migrate: true,
+            database_url: format!("{base}/{database_name}{query}"),
+            database_url_primary: None,
+            bind: "127.0.0.1".to_string(),
+            port: 0,
+            app_debug: true,
+            app_log_directory: std::env::temp_dir().to_string_lossy().to_string(),
+            app_encryption_key: rand::distr::Alphanumeric.sample_string(&mut rand::rng(), 32),
+            server_name: None,
+            metrics_token: Some(METRICS_TOKEN.to_string()),
+            // the file transport is what tests read mails from, sendmail is never installed
+            mail_sendmail_command: Some("/nonexistent/sendmail".to_string()),
+            mail_file_directory: Some(
+                std::env::temp_dir()
+                    .join(format!("calagopus-mails-{database_name}"))
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            egg_source_directory: Some(std::env::temp_dir().to_string_lossy().to_string()),
+        });
+
+        let jwt = Arc::new(shared::jwt::Jwt::new(&env));
+        let database = Arc::new(shared::database::Database::new(&env).await);
+        let cache = Arc::new(shared::cache::Cache::new(&env).await);
+
+        database.migrate().await.unwrap();
+
+        let extensions = Arc::new(shared::extensions::manager::ExtensionManager::new(
+            Vec::new(),
+        ));
+        let settings = Arc::new(shared::settings::Settings::new(database.clone()).await);
+        let storage = Arc::new(shared::storage::Storage::new(
+            settings.clone(),
+            database.clone(),
+        ));
+        let captcha = Arc::new(shared::captcha::Captcha::new(settings.clone()));
+        let mail = Arc::new(shared::mail::Mail::new(
+            settings.clone(),
+            database.clone(),
+            env.clone(),
+        ));
+
+        let state = Arc::new(shared::AppState {
+            start_time: Instant::now(),
+            version: format!("{}:{}", shared::VERSION, shared::GIT_COMMIT),
+
+            client: reqwest::Client::new(),
+
+            extensions,
+            settings,
+            jwt,
+            storage,
+            captcha,
+            mail,
+            database,
+            cache,
+            env,
+        });
+
+        // ratelimits are keyed by ip and the cache is shared between runs, so every app gets its own
+        let ip = IpAddr::V4(Ipv4Addr::new(
+            10,
+            rand::random(),
+            rand::random(),
+            rand::random(),
+        ));
+
+        let router = utoipa_axum::router::OpenApiRouter::new()
+            .merge(panel_rs::routes::router(&state))
+            .route(
+                "/metrics",
+                axum::routing::get(panel_rs::routes::metrics::route),
+            )
+            .route_layer(axum::middleware::from_fn(panel_rs::routes::metrics::handle))
+            .layer(axum::Extension(ip))
+            .layer(tower_cookies::CookieManagerLayer::new())
+            .with_state(state.clone())
+            .split_for_parts()
+            .0;
+
+        let worker = tokio::spawn(shared::jobs::work(state.clone()));
+
+        Self {
+            state,
+            wings: MockWings::start().await,
+            ip,
+            worker,
+            router,
+            admin_url,
+            database_name,
+            _serial: serial,
+        }
+    }
+
+    #[inline]
+    pub fn request(&self, method: Method, path: impl Into<String>) -> TestRequest<'_> {
+        TestRequest {
+            app: self,
+            method,
+            path: path.into(),
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, path: impl Into<String>) -> TestRequest<'_> {
+        self.request(Method::GET, path)
+    }
+
+    #[inline]
+    pub fn post(&self, path: impl Into<String>) -> TestRequest<'_> {
+        self.request(Method::POST, path)
+    }
+
+    #[inline]
+    pub fn delete(&self, path: impl Into<String>) -> TestRequest<'_> {
+        self.request(Method::DELETE, path)
+    }
+
+    #[inline]
+    pub fn patch(&self, path: impl Into<String>) -> TestRequest<'_> {
+        self.request(Method::PATCH, path)
+    }
+
+    #[inline]
+    pub fn put(&self, path: impl Into<String>) -> TestRequest<'_> {
+        self.request(Method::PUT, path)
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        self.worker.abort();
+
+        if let Some(path) = &self.state.env.mail_file_directory {
+            let _ = std::fs::remove_dir_all(path);
+        }
+
+        let admin_url = self.admin_url.clone();
+        let database_name = self.database_name.clone();
+
+        // the test runtime may already be shutting down, so clean up on a runtime of our own
+        let _ = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async move {
+                    if let Ok(mut connection) = PgConnection::connect(&admin_url).await {
+                        let _ = connection
+                            .execute(
+                                format!(
+                                    r#"DROP DATABASE IF EXISTS "{database_name}" WITH (FORCE)"#
+                                )
+                                .as_str(),
+                            )
+                            .await;
+                    }
+                });
+        })
+        .join();
+    }
+}
+
+pub struct TestRequest<'a> {
+    app: &'a TestApp,
+    method: Method,
+    path: String,
+    headers: HeaderMap,
+    body: Option<serde_json::Value>,
+}
+
+impl TestRequest<'_> {
+    pub fn header(mut self, name: &'static str, value: impl AsRef<str>) -> Self {
+        self.headers.insert(name, value.as_ref().parse().unwrap());
+        self
+    }
+
+    /// Adds a cookie to the request, keeping previously added ones.
+    pub fn cookie(self, name: &str, value: &str) -> Self {
+        let cookie = match self.headers.get("Cookie").and_then(|c| c.to_str().ok()) {
+            Some(cookies) => format!("{cookies}; {name}={value}"),
+            None => format!("{name}={value}"),
+        };
+
+        self.header("Cookie", cookie)
+    }
+
+    #[inline]
+    pub fn session(self, session: &str) -> Self {
+        self.cookie("session", session)
+    }
+
+    #[inline]
+    pub fn bearer(self, token: &str) -> Self {
+        self.header("Authorization", format!("Bearer {token}"))
+    }
+
+    #[inline]
+    pub fn json(mut self, body: serde_json::Value) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    pub async fn send(self) -> TestResponse {
+        let mut request = Request::builder().method(self.method).uri(self.path);
+
+        for (name, value) in self.headers.iter() {
+            request = request.header(name, value);
+        }
+
+        let request = match self.body {
+            Some(body) => request
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = self.app.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        TestResponse {
+            status,
+            headers,
+            body: serde_json::from_slice(&body).unwrap_or_else(|_| {
+                serde_json::Value::String(String::from_utf8_lossy(&body).to_string())
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: serde_json::Value,
+}
+
+impl TestResponse {
+    /// Returns the (non-empty) value of a cookie set by this response, if any.
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.headers
+            .get_all("Set-Cookie")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| {
+                value
+                    .split(';')
+                    .next()?
+                    .strip_prefix(name)?
+                    .strip_prefix('=')
+            })
+            .find(|value| !value.is_empty())
+            .map(String::from)
+    }
+
+    /// Returns the session key set by this response, if any.
+    #[inline]
+    pub fn session(&self) -> Option<String> {
+        self.cookie("session")
+    }
+
+    /// Returns the `Location` header of a redirect.
+    pub fn location(&self) -> Option<&str> {
+        self.headers.get("Location").and_then(|l| l.to_str().ok())
+    }
+
+    #[track_caller]
+    pub fn assert_status(&self, status: StatusCode) -> &Self {
+        assert_eq!(
+            self.status, status,
+            "unexpected response body: {}",
+            self.body
+        );
+        self
+    }
+}
@@ -0,0 +1,347 @@
+use crate::{app::TestApp, ldap::MockLdap, oidc::MockOidc, wings::MockWings};
+use axum::http::StatusCode;
+use rand::distr::SampleString;
+use shared::models::{
+    backup_configurations::{BackupConfigs, BackupConfiguration},
+    location::Location,
+    nest::Nest,
+    nest_egg::{
+        NestEgg, NestEggConfigAllocations, NestEggConfigScript, NestEggConfigStartup,
+        NestEggConfigStop,
+    },
+    node::Node,
+    node_allocation::NodeAllocation,
+    server::Server,
+    server_backup::BackupDisk,
+    user::User,
+    user_session::UserSession,
+};
+use sqlx::Row;
+
+pub struct TestUser {
+    pub user: User,
+    pub password: String,
+}
+
+#[inline]
+fn unique_name(prefix: &str) -> String {
+    format!(
+        "{prefix}{}",
+        rand::distr::Alphanumeric
+            .sample_string(&mut rand::rng(), 10)
+            .to_lowercase()
+    )
+}
+
+impl TestApp {
+    pub async fn create_user(&self, admin: bool) -> TestUser {
+        let username = unique_name("user");
+        let password = rand::distr::Alphanumeric.sample_string(&mut rand::rng(), 16);
+
+        let uuid = User::create(
+            &self.state.database,
+            &username,
+            &format!("{username}@example.com"),
+            "Test",
+            "User",
+            &password,
+            admin,
+        )
+        .await
+        .unwrap();
+
+        TestUser {
+            user: User::by_uuid(&self.state.database, uuid)
+                .await
+                .unwrap()
+                .unwrap(),
+            password,
+        }
+    }
+
+    /// Enables TOTP for the user and returns the base32 encoded secret.
+    pub async fn enable_totp(&self, user: &User) -> String {
+        let secret = totp_rs::Secret::generate_secret().to_encoded().to_string();
+
+        sqlx::query("UPDATE users SET totp_enabled = true, totp_secret = $2 WHERE users.uuid = $1")
+            .bind(user.uuid)
+            .bind(&secret)
+            .execute(self.state.database.write())
+            .await
+            .unwrap();
+
+        secret
+    }
+
+    pub async fn create_session(&self, user: &User) -> String {
+        UserSession::create(
+            &self.state.database,
+            user.uuid,
+            self.ip.into(),
+            "integration-tests",
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Creates a node in its own location, talking to the given Wings instance.
+    /// The location uses a local backup configuration.
+    pub async fn create_node(&self, wings: &MockWings) -> Node {
+        let backup_configuration = BackupConfiguration::create(
+            &self.state.database,
+            &unique_name("backups"),
+            None,
+            BackupDisk::Local,
+            BackupConfigs::default(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        let location = Location::create(
+            &self.state.database,
+            Some(backup_configuration.uuid),
+            &unique_name("location"),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let uuid = Node::create(
+            &self.state.database,
+            location.uuid,
+            None,
+            &unique_name("node"),
+            true,
+            None,
+            None,
+            wings.url(),
+            None,
+            2022,
+            None,
+            8192,
+            65536,
+        )
+        .await
+        .unwrap();
+
+        Node::by_uuid(&self.state.database, uuid)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    /// The `Authorization` token Wings uses for the remote API of this node.
+    #[inline]
+    pub fn node_token(&self, node: &Node) -> String {
+        format!(
+            "{}.{}",
+            node.token_id,
+            self.state.database.decrypt(&node.token).unwrap()
+        )
+    }
+
+    pub async fn create_allocation(&self, node: &Node, port: i32) -> uuid::Uuid {
+        NodeAllocation::create(
+            &self.state.database,
+            node.uuid,
+            &"127.0.0.1".parse().unwrap(),
+            None,
+            port,
+        )
+        .await
+        .unwrap();
+
+        sqlx::query(
+            "SELECT uuid FROM node_allocations WHERE node_allocations.node_uuid = $1 AND node_allocations.port = $2",
+        )
+        .bind(node.uuid)
+        .bind(port)
+        .fetch_one(self.state.database.read())
+        .await
+        .unwrap()
+        .get("uuid")
+    }
+
+    pub async fn create_egg(&self) -> NestEgg {
+        let nest = Nest::create(
+            &self.state.database,
+            "integration-tests",
+            &unique_name("nest"),
+            None,
+        )
+        .await
+        .unwrap();
+
+        NestEgg::create(
+            self.state.database.write(),
+            nest.uuid,
+            "integration-tests",
+            &unique_name("egg"),
+            None,
+            Vec::new(),
+            NestEggConfigStartup::default(),
+            NestEggConfigStop::default(),
+            NestEggConfigScript {
+                container: "alpine:latest".to_string(),
+                entrypoint: "ash".to_string(),
+                content: String::new(),
+            },
+            NestEggConfigAllocations {
+                user_self_assign: Default::default(),
+            },
+            "./start.sh",
+            false,
+            &[],
+            indexmap::IndexMap::from([("Alpine".to_string(), "alpine:latest".to_string())]),
+            &[],
+        )
+        .await
+        .unwrap()
+    }
+
+    /// A valid admin server creation payload, tests can adjust it before sending.
+    pub fn server_payload(
+        &self,
+        owner: &User,
+        node: &Node,
+        egg: &NestEgg,
+        allocation_uuid: uuid::Uuid,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "node_uuid": node.uuid,
+            "owner_uuid": owner.uuid,
+            "egg_uuid": egg.uuid,
+            "allocation_uuid": allocation_uuid,
+            "allocation_uuids": [],
+            "start_on_completion": false,
+            "skip_scripts": true,
+            "name": unique_name("server"),
+            "limits": {
+                "cpu": 100,
+                "memory": 1024,
+                "swap": 0,
+                "disk": 4096,
+            },
+            "pinned_cpus": [],
+            "startup": egg.startup,
+            "image": "alpine:latest",
+            "feature_limits": {
+                "allocations": 1,
+                "databases": 0,
+                "backups": 2,
+                "schedules": 0,
+            },
+        })
+    }
+
+    /// Creates a server through the admin API, asserting that the request succeeds.
+    pub async fn create_server(
+        &self,
+        admin_session: &str,
+        owner: &User,
+        node: &Node,
+        egg: &NestEgg,
+        allocation_uuid: uuid::Uuid,
+    ) -> Server {
+        let response = self
+            .post("/api/admin/servers")
+            .session(admin_session)
+            .json(self.server_payload(owner, node, egg, allocation_uuid))
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+
+        let uuid = response.body["server"]["uuid"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        Server::by_uuid(&self.state.database, uuid)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    /// Reads the install/backup status of a server straight from the database.
+    pub async fn server_status(&self, server: uuid::Uuid) -> Option<String> {
+        sqlx::query("SELECT status::text AS status FROM servers WHERE servers.uuid = $1")
+            .bind(server)
+            .fetch_one(self.state.database.read())
+            .await
+            .unwrap()
+            .get("status")
+    }
+
+    /// Switches the panel to the file mail transport, see [TestApp::wait_for_mail].
+    pub async fn use_file_mail(&self, admin_session: &str) {
+        self.put("/api/admin/settings")
+            .session(admin_session)
+            .json(serde_json::json!({
+                "mail_mode": {
+                    "type": "file",
+                    "from_address": "panel@example.com",
+                    "from_name": null,
+                },
+            }))
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    /// Waits for the job worker to deliver a mail containing `text` through the file transport
+    /// and returns it. The mail is removed, so the next call does not find it again.
+    pub async fn wait_for_mail(&self, text: &str) -> String {
+        let path = self.state.env.mail_file_directory.as_deref().unwrap();
+
+        for _ in 0..100 {
+            for entry in std::fs::read_dir(path).into_iter().flatten() {
+                let path = entry.unwrap().path();
+                let mail = std::fs::read_to_string(&path).unwrap();
+
+                if mail.contains(text) {
+                    std::fs::remove_file(path).unwrap();
+
+                    return mail;
+                }
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+
+        panic!("no mail containing {text:?} was delivered");
+    }
+
+    /// Enables single sign-on against the given issuer, mapping `groups` claims onto roles.
+    pub async fn configure_oidc(
+        &self,
+        oidc: &MockOidc,
+        auto_provision: bool,
+        role_mappings: Vec<shared::settings::RoleMapping>,
+    ) {
+        let mut settings = self.state.settings.get_mut().await;
+        settings.oidc.enabled = true;
+        settings.oidc.issuer = oidc.issuer().to_string();
+        settings.oidc.client_id = crate::oidc::CLIENT_ID.to_string();
+        settings.oidc.client_secret = crate::oidc::CLIENT_SECRET.to_string();
+        settings.oidc.groups_claim = Some("groups".to_string());
+        settings.oidc.role_mappings = role_mappings;
+        settings.oidc.auto_provision = auto_provision;
+        settings.save().await.unwrap();
+    }
+
+    /// Enables LDAP authentication against the given server, users live in `ou=people,dc=example,dc=com`.
+    pub async fn configure_ldap(
+        &self,
+        ldap: &MockLdap,
+        role_mappings: Vec<shared::settings::RoleMapping>,
+    ) {
+        let mut settings = self.state.settings.get_mut().await;
+        settings.ldap.enabled = true;
+        settings.ldap.url = ldap.url().to_string();
+        settings.ldap.bind_dn = "uid={username},ou=people,dc=example,dc=com".to_string();
+        settings.ldap.groups_attribute = Some("memberOf".to_string());
+        settings.ldap.role_mappings = role_mappings;
+        settings.save().await.unwrap();
+    }
+}
@@ -0,0 +1,221 @@
+use bytes::BytesMut;
+use ldap3::asn1::{PL, StructureTag, TagClass, parse_tag, write};
+use std::sync::{Arc, Mutex};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+struct Entry {
+    dn: String,
+    password: String,
+    attributes: Vec<(String, Vec<String>)>,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: Mutex<Vec<Entry>>,
+    binds: Mutex<Vec<String>>,
+}
+
+/// A tiny LDAP server that understands simple binds and answers every search
+/// with the entry of the bound user, which is all the panel needs.
+pub struct MockLdap {
+    url: String,
+    inner: Arc<Inner>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl MockLdap {
+    pub async fn start() -> Self {
+        let inner = Arc::new(Inner::default());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ldap://{}", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn({
+            let inner = inner.clone();
+
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(serve(inner.clone(), stream));
+                }
+            }
+        });
+
+        Self { url, inner, handle }
+    }
+
+    #[inline]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn add_user(&self, dn: &str, password: &str, attributes: &[(&str, &[&str])]) {
+        self.inner.entries.lock().unwrap().push(Entry {
+            dn: dn.to_string(),
+            password: password.to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(name, values)| {
+                    (
+                        name.to_string(),
+                        values.iter().map(|v| v.to_string()).collect(),
+                    )
+                })
+                .collect(),
+        });
+    }
+
+    /// The DNs of all bind attempts so far.
+    pub fn binds(&self) -> Vec<String> {
+        self.inner.binds.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockLdap {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[inline]
+fn tag(class: TagClass, id: u64, payload: PL) -> StructureTag {
+    StructureTag { class, id, payload }
+}
+
+#[inline]
+fn octet_string(value: &str) -> StructureTag {
+    tag(TagClass::Universal, 4, PL::P(value.as_bytes().to_vec()))
+}
+
+fn ldap_result(id: u64, result_code: u8) -> StructureTag {
+    tag(
+        TagClass::Application,
+        id,
+        PL::C(vec![
+            tag(TagClass::Universal, 10, PL::P(vec![result_code])),
+            octet_string(""),
+            octet_string(""),
+        ]),
+    )
+}
+
+fn search_entry(entry: &Entry) -> StructureTag {
+    let attributes = entry
+        .attributes
+        .iter()
+        .map(|(name, values)| {
+            let values = values.iter().map(|v| octet_string(v)).collect();
+
+            tag(
+                TagClass::Universal,
+                16,
+                PL::C(vec![
+                    octet_string(name),
+                    tag(TagClass::Universal, 17, PL::C(values)),
+                ]),
+            )
+        })
+        .collect();
+
+    tag(
+        TagClass::Application,
+        4,
+        PL::C(vec![
+            octet_string(&entry.dn),
+            tag(TagClass::Universal, 16, PL::C(attributes)),
+        ]),
+    )
+}
+
+async fn send(
+    stream: &mut TcpStream,
+    message_id: &StructureTag,
+    op: StructureTag,
+) -> std::io::Result<()> {
+    let mut buf = BytesMut::new();
+    write::encode_into(
+        &mut buf,
+        tag(TagClass::Universal, 16, PL::C(vec![message_id.clone(), op])),
+    )?;
+
+    stream.write_all(&buf).await
+}
+
+async fn serve(inner: Arc<Inner>, mut stream: TcpStream) {
+    let mut buf = Vec::new();
+    let mut bound = None;
+
+    loop {
+        let (consumed, message) = match parse_tag(&buf) {
+            Ok((rest, message)) => (buf.len() - rest.len(), message),
+            Err(_) => {
+                let mut chunk = [0; 4096];
+                match stream.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                }
+
+                continue;
+            }
+        };
+        buf.drain(..consumed);
+
+        let Some(mut parts) = message.expect_constructed().map(|p| p.into_iter()) else {
+            return;
+        };
+        let (Some(message_id), Some(op)) = (parts.next(), parts.next()) else {
+            return;
+        };
+
+        let result = match (op.class, op.id) {
+            // bind request: version, name, simple authentication
+            (TagClass::Application, 0) => {
+                let fields = op.expect_constructed().unwrap_or_default();
+                let field = |i: usize| {
+                    fields
+                        .get(i)
+                        .cloned()
+                        .and_then(|f| f.expect_primitive())
+                        .map(|f| String::from_utf8_lossy(&f).to_string())
+                        .unwrap_or_default()
+                };
+                let (dn, password) = (field(1), field(2));
+                inner.binds.lock().unwrap().push(dn.clone());
+
+                bound = inner
+                    .entries
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .position(|e| e.dn.eq_ignore_ascii_case(&dn) && e.password == password);
+
+                send(
+                    &mut stream,
+                    &message_id,
+                    ldap_result(1, if bound.is_some() { 0 } else { 49 }),
+                )
+                .await
+            }
+            // search request
+            (TagClass::Application, 3) => {
+                let entry = bound.map(|i| search_entry(&inner.entries.lock().unwrap()[i]));
+
+                match entry {
+                    Some(entry) => match send(&mut stream, &message_id, entry).await {
+                        Ok(()) => send(&mut stream, &message_id, ldap_result(5, 0)).await,
+                        Err(err) => Err(err),
+                    },
+                    None => send(&mut stream, &message_id, ldap_result(5, 0)).await,
+                }
+            }
+            // unbind request
+            (TagClass::Application, 2) => return,
+            _ => Ok(()),
+        };
+
+        if result.is_err() {
+            return;
+        }
+    }
+}
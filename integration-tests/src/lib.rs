@@ -0,0 +1,16 @@
+//! End-to-end tests for the panel API.
+//!
+//! The tests boot the full router against a real database and cache and replace Wings with
+//! [wings::MockWings]. They need `TEST_DATABASE_URL` (an account allowed to create databases)
+//! and `TEST_REDIS_URL`, so they are ignored by default and run with `make test-integration`.
+
+pub mod app;
+pub mod fixtures;
+pub mod ldap;
+pub mod oidc;
+pub mod wings;
+
+pub use app::{TestApp, TestRequest, TestResponse};
+pub use ldap::MockLdap;
+pub use oidc::MockOidc;
+pub use wings::MockWings;
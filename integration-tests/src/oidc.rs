@@ -0,0 +1,281 @@
+use axum::{
+    Form, Json,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use base64::Engine;
+use ring::signature::KeyPair;
+use sha2::Digest;
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+pub const CLIENT_ID: &str = "panel";
+pub const CLIENT_SECRET: &str = "panel-secret";
+
+struct PendingCode {
+    code_challenge: String,
+    claims: serde_json::Map<String, serde_json::Value>,
+}
+
+struct Inner {
+    issuer: String,
+    key_id: String,
+    signing_key: Vec<u8>,
+    public_key: Vec<u8>,
+    unsigned: AtomicBool,
+    codes: Mutex<HashMap<String, PendingCode>>,
+    access_tokens: Mutex<HashMap<String, serde_json::Map<String, serde_json::Value>>>,
+}
+
+/// A minimal OpenID Connect issuer implementing discovery, the authorization code flow with
+/// PKCE, EdDSA signed ID tokens and the userinfo endpoint. [MockOidc::authorize] plays the
+/// part of the user consenting.
+pub struct MockOidc {
+    inner: Arc<Inner>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+/// The result of [MockOidc::authorize], the query the provider redirects back with.
+pub struct Authorization {
+    pub code: String,
+    pub state: String,
+}
+
+impl MockOidc {
+    pub async fn start() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let signing_key =
+            ring::signature::Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new())
+                .unwrap();
+        let public_key = ring::signature::Ed25519KeyPair::from_pkcs8(signing_key.as_ref())
+            .unwrap()
+            .public_key()
+            .as_ref()
+            .to_vec();
+        let inner = Arc::new(Inner {
+            issuer: format!("http://{}", listener.local_addr().unwrap()),
+            key_id: uuid::Uuid::new_v4().to_string(),
+            signing_key: signing_key.as_ref().to_vec(),
+            public_key,
+            unsigned: AtomicBool::new(false),
+            codes: Mutex::new(HashMap::new()),
+            access_tokens: Mutex::new(HashMap::new()),
+        });
+
+        let router = axum::Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/token", post(token))
+            .route("/userinfo", get(userinfo))
+            .with_state(inner.clone());
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        Self { inner, handle }
+    }
+
+    #[inline]
+    pub fn issuer(&self) -> &str {
+        &self.inner.issuer
+    }
+
+    /// Issues ID tokens with `"alg": "none"` from now on, like a forged token would.
+    pub fn issue_unsigned_tokens(&self) {
+        self.inner.unsigned.store(true, Ordering::Relaxed);
+    }
+
+    /// Approves the authorization request at `url` for a user with the given `sub` and extra claims.
+    pub fn authorize(&self, url: &str, sub: &str, claims: serde_json::Value) -> Authorization {
+        let url = reqwest::Url::parse(url).unwrap();
+        assert_eq!(
+            url.as_str()
+                .split('?')
+                .next()
+                .unwrap_or_default()
+                .trim_end_matches('/'),
+            format!("{}/authorize", self.issuer())
+        );
+
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(query["response_type"], "code");
+        assert_eq!(query["client_id"], CLIENT_ID);
+        assert_eq!(query["code_challenge_method"], "S256");
+
+        let mut id_claims = serde_json::Map::from_iter([
+            ("iss".to_string(), self.issuer().into()),
+            ("sub".to_string(), sub.into()),
+            ("aud".to_string(), CLIENT_ID.into()),
+            (
+                "exp".to_string(),
+                (std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+                    + 300)
+                    .into(),
+            ),
+            ("nonce".to_string(), query["nonce"].clone().into()),
+        ]);
+        if let serde_json::Value::Object(claims) = claims {
+            id_claims.extend(claims);
+        }
+
+        let code = uuid::Uuid::new_v4().simple().to_string();
+        self.inner.codes.lock().unwrap().insert(
+            code.clone(),
+            PendingCode {
+                code_challenge: query["code_challenge"].clone(),
+                claims: id_claims,
+            },
+        );
+
+        Authorization {
+            code,
+            state: query["state"].clone(),
+        }
+    }
+}
+
+impl Drop for MockOidc {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn discovery(State(inner): State<Arc<Inner>>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "issuer": inner.issuer,
+        "authorization_endpoint": format!("{}/authorize", inner.issuer),
+        "token_endpoint": format!("{}/token", inner.issuer),
+        "userinfo_endpoint": format!("{}/userinfo", inner.issuer),
+        "jwks_uri": format!("{}/jwks", inner.issuer),
+        "response_types_supported": ["code"],
+        "subject_types_supported": ["public"],
+        "id_token_signing_alg_values_supported": ["EdDSA"],
+    }))
+}
+
+async fn jwks(State(inner): State<Arc<Inner>>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "keys": [{
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "use": "sig",
+            "alg": "EdDSA",
+            "kid": inner.key_id,
+            "x": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(&inner.public_key),
+        }],
+    }))
+}
+
+async fn token(
+    State(inner): State<Arc<Inner>>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let expected_authorization = format!(
+        "Basic {}",
+        engine.encode(format!("{CLIENT_ID}:{CLIENT_SECRET}"))
+    );
+    if headers.get("Authorization").and_then(|h| h.to_str().ok())
+        != Some(expected_authorization.as_str())
+    {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({ "error": "invalid_client" })),
+        )
+            .into_response();
+    }
+
+    let pending = match form
+        .get("code")
+        .and_then(|code| inner.codes.lock().unwrap().remove(code))
+    {
+        Some(pending)
+            if form.get("grant_type").map(String::as_str) == Some("authorization_code") =>
+        {
+            pending
+        }
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "invalid_grant" })),
+            )
+                .into_response();
+        }
+    };
+
+    let code_challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(
+        sha2::Sha256::digest(form.get("code_verifier").cloned().unwrap_or_default()),
+    );
+    if code_challenge != pending.code_challenge {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "invalid_grant" })),
+        )
+            .into_response();
+    }
+
+    let id_token = if inner.unsigned.load(Ordering::Relaxed) {
+        let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+        format!(
+            "{}.{}.",
+            engine.encode(r#"{"alg":"none","typ":"JWT"}"#),
+            engine.encode(serde_json::to_vec(&pending.claims).unwrap())
+        )
+    } else {
+        let mut header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::EdDSA);
+        header.kid = Some(inner.key_id.clone());
+
+        jsonwebtoken::encode(
+            &header,
+            &pending.claims,
+            &jsonwebtoken::EncodingKey::from_ed_der(&inner.signing_key),
+        )
+        .unwrap()
+    };
+
+    let access_token = uuid::Uuid::new_v4().simple().to_string();
+    inner
+        .access_tokens
+        .lock()
+        .unwrap()
+        .insert(access_token.clone(), pending.claims);
+
+    Json(serde_json::json!({
+        "access_token": access_token,
+        "token_type": "Bearer",
+        "expires_in": 300,
+        "id_token": id_token,
+    }))
+    .into_response()
+}
+
+async fn userinfo(State(inner): State<Arc<Inner>>, headers: HeaderMap) -> Response {
+    let claims = headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .and_then(|token| inner.access_tokens.lock().unwrap().get(token).cloned());
+
+    match claims {
+        Some(mut claims) => {
+            for claim in ["iss", "aud", "exp", "nonce"] {
+                claims.remove(claim);
+            }
+
+            Json(claims).into_response()
+        }
+        None => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
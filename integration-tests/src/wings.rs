@@ -0,0 +1,144 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub path: String,
+    pub authorization: Option<String>,
+    pub body: serde_json::Value,
+}
+
+struct MockResponse {
+    method: Method,
+    path: String,
+    status: StatusCode,
+    body: serde_json::Value,
+}
+
+#[derive(Default)]
+struct Inner {
+    requests: Mutex<Vec<RecordedRequest>>,
+    responses: Mutex<Vec<MockResponse>>,
+}
+
+/// An in-process stand-in for the Wings daemon. Every request is recorded and answered
+/// with `200 {}` unless a response was registered for its method and path.
+pub struct MockWings {
+    url: String,
+    inner: Arc<Inner>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl MockWings {
+    pub async fn start() -> Self {
+        let inner = Arc::new(Inner::default());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let router = axum::Router::new()
+            .fallback(handle)
+            .with_state(inner.clone());
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        Self { url, inner, handle }
+    }
+
+    #[inline]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Overrides the response for all future requests matching `method` and `path`.
+    pub fn respond(
+        &self,
+        method: Method,
+        path: impl Into<String>,
+        status: StatusCode,
+        body: serde_json::Value,
+    ) {
+        let path = path.into();
+        let mut responses = self.inner.responses.lock().unwrap();
+
+        responses.retain(|r| r.method != method || r.path != path);
+        responses.push(MockResponse {
+            method,
+            path,
+            status,
+            body,
+        });
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.inner.requests.lock().unwrap().clone()
+    }
+
+    pub fn find(&self, method: Method, path: &str) -> Option<RecordedRequest> {
+        self.inner
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|r| r.method == method && r.path == path)
+            .cloned()
+    }
+
+    /// Waits for a request that may be sent from a background task, panicking after 5 seconds.
+    pub async fn wait_for(&self, method: Method, path: &str) -> RecordedRequest {
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+
+        loop {
+            if let Some(request) = self.find(method.clone(), path) {
+                return request;
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                panic!("wings never received {method} {path}");
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+        }
+    }
+}
+
+impl Drop for MockWings {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn handle(
+    State(inner): State<Arc<Inner>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let path = uri.path().to_string();
+
+    inner.requests.lock().unwrap().push(RecordedRequest {
+        method: method.clone(),
+        path: path.clone(),
+        authorization: headers
+            .get("Authorization")
+            .and_then(|h| h.to_str().ok())
+            .map(String::from),
+        body: serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+    });
+
+    let responses = inner.responses.lock().unwrap();
+    match responses
+        .iter()
+        .find(|r| r.method == method && r.path == path)
+    {
+        Some(response) => (response.status, axum::Json(response.body.clone())).into_response(),
+        None => axum::Json(serde_json::json!({})).into_response(),
+    }
+}
@@ -0,0 +1,498 @@
+use axum::http::StatusCode;
+use integration_tests::TestApp;
+
+fn totp_code(secret: &str) -> String {
+    totp_rs::TOTP::new(
+        totp_rs::Algorithm::SHA1,
+        6,
+        1,
+        30,
+        totp_rs::Secret::Encoded(secret.to_string())
+            .to_bytes()
+            .unwrap(),
+    )
+    .unwrap()
+    .generate_current()
+    .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn login_without_two_factor_sets_session() {
+    let app = TestApp::new().await;
+    let user = app.create_user(false).await;
+
+    let response = app
+        .post("/api/auth/login")
+        .json(serde_json::json!({
+            "user": user.user.username,
+            "password": "wrong password",
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.session().is_none());
+
+    let response = app
+        .post("/api/auth/login")
+        .json(serde_json::json!({
+            "user": user.user.username,
+            "password": user.password,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["type"], "completed");
+
+    let session = response
+        .session()
+        .expect("login should set a session cookie");
+    let response = app
+        .get("/api/client/account")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["user"]["uuid"], user.user.uuid.to_string());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn login_with_totp_checkpoint() {
+    let app = TestApp::new().await;
+    let user = app.create_user(false).await;
+    let secret = app.enable_totp(&user.user).await;
+
+    let response = app
+        .post("/api/auth/login")
+        .json(serde_json::json!({
+            "user": user.user.username,
+            "password": user.password,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["type"], "two_factor_required");
+    assert!(response.session().is_none());
+
+    let token = response.body["token"].as_str().unwrap().to_string();
+
+    let response = app
+        .post("/api/auth/login/checkpoint")
+        .json(serde_json::json!({
+            "code": "000000",
+            "confirmation_token": "not a token",
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    let code = totp_code(&secret);
+    let wrong_code = format!("{:06}", (code.parse::<u32>().unwrap() + 1) % 1_000_000);
+
+    let response = app
+        .post("/api/auth/login/checkpoint")
+        .json(serde_json::json!({
+            "code": wrong_code,
+            "confirmation_token": token,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.session().is_none());
+
+    let response = app
+        .post("/api/auth/login/checkpoint")
+        .json(serde_json::json!({
+            "code": code,
+            "confirmation_token": token,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["user"]["uuid"], user.user.uuid.to_string());
+
+    let session = response
+        .session()
+        .expect("checkpoint should set a session cookie");
+    app.get("/api/client/account")
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn new_devices_are_logged_and_can_require_email_confirmation() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let user = app.create_user(false).await;
+
+    let login = |user_agent: &'static str| {
+        app.post("/api/auth/login")
+            .header("User-Agent", user_agent)
+            .json(serde_json::json!({
+                "user": user.user.username,
+                "password": user.password,
+            }))
+            .send()
+    };
+    let new_devices = || async {
+        sqlx::query_scalar::<_, serde_json::Value>(
+            r#"
+            SELECT user_activities.data
+            FROM user_activities
+            WHERE user_activities.user_uuid = $1 AND user_activities.event = 'auth:login.new-device'
+            ORDER BY user_activities.created
+            "#,
+        )
+        .bind(user.user.uuid)
+        .fetch_all(app.state.database.read())
+        .await
+        .unwrap()
+    };
+
+    // the first sign in has nothing to compare with, a browser update is the same device
+    for user_agent in [
+        "Mozilla/5.0 (X11; Linux x86_64) Firefox/128.0",
+        "Mozilla/5.0 (X11; Linux x86_64) Firefox/131.0.2",
+    ] {
+        let response = login(user_agent).await;
+        response.assert_status(StatusCode::OK);
+        assert_eq!(response.body["type"], "completed");
+    }
+    assert!(new_devices().await.is_empty());
+
+    let response = login("Mozilla/5.0 (Macintosh; Intel Mac OS X 14_5) Safari/605.1.15").await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["type"], "completed");
+    let logged = new_devices().await;
+    assert_eq!(logged.len(), 1);
+    assert_eq!(logged[0]["changes"]["new_user_agent"], true);
+    assert_eq!(logged[0]["changes"]["new_ip"], false);
+    assert_eq!(logged[0]["verification"], "none");
+
+    app.use_file_mail(&admin_session).await;
+    app.put("/api/admin/settings")
+        .session(&admin_session)
+        .json(serde_json::json!({
+            "security": {
+                "new_device_notifications": false,
+                "new_device_verification": "email",
+            },
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = login("Mozilla/5.0 (Windows NT 10.0; Win64; x64) Edg/126.0").await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["type"], "device_verification_required");
+    assert!(response.session().is_none());
+    let token = response.body["token"].as_str().unwrap().to_string();
+    assert_eq!(new_devices().await[1]["verification"], "email");
+
+    let mail = app.wait_for_mail("Confirm New Device").await;
+    assert!(mail.contains(&format!("To: {}", user.user.email)));
+    let code = mail
+        .split("Enter this code to confirm the sign in: ")
+        .nth(1)
+        .unwrap()
+        .chars()
+        .take(8)
+        .collect::<String>();
+
+    let response = app
+        .post("/api/auth/login/device")
+        .json(serde_json::json!({
+            "code": "AAAAAAAA",
+            "confirmation_token": token,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.session().is_none());
+
+    let response = app
+        .post("/api/auth/login/device")
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) Edg/126.0",
+        )
+        .json(serde_json::json!({
+            "code": code.to_lowercase(),
+            "confirmation_token": token,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["user"]["uuid"], user.user.uuid.to_string());
+    let session = response
+        .session()
+        .expect("confirming the device should set a session cookie");
+    app.get("/api/client/account")
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    // the confirmation can not be replayed for another session
+    let response = app
+        .post("/api/auth/login/device")
+        .json(serde_json::json!({
+            "code": code,
+            "confirmation_token": token,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.session().is_none());
+
+    // once confirmed the device signs in directly
+    let response = login("Mozilla/5.0 (Windows NT 10.0; Win64; x64) Edg/127.0").await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["type"], "completed");
+    assert_eq!(new_devices().await.len(), 2);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn client_api_requires_session() {
+    let app = TestApp::new().await;
+
+    app.get("/api/client/account")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    app.get("/api/client/account")
+        .session("invalid")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn failed_logins_are_delayed_then_lock_the_account() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let user = app.create_user(false).await;
+    let node = app.create_node(&app.wings).await;
+
+    app.put("/api/admin/settings")
+        .session(&admin_session)
+        .json(serde_json::json!({
+            "security": {
+                "login_delay_after": 2,
+                "login_max_delay": 60,
+                "login_lockout_after": 3,
+                "login_lockout_duration": 600,
+            },
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let login = |password: &str| {
+        app.post("/api/auth/login")
+            .json(serde_json::json!({
+                "user": user.user.username,
+                "password": password,
+            }))
+            .send()
+    };
+
+    login("wrong password")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    login("wrong password")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    // the correct password is not even checked while the account is delayed
+    let response = login(&user.password).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert!(
+        response.body["errors"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("too many failed attempts")
+    );
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    login("wrong password")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let response = login(&user.password).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert!(
+        response.body["errors"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("account is temporarily locked")
+    );
+
+    // the lockout covers sftp password auth as well
+    app.post("/api/remote/sftp/auth")
+        .bearer(&app.node_token(&node))
+        .json(serde_json::json!({
+            "type": "password",
+            "username": format!("{}.{}", user.user.username, uuid::Uuid::new_v4()),
+            "password": user.password,
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::TOO_MANY_REQUESTS);
+
+    let events = sqlx::query_as::<_, (String, serde_json::Value)>(
+        r#"
+        SELECT user_activities.event, user_activities.data
+        FROM user_activities
+        WHERE user_activities.user_uuid = $1
+        ORDER BY user_activities.created
+        "#,
+    )
+    .bind(user.user.uuid)
+    .fetch_all(app.state.database.read())
+    .await
+    .unwrap();
+    let events = events
+        .iter()
+        .map(|(event, data)| (event.as_str(), data["failures"].as_i64()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        [
+            ("auth:fail", Some(1)),
+            ("auth:fail", Some(2)),
+            ("auth:fail", Some(3)),
+            ("auth:lockout", Some(3)),
+        ]
+    );
+
+    let response = app
+        .get("/api/admin/users/lockouts?locked=true")
+        .session(&admin_session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["lockouts"]["total"], 1);
+    let lockout = &response.body["lockouts"]["data"][0];
+    assert_eq!(lockout["user"]["uuid"], user.user.uuid.to_string());
+    assert_eq!(lockout["failures"], 3);
+    assert_eq!(lockout["last_ip"], app.ip.to_string());
+    assert!(lockout["locked_until"].is_string());
+
+    let clear = || {
+        app.delete(format!("/api/admin/users/{}/lockout", user.user.uuid))
+            .session(&admin_session)
+            .send()
+    };
+    clear().await.assert_status(StatusCode::OK);
+    clear().await.assert_status(StatusCode::NOT_FOUND);
+
+    let response = login(&user.password).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["type"], "completed");
+
+    // a completed sign in forgets earlier failures
+    login("wrong password")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    login(&user.password).await.assert_status(StatusCode::OK);
+    let response = app
+        .get("/api/admin/users/lockouts")
+        .session(&admin_session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["lockouts"]["total"], 0);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn failed_logins_for_unknown_names_are_throttled_the_same_way() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let user = app.create_user(false).await;
+    let node = app.create_node(&app.wings).await;
+
+    app.put("/api/admin/settings")
+        .session(&admin_session)
+        .json(serde_json::json!({
+            "security": {
+                "login_delay_after": 0,
+                "login_lockout_after": 2,
+                "login_lockout_duration": 600,
+            },
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let unknown = format!("unknown-{}", uuid::Uuid::new_v4().simple());
+    for name in [&user.user.username, &unknown] {
+        for _ in 0..2 {
+            app.post("/api/auth/login")
+                .json(serde_json::json!({
+                    "user": name,
+                    "password": "wrong password",
+                }))
+                .send()
+                .await
+                .assert_status(StatusCode::BAD_REQUEST);
+        }
+
+        let response = app
+            .post("/api/auth/login")
+            .json(serde_json::json!({
+                "user": name,
+                "password": "wrong password",
+            }))
+            .send()
+            .await;
+        response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        assert!(
+            response.body["errors"][0]
+                .as_str()
+                .unwrap()
+                .starts_with("account is temporarily locked")
+        );
+    }
+
+    // sftp failures are logged with the client address wings sends along
+    app.delete(format!("/api/admin/users/{}/lockout", user.user.uuid))
+        .session(&admin_session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post("/api/remote/sftp/auth")
+        .bearer(&app.node_token(&node))
+        .json(serde_json::json!({
+            "type": "password",
+            "username": format!("{}.{}", user.user.username, uuid::Uuid::new_v4()),
+            "password": "wrong password",
+            "ip": "192.0.2.7",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::EXPECTATION_FAILED);
+
+    let ip = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT host(user_activities.ip)
+        FROM user_activities
+        WHERE user_activities.user_uuid = $1 AND user_activities.data->>'using' = 'sftp'
+        "#,
+    )
+    .bind(user.user.uuid)
+    .fetch_one(app.state.database.read())
+    .await
+    .unwrap();
+    assert_eq!(ip, "192.0.2.7");
+}
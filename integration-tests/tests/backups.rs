@@ -0,0 +1,838 @@
+use axum::http::{Method, StatusCode};
+use integration_tests::TestApp;
+use sha2::Digest;
+use shared::{
+    jobs::{Job, server::VerifyServerBackup},
+    models::server_backup::ServerBackup,
+};
+use sqlx::Row;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn backup_create_and_restore_callbacks() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let owner = app.create_user(false).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&admin_session, &owner.user, &node, &egg, allocation)
+        .await;
+    let session = app.create_session(&owner.user).await;
+    let node_token = app.node_token(&node);
+
+    let response = app
+        .post(format!("/api/client/servers/{}/backups", server.uuid))
+        .session(&session)
+        .json(serde_json::json!({
+            "name": "nightly",
+            "ignored_files": ["*.log"],
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+
+    let backup = response.body["backup"]["uuid"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let request = app
+        .wings
+        .wait_for(
+            Method::POST,
+            &format!("/api/servers/{}/backup", server.uuid),
+        )
+        .await;
+    assert_eq!(request.body["uuid"], backup);
+    assert_eq!(request.body["adapter"], "wings");
+    assert_eq!(request.body["ignore"], "*.log");
+
+    // restoring is only possible once wings reported the backup as completed
+    let restore_path = format!(
+        "/api/client/servers/{}/backups/{backup}/restore",
+        server.uuid
+    );
+    app.post(&restore_path)
+        .session(&session)
+        .json(serde_json::json!({ "truncate_directory": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::EXPECTATION_FAILED);
+
+    app.post(format!("/api/remote/backups/{backup}"))
+        .json(serde_json::json!({
+            "checksum": "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            "checksum_type": "sha1",
+            "size": 1024,
+            "files": 3,
+            "successful": true,
+            "parts": [],
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    app.post(format!("/api/remote/backups/{backup}"))
+        .bearer(&node_token)
+        .json(serde_json::json!({
+            "checksum": "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            "checksum_type": "sha1",
+            "size": 1024,
+            "files": 3,
+            "successful": true,
+            "parts": [],
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let row = sqlx::query(
+        "SELECT successful, checksum, bytes, files, completed IS NOT NULL AS completed FROM server_backups WHERE server_backups.uuid = $1::uuid",
+    )
+    .bind(&backup)
+    .fetch_one(app.state.database.read())
+    .await
+    .unwrap();
+    assert!(row.get::<bool, _>("successful"));
+    assert!(row.get::<bool, _>("completed"));
+    assert_eq!(
+        row.get::<Option<String>, _>("checksum").as_deref(),
+        Some("sha1:da39a3ee5e6b4b0d3255bfef95601890afd80709")
+    );
+    assert_eq!(row.get::<i64, _>("bytes"), 1024);
+    assert_eq!(row.get::<i64, _>("files"), 3);
+
+    app.post(&restore_path)
+        .session(&session)
+        .json(serde_json::json!({ "truncate_directory": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let request = app
+        .wings
+        .find(
+            Method::POST,
+            &format!("/api/servers/{}/backup/{backup}/restore", server.uuid),
+        )
+        .expect("wings should be asked to restore the backup");
+    assert_eq!(request.body["truncate_directory"], true);
+    assert_eq!(
+        app.server_status(server.uuid).await.as_deref(),
+        Some("RESTORING_BACKUP")
+    );
+
+    // the server is locked while the restore is running
+    app.post(&restore_path)
+        .session(&session)
+        .json(serde_json::json!({ "truncate_directory": false }))
+        .send()
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    let callback_path = format!("/api/remote/backups/{backup}/restore");
+    app.post(&callback_path)
+        .bearer(&node_token)
+        .json(serde_json::json!({ "successful": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(app.server_status(server.uuid).await, None);
+
+    app.post(&callback_path)
+        .bearer(&node_token)
+        .json(serde_json::json!({ "successful": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::EXPECTATION_FAILED);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn failed_backup_callback_marks_backup_failed() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let other_node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&admin_session, &admin.user, &node, &egg, allocation)
+        .await;
+
+    let response = app
+        .post(format!("/api/client/servers/{}/backups", server.uuid))
+        .session(&admin_session)
+        .json(serde_json::json!({
+            "name": "broken",
+            "ignored_files": [],
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+
+    let backup = response.body["backup"]["uuid"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let payload = serde_json::json!({
+        "checksum": "",
+        "checksum_type": "sha1",
+        "size": 0,
+        "files": 0,
+        "successful": false,
+        "parts": [],
+    });
+
+    // nodes can only report on their own backups
+    app.post(format!("/api/remote/backups/{backup}"))
+        .bearer(&app.node_token(&other_node))
+        .json(payload.clone())
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    app.post(format!("/api/remote/backups/{backup}"))
+        .bearer(&app.node_token(&node))
+        .json(payload)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let row = sqlx::query(
+        "SELECT successful, completed IS NOT NULL AS completed FROM server_backups WHERE server_backups.uuid = $1::uuid",
+    )
+    .bind(&backup)
+    .fetch_one(app.state.database.read())
+    .await
+    .unwrap();
+    assert!(!row.get::<bool, _>("successful"));
+    assert!(row.get::<bool, _>("completed"));
+}
+
+async fn insert_backup(
+    app: &TestApp,
+    server: &shared::models::server::Server,
+    days_ago: i32,
+    successful: bool,
+    locked: bool,
+    completed: bool,
+) -> uuid::Uuid {
+    sqlx::query(
+        r#"
+        INSERT INTO server_backups (server_uuid, node_uuid, backup_configuration_uuid, name, successful, locked, ignored_files, disk, completed, created)
+        VALUES ($1, $2, $3, $4, $5, $6, '{}', 'LOCAL', CASE WHEN $7 THEN NOW() ELSE NULL END, NOW() - make_interval(days => $8))
+        RETURNING uuid
+        "#,
+    )
+    .bind(server.uuid)
+    .bind(server.node.uuid)
+    .bind(server.backup_configuration().unwrap().uuid)
+    .bind(format!("{days_ago} days ago"))
+    .bind(successful)
+    .bind(locked)
+    .bind(completed)
+    .bind(days_ago)
+    .fetch_one(app.state.database.write())
+    .await
+    .unwrap()
+    .get("uuid")
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn deleting_a_backup_queues_the_removal_of_its_archive() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&session, &admin.user, &node, &egg, allocation)
+        .await;
+    let backup = insert_backup(&app, &server, 0, true, false, true).await;
+
+    let path = format!("/api/client/servers/{}/backups/{backup}", server.uuid);
+    app.delete(&path)
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    // the backup is gone right away, the archive is removed by a job
+    let remaining = ServerBackup::all_by_server_uuid(&app.state.database, server.uuid)
+        .await
+        .unwrap();
+    assert!(remaining.is_empty());
+    app.wings
+        .wait_for(Method::DELETE, &format!("/api/backups/{backup}"))
+        .await;
+
+    app.delete(&path)
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::CONFLICT);
+}
+
+fn expired(response: &integration_tests::TestResponse) -> Vec<String> {
+    response.body["expired_backups"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|backup| backup["uuid"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn retention_prunes_expired_backups() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&session, &admin.user, &node, &egg, allocation)
+        .await;
+
+    let today = insert_backup(&app, &server, 0, true, false, true).await;
+    let yesterday = insert_backup(&app, &server, 1, true, false, true).await;
+    let two_days = insert_backup(&app, &server, 2, true, false, true).await;
+    let failed = insert_backup(&app, &server, 3, false, false, true).await;
+    let ten_days = insert_backup(&app, &server, 10, true, false, true).await;
+    let locked = insert_backup(&app, &server, 20, true, true, true).await;
+    let running = insert_backup(&app, &server, 30, false, false, false).await;
+
+    let retention_path = format!("/api/admin/servers/{}/backup-retention", server.uuid);
+
+    // without any rules nothing expires
+    let response = app.get(&retention_path).session(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    assert!(expired(&response).is_empty());
+
+    app.patch(format!(
+        "/api/admin/backup-configurations/{}",
+        server.backup_configuration().unwrap().uuid
+    ))
+    .session(&session)
+    .json(serde_json::json!({ "retention": { "keep_last": 2 } }))
+    .send()
+    .await
+    .assert_status(StatusCode::OK);
+
+    let response = app.get(&retention_path).session(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.body["backup_retention"].is_null());
+    assert_eq!(response.body["effective_backup_retention"]["keep_last"], 2);
+    assert_eq!(
+        expired(&response),
+        [two_days, failed, ten_days].map(|uuid| uuid.to_string())
+    );
+
+    // a server override replaces the rules of the backup configuration
+    app.put(&retention_path)
+        .session(&session)
+        .json(serde_json::json!({ "backup_retention": { "keep_last": 0 } }))
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    app.put(&retention_path)
+        .session(&session)
+        .json(serde_json::json!({ "backup_retention": { "keep_daily": 3 } }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app.get(&retention_path).session(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["backup_retention"]["keep_daily"], 3);
+    assert_eq!(
+        expired(&response),
+        [failed, ten_days].map(|uuid| uuid.to_string())
+    );
+
+    let queued = ServerBackup::prune(&app.state.database).await.unwrap();
+    assert_eq!(queued, 2);
+
+    for backup in [failed, ten_days] {
+        app.wings
+            .wait_for(Method::DELETE, &format!("/api/backups/{backup}"))
+            .await;
+    }
+    for backup in [today, yesterday, two_days, locked, running] {
+        assert!(
+            app.wings
+                .find(Method::DELETE, &format!("/api/backups/{backup}"))
+                .is_none()
+        );
+    }
+
+    let remaining = ServerBackup::all_by_server_uuid(&app.state.database, server.uuid)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 5);
+
+    let response = app.get(&retention_path).session(&session).send().await;
+    assert!(expired(&response).is_empty());
+
+    // clearing the override falls back to the backup configuration again
+    app.put(&retention_path)
+        .session(&session)
+        .json(serde_json::json!({ "backup_retention": null }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app.get(&retention_path).session(&session).send().await;
+    assert_eq!(expired(&response), [two_days.to_string()]);
+}
+
+async fn insert_remote_backup(
+    app: &TestApp,
+    server: &shared::models::server::Server,
+    disk: &str,
+    checksum: &str,
+) -> uuid::Uuid {
+    sqlx::query(
+        r#"
+        INSERT INTO server_backups (server_uuid, node_uuid, backup_configuration_uuid, name, successful, ignored_files, checksum, disk, completed)
+        VALUES ($1, $2, $3, $4, true, '{}', $5, $6::backup_disk, NOW())
+        RETURNING uuid
+        "#,
+    )
+    .bind(server.uuid)
+    .bind(server.node.uuid)
+    .bind(server.backup_configuration().unwrap().uuid)
+    .bind(format!("{disk} backup"))
+    .bind(checksum)
+    .bind(disk)
+    .fetch_one(app.state.database.write())
+    .await
+    .unwrap()
+    .get("uuid")
+}
+
+/// Waits until the background job has recorded the outcome of a verification.
+async fn wait_for_verification(
+    app: &TestApp,
+    session: &str,
+    server: uuid::Uuid,
+    backup: uuid::Uuid,
+) -> serde_json::Value {
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+
+    loop {
+        let response = app
+            .get(format!("/api/client/servers/{server}/backups/{backup}"))
+            .session(session)
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+
+        if response.body["backup"]["verification_status"] != "pending" {
+            return response.body["backup"].clone();
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            panic!("backup {backup} was never verified");
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn verify_s3_backup_recomputes_checksum() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&session, &admin.user, &node, &egg, allocation)
+        .await;
+
+    // the mock wings server doubles as a path style S3 endpoint
+    app.patch(format!(
+        "/api/admin/backup-configurations/{}",
+        server.backup_configuration().unwrap().uuid
+    ))
+    .session(&session)
+    .json(serde_json::json!({
+        "backup_configs": {
+            "s3": {
+                "access_key": "access",
+                "secret_key": "secret",
+                "bucket": "backups",
+                "region": "us-east-1",
+                "endpoint": app.wings.url(),
+                "path_style": true,
+                "part_size": 1073741824,
+            },
+        },
+    }))
+    .send()
+    .await
+    .assert_status(StatusCode::OK);
+
+    let archive = serde_json::to_vec(&serde_json::json!("archive")).unwrap();
+    let checksum = format!("sha256:{:x}", sha2::Sha256::digest(&archive));
+    let intact = insert_remote_backup(&app, &server, "S3", &checksum).await;
+    let corrupted = insert_remote_backup(&app, &server, "S3", "sha1:0000").await;
+    let missing = insert_remote_backup(&app, &server, "S3", &checksum).await;
+
+    for backup in [intact, corrupted] {
+        app.wings.respond(
+            Method::GET,
+            format!("/backups/{}", ServerBackup::s3_path(server.uuid, backup)),
+            StatusCode::OK,
+            serde_json::json!("archive"),
+        );
+    }
+    app.wings.respond(
+        Method::GET,
+        format!("/backups/{}", ServerBackup::s3_path(server.uuid, missing)),
+        StatusCode::NOT_FOUND,
+        serde_json::json!({}),
+    );
+
+    for backup in [intact, corrupted, missing] {
+        app.post(format!(
+            "/api/client/servers/{}/backups/{backup}/verify",
+            server.uuid
+        ))
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    }
+
+    let backup = wait_for_verification(&app, &session, server.uuid, intact).await;
+    assert_eq!(backup["verification_status"], "successful");
+    assert!(backup["verification_error"].is_null());
+    assert!(!backup["verified"].is_null());
+
+    let backup = wait_for_verification(&app, &session, server.uuid, corrupted).await;
+    assert_eq!(backup["verification_status"], "failed");
+    assert!(
+        backup["verification_error"]
+            .as_str()
+            .unwrap()
+            .starts_with("checksum mismatch")
+    );
+
+    let backup = wait_for_verification(&app, &session, server.uuid, missing).await;
+    assert_eq!(backup["verification_status"], "failed");
+    assert_eq!(backup["verification_error"], "backup does not exist on S3");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn verify_backup_requires_s3() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&session, &admin.user, &node, &egg, allocation)
+        .await;
+
+    let restic = insert_remote_backup(&app, &server, "RESTIC", "sha256:abc").await;
+    let local = insert_backup(&app, &server, 0, true, false, true).await;
+    let pending = insert_remote_backup(&app, &server, "S3", "sha256:abc").await;
+
+    // wings cannot check restic repositories, so only backups stored on S3 can be verified
+    for backup in [restic, local] {
+        app.post(format!(
+            "/api/client/servers/{}/backups/{backup}/verify",
+            server.uuid
+        ))
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::EXPECTATION_FAILED);
+    }
+
+    // a pending verification is neither restarted nor queued twice
+    sqlx::query("UPDATE server_backups SET verification_status = 'PENDING' WHERE uuid = $1")
+        .bind(pending)
+        .execute(app.state.database.write())
+        .await
+        .unwrap();
+    app.post(format!(
+        "/api/client/servers/{}/backups/{pending}/verify",
+        server.uuid
+    ))
+    .session(&session)
+    .send()
+    .await
+    .assert_status(StatusCode::CONFLICT);
+    let queued: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM queued_jobs WHERE payload->>'backup_uuid' = $1")
+            .bind(pending.to_string())
+            .fetch_one(app.state.database.read())
+            .await
+            .unwrap();
+    assert_eq!(queued, 0);
+
+    // a verification of a backup that got deleted in the meantime does not stay pending
+    sqlx::query("UPDATE server_backups SET deleted = NOW() WHERE uuid = $1")
+        .bind(pending)
+        .execute(app.state.database.write())
+        .await
+        .unwrap();
+    VerifyServerBackup {
+        server_uuid: server.uuid,
+        backup_uuid: pending,
+    }
+    .run(&app.state)
+    .await
+    .unwrap();
+    let status: Option<String> =
+        sqlx::query_scalar("SELECT verification_status::text FROM server_backups WHERE uuid = $1")
+            .bind(pending)
+            .fetch_one(app.state.database.read())
+            .await
+            .unwrap();
+    assert_eq!(status, None);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn restore_backup_into_another_server() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let owner = app.create_user(false).await;
+    let stranger = app.create_user(false).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let session = app.create_session(&owner.user).await;
+    let node = app.create_node(&app.wings).await;
+    let other_node = app.create_node(&app.wings).await;
+    let egg = app.create_egg().await;
+
+    let mut servers = Vec::new();
+    for (node, user, port) in [
+        (&node, &owner.user, 25565),
+        (&node, &owner.user, 25566),
+        (&node, &stranger.user, 25567),
+        (&other_node, &owner.user, 25565),
+    ] {
+        let allocation = app.create_allocation(node, port).await;
+        servers.push(
+            app.create_server(&admin_session, user, node, &egg, allocation)
+                .await,
+        );
+    }
+    let [source, target, foreign, remote] = servers.try_into().ok().unwrap();
+
+    let backup = insert_backup(&app, &source, 0, true, false, true).await;
+    let restore_path = format!(
+        "/api/client/servers/{}/backups/{backup}/restore",
+        source.uuid
+    );
+
+    // the owner of both servers is required
+    app.post(&restore_path)
+        .session(&session)
+        .json(serde_json::json!({
+            "truncate_directory": false,
+            "target_server_uuid": foreign.uuid,
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    app.post(&restore_path)
+        .session(&session)
+        .json(serde_json::json!({
+            "truncate_directory": true,
+            "target_server_uuid": target.uuid,
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let request = app
+        .wings
+        .find(
+            Method::POST,
+            &format!("/api/servers/{}/backup/{backup}/restore", target.uuid),
+        )
+        .expect("wings should restore the backup into the target server");
+    assert_eq!(request.body["adapter"], "wings");
+    assert_eq!(request.body["truncate_directory"], true);
+    assert_eq!(app.server_status(source.uuid).await, None);
+    assert_eq!(
+        app.server_status(target.uuid).await.as_deref(),
+        Some("RESTORING_BACKUP")
+    );
+
+    // the callback only names the backup, so it can not be restored twice on one node at once
+    app.post(&restore_path)
+        .session(&session)
+        .json(serde_json::json!({ "truncate_directory": false }))
+        .send()
+        .await
+        .assert_status(StatusCode::CONFLICT);
+    assert_eq!(app.server_status(source.uuid).await, None);
+
+    app.post(format!("/api/remote/backups/{backup}/restore"))
+        .bearer(&app.node_token(&node))
+        .json(serde_json::json!({ "successful": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(app.server_status(target.uuid).await, None);
+
+    // local backups of another node are streamed from the node that stores them
+    app.post(&restore_path)
+        .session(&session)
+        .json(serde_json::json!({
+            "truncate_directory": false,
+            "target_server_uuid": remote.uuid,
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let request = app
+        .wings
+        .find(
+            Method::POST,
+            &format!("/api/servers/{}/backup/{backup}/restore", remote.uuid),
+        )
+        .expect("wings should restore the backup into the remote server");
+    assert_eq!(request.body["adapter"], "s3");
+    assert!(
+        request.body["download_url"]
+            .as_str()
+            .unwrap()
+            .contains("/download/backup?token=")
+    );
+
+    // the other node may only report on the backup while it is restoring it
+    app.post(format!("/api/remote/backups/{backup}/restore"))
+        .bearer(&app.node_token(&other_node))
+        .json(serde_json::json!({ "successful": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(app.server_status(remote.uuid).await, None);
+    app.post(format!("/api/remote/backups/{backup}/restore"))
+        .bearer(&app.node_token(&other_node))
+        .json(serde_json::json!({ "successful": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // detached backups of deleted servers can be restored by admins
+    sqlx::query("UPDATE server_backups SET server_uuid = NULL WHERE server_backups.uuid = $1")
+        .bind(backup)
+        .execute(app.state.database.write())
+        .await
+        .unwrap();
+
+    let detached_path = format!("/api/admin/nodes/{}/backups/{backup}/restore", node.uuid);
+    app.post(&detached_path)
+        .session(&session)
+        .json(serde_json::json!({
+            "server_uuid": target.uuid,
+            "truncate_directory": false,
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    app.post(&detached_path)
+        .session(&admin_session)
+        .json(serde_json::json!({
+            "server_uuid": foreign.uuid,
+            "truncate_directory": false,
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(
+        app.server_status(foreign.uuid).await.as_deref(),
+        Some("RESTORING_BACKUP")
+    );
+
+    // the server is locked while the restore is running
+    app.post(&detached_path)
+        .session(&admin_session)
+        .json(serde_json::json!({
+            "server_uuid": foreign.uuid,
+            "truncate_directory": false,
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::EXPECTATION_FAILED);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn restores_into_another_server_release_it_when_they_cannot_start() {
+    let app = TestApp::new().await;
+    let owner = app.create_user(true).await;
+    let session = app.create_session(&owner.user).await;
+    let node = app.create_node(&app.wings).await;
+    let egg = app.create_egg().await;
+
+    let mut servers = Vec::new();
+    for port in [25565, 25566] {
+        let allocation = app.create_allocation(&node, port).await;
+        servers.push(
+            app.create_server(&session, &owner.user, &node, &egg, allocation)
+                .await,
+        );
+    }
+    let [source, target] = servers.try_into().ok().unwrap();
+
+    let backup = insert_backup(&app, &source, 0, true, false, true).await;
+    let restore_path = format!(
+        "/api/client/servers/{}/backups/{backup}/restore",
+        source.uuid
+    );
+    let payload = serde_json::json!({
+        "truncate_directory": false,
+        "target_server_uuid": target.uuid,
+    });
+
+    app.wings.respond(
+        Method::POST,
+        format!("/api/servers/{}/backup/{backup}/restore", target.uuid),
+        StatusCode::INTERNAL_SERVER_ERROR,
+        serde_json::json!({ "error": "disk full" }),
+    );
+    app.post(&restore_path)
+        .session(&session)
+        .json(payload.clone())
+        .send()
+        .await
+        .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(app.server_status(target.uuid).await, None);
+
+    sqlx::query("UPDATE servers SET destination_node_uuid = $2 WHERE servers.uuid = $1")
+        .bind(target.uuid)
+        .bind(node.uuid)
+        .execute(app.state.database.write())
+        .await
+        .unwrap();
+    app.post(&restore_path)
+        .session(&session)
+        .json(payload)
+        .send()
+        .await
+        .assert_status(StatusCode::CONFLICT);
+    assert_eq!(app.server_status(target.uuid).await, None);
+}
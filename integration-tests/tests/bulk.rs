@@ -0,0 +1,259 @@
+use axum::http::{Method, StatusCode};
+use integration_tests::{TestApp, TestResponse};
+use shared::models::{
+    nest_egg_variable::{NestEggVariable, NestEggVariableKind},
+    server::Server,
+};
+
+async fn wait_for_job(app: &TestApp, session: &str, job: &str) -> TestResponse {
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+
+    loop {
+        let response = app
+            .get(format!("/api/admin/servers/bulk/{job}"))
+            .session(session)
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+
+        if !response.body["job"]["finished"].is_null() {
+            return response;
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            panic!("bulk job {job} never finished");
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn bulk_actions_run_on_matched_servers() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let other_node = app.create_node(&app.wings).await;
+    let egg = app.create_egg().await;
+    NestEggVariable::create(
+        app.state.database.write(),
+        egg.uuid,
+        "Max Players",
+        None,
+        0,
+        "MAX_PLAYERS",
+        Some("10"),
+        true,
+        true,
+        &["required".to_string(), "integer".to_string()],
+        NestEggVariableKind::Integer,
+        &[],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let mut servers = Vec::new();
+    for port in [25565, 25566] {
+        let allocation = app.create_allocation(&node, port).await;
+        servers.push(
+            app.create_server(&session, &admin.user, &node, &egg, allocation)
+                .await,
+        );
+    }
+    let allocation = app.create_allocation(&other_node, 25565).await;
+    let other = app
+        .create_server(&session, &admin.user, &other_node, &egg, allocation)
+        .await;
+
+    // only the servers of the first node match the filter
+    let response = app
+        .post("/api/admin/servers/bulk")
+        .session(&session)
+        .json(serde_json::json!({
+            "filter": { "node_uuid": node.uuid },
+            "action": { "type": "power", "signal": "start" },
+            "concurrency": 1,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["job"]["total"], 2);
+    let job = response.body["job"]["uuid"].as_str().unwrap().to_string();
+
+    let response = wait_for_job(&app, &session, &job).await;
+    assert_eq!(response.body["job"]["successful"], 2);
+    assert_eq!(response.body["job"]["failed"], 0);
+    for server in &servers {
+        let request = app
+            .wings
+            .find(Method::POST, &format!("/api/servers/{}/power", server.uuid))
+            .expect("wings should receive the power action");
+        assert_eq!(request.body["action"], "start");
+    }
+    assert!(
+        app.wings
+            .find(Method::POST, &format!("/api/servers/{}/power", other.uuid))
+            .is_none()
+    );
+
+    let response = app
+        .post("/api/admin/servers/bulk")
+        .session(&session)
+        .json(serde_json::json!({
+            "server_uuids": [servers[0].uuid, other.uuid],
+            "action": { "type": "suspend" },
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let job = response.body["job"]["uuid"].as_str().unwrap().to_string();
+    wait_for_job(&app, &session, &job).await;
+
+    for (uuid, suspended) in [
+        (servers[0].uuid, true),
+        (servers[1].uuid, false),
+        (other.uuid, true),
+    ] {
+        let server = Server::by_uuid(&app.state.database, uuid)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(server.suspended, suspended);
+    }
+
+    // a failing server does not stop the others, its error ends up in the results
+    app.wings.respond(
+        Method::POST,
+        format!("/api/servers/{}/reinstall", servers[1].uuid),
+        StatusCode::INTERNAL_SERVER_ERROR,
+        serde_json::json!({ "error": "disk full" }),
+    );
+    let response = app
+        .post("/api/admin/servers/bulk")
+        .session(&session)
+        .json(serde_json::json!({
+            "filter": { "egg_uuid": egg.uuid },
+            "action": { "type": "reinstall", "truncate_directory": false },
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let job = response.body["job"]["uuid"].as_str().unwrap().to_string();
+
+    let response = wait_for_job(&app, &session, &job).await;
+    assert_eq!(response.body["job"]["total"], 3);
+    assert_eq!(response.body["job"]["successful"], 2);
+    assert_eq!(response.body["job"]["failed"], 1);
+
+    let response = app
+        .get(format!("/api/admin/servers/bulk/{job}/results"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let results = response.body["results"]["data"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    let failed = results
+        .iter()
+        .find(|result| result["server_uuid"] == servers[1].uuid.to_string())
+        .unwrap();
+    assert_eq!(failed["successful"], false);
+    assert_eq!(failed["error"], "disk full");
+
+    let server = Server::by_uuid(&app.state.database, servers[1].uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(server.status.is_none());
+
+    // variables are validated per server against the rules of the egg
+    let response = app
+        .post("/api/admin/servers/bulk")
+        .session(&session)
+        .json(serde_json::json!({
+            "server_uuids": [servers[0].uuid],
+            "action": { "type": "variable", "env_variable": "MAX_PLAYERS", "value": "many" },
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let job = response.body["job"]["uuid"].as_str().unwrap().to_string();
+    let response = wait_for_job(&app, &session, &job).await;
+    assert_eq!(response.body["job"]["failed"], 1);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn bulk_jobs_require_a_valid_selection() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&session, &admin.user, &node, &egg, allocation)
+        .await;
+
+    for (payload, status) in [
+        (
+            serde_json::json!({
+                "server_uuids": [server.uuid],
+                "filter": { "node_uuid": node.uuid },
+                "action": { "type": "suspend" },
+            }),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            serde_json::json!({ "action": { "type": "suspend" } }),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            serde_json::json!({
+                "filter": { "node_uuid": uuid::Uuid::new_v4() },
+                "action": { "type": "suspend" },
+            }),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            serde_json::json!({
+                "server_uuids": [server.uuid, uuid::Uuid::new_v4()],
+                "action": { "type": "suspend" },
+            }),
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            serde_json::json!({
+                "server_uuids": [server.uuid],
+                "action": { "type": "mount", "mount_uuid": uuid::Uuid::new_v4() },
+            }),
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            serde_json::json!({
+                "server_uuids": [server.uuid],
+                "action": { "type": "suspend" },
+                "concurrency": 0,
+            }),
+            StatusCode::BAD_REQUEST,
+        ),
+    ] {
+        app.post("/api/admin/servers/bulk")
+            .session(&session)
+            .json(payload)
+            .send()
+            .await
+            .assert_status(status);
+    }
+
+    let non_admin = app.create_user(false).await;
+    let non_admin_session = app.create_session(&non_admin.user).await;
+    app.get("/api/admin/servers/bulk")
+        .session(&non_admin_session)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,422 @@
+use axum::http::{Method, StatusCode};
+use integration_tests::TestApp;
+use shared::models::{
+    database_host::{DatabaseHost, DatabaseType},
+    quota::Quotas,
+    server::Server,
+    server_schedule::ServerSchedule,
+    server_schedule_step::ServerScheduleStep,
+    user::User,
+};
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn admins_clone_servers_from_a_fresh_backup() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let owner = app.create_user(false).await;
+    let subuser = app.create_user(false).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let other_node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let other_allocation = app.create_allocation(&other_node, 25565).await;
+    let egg = app.create_egg().await;
+    let source = app
+        .create_server(&admin_session, &owner.user, &node, &egg, allocation)
+        .await;
+
+    let schedule = ServerSchedule::create(
+        &app.state.database,
+        source.uuid,
+        "restart",
+        true,
+        Vec::new(),
+        wings_api::ScheduleCondition::None,
+    )
+    .await
+    .unwrap();
+    ServerScheduleStep::create(
+        &app.state.database,
+        schedule.uuid,
+        wings_api::ScheduleActionInner::Sleep { duration: 5 },
+        1,
+    )
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO server_subusers (server_uuid, user_uuid, permissions, ignored_files) VALUES ($1, $2, '{control.start}', '{}')",
+    )
+    .bind(source.uuid)
+    .bind(subuser.user.uuid)
+    .execute(app.state.database.write())
+    .await
+    .unwrap();
+
+    let clone_path = format!("/api/admin/servers/{}/clone", source.uuid);
+    app.post(&clone_path)
+        .session(&app.create_session(&owner.user).await)
+        .json(serde_json::json!({ "node_uuid": other_node.uuid }))
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    // the clone counts towards the quotas of its owner
+    app.patch(format!("/api/admin/users/{}", owner.user.uuid))
+        .session(&admin_session)
+        .json(serde_json::json!({ "quotas": { "servers": 1 } }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let response = app
+        .post(&clone_path)
+        .session(&admin_session)
+        .json(serde_json::json!({
+            "node_uuid": other_node.uuid,
+            "allocation_uuid": other_allocation,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(
+        response.body["errors"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("servers quota exceeded")
+    );
+    app.patch(format!("/api/admin/users/{}", owner.user.uuid))
+        .session(&admin_session)
+        .json(serde_json::json!({ "quotas": {} }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app
+        .post(&clone_path)
+        .session(&admin_session)
+        .json(serde_json::json!({
+            "node_uuid": other_node.uuid,
+            "allocation_uuid": other_allocation,
+            "name": "staging",
+            "subusers": true,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+
+    let clone_uuid: uuid::Uuid = response.body["server"]["uuid"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let backup = response.body["backup_uuid"].as_str().unwrap().to_string();
+
+    let clone = Server::by_uuid(&app.state.database, clone_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(clone.name, "staging");
+    assert_eq!(clone.node.uuid, other_node.uuid);
+    assert_eq!(clone.owner.uuid, owner.user.uuid);
+    assert_eq!(clone.egg.uuid, source.egg.uuid);
+    assert_eq!(clone.memory, source.memory);
+    assert_eq!(clone.disk, source.disk);
+    assert_eq!(clone.backup_limit, source.backup_limit);
+
+    let schedules = ServerSchedule::all_by_server_uuid(&app.state.database, clone.uuid)
+        .await
+        .unwrap();
+    assert_eq!(schedules.len(), 1);
+    assert_eq!(schedules[0].name, "restart");
+    let steps = ServerScheduleStep::all_by_schedule_uuid(&app.state.database, schedules[0].uuid)
+        .await
+        .unwrap();
+    assert_eq!(steps.len(), 1);
+
+    let subusers: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM server_subusers WHERE server_uuid = $1 AND user_uuid = $2",
+    )
+    .bind(clone.uuid)
+    .bind(subuser.user.uuid)
+    .fetch_one(app.state.database.read())
+    .await
+    .unwrap();
+    assert_eq!(subusers, 1);
+
+    // the clone waits for the backup of the source before its files are restored
+    assert_eq!(
+        app.server_status(clone.uuid).await.as_deref(),
+        Some("RESTORING_BACKUP")
+    );
+    let request = app
+        .wings
+        .wait_for(
+            Method::POST,
+            &format!("/api/servers/{}/backup", source.uuid),
+        )
+        .await;
+    assert_eq!(request.body["uuid"], backup);
+    assert!(
+        app.wings
+            .find(
+                Method::POST,
+                &format!("/api/servers/{clone_uuid}/backup/{backup}/restore"),
+            )
+            .is_none()
+    );
+
+    app.post(format!("/api/remote/backups/{backup}"))
+        .bearer(&app.node_token(&node))
+        .json(serde_json::json!({
+            "checksum": "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            "checksum_type": "sha1",
+            "size": 1024,
+            "files": 3,
+            "successful": true,
+            "parts": [],
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let request = app
+        .wings
+        .wait_for(
+            Method::POST,
+            &format!("/api/servers/{clone_uuid}/backup/{backup}/restore"),
+        )
+        .await;
+    assert_eq!(request.body["adapter"], "s3");
+    assert_eq!(request.body["truncate_directory"], true);
+
+    app.post(format!("/api/remote/backups/{backup}/restore"))
+        .bearer(&app.node_token(&other_node))
+        .json(serde_json::json!({ "successful": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(app.server_status(clone_uuid).await, None);
+    assert_eq!(app.server_status(source.uuid).await, None);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn owners_clone_servers_within_quotas() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let owner = app.create_user(false).await;
+    let subuser = app.create_user(false).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let session = app.create_session(&owner.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    app.create_allocation(&node, 25566).await;
+    let egg = app.create_egg().await;
+    let source = app
+        .create_server(&admin_session, &owner.user, &node, &egg, allocation)
+        .await;
+    sqlx::query(
+        "INSERT INTO server_subusers (server_uuid, user_uuid, permissions, ignored_files) VALUES ($1, $2, '{control.start}', '{}')",
+    )
+    .bind(source.uuid)
+    .bind(subuser.user.uuid)
+    .execute(app.state.database.write())
+    .await
+    .unwrap();
+
+    let backup: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO server_backups (server_uuid, node_uuid, backup_configuration_uuid, name, successful, locked, ignored_files, disk, completed, created)
+        VALUES ($1, $2, $3, 'nightly', true, false, '{}', 'LOCAL', NOW(), NOW())
+        RETURNING uuid
+        "#,
+    )
+    .bind(source.uuid)
+    .bind(node.uuid)
+    .bind(source.backup_configuration().unwrap().uuid)
+    .fetch_one(app.state.database.write())
+    .await
+    .unwrap();
+
+    let clone_path = format!("/api/client/servers/{}/clone", source.uuid);
+    let payload = serde_json::json!({
+        "name": "staging",
+        "backup_uuid": backup,
+    });
+
+    // subusers cannot clone the server
+    app.post(&clone_path)
+        .session(&app.create_session(&subuser.user).await)
+        .json(payload.clone())
+        .send()
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    // accounts without quotas cannot create servers
+    app.post(&clone_path)
+        .session(&session)
+        .json(payload.clone())
+        .send()
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    let quotas = |servers| Quotas {
+        servers: Some(servers),
+        memory: Some(16384),
+        disk: Some(16384),
+        ..Default::default()
+    };
+    User::update_quotas(&app.state.database, owner.user.uuid, &quotas(1))
+        .await
+        .unwrap();
+    app.post(&clone_path)
+        .session(&session)
+        .json(payload.clone())
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    User::update_quotas(&app.state.database, owner.user.uuid, &quotas(2))
+        .await
+        .unwrap();
+
+    // a fresh backup counts against the backup limit of the server
+    sqlx::query("UPDATE servers SET backup_limit = 1 WHERE uuid = $1")
+        .bind(source.uuid)
+        .execute(app.state.database.write())
+        .await
+        .unwrap();
+    let response = app
+        .post(&clone_path)
+        .session(&session)
+        .json(serde_json::json!({ "name": "staging" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::EXPECTATION_FAILED);
+    assert_eq!(
+        response.body["errors"][0],
+        "maximum number of backups reached"
+    );
+
+    let response = app
+        .post(&clone_path)
+        .session(&session)
+        .json(payload.clone())
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["backup_uuid"], backup.to_string());
+
+    let clone_uuid = response.body["server"]["uuid"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let clone = Server::by_uuid(&app.state.database, clone_uuid.parse().unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(clone.owner.uuid, owner.user.uuid);
+    assert_eq!(clone.node.uuid, node.uuid);
+
+    // subusers are only copied on request
+    let subusers: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM server_subusers WHERE server_uuid = $1")
+            .bind(clone.uuid)
+            .fetch_one(app.state.database.read())
+            .await
+            .unwrap();
+    assert_eq!(subusers, 0);
+
+    // an existing backup is restored right away
+    let request = app
+        .wings
+        .wait_for(
+            Method::POST,
+            &format!("/api/servers/{clone_uuid}/backup/{backup}/restore"),
+        )
+        .await;
+    assert_eq!(request.body["adapter"], "wings");
+    assert!(
+        app.wings
+            .find(
+                Method::POST,
+                &format!("/api/servers/{}/backup", source.uuid),
+            )
+            .is_none()
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn failed_clones_are_removed_again() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let owner = app.create_user(false).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let other_allocation = app.create_allocation(&node, 25566).await;
+    let egg = app.create_egg().await;
+    let source = app
+        .create_server(&admin_session, &owner.user, &node, &egg, allocation)
+        .await;
+
+    // nothing listens on the database host, so its databases cannot be copied
+    let database_host = DatabaseHost::create(
+        &app.state.database,
+        "unreachable",
+        false,
+        DatabaseType::Postgres,
+        None,
+        "127.0.0.1",
+        None,
+        1,
+        "root",
+        "password",
+    )
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO server_databases (server_uuid, database_host_uuid, name, username, password) VALUES ($1, $2, 's1_data', 'u1_data', ''::bytea)",
+    )
+    .bind(source.uuid)
+    .bind(database_host.uuid)
+    .execute(app.state.database.write())
+    .await
+    .unwrap();
+
+    let response = app
+        .post(format!("/api/admin/servers/{}/clone", source.uuid))
+        .session(&admin_session)
+        .json(serde_json::json!({
+            "node_uuid": node.uuid,
+            "allocation_uuid": other_allocation,
+            "name": "staging",
+            "databases": true,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+
+    let servers: Vec<uuid::Uuid> =
+        sqlx::query_scalar("SELECT uuid FROM servers WHERE owner_uuid = $1")
+            .bind(owner.user.uuid)
+            .fetch_all(app.state.database.read())
+            .await
+            .unwrap();
+    assert_eq!(servers, vec![source.uuid]);
+
+    let created = app
+        .wings
+        .requests()
+        .into_iter()
+        .filter(|request| request.method == Method::POST && request.path == "/api/servers")
+        .map(|request| request.body["uuid"].as_str().unwrap().to_string())
+        .find(|uuid| *uuid != source.uuid.to_string())
+        .unwrap();
+    assert!(
+        app.wings
+            .find(Method::DELETE, &format!("/api/servers/{created}"))
+            .is_some()
+    );
+}
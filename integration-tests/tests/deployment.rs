@@ -0,0 +1,261 @@
+use axum::http::StatusCode;
+use integration_tests::TestApp;
+
+async fn dry_run(
+    app: &TestApp,
+    session: &str,
+    deploy: serde_json::Value,
+) -> integration_tests::TestResponse {
+    app.post("/api/admin/servers/deploy/dry-run")
+        .session(session)
+        .json(serde_json::json!({ "deploy": deploy, "memory": 1024, "disk": 4096 }))
+        .send()
+        .await
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn deployment_strategies_pick_nodes() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let owner = app.create_user(false).await;
+    let egg = app.create_egg().await;
+
+    let first = app.create_node(&app.wings).await;
+    let second = app.create_node(&app.wings).await;
+    let first_allocation = app.create_allocation(&first, 25565).await;
+    app.create_allocation(&first, 25566).await;
+    app.create_allocation(&second, 25565).await;
+    app.create_allocation(&second, 25566).await;
+
+    app.create_server(&session, &owner.user, &first, &egg, first_allocation)
+        .await;
+
+    let locations = [first.location.uuid, second.location.uuid];
+    let picked = |strategy: &'static str| {
+        let app = &app;
+        let session = &session;
+
+        async move {
+            let response = dry_run(
+                app,
+                session,
+                serde_json::json!({ "location_uuids": locations, "strategy": strategy }),
+            )
+            .await;
+            response.assert_status(StatusCode::OK);
+            response.body["node"]["uuid"].as_str().unwrap().to_string()
+        }
+    };
+
+    let second_uuid = second.uuid.to_string();
+    assert_eq!(picked("least_memory_used").await, second_uuid);
+    assert_eq!(picked("least_servers").await, second_uuid);
+    assert_eq!(picked("round_robin").await, second_uuid);
+
+    // create a bigger server through the deploy block, it lands on the empty node
+    let mut payload = app.server_payload(&owner.user, &first, &egg, first_allocation);
+    payload.as_object_mut().unwrap().remove("node_uuid");
+    payload.as_object_mut().unwrap().remove("allocation_uuid");
+    payload["limits"]["memory"] = serde_json::json!(2048);
+    payload["deploy"] = serde_json::json!({
+        "location_uuids": locations,
+        "strategy": "least_memory_used",
+        "additional_allocations": 1,
+    });
+    let response = app
+        .post("/api/admin/servers")
+        .session(&session)
+        .json(payload.clone())
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["server"]["node"]["uuid"], second_uuid);
+
+    let server = shared::models::server::Server::by_uuid(
+        &app.state.database,
+        response.body["server"]["uuid"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        server.allocation.as_ref().map(|a| a.allocation.port),
+        Some(25565)
+    );
+
+    let first_uuid = first.uuid.to_string();
+    assert_eq!(picked("least_memory_used").await, first_uuid);
+    // both nodes have one server, the older node wins the tie
+    assert_eq!(picked("least_servers").await, first_uuid);
+    assert_eq!(picked("round_robin").await, first_uuid);
+
+    // the second node has no free allocations left
+    let response = app
+        .post("/api/admin/servers")
+        .session(&session)
+        .json(payload)
+        .send()
+        .await;
+    response.assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn deployment_respects_ports_and_node_flags() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    app.create_allocation(&node, 25565).await;
+    app.create_allocation(&node, 30005).await;
+
+    let response = dry_run(
+        &app,
+        &session,
+        serde_json::json!({
+            "location_uuids": [node.location.uuid],
+            "port_ranges": [{ "start_port": 30000, "end_port": 30010 }],
+        }),
+    )
+    .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["allocations"][0]["port"], 30005);
+
+    dry_run(
+        &app,
+        &session,
+        serde_json::json!({
+            "location_uuids": [node.location.uuid],
+            "port_ranges": [{ "start_port": 30000, "end_port": 30010 }],
+            "additional_allocations": 1,
+        }),
+    )
+    .await
+    .assert_status(StatusCode::CONFLICT);
+
+    dry_run(
+        &app,
+        &session,
+        serde_json::json!({
+            "location_uuids": [node.location.uuid],
+            "port_ranges": [{ "start_port": 30010, "end_port": 30000 }],
+        }),
+    )
+    .await
+    .assert_status(StatusCode::BAD_REQUEST);
+
+    let deploy = serde_json::json!({ "location_uuids": [node.location.uuid] });
+    let set_node = |column: &'static str, value: Option<&'static str>| {
+        let app = &app;
+        let node = &node;
+
+        async move {
+            sqlx::query(&format!("UPDATE nodes SET {column} = $1 WHERE uuid = $2"))
+                .bind(value)
+                .bind(node.uuid)
+                .execute(app.state.database.write())
+                .await
+                .unwrap();
+        }
+    };
+
+    set_node("maintenance_message", Some("upgrading")).await;
+    dry_run(&app, &session, deploy.clone())
+        .await
+        .assert_status(StatusCode::CONFLICT);
+    set_node("maintenance_message", None).await;
+
+    sqlx::query("UPDATE nodes SET public = false WHERE uuid = $1")
+        .bind(node.uuid)
+        .execute(app.state.database.write())
+        .await
+        .unwrap();
+    dry_run(&app, &session, deploy)
+        .await
+        .assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn deploy_block_excludes_explicit_placement() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+
+    let mut payload = app.server_payload(&admin.user, &node, &egg, allocation);
+    payload["deploy"] = serde_json::json!({ "location_uuids": [node.location.uuid] });
+    app.post("/api/admin/servers")
+        .session(&session)
+        .json(payload.clone())
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let object = payload.as_object_mut().unwrap();
+    object.remove("node_uuid");
+    object.remove("allocation_uuid");
+    object.remove("deploy");
+    app.post("/api/admin/servers")
+        .session(&session)
+        .json(payload)
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn concurrent_deployments_do_not_overcommit_a_node() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let owner = app.create_user(false).await;
+    let egg = app.create_egg().await;
+
+    // the node has 8192 MiB of memory, enough for two of the servers below
+    let node = app.create_node(&app.wings).await;
+    let mut allocation = None;
+    for port in 25565..25571 {
+        allocation = Some(app.create_allocation(&node, port).await);
+    }
+
+    let mut payload = app.server_payload(&owner.user, &node, &egg, allocation.unwrap());
+    payload.as_object_mut().unwrap().remove("node_uuid");
+    payload.as_object_mut().unwrap().remove("allocation_uuid");
+    payload["limits"]["memory"] = serde_json::json!(3072);
+    payload["deploy"] = serde_json::json!({ "location_uuids": [node.location.uuid] });
+
+    let responses = futures_util::future::join_all((0..6).map(|_| {
+        app.post("/api/admin/servers")
+            .session(&session)
+            .json(payload.clone())
+            .send()
+    }))
+    .await;
+
+    let created = responses
+        .iter()
+        .filter(|response| response.status == StatusCode::OK)
+        .count();
+    assert_eq!(created, 2);
+    for response in responses.iter().filter(|r| r.status != StatusCode::OK) {
+        response.assert_status(StatusCode::CONFLICT);
+    }
+
+    let memory: i64 =
+        sqlx::query_scalar("SELECT SUM(servers.memory)::BIGINT FROM servers WHERE node_uuid = $1")
+            .bind(node.uuid)
+            .fetch_one(app.state.database.read())
+            .await
+            .unwrap();
+    assert_eq!(memory, 2 * 3072);
+}
@@ -0,0 +1,774 @@
+use axum::http::StatusCode;
+use integration_tests::TestApp;
+use shared::models::{
+    nest::Nest,
+    nest_egg::{
+        NestEgg, NestEggConfigAllocations, NestEggConfigAllocationsUserSelfAssign,
+        NestEggConfigScript, NestEggConfigStartup, NestEggConfigStop, ProcessConfigurationFile,
+        ProcessConfigurationFileReplacement, ServerConfigurationFileParser,
+    },
+    nest_egg_revision::NestEggRevision,
+    nest_egg_variable::{NestEggVariable, NestEggVariableKind},
+};
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn exported_eggs_round_trip_through_the_importer() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let nest = Nest::create(&app.state.database, "integration-tests", "exports", None)
+        .await
+        .unwrap();
+
+    let egg = NestEgg::create(
+        app.state.database.write(),
+        nest.uuid,
+        "integration-tests",
+        "Minecraft",
+        Some("A block game"),
+        vec![ProcessConfigurationFile {
+            file: "server.properties".to_string(),
+            parser: ServerConfigurationFileParser::Properties,
+            replace: vec![ProcessConfigurationFileReplacement {
+                r#match: "server-port".to_string(),
+                if_value: None,
+                replace_with: serde_json::json!("{{server.build.default.port}}"),
+            }],
+        }],
+        NestEggConfigStartup {
+            done: vec![")! For help, type ".to_string()],
+            strip_ansi: true,
+        },
+        NestEggConfigStop {
+            r#type: "command".to_string(),
+            value: Some("stop".to_string()),
+        },
+        NestEggConfigScript {
+            container: "alpine:latest".to_string(),
+            entrypoint: "ash".to_string(),
+            content: "echo installing".to_string(),
+        },
+        NestEggConfigAllocations {
+            user_self_assign: NestEggConfigAllocationsUserSelfAssign {
+                enabled: true,
+                require_primary_allocation: false,
+                start_port: 30000,
+                end_port: 31000,
+            },
+        },
+        "java -jar server.jar",
+        true,
+        &["eula".to_string()],
+        indexmap::IndexMap::from([("Java 21".to_string(), "java:21".to_string())]),
+        &["*.jar".to_string()],
+    )
+    .await
+    .unwrap();
+    NestEggVariable::create(
+        app.state.database.write(),
+        egg.uuid,
+        "Server Jar",
+        Some("The jar to start"),
+        3,
+        "SERVER_JARFILE",
+        Some("server.jar"),
+        true,
+        true,
+        &["required".to_string(), "string".to_string()],
+        NestEggVariableKind::String,
+        &[],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let export_path = format!("/api/admin/nests/{}/eggs/{}/export", nest.uuid, egg.uuid);
+    for (format, version) in [("ptdl_v2", "PTDL_v2"), ("native", "CALAGOPUS_v1")] {
+        let response = app
+            .get(format!("{export_path}?format={format}"))
+            .session(&session)
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+        assert_eq!(response.body["meta"]["version"], version);
+
+        let mut exported = response.body.clone();
+        exported["name"] = serde_json::json!(format!("Minecraft ({format})"));
+
+        let response = app
+            .post(format!("/api/admin/nests/{}/eggs/import", nest.uuid))
+            .session(&session)
+            .json(exported)
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+
+        let imported = NestEgg::by_uuid(
+            &app.state.database,
+            response.body["egg"]["uuid"]
+                .as_str()
+                .unwrap()
+                .parse()
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(imported.author, egg.author);
+        assert_eq!(imported.description, egg.description);
+        assert_eq!(imported.startup, egg.startup);
+        assert_eq!(imported.features, egg.features);
+        assert_eq!(imported.docker_images, egg.docker_images);
+        assert_eq!(imported.file_denylist, egg.file_denylist);
+        assert!(imported.force_outgoing_ip);
+        for (imported, original) in [
+            (
+                serde_json::to_value(&imported.config_files),
+                serde_json::to_value(&egg.config_files),
+            ),
+            (
+                serde_json::to_value(&imported.config_startup),
+                serde_json::to_value(&egg.config_startup),
+            ),
+            (
+                serde_json::to_value(&imported.config_stop),
+                serde_json::to_value(&egg.config_stop),
+            ),
+            (
+                serde_json::to_value(&imported.config_script),
+                serde_json::to_value(&egg.config_script),
+            ),
+        ] {
+            assert_eq!(imported.unwrap(), original.unwrap());
+        }
+
+        let variables = NestEggVariable::all_by_egg_uuid(app.state.database.read(), imported.uuid)
+            .await
+            .unwrap();
+        assert_eq!(variables.len(), 1);
+        assert_eq!(variables[0].env_variable, "SERVER_JARFILE");
+        assert_eq!(
+            variables[0].description.as_deref(),
+            Some("The jar to start")
+        );
+        assert_eq!(variables[0].default_value.as_deref(), Some("server.jar"));
+        assert_eq!(variables[0].rules, ["required", "string"]);
+
+        // only the native format keeps panel specific settings
+        let native = format == "native";
+        assert_eq!(imported.config_allocations.user_self_assign.enabled, native);
+        assert_eq!(variables[0].order, if native { 3 } else { 0 });
+    }
+}
+
+fn source_egg(name: &str, startup: &str) -> serde_json::Value {
+    serde_json::json!({
+        "meta": { "version": "PTDL_v2" },
+        "name": name,
+        "author": "catalogue@example.com",
+        "description": null,
+        "docker_images": { "Java 21": "java:21" },
+        "startup": startup,
+        "config": {
+            "files": "{}",
+            "startup": "{\"done\": \"Done\"}",
+            "stop": "stop",
+            "logs": "{}",
+        },
+        "scripts": {
+            "installation": {
+                "script": "echo installing",
+                "container": "alpine:latest",
+                "entrypoint": "ash",
+            },
+        },
+        "variables": [{
+            "name": "Version",
+            "description": "",
+            "env_variable": "VERSION",
+            "default_value": "latest",
+            "user_viewable": true,
+            "user_editable": true,
+            "rules": "required|string",
+        }],
+    })
+}
+
+async fn sync_source(app: &TestApp, session: &str, path: &str) -> serde_json::Value {
+    let previous =
+        app.get(path).session(session).send().await.body["egg_source"]["last_synced"].clone();
+    app.post(format!("{path}/sync"))
+        .session(session)
+        .json(serde_json::json!({}))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+    loop {
+        let response = app.get(path).session(session).send().await;
+        let source = &response.body["egg_source"];
+        if !source["last_synced"].is_null() && source["last_synced"] != previous {
+            assert!(source["last_sync_error"].is_null());
+            return source["last_sync_report"].clone();
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            panic!("egg source {path} never synced");
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn egg_sources_sync_without_overwriting_local_changes() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let nest = Nest::create(&app.state.database, "integration-tests", "catalogue", None)
+        .await
+        .unwrap();
+
+    let directory = std::env::temp_dir().join(format!("egg-source-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(directory.join("minecraft")).unwrap();
+    let write = |path: &str, egg: serde_json::Value| {
+        std::fs::write(directory.join(path), serde_json::to_vec(&egg).unwrap()).unwrap()
+    };
+    write(
+        "minecraft/paper.json",
+        source_egg("Paper", "java -jar paper.jar"),
+    );
+    write(
+        "minecraft/vanilla.json",
+        source_egg("Vanilla", "java -jar server.jar"),
+    );
+
+    let response = app
+        .post(format!("/api/admin/nests/{}/egg-sources", nest.uuid))
+        .session(&session)
+        .json(serde_json::json!({
+            "name": "catalogue",
+            "url": directory.to_str().unwrap(),
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let path = format!(
+        "/api/admin/nests/{}/egg-sources/{}",
+        nest.uuid,
+        response.body["egg_source"]["uuid"].as_str().unwrap()
+    );
+
+    // a dry run only reports what would be imported
+    let response = app
+        .post(format!("{path}/sync"))
+        .session(&session)
+        .json(serde_json::json!({ "dry_run": true }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.body["report"]["created"].as_array().unwrap().len(),
+        2
+    );
+    assert!(
+        NestEgg::by_nest_uuid_with_pagination(&app.state.database, nest.uuid, 1, 10, None)
+            .await
+            .unwrap()
+            .data
+            .is_empty()
+    );
+
+    let report = sync_source(&app, &session, &path).await;
+    assert_eq!(report["created"][0]["path"], "minecraft/paper.json");
+    assert_eq!(report["created"][1]["path"], "minecraft/vanilla.json");
+    let egg_uuid = |index: usize| -> uuid::Uuid {
+        report["created"][index]["egg_uuid"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap()
+    };
+    let (paper, vanilla) = (egg_uuid(0), egg_uuid(1));
+    let egg = NestEgg::by_uuid(&app.state.database, paper)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(egg.startup, "java -jar paper.jar");
+    assert_eq!(egg.config_stop.value.as_deref(), Some("stop"));
+
+    // upstream changes are applied to untouched eggs
+    write(
+        "minecraft/paper.json",
+        source_egg("Paper", "java -Xms128M -jar paper.jar"),
+    );
+    let report = sync_source(&app, &session, &path).await;
+    assert_eq!(report["updated"][0]["egg_uuid"], paper.to_string());
+    assert_eq!(
+        report["updated"][0]["changes"],
+        serde_json::json!(["startup"])
+    );
+    assert_eq!(report["unchanged"][0]["egg_uuid"], vanilla.to_string());
+    let egg = NestEgg::by_uuid(&app.state.database, paper)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(egg.startup, "java -Xms128M -jar paper.jar");
+
+    // local changes are kept and reported
+    app.patch(format!("/api/admin/nests/{}/eggs/{paper}", nest.uuid))
+        .session(&session)
+        .json(serde_json::json!({ "startup": "java -Xmx4G -jar paper.jar" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    write(
+        "minecraft/paper.json",
+        source_egg("Paper", "java -Xms256M -jar paper.jar"),
+    );
+    std::fs::remove_file(directory.join("minecraft/vanilla.json")).unwrap();
+
+    let report = sync_source(&app, &session, &path).await;
+    assert_eq!(report["modified"][0]["egg_uuid"], paper.to_string());
+    assert_eq!(report["missing"][0]["egg_uuid"], vanilla.to_string());
+    let egg = NestEgg::by_uuid(&app.state.database, paper)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(egg.startup, "java -Xmx4G -jar paper.jar");
+    assert!(
+        NestEgg::by_uuid(&app.state.database, vanilla)
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn egg_revisions_can_be_diffed_rolled_back_and_pinned() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&session, &admin.user, &node, &egg, allocation)
+        .await;
+
+    // eggs created through the api start with their first revision
+    let mut transaction = app.state.database.write().begin().await.unwrap();
+    NestEggRevision::record(&mut transaction, egg.uuid, None)
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+
+    let egg_path = format!("/api/admin/nests/{}/eggs/{}", server.nest.uuid, egg.uuid);
+    for payload in [
+        serde_json::json!({
+            "config_script": {
+                "container": "alpine:latest",
+                "entrypoint": "ash",
+                "content": "echo v2",
+            },
+        }),
+        serde_json::json!({ "startup": "./start.sh --nogui" }),
+    ] {
+        app.patch(&egg_path)
+            .session(&session)
+            .json(payload)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    let response = app
+        .get(format!("{egg_path}/revisions"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["revisions"]["total"], 3);
+    assert_eq!(response.body["revisions"]["data"][0]["revision"], 3);
+    assert_eq!(
+        response.body["revisions"]["data"][0]["user_uuid"],
+        admin.user.uuid.to_string()
+    );
+    assert!(response.body["revisions"]["data"][0]["data"].is_null());
+
+    let response = app
+        .get(format!("{egg_path}/revisions/diff?from=1&to=3"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let mut paths = response.body["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| change["path"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(paths, ["scripts.installation.script", "startup"]);
+
+    let response = app
+        .get(format!("{egg_path}/revisions/1"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["revision"]["data"]["startup"], "./start.sh");
+    let first_revision = response.body["revision"]["uuid"].clone();
+
+    // pinned servers keep installing with the configuration of their revision
+    let pin_path = format!("/api/admin/servers/{}/egg-revision", server.uuid);
+    let install_path = format!("/api/remote/servers/{}/install", server.uuid);
+    app.put(&pin_path)
+        .session(&session)
+        .json(serde_json::json!({ "egg_revision_uuid": uuid::Uuid::new_v4() }))
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    for (egg_revision_uuid, script) in [
+        (first_revision.clone(), ""),
+        (serde_json::Value::Null, "echo v2"),
+    ] {
+        app.put(&pin_path)
+            .session(&session)
+            .json(serde_json::json!({ "egg_revision_uuid": egg_revision_uuid }))
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+
+        let response = app
+            .get(&install_path)
+            .bearer(&app.node_token(&node))
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+        assert_eq!(response.body["script"], script);
+    }
+
+    let response = app
+        .post(format!("{egg_path}/revisions/1/rollback"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["revision"]["revision"], 4);
+
+    let egg = NestEgg::by_uuid(&app.state.database, egg.uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(egg.startup, "./start.sh");
+    assert_eq!(egg.config_script.content, "");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn startup_variables_are_validated_by_kind_and_secrets_are_encrypted() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&session, &admin.user, &node, &egg, allocation)
+        .await;
+
+    let variables_path = format!(
+        "/api/admin/nests/{}/eggs/{}/variables",
+        server.nest.uuid, egg.uuid
+    );
+    for (name, env_variable, kind, options) in [
+        ("Query Port", "QUERY_PORT", "port", serde_json::json!([])),
+        (
+            "Difficulty",
+            "DIFFICULTY",
+            "select",
+            serde_json::json!(["easy", "hard"]),
+        ),
+        (
+            "Rcon Password",
+            "RCON_PASSWORD",
+            "secret",
+            serde_json::json!([]),
+        ),
+    ] {
+        app.post(&variables_path)
+            .session(&session)
+            .json(serde_json::json!({
+                "name": name,
+                "order": 0,
+                "env_variable": env_variable,
+                "user_viewable": true,
+                "user_editable": true,
+                "rules": ["nullable", "string"],
+                "kind": kind,
+                "options": options,
+                "ui_hints": { "placeholder": name },
+            }))
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    app.post(&variables_path)
+        .session(&session)
+        .json(serde_json::json!({
+            "name": "Broken Select",
+            "order": 0,
+            "env_variable": "BROKEN_SELECT",
+            "user_viewable": true,
+            "user_editable": true,
+            "rules": ["nullable", "string"],
+            "kind": "select",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let startup_path = format!("/api/client/servers/{}/startup/variables", server.uuid);
+    for (env_variable, value, status) in [
+        ("QUERY_PORT", "70000", StatusCode::BAD_REQUEST),
+        ("QUERY_PORT", "25566", StatusCode::OK),
+        ("DIFFICULTY", "peaceful", StatusCode::BAD_REQUEST),
+        ("DIFFICULTY", "hard", StatusCode::OK),
+        ("RCON_PASSWORD", "hunter2", StatusCode::OK),
+    ] {
+        app.put(&startup_path)
+            .session(&session)
+            .json(serde_json::json!({ "env_variable": env_variable, "value": value }))
+            .send()
+            .await
+            .assert_status(status);
+    }
+
+    // the admin and wings write paths check the kind as well
+    app.put(format!("/api/admin/servers/{}/variables", server.uuid))
+        .session(&session)
+        .json(serde_json::json!({ "env_variable": "QUERY_PORT", "value": "70000" }))
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    app.put(format!(
+        "/api/remote/servers/{}/startup/variables",
+        server.uuid
+    ))
+    .bearer(&app.node_token(&node))
+    .json(serde_json::json!({ "env_variable": "DIFFICULTY", "value": "peaceful" }))
+    .send()
+    .await
+    .assert_status(StatusCode::BAD_REQUEST);
+
+    let response = app.get(&startup_path).session(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    let secret = response.body["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|variable| variable["env_variable"] == "RCON_PASSWORD")
+        .unwrap();
+    assert_eq!(secret["kind"], "secret");
+    assert_eq!(secret["value"], "");
+    assert_eq!(secret["ui_hints"]["placeholder"], "Rcon Password");
+
+    let (value, encrypted_value): (String, Option<Vec<u8>>) = sqlx::query_as(
+        r#"
+        SELECT server_variables.value, server_variables.encrypted_value
+        FROM server_variables
+        JOIN nest_egg_variables ON nest_egg_variables.uuid = server_variables.variable_uuid
+        WHERE server_variables.server_uuid = $1 AND nest_egg_variables.env_variable = 'RCON_PASSWORD'
+        "#,
+    )
+    .bind(server.uuid)
+    .fetch_one(app.state.database.read())
+    .await
+    .unwrap();
+    assert_eq!(value, "");
+    assert!(encrypted_value.is_some_and(|value| value != b"hunter2"));
+
+    let response = app
+        .get(format!("/api/remote/servers/{}", server.uuid))
+        .bearer(&app.node_token(&node))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let environment = &response.body["settings"]["environment"];
+    assert_eq!(environment["RCON_PASSWORD"], "hunter2");
+    assert_eq!(environment["QUERY_PORT"], "25566");
+    assert_eq!(environment["DIFFICULTY"], "hard");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn egg_sources_cannot_escape_their_location() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let nest = Nest::create(&app.state.database, "integration-tests", "escapes", None)
+        .await
+        .unwrap();
+
+    let directory = std::env::temp_dir().join(format!("egg-source-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(
+        directory.join("paper.json"),
+        serde_json::to_vec(&source_egg("Paper", "java -jar paper.jar")).unwrap(),
+    )
+    .unwrap();
+
+    let index = axum::Router::new()
+        .route(
+            "/index.json",
+            axum::routing::get(|| async {
+                axum::Json(serde_json::json!([
+                    "eggs/paper.json",
+                    "http://example.com/paper.json"
+                ]))
+            }),
+        )
+        .route(
+            "/redirect.json",
+            axum::routing::get(|| async {
+                axum::response::Redirect::temporary("http://example.com/index.json")
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, index).await });
+
+    let dry_run = |url: String| {
+        let app = &app;
+        let session = &session;
+        let nest = &nest;
+
+        async move {
+            let response = app
+                .post(format!("/api/admin/nests/{}/egg-sources", nest.uuid))
+                .session(session)
+                .json(serde_json::json!({
+                    "name": format!("source-{}", uuid::Uuid::new_v4()),
+                    "url": url,
+                }))
+                .send()
+                .await;
+            response.assert_status(StatusCode::OK);
+
+            app.post(format!(
+                "/api/admin/nests/{}/egg-sources/{}/sync",
+                nest.uuid,
+                response.body["egg_source"]["uuid"].as_str().unwrap()
+            ))
+            .session(session)
+            .json(serde_json::json!({ "dry_run": true }))
+            .send()
+            .await
+        }
+    };
+
+    // entries of an index may neither be absolute nor walk up the tree
+    for entry in ["../paper.json", "/etc/passwd"] {
+        std::fs::write(
+            directory.join("index.json"),
+            serde_json::to_vec(&serde_json::json!([entry])).unwrap(),
+        )
+        .unwrap();
+
+        let response = dry_run(directory.join("index.json").to_string_lossy().to_string()).await;
+        response.assert_status(StatusCode::EXPECTATION_FAILED);
+    }
+
+    std::fs::write(
+        directory.join("index.json"),
+        serde_json::to_vec(&serde_json::json!(["paper.json"])).unwrap(),
+    )
+    .unwrap();
+    let response = dry_run(directory.join("index.json").to_string_lossy().to_string()).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.body["report"]["created"].as_array().unwrap().len(),
+        1
+    );
+
+    // the source itself has to be inside of the configured directory
+    let response = dry_run("/etc".to_string()).await;
+    response.assert_status(StatusCode::EXPECTATION_FAILED);
+
+    // entries of http sources have to stay on the origin of the index
+    let response = dry_run(format!("http://{address}/index.json")).await;
+    response.assert_status(StatusCode::EXPECTATION_FAILED);
+    assert!(
+        response.body["errors"][0]
+            .as_str()
+            .unwrap()
+            .contains("http://example.com/paper.json")
+    );
+
+    // redirects are not followed
+    let response = dry_run(format!("http://{address}/redirect.json")).await;
+    response.assert_status(StatusCode::EXPECTATION_FAILED);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn concurrent_egg_changes_get_one_revision_each() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let egg = app.create_egg().await;
+    let nest_uuid: uuid::Uuid =
+        sqlx::query_scalar("SELECT nest_uuid FROM nest_eggs WHERE uuid = $1")
+            .bind(egg.uuid)
+            .fetch_one(app.state.database.read())
+            .await
+            .unwrap();
+
+    let egg_path = format!("/api/admin/nests/{nest_uuid}/eggs/{}", egg.uuid);
+    let responses = futures_util::future::join_all((0..8).map(|i| {
+        app.patch(&egg_path)
+            .session(&session)
+            .json(serde_json::json!({ "startup": format!("./start.sh {i}") }))
+            .send()
+    }))
+    .await;
+    for response in responses {
+        response.assert_status(StatusCode::OK);
+    }
+
+    let response = app
+        .get(format!("{egg_path}/revisions?per_page=50"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let revisions = response.body["revisions"]["data"].as_array().unwrap();
+    assert_eq!(
+        revisions
+            .iter()
+            .map(|revision| revision["revision"].as_i64().unwrap())
+            .collect::<Vec<_>>(),
+        (1..=8).rev().collect::<Vec<_>>()
+    );
+
+    // the latest revision matches what was stored last
+    let egg = NestEgg::by_uuid(&app.state.database, egg.uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    let response = app
+        .get(format!("{egg_path}/revisions/8"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["revision"]["data"]["startup"], egg.startup);
+}
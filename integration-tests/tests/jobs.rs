@@ -0,0 +1,330 @@
+use axum::http::{Method, StatusCode};
+use integration_tests::{TestApp, TestResponse};
+use shared::{
+    jobs::{
+        Job,
+        mail::{SendMail, SendTemplateMail},
+        server::{CreateServerBackup, PruneServerBackups},
+    },
+    mail::template::MailTemplateKind,
+    models::{
+        queued_job::QueuedJob,
+        server_backup::{BackupDisk, ServerBackup},
+    },
+};
+
+async fn wait_for_status(
+    app: &TestApp,
+    session: &str,
+    job: uuid::Uuid,
+    status: &str,
+) -> TestResponse {
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+
+    loop {
+        let response = app
+            .get(format!("/api/admin/jobs/{job}"))
+            .session(session)
+            .send()
+            .await;
+
+        if (status == "deleted" && response.status == StatusCode::NOT_FOUND)
+            || (response.status == StatusCode::OK && response.body["job"]["status"] == status)
+        {
+            return response;
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            panic!("job {job} never reached {status}");
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn failed_jobs_are_kept_and_can_be_retried() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&session, &admin.user, &node, &egg, allocation)
+        .await;
+
+    let backup = ServerBackup::create_raw(&app.state.database, &server, "nightly", Vec::new())
+        .await
+        .unwrap();
+    let backup_path = format!("/api/servers/{}/backup", server.uuid);
+    app.wings.respond(
+        Method::POST,
+        &backup_path,
+        StatusCode::INTERNAL_SERVER_ERROR,
+        serde_json::json!({ "error": "disk full" }),
+    );
+
+    let job = QueuedJob::create(
+        app.state.database.write(),
+        CreateServerBackup::KIND,
+        serde_json::to_value(CreateServerBackup {
+            server_uuid: server.uuid,
+            backup_uuid: backup,
+            disk: BackupDisk::Local,
+            ignored_files: Vec::new(),
+        })
+        .unwrap(),
+        1,
+        std::time::Duration::ZERO,
+    )
+    .await
+    .unwrap();
+
+    // the last attempt moves the job to the dead letters and marks the backup as failed
+    let response = wait_for_status(&app, &session, job, "failed").await;
+    assert_eq!(response.body["job"]["attempts"], 1);
+    assert_eq!(response.body["job"]["error"], "disk full");
+
+    let backup = ServerBackup::by_uuid(&app.state.database, backup)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!backup.successful);
+    assert!(backup.completed.is_some());
+
+    let response = app
+        .get("/api/admin/jobs?status=failed")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["jobs"]["total"], 1);
+    assert_eq!(response.body["jobs"]["data"][0]["uuid"], job.to_string());
+
+    let response = app
+        .get("/api/admin/jobs?status=pending")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["jobs"]["total"], 0);
+
+    // once wings recovers a retry goes through and removes the job
+    app.wings.respond(
+        Method::POST,
+        &backup_path,
+        StatusCode::OK,
+        serde_json::json!({}),
+    );
+    app.post(format!("/api/admin/jobs/{job}/retry"))
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    wait_for_status(&app, &session, job, "deleted").await;
+    assert_eq!(
+        app.wings
+            .requests()
+            .iter()
+            .filter(|request| request.method == Method::POST && request.path == backup_path)
+            .count(),
+        2
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn only_failed_jobs_can_be_retried() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+
+    let job = shared::jobs::enqueue_in(
+        &app.state.database,
+        &shared::jobs::server::SyncServer {
+            server_uuid: uuid::Uuid::new_v4(),
+        },
+        std::time::Duration::from_secs(3600),
+    )
+    .await
+    .unwrap();
+
+    let response = app
+        .get(format!("/api/admin/jobs/{job}"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["job"]["status"], "pending");
+    assert_eq!(response.body["job"]["kind"], "server:sync");
+
+    app.post(format!("/api/admin/jobs/{job}/retry"))
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    app.delete(format!("/api/admin/jobs/{job}"))
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.get(format!("/api/admin/jobs/{job}"))
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let non_admin = app.create_user(false).await;
+    let non_admin_session = app.create_session(&non_admin.user).await;
+    app.get("/api/admin/jobs")
+        .session(&non_admin_session)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn secret_payload_values_are_redacted() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+
+    let delay = std::time::Duration::from_secs(3600);
+    let mail = shared::jobs::enqueue_in(
+        &app.state.database,
+        &SendMail::new(
+            &app.state.database,
+            "someone@example.com".to_string(),
+            "Reset your password".to_string(),
+            "https://panel.example.com/reset?token=secret",
+            None,
+        )
+        .unwrap(),
+        delay,
+    )
+    .await
+    .unwrap();
+    let template = shared::jobs::enqueue_in(
+        &app.state.database,
+        &SendTemplateMail::new(
+            &app.state.database,
+            admin.user.uuid,
+            None,
+            MailTemplateKind::PasswordReset,
+            [("reset_link".to_string(), "secret".to_string())].into(),
+        )
+        .unwrap(),
+        delay,
+    )
+    .await
+    .unwrap();
+
+    let response = app
+        .get(format!("/api/admin/jobs/{mail}"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let payload = &response.body["job"]["payload"];
+    assert_eq!(payload["destination"], "someone@example.com");
+    assert_eq!(payload["body"], "[redacted]");
+    assert!(payload["text"].is_null());
+
+    let response = app.get("/api/admin/jobs").session(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    let job = response.body["jobs"]["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|job| job["uuid"] == template.to_string())
+        .unwrap();
+    assert_eq!(job["payload"]["user_uuid"], admin.user.uuid.to_string());
+    assert_eq!(job["payload"]["variables"]["reset_link"], "[redacted]");
+
+    // the stored payloads only hold the encrypted values
+    for uuid in [mail, template] {
+        let payload: String =
+            sqlx::query_scalar("SELECT payload::text FROM queued_jobs WHERE uuid = $1")
+                .bind(uuid)
+                .fetch_one(app.state.database.read())
+                .await
+                .unwrap();
+        assert!(!payload.contains("secret"));
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn running_jobs_extend_their_own_lock() {
+    let app = TestApp::new().await;
+
+    let uuid: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO queued_jobs (kind, payload, status, attempts, max_attempts, run_at, locked_until)
+        VALUES ('server:sync', '{}', 'RUNNING', 1, 5, NOW(), NOW() + INTERVAL '10 seconds')
+        RETURNING uuid
+        "#,
+    )
+    .fetch_one(app.state.database.write())
+    .await
+    .unwrap();
+    let job = QueuedJob::by_uuid(&app.state.database, uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    let locked_for = || async {
+        sqlx::query_scalar::<_, f64>(
+            "SELECT EXTRACT(EPOCH FROM locked_until - NOW())::float8 FROM queued_jobs WHERE uuid = $1",
+        )
+        .bind(uuid)
+        .fetch_one(app.state.database.read())
+        .await
+        .unwrap()
+    };
+
+    job.extend_lock(&app.state.database, std::time::Duration::from_secs(600))
+        .await
+        .unwrap();
+    assert!(locked_for().await > 590.0);
+
+    // once another worker claimed the job again, the old run no longer holds its lock
+    sqlx::query("UPDATE queued_jobs SET attempts = 2, locked_until = NOW() + INTERVAL '10 seconds' WHERE uuid = $1")
+        .bind(uuid)
+        .execute(app.state.database.write())
+        .await
+        .unwrap();
+    job.extend_lock(&app.state.database, std::time::Duration::from_secs(600))
+        .await
+        .unwrap();
+    assert!(locked_for().await < 11.0);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn periodic_jobs_are_only_queued_once() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+
+    let queued = futures_util::future::join_all(
+        (0..4).map(|_| shared::jobs::enqueue_unique(&app.state.database, &PruneServerBackups {})),
+    )
+    .await
+    .into_iter()
+    .filter_map(|result| result.unwrap())
+    .collect::<Vec<_>>();
+    assert_eq!(queued.len(), 1);
+
+    // once the job ran, the next one can be queued
+    wait_for_status(&app, &session, queued[0], "deleted").await;
+    let next = shared::jobs::enqueue_unique(&app.state.database, &PruneServerBackups {})
+        .await
+        .unwrap()
+        .unwrap();
+    wait_for_status(&app, &session, next, "deleted").await;
+}
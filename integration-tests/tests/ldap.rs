@@ -0,0 +1,264 @@
+use axum::http::StatusCode;
+use integration_tests::{MockLdap, MockOidc, TestApp};
+use shared::{models::role::Role, settings::RoleMapping};
+
+fn dn(name: &str) -> String {
+    format!("uid={name},ou=people,dc=example,dc=com")
+}
+
+/// Returns the name of the directory user, which is unique because failed sign ins of unknown
+/// names are throttled in the cache that all tests share.
+async fn setup(app: &TestApp) -> (MockLdap, Role, String) {
+    let ldap = MockLdap::start().await;
+    let role = Role::create(&app.state.database, "Operators", None, &[], &[])
+        .await
+        .unwrap();
+
+    let name = format!("dave_{:08x}", rand::random::<u32>());
+    ldap.add_user(
+        &dn(&name),
+        "correct horse",
+        &[
+            ("mail", &["dave@example.com"]),
+            ("givenName", &["Dave"]),
+            ("sn", &["Jones"]),
+            (
+                "memberOf",
+                &["CN=Panel Operators,OU=Groups,DC=example,DC=com"],
+            ),
+        ],
+    );
+    app.configure_ldap(
+        &ldap,
+        vec![RoleMapping {
+            group: "panel operators".to_string(),
+            role_uuid: role.uuid,
+        }],
+    )
+    .await;
+
+    (ldap, role, name)
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn ldap_login_provisions_user() {
+    let app = TestApp::new().await;
+    let (ldap, role, name) = setup(&app).await;
+
+    let response = app
+        .post("/api/auth/login")
+        .json(serde_json::json!({ "user": name, "password": "wrong" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(ldap.binds(), vec![dn(&name)]);
+
+    let response = app
+        .post("/api/auth/login")
+        .json(serde_json::json!({ "user": name, "password": "correct horse" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["type"], "completed");
+    assert!(response.session().is_some());
+
+    let user = response.body["user"].clone();
+    assert_eq!(user["username"], name);
+    assert_eq!(user["email"], "dave@example.com");
+    assert_eq!(user["name_last"], "Jones");
+    assert_eq!(user["role"]["uuid"], role.uuid.to_string());
+
+    // the second login finds the provisioned user again
+    let response = app
+        .post("/api/auth/login")
+        .json(serde_json::json!({ "user": name, "password": "correct horse" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["user"]["uuid"], user["uuid"]);
+
+    // an empty password must never reach the directory
+    let binds = ldap.binds().len();
+    app.post("/api/auth/login")
+        .json(serde_json::json!({ "user": name, "password": "" }))
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(ldap.binds().len(), binds);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn ldap_does_not_shadow_local_users() {
+    let app = TestApp::new().await;
+    let (ldap, _, _) = setup(&app).await;
+    let local = app.create_user(false).await;
+    let local_dn = format!("uid={},ou=people,dc=example,dc=com", local.user.username);
+    ldap.add_user(
+        &local_dn,
+        "directory password",
+        &[("mail", &["x@example.com"])],
+    );
+
+    app.post("/api/auth/login")
+        .json(serde_json::json!({
+            "user": local.user.username,
+            "password": "directory password",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    assert!(ldap.binds().is_empty());
+
+    app.post("/api/auth/login")
+        .json(serde_json::json!({
+            "user": local.user.username,
+            "password": local.password,
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn ldap_applies_to_sftp_password_auth() {
+    let app = TestApp::new().await;
+    let (_ldap, _, name) = setup(&app).await;
+    let admin = app.create_user(true).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+
+    // provision dave through the sftp endpoint itself, no server is found yet
+    let response = app
+        .post("/api/remote/sftp/auth")
+        .bearer(&app.node_token(&node))
+        .json(serde_json::json!({
+            "type": "password",
+            "username": format!("{name}.{}", uuid::Uuid::new_v4()),
+            "password": "correct horse",
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    let dave = shared::models::user::User::by_external_identity(&app.state.database, "ldap", &name)
+        .await
+        .unwrap()
+        .expect("sftp authentication should provision the ldap user");
+    let server = app
+        .create_server(&admin_session, &dave, &node, &egg, allocation)
+        .await;
+
+    let response = app
+        .post("/api/remote/sftp/auth")
+        .bearer(&app.node_token(&node))
+        .json(serde_json::json!({
+            "type": "password",
+            "username": format!("{name}.{}", server.uuid),
+            "password": "correct horse",
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["user"], dave.uuid.to_string());
+    assert_eq!(response.body["server"], server.uuid.to_string());
+
+    app.post("/api/remote/sftp/auth")
+        .bearer(&app.node_token(&node))
+        .json(serde_json::json!({
+            "type": "password",
+            "username": format!("{name}.{}", server.uuid),
+            "password": "wrong",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::EXPECTATION_FAILED);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn ldap_users_can_link_single_sign_on() {
+    let app = TestApp::new().await;
+    let (_ldap, _, name) = setup(&app).await;
+    let oidc = MockOidc::start().await;
+    app.configure_oidc(&oidc, false, Vec::new()).await;
+
+    let ldap_login = || async {
+        let response = app
+            .post("/api/auth/login")
+            .json(serde_json::json!({ "user": name, "password": "correct horse" }))
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+        response
+    };
+    let response = ldap_login().await;
+    let dave = response.body["user"]["uuid"].as_str().unwrap().to_string();
+    let session = response.session().unwrap();
+
+    // the external id of the account is left to integrations
+    sqlx::query("UPDATE users SET external_id = 'billing-42' WHERE users.uuid = $1::uuid")
+        .bind(&dave)
+        .execute(app.state.database.write())
+        .await
+        .unwrap();
+
+    let start = app
+        .post("/api/client/account/oidc")
+        .session(&session)
+        .send()
+        .await;
+    start.assert_status(StatusCode::OK);
+    let authorization = oidc.authorize(
+        start.body["url"].as_str().unwrap(),
+        "dave-sso",
+        serde_json::json!({ "email": "dave@example.com" }),
+    );
+    let response = app
+        .get(format!(
+            "/api/auth/oidc/callback?code={}&state={}",
+            authorization.code, authorization.state
+        ))
+        .cookie("oidc_state", &start.cookie("oidc_state").unwrap())
+        .send()
+        .await;
+    assert_eq!(response.location(), Some("http://localhost:8000/account"));
+
+    // both identities sign in to the same account
+    assert_eq!(ldap_login().await.body["user"]["uuid"], dave);
+
+    let start = app.get("/api/auth/oidc/redirect").send().await;
+    let authorization = oidc.authorize(
+        start.location().unwrap(),
+        "dave-sso",
+        serde_json::json!({ "email": "dave@example.com" }),
+    );
+    let session = app
+        .get(format!(
+            "/api/auth/oidc/callback?code={}&state={}",
+            authorization.code, authorization.state
+        ))
+        .cookie("oidc_state", &start.cookie("oidc_state").unwrap())
+        .send()
+        .await
+        .session()
+        .expect("the linked identity should sign in");
+    let response = app
+        .get("/api/client/account")
+        .session(&session)
+        .send()
+        .await;
+    assert_eq!(response.body["user"]["uuid"], dave);
+
+    let external_id: Option<String> =
+        sqlx::query_scalar("SELECT external_id FROM users WHERE users.uuid = $1::uuid")
+            .bind(&dave)
+            .fetch_one(app.state.database.read())
+            .await
+            .unwrap();
+    assert_eq!(external_id.as_deref(), Some("billing-42"));
+}
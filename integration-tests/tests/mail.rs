@@ -0,0 +1,261 @@
+use axum::http::{Method, StatusCode};
+use integration_tests::{MockWings, TestApp};
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn test_mails_are_delivered_through_each_transport() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+
+    // the test endpoint only uses the saved settings, the file transport writes one .eml file
+    // per mail into the directory from the environment
+    app.use_file_mail(&session).await;
+    app.post("/api/admin/settings/test-mail")
+        .session(&session)
+        .json(serde_json::json!({}))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let mail = app.wait_for_mail("Test Email").await;
+    assert!(mail.contains(&format!("To: {}", admin.user.email)));
+    assert!(mail.contains("Test Email"));
+
+    let provider = MockWings::start().await;
+    app.put("/api/admin/settings")
+        .session(&session)
+        .json(serde_json::json!({
+            "mail_mode": {
+                "type": "http",
+                "provider": "json",
+                "endpoint": format!("{}/send", provider.url()),
+                "api_key": "mail-key",
+                "from_address": "panel@example.com",
+                "from_name": "Panel",
+            },
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post("/api/admin/settings/test-mail")
+        .session(&session)
+        .json(serde_json::json!({ "destination": "someone@example.com" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let request = provider.find(Method::POST, "/send").unwrap();
+    assert_eq!(request.authorization.as_deref(), Some("Bearer mail-key"));
+    assert_eq!(request.body["to"], "someone@example.com");
+    assert_eq!(request.body["from"], "Panel <panel@example.com>");
+
+    // failures are reported without echoing what the provider responded
+    provider.respond(
+        Method::POST,
+        "/email",
+        StatusCode::UNPROCESSABLE_ENTITY,
+        serde_json::json!({ "Message": "Invalid sender signature" }),
+    );
+    app.put("/api/admin/settings")
+        .session(&session)
+        .json(serde_json::json!({
+            "mail_mode": {
+                "type": "http",
+                "provider": "postmark",
+                "endpoint": provider.url(),
+                "api_key": "mail-key",
+                "from_address": "panel@example.com",
+                "from_name": null,
+            },
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let response = app
+        .post("/api/admin/settings/test-mail")
+        .session(&session)
+        .json(serde_json::json!({}))
+        .send()
+        .await;
+    response.assert_status(StatusCode::EXPECTATION_FAILED);
+    assert!(
+        !response.body["errors"][0]
+            .as_str()
+            .unwrap()
+            .contains("Invalid sender signature")
+    );
+
+    // the sendmail command comes from the environment, which points at a missing binary here
+    app.put("/api/admin/settings")
+        .session(&session)
+        .json(serde_json::json!({
+            "mail_mode": {
+                "type": "sendmail",
+                "command": "/bin/sh",
+                "from_address": "panel@example.com",
+                "from_name": null,
+            },
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post("/api/admin/settings/test-mail")
+        .session(&session)
+        .json(serde_json::json!({}))
+        .send()
+        .await
+        .assert_status(StatusCode::EXPECTATION_FAILED);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn http_mail_settings_are_persisted_with_an_encrypted_key() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+
+    app.put("/api/admin/settings")
+        .session(&session)
+        .json(serde_json::json!({
+            "mail_mode": {
+                "type": "http",
+                "provider": "mailgun",
+                "endpoint": "https://api.mailgun.net/v3/mg.example.com",
+                "api_key": "key-123",
+                "from_address": "panel@example.com",
+                "from_name": null,
+            },
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let stored: String =
+        sqlx::query_scalar("SELECT settings.value FROM settings WHERE settings.key = $1")
+            .bind("::mail_http_api_key")
+            .fetch_one(app.state.database.read())
+            .await
+            .unwrap();
+    assert!(!stored.contains("key-123"));
+
+    let settings = shared::settings::Settings::new(app.state.database.clone()).await;
+    match &settings.get().await.mail_mode {
+        shared::settings::MailMode::Http {
+            provider: shared::settings::MailHttpProvider::Mailgun,
+            endpoint,
+            api_key,
+            ..
+        } => {
+            assert_eq!(endpoint, "https://api.mailgun.net/v3/mg.example.com");
+            assert_eq!(api_key, "key-123");
+        }
+        _ => panic!("mail mode was not persisted"),
+    }
+
+    let logged: serde_json::Value = sqlx::query_scalar(
+        r#"
+        SELECT admin_activities.data
+        FROM admin_activities
+        WHERE admin_activities.event = 'settings:update' AND admin_activities.user_uuid = $1
+        "#,
+    )
+    .bind(admin.user.uuid)
+    .fetch_one(app.state.database.read())
+    .await
+    .unwrap();
+    assert!(!logged.to_string().contains("key-123"));
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn notifications_use_overridden_templates_in_the_language_of_the_user() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+
+    app.use_file_mail(&session).await;
+
+    // templates may only use the variables of their kind
+    app.put("/api/admin/mail-templates/api_key_created/de")
+        .session(&session)
+        .json(serde_json::json!({
+            "subject": "Neuer API-Schluessel",
+            "html": "<p>{{reset_link}}</p>",
+            "text": "Hallo",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    app.put("/api/admin/mail-templates/api_key_created/de")
+        .session(&session)
+        .json(serde_json::json!({
+            "subject": "{{app_name}} - Neuer API-Schluessel",
+            "html": "<p>Hallo {{user_username}}, Schluessel {{api_key_name}} erstellt</p>",
+            "text": "Hallo {{user_username}}, Schluessel {{api_key_name}} erstellt",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app
+        .get("/api/admin/mail-templates")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let template = response.body["mail_templates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|template| template["name"] == "api_key_created")
+        .unwrap();
+    assert_eq!(template["languages"], serde_json::json!(["de"]));
+
+    app.patch("/api/client/account")
+        .session(&session)
+        .json(serde_json::json!({ "language": "de" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    app.post("/api/client/account/api-keys")
+        .session(&session)
+        .json(serde_json::json!({
+            "name": "deploy<key>",
+            "user_permissions": [],
+            "admin_permissions": [],
+            "server_permissions": [],
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let mail = app.wait_for_mail("Neuer API-Schluessel").await;
+    assert!(mail.contains("Neuer API-Schluessel"));
+    assert!(mail.contains("text/plain"));
+    assert!(mail.contains("Schluessel deploy<key> erstellt"));
+    assert!(mail.contains("Schluessel deploy&lt;key&gt; erstellt"));
+
+    // without an override for the language the built in template is used
+    app.delete("/api/admin/mail-templates/api_key_created/de")
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post("/api/client/account/api-keys")
+        .session(&session)
+        .json(serde_json::json!({
+            "name": "second",
+            "user_permissions": [],
+            "admin_permissions": [],
+            "server_permissions": [],
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let mail = app.wait_for_mail("API Key Created").await;
+    assert!(mail.contains("API Key Created"));
+    assert!(mail.contains("The API key \"second\" was created"));
+}
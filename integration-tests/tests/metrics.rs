@@ -0,0 +1,88 @@
+use axum::http::{Method, StatusCode};
+use integration_tests::{TestApp, app::METRICS_TOKEN};
+
+/// Returns the value of the sample with exactly the given name and labels.
+fn sample(metrics: &str, series: &str) -> Option<f64> {
+    metrics.lines().find_map(|line| {
+        line.strip_prefix(series)
+            .and_then(|value| value.strip_prefix(' '))
+            .and_then(|value| value.parse().ok())
+    })
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn metrics_count_http_and_wings_requests() {
+    let app = TestApp::new().await;
+    let user = app.create_user(false).await;
+    let session = app.create_session(&user.user).await;
+    let node = app.create_node(&app.wings).await;
+
+    app.get("/metrics")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    app.get("/metrics")
+        .bearer("wrong")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    for _ in 0..2 {
+        app.get("/api/client/account")
+            .session(&session)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    app.wings.respond(
+        Method::GET,
+        "/api/servers",
+        StatusCode::OK,
+        serde_json::json!([]),
+    );
+    app.wings.respond(
+        Method::GET,
+        "/api/extensions",
+        StatusCode::INTERNAL_SERVER_ERROR,
+        serde_json::json!({ "error": "unavailable" }),
+    );
+    let client = node.api_client(&app.state.database);
+    assert!(client.get_servers().await.is_ok());
+    assert!(client.get_extensions().await.is_err());
+
+    let response = app.get("/metrics").bearer(METRICS_TOKEN).send().await;
+    response.assert_status(StatusCode::OK);
+    let metrics = response.body.as_str().unwrap();
+
+    assert!(
+        sample(
+            metrics,
+            r#"panel_http_requests_total{method="GET",path="/api/client/account",status="200"}"#
+        )
+        .unwrap()
+            >= 2.0
+    );
+    assert_eq!(
+        sample(
+            metrics,
+            &format!(
+                r#"panel_wings_request_duration_seconds_count{{node="{}"}}"#,
+                node.uuid
+            )
+        ),
+        Some(2.0)
+    );
+    assert_eq!(
+        sample(
+            metrics,
+            &format!(
+                r#"panel_wings_request_errors_total{{node="{}"}}"#,
+                node.uuid
+            )
+        ),
+        Some(1.0)
+    );
+    assert_eq!(sample(metrics, "panel_nodes"), Some(1.0));
+}
@@ -0,0 +1,186 @@
+use axum::http::StatusCode;
+use integration_tests::TestApp;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn remote_events_notify_the_owner_as_they_prefer() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&session, &admin.user, &node, &egg, allocation)
+        .await;
+    let token = app.node_token(&node);
+
+    let crash = || {
+        app.post("/api/remote/activity")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "data": [{
+                    "user": null,
+                    "server": server.uuid,
+                    "event": "server:crash",
+                    "metadata": { "exit_code": 137 },
+                    "ip": null,
+                    "timestamp": "2026-01-01T00:00:00Z",
+                }],
+            }))
+            .send()
+    };
+    let install_failed = || {
+        app.post(format!("/api/remote/servers/{}/install", server.uuid))
+            .bearer(&token)
+            .json(serde_json::json!({ "successful": false, "reinstall": false }))
+            .send()
+    };
+
+    crash().await.assert_status(StatusCode::OK);
+    install_failed().await.assert_status(StatusCode::OK);
+
+    let response = app
+        .get("/api/client/account/notifications")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["unread"], 2);
+    let notifications = response.body["notifications"]["data"].as_array().unwrap();
+    assert_eq!(notifications[0]["event"], "server:install.fail");
+    assert_eq!(notifications[1]["event"], "server:crash");
+    assert_eq!(notifications[1]["category"], "server");
+    assert_eq!(notifications[1]["server_uuid"], server.uuid.to_string());
+    assert_eq!(notifications[1]["data"]["exit_code"], 137);
+    let crash_uuid = notifications[1]["uuid"].as_str().unwrap().to_string();
+
+    let response = app
+        .post(format!(
+            "/api/client/account/notifications/{crash_uuid}/read"
+        ))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.body["notification"]["read"].is_string());
+
+    let response = app
+        .get("/api/client/account/notifications?unread=true")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["unread"], 1);
+    assert_eq!(response.body["notifications"]["total"], 1);
+
+    let response = app
+        .post("/api/client/account/notifications/read")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["read"], 1);
+
+    app.delete(format!("/api/client/account/notifications/{crash_uuid}"))
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.delete(format!("/api/client/account/notifications/{crash_uuid}"))
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // other users can not see or touch the notifications
+    let other = app.create_user(false).await;
+    let other_session = app.create_session(&other.user).await;
+    let response = app
+        .get("/api/client/account/notifications")
+        .session(&other_session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["notifications"]["total"], 0);
+
+    // with only email delivery the mail is sent without an in-app notification
+    app.use_file_mail(&session).await;
+
+    let response = app
+        .put("/api/client/account/notifications/preferences")
+        .session(&session)
+        .json(serde_json::json!({
+            "server": { "email": true, "in_app": false },
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.body["preferences"]["backup"],
+        serde_json::json!({ "email": true, "in_app": true })
+    );
+
+    let response = app
+        .get("/api/client/account/notifications/preferences")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["preferences"]["server"]["in_app"], false);
+
+    crash().await.assert_status(StatusCode::OK);
+    install_failed().await.assert_status(StatusCode::OK);
+
+    let mail = app.wait_for_mail("Installation of").await;
+    assert!(mail.contains(&format!("To: {}", admin.user.email)));
+    assert!(mail.contains(&format!(
+        "The installation of your server \"{}\" failed.",
+        server.name
+    )));
+    assert!(mail.contains(&format!("/server/{:08x}", server.uuid_short)));
+
+    let response = app
+        .get("/api/client/account/notifications")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["unread"], 0);
+    assert_eq!(response.body["notifications"]["total"], 1);
+
+    // security notices can not be silenced
+    app.put("/api/client/account/notifications/preferences")
+        .session(&session)
+        .json(serde_json::json!({
+            "account": { "email": false, "in_app": false },
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post("/api/client/account/api-keys")
+        .session(&session)
+        .json(serde_json::json!({
+            "name": "silenced",
+            "user_permissions": [],
+            "admin_permissions": [],
+            "server_permissions": [],
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let mail = app.wait_for_mail("API Key Created").await;
+    assert!(mail.contains("The API key \"silenced\" was created"));
+
+    let response = app
+        .get("/api/client/account/notifications?unread=true")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.body["notifications"]["data"][0]["event"],
+        "account:api-key.created"
+    );
+}
@@ -0,0 +1,471 @@
+use axum::http::StatusCode;
+use integration_tests::{MockOidc, TestApp, TestResponse};
+use shared::{models::role::Role, settings::RoleMapping};
+
+/// Runs the callback for an authorization request started by `start`.
+async fn complete(
+    app: &TestApp,
+    oidc: &MockOidc,
+    start: &TestResponse,
+    url: &str,
+    sub: &str,
+    claims: serde_json::Value,
+) -> TestResponse {
+    let state_cookie = start
+        .cookie("oidc_state")
+        .expect("starting an authorization should set the state cookie");
+    let authorization = oidc.authorize(url, sub, claims);
+
+    app.get(format!(
+        "/api/auth/oidc/callback?code={}&state={}",
+        authorization.code, authorization.state
+    ))
+    .cookie("oidc_state", &state_cookie)
+    .send()
+    .await
+}
+
+async fn login(
+    app: &TestApp,
+    oidc: &MockOidc,
+    sub: &str,
+    claims: serde_json::Value,
+) -> TestResponse {
+    let start = app.get("/api/auth/oidc/redirect").send().await;
+    start.assert_status(StatusCode::FOUND);
+    let url = start.location().unwrap().to_string();
+
+    let response = complete(app, oidc, &start, &url, sub, claims).await;
+    response.assert_status(StatusCode::FOUND);
+
+    response
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn oidc_login_provisions_user_and_maps_roles() {
+    let app = TestApp::new().await;
+    let oidc = MockOidc::start().await;
+    let role = Role::create(&app.state.database, "Operators", None, &[], &[])
+        .await
+        .unwrap();
+
+    app.get("/api/auth/oidc/redirect")
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    app.configure_oidc(
+        &oidc,
+        true,
+        vec![RoleMapping {
+            group: "panel-operators".to_string(),
+            role_uuid: role.uuid,
+        }],
+    )
+    .await;
+
+    let response = app.get("/api/settings").send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["oidc"]["enabled"], true);
+
+    let claims = serde_json::json!({
+        "preferred_username": "alice.smith",
+        "email": "alice@example.com",
+        "given_name": "Alice",
+        "family_name": "Smith",
+        "groups": ["staff", "panel-operators"],
+    });
+
+    let response = login(&app, &oidc, "alice", claims.clone()).await;
+    assert_eq!(response.location(), Some("http://localhost:8000/"));
+    let session = response
+        .session()
+        .expect("oidc login should set a session cookie");
+
+    let response = app
+        .get("/api/client/account")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let user = response.body["user"].clone();
+    assert_eq!(user["username"], "alice_smith");
+    assert_eq!(user["email"], "alice@example.com");
+    assert_eq!(user["name_first"], "Alice");
+    assert_eq!(user["role"]["uuid"], role.uuid.to_string());
+
+    // logging in again reuses the account and keeps the role in sync with the groups claim
+    let mut claims = claims;
+    claims["groups"] = serde_json::json!(["staff"]);
+    let session = login(&app, &oidc, "alice", claims.clone())
+        .await
+        .session()
+        .unwrap();
+
+    let response = app
+        .get("/api/client/account")
+        .session(&session)
+        .send()
+        .await;
+    assert_eq!(response.body["user"]["uuid"], user["uuid"]);
+    assert!(response.body["user"]["role"].is_null());
+
+    // roles that no mapping manages are left alone
+    let manual = Role::create(&app.state.database, "Support", None, &[], &[])
+        .await
+        .unwrap();
+    shared::models::user::User::update_role(
+        &app.state.database,
+        user["uuid"].as_str().unwrap().parse().unwrap(),
+        Some(manual.uuid),
+    )
+    .await
+    .unwrap();
+    let session = login(&app, &oidc, "alice", claims).await.session().unwrap();
+
+    let response = app
+        .get("/api/client/account")
+        .session(&session)
+        .send()
+        .await;
+    assert_eq!(
+        response.body["user"]["role"]["uuid"],
+        manual.uuid.to_string()
+    );
+
+    // existing accounts are never taken over by email
+    let response = login(
+        &app,
+        &oidc,
+        "mallory",
+        serde_json::json!({ "email": "alice@example.com" }),
+    )
+    .await;
+    assert_eq!(
+        response.location(),
+        Some("http://localhost:8000/auth/login?oidc_error=email_in_use")
+    );
+    assert!(response.session().is_none());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn oidc_callback_requires_state() {
+    let app = TestApp::new().await;
+    let oidc = MockOidc::start().await;
+    app.configure_oidc(&oidc, true, Vec::new()).await;
+
+    let start = app.get("/api/auth/oidc/redirect").send().await;
+    start.assert_status(StatusCode::FOUND);
+    let authorization = oidc.authorize(
+        start.location().unwrap(),
+        "bob",
+        serde_json::json!({ "email": "bob@example.com" }),
+    );
+
+    // without the state cookie, e.g. a forged callback
+    let response = app
+        .get(format!(
+            "/api/auth/oidc/callback?code={}&state={}",
+            authorization.code, authorization.state
+        ))
+        .send()
+        .await;
+    response.assert_status(StatusCode::FOUND);
+    assert_eq!(
+        response.location(),
+        Some("http://localhost:8000/auth/login?oidc_error=invalid_state")
+    );
+    assert!(response.session().is_none());
+
+    // with a state that does not belong to the cookie
+    let response = app
+        .get(format!(
+            "/api/auth/oidc/callback?code={}&state=forged",
+            authorization.code
+        ))
+        .cookie("oidc_state", &start.cookie("oidc_state").unwrap())
+        .send()
+        .await;
+    assert_eq!(
+        response.location(),
+        Some("http://localhost:8000/auth/login?oidc_error=invalid_state")
+    );
+    assert!(response.session().is_none());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn oidc_link_and_unlink() {
+    let app = TestApp::new().await;
+    let oidc = MockOidc::start().await;
+    app.configure_oidc(&oidc, false, Vec::new()).await;
+    let user = app.create_user(false).await;
+    let other = app.create_user(false).await;
+    let session = app.create_session(&user.user).await;
+    let other_session = app.create_session(&other.user).await;
+    let claims = serde_json::json!({ "email": "carol@example.com" });
+
+    let response = login(&app, &oidc, "carol", claims.clone()).await;
+    assert_eq!(
+        response.location(),
+        Some("http://localhost:8000/auth/login?oidc_error=account_not_linked")
+    );
+
+    let link = |session: String| {
+        let app = &app;
+        let oidc = &oidc;
+        let claims = claims.clone();
+
+        async move {
+            let start = app
+                .post("/api/client/account/oidc")
+                .session(&session)
+                .send()
+                .await;
+            start.assert_status(StatusCode::OK);
+            let url = start.body["url"].as_str().unwrap().to_string();
+
+            complete(app, oidc, &start, &url, "carol", claims).await
+        }
+    };
+
+    let response = link(session.clone()).await;
+    response.assert_status(StatusCode::FOUND);
+    assert_eq!(response.location(), Some("http://localhost:8000/account"));
+
+    let response = app
+        .get("/api/client/account/oidc")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["linked"], true);
+
+    app.post("/api/client/account/oidc")
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    // the identity can only be linked to one account
+    let response = link(other_session).await;
+    assert_eq!(
+        response.location(),
+        Some("http://localhost:8000/account?oidc_error=identity_in_use")
+    );
+
+    let oidc_session = login(&app, &oidc, "carol", claims.clone())
+        .await
+        .session()
+        .expect("linked identities should be able to log in");
+    let response = app
+        .get("/api/client/account")
+        .session(&oidc_session)
+        .send()
+        .await;
+    assert_eq!(response.body["user"]["uuid"], user.user.uuid.to_string());
+
+    app.delete("/api/client/account/oidc")
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.delete("/api/client/account/oidc")
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let response = login(&app, &oidc, "carol", claims).await;
+    assert_eq!(
+        response.location(),
+        Some("http://localhost:8000/auth/login?oidc_error=account_not_linked")
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn oidc_identities_belong_to_their_issuer() {
+    let app = TestApp::new().await;
+    let oidc = MockOidc::start().await;
+    app.configure_oidc(&oidc, true, Vec::new()).await;
+    let claims = serde_json::json!({ "email": "erin@example.com" });
+
+    login(&app, &oidc, "erin", claims.clone())
+        .await
+        .session()
+        .expect("the identity should be provisioned");
+
+    // the same subject at another issuer is a different identity
+    let other = MockOidc::start().await;
+    app.configure_oidc(&other, false, Vec::new()).await;
+    let response = login(&app, &other, "erin", claims).await;
+    assert_eq!(
+        response.location(),
+        Some("http://localhost:8000/auth/login?oidc_error=account_not_linked")
+    );
+    assert!(response.session().is_none());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn oidc_login_with_totp_requires_checkpoint() {
+    let app = TestApp::new().await;
+    let oidc = MockOidc::start().await;
+    app.configure_oidc(&oidc, true, Vec::new()).await;
+    let claims = serde_json::json!({ "email": "frank@example.com" });
+
+    let session = login(&app, &oidc, "frank", claims.clone())
+        .await
+        .session()
+        .unwrap();
+    let response = app
+        .get("/api/client/account")
+        .session(&session)
+        .send()
+        .await;
+    let user = shared::models::user::User::by_uuid(
+        &app.state.database,
+        response.body["user"]["uuid"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let secret = app.enable_totp(&user).await;
+
+    let response = login(&app, &oidc, "frank", claims).await;
+    assert!(response.session().is_none());
+    assert_eq!(
+        response.location(),
+        Some("http://localhost:8000/auth/login?step=totp"),
+        "users with two factor should be sent to the checkpoint"
+    );
+    // the token only travels in an http only cookie and never carries the secret
+    let token = response.cookie("checkpoint_token").unwrap();
+    let payload = base64::Engine::decode(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        token.split('.').nth(1).unwrap(),
+    )
+    .unwrap();
+    assert!(!String::from_utf8(payload).unwrap().contains(&secret));
+
+    let code = totp_rs::TOTP::new(
+        totp_rs::Algorithm::SHA1,
+        6,
+        1,
+        30,
+        totp_rs::Secret::Encoded(secret).to_bytes().unwrap(),
+    )
+    .unwrap()
+    .generate_current()
+    .unwrap();
+    let response = app
+        .post("/api/auth/login/checkpoint")
+        .cookie("checkpoint_token", &token)
+        .json(serde_json::json!({ "code": code }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["user"]["uuid"], user.uuid.to_string());
+    assert!(response.session().is_some());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn oidc_rejects_unsigned_id_tokens() {
+    let app = TestApp::new().await;
+    let oidc = MockOidc::start().await;
+    app.configure_oidc(&oidc, true, Vec::new()).await;
+    let claims = serde_json::json!({ "email": "dave@example.com" });
+
+    login(&app, &oidc, "dave", claims.clone())
+        .await
+        .session()
+        .expect("signed id tokens should be accepted");
+
+    oidc.issue_unsigned_tokens();
+    let response = login(&app, &oidc, "dave", claims).await;
+    assert_eq!(
+        response.location(),
+        Some("http://localhost:8000/auth/login?oidc_error=invalid_response")
+    );
+    assert!(response.session().is_none());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn oidc_login_from_new_device_requires_confirmation() {
+    let app = TestApp::new().await;
+    let oidc = MockOidc::start().await;
+    app.configure_oidc(&oidc, true, Vec::new()).await;
+    let admin = app.create_user(true).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let claims = serde_json::json!({ "email": "dave@example.com" });
+
+    login(&app, &oidc, "dave", claims.clone())
+        .await
+        .session()
+        .expect("the first sign in has no device to compare with");
+
+    app.use_file_mail(&admin_session).await;
+    app.put("/api/admin/settings")
+        .session(&admin_session)
+        .json(serde_json::json!({
+            "security": {
+                "new_device_notifications": false,
+                "new_device_verification": "email",
+            },
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let user_agent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) Edg/126.0";
+    let start = app.get("/api/auth/oidc/redirect").send().await;
+    let url = start.location().unwrap().to_string();
+    let authorization = oidc.authorize(&url, "dave", claims);
+    let response = app
+        .get(format!(
+            "/api/auth/oidc/callback?code={}&state={}",
+            authorization.code, authorization.state
+        ))
+        .cookie("oidc_state", &start.cookie("oidc_state").unwrap())
+        .header("User-Agent", user_agent)
+        .send()
+        .await;
+    response.assert_status(StatusCode::FOUND);
+    assert!(response.session().is_none());
+    assert_eq!(
+        response.location(),
+        Some("http://localhost:8000/auth/login?step=device"),
+        "a new device should be sent to the confirmation step"
+    );
+    let token = response.cookie("device_token").unwrap();
+
+    let mail = app.wait_for_mail("Confirm New Device").await;
+    assert!(mail.contains("To: dave@example.com"));
+    let code = mail
+        .split("Enter this code to confirm the sign in: ")
+        .nth(1)
+        .unwrap()
+        .chars()
+        .take(8)
+        .collect::<String>();
+
+    let response = app
+        .post("/api/auth/login/device")
+        .header("User-Agent", user_agent)
+        .cookie("device_token", &token)
+        .json(serde_json::json!({ "code": code }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["user"]["email"], "dave@example.com");
+    assert!(response.session().is_some());
+}
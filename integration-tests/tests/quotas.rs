@@ -0,0 +1,171 @@
+use axum::http::StatusCode;
+use integration_tests::TestApp;
+use shared::models::role::Role;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn server_creation_respects_user_and_role_quotas() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let owner = app.create_user(false).await;
+    let owner_session = app.create_session(&owner.user).await;
+    let node = app.create_node(&app.wings).await;
+    let egg = app.create_egg().await;
+
+    let role = Role::create(&app.state.database, "Limited", None, &[], &[])
+        .await
+        .unwrap();
+    app.patch(format!("/api/admin/roles/{}", role.uuid))
+        .session(&admin_session)
+        .json(serde_json::json!({ "quotas": { "servers": 2, "memory": 2048, "backups": 4 } }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    shared::models::user::User::update_role(&app.state.database, owner.user.uuid, Some(role.uuid))
+        .await
+        .unwrap();
+
+    // the user quota overrides the role quota for memory only
+    app.patch(format!("/api/admin/users/{}", owner.user.uuid))
+        .session(&admin_session)
+        .json(serde_json::json!({ "quotas": { "memory": 3072 } }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app
+        .get("/api/client/account/quotas")
+        .session(&owner_session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.body["limits"],
+        serde_json::json!({ "servers": 2, "memory": 3072, "backups": 4 })
+    );
+    assert_eq!(response.body["usage"]["servers"], 0);
+
+    let owner_user = shared::models::user::User::by_uuid(&app.state.database, owner.user.uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    let allocation = app.create_allocation(&node, 25565).await;
+    app.create_server(&admin_session, &owner_user, &node, &egg, allocation)
+        .await;
+
+    let response = app
+        .get("/api/client/account/quotas")
+        .session(&owner_session)
+        .send()
+        .await;
+    assert_eq!(response.body["usage"]["servers"], 1);
+    assert_eq!(response.body["usage"]["memory"], 1024);
+    assert_eq!(response.body["usage"]["backups"], 2);
+
+    let create = |payload: serde_json::Value| {
+        let app = &app;
+        let admin_session = &admin_session;
+
+        async move {
+            app.post("/api/admin/servers")
+                .session(admin_session)
+                .json(payload)
+                .send()
+                .await
+        }
+    };
+
+    let allocation = app.create_allocation(&node, 25566).await;
+    let mut payload = app.server_payload(&owner_user, &node, &egg, allocation);
+
+    payload["limits"]["memory"] = serde_json::json!(4096);
+    let response = create(payload.clone()).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(
+        response.body["errors"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("memory quota exceeded")
+    );
+
+    // unlimited memory can never fit into a memory quota
+    payload["limits"]["memory"] = serde_json::json!(0);
+    create(payload.clone())
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    payload["limits"]["memory"] = serde_json::json!(2048);
+    payload["feature_limits"]["backups"] = serde_json::json!(3);
+    let response = create(payload.clone()).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(
+        response.body["errors"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("backups quota exceeded")
+    );
+
+    payload["feature_limits"]["backups"] = serde_json::json!(2);
+    create(payload.clone()).await.assert_status(StatusCode::OK);
+
+    let allocation = app.create_allocation(&node, 25567).await;
+    let mut payload = app.server_payload(&owner_user, &node, &egg, allocation);
+    payload["limits"]["memory"] = serde_json::json!(1);
+    payload["feature_limits"]["backups"] = serde_json::json!(0);
+    let response = create(payload).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(
+        response.body["errors"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("servers quota exceeded")
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn concurrent_admin_creations_cannot_exceed_quotas() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let owner = app.create_user(false).await;
+    let node = app.create_node(&app.wings).await;
+    let egg = app.create_egg().await;
+
+    app.patch(format!("/api/admin/users/{}", owner.user.uuid))
+        .session(&admin_session)
+        .json(serde_json::json!({ "quotas": { "servers": 2 } }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let owner_user = shared::models::user::User::by_uuid(&app.state.database, owner.user.uuid)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut payloads = Vec::new();
+    for port in 25565..25571 {
+        let allocation = app.create_allocation(&node, port).await;
+        let mut payload = app.server_payload(&owner_user, &node, &egg, allocation);
+        payload["limits"]["memory"] = serde_json::json!(512);
+        payloads.push(payload);
+    }
+
+    let responses = futures_util::future::join_all(payloads.into_iter().map(|payload| {
+        app.post("/api/admin/servers")
+            .session(&admin_session)
+            .json(payload)
+            .send()
+    }))
+    .await;
+
+    let created = responses
+        .iter()
+        .filter(|response| response.status == StatusCode::OK)
+        .count();
+    assert_eq!(created, 2);
+    for response in responses.iter().filter(|r| r.status != StatusCode::OK) {
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,172 @@
+use axum::http::StatusCode;
+use integration_tests::TestApp;
+use shared::models::{nest_egg::NestEgg, quota::Quotas, user::User};
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn users_create_servers_within_quotas() {
+    let app = TestApp::new().await;
+    let user = app.create_user(false).await;
+    let session = app.create_session(&user.user).await;
+    let node = app.create_node(&app.wings).await;
+    app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+
+    let create = |payload: serde_json::Value| {
+        let app = &app;
+        let session = &session;
+
+        async move {
+            app.post("/api/client/servers")
+                .session(session)
+                .json(payload)
+                .send()
+                .await
+        }
+    };
+    let payload = serde_json::json!({
+        "egg_uuid": egg.uuid,
+        "location_uuid": node.location.uuid,
+        "name": "my server",
+        "memory": 1024,
+        "disk": 4096,
+    });
+
+    // accounts without quotas cannot create servers
+    create(payload.clone())
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    User::update_quotas(
+        &app.state.database,
+        user.user.uuid,
+        &Quotas {
+            servers: Some(2),
+            memory: Some(16384),
+            disk: Some(16384),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    // eggs have to be marked deployable first
+    create(payload.clone())
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    NestEgg::update_deployable(app.state.database.write(), egg.uuid, true)
+        .await
+        .unwrap();
+
+    let response = app
+        .get("/api/client/servers/deployable")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["eggs"][0]["uuid"], egg.uuid.to_string());
+    assert_eq!(
+        response.body["locations"][0]["uuid"],
+        node.location.uuid.to_string()
+    );
+
+    // the node only has 8192 MiB of memory
+    let mut too_big = payload.clone();
+    too_big["memory"] = serde_json::json!(12288);
+    create(too_big).await.assert_status(StatusCode::CONFLICT);
+
+    let mut wrong_image = payload.clone();
+    wrong_image["image"] = serde_json::json!("ubuntu:latest");
+    create(wrong_image)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let response = create(payload.clone()).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["server"]["name"], "my server");
+    assert_eq!(response.body["server"]["allocation"]["port"], 25565);
+
+    // the only allocation of the node is taken now
+    create(payload.clone())
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    app.create_allocation(&node, 25566).await;
+    let mut over_quota = payload.clone();
+    over_quota["disk"] = serde_json::json!(16384);
+    let response = create(over_quota).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(
+        response.body["errors"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("disk quota exceeded")
+    );
+
+    let response = app
+        .get("/api/client/account/activity")
+        .session(&session)
+        .send()
+        .await;
+    assert_eq!(
+        response.body["activities"]["data"][0]["event"],
+        "user:server.create"
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn concurrent_creations_cannot_exceed_quotas() {
+    let app = TestApp::new().await;
+    let user = app.create_user(false).await;
+    let session = app.create_session(&user.user).await;
+    let node = app.create_node(&app.wings).await;
+    for port in 25565..25571 {
+        app.create_allocation(&node, port).await;
+    }
+    let egg = app.create_egg().await;
+    NestEgg::update_deployable(app.state.database.write(), egg.uuid, true)
+        .await
+        .unwrap();
+    User::update_quotas(
+        &app.state.database,
+        user.user.uuid,
+        &Quotas {
+            servers: Some(2),
+            memory: Some(16384),
+            disk: Some(16384),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let responses = futures_util::future::join_all((0..6).map(|i| {
+        app.post("/api/client/servers")
+            .session(&session)
+            .json(serde_json::json!({
+                "egg_uuid": egg.uuid,
+                "location_uuid": node.location.uuid,
+                "name": format!("server {i}"),
+                "memory": 512,
+                "disk": 1024,
+            }))
+            .send()
+    }))
+    .await;
+
+    let created = responses
+        .iter()
+        .filter(|response| response.status == StatusCode::OK)
+        .count();
+    assert_eq!(created, 2);
+    for response in responses.iter().filter(|r| r.status != StatusCode::OK) {
+        response.assert_status(StatusCode::BAD_REQUEST);
+        assert!(
+            response.body["errors"][0]
+                .as_str()
+                .unwrap()
+                .starts_with("servers quota exceeded")
+        );
+    }
+}
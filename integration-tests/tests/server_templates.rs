@@ -0,0 +1,383 @@
+use axum::http::StatusCode;
+use integration_tests::TestApp;
+use shared::models::{
+    nest_egg_variable::{NestEggVariable, NestEggVariableKind},
+    server::Server,
+    server_variable::ServerVariable,
+};
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn servers_are_created_from_templates_and_updated_with_them() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let egg = app.create_egg().await;
+    NestEggVariable::create(
+        app.state.database.write(),
+        egg.uuid,
+        "Max Players",
+        None,
+        0,
+        "MAX_PLAYERS",
+        Some("10"),
+        true,
+        true,
+        &["required".to_string(), "integer".to_string()],
+        NestEggVariableKind::Integer,
+        &[],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let template = serde_json::json!({
+        "egg_uuid": egg.uuid,
+        "name": "Small",
+        "limits": { "cpu": 100, "memory": 1024, "swap": 0, "disk": 4096 },
+        "startup": "./start.sh --small",
+        "image": "alpine:latest",
+        "feature_limits": { "allocations": 1, "databases": 0, "backups": 1, "schedules": 0 },
+        "variables": { "MAX_PLAYERS": "20" },
+    });
+
+    let mut invalid = template.clone();
+    invalid["variables"] = serde_json::json!({ "MAX_PLAYERS": "many" });
+    app.post("/api/admin/server-templates")
+        .session(&session)
+        .json(invalid)
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let mut unknown = template.clone();
+    unknown["variables"] = serde_json::json!({ "UNKNOWN": "1" });
+    app.post("/api/admin/server-templates")
+        .session(&session)
+        .json(unknown)
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post("/api/admin/server-templates")
+        .session(&session)
+        .json(template.clone())
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let template_uuid = response.body["server_template"]["uuid"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    app.post("/api/admin/server-templates")
+        .session(&session)
+        .json(template)
+        .send()
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    // only the name and the memory are overridden, the rest comes from the template
+    let mut servers = Vec::new();
+    for (port, memory) in [(25565, 2048), (25566, 1024)] {
+        let allocation = app.create_allocation(&node, port).await;
+        let response = app
+            .post("/api/admin/servers")
+            .session(&session)
+            .json(serde_json::json!({
+                "node_uuid": node.uuid,
+                "owner_uuid": admin.user.uuid,
+                "template_uuid": template_uuid,
+                "allocation_uuid": allocation,
+                "start_on_completion": false,
+                "skip_scripts": true,
+                "name": format!("templated {port}"),
+                "limits": { "cpu": 100, "memory": memory, "swap": 0, "disk": 4096 },
+            }))
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+        assert_eq!(
+            response.body["server"]["server_template_uuid"],
+            template_uuid
+        );
+        assert_eq!(response.body["server"]["startup"], "./start.sh --small");
+        assert_eq!(response.body["server"]["limits"]["memory"], memory);
+        assert_eq!(response.body["server"]["feature_limits"]["backups"], 1);
+
+        servers.push(
+            response.body["server"]["uuid"]
+                .as_str()
+                .unwrap()
+                .parse::<uuid::Uuid>()
+                .unwrap(),
+        );
+    }
+
+    let variables =
+        ServerVariable::all_by_server_uuid_egg_uuid(&app.state.database, servers[0], egg.uuid)
+            .await
+            .unwrap();
+    assert_eq!(variables[0].value, "20");
+
+    // the second server moves to another egg, applying the template must leave it alone
+    let other_egg = app.create_egg().await;
+    sqlx::query("UPDATE servers SET egg_uuid = $1 WHERE uuid = $2")
+        .bind(other_egg.uuid)
+        .bind(servers[1])
+        .execute(app.state.database.write())
+        .await
+        .unwrap();
+
+    app.patch(format!("/api/admin/server-templates/{template_uuid}"))
+        .session(&session)
+        .json(serde_json::json!({
+            "limits": { "cpu": 200, "memory": 3072, "swap": 0, "disk": 8192 },
+            "image": "alpine:edge",
+            "variables": { "MAX_PLAYERS": "40" },
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app
+        .get(format!("/api/admin/server-templates/{template_uuid}"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["server_template"]["servers"], 2);
+    assert_eq!(response.body["server_template"]["image"], "alpine:edge");
+
+    let response = app
+        .post(format!("/api/admin/server-templates/{template_uuid}/apply"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.body["servers"],
+        serde_json::json!([servers[0].to_string()])
+    );
+    assert_eq!(response.body["skipped"], 1);
+
+    let updated = Server::by_uuid(&app.state.database, servers[0])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.memory, 3072);
+    assert_eq!(updated.cpu, 200);
+    assert_eq!(updated.image, "alpine:edge");
+    let variables =
+        ServerVariable::all_by_server_uuid_egg_uuid(&app.state.database, servers[0], egg.uuid)
+            .await
+            .unwrap();
+    assert_eq!(variables[0].value, "40");
+
+    let skipped = Server::by_uuid(&app.state.database, servers[1])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(skipped.memory, 1024);
+    assert_eq!(skipped.image, "alpine:latest");
+
+    // deleting the template keeps its servers
+    app.delete(format!("/api/admin/server-templates/{template_uuid}"))
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let server = Server::by_uuid(&app.state.database, servers[0])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(server.server_template_uuid, None);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn server_creation_without_template_requires_all_fields() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+
+    let mut payload = app.server_payload(&admin.user, &node, &egg, allocation);
+    payload.as_object_mut().unwrap().remove("startup");
+    app.post("/api/admin/servers")
+        .session(&session)
+        .json(payload)
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let mut payload = app.server_payload(&admin.user, &node, &egg, allocation);
+    payload["template_uuid"] = serde_json::json!(uuid::Uuid::new_v4());
+    app.post("/api/admin/servers")
+        .session(&session)
+        .json(payload)
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn secret_template_variables_are_encrypted_and_masked() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let egg = app.create_egg().await;
+    NestEggVariable::create(
+        app.state.database.write(),
+        egg.uuid,
+        "Rcon Password",
+        None,
+        0,
+        "RCON_PASSWORD",
+        None,
+        true,
+        true,
+        &["nullable".to_string(), "string".to_string()],
+        NestEggVariableKind::Secret,
+        &[],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let response = app
+        .post("/api/admin/server-templates")
+        .session(&session)
+        .json(serde_json::json!({
+            "egg_uuid": egg.uuid,
+            "name": "Secret",
+            "limits": { "cpu": 100, "memory": 1024, "swap": 0, "disk": 4096 },
+            "startup": "./start.sh",
+            "image": "alpine:latest",
+            "feature_limits": { "allocations": 1, "databases": 0, "backups": 0, "schedules": 0 },
+            "variables": { "RCON_PASSWORD": "hunter2" },
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let template_uuid = response.body["server_template"]["uuid"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(
+        response.body["server_template"]["variables"]["RCON_PASSWORD"].is_null(),
+        "secret values should be masked: {}",
+        response.body
+    );
+
+    let (variables, encrypted_variables): (serde_json::Value, Option<Vec<u8>>) = sqlx::query_as(
+        "SELECT variables, encrypted_variables FROM server_templates WHERE uuid = $1::uuid",
+    )
+    .bind(&template_uuid)
+    .fetch_one(app.state.database.read())
+    .await
+    .unwrap();
+    assert!(!variables.to_string().contains("hunter2"));
+    assert!(
+        app.state
+            .database
+            .decrypt(encrypted_variables.unwrap())
+            .unwrap()
+            .contains("hunter2")
+    );
+
+    let allocation = app.create_allocation(&node, 25565).await;
+    let response = app
+        .post("/api/admin/servers")
+        .session(&session)
+        .json(serde_json::json!({
+            "node_uuid": node.uuid,
+            "owner_uuid": admin.user.uuid,
+            "template_uuid": template_uuid,
+            "allocation_uuid": allocation,
+            "start_on_completion": false,
+            "skip_scripts": true,
+            "name": "templated",
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let server_uuid = response.body["server"]["uuid"]
+        .as_str()
+        .unwrap()
+        .parse::<uuid::Uuid>()
+        .unwrap();
+
+    let variables =
+        ServerVariable::all_by_server_uuid_egg_uuid(&app.state.database, server_uuid, egg.uuid)
+            .await
+            .unwrap();
+    assert_eq!(
+        variables[0].decrypted_value(&app.state.database).unwrap(),
+        "hunter2"
+    );
+
+    // the value is kept when only other fields are updated
+    app.patch(format!("/api/admin/server-templates/{template_uuid}"))
+        .session(&session)
+        .json(serde_json::json!({ "startup": "./start.sh --secret" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let response = app
+        .get(format!("/api/admin/server-templates/{template_uuid}"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert!(!response.body.to_string().contains("hunter2"));
+    assert!(
+        response.body["server_template"]["variables"]
+            .as_object()
+            .unwrap()
+            .contains_key("RCON_PASSWORD")
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn unknown_server_templates_are_rejected_on_every_route() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+
+    let path = format!("/api/admin/server-templates/{}", uuid::Uuid::new_v4());
+    app.get(&path)
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.patch(&path)
+        .session(&session)
+        .json(serde_json::json!({ "name": "renamed" }))
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.delete(&path)
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.post(format!("{path}/apply"))
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    app.get("/api/admin/server-templates/not-a-uuid")
+        .session(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
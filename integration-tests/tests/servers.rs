@@ -0,0 +1,381 @@
+use axum::http::{Method, StatusCode};
+use integration_tests::TestApp;
+use sqlx::Row;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn admin_creates_server_on_wings() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let owner = app.create_user(false).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+
+    let server = app
+        .create_server(&session, &owner.user, &node, &egg, allocation)
+        .await;
+    assert_eq!(server.node.uuid, node.uuid);
+    assert_eq!(server.owner.uuid, owner.user.uuid);
+    assert_eq!(
+        server.allocation.as_ref().map(|a| a.allocation.uuid),
+        Some(allocation)
+    );
+
+    let request = app
+        .wings
+        .find(Method::POST, "/api/servers")
+        .expect("wings should be asked to create the server");
+    assert_eq!(request.body["uuid"], server.uuid.to_string());
+    assert_eq!(
+        request.authorization,
+        Some(format!(
+            "Bearer {}",
+            app.state.database.decrypt(&node.token).unwrap()
+        ))
+    );
+
+    // the allocation is taken now
+    let response = app
+        .post("/api/admin/servers")
+        .session(&session)
+        .json(serde_json::json!({
+            "node_uuid": node.uuid,
+            "owner_uuid": owner.user.uuid,
+            "egg_uuid": egg.uuid,
+            "allocation_uuid": allocation,
+            "allocation_uuids": [],
+            "start_on_completion": false,
+            "skip_scripts": true,
+            "name": "second server",
+            "limits": { "cpu": 100, "memory": 1024, "swap": 0, "disk": 4096 },
+            "pinned_cpus": [],
+            "startup": egg.startup,
+            "image": "alpine:latest",
+            "feature_limits": { "allocations": 1, "databases": 0, "backups": 0, "schedules": 0 },
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn server_creation_is_rolled_back_when_wings_fails() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+
+    app.wings.respond(
+        Method::POST,
+        "/api/servers",
+        StatusCode::INTERNAL_SERVER_ERROR,
+        serde_json::json!({ "error": "disk full" }),
+    );
+
+    let response = app
+        .post("/api/admin/servers")
+        .session(&session)
+        .json(serde_json::json!({
+            "node_uuid": node.uuid,
+            "owner_uuid": admin.user.uuid,
+            "egg_uuid": egg.uuid,
+            "allocation_uuid": allocation,
+            "allocation_uuids": [],
+            "start_on_completion": false,
+            "skip_scripts": true,
+            "name": "doomed server",
+            "limits": { "cpu": 100, "memory": 1024, "swap": 0, "disk": 4096 },
+            "pinned_cpus": [],
+            "startup": egg.startup,
+            "image": "alpine:latest",
+            "feature_limits": { "allocations": 1, "databases": 0, "backups": 0, "schedules": 0 },
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+
+    let servers: i64 = sqlx::query("SELECT COUNT(*) AS count FROM servers")
+        .fetch_one(app.state.database.read())
+        .await
+        .unwrap()
+        .get("count");
+    assert_eq!(servers, 0);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn power_actions_are_sent_to_wings() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let owner = app.create_user(false).await;
+    let stranger = app.create_user(false).await;
+    let admin_session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&admin_session, &owner.user, &node, &egg, allocation)
+        .await;
+
+    let power_path = format!("/api/client/servers/{}/power", server.uuid);
+    let wings_path = format!("/api/servers/{}/power", server.uuid);
+
+    let session = app.create_session(&owner.user).await;
+    app.post(&power_path)
+        .session(&session)
+        .json(serde_json::json!({ "signal": "start" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let request = app
+        .wings
+        .find(Method::POST, &wings_path)
+        .expect("wings should receive the power action");
+    assert_eq!(request.body["action"], "start");
+
+    // other users can not see the server at all
+    let stranger_session = app.create_session(&stranger.user).await;
+    let response = app
+        .post(&power_path)
+        .session(&stranger_session)
+        .json(serde_json::json!({ "signal": "kill" }))
+        .send()
+        .await;
+    assert!(response.status.is_client_error());
+    assert_eq!(
+        app.wings
+            .requests()
+            .iter()
+            .filter(|r| r.path == wings_path)
+            .count(),
+        1
+    );
+
+    app.wings.respond(
+        Method::POST,
+        &wings_path,
+        StatusCode::BAD_GATEWAY,
+        serde_json::json!({ "error": "server is offline" }),
+    );
+    app.post(&power_path)
+        .session(&session)
+        .json(serde_json::json!({ "signal": "stop" }))
+        .send()
+        .await
+        .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+async fn stored_variable(
+    app: &TestApp,
+    server_uuid: uuid::Uuid,
+    env_variable: &str,
+) -> (String, Option<Vec<u8>>) {
+    sqlx::query_as(
+        r#"
+        SELECT server_variables.value, server_variables.encrypted_value
+        FROM server_variables
+        JOIN nest_egg_variables ON nest_egg_variables.uuid = server_variables.variable_uuid
+        WHERE server_variables.server_uuid = $1 AND nest_egg_variables.env_variable = $2
+        "#,
+    )
+    .bind(server_uuid)
+    .bind(env_variable)
+    .fetch_one(app.state.database.read())
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn secret_variables_are_encrypted_and_masked() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let node = app.create_node(&app.wings).await;
+    let allocation = app.create_allocation(&node, 25565).await;
+    let egg = app.create_egg().await;
+
+    let nest_uuid: uuid::Uuid =
+        sqlx::query_scalar("SELECT nest_eggs.nest_uuid FROM nest_eggs WHERE nest_eggs.uuid = $1")
+            .bind(egg.uuid)
+            .fetch_one(app.state.database.read())
+            .await
+            .unwrap();
+
+    let variable_path = format!("/api/admin/nests/{nest_uuid}/eggs/{}/variables", egg.uuid);
+    let response = app
+        .post(&variable_path)
+        .session(&session)
+        .json(serde_json::json!({
+            "name": "Rcon Password",
+            "order": 0,
+            "env_variable": "RCON_PASSWORD",
+            "user_viewable": true,
+            "user_editable": true,
+            "rules": ["nullable", "string"],
+            "kind": "secret",
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let variable_path = format!(
+        "{variable_path}/{}",
+        response.body["variable"]["uuid"].as_str().unwrap()
+    );
+
+    let mut payload = app.server_payload(&admin.user, &node, &egg, allocation);
+    payload["variables"] = serde_json::json!({ "RCON_PASSWORD": "hunter2" });
+    let response = app
+        .post("/api/admin/servers")
+        .session(&session)
+        .json(payload)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let server_uuid: uuid::Uuid = response.body["server"]["uuid"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    let (value, encrypted_value) = stored_variable(&app, server_uuid, "RCON_PASSWORD").await;
+    assert_eq!(value, "");
+    assert_eq!(
+        app.state
+            .database
+            .decrypt(encrypted_value.unwrap())
+            .unwrap(),
+        "hunter2"
+    );
+
+    let variables_path = format!("/api/admin/servers/{server_uuid}/variables");
+    app.put(&variables_path)
+        .session(&session)
+        .json(serde_json::json!({ "env_variable": "RCON_PASSWORD", "value": "swordfish" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app.get(&variables_path).session(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    let variable = response.body["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|variable| variable["env_variable"] == "RCON_PASSWORD")
+        .unwrap();
+    assert_eq!(variable["value"], "");
+
+    let logged: Vec<serde_json::Value> = sqlx::query_scalar(
+        r#"
+        SELECT admin_activities.data
+        FROM admin_activities
+        WHERE admin_activities.event IN ('server:create', 'server:variable.update')
+            AND admin_activities.data->>'uuid' = $1
+        "#,
+    )
+    .bind(server_uuid.to_string())
+    .fetch_all(app.state.database.read())
+    .await
+    .unwrap();
+    assert_eq!(logged.len(), 2);
+    for data in logged {
+        assert!(!data.to_string().contains("hunter2"));
+        assert!(!data.to_string().contains("swordfish"));
+    }
+
+    let response = app
+        .get(format!("/api/remote/servers/{server_uuid}"))
+        .bearer(&app.node_token(&node))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.body["settings"]["environment"]["RCON_PASSWORD"],
+        "swordfish"
+    );
+
+    // stored values follow the variable in and out of being a secret
+    for (kind, secret) in [("string", false), ("secret", true)] {
+        app.patch(&variable_path)
+            .session(&session)
+            .json(serde_json::json!({ "kind": kind }))
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+
+        let (value, encrypted_value) = stored_variable(&app, server_uuid, "RCON_PASSWORD").await;
+        assert_eq!(encrypted_value.is_some(), secret);
+        assert_eq!(value, if secret { "" } else { "swordfish" });
+    }
+
+    // a value that can not be decrypted is an error, never an empty secret
+    let (_, encrypted_value) = stored_variable(&app, server_uuid, "RCON_PASSWORD").await;
+    sqlx::query(
+        "UPDATE server_variables SET encrypted_value = $2 WHERE server_variables.server_uuid = $1",
+    )
+    .bind(server_uuid)
+    .bind(b"not encrypted".to_vec())
+    .execute(app.state.database.write())
+    .await
+    .unwrap();
+
+    app.get(format!("/api/remote/servers/{server_uuid}"))
+        .bearer(&app.node_token(&node))
+        .send()
+        .await
+        .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    let response = app
+        .patch(&variable_path)
+        .session(&session)
+        .json(serde_json::json!({ "kind": "string" }))
+        .send()
+        .await;
+    assert!(response.status.is_server_error());
+    let (value, stored) = stored_variable(&app, server_uuid, "RCON_PASSWORD").await;
+    assert_eq!(value, "");
+    assert_eq!(stored.as_deref(), Some(b"not encrypted".as_slice()));
+
+    sqlx::query(
+        "UPDATE server_variables SET encrypted_value = $2 WHERE server_variables.server_uuid = $1",
+    )
+    .bind(server_uuid)
+    .bind(encrypted_value)
+    .execute(app.state.database.write())
+    .await
+    .unwrap();
+
+    let response = app
+        .post("/api/admin/servers/bulk")
+        .session(&session)
+        .json(serde_json::json!({
+            "server_uuids": [server_uuid],
+            "action": { "type": "variable", "env_variable": "RCON_PASSWORD", "value": "letmein" },
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["job"]["action"]["value"], "");
+
+    let (action, encrypted_value): (serde_json::Value, Option<Vec<u8>>) = sqlx::query_as(
+        "SELECT action, encrypted_value FROM server_bulk_jobs WHERE uuid = $1::uuid",
+    )
+    .bind(response.body["job"]["uuid"].as_str().unwrap())
+    .fetch_one(app.state.database.read())
+    .await
+    .unwrap();
+    assert!(!action.to_string().contains("letmein"));
+    assert_eq!(
+        app.state
+            .database
+            .decrypt(encrypted_value.unwrap())
+            .unwrap(),
+        "letmein"
+    );
+}
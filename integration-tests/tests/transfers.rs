@@ -0,0 +1,228 @@
+use axum::http::{Method, StatusCode};
+use integration_tests::{MockWings, TestApp};
+use shared::{
+    jobs::{Job, server::TransferServer},
+    models::{node::Node, server::Server},
+};
+use sqlx::Row;
+
+struct Transfer {
+    source: Node,
+    destination: Node,
+    destination_wings: MockWings,
+    destination_allocation: uuid::Uuid,
+    server: Server,
+}
+
+async fn start_transfer(app: &TestApp) -> Transfer {
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let destination_wings = MockWings::start().await;
+    let source = app.create_node(&app.wings).await;
+    let destination = app.create_node(&destination_wings).await;
+    let source_allocation = app.create_allocation(&source, 25565).await;
+    let destination_allocation = app.create_allocation(&destination, 25565).await;
+    let egg = app.create_egg().await;
+    let server = app
+        .create_server(&session, &admin.user, &source, &egg, source_allocation)
+        .await;
+
+    app.post(format!("/api/admin/servers/{}/transfer", server.uuid))
+        .session(&session)
+        .json(serde_json::json!({
+            "node_uuid": destination.uuid,
+            "allocation_uuid": destination_allocation,
+            "allocation_uuids": [],
+            "backups": [],
+            "delete_source_backups": false,
+            "archive_format": "tar_gz",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    // the source node is asked to start the transfer by a queued job
+    let request = app
+        .wings
+        .wait_for(
+            Method::POST,
+            &format!("/api/servers/{}/transfer", server.uuid),
+        )
+        .await;
+    assert_eq!(
+        request.body["url"],
+        format!("{}/api/transfers", destination_wings.url())
+    );
+    assert!(
+        request.body["token"]
+            .as_str()
+            .is_some_and(|token| token.starts_with("Bearer "))
+    );
+
+    // transfers can not be started twice
+    app.post(format!("/api/admin/servers/{}/transfer", server.uuid))
+        .session(&session)
+        .json(serde_json::json!({
+            "node_uuid": destination.uuid,
+            "allocation_uuid": null,
+            "allocation_uuids": [],
+            "backups": [],
+            "delete_source_backups": false,
+            "archive_format": "tar_gz",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    Transfer {
+        source,
+        destination,
+        destination_wings,
+        destination_allocation,
+        server,
+    }
+}
+
+async fn server_row(app: &TestApp, server: uuid::Uuid) -> sqlx::postgres::PgRow {
+    sqlx::query(
+        "SELECT node_uuid, destination_node_uuid, (SELECT COUNT(*) FROM server_allocations WHERE server_allocations.server_uuid = servers.uuid) AS allocations FROM servers WHERE servers.uuid = $1",
+    )
+    .bind(server)
+    .fetch_one(app.state.database.read())
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn transfer_success_callback() {
+    let app = TestApp::new().await;
+    let transfer = start_transfer(&app).await;
+    let path = format!(
+        "/api/remote/servers/{}/transfer/success",
+        transfer.server.uuid
+    );
+
+    // only the destination may report success
+    app.post(&path)
+        .bearer(&app.node_token(&transfer.source))
+        .json(serde_json::json!({ "backups": [] }))
+        .send()
+        .await
+        .assert_status(StatusCode::EXPECTATION_FAILED);
+
+    app.post(&path)
+        .bearer(&app.node_token(&transfer.destination))
+        .json(serde_json::json!({ "backups": [] }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let row = server_row(&app, transfer.server.uuid).await;
+    assert_eq!(
+        row.get::<uuid::Uuid, _>("node_uuid"),
+        transfer.destination.uuid
+    );
+    assert_eq!(
+        row.get::<Option<uuid::Uuid>, _>("destination_node_uuid"),
+        None
+    );
+    assert_eq!(row.get::<i64, _>("allocations"), 1);
+
+    let server = Server::by_uuid(&app.state.database, transfer.server.uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        server.allocation.map(|a| a.allocation.uuid),
+        Some(transfer.destination_allocation)
+    );
+
+    assert!(
+        app.wings
+            .find(
+                Method::DELETE,
+                &format!("/api/servers/{}", transfer.server.uuid)
+            )
+            .is_some(),
+        "the source node should delete its copy of the server"
+    );
+    assert!(transfer.destination_wings.requests().is_empty());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn transfer_failure_callback() {
+    let app = TestApp::new().await;
+    let transfer = start_transfer(&app).await;
+    let path = format!(
+        "/api/remote/servers/{}/transfer/failure",
+        transfer.server.uuid
+    );
+
+    // only the source may report failure
+    app.post(&path)
+        .bearer(&app.node_token(&transfer.destination))
+        .send()
+        .await
+        .assert_status(StatusCode::EXPECTATION_FAILED);
+
+    app.post(&path)
+        .bearer(&app.node_token(&transfer.source))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let row = server_row(&app, transfer.server.uuid).await;
+    assert_eq!(row.get::<uuid::Uuid, _>("node_uuid"), transfer.source.uuid);
+    assert_eq!(
+        row.get::<Option<uuid::Uuid>, _>("destination_node_uuid"),
+        None
+    );
+    assert_eq!(row.get::<i64, _>("allocations"), 1);
+
+    assert!(
+        transfer
+            .destination_wings
+            .find(
+                Method::DELETE,
+                &format!("/api/servers/{}", transfer.server.uuid)
+            )
+            .is_some(),
+        "the destination node should delete the partial server"
+    );
+
+    // the server is no longer being transferred
+    app.post(&path)
+        .bearer(&app.node_token(&transfer.source))
+        .send()
+        .await
+        .assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn failed_transfer_jobs_cancel_the_transfer() {
+    let app = TestApp::new().await;
+    let transfer = start_transfer(&app).await;
+
+    // runs once the source node could not be reached for every attempt
+    TransferServer {
+        server_uuid: transfer.server.uuid,
+        destination_node_uuid: transfer.destination.uuid,
+        backups: Vec::new(),
+        delete_backups: false,
+        archive_format: wings_api::TransferArchiveFormat::TarGz,
+        compression_level: None,
+    }
+    .failed(&app.state, "connection refused")
+    .await;
+
+    let row = server_row(&app, transfer.server.uuid).await;
+    assert_eq!(row.get::<uuid::Uuid, _>("node_uuid"), transfer.source.uuid);
+    assert_eq!(
+        row.get::<Option<uuid::Uuid>, _>("destination_node_uuid"),
+        None
+    );
+    assert_eq!(row.get::<i64, _>("allocations"), 1);
+}
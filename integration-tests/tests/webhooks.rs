@@ -0,0 +1,187 @@
+use axum::http::{HeaderMap, StatusCode};
+use hmac::Mac;
+use integration_tests::TestApp;
+use shared::models::webhook_delivery::WebhookDelivery;
+use std::sync::{Arc, Mutex};
+
+type Received = Arc<Mutex<Vec<(HeaderMap, bytes::Bytes)>>>;
+
+/// Starts a receiver that records every delivery and answers with `204`.
+async fn start_receiver() -> (String, Received) {
+    let received = Received::default();
+    let router = axum::Router::new().route(
+        "/hook",
+        axum::routing::post({
+            let received = received.clone();
+
+            move |headers: HeaderMap, body: bytes::Bytes| async move {
+                received.lock().unwrap().push((headers, body));
+                StatusCode::NO_CONTENT
+            }
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    (url, received)
+}
+
+/// Waits until the job worker recorded `count` successful deliveries of the webhook.
+async fn wait_for_deliveries(
+    app: &TestApp,
+    session: &str,
+    webhook: &str,
+    count: usize,
+) -> Vec<serde_json::Value> {
+    for _ in 0..100 {
+        let response = app
+            .get(format!("/api/admin/webhooks/{webhook}/deliveries"))
+            .session(session)
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+
+        let deliveries = response.body["deliveries"]["data"].as_array().unwrap();
+        if deliveries
+            .iter()
+            .filter(|delivery| delivery["successful"] == true)
+            .count()
+            >= count
+        {
+            return deliveries.clone();
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    panic!("webhook {webhook} never received {count} deliveries");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL and TEST_REDIS_URL"]
+async fn webhooks_deliver_signed_payloads_and_mask_their_secret() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(true).await;
+    let session = app.create_session(&admin.user).await;
+    let (url, received) = start_receiver().await;
+
+    let secret = "a-shared-webhook-secret";
+    let response = app
+        .post("/api/admin/webhooks")
+        .session(&session)
+        .json(serde_json::json!({
+            "name": "receiver",
+            "url": url,
+            "secret": secret,
+            "events": ["webhook:*"],
+            "enabled": true,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["webhook"]["secret"], secret);
+    let uuid = response.body["webhook"]["uuid"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // a generated secret is shown once as well
+    let response = app
+        .post("/api/admin/webhooks")
+        .session(&session)
+        .json(serde_json::json!({
+            "name": "generated",
+            "url": url,
+            "events": ["server:*"],
+            "enabled": false,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.body["webhook"]["secret"].as_str().unwrap().len(),
+        48
+    );
+
+    let response = app
+        .get(format!("/api/admin/webhooks/{uuid}"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.body["webhook"]["secret"].is_null());
+    let response = app
+        .get("/api/admin/webhooks")
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert!(
+        response.body["webhooks"]["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|webhook| webhook["secret"].is_null())
+    );
+
+    // both creations are admin activity and match the filter of the first webhook
+    let deliveries = wait_for_deliveries(&app, &session, &uuid, 2).await;
+    assert_eq!(deliveries.len(), 2);
+    assert!(
+        deliveries
+            .iter()
+            .all(|delivery| delivery["response_status"] == 204)
+    );
+
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 2);
+    for (headers, body) in &received {
+        assert_eq!(headers["X-Webhook-Event"], "webhook:create");
+
+        let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        assert_eq!(
+            headers["X-Webhook-Signature"].to_str().unwrap(),
+            format!("sha256={:x}", mac.finalize().into_bytes())
+        );
+
+        let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload["type"], "admin");
+        assert_eq!(payload["user_uuid"], admin.user.uuid.to_string());
+    }
+
+    // old successful deliveries are pruned
+    sqlx::query(
+        "UPDATE webhook_deliveries SET created = NOW() - INTERVAL '31 days' WHERE uuid = $1",
+    )
+    .bind(
+        deliveries[0]["uuid"]
+            .as_str()
+            .unwrap()
+            .parse::<uuid::Uuid>()
+            .unwrap(),
+    )
+    .execute(app.state.database.write())
+    .await
+    .unwrap();
+    assert_eq!(
+        WebhookDelivery::prune(&app.state.database).await.unwrap(),
+        1
+    );
+
+    let response = app
+        .get(format!("/api/admin/webhooks/{uuid}/deliveries"))
+        .session(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.body["deliveries"]["data"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
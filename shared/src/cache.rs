@@ -4,7 +4,8 @@ use colored::Colorize;
 use rustis::{
     client::Client,
     commands::{
-        GenericCommands, InfoSection, ServerCommands, SetCondition, SetExpiration, StringCommands,
+        ConnectionCommands, GenericCommands, InfoSection, PingOptions, ServerCommands,
+        SetCondition, SetExpiration, StringCommands,
     },
     resp::Json,
 };
@@ -57,6 +58,13 @@ impl Cache {
         Ok(version)
     }
 
+    pub async fn ping(&self) -> Result<std::time::Duration, rustis::Error> {
+        let start = std::time::Instant::now();
+        let _: String = self.client.ping(PingOptions::default()).await?;
+
+        Ok(start.elapsed())
+    }
+
     pub async fn ratelimit(
         &self,
         limit_identifier: impl AsRef<str>,
@@ -100,6 +108,14 @@ impl Cache {
         Ok(())
     }
 
+    /// Marks `key` as used for `ttl` seconds, returns whether it was unused before.
+    /// Used to make tokens single use across panel instances.
+    pub async fn claim(&self, key: &str, ttl: u64) -> Result<bool, rustis::Error> {
+        self.client
+            .set_with_options(key, 1, SetCondition::NX, SetExpiration::Ex(ttl), false)
+            .await
+    }
+
     pub async fn cached<T, F, Fut>(
         &self,
         key: &str,
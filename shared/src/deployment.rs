@@ -0,0 +1,269 @@
+use crate::models::{BaseModel, node::Node, node_allocation::NodeAllocation};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+#[derive(ToSchema, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[schema(rename_all = "snake_case")]
+pub enum DeploymentStrategy {
+    /// The node with the lowest share of its memory assigned to servers.
+    #[default]
+    LeastMemoryUsed,
+    /// The node with the fewest servers.
+    LeastServers,
+    /// The node that received a server the longest time ago.
+    RoundRobin,
+}
+
+impl DeploymentStrategy {
+    #[inline]
+    fn order_by(self) -> &'static str {
+        match self {
+            Self::LeastMemoryUsed => "used.memory::float8 / GREATEST(nodes.memory, 1)",
+            Self::LeastServers => "used.servers",
+            Self::RoundRobin => "used.last_created NULLS FIRST",
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize, Deserialize, Clone, Copy)]
+pub struct DeploymentPortRange {
+    pub start_port: u16,
+    pub end_port: u16,
+}
+
+fn validate_port_ranges(port_ranges: &[DeploymentPortRange]) -> Result<(), ValidationError> {
+    if port_ranges
+        .iter()
+        .any(|range| range.start_port == 0 || range.start_port > range.end_port)
+    {
+        return Err(ValidationError::new("port_ranges")
+            .with_message("port ranges must be 1-65535 and start_port <= end_port".into()));
+    }
+
+    Ok(())
+}
+
+#[derive(ToSchema, Validate, Serialize, Deserialize, Clone)]
+pub struct DeploymentRequest {
+    #[validate(length(min = 1, max = 100))]
+    #[schema(min_items = 1, max_items = 100)]
+    pub location_uuids: Vec<uuid::Uuid>,
+    /// Restricts the allocations to these ports, any port is used if empty.
+    #[validate(length(max = 100), custom(function = "validate_port_ranges"))]
+    #[schema(max_items = 100)]
+    #[serde(default)]
+    pub port_ranges: Vec<DeploymentPortRange>,
+    #[serde(default)]
+    pub strategy: DeploymentStrategy,
+    /// The number of allocations to assign besides the primary one.
+    #[validate(range(min = 0, max = 32))]
+    #[schema(minimum = 0, maximum = 32)]
+    #[serde(default)]
+    pub additional_allocations: i64,
+}
+
+impl DeploymentRequest {
+    #[inline]
+    fn port_bounds(&self) -> (Vec<i32>, Vec<i32>) {
+        self.port_ranges
+            .iter()
+            .map(|range| (range.start_port as i32, range.end_port as i32))
+            .unzip()
+    }
+}
+
+/// Returned when no node satisfies a deployment request.
+#[derive(Debug)]
+pub struct NoDeploymentAvailable;
+
+impl std::fmt::Display for NoDeploymentAvailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("no node satisfies the deployment requirements")
+    }
+}
+
+impl std::error::Error for NoDeploymentAvailable {}
+
+pub struct Deployment {
+    pub node: Node,
+    /// The primary allocation comes first.
+    pub allocations: Vec<NodeAllocation>,
+}
+
+const FREE_ALLOCATION_FILTER: &str = r#"
+    NOT EXISTS (
+        SELECT 1
+        FROM server_allocations
+        WHERE server_allocations.allocation_uuid = node_allocations.uuid
+    )
+    AND (
+        cardinality($2::int4[]) = 0
+        OR EXISTS (
+            SELECT 1
+            FROM unnest($2::int4[], $3::int4[]) AS ranges(start_port, end_port)
+            WHERE node_allocations.port BETWEEN ranges.start_port AND ranges.end_port
+        )
+    )
+"#;
+
+/// Lists all nodes that could take the deployment, best match first.
+async fn candidates(
+    executor: impl sqlx::PgExecutor<'_>,
+    request: &DeploymentRequest,
+    memory: i64,
+    disk: i64,
+) -> Result<Vec<Node>, sqlx::Error> {
+    let (start_ports, end_ports) = request.port_bounds();
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {}
+        FROM nodes
+        JOIN locations ON locations.uuid = nodes.location_uuid
+        LEFT JOIN backup_configurations location_backup_configurations ON location_backup_configurations.uuid = locations.backup_configuration_uuid
+        LEFT JOIN backup_configurations node_backup_configurations ON node_backup_configurations.uuid = nodes.backup_configuration_uuid
+        CROSS JOIN LATERAL (
+            SELECT
+                COALESCE(SUM(servers.memory), 0)::int8 AS memory,
+                COALESCE(SUM(servers.disk), 0)::int8 AS disk,
+                COUNT(*) AS servers,
+                MAX(servers.created) AS last_created
+            FROM servers
+            WHERE servers.node_uuid = nodes.uuid
+        ) used
+        WHERE
+            nodes.location_uuid = ANY($1)
+            AND nodes.public
+            AND nodes.maintenance_message IS NULL
+            AND nodes.memory - used.memory >= $4
+            AND nodes.disk - used.disk >= $5
+            AND (
+                SELECT COUNT(*)
+                FROM node_allocations
+                WHERE node_allocations.node_uuid = nodes.uuid AND {FREE_ALLOCATION_FILTER}
+            ) >= $6
+        ORDER BY {}, nodes.created
+        "#,
+        Node::columns_sql(None),
+        request.strategy.order_by(),
+    ))
+    .bind(&request.location_uuids)
+    .bind(start_ports)
+    .bind(end_ports)
+    .bind(memory)
+    .bind(disk)
+    .bind(1 + request.additional_allocations)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| Node::map(None, &row)).collect())
+}
+
+async fn free_allocations(
+    executor: impl sqlx::PgExecutor<'_>,
+    request: &DeploymentRequest,
+    node_uuid: uuid::Uuid,
+    lock: bool,
+) -> Result<Vec<NodeAllocation>, sqlx::Error> {
+    let (start_ports, end_ports) = request.port_bounds();
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {}
+        FROM node_allocations
+        WHERE node_allocations.node_uuid = $1 AND {FREE_ALLOCATION_FILTER}
+        ORDER BY node_allocations.ip, node_allocations.port
+        LIMIT $4
+        {}
+        "#,
+        NodeAllocation::columns_sql(None),
+        if lock { "FOR UPDATE SKIP LOCKED" } else { "" },
+    ))
+    .bind(node_uuid)
+    .bind(start_ports)
+    .bind(end_ports)
+    .bind(1 + request.additional_allocations)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| NodeAllocation::map(None, &row))
+        .collect())
+}
+
+/// Resolves a deployment without reserving anything, used for dry runs.
+pub async fn plan(
+    database: &crate::database::Database,
+    request: &DeploymentRequest,
+    memory: i64,
+    disk: i64,
+) -> Result<Option<Deployment>, sqlx::Error> {
+    let Some(node) = candidates(database.read(), request, memory, disk)
+        .await?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+
+    let allocations = free_allocations(database.read(), request, node.uuid, false).await?;
+
+    Ok(Some(Deployment { node, allocations }))
+}
+
+/// Resolves a deployment inside `transaction`, locking the node and its allocations
+/// so concurrent deployments cannot pick the same resources until the transaction ends.
+pub async fn reserve(
+    transaction: &mut sqlx::PgTransaction<'_>,
+    request: &DeploymentRequest,
+    memory: i64,
+    disk: i64,
+) -> Result<Option<Deployment>, sqlx::Error> {
+    for node in candidates(&mut **transaction, request, memory, disk).await? {
+        // every node is tried in its own savepoint, rolling it back releases the locks on
+        // nodes that are skipped, so at most one node lock is held while waiting for another
+        let mut savepoint = sqlx::Acquire::begin(&mut **transaction).await?;
+
+        // deployments to the same node wait here for the transaction holding the lock
+        sqlx::query("SELECT 1 FROM nodes WHERE nodes.uuid = $1 FOR UPDATE")
+            .bind(node.uuid)
+            .execute(&mut *savepoint)
+            .await?;
+
+        // a statement only sees rows committed before it started, so the capacity is
+        // re-checked in its own statement after the lock, including servers created by
+        // the transaction that held it
+        let fits: bool = sqlx::query_scalar(
+            r#"
+            SELECT nodes.memory - COALESCE(SUM(servers.memory), 0) >= $2
+                AND nodes.disk - COALESCE(SUM(servers.disk), 0) >= $3
+            FROM nodes
+            LEFT JOIN servers ON servers.node_uuid = nodes.uuid
+            WHERE nodes.uuid = $1
+            GROUP BY nodes.uuid, nodes.memory, nodes.disk
+            "#,
+        )
+        .bind(node.uuid)
+        .bind(memory)
+        .bind(disk)
+        .fetch_one(&mut *savepoint)
+        .await?;
+        if !fits {
+            savepoint.rollback().await?;
+            continue;
+        }
+
+        let allocations = free_allocations(&mut *savepoint, request, node.uuid, true).await?;
+        if allocations.len() as i64 == 1 + request.additional_allocations {
+            savepoint.commit().await?;
+            return Ok(Some(Deployment { node, allocations }));
+        }
+
+        savepoint.rollback().await?;
+    }
+
+    Ok(None)
+}
@@ -33,6 +33,13 @@ pub struct Env {
     pub app_log_directory: String,
     pub app_encryption_key: String,
     pub server_name: Option<String>,
+
+    pub metrics_token: Option<String>,
+
+    pub mail_sendmail_command: Option<String>,
+    pub mail_file_directory: Option<String>,
+
+    pub egg_source_directory: Option<String>,
 }
 
 impl Env {
@@ -106,6 +113,25 @@ impl Env {
             server_name: std::env::var("SERVER_NAME")
                 .ok()
                 .map(|s| s.trim_matches('"').to_string()),
+
+            metrics_token: std::env::var("METRICS_TOKEN")
+                .ok()
+                .map(|s| s.trim_matches('"').to_string())
+                .filter(|s| !s.is_empty()),
+
+            mail_sendmail_command: std::env::var("MAIL_SENDMAIL_COMMAND")
+                .ok()
+                .map(|s| s.trim_matches('"').to_string())
+                .filter(|s| !s.is_empty()),
+            mail_file_directory: std::env::var("MAIL_FILE_DIRECTORY")
+                .ok()
+                .map(|s| s.trim_matches('"').to_string())
+                .filter(|s| !s.is_empty()),
+
+            egg_source_directory: std::env::var("EGG_SOURCE_DIRECTORY")
+                .ok()
+                .map(|s| s.trim_matches('"').to_string())
+                .filter(|s| !s.is_empty()),
         };
 
         if !std::path::Path::new(&env.app_log_directory).exists() {
@@ -0,0 +1,152 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+fn encrypt(database: &crate::database::Database, value: &str) -> Result<String, sqlx::Error> {
+    database
+        .encrypt(value)
+        .map(|value| base64::engine::general_purpose::STANDARD.encode(value))
+        .ok_or_else(|| sqlx::Error::Encode("failed to encrypt mail payload".into()))
+}
+
+fn decrypt(database: &crate::database::Database, value: &str) -> Result<String, anyhow::Error> {
+    database
+        .decrypt(base64::engine::general_purpose::STANDARD.decode(value)?)
+        .ok_or_else(|| anyhow::anyhow!("failed to decrypt mail payload"))
+}
+
+/// Mail bodies can contain password reset links, so they are stored encrypted.
+#[derive(Serialize, Deserialize)]
+pub struct SendMail {
+    pub destination: String,
+    pub subject: String,
+    body: String,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+impl SendMail {
+    pub fn new(
+        database: &crate::database::Database,
+        destination: String,
+        subject: String,
+        body: &str,
+        text: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            destination,
+            subject,
+            body: encrypt(database, body)?,
+            text: text.map(|text| encrypt(database, text)).transpose()?,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl super::Job for SendMail {
+    const KIND: &'static str = "mail:send";
+
+    fn redact(payload: &mut serde_json::Value) {
+        for field in ["body", "text"] {
+            if let Some(value) = payload.get_mut(field)
+                && !value.is_null()
+            {
+                *value = super::REDACTED.into();
+            }
+        }
+    }
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        let body = decrypt(&state.database, &self.body)?;
+        let text = self
+            .text
+            .as_deref()
+            .map(|text| decrypt(&state.database, text))
+            .transpose()?;
+
+        state
+            .mail
+            .deliver(&self.destination, &self.subject, body, text)
+            .await
+    }
+}
+
+/// Renders and queues a template mail for a user, resolved when the job runs so
+/// producers do not need the mail service. Variables can carry links with tokens,
+/// like password resets, so their values are stored encrypted.
+#[derive(Serialize, Deserialize)]
+pub struct SendTemplateMail {
+    pub user_uuid: uuid::Uuid,
+    pub server_uuid: Option<uuid::Uuid>,
+    pub template: crate::mail::template::MailTemplateKind,
+    variables: std::collections::BTreeMap<String, String>,
+}
+
+impl SendTemplateMail {
+    pub fn new(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+        server_uuid: Option<uuid::Uuid>,
+        template: crate::mail::template::MailTemplateKind,
+        variables: std::collections::BTreeMap<String, String>,
+    ) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            user_uuid,
+            server_uuid,
+            template,
+            variables: variables
+                .into_iter()
+                .map(|(key, value)| Ok((key, encrypt(database, &value)?)))
+                .collect::<Result<_, sqlx::Error>>()?,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl super::Job for SendTemplateMail {
+    const KIND: &'static str = "mail:send-template";
+
+    fn redact(payload: &mut serde_json::Value) {
+        if let Some(variables) = payload
+            .get_mut("variables")
+            .and_then(|variables| variables.as_object_mut())
+        {
+            for value in variables.values_mut() {
+                *value = super::REDACTED.into();
+            }
+        }
+    }
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        let user = match crate::models::user::User::by_uuid(&state.database, self.user_uuid).await?
+        {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let mut variables = self
+            .variables
+            .iter()
+            .map(|(key, value)| Ok((key.as_str(), decrypt(&state.database, value)?)))
+            .collect::<Result<std::collections::BTreeMap<_, _>, anyhow::Error>>()?;
+
+        if let Some(server_uuid) = self.server_uuid
+            && let Some(server) =
+                crate::models::server::Server::by_uuid(&state.database, server_uuid).await?
+        {
+            let app_url = state.settings.get().await.app.url.clone();
+
+            variables.insert("server_name", server.name);
+            variables.insert(
+                "server_link",
+                format!("{app_url}/server/{:08x}", server.uuid_short),
+            );
+        }
+
+        state
+            .mail
+            .send_template(&user, self.template, variables)
+            .await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use crate::models::queued_job::QueuedJob;
+use serde::{Serialize, de::DeserializeOwned};
+use std::sync::Arc;
+
+pub mod mail;
+pub mod nest;
+pub mod server;
+pub mod storage;
+pub mod webhook;
+
+/// The maximum amount of jobs a single process runs at once.
+const CONCURRENCY: usize = 16;
+
+/// How long a claimed job is locked for, running jobs renew their lock every [HEARTBEAT_INTERVAL]
+/// so only jobs of a process that went away are picked up again.
+const LOCK_DURATION: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+const HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Replaces secret values in payloads shown through the admin api.
+const REDACTED: &str = "[redacted]";
+
+/// Work that is persisted in the `queued_jobs` table and survives restarts. Jobs may run
+/// more than once and should be safe to repeat.
+#[async_trait::async_trait]
+pub trait Job: Serialize + DeserializeOwned + Send + Sync + 'static {
+    const KIND: &'static str;
+    const MAX_ATTEMPTS: i32 = 5;
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error>;
+
+    /// Redacts secret values of a stored payload before it is shown through the admin api.
+    fn redact(_payload: &mut serde_json::Value) {}
+
+    /// Called once the job ran out of attempts, before it is kept as failed.
+    async fn failed(&self, _state: &crate::State, _error: &str) {}
+}
+
+pub async fn enqueue<J: Job>(
+    database: &crate::database::Database,
+    job: &J,
+) -> Result<uuid::Uuid, sqlx::Error> {
+    enqueue_in(database, job, std::time::Duration::ZERO).await
+}
+
+pub async fn enqueue_in<J: Job>(
+    database: &crate::database::Database,
+    job: &J,
+    delay: std::time::Duration,
+) -> Result<uuid::Uuid, sqlx::Error> {
+    enqueue_with(database.write(), job, delay).await
+}
+
+/// Queues the job unless one of the same kind is still pending or running, for periodic
+/// work that every panel instance schedules.
+pub async fn enqueue_unique<J: Job>(
+    database: &crate::database::Database,
+    job: &J,
+) -> Result<Option<uuid::Uuid>, sqlx::Error> {
+    let mut transaction = database.write().begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(J::KIND)
+        .execute(&mut *transaction)
+        .await?;
+
+    if QueuedJob::active_by_kind_exists(&mut *transaction, J::KIND).await? {
+        return Ok(None);
+    }
+
+    let uuid = enqueue_with(&mut *transaction, job, std::time::Duration::ZERO).await?;
+    transaction.commit().await?;
+
+    Ok(Some(uuid))
+}
+
+/// Queues the job on the given executor, so it only exists once the surrounding
+/// transaction is committed.
+pub async fn enqueue_with<J: Job>(
+    executor: impl sqlx::PgExecutor<'_>,
+    job: &J,
+    delay: std::time::Duration,
+) -> Result<uuid::Uuid, sqlx::Error> {
+    tracing::debug!(kind = J::KIND, ?delay, "queueing job");
+
+    QueuedJob::create(
+        executor,
+        J::KIND,
+        serde_json::to_value(job).unwrap(),
+        J::MAX_ATTEMPTS,
+        delay,
+    )
+    .await
+}
+
+async fn handle<J: Job>(state: &crate::State, job: &QueuedJob) -> Result<(), anyhow::Error> {
+    let data: J = serde_json::from_value(job.payload.clone())?;
+
+    match data.run(state).await {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            if job.attempts >= job.max_attempts {
+                data.failed(state, &err.to_string()).await;
+            }
+
+            Err(err)
+        }
+    }
+}
+
+/// Returns the payload of a job with the secret values of its kind redacted.
+pub fn redact_payload(kind: &str, mut payload: serde_json::Value) -> serde_json::Value {
+    match kind {
+        mail::SendMail::KIND => mail::SendMail::redact(&mut payload),
+        mail::SendTemplateMail::KIND => mail::SendTemplateMail::redact(&mut payload),
+        storage::RemoveEmptyDirectory::KIND => storage::RemoveEmptyDirectory::redact(&mut payload),
+        server::SyncServer::KIND => server::SyncServer::redact(&mut payload),
+        server::CreateServerBackup::KIND => server::CreateServerBackup::redact(&mut payload),
+        server::VerifyServerBackup::KIND => server::VerifyServerBackup::redact(&mut payload),
+        server::PruneServerBackups::KIND => server::PruneServerBackups::redact(&mut payload),
+        server::RestoreServerBackup::KIND => server::RestoreServerBackup::redact(&mut payload),
+        server::DeleteServerBackup::KIND => server::DeleteServerBackup::redact(&mut payload),
+        server::TransferServer::KIND => server::TransferServer::redact(&mut payload),
+        server::SyncSubuserPermissions::KIND => {
+            server::SyncSubuserPermissions::redact(&mut payload)
+        }
+        server::RunServerBulkJob::KIND => server::RunServerBulkJob::redact(&mut payload),
+        nest::SyncNestEggSource::KIND => nest::SyncNestEggSource::redact(&mut payload),
+        webhook::DeliverWebhook::KIND => webhook::DeliverWebhook::redact(&mut payload),
+        webhook::PruneWebhookDeliveries::KIND => {
+            webhook::PruneWebhookDeliveries::redact(&mut payload)
+        }
+        // nothing is known about the payloads of unknown kinds
+        _ => payload = REDACTED.into(),
+    }
+
+    payload
+}
+
+async fn execute(state: &crate::State, job: QueuedJob) {
+    tracing::debug!(job = %job.uuid, kind = job.kind, attempt = job.attempts, "running job");
+
+    let run = run(state, &job);
+    tokio::pin!(run);
+
+    let mut heartbeat = tokio::time::interval_at(
+        tokio::time::Instant::now() + HEARTBEAT_INTERVAL,
+        HEARTBEAT_INTERVAL,
+    );
+    let result = loop {
+        tokio::select! {
+            result = &mut run => break result,
+            _ = heartbeat.tick() => {
+                if let Err(err) = job.extend_lock(&state.database, LOCK_DURATION).await {
+                    tracing::warn!(job = %job.uuid, "failed to extend job lock: {:#?}", err);
+                }
+            }
+        }
+    };
+
+    let recorded = match result {
+        Ok(()) => QueuedJob::delete_by_uuid(&state.database, job.uuid).await,
+        Err(err) => {
+            tracing::warn!(job = %job.uuid, kind = job.kind, "job failed: {:#?}", err);
+
+            job.record_failure(&state.database, &err.to_string()).await
+        }
+    };
+
+    if let Err(err) = recorded {
+        tracing::error!(job = %job.uuid, "failed to record job result: {:#?}", err);
+    }
+}
+
+async fn run(state: &crate::State, job: &QueuedJob) -> Result<(), anyhow::Error> {
+    match job.kind.as_str() {
+        mail::SendMail::KIND => handle::<mail::SendMail>(state, job).await,
+        mail::SendTemplateMail::KIND => handle::<mail::SendTemplateMail>(state, job).await,
+        storage::RemoveEmptyDirectory::KIND => {
+            handle::<storage::RemoveEmptyDirectory>(state, job).await
+        }
+        server::SyncServer::KIND => handle::<server::SyncServer>(state, job).await,
+        server::CreateServerBackup::KIND => handle::<server::CreateServerBackup>(state, job).await,
+        server::VerifyServerBackup::KIND => handle::<server::VerifyServerBackup>(state, job).await,
+        server::PruneServerBackups::KIND => handle::<server::PruneServerBackups>(state, job).await,
+        server::RestoreServerBackup::KIND => {
+            handle::<server::RestoreServerBackup>(state, job).await
+        }
+        server::DeleteServerBackup::KIND => handle::<server::DeleteServerBackup>(state, job).await,
+        server::TransferServer::KIND => handle::<server::TransferServer>(state, job).await,
+        server::SyncSubuserPermissions::KIND => {
+            handle::<server::SyncSubuserPermissions>(state, job).await
+        }
+        server::RunServerBulkJob::KIND => handle::<server::RunServerBulkJob>(state, job).await,
+        nest::SyncNestEggSource::KIND => handle::<nest::SyncNestEggSource>(state, job).await,
+        webhook::DeliverWebhook::KIND => handle::<webhook::DeliverWebhook>(state, job).await,
+        webhook::PruneWebhookDeliveries::KIND => {
+            handle::<webhook::PruneWebhookDeliveries>(state, job).await
+        }
+        kind => Err(anyhow::anyhow!("unknown job kind {kind}")),
+    }
+}
+
+/// Runs due jobs until the process exits. Rows are claimed with `SKIP LOCKED`,
+/// so every panel instance can run a worker.
+pub async fn work(state: crate::State) {
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(CONCURRENCY));
+
+    loop {
+        let available = semaphore.available_permits();
+
+        if available > 0 {
+            match QueuedJob::claim_pending(&state.database, available as i64, LOCK_DURATION).await {
+                Ok(jobs) => {
+                    for job in jobs {
+                        let permit = semaphore.clone().acquire_owned().await.unwrap();
+                        let state = state.clone();
+
+                        tokio::spawn(async move {
+                            execute(&state, job).await;
+                            drop(permit);
+                        });
+                    }
+                }
+                Err(err) => tracing::error!("failed to claim queued jobs: {:#?}", err),
+            }
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+}
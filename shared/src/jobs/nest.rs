@@ -0,0 +1,43 @@
+use crate::models::nest_egg_source::NestEggSource;
+use serde::{Deserialize, Serialize};
+
+/// Syncs the eggs of a nest with an egg source, the outcome is stored on the source.
+#[derive(Serialize, Deserialize)]
+pub struct SyncNestEggSource {
+    pub source_uuid: uuid::Uuid,
+}
+
+#[async_trait::async_trait]
+impl super::Job for SyncNestEggSource {
+    const KIND: &'static str = "nest:egg-source.sync";
+    const MAX_ATTEMPTS: i32 = 3;
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        let source = match NestEggSource::by_uuid(&state.database, self.source_uuid).await? {
+            Some(source) => source,
+            None => return Ok(()),
+        };
+
+        let report = source.sync(&state.database, &state.env, false).await?;
+        tracing::info!(
+            source = %source.uuid,
+            created = report.created.len(),
+            updated = report.updated.len(),
+            modified = report.modified.len(),
+            failed = report.failed.len(),
+            "synced egg source"
+        );
+
+        NestEggSource::update_sync_result(&state.database, source.uuid, Ok(&report)).await?;
+
+        Ok(())
+    }
+
+    async fn failed(&self, state: &crate::State, error: &str) {
+        if let Err(err) =
+            NestEggSource::update_sync_result(&state.database, self.source_uuid, Err(error)).await
+        {
+            tracing::error!(source = %self.source_uuid, "failed to update egg source: {:#?}", err);
+        }
+    }
+}
@@ -0,0 +1,435 @@
+use crate::models::{
+    node::Node,
+    server::{Server, ServerStatus},
+    server_backup::{BackupDisk, BackupVerificationStatus, ServerBackup},
+    server_bulk_job::ServerBulkJob,
+    server_subuser::ServerSubuser,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Pushes the current configuration of a server to its node.
+#[derive(Serialize, Deserialize)]
+pub struct SyncServer {
+    pub server_uuid: uuid::Uuid,
+}
+
+#[async_trait::async_trait]
+impl super::Job for SyncServer {
+    const KIND: &'static str = "server:sync";
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        match Server::by_uuid(&state.database, self.server_uuid).await? {
+            Some(server) => server.sync(&state.database).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateServerBackup {
+    pub server_uuid: uuid::Uuid,
+    pub backup_uuid: uuid::Uuid,
+    pub disk: BackupDisk,
+    pub ignored_files: Vec<String>,
+}
+
+#[async_trait::async_trait]
+impl super::Job for CreateServerBackup {
+    const KIND: &'static str = "server:backup.create";
+    const MAX_ATTEMPTS: i32 = 3;
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        let server = match Server::by_uuid(&state.database, self.server_uuid).await? {
+            Some(server) => server,
+            None => return Ok(()),
+        };
+
+        tracing::debug!(backup = %self.backup_uuid, "creating server backup");
+
+        if let Err((_, err)) = server
+            .node
+            .api_client(&state.database)
+            .post_servers_server_backup(
+                server.uuid,
+                &wings_api::servers_server_backup::post::RequestBody {
+                    adapter: self.disk.to_wings_adapter(),
+                    uuid: self.backup_uuid,
+                    ignore: self.ignored_files.join("\n"),
+                },
+            )
+            .await
+        {
+            return Err(anyhow::anyhow!(err.error));
+        }
+
+        Ok(())
+    }
+
+    async fn failed(&self, state: &crate::State, _error: &str) {
+        if let Err(err) = sqlx::query(
+            r#"
+            UPDATE server_backups
+            SET successful = false, completed = NOW()
+            WHERE server_backups.uuid = $1
+            "#,
+        )
+        .bind(self.backup_uuid)
+        .execute(state.database.write())
+        .await
+        {
+            tracing::error!(backup = %self.backup_uuid, "failed to update server backup status: {:#?}", err);
+        }
+
+        if let Err(err) =
+            ServerBackup::resume_pending_restores(&state.database, self.backup_uuid, false).await
+        {
+            tracing::error!(backup = %self.backup_uuid, "failed to release servers waiting for backup: {:#?}", err);
+        }
+    }
+}
+
+/// Restores a backup into a server that was put into the restoring state beforehand,
+/// the server is released again if the restore cannot be started.
+#[derive(Serialize, Deserialize)]
+pub struct RestoreServerBackup {
+    pub server_uuid: uuid::Uuid,
+    pub backup_uuid: uuid::Uuid,
+}
+
+#[async_trait::async_trait]
+impl super::Job for RestoreServerBackup {
+    const KIND: &'static str = "server:backup.restore";
+    const MAX_ATTEMPTS: i32 = 3;
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        let server = match Server::by_uuid(&state.database, self.server_uuid).await? {
+            Some(server) if server.status == Some(ServerStatus::RestoringBackup) => server,
+            _ => return Ok(()),
+        };
+        let backup = match ServerBackup::by_uuid(&state.database, self.backup_uuid).await? {
+            Some(backup) if backup.deleted.is_none() => backup,
+            _ => return Err(anyhow::anyhow!("backup not found")),
+        };
+
+        backup
+            .restore(&state.database, &state.jwt, server, true)
+            .await
+    }
+
+    async fn failed(&self, state: &crate::State, _error: &str) {
+        if let Err(err) =
+            ServerBackup::stop_restoring(&state.database, self.server_uuid, self.backup_uuid).await
+        {
+            tracing::error!(server = %self.server_uuid, "failed to release server after restore: {:#?}", err);
+        }
+    }
+}
+
+/// Removes the archive of a backup that was already marked as deleted, see
+/// [`ServerBackup::queue_deletion`].
+#[derive(Serialize, Deserialize)]
+pub struct DeleteServerBackup {
+    pub backup_uuid: uuid::Uuid,
+}
+
+#[async_trait::async_trait]
+impl super::Job for DeleteServerBackup {
+    const KIND: &'static str = "server:backup.delete";
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        let backup = match ServerBackup::by_uuid(&state.database, self.backup_uuid).await? {
+            Some(backup) => backup,
+            None => return Ok(()),
+        };
+
+        let server = match backup.server_uuid {
+            Some(server_uuid) => Server::by_uuid(&state.database, server_uuid).await?,
+            None => None,
+        };
+
+        match server {
+            Some(server) => backup.delete(&state.database, &server).await,
+            None => {
+                let node = Node::by_uuid(&state.database, backup.node_uuid)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("node of the backup not found"))?;
+
+                backup.delete_detached(&state.database, node).await
+            }
+        }
+    }
+}
+
+/// Asks the node of a server to send it to the destination of its transfer, the
+/// transfer is cancelled if the node cannot be reached.
+#[derive(Serialize, Deserialize)]
+pub struct TransferServer {
+    pub server_uuid: uuid::Uuid,
+    pub destination_node_uuid: uuid::Uuid,
+    pub backups: Vec<uuid::Uuid>,
+    pub delete_backups: bool,
+    pub archive_format: wings_api::TransferArchiveFormat,
+    pub compression_level: Option<wings_api::CompressionLevel>,
+}
+
+#[async_trait::async_trait]
+impl super::Job for TransferServer {
+    const KIND: &'static str = "server:transfer";
+    const MAX_ATTEMPTS: i32 = 3;
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        let server = match Server::by_uuid(&state.database, self.server_uuid).await? {
+            Some(server) if server.destination_node_uuid == Some(self.destination_node_uuid) => {
+                server
+            }
+            _ => return Ok(()),
+        };
+        let destination_node = Node::by_uuid(&state.database, self.destination_node_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("destination node not found"))?;
+
+        let token = destination_node.create_jwt(
+            &state.database,
+            &state.jwt,
+            &crate::jwt::BasePayload {
+                issuer: "panel".into(),
+                subject: Some(server.uuid.to_string()),
+                audience: Vec::new(),
+                expiration_time: Some(chrono::Utc::now().timestamp() + 600),
+                not_before: None,
+                issued_at: Some(chrono::Utc::now().timestamp()),
+                jwt_id: server.node.uuid.to_string(),
+            },
+        )?;
+
+        let mut url = destination_node.url;
+        url.set_path("/api/transfers");
+
+        if let Err((_, err)) = server
+            .node
+            .api_client(&state.database)
+            .post_servers_server_transfer(
+                server.uuid,
+                &wings_api::servers_server_transfer::post::RequestBody {
+                    url: url.to_string(),
+                    token: format!("Bearer {token}"),
+                    backups: self.backups.clone(),
+                    delete_backups: self.delete_backups,
+                    archive_format: self.archive_format,
+                    compression_level: self.compression_level,
+                },
+            )
+            .await
+        {
+            return Err(anyhow::anyhow!(err.error));
+        }
+
+        Ok(())
+    }
+
+    async fn failed(&self, state: &crate::State, _error: &str) {
+        let server = match Server::by_uuid(&state.database, self.server_uuid).await {
+            Ok(Some(server))
+                if server.destination_node_uuid == Some(self.destination_node_uuid) =>
+            {
+                server
+            }
+            Ok(_) => return,
+            Err(err) => {
+                tracing::error!(server = %self.server_uuid, "failed to load server of transfer: {:#?}", err);
+                return;
+            }
+        };
+
+        if let Err(err) = server.cancel_transfer(&state.database).await {
+            tracing::error!(server = %self.server_uuid, "failed to cancel server transfer: {:#?}", err);
+        }
+    }
+}
+
+/// Checks a backup for corruption and records the outcome on the backup.
+#[derive(Serialize, Deserialize)]
+pub struct VerifyServerBackup {
+    pub server_uuid: uuid::Uuid,
+    pub backup_uuid: uuid::Uuid,
+}
+
+#[async_trait::async_trait]
+impl super::Job for VerifyServerBackup {
+    const KIND: &'static str = "server:backup.verify";
+    const MAX_ATTEMPTS: i32 = 3;
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        // a verification that cannot run anymore must not stay pending
+        let server = match Server::by_uuid(&state.database, self.server_uuid).await? {
+            Some(server) => server,
+            None => {
+                ServerBackup::reset_verification(&state.database, self.backup_uuid).await?;
+
+                return Ok(());
+            }
+        };
+        let backup = match ServerBackup::by_server_uuid_uuid(
+            &state.database,
+            self.server_uuid,
+            self.backup_uuid,
+        )
+        .await?
+        {
+            Some(backup) if backup.deleted.is_none() => backup,
+            _ => {
+                ServerBackup::reset_verification(&state.database, self.backup_uuid).await?;
+
+                return Ok(());
+            }
+        };
+
+        let (status, error) = match backup.verify(&state.database, &server).await? {
+            None => (BackupVerificationStatus::Successful, None),
+            Some(error) => {
+                tracing::warn!(backup = %backup.uuid, "backup failed verification: {}", error);
+
+                (BackupVerificationStatus::Failed, Some(error))
+            }
+        };
+
+        ServerBackup::update_verification(&state.database, backup.uuid, status, error.as_deref())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn failed(&self, state: &crate::State, error: &str) {
+        if let Err(err) = ServerBackup::update_verification(
+            &state.database,
+            self.backup_uuid,
+            BackupVerificationStatus::Failed,
+            Some(&format!("unable to verify backup: {error}")),
+        )
+        .await
+        {
+            tracing::error!(backup = %self.backup_uuid, "failed to update backup verification: {:#?}", err);
+        }
+    }
+}
+
+/// Queues the deletion of the expired backups of every server, queued once an hour.
+#[derive(Serialize, Deserialize)]
+pub struct PruneServerBackups {}
+
+#[async_trait::async_trait]
+impl super::Job for PruneServerBackups {
+    const KIND: &'static str = "server:backup.prune";
+    const MAX_ATTEMPTS: i32 = 1;
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        match ServerBackup::prune(&state.database).await? {
+            0 => {}
+            queued => tracing::info!(queued, "queued deletion of expired server backups"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Sends the current permissions of a subuser to wings, a removed subuser loses
+/// all permissions and its open websocket connections.
+#[derive(Serialize, Deserialize)]
+pub struct SyncSubuserPermissions {
+    pub server_uuid: uuid::Uuid,
+    pub user_uuid: uuid::Uuid,
+}
+
+#[async_trait::async_trait]
+impl super::Job for SyncSubuserPermissions {
+    const KIND: &'static str = "server:subuser.sync";
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        let mut server = match Server::by_uuid(&state.database, self.server_uuid).await? {
+            Some(server) => server,
+            None => return Ok(()),
+        };
+
+        let subuser = ServerSubuser::by_server_uuid_user_uuid(
+            &state.database,
+            self.server_uuid,
+            self.user_uuid,
+        )
+        .await?;
+
+        let (permissions, ignored_files) = match subuser {
+            Some(subuser) => {
+                server.subuser_permissions = Some(Arc::new(subuser.permissions));
+
+                (
+                    server
+                        .wings_permissions(&subuser.user)
+                        .into_iter()
+                        .map(String::from)
+                        .collect(),
+                    subuser.ignored_files,
+                )
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+        let removed = permissions.is_empty();
+
+        if let Err((_, err)) = server
+            .node
+            .api_client(&state.database)
+            .post_servers_server_ws_permissions(
+                server.uuid,
+                &wings_api::servers_server_ws_permissions::post::RequestBody {
+                    user_permissions: vec![
+                        wings_api::servers_server_ws_permissions::post::RequestBodyUserPermissions {
+                            user: self.user_uuid,
+                            permissions,
+                            ignored_files,
+                        },
+                    ],
+                },
+            )
+            .await
+        {
+            return Err(anyhow::anyhow!(err.error));
+        }
+
+        if removed
+            && let Err((_, err)) = server
+                .node
+                .api_client(&state.database)
+                .post_servers_server_ws_deny(
+                    server.uuid,
+                    &wings_api::servers_server_ws_deny::post::RequestBody {
+                        jtis: vec![self.user_uuid.to_string()],
+                    },
+                )
+                .await
+        {
+            return Err(anyhow::anyhow!(err.error));
+        }
+
+        Ok(())
+    }
+}
+
+/// Works through the pending servers of a bulk job, a retry only picks up
+/// servers that did not finish yet.
+#[derive(Serialize, Deserialize)]
+pub struct RunServerBulkJob {
+    pub job_uuid: uuid::Uuid,
+}
+
+#[async_trait::async_trait]
+impl super::Job for RunServerBulkJob {
+    const KIND: &'static str = "server:bulk";
+    const MAX_ATTEMPTS: i32 = 3;
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        match ServerBulkJob::by_uuid(&state.database, self.job_uuid).await? {
+            Some(job) => Ok(job.run(&state.database).await?),
+            None => Ok(()),
+        }
+    }
+}
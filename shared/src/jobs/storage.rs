@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Removes a directory of the filesystem storage driver once it is empty.
+#[derive(Serialize, Deserialize)]
+pub struct RemoveEmptyDirectory {
+    pub path: std::path::PathBuf,
+}
+
+#[async_trait::async_trait]
+impl super::Job for RemoveEmptyDirectory {
+    const KIND: &'static str = "storage:remove_empty_directory";
+    const MAX_ATTEMPTS: i32 = 1;
+
+    async fn run(&self, _state: &crate::State) -> Result<(), anyhow::Error> {
+        let mut directory = match tokio::fs::read_dir(&self.path).await {
+            Ok(directory) => directory,
+            Err(_) => return Ok(()),
+        };
+
+        if directory.next_entry().await?.is_none() {
+            tokio::fs::remove_dir(&self.path).await.ok();
+        }
+
+        Ok(())
+    }
+}
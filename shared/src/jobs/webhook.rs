@@ -0,0 +1,38 @@
+use crate::models::webhook_delivery::WebhookDelivery;
+use serde::{Deserialize, Serialize};
+
+/// Sends a webhook delivery, failed attempts queue the next one themselves.
+#[derive(Serialize, Deserialize)]
+pub struct DeliverWebhook {
+    pub delivery_uuid: uuid::Uuid,
+}
+
+#[async_trait::async_trait]
+impl super::Job for DeliverWebhook {
+    const KIND: &'static str = "webhook:deliver";
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        WebhookDelivery::deliver(&state.database, &state.client, self.delivery_uuid).await?;
+
+        Ok(())
+    }
+}
+
+/// Deletes old successful webhook deliveries, queued once an hour.
+#[derive(Serialize, Deserialize)]
+pub struct PruneWebhookDeliveries {}
+
+#[async_trait::async_trait]
+impl super::Job for PruneWebhookDeliveries {
+    const KIND: &'static str = "webhook:delivery.prune";
+    const MAX_ATTEMPTS: i32 = 1;
+
+    async fn run(&self, state: &crate::State) -> Result<(), anyhow::Error> {
+        match WebhookDelivery::prune(&state.database).await? {
+            0 => {}
+            deleted => tracing::info!(deleted, "pruned webhook deliveries"),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use ldap3::{LdapConnAsync, LdapConnSettings, Scope, SearchEntry};
+use std::time::Duration;
+
+/// LDAP result code for a failed bind.
+const INVALID_CREDENTIALS: u32 = 49;
+
+pub struct LdapUser {
+    pub dn: String,
+    pub email: Option<String>,
+    pub name_first: Option<String>,
+    pub name_last: Option<String>,
+    pub groups: Vec<String>,
+}
+
+impl LdapUser {
+    /// Checks whether the user is a member of `group`, which may be a full DN
+    /// or just the value of its first RDN (`CN=Panel Admins,OU=Groups,...` matches `Panel Admins`).
+    pub fn is_member_of(&self, group: &str) -> bool {
+        self.groups.iter().any(|dn| {
+            dn.eq_ignore_ascii_case(group)
+                || dn
+                    .split(',')
+                    .next()
+                    .and_then(|rdn| rdn.split_once('='))
+                    .is_some_and(|(_, value)| value.trim().eq_ignore_ascii_case(group))
+        })
+    }
+}
+
+/// Binds as the user and looks up their entry, returns `None` if the credentials are invalid
+/// or the search yields no entry.
+pub async fn authenticate(
+    settings: &crate::settings::AppSettingsLdap,
+    username: &str,
+    password: &str,
+) -> Result<Option<LdapUser>, anyhow::Error> {
+    // an empty password would be an unauthenticated bind, which most servers accept
+    if password.is_empty() {
+        return Ok(None);
+    }
+
+    let (conn, mut ldap) = LdapConnAsync::with_settings(
+        LdapConnSettings::new()
+            .set_conn_timeout(Duration::from_secs(10))
+            .set_starttls(settings.starttls),
+        &settings.url,
+    )
+    .await?;
+    ldap3::drive!(conn);
+
+    let bind = ldap
+        .with_timeout(Duration::from_secs(10))
+        .simple_bind(
+            &settings
+                .bind_dn
+                .replace("{username}", &ldap3::dn_escape(username)),
+            password,
+        )
+        .await?;
+    if bind.rc == INVALID_CREDENTIALS {
+        let _ = ldap.unbind().await;
+        return Ok(None);
+    }
+    bind.success()?;
+
+    let mut attributes = vec![
+        settings.email_attribute.as_str(),
+        settings.name_first_attribute.as_str(),
+        settings.name_last_attribute.as_str(),
+    ];
+    if let Some(groups_attribute) = &settings.groups_attribute {
+        attributes.push(groups_attribute);
+    }
+
+    let (entries, _) = ldap
+        .with_timeout(Duration::from_secs(10))
+        .search(
+            &settings.search_base,
+            Scope::Subtree,
+            &settings
+                .search_filter
+                .replace("{username}", &ldap3::ldap_escape(username)),
+            attributes,
+        )
+        .await?
+        .success()?;
+    let _ = ldap.unbind().await;
+
+    let entry = match entries.into_iter().next() {
+        Some(entry) => SearchEntry::construct(entry),
+        None => return Ok(None),
+    };
+
+    // attribute names are case insensitive
+    let values = |attribute: &str| {
+        entry
+            .attrs
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(attribute))
+            .map(|(_, values)| values.clone())
+            .unwrap_or_default()
+    };
+    let value = |attribute: &str| values(attribute).into_iter().find(|v| !v.is_empty());
+
+    Ok(Some(LdapUser {
+        email: value(&settings.email_attribute),
+        name_first: value(&settings.name_first_attribute),
+        name_last: value(&settings.name_last_attribute),
+        groups: settings
+            .groups_attribute
+            .as_deref()
+            .map(values)
+            .unwrap_or_default(),
+        dn: entry.dn,
+    }))
+}
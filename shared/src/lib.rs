@@ -5,13 +5,18 @@ use utoipa::ToSchema;
 pub mod cache;
 pub mod captcha;
 pub mod database;
+pub mod deployment;
 pub mod deserialize;
 pub mod env;
 pub mod extensions;
 pub mod extract;
+pub mod jobs;
 pub mod jwt;
+pub mod ldap;
 pub mod mail;
+pub mod metrics;
 pub mod models;
+pub mod oidc;
 pub mod permissions;
 pub mod response;
 pub mod settings;
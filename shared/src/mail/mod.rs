@@ -0,0 +1,384 @@
+use crate::settings::{MailHttpProvider, MailMode};
+use lettre::{AsyncTransport, message::Mailbox};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, LazyLock},
+};
+use template::{MailTemplateKind, Template};
+
+pub mod template;
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .user_agent(format!("github.com/calagopus-rs/panel {}", crate::VERSION))
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .expect("Failed to create HTTP client")
+});
+
+enum Transport {
+    None,
+    Smtp {
+        transport: lettre::AsyncSmtpTransport<lettre::Tokio1Executor>,
+        from: Mailbox,
+    },
+    Sendmail {
+        transport: lettre::AsyncSendmailTransport<lettre::Tokio1Executor>,
+        from: Mailbox,
+    },
+    File {
+        transport: lettre::AsyncFileTransport<lettre::Tokio1Executor>,
+        path: String,
+        from: Mailbox,
+    },
+    Http {
+        provider: MailHttpProvider,
+        endpoint: String,
+        api_key: String,
+        from: Mailbox,
+    },
+}
+
+impl Transport {
+    fn new(mail_mode: &MailMode, env: &crate::env::Env) -> Result<Self, anyhow::Error> {
+        let from = |from_address: &str, from_name: &Option<String>| {
+            Ok::<_, anyhow::Error>(Mailbox::new(from_name.clone(), from_address.parse()?))
+        };
+
+        match mail_mode {
+            MailMode::None => Ok(Self::None),
+            MailMode::Smtp {
+                host,
+                port,
+                username,
+                password,
+                use_tls,
+                from_address,
+                from_name,
+            } => {
+                let mut transport =
+                    lettre::AsyncSmtpTransport::<lettre::Tokio1Executor>::builder_dangerous(host)
+                        .port(*port)
+                        .tls(if *use_tls {
+                            lettre::transport::smtp::client::Tls::Required(
+                                lettre::transport::smtp::client::TlsParametersBuilder::new(
+                                    host.clone(),
+                                )
+                                .build_rustls()
+                                .unwrap(),
+                            )
+                        } else {
+                            lettre::transport::smtp::client::Tls::None
+                        });
+
+                if let Some(username) = username {
+                    transport = transport.credentials(
+                        lettre::transport::smtp::authentication::Credentials::new(
+                            username.clone(),
+                            password.clone().unwrap_or_default(),
+                        ),
+                    );
+                }
+
+                Ok(Self::Smtp {
+                    transport: transport.build(),
+                    from: from(from_address, from_name)?,
+                })
+            }
+            MailMode::Sendmail {
+                from_address,
+                from_name,
+            } => Ok(Self::Sendmail {
+                transport: match &env.mail_sendmail_command {
+                    Some(command) => lettre::AsyncSendmailTransport::new_with_command(command),
+                    None => lettre::AsyncSendmailTransport::new(),
+                },
+                from: from(from_address, from_name)?,
+            }),
+            MailMode::File {
+                from_address,
+                from_name,
+            } => {
+                let path = env.mail_file_directory.clone().ok_or_else(|| {
+                    anyhow::anyhow!("the file mail mode requires MAIL_FILE_DIRECTORY to be set")
+                })?;
+
+                Ok(Self::File {
+                    transport: lettre::AsyncFileTransport::new(&path),
+                    path,
+                    from: from(from_address, from_name)?,
+                })
+            }
+            MailMode::Http {
+                provider,
+                endpoint,
+                api_key,
+                from_address,
+                from_name,
+            } => Ok(Self::Http {
+                provider: *provider,
+                endpoint: endpoint.trim_end_matches('/').to_string(),
+                api_key: api_key.clone(),
+                from: from(from_address, from_name)?,
+            }),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Smtp { .. } => "smtp",
+            Self::Sendmail { .. } => "sendmail",
+            Self::File { .. } => "file",
+            Self::Http { .. } => "http",
+        }
+    }
+}
+
+fn message(
+    from: Mailbox,
+    destination: &str,
+    subject: &str,
+    body: String,
+    text: Option<String>,
+) -> Result<lettre::Message, anyhow::Error> {
+    let builder = lettre::Message::builder()
+        .subject(subject)
+        .to(Mailbox::new(None, destination.parse()?))
+        .from(from);
+
+    Ok(match text {
+        Some(text) => builder.multipart(lettre::message::MultiPart::alternative_plain_html(
+            text, body,
+        ))?,
+        None => builder
+            .header(lettre::message::header::ContentType::TEXT_HTML)
+            .body(body)?,
+    })
+}
+
+pub struct Mail {
+    settings: Arc<super::settings::Settings>,
+    database: Arc<super::database::Database>,
+    env: Arc<super::env::Env>,
+}
+
+impl Mail {
+    pub fn new(
+        settings: Arc<super::settings::Settings>,
+        database: Arc<super::database::Database>,
+        env: Arc<super::env::Env>,
+    ) -> Self {
+        Self {
+            settings,
+            database,
+            env,
+        }
+    }
+
+    /// Queues a mail, it is delivered by the job worker and retried if the transport fails.
+    pub async fn send(
+        &self,
+        destination: String,
+        subject: String,
+        body: String,
+        text: Option<String>,
+    ) {
+        let job = match super::jobs::mail::SendMail::new(
+            &self.database,
+            destination,
+            subject,
+            &body,
+            text.as_deref(),
+        ) {
+            Ok(job) => job,
+            Err(err) => {
+                tracing::error!("failed to queue email: {:#?}", err);
+                return;
+            }
+        };
+
+        if let Err(err) = super::jobs::enqueue(&self.database, &job).await {
+            tracing::error!("failed to queue email: {:#?}", err);
+        }
+    }
+
+    /// Renders a template in the language of the user and queues it. Admin overrides are
+    /// preferred over the built in templates, an override that fails to render falls back
+    /// to the built in one.
+    pub async fn send_template(
+        &self,
+        user: &super::models::user::User,
+        kind: MailTemplateKind,
+        variables: BTreeMap<&str, String>,
+    ) {
+        let mut variables = variables
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect::<BTreeMap<_, _>>();
+
+        let settings = self.settings.get().await;
+        variables.insert("app_name".into(), settings.app.name.clone());
+        variables.insert("app_url".into(), settings.app.url.clone());
+        variables.insert("user_username".into(), user.username.clone());
+        drop(settings);
+
+        let custom = match super::models::mail_template::MailTemplate::by_name_language(
+            &self.database,
+            kind.name(),
+            &user.language,
+        )
+        .await
+        {
+            Ok(custom) => custom,
+            Err(err) => {
+                tracing::error!(
+                    template = kind.name(),
+                    "failed to load mail template: {:#?}",
+                    err
+                );
+                None
+            }
+        };
+
+        let render = |subject: &str, html: &str, text: &str| {
+            Ok::<_, String>((
+                Template::render(subject, &variables, false)?,
+                Template::render(html, &variables, true)?,
+                Template::render(text, &variables, false)?,
+            ))
+        };
+
+        let (subject, html, text) = match custom
+            .as_ref()
+            .map(|custom| render(&custom.subject, &custom.html, &custom.text))
+        {
+            Some(Ok(rendered)) => rendered,
+            result => {
+                if let Some(Err(err)) = result {
+                    tracing::warn!(
+                        template = kind.name(),
+                        language = user.language,
+                        "failed to render mail template, using the default: {}",
+                        err
+                    );
+                }
+
+                let (subject, html, text) = kind.default_content();
+                render(subject, html, text).expect("default mail templates are valid")
+            }
+        };
+
+        self.send(user.email.clone(), subject, html, Some(text))
+            .await;
+    }
+
+    pub async fn deliver(
+        &self,
+        destination: &str,
+        subject: &str,
+        body: String,
+        text: Option<String>,
+    ) -> Result<(), anyhow::Error> {
+        let mail_mode = self.settings.get().await.mail_mode.clone();
+        let transport = Transport::new(&mail_mode, &self.env)?;
+
+        tracing::debug!(
+            transport = transport.name(),
+            destination = destination,
+            subject = subject,
+            "sending email"
+        );
+
+        match transport {
+            Transport::None => {}
+            Transport::Smtp { transport, from } => {
+                transport
+                    .send(message(from, destination, subject, body, text)?)
+                    .await?;
+            }
+            Transport::Sendmail { transport, from } => {
+                transport
+                    .send(message(from, destination, subject, body, text)?)
+                    .await?;
+            }
+            Transport::File {
+                transport,
+                path,
+                from,
+            } => {
+                tokio::fs::create_dir_all(&path).await?;
+
+                transport
+                    .send(message(from, destination, subject, body, text)?)
+                    .await?;
+            }
+            Transport::Http {
+                provider,
+                endpoint,
+                api_key,
+                from,
+            } => {
+                let request = match provider {
+                    MailHttpProvider::Mailgun => {
+                        let from = from.to_string();
+                        let mut form = vec![
+                            ("from", from.as_str()),
+                            ("to", destination),
+                            ("subject", subject),
+                            ("html", &body),
+                        ];
+                        if let Some(text) = &text {
+                            form.push(("text", text));
+                        }
+
+                        CLIENT
+                            .post(format!("{endpoint}/messages"))
+                            .basic_auth("api", Some(api_key))
+                            .form(&form)
+                    }
+                    MailHttpProvider::Postmark => CLIENT
+                        .post(format!("{endpoint}/email"))
+                        .header("X-Postmark-Server-Token", api_key)
+                        .header("Accept", "application/json")
+                        .json(&serde_json::json!({
+                            "From": from.to_string(),
+                            "To": destination,
+                            "Subject": subject,
+                            "HtmlBody": body,
+                            "TextBody": text,
+                        })),
+                    MailHttpProvider::Json => {
+                        CLIENT
+                            .post(endpoint)
+                            .bearer_auth(api_key)
+                            .json(&serde_json::json!({
+                                "from": from.to_string(),
+                                "to": destination,
+                                "subject": subject,
+                                "html": body,
+                                "text": text,
+                            }))
+                    }
+                };
+
+                let response = request.send().await?;
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let body = response.text().await.unwrap_or_default();
+                    tracing::warn!(
+                        status = status.as_u16(),
+                        "mail provider rejected the email: {}",
+                        body.chars().take(512).collect::<String>()
+                    );
+
+                    return Err(anyhow::anyhow!("mail provider responded with {status}"));
+                }
+            }
+        }
+
+        tracing::debug!("email sent successfully");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use utoipa::ToSchema;
+
+/// Variables every template can use.
+pub const COMMON_VARIABLES: &[&str] = &["app_name", "app_url", "user_username"];
+
+#[derive(ToSchema, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MailTemplateKind {
+    PasswordReset,
+    AccountCreated,
+    NewLogin,
+    NewDeviceVerification,
+    TwoFactorEnabled,
+    TwoFactorDisabled,
+    ApiKeyCreated,
+    BackupFailed,
+    ServerSuspended,
+    SubuserAdded,
+    ServerInstallFailed,
+    TransferCompleted,
+    TransferFailed,
+}
+
+impl MailTemplateKind {
+    pub const ALL: [Self; 13] = [
+        Self::PasswordReset,
+        Self::AccountCreated,
+        Self::NewLogin,
+        Self::NewDeviceVerification,
+        Self::TwoFactorEnabled,
+        Self::TwoFactorDisabled,
+        Self::ApiKeyCreated,
+        Self::BackupFailed,
+        Self::ServerSuspended,
+        Self::SubuserAdded,
+        Self::ServerInstallFailed,
+        Self::TransferCompleted,
+        Self::TransferFailed,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PasswordReset => "password_reset",
+            Self::AccountCreated => "account_created",
+            Self::NewLogin => "new_login",
+            Self::NewDeviceVerification => "new_device_verification",
+            Self::TwoFactorEnabled => "two_factor_enabled",
+            Self::TwoFactorDisabled => "two_factor_disabled",
+            Self::ApiKeyCreated => "api_key_created",
+            Self::BackupFailed => "backup_failed",
+            Self::ServerSuspended => "server_suspended",
+            Self::SubuserAdded => "subuser_added",
+            Self::ServerInstallFailed => "server_install_failed",
+            Self::TransferCompleted => "transfer_completed",
+            Self::TransferFailed => "transfer_failed",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Variables of this template in addition to [`COMMON_VARIABLES`].
+    pub fn variables(self) -> &'static [&'static str] {
+        match self {
+            Self::PasswordReset | Self::AccountCreated => &["reset_link"],
+            Self::NewLogin => &["ip", "user_agent"],
+            Self::NewDeviceVerification => &["ip", "user_agent", "code"],
+            Self::TwoFactorEnabled | Self::TwoFactorDisabled => &[],
+            Self::ApiKeyCreated => &["api_key_name"],
+            Self::BackupFailed => &["server_name", "server_link", "backup_name"],
+            Self::ServerSuspended
+            | Self::SubuserAdded
+            | Self::ServerInstallFailed
+            | Self::TransferCompleted
+            | Self::TransferFailed => &["server_name", "server_link"],
+        }
+    }
+
+    /// The built in subject, html and plain text templates.
+    pub fn default_content(self) -> (&'static str, &'static str, &'static str) {
+        macro_rules! content {
+            ($subject:literal, $name:literal) => {
+                (
+                    $subject,
+                    include_str!(concat!("../../mails/", $name, ".html")),
+                    include_str!(concat!("../../mails/", $name, ".txt")),
+                )
+            };
+        }
+
+        match self {
+            Self::PasswordReset => content!("{{app_name}} - Password Reset", "password_reset"),
+            Self::AccountCreated => content!("{{app_name}} - Account Created", "account_created"),
+            Self::NewLogin => content!("{{app_name}} - New Login", "new_login"),
+            Self::NewDeviceVerification => content!(
+                "{{app_name}} - Confirm New Device",
+                "new_device_verification"
+            ),
+            Self::TwoFactorEnabled => content!(
+                "{{app_name}} - Two-Factor Authentication Enabled",
+                "two_factor_enabled"
+            ),
+            Self::TwoFactorDisabled => content!(
+                "{{app_name}} - Two-Factor Authentication Disabled",
+                "two_factor_disabled"
+            ),
+            Self::ApiKeyCreated => content!("{{app_name}} - API Key Created", "api_key_created"),
+            Self::BackupFailed => content!(
+                "{{app_name}} - Backup of {{server_name}} Failed",
+                "backup_failed"
+            ),
+            Self::ServerSuspended => content!(
+                "{{app_name}} - {{server_name}} Suspended",
+                "server_suspended"
+            ),
+            Self::SubuserAdded => {
+                content!("{{app_name}} - Added to {{server_name}}", "subuser_added")
+            }
+            Self::ServerInstallFailed => content!(
+                "{{app_name}} - Installation of {{server_name}} Failed",
+                "server_install_failed"
+            ),
+            Self::TransferCompleted => content!(
+                "{{app_name}} - Transfer of {{server_name}} Completed",
+                "transfer_completed"
+            ),
+            Self::TransferFailed => content!(
+                "{{app_name}} - Transfer of {{server_name}} Failed",
+                "transfer_failed"
+            ),
+        }
+    }
+
+    /// Checks that the template parses and only uses variables of this kind.
+    pub fn validate(self, source: &str) -> Result<(), String> {
+        match Template::variables(source)?.into_iter().find(|variable| {
+            !COMMON_VARIABLES.contains(&variable.as_str())
+                && !self.variables().contains(&variable.as_str())
+        }) {
+            Some(variable) => Err(format!("unknown variable {variable}")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Mail templates use the Jinja syntax of minijinja, `{{ name }}` inserts a variable and
+/// `{% if name %}..{% else %}..{% endif %}` renders a section when the variable is not empty.
+/// Variables are escaped in html templates.
+pub struct Template;
+
+impl Template {
+    /// Caps how much work a single template may do, overrides are written by admins.
+    const FUEL: u64 = 50_000;
+
+    fn environment(html: bool) -> minijinja::Environment<'static> {
+        let mut environment = minijinja::Environment::new();
+        environment.set_fuel(Some(Self::FUEL));
+        environment.set_keep_trailing_newline(true);
+        environment.set_auto_escape_callback(move |_| {
+            if html {
+                minijinja::AutoEscape::Html
+            } else {
+                minijinja::AutoEscape::None
+            }
+        });
+
+        environment
+    }
+
+    /// Returns the variables the template reads, leaving out the built in functions.
+    pub fn variables(source: &str) -> Result<BTreeSet<String>, String> {
+        let environment = Self::environment(false);
+        let template = environment
+            .template_from_str(source)
+            .map_err(|err| err.to_string())?;
+
+        let mut variables = template
+            .undeclared_variables(false)
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        for (global, _) in environment.globals() {
+            variables.remove(global);
+        }
+
+        Ok(variables)
+    }
+
+    /// Renders the template, unknown variables render as empty.
+    pub fn render(
+        source: &str,
+        variables: &BTreeMap<String, String>,
+        html: bool,
+    ) -> Result<String, String> {
+        Self::environment(html)
+            .render_str(source, variables)
+            .map_err(|err| err.to_string())
+    }
+}
@@ -0,0 +1,211 @@
+use prometheus::{
+    Encoder, Gauge, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use sqlx::Row;
+use std::sync::LazyLock;
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub struct Metrics {
+    registry: Registry,
+
+    pub http_requests: IntCounterVec,
+    pub http_request_duration: HistogramVec,
+
+    pub database_connections: IntGaugeVec,
+    pub redis_rtt: Gauge,
+
+    pub wings_request_duration: HistogramVec,
+    pub wings_request_errors: IntCounterVec,
+
+    pub servers: IntGauge,
+    pub nodes: IntGauge,
+    pub users: IntGauge,
+    pub server_transfers: IntGauge,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("panel".to_string()), None).unwrap();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Total HTTP requests handled"),
+            &["method", "path", "status"],
+        )
+        .unwrap();
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency in seconds",
+            ),
+            &["method", "path", "status"],
+        )
+        .unwrap();
+
+        let database_connections = IntGaugeVec::new(
+            Opts::new(
+                "database_connections",
+                "Postgres pool connections by pool and state",
+            ),
+            &["pool", "state"],
+        )
+        .unwrap();
+        let redis_rtt =
+            Gauge::new("redis_rtt_seconds", "Redis PING round-trip time in seconds").unwrap();
+
+        let wings_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "wings_request_duration_seconds",
+                "Wings API request latency in seconds",
+            ),
+            &["node"],
+        )
+        .unwrap();
+        let wings_request_errors = IntCounterVec::new(
+            Opts::new(
+                "wings_request_errors_total",
+                "Total failed Wings API requests",
+            ),
+            &["node"],
+        )
+        .unwrap();
+
+        let servers = IntGauge::new("servers", "Amount of servers").unwrap();
+        let nodes = IntGauge::new("nodes", "Amount of nodes").unwrap();
+        let users = IntGauge::new("users", "Amount of users").unwrap();
+        let server_transfers =
+            IntGauge::new("server_transfers", "Amount of in-flight server transfers").unwrap();
+
+        registry.register(Box::new(http_requests.clone())).unwrap();
+        registry
+            .register(Box::new(http_request_duration.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(database_connections.clone()))
+            .unwrap();
+        registry.register(Box::new(redis_rtt.clone())).unwrap();
+        registry
+            .register(Box::new(wings_request_duration.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(wings_request_errors.clone()))
+            .unwrap();
+        registry.register(Box::new(servers.clone())).unwrap();
+        registry.register(Box::new(nodes.clone())).unwrap();
+        registry.register(Box::new(users.clone())).unwrap();
+        registry
+            .register(Box::new(server_transfers.clone()))
+            .unwrap();
+
+        Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            database_connections,
+            redis_rtt,
+            wings_request_duration,
+            wings_request_errors,
+            servers,
+            nodes,
+            users,
+            server_transfers,
+        }
+    }
+
+    #[inline]
+    pub fn is_authorized(env: &crate::env::Env, authorization: Option<&str>) -> bool {
+        match (
+            &env.metrics_token,
+            authorization.and_then(|a| a.strip_prefix("Bearer ")),
+        ) {
+            (Some(token), Some(provided)) => {
+                constant_time_eq::constant_time_eq(token.as_bytes(), provided.as_bytes())
+            }
+            _ => false,
+        }
+    }
+
+    pub fn observe_http_request(
+        &self,
+        method: &str,
+        path: &str,
+        status: u16,
+        duration: std::time::Duration,
+    ) {
+        let status = status.to_string();
+
+        self.http_requests
+            .with_label_values(&[method, path, &status])
+            .inc();
+        self.http_request_duration
+            .with_label_values(&[method, path, &status])
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn wings_observer(node_uuid: uuid::Uuid) -> wings_api::client::RequestObserver {
+        let node = node_uuid.to_string();
+
+        std::sync::Arc::new(move |duration, failed| {
+            METRICS
+                .wings_request_duration
+                .with_label_values(&[&node])
+                .observe(duration.as_secs_f64());
+
+            if failed {
+                METRICS
+                    .wings_request_errors
+                    .with_label_values(&[&node])
+                    .inc();
+            }
+        })
+    }
+
+    /// Refreshes the point-in-time gauges and renders all metrics in the Prometheus text format.
+    pub async fn render(&self, state: &crate::AppState) -> Result<String, anyhow::Error> {
+        for (pool_name, pool) in [
+            ("write", state.database.write()),
+            ("read", state.database.read()),
+        ] {
+            let size = pool.size() as i64;
+            let idle = pool.num_idle() as i64;
+
+            self.database_connections
+                .with_label_values(&[pool_name, "idle"])
+                .set(idle);
+            self.database_connections
+                .with_label_values(&[pool_name, "active"])
+                .set(size - idle);
+            self.database_connections
+                .with_label_values(&[pool_name, "max"])
+                .set(pool.options().get_max_connections() as i64);
+        }
+
+        match state.cache.ping().await {
+            Ok(rtt) => self.redis_rtt.set(rtt.as_secs_f64()),
+            Err(err) => tracing::warn!("failed to ping redis for metrics: {:#?}", err),
+        }
+
+        let row = sqlx::query(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM servers) AS servers,
+                (SELECT COUNT(*) FROM nodes) AS nodes,
+                (SELECT COUNT(*) FROM users) AS users,
+                (SELECT COUNT(*) FROM servers WHERE servers.destination_node_uuid IS NOT NULL) AS transfers
+            "#,
+        )
+        .fetch_one(state.database.read())
+        .await?;
+
+        self.servers.set(row.get("servers"));
+        self.nodes.set(row.get("nodes"));
+        self.users.set(row.get("users"));
+        self.server_transfers.set(row.get("transfers"));
+
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+
+        Ok(String::from_utf8(buffer)?)
+    }
+}
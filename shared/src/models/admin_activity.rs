@@ -110,10 +110,25 @@ impl AdminActivity {
         .bind(api_key_uuid)
         .bind(event)
         .bind(ip)
-        .bind(data)
+        .bind(&data)
         .execute(database.write())
         .await?;
 
+        super::webhook::Webhook::trigger(
+            database,
+            event,
+            serde_json::json!({
+                "type": "admin",
+                "event": event,
+                "user_uuid": user_uuid,
+                "api_key_uuid": api_key_uuid,
+                "ip": ip.map(|ip| ip.ip()),
+                "data": data,
+                "timestamp": chrono::Utc::now(),
+            }),
+        )
+        .await;
+
         Ok(())
     }
 
@@ -142,6 +142,7 @@ pub struct BackupConfiguration {
 
     pub backup_disk: super::server_backup::BackupDisk,
     pub backup_configs: BackupConfigs,
+    pub retention: super::backup_retention::BackupRetention,
 
     pub created: chrono::NaiveDateTime,
 }
@@ -166,6 +167,10 @@ impl BaseModel for BackupConfiguration {
                 "backup_configurations.backup_configs",
                 format!("{prefix}backup_configs"),
             ),
+            (
+                "backup_configurations.retention",
+                format!("{prefix}retention"),
+            ),
             ("backup_configurations.created", format!("{prefix}created")),
         ])
     }
@@ -183,6 +188,9 @@ impl BaseModel for BackupConfiguration {
                 row.get(format!("{prefix}backup_configs").as_str()),
             )
             .unwrap_or_default(),
+            retention: super::backup_retention::BackupRetention::from_value(
+                row.get(format!("{prefix}retention").as_str()),
+            ),
             created: row.get(format!("{prefix}created").as_str()),
         }
     }
@@ -214,6 +222,10 @@ impl BackupConfiguration {
                 "location_backup_configurations.backup_configs",
                 format!("{prefix}backup_configs"),
             ),
+            (
+                "location_backup_configurations.retention",
+                format!("{prefix}retention"),
+            ),
             (
                 "location_backup_configurations.created",
                 format!("{prefix}created"),
@@ -240,6 +252,10 @@ impl BackupConfiguration {
                 "node_backup_configurations.backup_configs",
                 format!("{prefix}backup_configs"),
             ),
+            (
+                "node_backup_configurations.retention",
+                format!("{prefix}retention"),
+            ),
             (
                 "node_backup_configurations.created",
                 format!("{prefix}created"),
@@ -253,13 +269,14 @@ impl BackupConfiguration {
         description: Option<&str>,
         backup_disk: super::server_backup::BackupDisk,
         mut backup_configs: BackupConfigs,
+        retention: super::backup_retention::BackupRetention,
     ) -> Result<Self, sqlx::Error> {
         backup_configs.encrypt(database);
 
         let row = sqlx::query(&format!(
             r#"
-            INSERT INTO backup_configurations (name, description, backup_disk, backup_configs)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO backup_configurations (name, description, backup_disk, backup_configs, retention)
+            VALUES ($1, $2, $3, $4, $5)
             RETURNING {}
             "#,
             Self::columns_sql(None)
@@ -268,6 +285,7 @@ impl BackupConfiguration {
         .bind(description)
         .bind(backup_disk)
         .bind(serde_json::to_value(backup_configs).unwrap())
+        .bind(serde_json::to_value(retention).unwrap())
         .fetch_one(database.write())
         .await?;
 
@@ -325,6 +343,26 @@ impl BackupConfiguration {
         })
     }
 
+    pub async fn update_retention(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+        retention: super::backup_retention::BackupRetention,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE backup_configurations
+            SET retention = $2
+            WHERE backup_configurations.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .bind(serde_json::to_value(retention).unwrap())
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn delete_by_uuid(
         database: &crate::database::Database,
         uuid: uuid::Uuid,
@@ -355,6 +393,7 @@ impl BackupConfiguration {
             description: self.description,
             backup_disk: self.backup_disk,
             backup_configs: self.backup_configs,
+            retention: self.retention,
             created: self.created.and_utc(),
         }
     }
@@ -370,6 +409,7 @@ pub struct AdminApiBackupConfiguration {
 
     pub backup_disk: super::server_backup::BackupDisk,
     pub backup_configs: BackupConfigs,
+    pub retention: super::backup_retention::BackupRetention,
 
     pub created: chrono::DateTime<chrono::Utc>,
 }
@@ -0,0 +1,100 @@
+use super::server_backup::ServerBackup;
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// Retention rules of a backup configuration or server, stored as JSON. A policy without
+/// any rules keeps every backup.
+#[derive(ToSchema, Validate, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BackupRetention {
+    /// Keeps the newest `keep_last` successful backups.
+    #[validate(range(min = 1, max = 1000))]
+    #[schema(minimum = 1, maximum = 1000)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<i32>,
+    /// Keeps the newest successful backup of each of the last `keep_daily` days.
+    #[validate(range(min = 1, max = 3650))]
+    #[schema(minimum = 1, maximum = 3650)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_daily: Option<i32>,
+    /// Keeps the newest successful backup of each of the last `keep_weekly` weeks.
+    #[validate(range(min = 1, max = 520))]
+    #[schema(minimum = 1, maximum = 520)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_weekly: Option<i32>,
+}
+
+impl BackupRetention {
+    #[inline]
+    pub fn from_value(value: serde_json::Value) -> Self {
+        serde_json::from_value(value).unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keep_last.is_none() && self.keep_daily.is_none() && self.keep_weekly.is_none()
+    }
+
+    /// Returns the backups that are not covered by any rule, newest first.
+    ///
+    /// Locked and unfinished backups are never returned, failed backups do not count
+    /// towards any rule and are always returned once a rule is set.
+    pub fn expired(
+        &self,
+        mut backups: Vec<ServerBackup>,
+        now: chrono::NaiveDateTime,
+    ) -> Vec<ServerBackup> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        backups.retain(|backup| {
+            !backup.locked && backup.completed.is_some() && backup.deleted.is_none()
+        });
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created));
+
+        let mut keep = HashSet::new();
+        let successful = backups.iter().filter(|backup| backup.successful);
+
+        if let Some(keep_last) = self.keep_last {
+            keep.extend(
+                successful
+                    .clone()
+                    .take(keep_last as usize)
+                    .map(|backup| backup.uuid),
+            );
+        }
+
+        if let Some(keep_daily) = self.keep_daily {
+            let since = now.date() - chrono::Days::new(keep_daily as u64 - 1);
+            let mut days = HashSet::new();
+
+            for backup in successful.clone() {
+                let day = backup.created.date();
+
+                if day >= since && days.insert(day) {
+                    keep.insert(backup.uuid);
+                }
+            }
+        }
+
+        if let Some(keep_weekly) = self.keep_weekly {
+            let since = now.date() - chrono::Days::new(keep_weekly as u64 * 7 - 1);
+            let mut weeks = HashSet::new();
+
+            for backup in successful {
+                let day = backup.created.date();
+
+                if day >= since && weeks.insert(day.iso_week()) {
+                    keep.insert(backup.uuid);
+                }
+            }
+        }
+
+        backups.retain(|backup| !keep.contains(&backup.uuid));
+
+        backups
+    }
+}
@@ -78,9 +78,14 @@ impl Location {
     ) -> Result<Self, sqlx::Error> {
         let row = sqlx::query(&format!(
             r#"
-            INSERT INTO locations (backup_configuration_uuid, name, description)
-            VALUES ($1, $2, $3)
-            RETURNING {}
+            WITH locations AS (
+                INSERT INTO locations (backup_configuration_uuid, name, description)
+                VALUES ($1, $2, $3)
+                RETURNING *
+            )
+            SELECT {}
+            FROM locations
+            LEFT JOIN backup_configurations location_backup_configurations ON location_backup_configurations.uuid = locations.backup_configuration_uuid
             "#,
             Self::columns_sql(None)
         ))
@@ -181,6 +186,30 @@ impl Location {
         })
     }
 
+    /// Locations with at least one public node that is not in maintenance.
+    pub async fn deployable(
+        database: &crate::database::Database,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM locations
+            LEFT JOIN backup_configurations location_backup_configurations ON location_backup_configurations.uuid = locations.backup_configuration_uuid
+            WHERE EXISTS (
+                SELECT 1
+                FROM nodes
+                WHERE nodes.location_uuid = locations.uuid AND nodes.public AND nodes.maintenance_message IS NULL
+            )
+            ORDER BY locations.name
+            "#,
+            Self::columns_sql(None)
+        ))
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| Self::map(None, &row)).collect())
+    }
+
     pub async fn delete_by_uuid(
         database: &crate::database::Database,
         uuid: uuid::Uuid,
@@ -198,6 +227,15 @@ impl Location {
         Ok(())
     }
 
+    #[inline]
+    pub fn into_api_object(self) -> ApiLocation {
+        ApiLocation {
+            uuid: self.uuid,
+            name: self.name,
+            description: self.description,
+        }
+    }
+
     #[inline]
     pub fn into_admin_api_object(self, database: &crate::database::Database) -> AdminApiLocation {
         AdminApiLocation {
@@ -226,3 +264,12 @@ pub struct AdminApiLocation {
 
     pub created: chrono::DateTime<chrono::Utc>,
 }
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "ClientLocation")]
+pub struct ApiLocation {
+    pub uuid: uuid::Uuid,
+
+    pub name: String,
+    pub description: Option<String>,
+}
@@ -0,0 +1,180 @@
+use super::BaseModel;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, postgres::PgRow};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// An admin override of a built in mail template for one language.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MailTemplate {
+    pub uuid: uuid::Uuid,
+
+    pub name: String,
+    pub language: String,
+
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+
+    pub created: chrono::NaiveDateTime,
+    pub updated: chrono::NaiveDateTime,
+}
+
+impl BaseModel for MailTemplate {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            ("mail_templates.uuid", format!("{prefix}uuid")),
+            ("mail_templates.name", format!("{prefix}name")),
+            ("mail_templates.language", format!("{prefix}language")),
+            ("mail_templates.subject", format!("{prefix}subject")),
+            ("mail_templates.html", format!("{prefix}html")),
+            ("mail_templates.text", format!("{prefix}text")),
+            ("mail_templates.created", format!("{prefix}created")),
+            ("mail_templates.updated", format!("{prefix}updated")),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            uuid: row.get(format!("{prefix}uuid").as_str()),
+            name: row.get(format!("{prefix}name").as_str()),
+            language: row.get(format!("{prefix}language").as_str()),
+            subject: row.get(format!("{prefix}subject").as_str()),
+            html: row.get(format!("{prefix}html").as_str()),
+            text: row.get(format!("{prefix}text").as_str()),
+            created: row.get(format!("{prefix}created").as_str()),
+            updated: row.get(format!("{prefix}updated").as_str()),
+        }
+    }
+}
+
+impl MailTemplate {
+    pub async fn upsert(
+        database: &crate::database::Database,
+        name: &str,
+        language: &str,
+        subject: &str,
+        html: &str,
+        text: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO mail_templates (name, language, subject, html, text)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (name, language) DO UPDATE SET
+                subject = EXCLUDED.subject,
+                html = EXCLUDED.html,
+                text = EXCLUDED.text,
+                updated = NOW()
+            RETURNING {}
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(name)
+        .bind(language)
+        .bind(subject)
+        .bind(html)
+        .bind(text)
+        .fetch_one(database.write())
+        .await?;
+
+        Ok(Self::map(None, &row))
+    }
+
+    pub async fn all_by_name(
+        database: &crate::database::Database,
+        name: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM mail_templates
+            WHERE mail_templates.name = $1
+            ORDER BY mail_templates.language
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(name)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| Self::map(None, &row)).collect())
+    }
+
+    /// Finds the override for the language, falling back to the english one.
+    pub async fn by_name_language(
+        database: &crate::database::Database,
+        name: &str,
+        language: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM mail_templates
+            WHERE mail_templates.name = $1 AND mail_templates.language IN ($2, 'en')
+            ORDER BY mail_templates.language = $2 DESC
+            LIMIT 1
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(name)
+        .bind(language)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn delete_by_name_language(
+        database: &crate::database::Database,
+        name: &str,
+        language: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM mail_templates
+            WHERE mail_templates.name = $1 AND mail_templates.language = $2
+            "#,
+        )
+        .bind(name)
+        .bind(language)
+        .execute(database.write())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[inline]
+    pub fn into_admin_api_object(self) -> AdminApiMailTemplate {
+        AdminApiMailTemplate {
+            uuid: self.uuid,
+            language: self.language,
+            subject: self.subject,
+            html: self.html,
+            text: self.text,
+            created: self.created.and_utc(),
+            updated: self.updated.and_utc(),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "MailTemplate")]
+pub struct AdminApiMailTemplate {
+    pub uuid: uuid::Uuid,
+
+    pub language: String,
+
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+
+    pub created: chrono::DateTime<chrono::Utc>,
+    pub updated: chrono::DateTime<chrono::Utc>,
+}
@@ -6,36 +6,49 @@ use validator::Validate;
 
 pub mod admin_activity;
 pub mod backup_configurations;
+pub mod backup_retention;
 pub mod database_host;
 pub mod location;
 pub mod location_database_host;
+pub mod mail_template;
 pub mod mount;
 pub mod nest;
 pub mod nest_egg;
 pub mod nest_egg_mount;
+pub mod nest_egg_revision;
+pub mod nest_egg_source;
 pub mod nest_egg_variable;
 pub mod node;
 pub mod node_allocation;
 pub mod node_mount;
+pub mod queued_job;
+pub mod quota;
 pub mod role;
 pub mod server;
 pub mod server_activity;
 pub mod server_allocation;
 pub mod server_backup;
+pub mod server_bulk_job;
 pub mod server_database;
 pub mod server_mount;
 pub mod server_schedule;
 pub mod server_schedule_step;
 pub mod server_subuser;
+pub mod server_template;
 pub mod server_variable;
 pub mod user;
 pub mod user_activity;
 pub mod user_api_key;
+pub mod user_external_identity;
+pub mod user_login_failure;
+pub mod user_notification;
 pub mod user_password_reset;
 pub mod user_recovery_code;
 pub mod user_security_key;
 pub mod user_session;
 pub mod user_ssh_key;
+pub mod webhook;
+pub mod webhook_delivery;
 
 #[derive(ToSchema, Validate, Deserialize)]
 pub struct PaginationParams {
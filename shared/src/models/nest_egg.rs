@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::{Row, postgres::PgRow};
 use std::collections::BTreeMap;
 use utoipa::ToSchema;
+use validator::Validate;
 
 #[derive(ToSchema, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
@@ -33,6 +34,65 @@ pub struct ProcessConfigurationFile {
     pub replace: Vec<ProcessConfigurationFileReplacement>,
 }
 
+impl ProcessConfigurationFile {
+    /// Parses the configuration files of an exported egg, either the native list or
+    /// the `{ "file": { "parser": .., "find": { .. } } }` map used by PTDL eggs.
+    pub fn parse_exported(value: serde_json::Value) -> Vec<Self> {
+        #[derive(Deserialize)]
+        struct PtdlFile {
+            parser: ServerConfigurationFileParser,
+            #[serde(default)]
+            find: IndexMap<String, serde_json::Value>,
+        }
+
+        match value {
+            serde_json::Value::String(value) => {
+                serde_json::from_str(&value).map_or_else(|_| Vec::new(), Self::parse_exported)
+            }
+            serde_json::Value::Object(_) => {
+                serde_json::from_value::<IndexMap<String, PtdlFile>>(value)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(file, config)| Self {
+                        file,
+                        parser: config.parser,
+                        replace: config
+                            .find
+                            .into_iter()
+                            .map(
+                                |(r#match, replace_with)| ProcessConfigurationFileReplacement {
+                                    r#match,
+                                    if_value: None,
+                                    replace_with,
+                                },
+                            )
+                            .collect(),
+                    })
+                    .collect()
+            }
+            value => serde_json::from_value(value).unwrap_or_default(),
+        }
+    }
+
+    fn into_ptdl(files: Vec<Self>) -> IndexMap<String, serde_json::Value> {
+        files
+            .into_iter()
+            .map(|file| {
+                let find: IndexMap<String, serde_json::Value> = file
+                    .replace
+                    .into_iter()
+                    .map(|replacement| (replacement.r#match, replacement.replace_with))
+                    .collect();
+
+                (
+                    file.file,
+                    serde_json::json!({ "parser": file.parser, "find": find }),
+                )
+            })
+            .collect()
+    }
+}
+
 #[derive(ToSchema, Serialize, Clone)]
 pub struct ProcessConfiguration {
     #[schema(inline)]
@@ -50,12 +110,77 @@ pub struct NestEggConfigStartup {
     pub strip_ansi: bool,
 }
 
+impl NestEggConfigStartup {
+    /// Parses the startup configuration of an exported egg, PTDL eggs may use a single `done` string.
+    pub fn parse_exported(value: serde_json::Value) -> Self {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Done {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        #[derive(Deserialize)]
+        struct Startup {
+            done: Option<Done>,
+            #[serde(default)]
+            strip_ansi: bool,
+        }
+
+        let value = match value {
+            serde_json::Value::String(value) => serde_json::from_str(&value).unwrap_or_default(),
+            value => value,
+        };
+
+        match serde_json::from_value::<Startup>(value) {
+            Ok(startup) => Self {
+                done: match startup.done {
+                    Some(Done::One(done)) => vec![done],
+                    Some(Done::Many(done)) => done,
+                    None => Vec::new(),
+                },
+                strip_ansi: startup.strip_ansi,
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
 #[derive(ToSchema, Serialize, Deserialize, Clone, Default)]
 pub struct NestEggConfigStop {
     pub r#type: String,
     pub value: Option<String>,
 }
 
+impl NestEggConfigStop {
+    /// Parses the stop configuration of an exported egg, PTDL eggs use a plain
+    /// command or `^C` for an interrupt signal.
+    pub fn parse_exported(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::String(value) => match serde_json::from_str(&value) {
+                Ok(stop) => stop,
+                Err(_) if value == "^C" => Self {
+                    r#type: "signal".to_string(),
+                    value: Some("SIGINT".to_string()),
+                },
+                Err(_) => Self {
+                    r#type: "command".to_string(),
+                    value: Some(value),
+                },
+            },
+            value => serde_json::from_value(value).unwrap_or_default(),
+        }
+    }
+
+    fn into_ptdl(self) -> String {
+        match (self.r#type.as_str(), self.value) {
+            ("signal", Some(value)) if value == "SIGINT" => "^C".to_string(),
+            ("command", Some(value)) => value,
+            (r#type, value) => serde_json::json!({ "type": r#type, "value": value }).to_string(),
+        }
+    }
+}
+
 #[derive(ToSchema, Serialize, Deserialize, Clone)]
 pub struct NestEggConfigScript {
     pub container: String,
@@ -98,6 +223,191 @@ pub struct NestEggConfigAllocations {
     pub user_self_assign: NestEggConfigAllocationsUserSelfAssign,
 }
 
+#[derive(ToSchema, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum NestEggImportVariableRules {
+    Joined(String),
+    List(Vec<String>),
+}
+
+#[derive(ToSchema, Validate, Deserialize, Clone)]
+pub struct NestEggImportVariable {
+    #[validate(length(min = 1, max = 255))]
+    #[schema(min_length = 1, max_length = 255)]
+    pub name: String,
+    #[validate(length(max = 1024))]
+    #[schema(max_length = 1024)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub order: i16,
+
+    #[validate(length(min = 1, max = 255))]
+    #[schema(min_length = 1, max_length = 255)]
+    pub env_variable: String,
+    #[validate(length(max = 1024))]
+    #[schema(max_length = 1024)]
+    pub default_value: Option<String>,
+
+    pub user_viewable: bool,
+    pub user_editable: bool,
+    #[schema(inline)]
+    pub rules: Option<NestEggImportVariableRules>,
+    #[serde(default)]
+    pub kind: super::nest_egg_variable::NestEggVariableKind,
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[validate(nested)]
+    #[serde(default)]
+    pub ui_hints: Option<super::nest_egg_variable::NestEggVariableUiHints>,
+}
+
+#[derive(ToSchema, Validate, Deserialize, Clone)]
+pub struct NestEggImportScripts {
+    #[schema(inline)]
+    pub installation: NestEggConfigScript,
+}
+
+/// An egg file in the PTDL_v2 or native format.
+#[derive(ToSchema, Validate, Deserialize, Clone)]
+pub struct NestEggImport {
+    #[validate(length(min = 2, max = 255))]
+    #[schema(min_length = 2, max_length = 255)]
+    pub author: String,
+    #[validate(length(min = 3, max = 255))]
+    #[schema(min_length = 3, max_length = 255)]
+    pub name: String,
+    #[validate(length(max = 1024))]
+    #[schema(max_length = 1024)]
+    pub description: Option<String>,
+
+    /// Values may be JSON encoded strings, as in PTDL eggs.
+    pub config: std::collections::HashMap<String, serde_json::Value>,
+    #[schema(inline)]
+    pub scripts: NestEggImportScripts,
+
+    #[validate(length(min = 1, max = 255))]
+    #[schema(min_length = 1, max_length = 255)]
+    pub startup: String,
+    #[serde(default)]
+    pub force_outgoing_ip: bool,
+
+    #[serde(default)]
+    pub features: Vec<String>,
+    pub docker_images: IndexMap<String, String>,
+    #[serde(default)]
+    pub file_denylist: Vec<String>,
+
+    #[schema(inline)]
+    pub variables: Vec<NestEggImportVariable>,
+}
+
+impl NestEggImport {
+    /// Applies the import to an egg and its variables without saving them, variables
+    /// with invalid rules are dropped and new ones get a nil uuid.
+    pub fn apply(
+        self,
+        egg: &mut NestEgg,
+        variables: &mut Vec<super::nest_egg_variable::NestEggVariable>,
+    ) {
+        let mut config = self.config;
+
+        egg.author = self.author;
+        egg.name = self.name;
+        egg.description = self
+            .description
+            .filter(|description| !description.is_empty());
+        egg.config_files =
+            ProcessConfigurationFile::parse_exported(config.remove("files").unwrap_or_default());
+        egg.config_startup =
+            NestEggConfigStartup::parse_exported(config.remove("startup").unwrap_or_default());
+        egg.config_stop =
+            NestEggConfigStop::parse_exported(config.remove("stop").unwrap_or_default());
+        egg.config_script = self.scripts.installation;
+        egg.config_allocations = config
+            .remove("allocations")
+            .and_then(|allocations| {
+                serde_json::from_value::<NestEggConfigAllocations>(allocations).ok()
+            })
+            .filter(|allocations| allocations.user_self_assign.is_valid())
+            .unwrap_or(NestEggConfigAllocations {
+                user_self_assign: Default::default(),
+            });
+        egg.startup = self.startup;
+        egg.force_outgoing_ip = self.force_outgoing_ip;
+        egg.features = self.features;
+        egg.docker_images = self.docker_images;
+        egg.file_denylist = self.file_denylist;
+
+        let mut existing = std::mem::take(variables);
+        for variable in self.variables {
+            let rules = match variable.rules {
+                Some(NestEggImportVariableRules::Joined(rules)) => rules
+                    .split('|')
+                    .filter(|rule| !rule.is_empty())
+                    .map(String::from)
+                    .collect::<Vec<String>>(),
+                Some(NestEggImportVariableRules::List(rules)) => rules,
+                None => Vec::new(),
+            };
+
+            if rule_validator::validate_rules(&rules).is_err() {
+                continue;
+            }
+
+            let (uuid, created) = match existing
+                .iter()
+                .position(|existing| existing.env_variable == variable.env_variable)
+            {
+                Some(index) => {
+                    let existing = existing.swap_remove(index);
+
+                    (existing.uuid, existing.created)
+                }
+                None => (uuid::Uuid::nil(), chrono::Utc::now().naive_utc()),
+            };
+
+            variables.push(super::nest_egg_variable::NestEggVariable {
+                uuid,
+                name: variable.name,
+                description: variable
+                    .description
+                    .filter(|description| !description.is_empty()),
+                order: variable.order,
+                env_variable: variable.env_variable,
+                default_value: variable.default_value,
+                user_viewable: variable.user_viewable,
+                user_editable: variable.user_editable,
+                rules,
+                kind: variable.kind,
+                options: variable.options,
+                ui_hints: variable.ui_hints,
+                created,
+            });
+        }
+    }
+}
+
+#[derive(ToSchema, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+#[schema(rename_all = "snake_case")]
+pub enum NestEggExportFormat {
+    /// The egg format of Pterodactyl and Pelican, some settings have no equivalent there.
+    #[default]
+    PtdlV2,
+    /// Keeps every setting of the egg.
+    Native,
+}
+
+impl NestEggExportFormat {
+    #[inline]
+    pub fn version(self) -> &'static str {
+        match self {
+            Self::PtdlV2 => "PTDL_v2",
+            Self::Native => "CALAGOPUS_v1",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct NestEgg {
     pub uuid: uuid::Uuid,
@@ -114,11 +424,17 @@ pub struct NestEgg {
 
     pub startup: String,
     pub force_outgoing_ip: bool,
+    pub deployable: bool,
 
     pub features: Vec<String>,
     pub docker_images: IndexMap<String, String>,
     pub file_denylist: Vec<String>,
 
+    pub source_uuid: Option<uuid::Uuid>,
+    pub source_path: Option<String>,
+    pub upstream_hash: Option<String>,
+    pub synced_hash: Option<String>,
+
     pub servers: i64,
 
     pub created: chrono::NaiveDateTime,
@@ -150,9 +466,14 @@ impl BaseModel for NestEgg {
                 "nest_eggs.force_outgoing_ip",
                 format!("{prefix}force_outgoing_ip"),
             ),
+            ("nest_eggs.deployable", format!("{prefix}deployable")),
             ("nest_eggs.features", format!("{prefix}features")),
             ("nest_eggs.docker_images", format!("{prefix}docker_images")),
             ("nest_eggs.file_denylist", format!("{prefix}file_denylist")),
+            ("nest_eggs.source_uuid", format!("{prefix}source_uuid")),
+            ("nest_eggs.source_path", format!("{prefix}source_path")),
+            ("nest_eggs.upstream_hash", format!("{prefix}upstream_hash")),
+            ("nest_eggs.synced_hash", format!("{prefix}synced_hash")),
             ("nest_eggs.created", format!("{prefix}created")),
             (
                 "(SELECT COUNT(*) FROM servers WHERE servers.egg_uuid = nest_eggs.uuid)",
@@ -189,12 +510,17 @@ impl BaseModel for NestEgg {
             .unwrap(),
             startup: row.get(format!("{prefix}startup").as_str()),
             force_outgoing_ip: row.get(format!("{prefix}force_outgoing_ip").as_str()),
+            deployable: row.get(format!("{prefix}deployable").as_str()),
             features: row.get(format!("{prefix}features").as_str()),
             docker_images: serde_json::from_value(
                 row.get(format!("{prefix}docker_images").as_str()),
             )
             .unwrap_or_default(),
             file_denylist: row.get(format!("{prefix}file_denylist").as_str()),
+            source_uuid: row.get(format!("{prefix}source_uuid").as_str()),
+            source_path: row.get(format!("{prefix}source_path").as_str()),
+            upstream_hash: row.get(format!("{prefix}upstream_hash").as_str()),
+            synced_hash: row.get(format!("{prefix}synced_hash").as_str()),
             servers: row.get(format!("{prefix}servers").as_str()),
             created: row.get(format!("{prefix}created").as_str()),
         }
@@ -204,7 +530,7 @@ impl BaseModel for NestEgg {
 impl NestEgg {
     #[allow(clippy::too_many_arguments)]
     pub async fn create(
-        database: &crate::database::Database,
+        executor: impl sqlx::PgExecutor<'_>,
         nest_uuid: uuid::Uuid,
         author: &str,
         name: &str,
@@ -246,7 +572,7 @@ impl NestEgg {
         .bind(features)
         .bind(serde_json::to_value(docker_images).unwrap())
         .bind(file_denylist)
-        .fetch_one(database.write())
+        .fetch_one(executor)
         .await?;
 
         Ok(Self::map(None, &row))
@@ -305,6 +631,28 @@ impl NestEgg {
         Ok(row.map(|row| Self::map(None, &row)))
     }
 
+    /// Fetches the egg and locks its row until the transaction ends, so changes to
+    /// the egg and its variables are serialized.
+    pub async fn lock(
+        executor: impl sqlx::PgExecutor<'_>,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM nest_eggs
+            WHERE nest_eggs.uuid = $1
+            FOR UPDATE
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(uuid)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
     pub async fn by_nest_uuid_uuid(
         database: &crate::database::Database,
         nest_uuid: uuid::Uuid,
@@ -326,6 +674,44 @@ impl NestEgg {
         Ok(row.map(|row| Self::map(None, &row)))
     }
 
+    pub async fn deployable(
+        database: &crate::database::Database,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM nest_eggs
+            WHERE nest_eggs.deployable
+            ORDER BY nest_eggs.name
+            "#,
+            Self::columns_sql(None)
+        ))
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| Self::map(None, &row)).collect())
+    }
+
+    pub async fn update_deployable(
+        executor: impl sqlx::PgExecutor<'_>,
+        uuid: uuid::Uuid,
+        deployable: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE nest_eggs
+            SET deployable = $2
+            WHERE nest_eggs.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .bind(deployable)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn delete_by_uuid(
         database: &crate::database::Database,
         uuid: uuid::Uuid,
@@ -343,6 +729,372 @@ impl NestEgg {
         Ok(())
     }
 
+    /// Builds an egg file that can be imported again, `variables` should be ordered.
+    pub fn export(
+        &self,
+        variables: &[super::nest_egg_variable::NestEggVariable],
+        format: NestEggExportFormat,
+    ) -> serde_json::Value {
+        let (config, variables) = match format {
+            NestEggExportFormat::PtdlV2 => (
+                serde_json::json!({
+                    "files": serde_json::to_string(&ProcessConfigurationFile::into_ptdl(self.config_files.clone())).unwrap(),
+                    "startup": serde_json::to_string(&self.config_startup).unwrap(),
+                    "logs": "{}",
+                    "stop": self.config_stop.clone().into_ptdl(),
+                }),
+                variables
+                    .iter()
+                    .map(|variable| {
+                        serde_json::json!({
+                            "name": variable.name,
+                            "description": variable.description.as_deref().unwrap_or_default(),
+                            "env_variable": variable.env_variable,
+                            "default_value": variable.default_value.as_deref().unwrap_or_default(),
+                            "user_viewable": variable.user_viewable,
+                            "user_editable": variable.user_editable,
+                            "rules": variable.rules.join("|"),
+                            "field_type": "text",
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            NestEggExportFormat::Native => (
+                serde_json::json!({
+                    "files": self.config_files,
+                    "startup": self.config_startup,
+                    "stop": self.config_stop,
+                    "allocations": self.config_allocations,
+                }),
+                variables
+                    .iter()
+                    .map(|variable| {
+                        serde_json::json!({
+                            "name": variable.name,
+                            "description": variable.description,
+                            "order": variable.order,
+                            "env_variable": variable.env_variable,
+                            "default_value": variable.default_value,
+                            "user_viewable": variable.user_viewable,
+                            "user_editable": variable.user_editable,
+                            "rules": variable.rules,
+                            "kind": variable.kind,
+                            "options": variable.options,
+                            "ui_hints": variable.ui_hints,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        };
+
+        serde_json::json!({
+            "meta": {
+                "version": format.version(),
+                "update_url": null,
+            },
+            "exported_at": chrono::Utc::now(),
+            "name": self.name,
+            "author": self.author,
+            "description": self.description,
+            "features": self.features,
+            "docker_images": self.docker_images,
+            "file_denylist": self.file_denylist,
+            "startup": self.startup,
+            "force_outgoing_ip": self.force_outgoing_ip,
+            "config": config,
+            "scripts": {
+                "installation": {
+                    "script": self.config_script.content,
+                    "container": self.config_script.container,
+                    "entrypoint": self.config_script.entrypoint,
+                },
+            },
+            "variables": variables,
+        })
+    }
+
+    /// The native export of the egg without its metadata, importable through [`NestEggImport`].
+    pub fn snapshot(
+        &self,
+        variables: &[super::nest_egg_variable::NestEggVariable],
+    ) -> serde_json::Value {
+        let mut export = self.export(variables, NestEggExportFormat::Native);
+        if let Some(export) = export.as_object_mut() {
+            export.remove("meta");
+            export.remove("exported_at");
+        }
+
+        export
+    }
+
+    /// A hash of everything an export contains, used to detect local changes to synced eggs.
+    pub fn content_hash(&self, variables: &[super::nest_egg_variable::NestEggVariable]) -> String {
+        use sha2::Digest;
+
+        format!(
+            "{:x}",
+            sha2::Sha256::digest(self.snapshot(variables).to_string())
+        )
+    }
+
+    /// Creates the egg and its variables inside `transaction`, variables that fail to
+    /// insert are skipped.
+    pub async fn import(
+        transaction: &mut sqlx::PgTransaction<'_>,
+        nest_uuid: uuid::Uuid,
+        data: NestEggImport,
+    ) -> Result<Self, sqlx::Error> {
+        let mut egg = Self {
+            uuid: uuid::Uuid::nil(),
+            author: String::new(),
+            name: String::new(),
+            description: None,
+            config_files: Vec::new(),
+            config_startup: Default::default(),
+            config_stop: Default::default(),
+            config_script: data.scripts.installation.clone(),
+            config_allocations: NestEggConfigAllocations {
+                user_self_assign: Default::default(),
+            },
+            startup: String::new(),
+            force_outgoing_ip: false,
+            deployable: false,
+            features: Vec::new(),
+            docker_images: IndexMap::new(),
+            file_denylist: Vec::new(),
+            source_uuid: None,
+            source_path: None,
+            upstream_hash: None,
+            synced_hash: None,
+            servers: 0,
+            created: chrono::Utc::now().naive_utc(),
+        };
+        let mut variables = Vec::new();
+        data.apply(&mut egg, &mut variables);
+
+        let egg = Self::create(
+            &mut **transaction,
+            nest_uuid,
+            &egg.author,
+            &egg.name,
+            egg.description.as_deref(),
+            egg.config_files,
+            egg.config_startup,
+            egg.config_stop,
+            egg.config_script,
+            egg.config_allocations,
+            &egg.startup,
+            egg.force_outgoing_ip,
+            &egg.features,
+            egg.docker_images,
+            &egg.file_denylist,
+        )
+        .await?;
+
+        for variable in variables {
+            let mut savepoint = sqlx::Acquire::begin(&mut *transaction).await?;
+
+            if super::nest_egg_variable::NestEggVariable::create(
+                &mut *savepoint,
+                egg.uuid,
+                &variable.name,
+                variable.description.as_deref(),
+                variable.order,
+                &variable.env_variable,
+                variable.default_value.as_deref(),
+                variable.user_viewable,
+                variable.user_editable,
+                &variable.rules,
+                variable.kind,
+                &variable.options,
+                variable.ui_hints.as_ref(),
+            )
+            .await
+            .is_ok()
+            {
+                savepoint.commit().await?;
+            }
+        }
+
+        Ok(egg)
+    }
+
+    /// Replaces the egg and its variables with an import, variables are matched by
+    /// their environment variable so server values are kept.
+    pub async fn update_from_import(
+        mut self,
+        database: &crate::database::Database,
+        transaction: &mut sqlx::PgTransaction<'_>,
+        mut variables: Vec<super::nest_egg_variable::NestEggVariable>,
+        data: NestEggImport,
+    ) -> Result<(), sqlx::Error> {
+        data.apply(&mut self, &mut variables);
+
+        sqlx::query(
+            r#"
+            UPDATE nest_eggs
+            SET author = $2, name = $3, description = $4, config_files = $5, config_startup = $6,
+                config_stop = $7, config_script = $8, config_allocations = $9, startup = $10,
+                force_outgoing_ip = $11, features = $12, docker_images = $13, file_denylist = $14
+            WHERE nest_eggs.uuid = $1
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(&self.author)
+        .bind(&self.name)
+        .bind(&self.description)
+        .bind(serde_json::to_value(&self.config_files).unwrap())
+        .bind(serde_json::to_value(&self.config_startup).unwrap())
+        .bind(serde_json::to_value(&self.config_stop).unwrap())
+        .bind(serde_json::to_value(&self.config_script).unwrap())
+        .bind(serde_json::to_value(self.config_allocations).unwrap())
+        .bind(&self.startup)
+        .bind(self.force_outgoing_ip)
+        .bind(&self.features)
+        .bind(serde_json::to_value(&self.docker_images).unwrap())
+        .bind(&self.file_denylist)
+        .execute(&mut **transaction)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM nest_egg_variables
+            WHERE nest_egg_variables.egg_uuid = $1 AND NOT (nest_egg_variables.uuid = ANY($2))
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(
+            variables
+                .iter()
+                .map(|variable| variable.uuid)
+                .collect::<Vec<_>>(),
+        )
+        .execute(&mut **transaction)
+        .await?;
+
+        for variable in variables {
+            if variable.uuid.is_nil() {
+                super::nest_egg_variable::NestEggVariable::create(
+                    &mut **transaction,
+                    self.uuid,
+                    &variable.name,
+                    variable.description.as_deref(),
+                    variable.order,
+                    &variable.env_variable,
+                    variable.default_value.as_deref(),
+                    variable.user_viewable,
+                    variable.user_editable,
+                    &variable.rules,
+                    variable.kind,
+                    &variable.options,
+                    variable.ui_hints.as_ref(),
+                )
+                .await?;
+
+                continue;
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE nest_egg_variables
+                SET name = $2, description = $3, order_ = $4, default_value = $5,
+                    user_viewable = $6, user_editable = $7, rules = $8, kind = $9,
+                    options = $10, ui_hints = $11
+                WHERE nest_egg_variables.uuid = $1
+                "#,
+            )
+            .bind(variable.uuid)
+            .bind(&variable.name)
+            .bind(&variable.description)
+            .bind(variable.order)
+            .bind(&variable.default_value)
+            .bind(variable.user_viewable)
+            .bind(variable.user_editable)
+            .bind(&variable.rules)
+            .bind(variable.kind)
+            .bind(&variable.options)
+            .bind(
+                variable
+                    .ui_hints
+                    .as_ref()
+                    .map(|ui_hints| serde_json::to_value(ui_hints).unwrap()),
+            )
+            .execute(&mut **transaction)
+            .await?;
+
+            super::server_variable::ServerVariable::update_encryption(
+                database,
+                transaction,
+                variable.uuid,
+                variable.is_secret(),
+            )
+            .await?;
+            super::server_template::ServerTemplate::update_encryption(
+                database,
+                transaction,
+                variable.uuid,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Links the egg to the entry of a source it was synced from, the current
+    /// state of the egg is remembered to detect later local changes.
+    pub async fn update_source(
+        transaction: &mut sqlx::PgTransaction<'_>,
+        uuid: uuid::Uuid,
+        source_uuid: uuid::Uuid,
+        source_path: &str,
+        upstream_hash: &str,
+    ) -> Result<(), sqlx::Error> {
+        let egg = match Self::lock(&mut **transaction, uuid).await? {
+            Some(egg) => egg,
+            None => return Ok(()),
+        };
+        let variables =
+            super::nest_egg_variable::NestEggVariable::all_by_egg_uuid(&mut **transaction, uuid)
+                .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE nest_eggs
+            SET source_uuid = $2, source_path = $3, upstream_hash = $4, synced_hash = $5
+            WHERE nest_eggs.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .bind(source_uuid)
+        .bind(source_path)
+        .bind(upstream_hash)
+        .bind(egg.content_hash(&variables))
+        .execute(&mut **transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn all_by_source_uuid(
+        database: &crate::database::Database,
+        source_uuid: uuid::Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM nest_eggs
+            WHERE nest_eggs.source_uuid = $1
+            ORDER BY nest_eggs.created
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(source_uuid)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| Self::map(None, &row)).collect())
+    }
+
     #[inline]
     pub fn into_admin_api_object(self) -> AdminApiNestEgg {
         AdminApiNestEgg {
@@ -357,9 +1109,12 @@ impl NestEgg {
             config_allocations: self.config_allocations,
             startup: self.startup,
             force_outgoing_ip: self.force_outgoing_ip,
+            deployable: self.deployable,
             features: self.features,
             docker_images: self.docker_images,
             file_denylist: self.file_denylist,
+            source_uuid: self.source_uuid,
+            source_path: self.source_path,
             servers: self.servers,
             created: self.created.and_utc(),
         }
@@ -401,11 +1156,15 @@ pub struct AdminApiNestEgg {
 
     pub startup: String,
     pub force_outgoing_ip: bool,
+    pub deployable: bool,
 
     pub features: Vec<String>,
     pub docker_images: IndexMap<String, String>,
     pub file_denylist: Vec<String>,
 
+    pub source_uuid: Option<uuid::Uuid>,
+    pub source_path: Option<String>,
+
     pub servers: i64,
 
     pub created: chrono::DateTime<chrono::Utc>,
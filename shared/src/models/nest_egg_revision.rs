@@ -0,0 +1,309 @@
+use super::BaseModel;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use sqlx::{Row, postgres::PgRow};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+#[derive(ToSchema, Serialize)]
+pub struct NestEggRevisionChange {
+    pub path: String,
+    pub from: Option<serde_json::Value>,
+    pub to: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NestEggRevision {
+    pub uuid: uuid::Uuid,
+    pub egg_uuid: uuid::Uuid,
+    pub user_uuid: Option<uuid::Uuid>,
+    pub user_username: Option<String>,
+
+    pub revision: i32,
+    /// A snapshot of the egg, see [`super::nest_egg::NestEgg::snapshot`].
+    pub data: serde_json::Value,
+    pub hash: String,
+
+    pub created: chrono::NaiveDateTime,
+}
+
+impl BaseModel for NestEggRevision {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            ("nest_egg_revisions.uuid", format!("{prefix}uuid")),
+            ("nest_egg_revisions.egg_uuid", format!("{prefix}egg_uuid")),
+            ("nest_egg_revisions.user_uuid", format!("{prefix}user_uuid")),
+            (
+                "(SELECT users.username FROM users WHERE users.uuid = nest_egg_revisions.user_uuid)",
+                format!("{prefix}user_username"),
+            ),
+            ("nest_egg_revisions.revision", format!("{prefix}revision")),
+            ("nest_egg_revisions.data", format!("{prefix}data")),
+            ("nest_egg_revisions.hash", format!("{prefix}hash")),
+            ("nest_egg_revisions.created", format!("{prefix}created")),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            uuid: row.get(format!("{prefix}uuid").as_str()),
+            egg_uuid: row.get(format!("{prefix}egg_uuid").as_str()),
+            user_uuid: row.get(format!("{prefix}user_uuid").as_str()),
+            user_username: row.get(format!("{prefix}user_username").as_str()),
+            revision: row.get(format!("{prefix}revision").as_str()),
+            data: row.get(format!("{prefix}data").as_str()),
+            hash: row.get(format!("{prefix}hash").as_str()),
+            created: row.get(format!("{prefix}created").as_str()),
+        }
+    }
+}
+
+impl NestEggRevision {
+    /// Stores the state of the egg as a new revision, unless it matches the latest one.
+    /// Call it once after changing the egg, inside the same transaction. The egg row stays
+    /// locked until the transaction ends, so concurrent changes get consecutive revisions.
+    pub async fn record(
+        transaction: &mut sqlx::PgTransaction<'_>,
+        egg_uuid: uuid::Uuid,
+        user_uuid: Option<uuid::Uuid>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let egg = match super::nest_egg::NestEgg::lock(&mut **transaction, egg_uuid).await? {
+            Some(egg) => egg,
+            None => return Ok(None),
+        };
+        let variables = super::nest_egg_variable::NestEggVariable::all_by_egg_uuid(
+            &mut **transaction,
+            egg_uuid,
+        )
+        .await?;
+
+        let data = egg.snapshot(&variables);
+        let hash = format!("{:x}", sha2::Sha256::digest(data.to_string()));
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO nest_egg_revisions (egg_uuid, user_uuid, revision, data, hash)
+            SELECT $1, $2, COALESCE(MAX(nest_egg_revisions.revision), 0) + 1, $3, $4
+            FROM nest_egg_revisions
+            WHERE nest_egg_revisions.egg_uuid = $1
+            HAVING (
+                SELECT latest.hash FROM nest_egg_revisions latest
+                WHERE latest.egg_uuid = $1
+                ORDER BY latest.revision DESC
+                LIMIT 1
+            ) IS DISTINCT FROM $4
+            RETURNING {}
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(egg_uuid)
+        .bind(user_uuid)
+        .bind(data)
+        .bind(hash)
+        .fetch_optional(&mut **transaction)
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn by_egg_uuid_revision(
+        database: &crate::database::Database,
+        egg_uuid: uuid::Uuid,
+        revision: i32,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM nest_egg_revisions
+            WHERE nest_egg_revisions.egg_uuid = $1 AND nest_egg_revisions.revision = $2
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(egg_uuid)
+        .bind(revision)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn by_egg_uuid_uuid(
+        database: &crate::database::Database,
+        egg_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM nest_egg_revisions
+            WHERE nest_egg_revisions.egg_uuid = $1 AND nest_egg_revisions.uuid = $2
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(egg_uuid)
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn by_egg_uuid_with_pagination(
+        database: &crate::database::Database,
+        egg_uuid: uuid::Uuid,
+        page: i64,
+        per_page: i64,
+    ) -> Result<super::Pagination<Self>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER() AS total_count
+            FROM nest_egg_revisions
+            WHERE nest_egg_revisions.egg_uuid = $1
+            ORDER BY nest_egg_revisions.revision DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(egg_uuid)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(super::Pagination {
+            total: rows.first().map_or(0, |row| row.get("total_count")),
+            per_page,
+            page,
+            data: rows.into_iter().map(|row| Self::map(None, &row)).collect(),
+        })
+    }
+
+    /// Overlays the revision onto the egg, variables are not part of the overlay
+    /// as server variables reference the current ones.
+    pub fn apply(&self, egg: &mut super::nest_egg::NestEgg) {
+        let import: super::nest_egg::NestEggImport = match serde_json::from_value(self.data.clone())
+        {
+            Ok(import) => import,
+            Err(err) => {
+                tracing::error!(revision = %self.uuid, "failed to parse egg revision: {:#?}", err);
+                return;
+            }
+        };
+
+        import.apply(egg, &mut Vec::new());
+    }
+
+    /// Lists every value that differs between two revisions, variables are matched by their
+    /// environment variable.
+    pub fn diff(&self, other: &Self) -> Vec<NestEggRevisionChange> {
+        let mut changes = Vec::new();
+        diff_values(
+            String::new(),
+            Some(&self.data),
+            Some(&other.data),
+            &mut changes,
+        );
+
+        changes
+    }
+
+    #[inline]
+    pub fn into_admin_api_object(self, include_data: bool) -> AdminApiNestEggRevision {
+        AdminApiNestEggRevision {
+            uuid: self.uuid,
+            revision: self.revision,
+            user_uuid: self.user_uuid,
+            user_username: self.user_username,
+            data: include_data.then_some(self.data),
+            hash: self.hash,
+            created: self.created.and_utc(),
+        }
+    }
+}
+
+fn diff_values(
+    path: String,
+    from: Option<&serde_json::Value>,
+    to: Option<&serde_json::Value>,
+    changes: &mut Vec<NestEggRevisionChange>,
+) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+
+    match (from, to) {
+        (Some(serde_json::Value::Object(from)), Some(serde_json::Value::Object(to))) => {
+            for (key, value) in from {
+                diff_values(join(key), Some(value), to.get(key), changes);
+            }
+            for (key, value) in to {
+                if !from.contains_key(key) {
+                    diff_values(join(key), None, Some(value), changes);
+                }
+            }
+        }
+        (Some(serde_json::Value::Array(from)), Some(serde_json::Value::Array(to)))
+            if from
+                .iter()
+                .chain(to)
+                .all(|value| value["env_variable"].is_string()) =>
+        {
+            let key =
+                |value: &serde_json::Value| value["env_variable"].as_str().map(str::to_string);
+
+            for value in from {
+                let key = key(value).unwrap_or_default();
+                let other = to
+                    .iter()
+                    .find(|other| other["env_variable"] == value["env_variable"]);
+                diff_values(join(&key), Some(value), other, changes);
+            }
+            for value in to {
+                if !from
+                    .iter()
+                    .any(|other| other["env_variable"] == value["env_variable"])
+                {
+                    diff_values(
+                        join(&key(value).unwrap_or_default()),
+                        None,
+                        Some(value),
+                        changes,
+                    );
+                }
+            }
+        }
+        (from, to) if from != to => changes.push(NestEggRevisionChange {
+            path,
+            from: from.cloned(),
+            to: to.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "NestEggRevision")]
+pub struct AdminApiNestEggRevision {
+    pub uuid: uuid::Uuid,
+    pub revision: i32,
+
+    pub user_uuid: Option<uuid::Uuid>,
+    pub user_username: Option<String>,
+
+    pub data: Option<serde_json::Value>,
+    pub hash: String,
+
+    pub created: chrono::DateTime<chrono::Utc>,
+}
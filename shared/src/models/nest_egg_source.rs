@@ -0,0 +1,744 @@
+use super::BaseModel;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use sqlx::{Row, postgres::PgRow};
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+    sync::LazyLock,
+};
+use utoipa::ToSchema;
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .user_agent(format!("github.com/calagopus-rs/panel {}", crate::VERSION))
+        .timeout(std::time::Duration::from_secs(30))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("Failed to create HTTP client")
+});
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SourceIndex {
+    List(Vec<String>),
+    Object { eggs: Vec<String> },
+}
+
+impl SourceIndex {
+    fn into_entries(self) -> Vec<String> {
+        match self {
+            Self::List(entries) | Self::Object { eggs: entries } => entries,
+        }
+    }
+}
+
+enum SourceLocation {
+    Http(reqwest::Url),
+    Local(PathBuf),
+}
+
+/// Indexes and eggs are small json documents, anything larger is refused.
+const MAX_DOCUMENT_SIZE: u64 = 4 * 1024 * 1024;
+
+impl SourceLocation {
+    async fn read(&self) -> Result<Vec<u8>, anyhow::Error> {
+        match self {
+            Self::Http(url) => {
+                let mut response = CLIENT.get(url.clone()).send().await?;
+                if !response.status().is_success() {
+                    return Err(anyhow::anyhow!(
+                        "unexpected status {} for {url}",
+                        response.status()
+                    ));
+                }
+
+                if response
+                    .content_length()
+                    .is_some_and(|length| length > MAX_DOCUMENT_SIZE)
+                {
+                    return Err(anyhow::anyhow!(
+                        "{url} is larger than {MAX_DOCUMENT_SIZE} bytes"
+                    ));
+                }
+
+                let mut data = Vec::new();
+                while let Some(chunk) = response.chunk().await? {
+                    if data.len() as u64 + chunk.len() as u64 > MAX_DOCUMENT_SIZE {
+                        return Err(anyhow::anyhow!(
+                            "{url} is larger than {MAX_DOCUMENT_SIZE} bytes"
+                        ));
+                    }
+
+                    data.extend_from_slice(&chunk);
+                }
+
+                Ok(data)
+            }
+            Self::Local(path) => {
+                if tokio::fs::metadata(path).await?.len() > MAX_DOCUMENT_SIZE {
+                    return Err(anyhow::anyhow!(
+                        "{} is larger than {MAX_DOCUMENT_SIZE} bytes",
+                        path.display()
+                    ));
+                }
+
+                Ok(tokio::fs::read(path).await?)
+            }
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize, Deserialize, Clone)]
+pub struct NestEggSourceSyncEntry {
+    pub path: String,
+    pub egg_uuid: Option<uuid::Uuid>,
+    pub name: Option<String>,
+
+    /// The parts of the egg that differ from the source.
+    #[serde(default)]
+    pub changes: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl NestEggSourceSyncEntry {
+    fn new(path: &str, egg: Option<&super::nest_egg::NestEgg>) -> Self {
+        Self {
+            path: path.to_string(),
+            egg_uuid: egg.map(|egg| egg.uuid),
+            name: egg.map(|egg| egg.name.clone()),
+            changes: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize, Deserialize, Clone, Default)]
+pub struct NestEggSourceSyncReport {
+    pub created: Vec<NestEggSourceSyncEntry>,
+    pub updated: Vec<NestEggSourceSyncEntry>,
+    pub unchanged: Vec<NestEggSourceSyncEntry>,
+    /// Eggs that differ from the source but were changed locally since the last sync.
+    pub modified: Vec<NestEggSourceSyncEntry>,
+    /// Eggs that are no longer part of the source, these are kept.
+    pub missing: Vec<NestEggSourceSyncEntry>,
+    pub failed: Vec<NestEggSourceSyncEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NestEggSource {
+    pub uuid: uuid::Uuid,
+    pub nest_uuid: uuid::Uuid,
+
+    pub name: String,
+    pub url: String,
+    pub sync_interval: Option<i32>,
+
+    pub last_synced: Option<chrono::NaiveDateTime>,
+    pub last_sync_report: Option<NestEggSourceSyncReport>,
+    pub last_sync_error: Option<String>,
+
+    pub eggs: i64,
+
+    pub created: chrono::NaiveDateTime,
+}
+
+impl BaseModel for NestEggSource {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            ("nest_egg_sources.uuid", format!("{prefix}uuid")),
+            ("nest_egg_sources.nest_uuid", format!("{prefix}nest_uuid")),
+            ("nest_egg_sources.name", format!("{prefix}name")),
+            ("nest_egg_sources.url", format!("{prefix}url")),
+            (
+                "nest_egg_sources.sync_interval",
+                format!("{prefix}sync_interval"),
+            ),
+            (
+                "nest_egg_sources.last_synced",
+                format!("{prefix}last_synced"),
+            ),
+            (
+                "nest_egg_sources.last_sync_report",
+                format!("{prefix}last_sync_report"),
+            ),
+            (
+                "nest_egg_sources.last_sync_error",
+                format!("{prefix}last_sync_error"),
+            ),
+            (
+                "(SELECT COUNT(*) FROM nest_eggs WHERE nest_eggs.source_uuid = nest_egg_sources.uuid)",
+                format!("{prefix}eggs"),
+            ),
+            ("nest_egg_sources.created", format!("{prefix}created")),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            uuid: row.get(format!("{prefix}uuid").as_str()),
+            nest_uuid: row.get(format!("{prefix}nest_uuid").as_str()),
+            name: row.get(format!("{prefix}name").as_str()),
+            url: row.get(format!("{prefix}url").as_str()),
+            sync_interval: row.get(format!("{prefix}sync_interval").as_str()),
+            last_synced: row.get(format!("{prefix}last_synced").as_str()),
+            last_sync_report: row
+                .get::<Option<serde_json::Value>, _>(format!("{prefix}last_sync_report").as_str())
+                .and_then(|report| serde_json::from_value(report).ok()),
+            last_sync_error: row.get(format!("{prefix}last_sync_error").as_str()),
+            eggs: row.get(format!("{prefix}eggs").as_str()),
+            created: row.get(format!("{prefix}created").as_str()),
+        }
+    }
+}
+
+impl NestEggSource {
+    pub async fn create(
+        database: &crate::database::Database,
+        nest_uuid: uuid::Uuid,
+        name: &str,
+        url: &str,
+        sync_interval: Option<i32>,
+    ) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO nest_egg_sources (nest_uuid, name, url, sync_interval)
+            VALUES ($1, $2, $3, $4)
+            RETURNING {}
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(nest_uuid)
+        .bind(name)
+        .bind(url)
+        .bind(sync_interval)
+        .fetch_one(database.write())
+        .await?;
+
+        Ok(Self::map(None, &row))
+    }
+
+    pub async fn by_uuid(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM nest_egg_sources
+            WHERE nest_egg_sources.uuid = $1
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn by_nest_uuid_uuid(
+        database: &crate::database::Database,
+        nest_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM nest_egg_sources
+            WHERE nest_egg_sources.nest_uuid = $1 AND nest_egg_sources.uuid = $2
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(nest_uuid)
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn by_nest_uuid_with_pagination(
+        database: &crate::database::Database,
+        nest_uuid: uuid::Uuid,
+        page: i64,
+        per_page: i64,
+        search: Option<&str>,
+    ) -> Result<super::Pagination<Self>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER() AS total_count
+            FROM nest_egg_sources
+            WHERE nest_egg_sources.nest_uuid = $1 AND ($2 IS NULL OR nest_egg_sources.name ILIKE '%' || $2 || '%')
+            ORDER BY nest_egg_sources.created
+            LIMIT $3 OFFSET $4
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(nest_uuid)
+        .bind(search)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(super::Pagination {
+            total: rows.first().map_or(0, |row| row.get("total_count")),
+            per_page,
+            page,
+            data: rows.into_iter().map(|row| Self::map(None, &row)).collect(),
+        })
+    }
+
+    pub async fn update(&self, database: &crate::database::Database) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE nest_egg_sources
+            SET name = $2, url = $3, sync_interval = $4
+            WHERE nest_egg_sources.uuid = $1
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(&self.name)
+        .bind(&self.url)
+        .bind(self.sync_interval)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_sync_result(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+        result: Result<&NestEggSourceSyncReport, &str>,
+    ) -> Result<(), sqlx::Error> {
+        let (report, error) = match result {
+            Ok(report) => (Some(serde_json::to_value(report).unwrap()), None),
+            Err(error) => (None, Some(error)),
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE nest_egg_sources
+            SET last_synced = NOW(), last_sync_report = COALESCE($2, last_sync_report), last_sync_error = $3
+            WHERE nest_egg_sources.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .bind(report)
+        .bind(error)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_by_uuid(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM nest_egg_sources
+            WHERE nest_egg_sources.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn is_syncing_sql() -> String {
+        format!(
+            r#"
+            EXISTS (
+                SELECT 1 FROM queued_jobs
+                WHERE queued_jobs.kind = '{}' AND queued_jobs.payload->>'source_uuid' = nest_egg_sources.uuid::text
+            )
+            "#,
+            <crate::jobs::nest::SyncNestEggSource as crate::jobs::Job>::KIND
+        )
+    }
+
+    /// Whether a sync of the source is queued or running.
+    pub async fn is_syncing(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar(&format!(
+            "SELECT {} FROM nest_egg_sources WHERE nest_egg_sources.uuid = $1",
+            Self::is_syncing_sql()
+        ))
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await
+        .map(|syncing| syncing.unwrap_or_default())
+    }
+
+    /// Queues a sync of every source whose interval passed, returns the amount of queued syncs.
+    pub async fn enqueue_due(database: &crate::database::Database) -> Result<usize, anyhow::Error> {
+        let uuids: Vec<uuid::Uuid> = sqlx::query_scalar(&format!(
+            r#"
+            SELECT nest_egg_sources.uuid
+            FROM nest_egg_sources
+            WHERE nest_egg_sources.sync_interval IS NOT NULL
+                AND (
+                    nest_egg_sources.last_synced IS NULL
+                    OR nest_egg_sources.last_synced + make_interval(mins => nest_egg_sources.sync_interval) <= NOW()
+                )
+                AND NOT {}
+            "#,
+            Self::is_syncing_sql()
+        ))
+        .fetch_all(database.read())
+        .await?;
+
+        for uuid in &uuids {
+            crate::jobs::enqueue(
+                database,
+                &crate::jobs::nest::SyncNestEggSource { source_uuid: *uuid },
+            )
+            .await?;
+        }
+
+        Ok(uuids.len())
+    }
+
+    /// Resolves the index of the source into its entries. The url is either an http(s)
+    /// url or a local path to an index file, listing egg files relative to it, or to a
+    /// directory whose json files are used.
+    ///
+    /// Entries of http sources have to stay on the origin of the source and redirects are
+    /// not followed, local sources and their entries have to stay inside of
+    /// `EGG_SOURCE_DIRECTORY`.
+    async fn entries(
+        &self,
+        env: &crate::env::Env,
+    ) -> Result<Vec<(String, SourceLocation)>, anyhow::Error> {
+        if self.url.starts_with("http://") || self.url.starts_with("https://") {
+            let url = reqwest::Url::parse(&self.url)?;
+            let index: SourceIndex =
+                serde_json::from_slice(&SourceLocation::Http(url.clone()).read().await?)?;
+
+            return index
+                .into_entries()
+                .into_iter()
+                .map(|entry| {
+                    let location = url.join(&entry)?;
+                    if location.origin() != url.origin() {
+                        return Err(anyhow::anyhow!(
+                            "entry {entry} is not on the origin of the source"
+                        ));
+                    }
+
+                    Ok((entry, SourceLocation::Http(location)))
+                })
+                .collect();
+        }
+
+        let directory = env.egg_source_directory.as_deref().ok_or_else(|| {
+            anyhow::anyhow!("local egg sources require EGG_SOURCE_DIRECTORY to be set")
+        })?;
+        let directory = tokio::fs::canonicalize(directory).await?;
+
+        let root = tokio::fs::canonicalize(directory.join(&self.url)).await?;
+        if !root.starts_with(&directory) {
+            return Err(anyhow::anyhow!(
+                "egg source is not inside of EGG_SOURCE_DIRECTORY"
+            ));
+        }
+
+        if !tokio::fs::metadata(&root).await?.is_dir() {
+            let index: SourceIndex = serde_json::from_slice(&tokio::fs::read(&root).await?)?;
+            let parent = root.parent().unwrap_or(Path::new("/"));
+
+            let mut entries = Vec::new();
+            for entry in index.into_entries() {
+                if !Path::new(&entry)
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)))
+                {
+                    return Err(anyhow::anyhow!("entry {entry} is not a relative path"));
+                }
+
+                // symlinks may still point outside, only the resolved path counts
+                let path = tokio::fs::canonicalize(parent.join(&entry)).await;
+                match path {
+                    Ok(path) if !path.starts_with(&directory) => {
+                        return Err(anyhow::anyhow!(
+                            "entry {entry} is not inside of EGG_SOURCE_DIRECTORY"
+                        ));
+                    }
+                    Ok(path) => entries.push((entry, SourceLocation::Local(path))),
+                    // missing files are reported per entry by the sync
+                    Err(_) => {
+                        let location = SourceLocation::Local(parent.join(&entry));
+                        entries.push((entry, location));
+                    }
+                }
+            }
+
+            return Ok(entries);
+        }
+
+        let mut entries = Vec::new();
+        let mut directories = vec![root.clone()];
+        while let Some(directory) = directories.pop() {
+            let mut read_dir = tokio::fs::read_dir(&directory).await?;
+
+            while let Some(entry) = read_dir.next_entry().await? {
+                let path = entry.path();
+                let file_type = entry.file_type().await?;
+
+                if file_type.is_dir() {
+                    if !entry.file_name().to_string_lossy().starts_with('.') {
+                        directories.push(path);
+                    }
+                } else if file_type.is_file()
+                    && path
+                        .extension()
+                        .is_some_and(|extension| extension == "json")
+                {
+                    let relative = path
+                        .strip_prefix(&root)?
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+
+                    entries.push((relative, SourceLocation::Local(path)));
+                }
+            }
+        }
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Ok(entries)
+    }
+
+    /// Imports new eggs of the source and updates changed ones, eggs that were changed
+    /// locally since the last sync are left alone. With `dry_run` only the report is built.
+    pub async fn sync(
+        &self,
+        database: &crate::database::Database,
+        env: &crate::env::Env,
+        dry_run: bool,
+    ) -> Result<NestEggSourceSyncReport, anyhow::Error> {
+        let entries = self.entries(env).await?;
+        let mut eggs = super::nest_egg::NestEgg::all_by_source_uuid(database, self.uuid).await?;
+        let mut report = NestEggSourceSyncReport::default();
+
+        for (path, location) in entries {
+            let existing = eggs
+                .iter()
+                .position(|egg| egg.source_path.as_deref() == Some(path.as_str()))
+                .map(|index| eggs.swap_remove(index));
+            let mut entry = NestEggSourceSyncEntry::new(&path, existing.as_ref());
+
+            let import = match location.read().await.and_then(|data| {
+                let upstream_hash = format!("{:x}", sha2::Sha256::digest(&data));
+                let import: super::nest_egg::NestEggImport = serde_json::from_slice(&data)?;
+                if let Err(errors) = crate::utils::validate_data(&import) {
+                    return Err(anyhow::anyhow!(errors.join(", ")));
+                }
+
+                Ok((upstream_hash, import))
+            }) {
+                Ok(import) => import,
+                Err(err) => {
+                    entry.error = Some(err.to_string());
+                    report.failed.push(entry);
+
+                    continue;
+                }
+            };
+            let (upstream_hash, import) = import;
+
+            let egg = match existing {
+                Some(egg) => egg,
+                None => {
+                    entry.name = Some(import.name.clone());
+
+                    if !dry_run {
+                        let mut transaction = database.write().begin().await?;
+
+                        match super::nest_egg::NestEgg::import(
+                            &mut transaction,
+                            self.nest_uuid,
+                            import,
+                        )
+                        .await
+                        {
+                            Ok(egg) => {
+                                super::nest_egg::NestEgg::update_source(
+                                    &mut transaction,
+                                    egg.uuid,
+                                    self.uuid,
+                                    &path,
+                                    &upstream_hash,
+                                )
+                                .await?;
+                                super::nest_egg_revision::NestEggRevision::record(
+                                    &mut transaction,
+                                    egg.uuid,
+                                    None,
+                                )
+                                .await?;
+                                transaction.commit().await?;
+
+                                entry.egg_uuid = Some(egg.uuid);
+                            }
+                            Err(err) => {
+                                entry.error =
+                                    Some(if err.to_string().contains("unique constraint") {
+                                        "egg with name already exists".to_string()
+                                    } else {
+                                        err.to_string()
+                                    });
+                                report.failed.push(entry);
+
+                                continue;
+                            }
+                        }
+                    }
+
+                    report.created.push(entry);
+                    continue;
+                }
+            };
+
+            if egg.upstream_hash.as_deref() == Some(upstream_hash.as_str()) {
+                report.unchanged.push(entry);
+                continue;
+            }
+
+            let variables = super::nest_egg_variable::NestEggVariable::all_by_egg_uuid(
+                database.read(),
+                egg.uuid,
+            )
+            .await?;
+            let locally_modified =
+                egg.synced_hash.as_deref() != Some(egg.content_hash(&variables).as_str());
+
+            let current = egg.export(&variables, super::nest_egg::NestEggExportFormat::Native);
+            let mut updated = egg.clone();
+            let mut updated_variables = variables.clone();
+            import.clone().apply(&mut updated, &mut updated_variables);
+            entry.changes = export_changes(
+                &current,
+                &updated.export(
+                    &updated_variables,
+                    super::nest_egg::NestEggExportFormat::Native,
+                ),
+            );
+
+            if locally_modified {
+                report.modified.push(entry);
+                continue;
+            }
+
+            if !dry_run {
+                let uuid = egg.uuid;
+                let mut transaction = database.write().begin().await?;
+
+                egg.update_from_import(database, &mut transaction, variables, import)
+                    .await?;
+                super::nest_egg::NestEgg::update_source(
+                    &mut transaction,
+                    uuid,
+                    self.uuid,
+                    &path,
+                    &upstream_hash,
+                )
+                .await?;
+                super::nest_egg_revision::NestEggRevision::record(&mut transaction, uuid, None)
+                    .await?;
+                transaction.commit().await?;
+            }
+
+            if entry.changes.is_empty() {
+                report.unchanged.push(entry);
+            } else {
+                report.updated.push(entry);
+            }
+        }
+
+        for egg in eggs {
+            report.missing.push(NestEggSourceSyncEntry::new(
+                egg.source_path.as_deref().unwrap_or_default(),
+                Some(&egg),
+            ));
+        }
+
+        Ok(report)
+    }
+
+    #[inline]
+    pub fn into_admin_api_object(self) -> AdminApiNestEggSource {
+        AdminApiNestEggSource {
+            uuid: self.uuid,
+            name: self.name,
+            url: self.url,
+            sync_interval: self.sync_interval,
+            last_synced: self.last_synced.map(|dt| dt.and_utc()),
+            last_sync_report: self.last_sync_report,
+            last_sync_error: self.last_sync_error,
+            eggs: self.eggs,
+            created: self.created.and_utc(),
+        }
+    }
+}
+
+/// Lists the top level fields of two exports that differ, with config fields as `config.x`.
+fn export_changes(current: &serde_json::Value, updated: &serde_json::Value) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    for (key, value) in updated.as_object().into_iter().flatten() {
+        if matches!(key.as_str(), "meta" | "exported_at") {
+            continue;
+        }
+
+        match (key.as_str(), value, &current[key]) {
+            ("config", serde_json::Value::Object(updated), serde_json::Value::Object(current)) => {
+                for (key, value) in updated {
+                    if current.get(key) != Some(value) {
+                        changes.push(format!("config.{key}"));
+                    }
+                }
+            }
+            (_, value, current) if value != current => changes.push(key.clone()),
+            _ => {}
+        }
+    }
+
+    changes
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "NestEggSource")]
+pub struct AdminApiNestEggSource {
+    pub uuid: uuid::Uuid,
+
+    pub name: String,
+    pub url: String,
+    pub sync_interval: Option<i32>,
+
+    pub last_synced: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_sync_report: Option<NestEggSourceSyncReport>,
+    pub last_sync_error: Option<String>,
+
+    pub eggs: i64,
+
+    pub created: chrono::DateTime<chrono::Utc>,
+}
@@ -1,10 +1,94 @@
 use super::BaseModel;
 use serde::{Deserialize, Serialize};
-use sqlx::{Row, postgres::PgRow};
+use sqlx::{Row, Type, postgres::PgRow};
 use std::collections::BTreeMap;
 use utoipa::ToSchema;
+use validator::Validate;
 
-#[derive(Serialize, Deserialize)]
+#[derive(ToSchema, Serialize, Deserialize, Type, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+#[schema(rename_all = "snake_case")]
+#[sqlx(
+    type_name = "nest_egg_variable_kind",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum NestEggVariableKind {
+    #[default]
+    String,
+    Integer,
+    Boolean,
+    Select,
+    Secret,
+    Port,
+    FilePath,
+    Multiline,
+}
+
+impl NestEggVariableKind {
+    /// Checks a value against the kind, empty values are left to the rules of the variable.
+    pub fn validate(self, value: &str, options: &[String]) -> Result<(), &'static str> {
+        if value.is_empty() {
+            return Ok(());
+        }
+
+        if !matches!(self, Self::Multiline | Self::Secret) && value.contains(['\n', '\r']) {
+            return Err("value must be a single line");
+        }
+
+        match self {
+            Self::Integer if value.parse::<i64>().is_err() => Err("value must be an integer"),
+            Self::Boolean if !matches!(value, "0" | "1" | "true" | "false") => {
+                Err("value must be a boolean")
+            }
+            Self::Select
+                if !options.is_empty() && !options.iter().any(|option| option == value) =>
+            {
+                Err("value must be one of the options")
+            }
+            Self::Port if !matches!(value.parse::<u16>(), Ok(1..)) => {
+                Err("value must be a port between 1 and 65535")
+            }
+            Self::FilePath
+                if value.contains('\0')
+                    || std::path::Path::new(value)
+                        .components()
+                        .any(|component| matches!(component, std::path::Component::ParentDir)) =>
+            {
+                Err("value must be a path within the server")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the options and default value of a variable fit the kind.
+    pub fn validate_definition(
+        self,
+        options: &[String],
+        default_value: Option<&str>,
+    ) -> Result<(), &'static str> {
+        if self == Self::Select && options.is_empty() {
+            return Err("select variables require options");
+        }
+
+        self.validate(default_value.unwrap_or_default(), options)
+    }
+}
+
+#[derive(ToSchema, Validate, Serialize, Deserialize, Clone, Default)]
+pub struct NestEggVariableUiHints {
+    #[validate(length(max = 255))]
+    #[schema(max_length = 255)]
+    pub placeholder: Option<String>,
+    #[validate(length(max = 1024))]
+    #[schema(max_length = 1024)]
+    pub help: Option<String>,
+    /// Variables with the same group are shown together.
+    #[validate(length(max = 255))]
+    #[schema(max_length = 255)]
+    pub group: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct NestEggVariable {
     pub uuid: uuid::Uuid,
 
@@ -17,6 +101,9 @@ pub struct NestEggVariable {
     pub user_viewable: bool,
     pub user_editable: bool,
     pub rules: Vec<String>,
+    pub kind: NestEggVariableKind,
+    pub options: Vec<String>,
+    pub ui_hints: Option<NestEggVariableUiHints>,
 
     pub created: chrono::NaiveDateTime,
 }
@@ -51,6 +138,9 @@ impl BaseModel for NestEggVariable {
                 format!("{prefix}user_editable"),
             ),
             ("nest_egg_variables.rules", format!("{prefix}rules")),
+            ("nest_egg_variables.kind", format!("{prefix}kind")),
+            ("nest_egg_variables.options", format!("{prefix}options")),
+            ("nest_egg_variables.ui_hints", format!("{prefix}ui_hints")),
             ("nest_egg_variables.created", format!("{prefix}created")),
         ])
     }
@@ -69,6 +159,11 @@ impl BaseModel for NestEggVariable {
             user_viewable: row.get(format!("{prefix}user_viewable").as_str()),
             user_editable: row.get(format!("{prefix}user_editable").as_str()),
             rules: row.get(format!("{prefix}rules").as_str()),
+            kind: row.get(format!("{prefix}kind").as_str()),
+            options: row.get(format!("{prefix}options").as_str()),
+            ui_hints: row
+                .get::<Option<serde_json::Value>, _>(format!("{prefix}ui_hints").as_str())
+                .and_then(|ui_hints| serde_json::from_value(ui_hints).ok()),
             created: row.get(format!("{prefix}created").as_str()),
         }
     }
@@ -77,7 +172,7 @@ impl BaseModel for NestEggVariable {
 impl NestEggVariable {
     #[allow(clippy::too_many_arguments)]
     pub async fn create(
-        database: &crate::database::Database,
+        executor: impl sqlx::PgExecutor<'_>,
         egg_uuid: uuid::Uuid,
         name: &str,
         description: Option<&str>,
@@ -87,14 +182,18 @@ impl NestEggVariable {
         user_viewable: bool,
         user_editable: bool,
         rules: &[String],
+        kind: NestEggVariableKind,
+        options: &[String],
+        ui_hints: Option<&NestEggVariableUiHints>,
     ) -> Result<Self, sqlx::Error> {
         let row = sqlx::query(&format!(
             r#"
             INSERT INTO nest_egg_variables (
                 egg_uuid, name, description, order_, env_variable,
-                default_value, user_viewable, user_editable, rules
+                default_value, user_viewable, user_editable, rules,
+                kind, options, ui_hints
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             RETURNING {}
             "#,
             Self::columns_sql(None)
@@ -108,7 +207,10 @@ impl NestEggVariable {
         .bind(user_viewable)
         .bind(user_editable)
         .bind(rules)
-        .fetch_one(database.write())
+        .bind(kind)
+        .bind(options)
+        .bind(ui_hints.map(|ui_hints| serde_json::to_value(ui_hints).unwrap()))
+        .fetch_one(executor)
         .await?;
 
         Ok(Self::map(None, &row))
@@ -136,7 +238,7 @@ impl NestEggVariable {
     }
 
     pub async fn all_by_egg_uuid(
-        database: &crate::database::Database,
+        executor: impl sqlx::PgExecutor<'_>,
         egg_uuid: uuid::Uuid,
     ) -> Result<Vec<Self>, sqlx::Error> {
         let rows = sqlx::query(&format!(
@@ -149,14 +251,49 @@ impl NestEggVariable {
             Self::columns_sql(None)
         ))
         .bind(egg_uuid)
-        .fetch_all(database.read())
+        .fetch_all(executor)
         .await?;
 
         Ok(rows.into_iter().map(|row| Self::map(None, &row)).collect())
     }
 
-    pub async fn delete_by_uuid(
+    pub async fn update_kind(
         database: &crate::database::Database,
+        transaction: &mut sqlx::PgTransaction<'_>,
+        uuid: uuid::Uuid,
+        kind: NestEggVariableKind,
+        options: &[String],
+        ui_hints: Option<&NestEggVariableUiHints>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE nest_egg_variables
+            SET kind = $2, options = $3, ui_hints = $4
+            WHERE nest_egg_variables.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .bind(kind)
+        .bind(options)
+        .bind(ui_hints.map(|ui_hints| serde_json::to_value(ui_hints).unwrap()))
+        .execute(&mut **transaction)
+        .await?;
+
+        super::server_variable::ServerVariable::update_encryption(
+            database,
+            transaction,
+            uuid,
+            kind == NestEggVariableKind::Secret,
+        )
+        .await?;
+        super::server_template::ServerTemplate::update_encryption(database, transaction, uuid)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_by_uuid(
+        executor: impl sqlx::PgExecutor<'_>,
         uuid: uuid::Uuid,
     ) -> Result<(), sqlx::Error> {
         sqlx::query(
@@ -166,12 +303,35 @@ impl NestEggVariable {
             "#,
         )
         .bind(uuid)
-        .execute(database.write())
+        .execute(executor)
         .await?;
 
         Ok(())
     }
 
+    #[inline]
+    pub fn is_secret(&self) -> bool {
+        self.kind == NestEggVariableKind::Secret
+    }
+
+    /// Values by env variable with those of secret variables replaced by `None`,
+    /// as written to activity logs.
+    pub fn mask_values<'a>(
+        variables: &[Self],
+        values: &'a BTreeMap<String, String>,
+    ) -> BTreeMap<&'a str, Option<&'a str>> {
+        values
+            .iter()
+            .map(|(env_variable, value)| {
+                let secret = variables
+                    .iter()
+                    .any(|variable| &variable.env_variable == env_variable && variable.is_secret());
+
+                (env_variable.as_str(), (!secret).then_some(value.as_str()))
+            })
+            .collect()
+    }
+
     #[inline]
     pub fn into_admin_api_object(self) -> AdminApiNestEggVariable {
         AdminApiNestEggVariable {
@@ -184,6 +344,9 @@ impl NestEggVariable {
             user_viewable: self.user_viewable,
             user_editable: self.user_editable,
             rules: self.rules,
+            kind: self.kind,
+            options: self.options,
+            ui_hints: self.ui_hints,
             created: self.created.and_utc(),
         }
     }
@@ -203,6 +366,9 @@ pub struct AdminApiNestEggVariable {
     pub user_viewable: bool,
     pub user_editable: bool,
     pub rules: Vec<String>,
+    pub kind: NestEggVariableKind,
+    pub options: Vec<String>,
+    pub ui_hints: Option<NestEggVariableUiHints>,
 
     pub created: chrono::DateTime<chrono::Utc>,
 }
@@ -396,6 +396,7 @@ impl Node {
             self.url.to_string(),
             database.decrypt(&self.token).unwrap(),
         )
+        .with_observer(crate::metrics::Metrics::wings_observer(self.uuid))
     }
 
     #[inline]
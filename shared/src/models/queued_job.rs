@@ -0,0 +1,318 @@
+use super::BaseModel;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, Type, postgres::PgRow};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+pub type GetQueuedJob = crate::extract::ConsumingExtension<QueuedJob>;
+
+#[derive(ToSchema, Serialize, Deserialize, Type, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[schema(rename_all = "snake_case")]
+#[sqlx(type_name = "queued_job_status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum QueuedJobStatus {
+    Pending,
+    Running,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QueuedJob {
+    pub uuid: uuid::Uuid,
+
+    pub kind: String,
+    pub payload: serde_json::Value,
+
+    pub status: QueuedJobStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub error: Option<String>,
+
+    pub run_at: chrono::NaiveDateTime,
+    pub locked_until: Option<chrono::NaiveDateTime>,
+    pub created: chrono::NaiveDateTime,
+}
+
+impl BaseModel for QueuedJob {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            ("queued_jobs.uuid", format!("{prefix}uuid")),
+            ("queued_jobs.kind", format!("{prefix}kind")),
+            ("queued_jobs.payload", format!("{prefix}payload")),
+            ("queued_jobs.status", format!("{prefix}status")),
+            ("queued_jobs.attempts", format!("{prefix}attempts")),
+            ("queued_jobs.max_attempts", format!("{prefix}max_attempts")),
+            ("queued_jobs.error", format!("{prefix}error")),
+            ("queued_jobs.run_at", format!("{prefix}run_at")),
+            ("queued_jobs.locked_until", format!("{prefix}locked_until")),
+            ("queued_jobs.created", format!("{prefix}created")),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            uuid: row.get(format!("{prefix}uuid").as_str()),
+            kind: row.get(format!("{prefix}kind").as_str()),
+            payload: row.get(format!("{prefix}payload").as_str()),
+            status: row.get(format!("{prefix}status").as_str()),
+            attempts: row.get(format!("{prefix}attempts").as_str()),
+            max_attempts: row.get(format!("{prefix}max_attempts").as_str()),
+            error: row.get(format!("{prefix}error").as_str()),
+            run_at: row.get(format!("{prefix}run_at").as_str()),
+            locked_until: row.get(format!("{prefix}locked_until").as_str()),
+            created: row.get(format!("{prefix}created").as_str()),
+        }
+    }
+}
+
+impl QueuedJob {
+    pub async fn create(
+        executor: impl sqlx::PgExecutor<'_>,
+        kind: &str,
+        payload: serde_json::Value,
+        max_attempts: i32,
+        delay: std::time::Duration,
+    ) -> Result<uuid::Uuid, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO queued_jobs (kind, payload, max_attempts, run_at)
+            VALUES ($1, $2, $3, NOW() + make_interval(secs => $4))
+            RETURNING queued_jobs.uuid
+            "#,
+        )
+        .bind(kind)
+        .bind(payload)
+        .bind(max_attempts)
+        .bind(delay.as_secs_f64())
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn by_uuid(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM queued_jobs
+            WHERE queued_jobs.uuid = $1
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn all_with_pagination(
+        database: &crate::database::Database,
+        page: i64,
+        per_page: i64,
+        search: Option<&str>,
+        status: Option<QueuedJobStatus>,
+    ) -> Result<super::Pagination<Self>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER() AS total_count
+            FROM queued_jobs
+            WHERE ($1 IS NULL OR queued_jobs.kind ILIKE '%' || $1 || '%')
+                AND ($2::queued_job_status IS NULL OR queued_jobs.status = $2)
+            ORDER BY queued_jobs.created DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(search)
+        .bind(status)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(super::Pagination {
+            total: rows.first().map_or(0, |row| row.get("total_count")),
+            per_page,
+            page,
+            data: rows.into_iter().map(|row| Self::map(None, &row)).collect(),
+        })
+    }
+
+    /// Claims up to `limit` due jobs and locks them for `lock`. Jobs that are still marked as
+    /// running after their lock expired belong to a process that went away and are picked up again.
+    pub async fn claim_pending(
+        database: &crate::database::Database,
+        limit: i64,
+        lock: std::time::Duration,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(&format!(
+            r#"
+            UPDATE queued_jobs
+            SET status = 'RUNNING', attempts = queued_jobs.attempts + 1, locked_until = NOW() + make_interval(secs => $2)
+            WHERE queued_jobs.uuid IN (
+                SELECT queued_jobs.uuid
+                FROM queued_jobs
+                WHERE (queued_jobs.status = 'PENDING' AND queued_jobs.run_at <= NOW())
+                    OR (queued_jobs.status = 'RUNNING' AND queued_jobs.locked_until <= NOW())
+                ORDER BY queued_jobs.run_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(limit)
+        .bind(lock.as_secs_f64())
+        .fetch_all(database.write())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| Self::map(None, &row)).collect())
+    }
+
+    /// Extends the lock of a running job, as long as it was not claimed again in the meantime.
+    pub async fn extend_lock(
+        &self,
+        database: &crate::database::Database,
+        lock: std::time::Duration,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE queued_jobs
+            SET locked_until = NOW() + make_interval(secs => $3)
+            WHERE queued_jobs.uuid = $1 AND queued_jobs.status = 'RUNNING' AND queued_jobs.attempts = $2
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(self.attempts)
+        .bind(lock.as_secs_f64())
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records a failed attempt, the job is retried with exponential backoff until it runs
+    /// out of attempts and is kept as failed.
+    pub async fn record_failure(
+        &self,
+        database: &crate::database::Database,
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        let backoff = 10i64 * 2i64.pow(self.attempts.clamp(1, 9) as u32 - 1);
+
+        sqlx::query(
+            r#"
+            UPDATE queued_jobs
+            SET
+                status = CASE WHEN queued_jobs.attempts >= queued_jobs.max_attempts THEN 'FAILED'::queued_job_status ELSE 'PENDING'::queued_job_status END,
+                error = $2,
+                run_at = NOW() + make_interval(secs => $3),
+                locked_until = NULL
+            WHERE queued_jobs.uuid = $1
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(error)
+        .bind(backoff as f64)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Queues a failed job again with a fresh set of attempts.
+    pub async fn retry(&self, database: &crate::database::Database) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE queued_jobs
+            SET status = 'PENDING', attempts = 0, run_at = NOW(), locked_until = NULL
+            WHERE queued_jobs.uuid = $1
+            "#,
+        )
+        .bind(self.uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Whether a job of the kind is waiting to run or running right now.
+    pub async fn active_by_kind_exists(
+        executor: impl sqlx::PgExecutor<'_>,
+        kind: &str,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM queued_jobs
+                WHERE queued_jobs.kind = $1 AND queued_jobs.status IN ('PENDING', 'RUNNING')
+            )
+            "#,
+        )
+        .bind(kind)
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn delete_by_uuid(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM queued_jobs
+            WHERE queued_jobs.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn into_admin_api_object(self) -> AdminApiQueuedJob {
+        AdminApiQueuedJob {
+            uuid: self.uuid,
+            payload: crate::jobs::redact_payload(&self.kind, self.payload),
+            kind: self.kind,
+            status: self.status,
+            attempts: self.attempts,
+            max_attempts: self.max_attempts,
+            error: self.error,
+            run_at: self.run_at.and_utc(),
+            created: self.created.and_utc(),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "QueuedJob")]
+pub struct AdminApiQueuedJob {
+    pub uuid: uuid::Uuid,
+
+    pub kind: String,
+    /// Secret values, like mail bodies, are replaced with `[redacted]`.
+    pub payload: serde_json::Value,
+
+    pub status: QueuedJobStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub error: Option<String>,
+
+    pub run_at: chrono::DateTime<chrono::Utc>,
+    pub created: chrono::DateTime<chrono::Utc>,
+}
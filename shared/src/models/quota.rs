@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// Resource quotas of a user or role, stored as JSON. Missing values are unlimited.
+#[derive(ToSchema, Validate, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Quotas {
+    #[validate(range(min = 0))]
+    #[schema(minimum = 0)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub servers: Option<i64>,
+    #[validate(range(min = 0))]
+    #[schema(minimum = 0)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<i64>,
+    #[validate(range(min = 0))]
+    #[schema(minimum = 0)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk: Option<i64>,
+    #[validate(range(min = 0))]
+    #[schema(minimum = 0)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<i64>,
+    #[validate(range(min = 0))]
+    #[schema(minimum = 0)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub databases: Option<i64>,
+    #[validate(range(min = 0))]
+    #[schema(minimum = 0)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backups: Option<i64>,
+}
+
+impl Quotas {
+    #[inline]
+    pub fn from_value(value: serde_json::Value) -> Self {
+        serde_json::from_value(value).unwrap_or_default()
+    }
+
+    /// Fills every unset quota from `fallback`, used to apply role quotas below user quotas.
+    #[inline]
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            servers: self.servers.or(fallback.servers),
+            memory: self.memory.or(fallback.memory),
+            disk: self.disk.or(fallback.disk),
+            cpu: self.cpu.or(fallback.cpu),
+            databases: self.databases.or(fallback.databases),
+            backups: self.backups.or(fallback.backups),
+        }
+    }
+
+    /// Checks whether a new server with the given limits fits into the quotas.
+    ///
+    /// A limit of `0` means unlimited for memory, disk and cpu, so it is never allowed
+    /// while a quota for that resource is set.
+    pub fn check_server(
+        &self,
+        usage: &QuotaUsage,
+        limits: &super::server::ApiServerLimits,
+        feature_limits: &super::server::ApiServerFeatureLimits,
+    ) -> Result<(), QuotaExceeded> {
+        let checks = [
+            ("servers", self.servers, usage.servers, 1, false),
+            ("memory", self.memory, usage.memory, limits.memory, true),
+            ("disk", self.disk, usage.disk, limits.disk, true),
+            ("cpu", self.cpu, usage.cpu, i64::from(limits.cpu), true),
+            (
+                "databases",
+                self.databases,
+                usage.databases,
+                i64::from(feature_limits.databases),
+                false,
+            ),
+            (
+                "backups",
+                self.backups,
+                usage.backups,
+                i64::from(feature_limits.backups),
+                false,
+            ),
+        ];
+
+        for (resource, limit, used, requested, zero_is_unlimited) in checks {
+            let Some(limit) = limit else {
+                continue;
+            };
+
+            if (zero_is_unlimited && requested == 0) || (requested > 0 && used + requested > limit)
+            {
+                return Err(QuotaExceeded {
+                    resource,
+                    limit,
+                    used,
+                    requested,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks a new server against the quotas inside the transaction that creates it.
+    ///
+    /// Holds a lock on the quotas of the owner until the transaction ends, so concurrent
+    /// creations for the same owner are checked one after another against the usage
+    /// including the servers created before them. Fails with [QuotaExceeded].
+    pub async fn lock_server(
+        &self,
+        transaction: &mut sqlx::PgTransaction<'_>,
+        owner_uuid: uuid::Uuid,
+        limits: &super::server::ApiServerLimits,
+        feature_limits: &super::server::ApiServerFeatureLimits,
+    ) -> Result<(), anyhow::Error> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("quotas:{owner_uuid}"))
+            .execute(&mut **transaction)
+            .await?;
+
+        let usage = QuotaUsage::fetch(&mut **transaction, owner_uuid).await?;
+        self.check_server(&usage, limits, feature_limits)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct QuotaExceeded {
+    pub resource: &'static str,
+    pub limit: i64,
+    pub used: i64,
+    pub requested: i64,
+}
+
+impl std::fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.requested == 0 {
+            write!(
+                f,
+                "{} quota exceeded: unlimited {} is not allowed",
+                self.resource, self.resource
+            )
+        } else {
+            write!(
+                f,
+                "{} quota exceeded: {} of {} used, {} requested",
+                self.resource, self.used, self.limit, self.requested
+            )
+        }
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+/// The resources allocated to all servers owned by a user.
+#[derive(ToSchema, Serialize, Default, Clone, Copy)]
+pub struct QuotaUsage {
+    pub servers: i64,
+    pub memory: i64,
+    pub disk: i64,
+    pub cpu: i64,
+    pub databases: i64,
+    pub backups: i64,
+}
+
+impl QuotaUsage {
+    /// Reads from the primary, so quota checks see servers that were created just before.
+    #[inline]
+    pub async fn by_user_uuid(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        Self::fetch(database.write(), user_uuid).await
+    }
+
+    async fn fetch(
+        executor: impl sqlx::PgExecutor<'_>,
+        user_uuid: uuid::Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COUNT(*) AS servers,
+                COALESCE(SUM(servers.memory), 0)::int8 AS memory,
+                COALESCE(SUM(servers.disk), 0)::int8 AS disk,
+                COALESCE(SUM(servers.cpu), 0)::int8 AS cpu,
+                COALESCE(SUM(servers.database_limit), 0)::int8 AS databases,
+                COALESCE(SUM(servers.backup_limit), 0)::int8 AS backups
+            FROM servers
+            WHERE servers.owner_uuid = $1
+            "#,
+        )
+        .bind(user_uuid)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(Self {
+            servers: row.get("servers"),
+            memory: row.get("memory"),
+            disk: row.get("disk"),
+            cpu: row.get("cpu"),
+            databases: row.get("databases"),
+            backups: row.get("backups"),
+        })
+    }
+}
@@ -14,6 +14,7 @@ pub struct Role {
     pub admin_permissions: Arc<Vec<String>>,
     pub server_permissions: Arc<Vec<String>>,
 
+    pub quotas: super::quota::Quotas,
     pub users: i64,
 
     pub created: chrono::NaiveDateTime,
@@ -36,6 +37,7 @@ impl BaseModel for Role {
                 "roles.server_permissions",
                 format!("{prefix}server_permissions"),
             ),
+            ("roles.quotas", format!("{prefix}quotas")),
             (
                 "(SELECT COUNT(*) FROM users WHERE users.role_uuid = roles.uuid)",
                 format!("{prefix}users"),
@@ -54,6 +56,7 @@ impl BaseModel for Role {
             description: row.get(format!("{prefix}description").as_str()),
             admin_permissions: Arc::new(row.get(format!("{prefix}admin_permissions").as_str())),
             server_permissions: Arc::new(row.get(format!("{prefix}server_permissions").as_str())),
+            quotas: super::quota::Quotas::from_value(row.get(format!("{prefix}quotas").as_str())),
             users: row.get(format!("{prefix}users").as_str()),
             created: row.get(format!("{prefix}created").as_str()),
         }
@@ -71,7 +74,7 @@ impl Role {
         let row = sqlx::query(&format!(
             r#"
             INSERT INTO roles (name, description, admin_permissions, server_permissions)
-            VALUES ($1, $2, $3)
+            VALUES ($1, $2, $3, $4)
             RETURNING {}
             "#,
             Self::columns_sql(None)
@@ -137,6 +140,26 @@ impl Role {
         })
     }
 
+    pub async fn update_quotas(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+        quotas: &super::quota::Quotas,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE roles
+            SET quotas = $2
+            WHERE roles.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .bind(serde_json::to_value(quotas).unwrap_or_default())
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn delete_by_uuid(
         database: &crate::database::Database,
         uuid: uuid::Uuid,
@@ -162,6 +185,7 @@ impl Role {
             description: self.description,
             admin_permissions: self.admin_permissions,
             server_permissions: self.server_permissions,
+            quotas: self.quotas,
             users: self.users,
             created: self.created.and_utc(),
         }
@@ -179,6 +203,7 @@ pub struct AdminApiRole {
     pub admin_permissions: Arc<Vec<String>>,
     pub server_permissions: Arc<Vec<String>>,
 
+    pub quotas: super::quota::Quotas,
     pub users: i64,
 
     pub created: chrono::DateTime<chrono::Utc>,
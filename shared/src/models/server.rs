@@ -4,6 +4,7 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use sqlx::{Row, postgres::PgRow, prelude::Type};
 use std::{
+    borrow::Cow,
     collections::{BTreeMap, HashMap},
     sync::Arc,
 };
@@ -66,8 +67,10 @@ pub struct Server {
     pub destination_node_uuid: Option<uuid::Uuid>,
     pub owner: super::user::User,
     pub egg: Box<super::nest_egg::NestEgg>,
+    pub egg_revision_uuid: Option<uuid::Uuid>,
     pub nest: Box<super::nest::Nest>,
     pub backup_configuration: Option<Box<super::backup_configurations::BackupConfiguration>>,
+    pub server_template_uuid: Option<uuid::Uuid>,
 
     pub status: Option<ServerStatus>,
     pub suspended: bool,
@@ -91,6 +94,7 @@ pub struct Server {
     pub database_limit: i32,
     pub backup_limit: i32,
     pub schedule_limit: i32,
+    pub backup_retention: Option<super::backup_retention::BackupRetention>,
 
     pub subuser_permissions: Option<Arc<Vec<String>>>,
     pub subuser_ignored_files: Option<Vec<String>>,
@@ -117,6 +121,14 @@ impl BaseModel for Server {
                 "servers.destination_node_uuid",
                 format!("{prefix}destination_node_uuid"),
             ),
+            (
+                "servers.server_template_uuid",
+                format!("{prefix}server_template_uuid"),
+            ),
+            (
+                "servers.egg_revision_uuid",
+                format!("{prefix}egg_revision_uuid"),
+            ),
             ("servers.status", format!("{prefix}status")),
             ("servers.suspended", format!("{prefix}suspended")),
             ("servers.name", format!("{prefix}name")),
@@ -137,6 +149,10 @@ impl BaseModel for Server {
             ),
             ("servers.database_limit", format!("{prefix}database_limit")),
             ("servers.backup_limit", format!("{prefix}backup_limit")),
+            (
+                "servers.backup_retention",
+                format!("{prefix}backup_retention"),
+            ),
             ("servers.schedule_limit", format!("{prefix}schedule_limit")),
             ("servers.created", format!("{prefix}created")),
         ]);
@@ -197,6 +213,10 @@ impl BaseModel for Server {
             } else {
                 None
             },
+            server_template_uuid: row
+                .try_get::<uuid::Uuid, _>(format!("{prefix}server_template_uuid").as_str())
+                .ok(),
+            egg_revision_uuid: row.get(format!("{prefix}egg_revision_uuid").as_str()),
             status: row.get(format!("{prefix}status").as_str()),
             suspended: row.get(format!("{prefix}suspended").as_str()),
             name: row.get(format!("{prefix}name").as_str()),
@@ -218,6 +238,9 @@ impl BaseModel for Server {
             database_limit: row.get(format!("{prefix}database_limit").as_str()),
             backup_limit: row.get(format!("{prefix}backup_limit").as_str()),
             schedule_limit: row.get(format!("{prefix}schedule_limit").as_str()),
+            backup_retention: row
+                .get::<Option<serde_json::Value>, _>(format!("{prefix}backup_retention").as_str())
+                .map(super::backup_retention::BackupRetention::from_value),
             subuser_permissions: row
                 .try_get::<Vec<String>, _>("permissions")
                 .map(Arc::new)
@@ -229,16 +252,41 @@ impl BaseModel for Server {
     }
 }
 
+/// What [`Server::create_clone`] copies besides the configuration of the server.
+pub struct ServerCloneOptions<'a> {
+    pub owner_uuid: uuid::Uuid,
+    pub name: &'a str,
+    pub subusers: bool,
+    pub databases: bool,
+    /// A completed backup of the server to seed the clone from instead of a fresh one.
+    pub backup_uuid: Option<uuid::Uuid>,
+    /// Quotas of the owner the clone has to fit into, see [Server::create].
+    pub quotas: Option<super::quota::Quotas>,
+}
+
+/// Where a new server is placed, either on an explicit node and allocations
+/// or on whatever node a deployment request resolves to.
+pub enum ServerPlacement<'a> {
+    Node {
+        node: &'a super::node::Node,
+        allocation_uuid: Option<uuid::Uuid>,
+        allocation_uuids: &'a [uuid::Uuid],
+    },
+    Deploy(&'a crate::deployment::DeploymentRequest),
+}
+
 impl Server {
+    /// Creates the server and installs it on its node, checking it against `quotas`
+    /// of the owner inside the same transaction when given.
     #[allow(clippy::too_many_arguments)]
     pub async fn create(
         database: &crate::database::Database,
-        node: &super::node::Node,
+        placement: ServerPlacement<'_>,
         owner_uuid: uuid::Uuid,
+        quotas: Option<&super::quota::Quotas>,
         egg_uuid: uuid::Uuid,
         backup_configuration_uuid: Option<uuid::Uuid>,
-        allocation_uuid: Option<uuid::Uuid>,
-        allocation_uuids: &[uuid::Uuid],
+        server_template_uuid: Option<uuid::Uuid>,
         external_id: Option<&str>,
         start_on_completion: bool,
         skip_scripts: bool,
@@ -250,10 +298,57 @@ impl Server {
         image: &str,
         timezone: Option<&str>,
         feature_limits: &ApiServerFeatureLimits,
-    ) -> Result<uuid::Uuid, sqlx::Error> {
+        variables: &BTreeMap<String, String>,
+        mount_uuids: &[uuid::Uuid],
+    ) -> Result<uuid::Uuid, anyhow::Error> {
         let mut transaction = database.write().begin().await?;
         let mut attempts = 0;
 
+        if let Some(quotas) = quotas {
+            quotas
+                .lock_server(&mut transaction, owner_uuid, limits, feature_limits)
+                .await?;
+        }
+
+        let (node, allocation_uuid, allocation_uuids) = match placement {
+            ServerPlacement::Node {
+                node,
+                allocation_uuid,
+                allocation_uuids,
+            } => (
+                Cow::Borrowed(node),
+                allocation_uuid,
+                Cow::Borrowed(allocation_uuids),
+            ),
+            ServerPlacement::Deploy(request) => {
+                let Some(deployment) = crate::deployment::reserve(
+                    &mut transaction,
+                    request,
+                    limits.memory,
+                    limits.disk,
+                )
+                .await?
+                else {
+                    transaction.rollback().await?;
+
+                    return Err(crate::deployment::NoDeploymentAvailable.into());
+                };
+
+                let mut allocation_uuids: Vec<uuid::Uuid> = deployment
+                    .allocations
+                    .into_iter()
+                    .map(|allocation| allocation.uuid)
+                    .collect();
+                let allocation_uuid = allocation_uuids.remove(0);
+
+                (
+                    Cow::Owned(deployment.node),
+                    Some(allocation_uuid),
+                    Cow::Owned(allocation_uuids),
+                )
+            }
+        };
+
         loop {
             let uuid = uuid::Uuid::new_v4();
             let uuid_short = uuid.as_fields().0 as i32;
@@ -268,6 +363,7 @@ impl Server {
                     owner_uuid,
                     egg_uuid,
                     backup_configuration_uuid,
+                    server_template_uuid,
                     name,
                     description,
                     status,
@@ -288,7 +384,7 @@ impl Server {
                 VALUES (
                     $1, $2, $3, $4, $5, $6, $7, $8, $9,
                     $10, $11, $12, $13, $14, $15, $16,
-                    $17, $18, $19, $20, $21, $22, $23
+                    $17, $18, $19, $20, $21, $22, $23, $24
                 )
                 RETURNING uuid
                 "#,
@@ -300,6 +396,7 @@ impl Server {
             .bind(owner_uuid)
             .bind(egg_uuid)
             .bind(backup_configuration_uuid)
+            .bind(server_template_uuid)
             .bind(name)
             .bind(description)
             .bind(if skip_scripts {
@@ -345,7 +442,7 @@ impl Server {
                             None
                         };
 
-                    for allocation_uuid in allocation_uuids {
+                    for allocation_uuid in allocation_uuids.iter() {
                         sqlx::query(
                             r#"
                             INSERT INTO server_allocations (server_uuid, allocation_uuid)
@@ -370,6 +467,20 @@ impl Server {
                     .execute(&mut *transaction)
                     .await?;
 
+                    super::server_variable::ServerVariable::set_by_env_variables(
+                        database,
+                        &mut *transaction,
+                        &[uuid],
+                        variables,
+                    )
+                    .await?;
+                    super::server_mount::ServerMount::create_available(
+                        &mut *transaction,
+                        &[uuid],
+                        mount_uuids,
+                    )
+                    .await?;
+
                     transaction.commit().await?;
 
                     if let Err(err) = node
@@ -387,7 +498,7 @@ impl Server {
                             .execute(database.write())
                             .await?;
 
-                        return Err(sqlx::Error::Io(std::io::Error::other(err.1.error)));
+                        return Err(anyhow::anyhow!(err.1.error));
                     }
 
                     return Ok(uuid);
@@ -400,7 +511,7 @@ impl Server {
                         );
                         transaction.rollback().await?;
 
-                        return Err(err);
+                        return Err(err.into());
                     }
                     attempts += 1;
 
@@ -415,6 +526,228 @@ impl Server {
         }
     }
 
+    /// Creates a copy of the server with the same egg, limits, variables, mounts and
+    /// schedules. The files are restored from `options.backup_uuid`, or from a fresh
+    /// backup once it completes, the clone stays in the restoring state until then.
+    ///
+    /// Returns the uuids of the clone and of the backup it is seeded from. If anything
+    /// after creating the clone fails, the clone is deleted again.
+    pub async fn create_clone(
+        &self,
+        database: &Arc<crate::database::Database>,
+        placement: ServerPlacement<'_>,
+        options: &ServerCloneOptions<'_>,
+    ) -> Result<(uuid::Uuid, uuid::Uuid), anyhow::Error> {
+        let variables: BTreeMap<String, String> =
+            super::server_variable::ServerVariable::all_by_server_uuid_egg_uuid(
+                database,
+                self.uuid,
+                self.egg.uuid,
+            )
+            .await?
+            .into_iter()
+            .map(|variable| {
+                let value = variable.decrypted_value(database)?;
+
+                Ok::<_, sqlx::Error>((variable.variable.env_variable, value))
+            })
+            .collect::<Result<_, _>>()?;
+        let mount_uuids: Vec<uuid::Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT server_mounts.mount_uuid
+            FROM server_mounts
+            WHERE server_mounts.server_uuid = $1
+            "#,
+        )
+        .bind(self.uuid)
+        .fetch_all(database.read())
+        .await?;
+
+        let uuid = Self::create(
+            database,
+            placement,
+            options.owner_uuid,
+            options.quotas.as_ref(),
+            self.egg.uuid,
+            self.backup_configuration
+                .as_ref()
+                .map(|backup_configuration| backup_configuration.uuid),
+            self.server_template_uuid,
+            None,
+            false,
+            true,
+            options.name,
+            self.description.as_deref(),
+            &self.limits(),
+            &self.pinned_cpus,
+            &self.startup,
+            &self.image,
+            self.timezone.as_deref(),
+            &self.feature_limits(),
+            &variables,
+            &mount_uuids,
+        )
+        .await?;
+
+        match self.populate_clone(database, uuid, options).await {
+            Ok(backup_uuid) => Ok((uuid, backup_uuid)),
+            Err(err) => {
+                // the clone exists on wings already, so it has to be deleted like any server
+                match Self::by_uuid(database, uuid).await {
+                    Ok(Some(clone)) => {
+                        if let Err(err) = clone.delete(database, true).await {
+                            tracing::error!(server = %uuid, "failed to delete incomplete clone: {:#?}", err);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        tracing::error!(server = %uuid, "failed to delete incomplete clone: {:#?}", err);
+                    }
+                }
+
+                Err(err)
+            }
+        }
+    }
+
+    /// Copies everything but the files of the server into the freshly created clone and
+    /// queues the restore of its files, returns the uuid of the backup they come from.
+    async fn populate_clone(
+        &self,
+        database: &Arc<crate::database::Database>,
+        uuid: uuid::Uuid,
+        options: &ServerCloneOptions<'_>,
+    ) -> Result<uuid::Uuid, anyhow::Error> {
+        sqlx::query(
+            r#"
+            UPDATE servers
+            SET auto_kill = $2, backup_retention = $3
+            WHERE servers.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .bind(serde_json::to_value(&self.auto_kill)?)
+        .bind(
+            self.backup_retention
+                .map(|retention| serde_json::to_value(retention).unwrap()),
+        )
+        .execute(database.write())
+        .await?;
+
+        for schedule in
+            super::server_schedule::ServerSchedule::all_by_server_uuid(database, self.uuid).await?
+        {
+            let steps = super::server_schedule_step::ServerScheduleStep::all_by_schedule_uuid(
+                database,
+                schedule.uuid,
+            )
+            .await?;
+            let copy = super::server_schedule::ServerSchedule::create(
+                database,
+                uuid,
+                &schedule.name,
+                schedule.enabled,
+                schedule.triggers,
+                schedule.condition,
+            )
+            .await?;
+
+            for step in steps {
+                super::server_schedule_step::ServerScheduleStep::create(
+                    database,
+                    copy.uuid,
+                    step.action,
+                    step.order,
+                )
+                .await?;
+            }
+        }
+
+        if options.subusers {
+            sqlx::query(
+                r#"
+                INSERT INTO server_subusers (server_uuid, user_uuid, permissions, ignored_files)
+                SELECT $2, server_subusers.user_uuid, server_subusers.permissions, server_subusers.ignored_files
+                FROM server_subusers
+                WHERE server_subusers.server_uuid = $1 AND server_subusers.user_uuid != $3
+                "#,
+            )
+            .bind(self.uuid)
+            .bind(uuid)
+            .bind(options.owner_uuid)
+            .execute(database.write())
+            .await?;
+        }
+
+        if options.databases {
+            let clone = Self::by_uuid(database, uuid)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound)?;
+
+            for server_database in
+                super::server_database::ServerDatabase::all_by_server_uuid(database, self.uuid)
+                    .await?
+            {
+                // names are prefixed with the id of the server they belong to
+                let name = server_database
+                    .name
+                    .split_once('_')
+                    .map_or(server_database.name.as_str(), |(_, name)| name);
+
+                super::server_database::ServerDatabase::create(
+                    database,
+                    &clone,
+                    &server_database.database_host,
+                    name,
+                )
+                .await?;
+            }
+        }
+
+        let backup_uuid = match options.backup_uuid {
+            Some(backup_uuid) => backup_uuid,
+            None => {
+                super::server_backup::ServerBackup::create(
+                    database,
+                    self.clone(),
+                    &format!("Clone to {}", options.name),
+                    Vec::new(),
+                )
+                .await?
+            }
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE servers
+            SET status = 'RESTORING_BACKUP', restoring_backup_uuid = $2
+            WHERE servers.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .bind(backup_uuid)
+        .execute(database.write())
+        .await?;
+
+        if options.backup_uuid.is_some() {
+            crate::jobs::enqueue(
+                database,
+                &crate::jobs::server::RestoreServerBackup {
+                    server_uuid: uuid,
+                    backup_uuid,
+                },
+            )
+            .await?;
+        }
+        crate::jobs::enqueue(
+            database,
+            &crate::jobs::server::SyncServer { server_uuid: uuid },
+        )
+        .await?;
+
+        Ok(backup_uuid)
+    }
+
     pub async fn by_uuid(
         database: &crate::database::Database,
         uuid: uuid::Uuid,
@@ -880,6 +1213,27 @@ impl Server {
         .unwrap_or(0)
     }
 
+    #[inline]
+    pub fn limits(&self) -> ApiServerLimits {
+        ApiServerLimits {
+            cpu: self.cpu,
+            memory: self.memory,
+            swap: self.swap,
+            disk: self.disk,
+            io_weight: self.io_weight,
+        }
+    }
+
+    #[inline]
+    pub fn feature_limits(&self) -> ApiServerFeatureLimits {
+        ApiServerFeatureLimits {
+            allocations: self.allocation_limit,
+            databases: self.database_limit,
+            backups: self.backup_limit,
+            schedules: self.schedule_limit,
+        }
+    }
+
     pub async fn sync(self, database: &crate::database::Database) -> Result<(), anyhow::Error> {
         match self
             .node
@@ -910,6 +1264,44 @@ impl Server {
         }
     }
 
+    /// Cancels the transfer of the server and releases the allocations it reserved on
+    /// the destination node.
+    pub async fn cancel_transfer(
+        &self,
+        database: &crate::database::Database,
+    ) -> Result<(), sqlx::Error> {
+        let mut transaction = database.write().begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE servers
+            SET destination_allocation_uuid = NULL, destination_node_uuid = NULL
+            WHERE servers.uuid = $1
+            "#,
+        )
+        .bind(self.uuid)
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM server_allocations
+            USING node_allocations
+            WHERE node_allocations.uuid = server_allocations.allocation_uuid
+                AND server_allocations.server_uuid = $1
+                AND node_allocations.node_uuid != $2
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(self.node.uuid)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
     pub async fn delete(
         &self,
         database: &crate::database::Database,
@@ -1028,6 +1420,82 @@ impl Server {
         None
     }
 
+    /// The retention rules of the server, falling back to its backup configuration.
+    pub fn backup_retention(&self) -> super::backup_retention::BackupRetention {
+        match self.backup_retention {
+            Some(backup_retention) => backup_retention,
+            None => self
+                .backup_configuration()
+                .map(|backup_configuration| backup_configuration.retention)
+                .unwrap_or_default(),
+        }
+    }
+
+    pub async fn update_backup_retention(
+        &self,
+        database: &crate::database::Database,
+        backup_retention: Option<super::backup_retention::BackupRetention>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE servers
+            SET backup_retention = $2
+            WHERE servers.uuid = $1
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(
+            backup_retention
+                .map(|backup_retention| serde_json::to_value(backup_retention).unwrap()),
+        )
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_egg_revision(
+        &self,
+        database: &crate::database::Database,
+        egg_revision_uuid: Option<uuid::Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE servers
+            SET egg_revision_uuid = $2
+            WHERE servers.uuid = $1
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(egg_revision_uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replaces the egg configuration with the revision the server is pinned to, if any.
+    pub async fn apply_egg_revision(
+        &mut self,
+        database: &crate::database::Database,
+    ) -> Result<(), sqlx::Error> {
+        let Some(egg_revision_uuid) = self.egg_revision_uuid else {
+            return Ok(());
+        };
+
+        if let Some(revision) = super::nest_egg_revision::NestEggRevision::by_egg_uuid_uuid(
+            database,
+            self.egg.uuid,
+            egg_revision_uuid,
+        )
+        .await?
+        {
+            revision.apply(&mut self.egg);
+        }
+
+        Ok(())
+    }
+
     pub fn is_ignored(&mut self, path: &str, is_dir: bool) -> bool {
         if let Some(ignored_files) = &self.subuser_ignored_files {
             if let Some(overrides) = &self.subuser_ignored_files_overrides {
@@ -1053,9 +1521,11 @@ impl Server {
 
     #[inline]
     pub async fn into_remote_api_object(
-        self,
+        mut self,
         database: &crate::database::Database,
     ) -> Result<RemoteApiServer, anyhow::Error> {
+        self.apply_egg_revision(database).await?;
+
         let (variables, backups, schedules, mounts, allocations) = tokio::try_join!(
             sqlx::query!(
                 "SELECT nest_egg_variables.env_variable, COALESCE(server_variables.value, nest_egg_variables.default_value) AS value
@@ -1122,6 +1592,38 @@ impl Server {
             schedule_steps.insert(schedules[i].uuid, steps);
         }
 
+        let encrypted_variables: Vec<(String, Vec<u8>)> = sqlx::query_as(
+            r#"
+            SELECT nest_egg_variables.env_variable, server_variables.encrypted_value
+            FROM server_variables
+            JOIN nest_egg_variables ON nest_egg_variables.uuid = server_variables.variable_uuid
+            WHERE server_variables.server_uuid = $1 AND nest_egg_variables.egg_uuid = $2
+                AND server_variables.encrypted_value IS NOT NULL
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(self.egg.uuid)
+        .fetch_all(database.read())
+        .await?;
+        let mut encrypted_variables: HashMap<String, Vec<u8>> =
+            encrypted_variables.into_iter().collect();
+
+        let environment = variables
+            .into_iter()
+            .map(|v| {
+                let value = match encrypted_variables.remove(&v.env_variable) {
+                    Some(encrypted_value) => {
+                        database.decrypt(encrypted_value).ok_or_else(|| {
+                            anyhow::anyhow!("failed to decrypt variable {}", v.env_variable)
+                        })?
+                    }
+                    None => v.value.unwrap_or_default(),
+                };
+
+                Ok((v.env_variable, serde_json::Value::String(value)))
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+
         Ok(RemoteApiServer {
             settings: wings_api::ServerConfiguration {
                 uuid: self.uuid,
@@ -1133,15 +1635,7 @@ impl Server {
                 suspended: self.suspended,
                 invocation: self.startup,
                 skip_egg_scripts: false,
-                environment: variables
-                    .into_iter()
-                    .map(|v| {
-                        (
-                            v.env_variable,
-                            serde_json::Value::String(v.value.unwrap_or_default()),
-                        )
-                    })
-                    .collect(),
+                environment,
                 labels: IndexMap::new(),
                 backups: backups.into_iter().map(|b| b.uuid).collect(),
                 schedules: schedules
@@ -1253,10 +1747,12 @@ impl Server {
             node: self.node.into_admin_api_object(database),
             owner: self.owner.into_api_full_object(storage_url_retriever),
             egg: self.egg.into_admin_api_object(),
+            egg_revision_uuid: self.egg_revision_uuid,
             nest: self.nest.into_admin_api_object(),
             backup_configuration: self
                 .backup_configuration
                 .map(|backup_configuration| backup_configuration.into_admin_api_object(database)),
+            server_template_uuid: self.server_template_uuid,
             status: self.status,
             suspended: self.suspended,
             name: self.name,
@@ -1275,6 +1771,7 @@ impl Server {
                 backups: self.backup_limit,
                 schedules: self.schedule_limit,
             },
+            backup_retention: self.backup_retention,
             startup: self.startup,
             image: self.image,
             auto_kill: self.auto_kill,
@@ -1389,8 +1886,10 @@ pub struct AdminApiServer {
     pub node: super::node::AdminApiNode,
     pub owner: super::user::ApiFullUser,
     pub egg: super::nest_egg::AdminApiNestEgg,
+    pub egg_revision_uuid: Option<uuid::Uuid>,
     pub nest: super::nest::AdminApiNest,
     pub backup_configuration: Option<super::backup_configurations::AdminApiBackupConfiguration>,
+    pub server_template_uuid: Option<uuid::Uuid>,
 
     pub status: Option<ServerStatus>,
     pub suspended: bool,
@@ -1403,6 +1902,7 @@ pub struct AdminApiServer {
     pub pinned_cpus: Vec<i16>,
     #[schema(inline)]
     pub feature_limits: ApiServerFeatureLimits,
+    pub backup_retention: Option<super::backup_retention::BackupRetention>,
 
     pub startup: String,
     pub image: String,
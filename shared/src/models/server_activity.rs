@@ -81,10 +81,26 @@ impl ServerActivity {
         .bind(api_key_uuid)
         .bind(event)
         .bind(ip)
-        .bind(data)
+        .bind(&data)
         .execute(database.write())
         .await?;
 
+        super::webhook::Webhook::trigger(
+            database,
+            event,
+            serde_json::json!({
+                "type": "server",
+                "event": event,
+                "server_uuid": server_uuid,
+                "user_uuid": user_uuid,
+                "api_key_uuid": api_key_uuid,
+                "ip": ip.map(|ip| ip.ip()),
+                "data": data,
+                "timestamp": chrono::Utc::now(),
+            }),
+        )
+        .await;
+
         Ok(())
     }
 
@@ -108,11 +124,82 @@ impl ServerActivity {
         .bind(user_uuid)
         .bind(event)
         .bind(ip)
-        .bind(data)
+        .bind(&data)
         .bind(timestamp.naive_utc())
         .execute(database.write())
         .await?;
 
+        super::webhook::Webhook::trigger(
+            database,
+            event,
+            serde_json::json!({
+                "type": "server",
+                "event": event,
+                "server_uuid": server_uuid,
+                "user_uuid": user_uuid,
+                "api_key_uuid": null,
+                "ip": ip.map(|ip| ip.ip()),
+                "data": data,
+                "timestamp": timestamp,
+            }),
+        )
+        .await;
+
+        Self::notify_remote(database, server_uuid, event, data).await?;
+
+        Ok(())
+    }
+
+    /// Notifies the owner about events reported by wings that need their attention.
+    async fn notify_remote(
+        database: &crate::database::Database,
+        server_uuid: uuid::Uuid,
+        event: &str,
+        data: serde_json::Value,
+    ) -> Result<(), sqlx::Error> {
+        use super::user_notification::{Notification, NotificationCategory, UserNotification};
+        use crate::mail::template::MailTemplateKind;
+
+        if !matches!(event, "server:crash" | "server:install.fail") {
+            return Ok(());
+        }
+
+        let row = match sqlx::query(
+            "SELECT servers.owner_uuid, servers.name FROM servers WHERE servers.uuid = $1",
+        )
+        .bind(server_uuid)
+        .fetch_optional(database.read())
+        .await?
+        {
+            Some(row) => row,
+            None => return Ok(()),
+        };
+        let name: String = row.get("name");
+
+        let notification = if event == "server:crash" {
+            Notification {
+                category: NotificationCategory::Server,
+                event: "server:crash",
+                server_uuid: Some(server_uuid),
+                title: "Server crashed".into(),
+                body: format!("Your server {name} has crashed."),
+                data,
+                mail: None,
+            }
+        } else {
+            Notification {
+                category: NotificationCategory::Server,
+                event: "server:install.fail",
+                server_uuid: Some(server_uuid),
+                title: "Server installation failed".into(),
+                body: format!("The installation of your server {name} has failed."),
+                data,
+                mail: Some((MailTemplateKind::ServerInstallFailed, BTreeMap::new())),
+            }
+        };
+
+        UserNotification::notify(database, row.get("owner_uuid"), notification).await;
+
         Ok(())
     }
 
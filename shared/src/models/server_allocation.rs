@@ -26,7 +26,7 @@ impl BaseModel for ServerAllocation {
         ]);
 
         columns.extend(super::node_allocation::NodeAllocation::columns(Some(
-            "allocation_",
+            &format!("{prefix}allocation_"),
         )));
 
         columns
@@ -38,7 +38,10 @@ impl BaseModel for ServerAllocation {
 
         Self {
             uuid: row.get(format!("{prefix}uuid").as_str()),
-            allocation: super::node_allocation::NodeAllocation::map(Some("allocation_"), row),
+            allocation: super::node_allocation::NodeAllocation::map(
+                Some(&format!("{prefix}allocation_")),
+                row,
+            ),
             notes: row.get(format!("{prefix}notes").as_str()),
             created: row.get(format!("{prefix}created").as_str()),
         }
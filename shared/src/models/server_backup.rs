@@ -30,6 +30,42 @@ impl BackupDisk {
             BackupDisk::Restic => wings_api::BackupAdapter::Restic,
         }
     }
+
+    /// Whether backups on this disk can be restored on any node.
+    #[inline]
+    pub fn is_portable(self) -> bool {
+        matches!(
+            self,
+            BackupDisk::Local | BackupDisk::S3 | BackupDisk::Restic
+        )
+    }
+}
+
+#[derive(ToSchema, Serialize, Deserialize, Type, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[schema(rename_all = "snake_case")]
+#[sqlx(
+    type_name = "backup_verification_status",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum BackupVerificationStatus {
+    Pending,
+    Successful,
+    Failed,
+}
+
+/// The outcome of [`ServerBackup::restore_into`].
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum BackupRestoreIntoStatus {
+    Started,
+    /// The server is being transferred to another node.
+    ServerTransferring,
+    /// The backup is stored on another node and cannot be streamed to the node of the server.
+    NotRestorable,
+    /// The server is installing, restoring or otherwise busy.
+    ServerBusy,
+    /// The backup is already being restored into another server on the same node.
+    BackupBusy,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -52,7 +88,11 @@ pub struct ServerBackup {
     pub upload_id: Option<String>,
     pub upload_path: Option<String>,
 
+    pub verification_status: Option<BackupVerificationStatus>,
+    pub verification_error: Option<String>,
+
     pub completed: Option<chrono::NaiveDateTime>,
+    pub verified: Option<chrono::NaiveDateTime>,
     pub deleted: Option<chrono::NaiveDateTime>,
     pub created: chrono::NaiveDateTime,
 }
@@ -79,7 +119,16 @@ impl BaseModel for ServerBackup {
             ("server_backups.disk", format!("{prefix}disk")),
             ("server_backups.upload_id", format!("{prefix}upload_id")),
             ("server_backups.upload_path", format!("{prefix}upload_path")),
+            (
+                "server_backups.verification_status",
+                format!("{prefix}verification_status"),
+            ),
+            (
+                "server_backups.verification_error",
+                format!("{prefix}verification_error"),
+            ),
             ("server_backups.completed", format!("{prefix}completed")),
+            ("server_backups.verified", format!("{prefix}verified")),
             ("server_backups.deleted", format!("{prefix}deleted")),
             ("server_backups.created", format!("{prefix}created")),
         ]);
@@ -122,7 +171,10 @@ impl BaseModel for ServerBackup {
             disk: row.get(format!("{prefix}disk").as_str()),
             upload_id: row.get(format!("{prefix}upload_id").as_str()),
             upload_path: row.get(format!("{prefix}upload_path").as_str()),
+            verification_status: row.get(format!("{prefix}verification_status").as_str()),
+            verification_error: row.get(format!("{prefix}verification_error").as_str()),
             completed: row.get(format!("{prefix}completed").as_str()),
+            verified: row.get(format!("{prefix}verified").as_str()),
             deleted: row.get(format!("{prefix}deleted").as_str()),
             created: row.get(format!("{prefix}created").as_str()),
         }
@@ -142,12 +194,13 @@ impl ServerBackup {
 
         let row = sqlx::query(
             r#"
-            INSERT INTO server_backups (server_uuid, node_uuid, name, ignored_files, bytes, disk) VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO server_backups (server_uuid, node_uuid, backup_configuration_uuid, name, ignored_files, bytes, disk) VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING uuid
             "#
         )
         .bind(server.uuid)
         .bind(server.node.uuid)
+        .bind(backup_configuration.uuid)
         .bind(name)
         .bind(&ignored_files)
         .bind(0i64)
@@ -155,47 +208,20 @@ impl ServerBackup {
         .fetch_one(database.write())
         .await?;
 
-        tokio::spawn({
-            let uuid = row.get::<uuid::Uuid, _>("uuid");
-            let backup_disk = backup_configuration.backup_disk;
-            let database = Arc::clone(database);
-
-            async move {
-                tracing::debug!(backup = %uuid, "creating server backup");
-
-                if let Err(err) = server
-                    .node
-                    .api_client(&database)
-                    .post_servers_server_backup(
-                        server.uuid,
-                        &wings_api::servers_server_backup::post::RequestBody {
-                            adapter: backup_disk.to_wings_adapter(),
-                            uuid,
-                            ignore: ignored_files.join("\n"),
-                        },
-                    )
-                    .await
-                {
-                    tracing::error!(backup = %uuid, "failed to create server backup: {:#?}", err);
-
-                    if let Err(err) = sqlx::query!(
-                        r#"
-                        UPDATE server_backups
-                        SET successful = false, completed = NOW()
-                        WHERE server_backups.uuid = $1
-                        "#,
-                        uuid
-                    )
-                    .execute(database.write())
-                    .await
-                    {
-                        tracing::error!(backup = %uuid, "failed to update server backup status: {:#?}", err);
-                    }
-                }
-            }
-        });
+        let uuid = row.get("uuid");
 
-        Ok(row.get("uuid"))
+        crate::jobs::enqueue(
+            database,
+            &crate::jobs::server::CreateServerBackup {
+                server_uuid: server.uuid,
+                backup_uuid: uuid,
+                disk: backup_configuration.backup_disk,
+                ignored_files,
+            },
+        )
+        .await?;
+
+        Ok(uuid)
     }
 
     pub async fn create_raw(
@@ -210,12 +236,13 @@ impl ServerBackup {
 
         let row = sqlx::query(
             r#"
-            INSERT INTO server_backups (server_uuid, node_uuid, name, ignored_files, bytes, disk) VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO server_backups (server_uuid, node_uuid, backup_configuration_uuid, name, ignored_files, bytes, disk) VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING uuid
             "#
         )
         .bind(server.uuid)
         .bind(server.node.uuid)
+        .bind(backup_configuration.uuid)
         .bind(name)
         .bind(&ignored_files)
         .bind(0i64)
@@ -235,7 +262,7 @@ impl ServerBackup {
             SELECT {}
             FROM server_backups
             LEFT JOIN backup_configurations ON backup_configurations.uuid = server_backups.backup_configuration_uuid
-            WHERE AND server_backups.uuid = $1
+            WHERE server_backups.uuid = $1
             "#,
             Self::columns_sql(None)
         ))
@@ -290,6 +317,36 @@ impl ServerBackup {
         Ok(row.map(|row| Self::map(None, &row)))
     }
 
+    /// Like [`Self::by_node_uuid_uuid`], but also returns backups of other nodes that are
+    /// currently being restored into a server on the node.
+    pub async fn by_node_uuid_uuid_or_restoring(
+        database: &crate::database::Database,
+        node_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM server_backups
+            LEFT JOIN backup_configurations ON backup_configurations.uuid = server_backups.backup_configuration_uuid
+            WHERE server_backups.uuid = $2 AND (
+                server_backups.node_uuid = $1
+                OR EXISTS (
+                    SELECT 1 FROM servers
+                    WHERE servers.node_uuid = $1 AND servers.restoring_backup_uuid = server_backups.uuid
+                )
+            )
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(node_uuid)
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
     pub async fn by_server_uuid_with_pagination(
         database: &crate::database::Database,
         server_uuid: uuid::Uuid,
@@ -404,9 +461,107 @@ impl ServerBackup {
         .unwrap_or(0)
     }
 
+    /// Whether wings on `node` is able to restore this backup. Backups stored on a
+    /// node can only be moved to another node as a plain archive.
+    #[inline]
+    pub fn is_restorable_on(&self, node: &super::node::Node) -> bool {
+        self.node_uuid == node.uuid || self.disk.is_portable()
+    }
+
+    /// Puts the server into the restoring state for this backup.
+    ///
+    /// The completion callback of wings only names the backup, so a backup is restored
+    /// into one server of a node at a time, enforced by a unique index.
+    pub async fn start_restoring(
+        &self,
+        database: &crate::database::Database,
+        server_uuid: uuid::Uuid,
+    ) -> Result<BackupRestoreIntoStatus, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE servers
+            SET status = 'RESTORING_BACKUP', restoring_backup_uuid = $2
+            WHERE servers.uuid = $1 AND servers.status IS NULL
+            "#,
+        )
+        .bind(server_uuid)
+        .bind(self.uuid)
+        .execute(database.write())
+        .await;
+
+        match result {
+            Ok(result) if result.rows_affected() == 0 => Ok(BackupRestoreIntoStatus::ServerBusy),
+            Ok(_) => Ok(BackupRestoreIntoStatus::Started),
+            Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+                Ok(BackupRestoreIntoStatus::BackupBusy)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Releases a server that is still restoring the given backup.
+    pub async fn stop_restoring(
+        database: &crate::database::Database,
+        server_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE servers
+            SET status = NULL, restoring_backup_uuid = NULL
+            WHERE servers.uuid = $1 AND servers.restoring_backup_uuid = $2
+            "#,
+        )
+        .bind(server_uuid)
+        .bind(uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Restores the backup into a server, which does not have to be the one that created
+    /// it. The restoring state is committed before wings is asked to restore, so its
+    /// completion callback always finds it, and undone if the restore cannot be started.
+    pub async fn restore_into(
+        self,
+        database: &crate::database::Database,
+        jwt: &crate::jwt::Jwt,
+        server: super::server::Server,
+        truncate_directory: bool,
+    ) -> Result<BackupRestoreIntoStatus, anyhow::Error> {
+        if server.destination_node_uuid.is_some() {
+            return Ok(BackupRestoreIntoStatus::ServerTransferring);
+        }
+
+        if !self.is_restorable_on(&server.node) {
+            return Ok(BackupRestoreIntoStatus::NotRestorable);
+        }
+
+        let status = self.start_restoring(database, server.uuid).await?;
+        if !matches!(status, BackupRestoreIntoStatus::Started) {
+            return Ok(status);
+        }
+
+        let (server_uuid, uuid) = (server.uuid, self.uuid);
+        if let Err(err) = self
+            .restore(database, jwt, server, truncate_directory)
+            .await
+        {
+            Self::stop_restoring(database, server_uuid, uuid).await?;
+
+            return Err(err);
+        }
+
+        Ok(BackupRestoreIntoStatus::Started)
+    }
+
+    /// Restores the backup into `server`, which does not have to be the server that
+    /// created it. Local backups of another node are streamed from that node.
     pub async fn restore(
         self,
         database: &crate::database::Database,
+        jwt: &crate::jwt::Jwt,
         server: super::server::Server,
         truncate_directory: bool,
     ) -> Result<(), anyhow::Error> {
@@ -414,6 +569,74 @@ impl ServerBackup {
             anyhow::anyhow!("no backup configuration available, unable to create backup")
         })?;
 
+        let (adapter, download_url) = match self.disk {
+            BackupDisk::S3 => {
+                if let Some(mut s3_configuration) = backup_configuration.backup_configs.s3 {
+                    s3_configuration.decrypt(database);
+
+                    let client = s3_configuration.into_client()?;
+                    let file_path = match self.upload_path.as_ref() {
+                        Some(path) => path,
+                        None => &Self::s3_path(self.server_uuid.unwrap_or(server.uuid), self.uuid),
+                    };
+
+                    (
+                        wings_api::BackupAdapter::S3,
+                        Some(client.presign_get(file_path, 60 * 60, None).await?),
+                    )
+                } else {
+                    (wings_api::BackupAdapter::S3, None)
+                }
+            }
+            BackupDisk::Local if self.node_uuid != server.node.uuid => {
+                #[derive(Serialize)]
+                struct BackupDownloadJwt {
+                    #[serde(flatten)]
+                    base: crate::jwt::BasePayload,
+
+                    backup_uuid: uuid::Uuid,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    server_uuid: Option<uuid::Uuid>,
+                    unique_id: uuid::Uuid,
+                }
+
+                let node = super::node::Node::by_uuid(database, self.node_uuid)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("node of the backup not found"))?;
+
+                let token = node.create_jwt(
+                    database,
+                    jwt,
+                    &BackupDownloadJwt {
+                        base: crate::jwt::BasePayload {
+                            issuer: "panel".into(),
+                            subject: None,
+                            audience: Vec::new(),
+                            expiration_time: Some(chrono::Utc::now().timestamp() + 60 * 60),
+                            not_before: None,
+                            issued_at: Some(chrono::Utc::now().timestamp()),
+                            jwt_id: server.uuid.to_string(),
+                        },
+                        backup_uuid: self.uuid,
+                        server_uuid: self.server_uuid,
+                        unique_id: uuid::Uuid::new_v4(),
+                    },
+                )?;
+
+                let mut url = node.public_url();
+                url.set_path("/download/backup");
+                url.set_query(Some(&format!(
+                    "token={}&archive_format={}",
+                    urlencoding::encode(&token),
+                    wings_api::StreamableArchiveFormat::TarGz
+                )));
+
+                // wings downloads plain archives through the s3 adapter
+                (wings_api::BackupAdapter::S3, Some(url.to_string()))
+            }
+            disk => (disk.to_wings_adapter(), None),
+        };
+
         if let Err((status, error)) = server
             .node
             .api_client(database)
@@ -421,27 +644,8 @@ impl ServerBackup {
                 server.uuid,
                 self.uuid,
                 &wings_api::servers_server_backup_backup_restore::post::RequestBody {
-                    adapter: self.disk.to_wings_adapter(),
-                    download_url: match self.disk {
-                        BackupDisk::S3 => {
-                            if let Some(mut s3_configuration) =
-                                backup_configuration.backup_configs.s3
-                            {
-                                s3_configuration.decrypt(database);
-
-                                let client = s3_configuration.into_client()?;
-                                let file_path = match self.upload_path.as_ref() {
-                                    Some(path) => path,
-                                    None => &Self::s3_path(server.uuid, self.uuid),
-                                };
-
-                                Some(client.presign_get(file_path, 60 * 60, None).await?)
-                            } else {
-                                None
-                            }
-                        }
-                        _ => None,
-                    },
+                    adapter,
+                    download_url,
                     truncate_directory,
                 },
             )
@@ -453,6 +657,231 @@ impl ServerBackup {
         Ok(())
     }
 
+    /// Starts restoring the backup into the servers that wait for it to complete, see
+    /// [`super::server::Server::create_clone`]. If the backup failed they are released
+    /// without their files instead.
+    pub async fn resume_pending_restores(
+        database: &Arc<crate::database::Database>,
+        uuid: uuid::Uuid,
+        successful: bool,
+    ) -> Result<(), anyhow::Error> {
+        if !successful {
+            sqlx::query(
+                r#"
+                UPDATE servers
+                SET status = NULL, restoring_backup_uuid = NULL
+                WHERE servers.restoring_backup_uuid = $1 AND servers.status = 'RESTORING_BACKUP'
+                "#,
+            )
+            .bind(uuid)
+            .execute(database.write())
+            .await?;
+
+            return Ok(());
+        }
+
+        let server_uuids: Vec<uuid::Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT servers.uuid
+            FROM servers
+            WHERE servers.restoring_backup_uuid = $1 AND servers.status = 'RESTORING_BACKUP'
+            "#,
+        )
+        .bind(uuid)
+        .fetch_all(database.read())
+        .await?;
+
+        for server_uuid in server_uuids {
+            crate::jobs::enqueue(
+                database,
+                &crate::jobs::server::RestoreServerBackup {
+                    server_uuid,
+                    backup_uuid: uuid,
+                },
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Only S3 backups can be verified, wings has no endpoint to check restic repositories yet.
+    #[inline]
+    pub fn is_verifiable(&self) -> bool {
+        matches!(self.disk, BackupDisk::S3)
+    }
+
+    /// Checks the stored backup for corruption. S3 objects are downloaded and hashed against
+    /// the recorded checksum.
+    ///
+    /// Returns the reason if the backup failed verification, an error means that the
+    /// verification itself could not be run.
+    pub async fn verify(
+        &self,
+        database: &crate::database::Database,
+        server: &super::server::Server,
+    ) -> Result<Option<String>, anyhow::Error> {
+        match self.disk {
+            BackupDisk::S3 => {
+                let (checksum_type, checksum) =
+                    match self.checksum.as_ref().and_then(|c| c.split_once(':')) {
+                        Some(checksum) => checksum,
+                        None => return Ok(Some("backup does not have a checksum".to_string())),
+                    };
+
+                let mut s3_configuration = self
+                    .backup_configuration
+                    .as_ref()
+                    .and_then(|backup_configuration| backup_configuration.backup_configs.s3.clone())
+                    .ok_or_else(|| anyhow::anyhow!("S3 configuration not found"))?;
+                s3_configuration.decrypt(database);
+
+                let client = s3_configuration.into_client()?;
+                let file_path = match self.upload_path.as_ref() {
+                    Some(path) => path,
+                    None => &Self::s3_path(server.uuid, self.uuid),
+                };
+
+                let mut response = match client.get_object_stream(file_path).await {
+                    Ok(response) if response.status_code == 404 => {
+                        return Ok(Some("backup does not exist on S3".to_string()));
+                    }
+                    Ok(response) if !(200..300).contains(&response.status_code) => {
+                        return Err(anyhow::anyhow!(
+                            "unexpected status code {} from S3",
+                            response.status_code
+                        ));
+                    }
+                    Ok(response) => response,
+                    Err(s3::error::S3Error::HttpFailWithBody(404, _)) => {
+                        return Ok(Some("backup does not exist on S3".to_string()));
+                    }
+                    Err(err) => return Err(err.into()),
+                };
+
+                let computed = match checksum_type {
+                    "sha1" => Self::hash_stream::<sha1::Sha1>(response.bytes()).await?,
+                    "sha256" => Self::hash_stream::<sha2::Sha256>(response.bytes()).await?,
+                    checksum_type => {
+                        return Ok(Some(format!("unsupported checksum type {checksum_type}")));
+                    }
+                };
+
+                if computed == checksum {
+                    Ok(None)
+                } else {
+                    Ok(Some(format!(
+                        "checksum mismatch, expected {checksum} but got {computed}"
+                    )))
+                }
+            }
+            _ => Err(anyhow::anyhow!(
+                "verification is not supported for this backup disk"
+            )),
+        }
+    }
+
+    async fn hash_stream<D: sha2::Digest>(
+        stream: &mut s3::request::DataStream,
+    ) -> Result<String, s3::error::S3Error> {
+        use futures_util::StreamExt;
+
+        let mut hasher = D::new();
+        while let Some(chunk) = stream.next().await {
+            hasher.update(chunk?);
+        }
+
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect())
+    }
+
+    /// Marks the backup as being verified and queues the verification in one transaction.
+    /// Returns `false` without queueing anything if a verification is already pending.
+    pub async fn queue_verification(
+        database: &crate::database::Database,
+        server_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let mut transaction = database.write().begin().await?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE server_backups
+            SET verification_status = 'PENDING', verification_error = NULL
+            WHERE server_backups.uuid = $1
+                AND server_backups.verification_status IS DISTINCT FROM 'PENDING'
+            "#,
+        )
+        .bind(uuid)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Ok(false);
+        }
+
+        crate::jobs::enqueue_with(
+            &mut *transaction,
+            &crate::jobs::server::VerifyServerBackup {
+                server_uuid,
+                backup_uuid: uuid,
+            },
+            std::time::Duration::ZERO,
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(true)
+    }
+
+    pub async fn update_verification(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+        status: BackupVerificationStatus,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE server_backups
+            SET
+                verification_status = $2,
+                verification_error = $3,
+                verified = CASE WHEN $2 = 'PENDING' THEN server_backups.verified ELSE NOW() END
+            WHERE server_backups.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .bind(status)
+        .bind(error)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Clears a pending verification that cannot run anymore.
+    pub async fn reset_verification(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE server_backups
+            SET verification_status = NULL, verification_error = NULL
+            WHERE server_backups.uuid = $1 AND server_backups.verification_status = 'PENDING'
+            "#,
+        )
+        .bind(uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn delete(
         self,
         database: &crate::database::Database,
@@ -510,7 +939,7 @@ impl ServerBackup {
         sqlx::query(
             r#"
             UPDATE server_backups
-            SET deleted = NOW()
+            SET deleted = COALESCE(server_backups.deleted, NOW())
             WHERE server_backups.uuid = $1
             "#,
         )
@@ -521,6 +950,41 @@ impl ServerBackup {
         Ok(())
     }
 
+    /// Marks the backup as deleted and queues the removal of its archive in one transaction.
+    /// Returns `false` without queueing anything if the backup was deleted already.
+    pub async fn queue_deletion(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let mut transaction = database.write().begin().await?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE server_backups
+            SET deleted = NOW()
+            WHERE server_backups.uuid = $1 AND server_backups.deleted IS NULL
+            "#,
+        )
+        .bind(uuid)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Ok(false);
+        }
+
+        crate::jobs::enqueue_with(
+            &mut *transaction,
+            &crate::jobs::server::DeleteServerBackup { backup_uuid: uuid },
+            std::time::Duration::ZERO,
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(true)
+    }
+
     pub async fn delete_detached(
         self,
         database: &crate::database::Database,
@@ -572,7 +1036,7 @@ impl ServerBackup {
         sqlx::query(
             r#"
             UPDATE server_backups
-            SET deleted = NOW()
+            SET deleted = COALESCE(server_backups.deleted, NOW())
             WHERE server_backups.uuid = $1
             "#,
         )
@@ -613,6 +1077,55 @@ impl ServerBackup {
         }
     }
 
+    /// Returns the backups of a server that its retention rules no longer cover.
+    pub async fn expired_by_server(
+        database: &crate::database::Database,
+        server: &super::server::Server,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let retention = server.backup_retention();
+        if retention.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let backups = Self::all_by_server_uuid(database, server.uuid).await?;
+
+        Ok(retention.expired(backups, chrono::Utc::now().naive_utc()))
+    }
+
+    /// Queues the deletion of the expired backups of every server and returns how many
+    /// were queued.
+    pub async fn prune(database: &crate::database::Database) -> Result<usize, anyhow::Error> {
+        let server_uuids: Vec<uuid::Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT server_backups.server_uuid
+            FROM server_backups
+            WHERE server_backups.server_uuid IS NOT NULL
+                AND server_backups.locked = false
+                AND server_backups.completed IS NOT NULL
+                AND server_backups.deleted IS NULL
+            "#,
+        )
+        .fetch_all(database.read())
+        .await?;
+
+        let mut queued = 0;
+
+        for server_uuid in server_uuids {
+            let server = match super::server::Server::by_uuid(database, server_uuid).await? {
+                Some(server) => server,
+                None => continue,
+            };
+
+            for backup in Self::expired_by_server(database, &server).await? {
+                if Self::queue_deletion(database, backup.uuid).await? {
+                    queued += 1;
+                }
+            }
+        }
+
+        Ok(queued)
+    }
+
     #[inline]
     pub fn s3_path(server_uuid: uuid::Uuid, backup_uuid: uuid::Uuid) -> String {
         format!("{server_uuid}/{backup_uuid}.tar.gz")
@@ -646,7 +1159,10 @@ impl ServerBackup {
             checksum: self.checksum,
             bytes: self.bytes,
             files: self.files,
+            verification_status: self.verification_status,
+            verification_error: self.verification_error,
             completed: self.completed.map(|dt| dt.and_utc()),
+            verified: self.verified.map(|dt| dt.and_utc()),
             created: self.created.and_utc(),
         }
     }
@@ -669,6 +1185,10 @@ pub struct ApiServerBackup {
     pub bytes: i64,
     pub files: i64,
 
+    pub verification_status: Option<BackupVerificationStatus>,
+    pub verification_error: Option<String>,
+
     pub completed: Option<chrono::DateTime<chrono::Utc>>,
+    pub verified: Option<chrono::DateTime<chrono::Utc>>,
     pub created: chrono::DateTime<chrono::Utc>,
 }
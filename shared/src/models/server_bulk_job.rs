@@ -0,0 +1,755 @@
+use super::{
+    BaseModel,
+    user_notification::{Notification, UserNotification},
+};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, postgres::PgRow};
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+#[derive(ToSchema, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[schema(title = "ServerBulkAction")]
+pub enum ServerBulkAction {
+    Power {
+        signal: wings_api::ServerPowerAction,
+    },
+    Suspend,
+    Unsuspend,
+    Reinstall {
+        #[serde(default)]
+        truncate_directory: bool,
+    },
+    Image {
+        image: String,
+    },
+    Variable {
+        env_variable: String,
+        value: String,
+    },
+    Mount {
+        mount_uuid: uuid::Uuid,
+    },
+    /// A stored action this version no longer understands.
+    #[serde(skip_deserializing)]
+    Unknown,
+}
+
+impl ServerBulkAction {
+    async fn execute(
+        &self,
+        database: &crate::database::Database,
+        server_uuid: uuid::Uuid,
+    ) -> Result<(), anyhow::Error> {
+        let mut server = match super::server::Server::by_uuid(database, server_uuid).await? {
+            Some(server) => server,
+            None => return Err(anyhow::anyhow!("server not found")),
+        };
+
+        match self {
+            Self::Power { signal } => {
+                if let Err((_, err)) = server
+                    .node
+                    .api_client(database)
+                    .post_servers_server_power(
+                        server.uuid,
+                        &wings_api::servers_server_power::post::RequestBody {
+                            action: *signal,
+                            wait_seconds: None,
+                        },
+                    )
+                    .await
+                {
+                    return Err(anyhow::anyhow!(err.error));
+                }
+            }
+            Self::Suspend | Self::Unsuspend => {
+                let was_suspended = server.suspended;
+                server.suspended = matches!(self, Self::Suspend);
+
+                sqlx::query("UPDATE servers SET suspended = $1 WHERE servers.uuid = $2")
+                    .bind(server.suspended)
+                    .bind(server.uuid)
+                    .execute(database.write())
+                    .await?;
+
+                if server.suspended && !was_suspended {
+                    UserNotification::notify(
+                        database,
+                        server.owner.uuid,
+                        Notification::server_suspended(&server),
+                    )
+                    .await;
+                }
+
+                server.sync(database).await?;
+            }
+            Self::Reinstall { truncate_directory } => {
+                let mut transaction = database.write().begin().await?;
+
+                let rows_affected = sqlx::query(
+                    r#"
+                    UPDATE servers
+                    SET status = 'INSTALLING'
+                    WHERE servers.uuid = $1 AND servers.status IS NULL
+                    "#,
+                )
+                .bind(server.uuid)
+                .execute(&mut *transaction)
+                .await?
+                .rows_affected();
+
+                if rows_affected == 0 {
+                    transaction.rollback().await?;
+
+                    return Err(anyhow::anyhow!(
+                        "server is not in a valid state to reinstall"
+                    ));
+                }
+
+                if let Err((_, err)) = server
+                    .node
+                    .api_client(database)
+                    .post_servers_server_reinstall(
+                        server.uuid,
+                        &wings_api::servers_server_reinstall::post::RequestBody {
+                            truncate_directory: *truncate_directory,
+                        },
+                    )
+                    .await
+                {
+                    transaction.rollback().await?;
+
+                    return Err(anyhow::anyhow!(err.error));
+                }
+
+                transaction.commit().await?;
+            }
+            Self::Image { image } => {
+                server.image = image.clone();
+
+                sqlx::query("UPDATE servers SET image = $1 WHERE servers.uuid = $2")
+                    .bind(&server.image)
+                    .bind(server.uuid)
+                    .execute(database.write())
+                    .await?;
+
+                server.sync(database).await?;
+            }
+            Self::Variable {
+                env_variable,
+                value,
+            } => {
+                let variables =
+                    super::server_variable::ServerVariable::all_by_server_uuid_egg_uuid(
+                        database,
+                        server.uuid,
+                        server.egg.uuid,
+                    )
+                    .await?;
+
+                let target = match variables
+                    .iter()
+                    .find(|variable| &variable.variable.env_variable == env_variable)
+                {
+                    Some(variable) => variable.variable.clone(),
+                    None => return Err(anyhow::anyhow!("variable not found")),
+                };
+
+                target
+                    .kind
+                    .validate(value, &target.options)
+                    .map_err(|err| anyhow::anyhow!(err))?;
+
+                let mut validator_variables = HashMap::new();
+                validator_variables.reserve(variables.len());
+
+                for variable in variables {
+                    let value = if &variable.variable.env_variable == env_variable {
+                        value.clone()
+                    } else {
+                        variable.decrypted_value(database)?
+                    };
+
+                    validator_variables.insert(
+                        variable.variable.env_variable,
+                        (variable.variable.rules, value),
+                    );
+                }
+
+                rule_validator::Validator::new(validator_variables)
+                    .and_then(|validator| validator.validate())
+                    .map_err(|err| anyhow::anyhow!(err))?;
+
+                super::server_variable::ServerVariable::set(database, server.uuid, &target, value)
+                    .await?;
+            }
+            Self::Mount { mount_uuid } => {
+                if super::mount::Mount::by_node_uuid_egg_uuid_uuid(
+                    database,
+                    server.node.uuid,
+                    server.egg.uuid,
+                    *mount_uuid,
+                )
+                .await?
+                .is_none()
+                {
+                    return Err(anyhow::anyhow!("mount not available for server"));
+                }
+
+                // attaching a mount that is already attached counts as success
+                match super::server_mount::ServerMount::create(database, server.uuid, *mount_uuid)
+                    .await
+                {
+                    Ok(_) => {}
+                    Err(err) if err.to_string().contains("unique constraint") => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            Self::Unknown => return Err(anyhow::anyhow!("unknown bulk action")),
+        }
+
+        Ok(())
+    }
+}
+
+/// Selects servers for a bulk job, fields that are not set match all servers.
+#[derive(ToSchema, Serialize, Deserialize, Default)]
+pub struct ServerBulkFilter {
+    pub node_uuid: Option<uuid::Uuid>,
+    pub egg_uuid: Option<uuid::Uuid>,
+    pub nest_uuid: Option<uuid::Uuid>,
+    pub owner_uuid: Option<uuid::Uuid>,
+    pub location_uuid: Option<uuid::Uuid>,
+    pub suspended: Option<bool>,
+}
+
+impl ServerBulkFilter {
+    pub async fn server_uuids(
+        &self,
+        database: &crate::database::Database,
+    ) -> Result<Vec<uuid::Uuid>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT servers.uuid
+            FROM servers
+            JOIN nodes ON nodes.uuid = servers.node_uuid
+            JOIN nest_eggs ON nest_eggs.uuid = servers.egg_uuid
+            WHERE ($1 IS NULL OR servers.node_uuid = $1)
+                AND ($2 IS NULL OR servers.egg_uuid = $2)
+                AND ($3 IS NULL OR nest_eggs.nest_uuid = $3)
+                AND ($4 IS NULL OR servers.owner_uuid = $4)
+                AND ($5 IS NULL OR nodes.location_uuid = $5)
+                AND ($6 IS NULL OR servers.suspended = $6)
+            ORDER BY servers.created
+            "#,
+        )
+        .bind(self.node_uuid)
+        .bind(self.egg_uuid)
+        .bind(self.nest_uuid)
+        .bind(self.owner_uuid)
+        .bind(self.location_uuid)
+        .bind(self.suspended)
+        .fetch_all(database.read())
+        .await
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ServerBulkJob {
+    pub uuid: uuid::Uuid,
+    pub user_uuid: Option<uuid::Uuid>,
+
+    pub action: ServerBulkAction,
+    /// The value of a variable action that sets a secret variable, the value of the action is
+    /// left empty then.
+    pub encrypted_value: Option<Vec<u8>>,
+    /// Whether the action sets a secret variable on any of the servers.
+    pub secret: bool,
+    pub concurrency: i32,
+
+    pub total: i64,
+    pub successful: i64,
+    pub failed: i64,
+
+    pub finished: Option<chrono::NaiveDateTime>,
+    pub created: chrono::NaiveDateTime,
+}
+
+impl BaseModel for ServerBulkJob {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            ("server_bulk_jobs.uuid", format!("{prefix}uuid")),
+            ("server_bulk_jobs.user_uuid", format!("{prefix}user_uuid")),
+            ("server_bulk_jobs.action", format!("{prefix}action")),
+            (
+                "server_bulk_jobs.encrypted_value",
+                format!("{prefix}encrypted_value"),
+            ),
+            (
+                r#"EXISTS (
+                    SELECT 1 FROM server_bulk_job_results
+                    JOIN servers ON servers.uuid = server_bulk_job_results.server_uuid
+                    JOIN nest_egg_variables ON nest_egg_variables.egg_uuid = servers.egg_uuid
+                    WHERE server_bulk_job_results.job_uuid = server_bulk_jobs.uuid
+                        AND nest_egg_variables.env_variable = server_bulk_jobs.action->>'env_variable'
+                        AND nest_egg_variables.kind = 'SECRET'
+                )"#,
+                format!("{prefix}secret"),
+            ),
+            (
+                "server_bulk_jobs.concurrency",
+                format!("{prefix}concurrency"),
+            ),
+            (
+                "(SELECT COUNT(*) FROM server_bulk_job_results WHERE server_bulk_job_results.job_uuid = server_bulk_jobs.uuid)",
+                format!("{prefix}total"),
+            ),
+            (
+                "(SELECT COUNT(*) FROM server_bulk_job_results WHERE server_bulk_job_results.job_uuid = server_bulk_jobs.uuid AND server_bulk_job_results.successful)",
+                format!("{prefix}successful"),
+            ),
+            (
+                "(SELECT COUNT(*) FROM server_bulk_job_results WHERE server_bulk_job_results.job_uuid = server_bulk_jobs.uuid AND NOT server_bulk_job_results.successful)",
+                format!("{prefix}failed"),
+            ),
+            ("server_bulk_jobs.finished", format!("{prefix}finished")),
+            ("server_bulk_jobs.created", format!("{prefix}created")),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            uuid: row.get(format!("{prefix}uuid").as_str()),
+            user_uuid: row.get(format!("{prefix}user_uuid").as_str()),
+            action: serde_json::from_value(row.get(format!("{prefix}action").as_str()))
+                .unwrap_or(ServerBulkAction::Unknown),
+            encrypted_value: row.get(format!("{prefix}encrypted_value").as_str()),
+            secret: row.get(format!("{prefix}secret").as_str()),
+            concurrency: row.get(format!("{prefix}concurrency").as_str()),
+            total: row.get(format!("{prefix}total").as_str()),
+            successful: row.get(format!("{prefix}successful").as_str()),
+            failed: row.get(format!("{prefix}failed").as_str()),
+            finished: row.get(format!("{prefix}finished").as_str()),
+            created: row.get(format!("{prefix}created").as_str()),
+        }
+    }
+}
+
+impl ServerBulkJob {
+    /// Creates a job with a pending result for every given server that exists.
+    /// Values of secret variables are stored encrypted.
+    pub async fn create(
+        database: &crate::database::Database,
+        user_uuid: Option<uuid::Uuid>,
+        action: &ServerBulkAction,
+        concurrency: i32,
+        server_uuids: &[uuid::Uuid],
+    ) -> Result<Self, sqlx::Error> {
+        let mut action = action.clone();
+        let mut encrypted_value = None;
+
+        if let ServerBulkAction::Variable {
+            env_variable,
+            value,
+        } = &mut action
+        {
+            let secret: bool = sqlx::query_scalar(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM servers
+                    JOIN nest_egg_variables ON nest_egg_variables.egg_uuid = servers.egg_uuid
+                    WHERE servers.uuid = ANY($1)
+                        AND nest_egg_variables.env_variable = $2
+                        AND nest_egg_variables.kind = 'SECRET'
+                )
+                "#,
+            )
+            .bind(server_uuids)
+            .bind(&*env_variable)
+            .fetch_one(database.read())
+            .await?;
+
+            if secret {
+                encrypted_value =
+                    Some(database.encrypt(std::mem::take(value)).ok_or_else(|| {
+                        sqlx::Error::Encode("failed to encrypt variable value".into())
+                    })?);
+            }
+        }
+
+        let mut transaction = database.write().begin().await?;
+
+        let uuid: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO server_bulk_jobs (user_uuid, action, encrypted_value, concurrency)
+            VALUES ($1, $2, $3, $4)
+            RETURNING server_bulk_jobs.uuid
+            "#,
+        )
+        .bind(user_uuid)
+        .bind(serde_json::to_value(&action).unwrap())
+        .bind(encrypted_value)
+        .bind(concurrency)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO server_bulk_job_results (job_uuid, server_uuid)
+            SELECT $1, servers.uuid
+            FROM servers
+            WHERE servers.uuid = ANY($2)
+            "#,
+        )
+        .bind(uuid)
+        .bind(server_uuids)
+        .execute(&mut *transaction)
+        .await?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM server_bulk_jobs
+            WHERE server_bulk_jobs.uuid = $1
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(uuid)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(Self::map(None, &row))
+    }
+
+    pub async fn by_uuid(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM server_bulk_jobs
+            WHERE server_bulk_jobs.uuid = $1
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn all_with_pagination(
+        database: &crate::database::Database,
+        page: i64,
+        per_page: i64,
+    ) -> Result<super::Pagination<Self>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER() AS total_count
+            FROM server_bulk_jobs
+            ORDER BY server_bulk_jobs.created DESC
+            LIMIT $1 OFFSET $2
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(super::Pagination {
+            total: rows.first().map_or(0, |row| row.get("total_count")),
+            per_page,
+            page,
+            data: rows.into_iter().map(|row| Self::map(None, &row)).collect(),
+        })
+    }
+
+    /// The action with the value of a secret variable decrypted.
+    pub fn decrypted_action(
+        &self,
+        database: &crate::database::Database,
+    ) -> Result<ServerBulkAction, sqlx::Error> {
+        let mut action = self.action.clone();
+
+        if let (ServerBulkAction::Variable { value, .. }, Some(encrypted_value)) =
+            (&mut action, &self.encrypted_value)
+        {
+            *value = database
+                .decrypt(encrypted_value)
+                .ok_or_else(|| sqlx::Error::Decode("failed to decrypt variable value".into()))?;
+        }
+
+        Ok(action)
+    }
+
+    /// Runs the action on all pending servers of the job. Nodes are worked on in parallel,
+    /// with at most `concurrency` servers of the same node in flight at once.
+    pub async fn run(&self, database: &crate::database::Database) -> Result<(), sqlx::Error> {
+        let action = self.decrypted_action(database)?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT server_bulk_job_results.server_uuid, servers.node_uuid
+            FROM server_bulk_job_results
+            JOIN servers ON servers.uuid = server_bulk_job_results.server_uuid
+            WHERE server_bulk_job_results.job_uuid = $1 AND server_bulk_job_results.finished IS NULL
+            "#,
+        )
+        .bind(self.uuid)
+        .fetch_all(database.read())
+        .await?;
+
+        let mut nodes: HashMap<uuid::Uuid, Vec<uuid::Uuid>> = HashMap::new();
+        for row in rows {
+            nodes
+                .entry(row.get("node_uuid"))
+                .or_default()
+                .push(row.get("server_uuid"));
+        }
+
+        let concurrency = self.concurrency.max(1) as usize;
+        let mut futures = Vec::with_capacity(nodes.len());
+        for server_uuids in nodes.into_values() {
+            futures.push(self.run_node(database, &action, server_uuids, concurrency));
+        }
+
+        futures_util::future::join_all(futures).await;
+
+        sqlx::query(
+            "UPDATE server_bulk_jobs SET finished = NOW() WHERE server_bulk_jobs.uuid = $1",
+        )
+        .bind(self.uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn run_node(
+        &self,
+        database: &crate::database::Database,
+        action: &ServerBulkAction,
+        server_uuids: Vec<uuid::Uuid>,
+        concurrency: usize,
+    ) {
+        let mut running = futures_util::stream::FuturesUnordered::new();
+
+        for server_uuid in server_uuids {
+            if running.len() >= concurrency {
+                running.next().await;
+            }
+
+            running.push(self.run_server(database, action, server_uuid));
+        }
+
+        while running.next().await.is_some() {}
+    }
+
+    async fn run_server(
+        &self,
+        database: &crate::database::Database,
+        action: &ServerBulkAction,
+        server_uuid: uuid::Uuid,
+    ) {
+        let error = match action.execute(database, server_uuid).await {
+            Ok(()) => None,
+            Err(err) => Some(err.to_string()),
+        };
+
+        if let Err(err) = sqlx::query(
+            r#"
+            UPDATE server_bulk_job_results
+            SET successful = $3, error = $4, finished = NOW()
+            WHERE server_bulk_job_results.job_uuid = $1 AND server_bulk_job_results.server_uuid = $2
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(server_uuid)
+        .bind(error.is_none())
+        .bind(error)
+        .execute(database.write())
+        .await
+        {
+            tracing::error!(
+                job = %self.uuid,
+                server = %server_uuid,
+                "failed to record bulk job result: {:#?}",
+                err
+            );
+        }
+    }
+
+    /// The action with the value of secret variables left empty.
+    pub fn masked_action(&self) -> ServerBulkAction {
+        match &self.action {
+            ServerBulkAction::Variable { env_variable, .. } if self.secret => {
+                ServerBulkAction::Variable {
+                    env_variable: env_variable.clone(),
+                    value: String::new(),
+                }
+            }
+            action => action.clone(),
+        }
+    }
+
+    #[inline]
+    pub fn into_admin_api_object(self) -> AdminApiServerBulkJob {
+        AdminApiServerBulkJob {
+            uuid: self.uuid,
+            user_uuid: self.user_uuid,
+            action: self.masked_action(),
+            concurrency: self.concurrency,
+            total: self.total,
+            successful: self.successful,
+            failed: self.failed,
+            finished: self.finished.map(|finished| finished.and_utc()),
+            created: self.created.and_utc(),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "ServerBulkJob")]
+pub struct AdminApiServerBulkJob {
+    pub uuid: uuid::Uuid,
+    pub user_uuid: Option<uuid::Uuid>,
+
+    pub action: ServerBulkAction,
+    pub concurrency: i32,
+
+    pub total: i64,
+    pub successful: i64,
+    pub failed: i64,
+
+    pub finished: Option<chrono::DateTime<chrono::Utc>>,
+    pub created: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ServerBulkJobResult {
+    pub server_uuid: uuid::Uuid,
+    pub server_name: String,
+    pub node_uuid: uuid::Uuid,
+
+    pub successful: Option<bool>,
+    pub error: Option<String>,
+
+    pub finished: Option<chrono::NaiveDateTime>,
+}
+
+impl BaseModel for ServerBulkJobResult {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            (
+                "server_bulk_job_results.server_uuid",
+                format!("{prefix}server_uuid"),
+            ),
+            ("servers.name", format!("{prefix}server_name")),
+            ("servers.node_uuid", format!("{prefix}node_uuid")),
+            (
+                "server_bulk_job_results.successful",
+                format!("{prefix}successful"),
+            ),
+            ("server_bulk_job_results.error", format!("{prefix}error")),
+            (
+                "server_bulk_job_results.finished",
+                format!("{prefix}finished"),
+            ),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            server_uuid: row.get(format!("{prefix}server_uuid").as_str()),
+            server_name: row.get(format!("{prefix}server_name").as_str()),
+            node_uuid: row.get(format!("{prefix}node_uuid").as_str()),
+            successful: row.get(format!("{prefix}successful").as_str()),
+            error: row.get(format!("{prefix}error").as_str()),
+            finished: row.get(format!("{prefix}finished").as_str()),
+        }
+    }
+}
+
+impl ServerBulkJobResult {
+    pub async fn by_job_uuid_with_pagination(
+        database: &crate::database::Database,
+        job_uuid: uuid::Uuid,
+        page: i64,
+        per_page: i64,
+        search: Option<&str>,
+    ) -> Result<super::Pagination<Self>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER() AS total_count
+            FROM server_bulk_job_results
+            JOIN servers ON servers.uuid = server_bulk_job_results.server_uuid
+            WHERE server_bulk_job_results.job_uuid = $1 AND ($2 IS NULL OR servers.name ILIKE '%' || $2 || '%')
+            ORDER BY servers.created
+            LIMIT $3 OFFSET $4
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(job_uuid)
+        .bind(search)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(super::Pagination {
+            total: rows.first().map_or(0, |row| row.get("total_count")),
+            per_page,
+            page,
+            data: rows.into_iter().map(|row| Self::map(None, &row)).collect(),
+        })
+    }
+
+    #[inline]
+    pub fn into_admin_api_object(self) -> AdminApiServerBulkJobResult {
+        AdminApiServerBulkJobResult {
+            server_uuid: self.server_uuid,
+            server_name: self.server_name,
+            node_uuid: self.node_uuid,
+            successful: self.successful,
+            error: self.error,
+            finished: self.finished.map(|finished| finished.and_utc()),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "ServerBulkJobResult")]
+pub struct AdminApiServerBulkJobResult {
+    pub server_uuid: uuid::Uuid,
+    pub server_name: String,
+    pub node_uuid: uuid::Uuid,
+
+    /// Not set while the server is still pending.
+    pub successful: Option<bool>,
+    pub error: Option<String>,
+
+    pub finished: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -54,6 +54,36 @@ impl ServerMount {
         Ok(())
     }
 
+    /// Attaches the mounts to all given servers, skipping mounts that are not
+    /// available on the node and egg of a server or already attached.
+    pub async fn create_available(
+        executor: impl sqlx::PgExecutor<'_>,
+        server_uuids: &[uuid::Uuid],
+        mount_uuids: &[uuid::Uuid],
+    ) -> Result<(), sqlx::Error> {
+        if mount_uuids.is_empty() {
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO server_mounts (server_uuid, mount_uuid)
+            SELECT servers.uuid, node_mounts.mount_uuid
+            FROM servers
+            JOIN node_mounts ON node_mounts.node_uuid = servers.node_uuid
+            JOIN nest_egg_mounts ON nest_egg_mounts.egg_uuid = servers.egg_uuid AND nest_egg_mounts.mount_uuid = node_mounts.mount_uuid
+            WHERE servers.uuid = ANY($1) AND node_mounts.mount_uuid = ANY($2)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(server_uuids)
+        .bind(mount_uuids)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn by_server_uuid_mount_uuid(
         database: &crate::database::Database,
         server_uuid: uuid::Uuid,
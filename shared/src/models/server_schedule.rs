@@ -133,6 +133,26 @@ impl ServerSchedule {
         Ok(row.map(|row| Self::map(None, &row)))
     }
 
+    pub async fn all_by_server_uuid(
+        database: &crate::database::Database,
+        server_uuid: uuid::Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM server_schedules
+            WHERE server_schedules.server_uuid = $1
+            ORDER BY server_schedules.created
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(server_uuid)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| Self::map(None, &row)).collect())
+    }
+
     pub async fn by_server_uuid_with_pagination(
         database: &crate::database::Database,
         server_uuid: uuid::Uuid,
@@ -1,5 +1,6 @@
 use super::BaseModel;
 use crate::{
+    mail::template::MailTemplateKind,
     models::{user::User, user_password_reset::UserPasswordReset},
     storage::StorageUrlRetriever,
 };
@@ -98,24 +99,16 @@ impl ServerSubuser {
 
                 match UserPasswordReset::create(database, user.uuid).await {
                     Ok(token) => {
-                        let settings = settings.get().await;
-
-                        let mail_content = crate::mail::MAIL_ACCOUNT_CREATED
-                            .replace("{{app_name}}", &settings.app.name)
-                            .replace("{{user_username}}", &user.username)
-                            .replace(
-                                "{{reset_link}}",
-                                &format!(
-                                    "{}/auth/reset-password?token={}",
-                                    settings.app.url,
-                                    urlencoding::encode(&token),
-                                ),
-                            );
-
-                        mail.send(
-                            user.email.clone(),
-                            format!("{} - Account Created", settings.app.name),
-                            mail_content,
+                        let reset_link = format!(
+                            "{}/auth/reset-password?token={}",
+                            settings.get().await.app.url,
+                            urlencoding::encode(&token),
+                        );
+
+                        mail.send_template(
+                            &user,
+                            MailTemplateKind::AccountCreated,
+                            BTreeMap::from([("reset_link", reset_link)]),
                         )
                         .await;
 
@@ -153,6 +146,21 @@ impl ServerSubuser {
         .execute(database.write())
         .await?;
 
+        super::user_notification::UserNotification::notify(
+            database,
+            user.uuid,
+            super::user_notification::Notification {
+                category: super::user_notification::NotificationCategory::Server,
+                event: "server:subuser.added",
+                server_uuid: Some(server.uuid),
+                title: "Added to server".into(),
+                body: format!("You have been added as a subuser to {}.", server.name),
+                data: serde_json::json!({}),
+                mail: Some((MailTemplateKind::SubuserAdded, BTreeMap::new())),
+            },
+        )
+        .await;
+
         Ok(user.username)
     }
 
@@ -179,6 +187,29 @@ impl ServerSubuser {
         Ok(row.map(|row| Self::map(None, &row)))
     }
 
+    pub async fn by_server_uuid_user_uuid(
+        database: &crate::database::Database,
+        server_uuid: uuid::Uuid,
+        user_uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM server_subusers
+            JOIN users ON users.uuid = server_subusers.user_uuid
+            LEFT JOIN roles ON roles.uuid = users.role_uuid
+            WHERE server_subusers.server_uuid = $1 AND server_subusers.user_uuid = $2
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(server_uuid)
+        .bind(user_uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
     pub async fn by_server_uuid_with_pagination(
         database: &crate::database::Database,
         server_uuid: uuid::Uuid,
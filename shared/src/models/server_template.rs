@@ -0,0 +1,542 @@
+use super::{
+    BaseModel,
+    server::{ApiServerFeatureLimits, ApiServerLimits},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, postgres::PgRow};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// Variables with the values of secret variables left empty, and those values encrypted.
+pub type EncryptedVariables = (BTreeMap<String, String>, Option<Vec<u8>>);
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ServerTemplate {
+    pub uuid: uuid::Uuid,
+    pub egg_uuid: uuid::Uuid,
+
+    pub name: String,
+    pub description: Option<String>,
+
+    pub memory: i64,
+    pub swap: i64,
+    pub disk: i64,
+    pub io_weight: Option<i16>,
+    pub cpu: i32,
+    pub pinned_cpus: Vec<i16>,
+
+    pub startup: String,
+    pub image: String,
+    pub timezone: Option<String>,
+
+    pub allocation_limit: i32,
+    pub database_limit: i32,
+    pub backup_limit: i32,
+    pub schedule_limit: i32,
+
+    /// Values by env variable, empty for secret variables.
+    pub variables: BTreeMap<String, String>,
+    /// The values of secret variables, as encrypted JSON.
+    pub encrypted_variables: Option<Vec<u8>>,
+    /// The env variables of the egg that are currently secret.
+    pub secret_variables: Vec<String>,
+    pub mounts: Vec<uuid::Uuid>,
+
+    pub servers: i64,
+
+    pub created: chrono::NaiveDateTime,
+}
+
+impl BaseModel for ServerTemplate {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            ("server_templates.uuid", format!("{prefix}uuid")),
+            ("server_templates.egg_uuid", format!("{prefix}egg_uuid")),
+            ("server_templates.name", format!("{prefix}name")),
+            (
+                "server_templates.description",
+                format!("{prefix}description"),
+            ),
+            ("server_templates.memory", format!("{prefix}memory")),
+            ("server_templates.swap", format!("{prefix}swap")),
+            ("server_templates.disk", format!("{prefix}disk")),
+            ("server_templates.io_weight", format!("{prefix}io_weight")),
+            ("server_templates.cpu", format!("{prefix}cpu")),
+            (
+                "server_templates.pinned_cpus",
+                format!("{prefix}pinned_cpus"),
+            ),
+            ("server_templates.startup", format!("{prefix}startup")),
+            ("server_templates.image", format!("{prefix}image")),
+            ("server_templates.timezone", format!("{prefix}timezone")),
+            (
+                "server_templates.allocation_limit",
+                format!("{prefix}allocation_limit"),
+            ),
+            (
+                "server_templates.database_limit",
+                format!("{prefix}database_limit"),
+            ),
+            (
+                "server_templates.backup_limit",
+                format!("{prefix}backup_limit"),
+            ),
+            (
+                "server_templates.schedule_limit",
+                format!("{prefix}schedule_limit"),
+            ),
+            ("server_templates.variables", format!("{prefix}variables")),
+            (
+                "server_templates.encrypted_variables",
+                format!("{prefix}encrypted_variables"),
+            ),
+            (
+                "ARRAY(SELECT nest_egg_variables.env_variable FROM nest_egg_variables WHERE nest_egg_variables.egg_uuid = server_templates.egg_uuid AND nest_egg_variables.kind = 'SECRET')",
+                format!("{prefix}secret_variables"),
+            ),
+            ("server_templates.mounts", format!("{prefix}mounts")),
+            (
+                "(SELECT COUNT(*) FROM servers WHERE servers.server_template_uuid = server_templates.uuid)",
+                format!("{prefix}servers"),
+            ),
+            ("server_templates.created", format!("{prefix}created")),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            uuid: row.get(format!("{prefix}uuid").as_str()),
+            egg_uuid: row.get(format!("{prefix}egg_uuid").as_str()),
+            name: row.get(format!("{prefix}name").as_str()),
+            description: row.get(format!("{prefix}description").as_str()),
+            memory: row.get(format!("{prefix}memory").as_str()),
+            swap: row.get(format!("{prefix}swap").as_str()),
+            disk: row.get(format!("{prefix}disk").as_str()),
+            io_weight: row.get(format!("{prefix}io_weight").as_str()),
+            cpu: row.get(format!("{prefix}cpu").as_str()),
+            pinned_cpus: row.get(format!("{prefix}pinned_cpus").as_str()),
+            startup: row.get(format!("{prefix}startup").as_str()),
+            image: row.get(format!("{prefix}image").as_str()),
+            timezone: row.get(format!("{prefix}timezone").as_str()),
+            allocation_limit: row.get(format!("{prefix}allocation_limit").as_str()),
+            database_limit: row.get(format!("{prefix}database_limit").as_str()),
+            backup_limit: row.get(format!("{prefix}backup_limit").as_str()),
+            schedule_limit: row.get(format!("{prefix}schedule_limit").as_str()),
+            variables: serde_json::from_value(
+                row.get::<serde_json::Value, _>(format!("{prefix}variables").as_str()),
+            )
+            .unwrap_or_default(),
+            encrypted_variables: row.get(format!("{prefix}encrypted_variables").as_str()),
+            secret_variables: row.get(format!("{prefix}secret_variables").as_str()),
+            mounts: row.get(format!("{prefix}mounts").as_str()),
+            servers: row.get(format!("{prefix}servers").as_str()),
+            created: row.get(format!("{prefix}created").as_str()),
+        }
+    }
+}
+
+impl ServerTemplate {
+    /// Splits off the values of secret variables and encrypts them.
+    pub fn encrypt_variables(
+        database: &crate::database::Database,
+        egg_variables: &[super::nest_egg_variable::NestEggVariable],
+        variables: &BTreeMap<String, String>,
+    ) -> Result<EncryptedVariables, sqlx::Error> {
+        let mut plain = variables.clone();
+        let mut secret = BTreeMap::new();
+
+        for variable in egg_variables.iter().filter(|variable| variable.is_secret()) {
+            if let Some(value) = plain.get_mut(&variable.env_variable) {
+                secret.insert(variable.env_variable.as_str(), std::mem::take(value));
+            }
+        }
+
+        if secret.is_empty() {
+            return Ok((plain, None));
+        }
+
+        let encrypted_variables = database
+            .encrypt(serde_json::to_vec(&secret).map_err(|err| sqlx::Error::Encode(err.into()))?)
+            .ok_or_else(|| sqlx::Error::Encode("failed to encrypt variable values".into()))?;
+
+        Ok((plain, Some(encrypted_variables)))
+    }
+
+    /// The values of all variables, including the decrypted values of secret variables.
+    pub fn decrypted_variables(
+        &self,
+        database: &crate::database::Database,
+    ) -> Result<BTreeMap<String, String>, sqlx::Error> {
+        let mut variables = self.variables.clone();
+
+        if let Some(encrypted_variables) = &self.encrypted_variables {
+            let decrypted = database
+                .decrypt(encrypted_variables)
+                .ok_or_else(|| sqlx::Error::Decode("failed to decrypt variable values".into()))?;
+
+            variables.extend(
+                serde_json::from_str::<BTreeMap<String, String>>(&decrypted)
+                    .map_err(|err| sqlx::Error::Decode(err.into()))?,
+            );
+        }
+
+        Ok(variables)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        database: &crate::database::Database,
+        egg_uuid: uuid::Uuid,
+        name: &str,
+        description: Option<&str>,
+        limits: &ApiServerLimits,
+        pinned_cpus: &[i16],
+        startup: &str,
+        image: &str,
+        timezone: Option<&str>,
+        feature_limits: &ApiServerFeatureLimits,
+        egg_variables: &[super::nest_egg_variable::NestEggVariable],
+        variables: &BTreeMap<String, String>,
+        mounts: &[uuid::Uuid],
+    ) -> Result<Self, sqlx::Error> {
+        let (variables, encrypted_variables) =
+            Self::encrypt_variables(database, egg_variables, variables)?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO server_templates (
+                egg_uuid,
+                name,
+                description,
+                memory,
+                swap,
+                disk,
+                io_weight,
+                cpu,
+                pinned_cpus,
+                startup,
+                image,
+                timezone,
+                allocation_limit,
+                database_limit,
+                backup_limit,
+                schedule_limit,
+                variables,
+                encrypted_variables,
+                mounts
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
+            RETURNING {}
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(egg_uuid)
+        .bind(name)
+        .bind(description)
+        .bind(limits.memory)
+        .bind(limits.swap)
+        .bind(limits.disk)
+        .bind(limits.io_weight)
+        .bind(limits.cpu)
+        .bind(pinned_cpus)
+        .bind(startup)
+        .bind(image)
+        .bind(timezone)
+        .bind(feature_limits.allocations)
+        .bind(feature_limits.databases)
+        .bind(feature_limits.backups)
+        .bind(feature_limits.schedules)
+        .bind(serde_json::to_value(variables).unwrap())
+        .bind(encrypted_variables)
+        .bind(mounts)
+        .fetch_one(database.write())
+        .await?;
+
+        Ok(Self::map(None, &row))
+    }
+
+    pub async fn by_uuid(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM server_templates
+            WHERE server_templates.uuid = $1
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn all_with_pagination(
+        database: &crate::database::Database,
+        page: i64,
+        per_page: i64,
+        search: Option<&str>,
+    ) -> Result<super::Pagination<Self>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER() AS total_count
+            FROM server_templates
+            WHERE ($1 IS NULL OR server_templates.name ILIKE '%' || $1 || '%')
+            ORDER BY server_templates.created
+            LIMIT $2 OFFSET $3
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(search)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(super::Pagination {
+            total: rows.first().map_or(0, |row| row.get("total_count")),
+            per_page,
+            page,
+            data: rows.into_iter().map(|row| Self::map(None, &row)).collect(),
+        })
+    }
+
+    /// Moves the values of a variable between the plain and encrypted variables of the
+    /// templates of its egg after it was marked as secret or no longer is.
+    pub async fn update_encryption(
+        database: &crate::database::Database,
+        transaction: &mut sqlx::PgTransaction<'_>,
+        variable_uuid: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, nest_egg_variables.env_variable AS variable_env_variable
+            FROM server_templates
+            JOIN nest_egg_variables ON nest_egg_variables.egg_uuid = server_templates.egg_uuid
+            WHERE nest_egg_variables.uuid = $1
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(variable_uuid)
+        .fetch_all(&mut **transaction)
+        .await?;
+
+        let mut egg_variables = None;
+        for row in rows {
+            let template = Self::map(None, &row);
+            let env_variable: String = row.get("variable_env_variable");
+            if !template.variables.contains_key(&env_variable) {
+                continue;
+            }
+
+            let egg_variables = match &egg_variables {
+                Some(egg_variables) => egg_variables,
+                None => egg_variables.insert(
+                    super::nest_egg_variable::NestEggVariable::all_by_egg_uuid(
+                        &mut **transaction,
+                        template.egg_uuid,
+                    )
+                    .await?,
+                ),
+            };
+
+            let (variables, encrypted_variables) = Self::encrypt_variables(
+                database,
+                egg_variables,
+                &template.decrypted_variables(database)?,
+            )?;
+
+            sqlx::query(
+                r#"
+                UPDATE server_templates
+                SET variables = $2, encrypted_variables = $3
+                WHERE server_templates.uuid = $1
+                "#,
+            )
+            .bind(template.uuid)
+            .bind(serde_json::to_value(variables).unwrap())
+            .bind(encrypted_variables)
+            .execute(&mut **transaction)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn delete_by_uuid(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM server_templates
+            WHERE server_templates.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Copies the template onto all servers created from it that still use its egg,
+    /// returning the uuids of the updated servers.
+    ///
+    /// Variables are overwritten and mounts are attached, mounts that were attached
+    /// to a server by hand are kept. Syncing the servers with wings is queued in the same
+    /// transaction.
+    pub async fn apply(
+        &self,
+        database: &crate::database::Database,
+    ) -> Result<Vec<uuid::Uuid>, sqlx::Error> {
+        let mut transaction = database.write().begin().await?;
+
+        let server_uuids: Vec<uuid::Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE servers
+            SET
+                memory = $3, swap = $4, disk = $5, io_weight = $6, cpu = $7,
+                pinned_cpus = $8, startup = $9, image = $10, timezone = $11,
+                allocation_limit = $12, database_limit = $13, backup_limit = $14,
+                schedule_limit = $15
+            WHERE servers.server_template_uuid = $1 AND servers.egg_uuid = $2
+            RETURNING servers.uuid
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(self.egg_uuid)
+        .bind(self.memory)
+        .bind(self.swap)
+        .bind(self.disk)
+        .bind(self.io_weight)
+        .bind(self.cpu)
+        .bind(&self.pinned_cpus)
+        .bind(&self.startup)
+        .bind(&self.image)
+        .bind(&self.timezone)
+        .bind(self.allocation_limit)
+        .bind(self.database_limit)
+        .bind(self.backup_limit)
+        .bind(self.schedule_limit)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        super::server_variable::ServerVariable::set_by_env_variables(
+            database,
+            &mut *transaction,
+            &server_uuids,
+            &self.decrypted_variables(database)?,
+        )
+        .await?;
+        super::server_mount::ServerMount::create_available(
+            &mut *transaction,
+            &server_uuids,
+            &self.mounts,
+        )
+        .await?;
+
+        for server_uuid in &server_uuids {
+            crate::jobs::enqueue_with(
+                &mut *transaction,
+                &crate::jobs::server::SyncServer {
+                    server_uuid: *server_uuid,
+                },
+                std::time::Duration::ZERO,
+            )
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(server_uuids)
+    }
+
+    #[inline]
+    pub fn limits(&self) -> ApiServerLimits {
+        ApiServerLimits {
+            cpu: self.cpu,
+            memory: self.memory,
+            swap: self.swap,
+            disk: self.disk,
+            io_weight: self.io_weight,
+        }
+    }
+
+    #[inline]
+    pub fn feature_limits(&self) -> ApiServerFeatureLimits {
+        ApiServerFeatureLimits {
+            allocations: self.allocation_limit,
+            databases: self.database_limit,
+            backups: self.backup_limit,
+            schedules: self.schedule_limit,
+        }
+    }
+
+    #[inline]
+    pub fn into_admin_api_object(self) -> AdminApiServerTemplate {
+        AdminApiServerTemplate {
+            uuid: self.uuid,
+            egg_uuid: self.egg_uuid,
+            limits: self.limits(),
+            feature_limits: self.feature_limits(),
+            name: self.name,
+            description: self.description,
+            pinned_cpus: self.pinned_cpus,
+            startup: self.startup,
+            image: self.image,
+            timezone: self.timezone,
+            variables: self
+                .variables
+                .into_iter()
+                .map(|(env_variable, value)| {
+                    let secret = self.secret_variables.contains(&env_variable);
+
+                    (env_variable, (!secret).then_some(value))
+                })
+                .collect(),
+            mounts: self.mounts,
+            servers: self.servers,
+            created: self.created.and_utc(),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "ServerTemplate")]
+pub struct AdminApiServerTemplate {
+    pub uuid: uuid::Uuid,
+    pub egg_uuid: uuid::Uuid,
+
+    pub name: String,
+    pub description: Option<String>,
+
+    #[schema(inline)]
+    pub limits: ApiServerLimits,
+    pub pinned_cpus: Vec<i16>,
+    #[schema(inline)]
+    pub feature_limits: ApiServerFeatureLimits,
+
+    pub startup: String,
+    pub image: String,
+    pub timezone: Option<String>,
+
+    /// Values of secret variables are write-only and always `null`.
+    pub variables: BTreeMap<String, Option<String>>,
+    pub mounts: Vec<uuid::Uuid>,
+
+    pub servers: i64,
+
+    pub created: chrono::DateTime<chrono::Utc>,
+}
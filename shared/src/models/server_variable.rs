@@ -9,6 +9,7 @@ pub struct ServerVariable {
     pub variable: super::nest_egg_variable::NestEggVariable,
 
     pub value: String,
+    pub encrypted_value: Option<Vec<u8>>,
 
     pub created: chrono::NaiveDateTime,
 }
@@ -20,6 +21,10 @@ impl BaseModel for ServerVariable {
 
         let mut columns = BTreeMap::from([
             ("server_variables.value", format!("{prefix}value")),
+            (
+                "server_variables.encrypted_value",
+                format!("{prefix}encrypted_value"),
+            ),
             ("server_variables.created", format!("{prefix}created")),
         ]);
 
@@ -47,6 +52,9 @@ impl BaseModel for ServerVariable {
         Self {
             variable,
             value,
+            encrypted_value: row
+                .try_get(format!("{prefix}encrypted_value").as_str())
+                .unwrap_or_default(),
             created: row
                 .try_get(format!("{prefix}created").as_str())
                 .unwrap_or_else(|_| chrono::Utc::now().naive_utc()),
@@ -65,7 +73,7 @@ impl ServerVariable {
             r#"
             INSERT INTO server_variables (server_uuid, variable_uuid, value)
             VALUES ($1, $2, $3)
-            ON CONFLICT (server_uuid, variable_uuid) DO UPDATE SET value = EXCLUDED.value
+            ON CONFLICT (server_uuid, variable_uuid) DO UPDATE SET value = EXCLUDED.value, encrypted_value = NULL
             "#,
         )
         .bind(server_uuid)
@@ -77,6 +85,164 @@ impl ServerVariable {
         Ok(())
     }
 
+    /// Stores the value encrypted, as used for secret variables.
+    pub async fn create_encrypted(
+        database: &crate::database::Database,
+        server_uuid: uuid::Uuid,
+        variable_uuid: uuid::Uuid,
+        value: &str,
+    ) -> Result<(), anyhow::Error> {
+        let encrypted_value = database
+            .encrypt(value)
+            .ok_or_else(|| anyhow::anyhow!("failed to encrypt variable value"))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO server_variables (server_uuid, variable_uuid, value, encrypted_value)
+            VALUES ($1, $2, '', $3)
+            ON CONFLICT (server_uuid, variable_uuid) DO UPDATE SET value = '', encrypted_value = EXCLUDED.encrypted_value
+            "#,
+        )
+        .bind(server_uuid)
+        .bind(variable_uuid)
+        .bind(encrypted_value)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stores the value, encrypting it when the variable is a secret.
+    pub async fn set(
+        database: &crate::database::Database,
+        server_uuid: uuid::Uuid,
+        variable: &super::nest_egg_variable::NestEggVariable,
+        value: &str,
+    ) -> Result<(), anyhow::Error> {
+        if variable.is_secret() {
+            Self::create_encrypted(database, server_uuid, variable.uuid, value).await
+        } else {
+            Ok(Self::create(database, server_uuid, variable.uuid, value).await?)
+        }
+    }
+
+    /// Sets variables by their env variable on all given servers, skipping variables
+    /// the egg of a server does not define. Values of secret variables are encrypted.
+    pub async fn set_by_env_variables(
+        database: &crate::database::Database,
+        executor: impl sqlx::PgExecutor<'_>,
+        server_uuids: &[uuid::Uuid],
+        variables: &BTreeMap<String, String>,
+    ) -> Result<(), sqlx::Error> {
+        if variables.is_empty() {
+            return Ok(());
+        }
+
+        let mut env_variables = Vec::with_capacity(variables.len());
+        let mut values = Vec::with_capacity(variables.len());
+        let mut encrypted_values = Vec::with_capacity(variables.len());
+        for (env_variable, value) in variables {
+            env_variables.push(env_variable.as_str());
+            values.push(value.as_str());
+            encrypted_values.push(
+                database.encrypt(value).ok_or_else(|| {
+                    sqlx::Error::Encode("failed to encrypt variable value".into())
+                })?,
+            );
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO server_variables (server_uuid, variable_uuid, value, encrypted_value)
+            SELECT
+                servers.uuid,
+                nest_egg_variables.uuid,
+                CASE WHEN nest_egg_variables.kind = 'SECRET' THEN '' ELSE variables.value END,
+                CASE WHEN nest_egg_variables.kind = 'SECRET' THEN variables.encrypted_value END
+            FROM servers
+            JOIN nest_egg_variables ON nest_egg_variables.egg_uuid = servers.egg_uuid
+            JOIN UNNEST($2::text[], $3::text[], $4::bytea[]) AS variables(env_variable, value, encrypted_value)
+                ON variables.env_variable = nest_egg_variables.env_variable
+            WHERE servers.uuid = ANY($1)
+            ON CONFLICT (server_uuid, variable_uuid) DO UPDATE SET value = EXCLUDED.value, encrypted_value = EXCLUDED.encrypted_value
+            "#,
+        )
+        .bind(server_uuids)
+        .bind(env_variables)
+        .bind(values)
+        .bind(encrypted_values)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Encrypts or decrypts the stored values of a variable after it was marked as
+    /// secret or no longer is.
+    pub async fn update_encryption(
+        database: &crate::database::Database,
+        transaction: &mut sqlx::PgTransaction<'_>,
+        variable_uuid: uuid::Uuid,
+        secret: bool,
+    ) -> Result<(), sqlx::Error> {
+        let rows: Vec<(uuid::Uuid, String, Option<Vec<u8>>)> = sqlx::query_as(
+            r#"
+            SELECT server_variables.server_uuid, server_variables.value, server_variables.encrypted_value
+            FROM server_variables
+            WHERE server_variables.variable_uuid = $1 AND (server_variables.encrypted_value IS NULL) = $2
+            "#,
+        )
+        .bind(variable_uuid)
+        .bind(secret)
+        .fetch_all(&mut **transaction)
+        .await?;
+
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let mut server_uuids = Vec::with_capacity(rows.len());
+        let mut values = Vec::with_capacity(rows.len());
+        let mut encrypted_values = Vec::with_capacity(rows.len());
+        for (server_uuid, value, encrypted_value) in rows {
+            server_uuids.push(server_uuid);
+
+            if secret {
+                values.push(String::new());
+                encrypted_values.push(Some(database.encrypt(value).ok_or_else(|| {
+                    sqlx::Error::Encode("failed to encrypt variable value".into())
+                })?));
+            } else {
+                values.push(match encrypted_value {
+                    Some(encrypted_value) => {
+                        database.decrypt(encrypted_value).ok_or_else(|| {
+                            sqlx::Error::Decode("failed to decrypt variable value".into())
+                        })?
+                    }
+                    None => value,
+                });
+                encrypted_values.push(None);
+            }
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE server_variables
+            SET value = variables.value, encrypted_value = variables.encrypted_value
+            FROM UNNEST($2::uuid[], $3::text[], $4::bytea[]) AS variables(server_uuid, value, encrypted_value)
+            WHERE server_variables.variable_uuid = $1 AND server_variables.server_uuid = variables.server_uuid
+            "#,
+        )
+        .bind(variable_uuid)
+        .bind(server_uuids)
+        .bind(values)
+        .bind(encrypted_values)
+        .execute(&mut **transaction)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn all_by_server_uuid_egg_uuid(
         database: &crate::database::Database,
         server_uuid: uuid::Uuid,
@@ -100,16 +266,39 @@ impl ServerVariable {
         Ok(rows.into_iter().map(|row| Self::map(None, &row)).collect())
     }
 
+    /// The plain value of the variable, decrypting it when it was stored encrypted.
+    /// Fails instead of returning an empty value when it cannot be decrypted.
+    pub fn decrypted_value(
+        &self,
+        database: &crate::database::Database,
+    ) -> Result<String, sqlx::Error> {
+        match &self.encrypted_value {
+            Some(encrypted_value) => database
+                .decrypt(encrypted_value)
+                .ok_or_else(|| sqlx::Error::Decode("failed to decrypt variable value".into())),
+            None => Ok(self.value.clone()),
+        }
+    }
+
     #[inline]
     pub fn into_api_object(self) -> ApiServerVariable {
+        let secret = self.variable.is_secret();
+
         ApiServerVariable {
             name: self.variable.name,
             description: self.variable.description,
             env_variable: self.variable.env_variable,
-            default_value: self.variable.default_value,
-            value: self.value,
+            default_value: if secret {
+                None
+            } else {
+                self.variable.default_value
+            },
+            value: if secret { String::new() } else { self.value },
             is_editable: self.variable.user_editable,
             rules: self.variable.rules,
+            kind: self.variable.kind,
+            options: self.variable.options,
+            ui_hints: self.variable.ui_hints,
             created: self.created.and_utc(),
         }
     }
@@ -126,6 +315,10 @@ pub struct ApiServerVariable {
     pub value: String,
     pub is_editable: bool,
     pub rules: Vec<String>,
+    /// Values of `secret` variables are write-only and always empty.
+    pub kind: super::nest_egg_variable::NestEggVariableKind,
+    pub options: Vec<String>,
+    pub ui_hints: Option<super::nest_egg_variable::NestEggVariableUiHints>,
 
     pub created: chrono::DateTime<chrono::Utc>,
 }
@@ -13,6 +13,9 @@ use webauthn_rs::prelude::CredentialID;
 
 pub static USERNAME_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_]+$").expect("Failed to compile username regex"));
+pub static LANGUAGE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[a-z]{2,3}(-[A-Za-z0-9]{2,8})*$").expect("Failed to compile language regex")
+});
 
 #[derive(Clone)]
 pub enum AuthMethod {
@@ -114,15 +117,15 @@ impl PermissionManager {
 
         if self.server_subuser_permissions.is_none() && self.role_server_permissions.is_none() {
             if let Some(api_key_permissions) = &self.api_key_server_permissions
-                && api_key_permissions.iter().any(|p| p == permission)
+                && !api_key_permissions.iter().any(|p| p == permission)
             {
-                return Ok(());
+                return Err(ApiResponse::new(Body::from(format!(
+                    "you do not have permission to perform this action: {permission}"
+                )))
+                .with_status(StatusCode::FORBIDDEN));
             }
 
-            return Err(ApiResponse::new(Body::from(format!(
-                "you do not have permission to perform this action: {permission}"
-            )))
-            .with_status(StatusCode::FORBIDDEN));
+            return Ok(());
         }
 
         let has_role_permission = if let Some(permissions) = &self.role_server_permissions {
@@ -171,11 +174,14 @@ pub struct User {
 
     pub name_first: String,
     pub name_last: String,
+    pub language: String,
 
     pub admin: bool,
     pub totp_enabled: bool,
     pub totp_secret: Option<String>,
 
+    pub quotas: super::quota::Quotas,
+
     pub created: chrono::NaiveDateTime,
 }
 
@@ -192,9 +198,11 @@ impl BaseModel for User {
             ("users.email", format!("{prefix}email")),
             ("users.name_first", format!("{prefix}name_first")),
             ("users.name_last", format!("{prefix}name_last")),
+            ("users.language", format!("{prefix}language")),
             ("users.admin", format!("{prefix}admin")),
             ("users.totp_enabled", format!("{prefix}totp_enabled")),
             ("users.totp_secret", format!("{prefix}totp_secret")),
+            ("users.quotas", format!("{prefix}quotas")),
             ("users.created", format!("{prefix}created")),
             ("users.created", format!("{prefix}created")),
         ]);
@@ -224,9 +232,11 @@ impl BaseModel for User {
             email: row.get(format!("{prefix}email").as_str()),
             name_first: row.get(format!("{prefix}name_first").as_str()),
             name_last: row.get(format!("{prefix}name_last").as_str()),
+            language: row.get(format!("{prefix}language").as_str()),
             admin: row.get(format!("{prefix}admin").as_str()),
             totp_enabled: row.get(format!("{prefix}totp_enabled").as_str()),
             totp_secret: row.get(format!("{prefix}totp_secret").as_str()),
+            quotas: super::quota::Quotas::from_value(row.get(format!("{prefix}quotas").as_str())),
             created: row.get(format!("{prefix}created").as_str()),
         }
     }
@@ -389,8 +399,48 @@ impl User {
         Ok(row.map(|row| Self::map(None, &row)))
     }
 
+    /// Resolves the account a sign in is for without checking its credentials.
+    pub async fn uuid_by_username_or_email(
+        database: &crate::database::Database,
+        username_or_email: &str,
+    ) -> Result<Option<uuid::Uuid>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT users.uuid FROM users WHERE users.username = $1 OR users.email = $1",
+        )
+        .bind(username_or_email)
+        .fetch_optional(database.read())
+        .await
+    }
+
+    /// The user with `subject` at `provider`, see [super::user_external_identity::UserExternalIdentity].
+    pub async fn by_external_identity(
+        database: &crate::database::Database,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM users
+            JOIN user_external_identities ON user_external_identities.user_uuid = users.uuid
+            LEFT JOIN roles ON roles.uuid = users.role_uuid
+            WHERE user_external_identities.provider = $1 AND user_external_identities.subject = $2
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(provider)
+        .bind(subject)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    /// Checks the password of a local user, falling through to an LDAP bind when enabled.
+    /// LDAP users are created on their first login and linked to their lowercase username.
     pub async fn by_username_password(
         database: &crate::database::Database,
+        settings: &crate::settings::Settings,
         username: &str,
         password: &str,
     ) -> Result<Option<Self>, sqlx::Error> {
@@ -408,7 +458,94 @@ impl User {
         .fetch_optional(database.read())
         .await?;
 
-        Ok(row.map(|row| Self::map(None, &row)))
+        if let Some(row) = row {
+            return Ok(Some(Self::map(None, &row)));
+        }
+
+        let ldap_settings = settings.get().await.ldap.clone();
+        if !ldap_settings.enabled {
+            return Ok(None);
+        }
+
+        let subject = username.to_lowercase();
+        let user = Self::by_external_identity(database, "ldap", &subject).await?;
+
+        // local accounts are never shadowed by directory accounts
+        if user.is_none()
+            && sqlx::query("SELECT 1 FROM users WHERE users.username = $1")
+                .bind(username)
+                .fetch_optional(database.read())
+                .await?
+                .is_some()
+        {
+            return Ok(None);
+        }
+
+        let ldap_user = match crate::ldap::authenticate(&ldap_settings, username, password).await {
+            Ok(Some(ldap_user)) => ldap_user,
+            Ok(None) => return Ok(None),
+            Err(err) => {
+                tracing::warn!(username, "failed to authenticate against ldap: {:#?}", err);
+                return Ok(None);
+            }
+        };
+
+        let user_uuid = match user {
+            Some(user) => user.uuid,
+            None => {
+                if !(3..=15).contains(&username.len()) || !USERNAME_REGEX.is_match(username) {
+                    tracing::warn!(username, "ldap username is not a valid panel username");
+                    return Ok(None);
+                }
+
+                let email = match &ldap_user.email {
+                    Some(email) => email,
+                    None => {
+                        tracing::warn!(dn = ldap_user.dn, "ldap user has no email address");
+                        return Ok(None);
+                    }
+                };
+                if Self::by_email(database, email).await?.is_some() {
+                    tracing::warn!(dn = ldap_user.dn, "ldap user email is already in use");
+                    return Ok(None);
+                }
+
+                let password = rand::distr::SampleString::sample_string(
+                    &rand::distr::Alphanumeric,
+                    &mut rand::rng(),
+                    64,
+                );
+                let user_uuid = Self::create(
+                    database,
+                    username,
+                    email,
+                    ldap_user.name_first.as_deref().unwrap_or(username),
+                    ldap_user.name_last.as_deref().unwrap_or(username),
+                    &password,
+                    false,
+                )
+                .await?;
+
+                super::user_external_identity::UserExternalIdentity::create(
+                    database.write(),
+                    user_uuid,
+                    "ldap",
+                    &subject,
+                )
+                .await?;
+
+                user_uuid
+            }
+        };
+
+        if ldap_settings.groups_attribute.is_some() && !ldap_settings.role_mappings.is_empty() {
+            Self::update_mapped_role(database, user_uuid, &ldap_settings.role_mappings, |group| {
+                ldap_user.is_member_of(group)
+            })
+            .await?;
+        }
+
+        Self::by_uuid(database, user_uuid).await
     }
 
     pub async fn by_username_public_key(
@@ -545,6 +682,78 @@ impl User {
         Ok(())
     }
 
+    /// Sets the role of a user, roles that no longer exist are treated as no role.
+    pub async fn update_role(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+        role_uuid: Option<uuid::Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE users SET role_uuid = (SELECT roles.uuid FROM roles WHERE roles.uuid = $2) WHERE users.uuid = $1",
+        )
+        .bind(uuid)
+        .bind(role_uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Applies the role of the first mapping whose group matches. Without a match, the role is
+    /// only removed when it is one of the mapped roles, manually assigned roles are kept.
+    pub async fn update_mapped_role(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+        mappings: &[crate::settings::RoleMapping],
+        matches: impl Fn(&str) -> bool,
+    ) -> Result<(), sqlx::Error> {
+        if let Some(role_uuid) = crate::settings::RoleMapping::resolve(mappings, matches) {
+            return Self::update_role(database, uuid, Some(role_uuid)).await;
+        }
+
+        sqlx::query(
+            "UPDATE users SET role_uuid = NULL WHERE users.uuid = $1 AND users.role_uuid = ANY($2)",
+        )
+        .bind(uuid)
+        .bind(
+            mappings
+                .iter()
+                .map(|mapping| mapping.role_uuid)
+                .collect::<Vec<_>>(),
+        )
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_quotas(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+        quotas: &super::quota::Quotas,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET quotas = $2
+            WHERE users.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .bind(serde_json::to_value(quotas).unwrap_or_default())
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    /// The quotas that apply to the user, quotas set on the user take precedence over their role.
+    #[inline]
+    pub fn effective_quotas(&self) -> super::quota::Quotas {
+        self.quotas
+            .or(self.role.as_ref().map(|r| r.quotas).unwrap_or_default())
+    }
+
     pub async fn validate_password(
         &self,
         database: &crate::database::Database,
@@ -617,8 +826,10 @@ impl User {
             email: self.email,
             name_first: self.name_first,
             name_last: self.name_last,
+            language: self.language,
             admin: self.admin,
             totp_enabled: self.totp_enabled,
+            quotas: self.quotas,
             created: self.created.and_utc(),
         }
     }
@@ -651,9 +862,12 @@ pub struct ApiFullUser {
 
     pub name_first: String,
     pub name_last: String,
+    pub language: String,
 
     pub admin: bool,
     pub totp_enabled: bool,
 
+    pub quotas: super::quota::Quotas,
+
     pub created: chrono::DateTime<chrono::Utc>,
 }
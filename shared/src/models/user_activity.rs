@@ -98,10 +98,25 @@ impl UserActivity {
         .bind(api_key_uuid)
         .bind(event)
         .bind(ip)
-        .bind(data)
+        .bind(&data)
         .execute(database.write())
         .await?;
 
+        super::webhook::Webhook::trigger(
+            database,
+            event,
+            serde_json::json!({
+                "type": "user",
+                "event": event,
+                "user_uuid": user_uuid,
+                "api_key_uuid": api_key_uuid,
+                "ip": ip.ip(),
+                "data": data,
+                "timestamp": chrono::Utc::now(),
+            }),
+        )
+        .await;
+
         Ok(())
     }
 
@@ -0,0 +1,114 @@
+use super::BaseModel;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, postgres::PgRow};
+use std::collections::BTreeMap;
+
+/// The identity of a user at a sign in provider, the OIDC issuer or `ldap`, one per provider.
+#[derive(Serialize, Deserialize)]
+pub struct UserExternalIdentity {
+    pub provider: String,
+    pub subject: String,
+
+    pub created: chrono::NaiveDateTime,
+}
+
+impl BaseModel for UserExternalIdentity {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            (
+                "user_external_identities.provider",
+                format!("{prefix}provider"),
+            ),
+            (
+                "user_external_identities.subject",
+                format!("{prefix}subject"),
+            ),
+            (
+                "user_external_identities.created",
+                format!("{prefix}created"),
+            ),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            provider: row.get(format!("{prefix}provider").as_str()),
+            subject: row.get(format!("{prefix}subject").as_str()),
+            created: row.get(format!("{prefix}created").as_str()),
+        }
+    }
+}
+
+impl UserExternalIdentity {
+    /// Links `subject` at `provider` to the user, replacing an earlier identity of the user
+    /// at that provider. Fails with a unique violation if another user has the identity.
+    pub async fn create(
+        executor: impl sqlx::PgExecutor<'_>,
+        user_uuid: uuid::Uuid,
+        provider: &str,
+        subject: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_external_identities (user_uuid, provider, subject)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_uuid, provider) DO UPDATE SET subject = EXCLUDED.subject, created = NOW()
+            "#,
+        )
+        .bind(user_uuid)
+        .bind(provider)
+        .bind(subject)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn by_user_uuid_provider(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+        provider: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM user_external_identities
+            WHERE user_external_identities.user_uuid = $1 AND user_external_identities.provider = $2
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(user_uuid)
+        .bind(provider)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn delete_by_user_uuid_provider(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+        provider: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            DELETE FROM user_external_identities
+            WHERE user_external_identities.user_uuid = $1 AND user_external_identities.provider = $2
+            RETURNING {}
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(user_uuid)
+        .bind(provider)
+        .fetch_optional(database.write())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+}
@@ -0,0 +1,449 @@
+use super::BaseModel;
+use crate::{response::ApiResponse, settings::AppSettingsSecurity, storage::StorageUrlRetriever};
+use axum::http::StatusCode;
+use rustis::{
+    commands::{SetCondition, SetExpiration, StringCommands},
+    resp::Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, postgres::PgRow};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// Why a sign in attempt is refused before its credentials are checked.
+pub enum LoginThrottle {
+    /// Seconds until the next attempt is accepted.
+    Delayed(i64),
+    /// Seconds until the lockout ends.
+    Locked(i64),
+}
+
+impl LoginThrottle {
+    pub fn into_response(self) -> ApiResponse {
+        match self {
+            Self::Delayed(seconds) => {
+                ApiResponse::error(&format!("too many failed attempts, retry in {seconds}s"))
+            }
+            Self::Locked(seconds) => ApiResponse::error(&format!(
+                "account is temporarily locked, retry in {seconds}s"
+            )),
+        }
+        .with_status(StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// Lockouts win over delays. Delays double with every failure past `login_delay_after`, a
+    /// counter without failures for `login_lockout_duration` seconds no longer delays.
+    fn new(
+        settings: &AppSettingsSecurity,
+        failures: i32,
+        last_failure: chrono::NaiveDateTime,
+        locked_until: Option<chrono::NaiveDateTime>,
+    ) -> Option<Self> {
+        let delay_after = settings.login_delay_after;
+        let max_delay = settings.login_max_delay as i64;
+        let window = settings.login_lockout_duration as i64;
+
+        let now = chrono::Utc::now().naive_utc();
+
+        if let Some(locked_until) = locked_until
+            && locked_until > now
+        {
+            return Some(Self::Locked((locked_until - now).num_seconds() + 1));
+        }
+
+        if delay_after == 0
+            || failures < delay_after as i32
+            || last_failure + chrono::Duration::seconds(window) < now
+        {
+            return None;
+        }
+
+        let delay = 2i64
+            .saturating_pow((failures - delay_after as i32) as u32)
+            .min(max_delay);
+        let retry = last_failure + chrono::Duration::seconds(delay) - now;
+
+        if retry > chrono::Duration::zero() {
+            Some(Self::Delayed(retry.num_seconds() + 1))
+        } else {
+            None
+        }
+    }
+}
+
+/// Failed sign in attempts for a username or email that matches no account. Kept in the
+/// cache and throttled like known accounts, so responses do not tell which accounts exist
+/// and directory users are throttled before they are provisioned.
+#[derive(Serialize, Deserialize)]
+struct UnknownLoginFailure {
+    failures: i32,
+    last_failure: chrono::NaiveDateTime,
+    locked_until: Option<chrono::NaiveDateTime>,
+}
+
+impl UnknownLoginFailure {
+    #[inline]
+    fn key(identifier: &str) -> String {
+        format!(
+            "login_failures::{}",
+            crate::utils::slice_up_to(identifier, 255).to_lowercase()
+        )
+    }
+}
+
+/// Failed sign in attempts of an account, covering passwords, two-factor and recovery codes
+/// and SFTP passwords. Removed on the next completed sign in.
+#[derive(Serialize, Deserialize)]
+pub struct UserLoginFailure {
+    pub user: super::user::User,
+
+    pub failures: i32,
+    pub last_ip: Option<sqlx::types::ipnetwork::IpNetwork>,
+
+    pub last_failure: chrono::NaiveDateTime,
+    pub locked_until: Option<chrono::NaiveDateTime>,
+}
+
+impl BaseModel for UserLoginFailure {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        let mut columns = BTreeMap::from([
+            ("user_login_failures.failures", format!("{prefix}failures")),
+            ("user_login_failures.last_ip", format!("{prefix}last_ip")),
+            (
+                "user_login_failures.last_failure",
+                format!("{prefix}last_failure"),
+            ),
+            (
+                "user_login_failures.locked_until",
+                format!("{prefix}locked_until"),
+            ),
+        ]);
+
+        columns.extend(super::user::User::columns(Some("user_")));
+
+        columns
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            user: super::user::User::map(Some("user_"), row),
+            failures: row.get(format!("{prefix}failures").as_str()),
+            last_ip: row.get(format!("{prefix}last_ip").as_str()),
+            last_failure: row.get(format!("{prefix}last_failure").as_str()),
+            locked_until: row.get(format!("{prefix}locked_until").as_str()),
+        }
+    }
+}
+
+impl UserLoginFailure {
+    /// Returns why the next attempt for the account has to wait, if it does.
+    pub async fn check(
+        database: &crate::database::Database,
+        settings: &crate::settings::Settings,
+        user_uuid: uuid::Uuid,
+    ) -> Result<Option<LoginThrottle>, sqlx::Error> {
+        let failure = match Self::by_user_uuid(database, user_uuid).await? {
+            Some(failure) => failure,
+            None => return Ok(None),
+        };
+
+        Ok(failure.throttle(settings).await)
+    }
+
+    /// Like [Self::check], for a submitted username or email that matches no account.
+    pub async fn check_unknown(
+        cache: &crate::cache::Cache,
+        settings: &crate::settings::Settings,
+        identifier: &str,
+    ) -> Result<Option<LoginThrottle>, rustis::Error> {
+        let failure: Option<Json<UnknownLoginFailure>> = cache
+            .client
+            .get(UnknownLoginFailure::key(identifier))
+            .await?;
+        let failure = match failure {
+            Some(failure) => failure.into_inner(),
+            None => return Ok(None),
+        };
+
+        let settings = settings.get().await;
+
+        Ok(LoginThrottle::new(
+            &settings.security,
+            failure.failures,
+            failure.last_failure,
+            failure.locked_until,
+        ))
+    }
+
+    /// Like [Self::record], for a submitted username or email that matches no account.
+    pub async fn record_unknown(
+        cache: &crate::cache::Cache,
+        settings: &crate::settings::Settings,
+        identifier: &str,
+    ) -> Result<(), rustis::Error> {
+        let settings = settings.get().await;
+        let lockout_after = settings.security.login_lockout_after;
+        let lockout_duration = settings.security.login_lockout_duration;
+        drop(settings);
+
+        let key = UnknownLoginFailure::key(identifier);
+        let now = chrono::Utc::now().naive_utc();
+
+        let failure: Option<Json<UnknownLoginFailure>> = cache.client.get(&key).await?;
+        let failures = failure
+            .map(Json::into_inner)
+            .filter(|failure| {
+                failure.last_failure + chrono::Duration::seconds(lockout_duration as i64) >= now
+            })
+            .map_or(1, |failure| failure.failures + 1);
+
+        let failure = UnknownLoginFailure {
+            failures,
+            last_failure: now,
+            locked_until: (lockout_after != 0 && failures >= lockout_after as i32)
+                .then(|| now + chrono::Duration::seconds(lockout_duration as i64)),
+        };
+
+        cache
+            .client
+            .set_with_options(
+                key,
+                Json(&failure),
+                SetCondition::None,
+                SetExpiration::Ex(lockout_duration.max(1)),
+                false,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Counts a failed attempt, logs it and locks the account once the limit is reached.
+    /// Attempts without a known client address are counted but not logged.
+    pub async fn record(
+        database: &crate::database::Database,
+        settings: &crate::settings::Settings,
+        user_uuid: uuid::Uuid,
+        ip: Option<std::net::IpAddr>,
+        mut data: serde_json::Value,
+    ) -> Result<(), sqlx::Error> {
+        let settings = settings.get().await;
+        let lockout_after = settings.security.login_lockout_after;
+        let lockout_duration = settings.security.login_lockout_duration;
+        drop(settings);
+
+        let failures: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO user_login_failures (user_uuid, failures, last_ip, last_failure)
+            VALUES ($1, 1, $2, NOW())
+            ON CONFLICT (user_uuid) DO UPDATE SET
+                failures = CASE
+                    WHEN user_login_failures.last_failure < NOW() - make_interval(secs => $3) THEN 1
+                    ELSE user_login_failures.failures + 1
+                END,
+                last_ip = EXCLUDED.last_ip,
+                last_failure = NOW(),
+                locked_until = NULL
+            RETURNING failures
+            "#,
+        )
+        .bind(user_uuid)
+        .bind(ip.map(sqlx::types::ipnetwork::IpNetwork::from))
+        .bind(lockout_duration as f64)
+        .fetch_one(database.write())
+        .await?;
+
+        if let Some(ip) = ip {
+            data["failures"] = failures.into();
+            super::user_activity::UserActivity::log(
+                database,
+                user_uuid,
+                None,
+                "auth:fail",
+                ip.into(),
+                data,
+            )
+            .await?;
+        }
+
+        if lockout_after == 0 || failures < lockout_after as i32 {
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE user_login_failures
+            SET locked_until = NOW() + make_interval(secs => $2)
+            WHERE user_login_failures.user_uuid = $1
+            "#,
+        )
+        .bind(user_uuid)
+        .bind(lockout_duration as f64)
+        .execute(database.write())
+        .await?;
+
+        if let Some(ip) = ip {
+            super::user_activity::UserActivity::log(
+                database,
+                user_uuid,
+                None,
+                "auth:lockout",
+                ip.into(),
+                serde_json::json!({
+                    "failures": failures,
+                    "duration": lockout_duration,
+                }),
+            )
+            .await?;
+        }
+
+        super::user_notification::UserNotification::notify(
+            database,
+            user_uuid,
+            super::user_notification::Notification {
+                category: super::user_notification::NotificationCategory::Account,
+                event: "account:lockout",
+                server_uuid: None,
+                title: "Account temporarily locked".into(),
+                body: match ip {
+                    Some(ip) => format!(
+                        "Sign ins to your account were paused for {} minutes after {failures} failed attempts, the last from {ip}.",
+                        lockout_duration.div_ceil(60)
+                    ),
+                    None => format!(
+                        "Sign ins to your account were paused for {} minutes after {failures} failed attempts.",
+                        lockout_duration.div_ceil(60)
+                    ),
+                },
+                data: serde_json::json!({
+                    "ip": ip.map(|ip| ip.to_string()),
+                    "failures": failures,
+                }),
+                mail: None,
+            },
+        )
+        .await;
+
+        Ok(())
+    }
+
+    pub async fn by_user_uuid(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM user_login_failures
+            JOIN users ON users.uuid = user_login_failures.user_uuid
+            LEFT JOIN roles ON roles.uuid = users.role_uuid
+            WHERE user_login_failures.user_uuid = $1
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(user_uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn all_with_pagination(
+        database: &crate::database::Database,
+        page: i64,
+        per_page: i64,
+        search: Option<&str>,
+        locked: bool,
+    ) -> Result<super::Pagination<Self>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER() AS total_count
+            FROM user_login_failures
+            JOIN users ON users.uuid = user_login_failures.user_uuid
+            LEFT JOIN roles ON roles.uuid = users.role_uuid
+            WHERE ($1 IS NULL OR users.username ILIKE '%' || $1 || '%' OR users.email ILIKE '%' || $1 || '%')
+                AND (NOT $2 OR user_login_failures.locked_until > NOW())
+            ORDER BY user_login_failures.last_failure DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(search)
+        .bind(locked)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(super::Pagination {
+            total: rows.first().map_or(0, |row| row.get("total_count")),
+            per_page,
+            page,
+            data: rows.into_iter().map(|row| Self::map(None, &row)).collect(),
+        })
+    }
+
+    /// Resets the counter of the account, returns whether there was anything to reset.
+    pub async fn delete_by_user_uuid(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result =
+            sqlx::query("DELETE FROM user_login_failures WHERE user_login_failures.user_uuid = $1")
+                .bind(user_uuid)
+                .execute(database.write())
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn throttle(&self, settings: &crate::settings::Settings) -> Option<LoginThrottle> {
+        let settings = settings.get().await;
+
+        LoginThrottle::new(
+            &settings.security,
+            self.failures,
+            self.last_failure,
+            self.locked_until,
+        )
+    }
+
+    #[inline]
+    pub fn into_admin_api_object(
+        self,
+        storage_url_retriever: &StorageUrlRetriever<'_>,
+    ) -> AdminApiUserLoginFailure {
+        let now = chrono::Utc::now().naive_utc();
+
+        AdminApiUserLoginFailure {
+            user: self.user.into_api_object(storage_url_retriever),
+            failures: self.failures,
+            last_ip: self.last_ip.map(|ip| ip.ip().to_string()),
+            last_failure: self.last_failure.and_utc(),
+            locked_until: self
+                .locked_until
+                .filter(|locked_until| *locked_until > now)
+                .map(|locked_until| locked_until.and_utc()),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "AdminUserLoginFailure")]
+pub struct AdminApiUserLoginFailure {
+    pub user: super::user::ApiUser,
+
+    pub failures: i32,
+    pub last_ip: Option<String>,
+
+    pub last_failure: chrono::DateTime<chrono::Utc>,
+    pub locked_until: Option<chrono::DateTime<chrono::Utc>>,
+}
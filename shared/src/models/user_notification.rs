@@ -0,0 +1,378 @@
+use super::BaseModel;
+use crate::mail::template::MailTemplateKind;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, Type, postgres::PgRow};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+#[derive(ToSchema, Serialize, Deserialize, Type, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[schema(rename_all = "snake_case")]
+#[sqlx(
+    type_name = "notification_category",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum NotificationCategory {
+    /// Security notices like new sign ins, two-factor changes, new API keys and lockouts.
+    /// They are always mailed and shown in-app, whatever the preferences say.
+    Account,
+    Server,
+    Backup,
+    Transfer,
+}
+
+#[derive(ToSchema, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct NotificationDelivery {
+    pub email: bool,
+    pub in_app: bool,
+}
+
+impl Default for NotificationDelivery {
+    fn default() -> Self {
+        Self {
+            email: true,
+            in_app: true,
+        }
+    }
+}
+
+/// How a user wants to be notified per category, stored as JSON. Missing categories
+/// use both email and in-app delivery, account notices can not be turned off.
+#[derive(ToSchema, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NotificationPreferences {
+    #[serde(default)]
+    pub server: NotificationDelivery,
+    #[serde(default)]
+    pub backup: NotificationDelivery,
+    #[serde(default)]
+    pub transfer: NotificationDelivery,
+}
+
+impl NotificationPreferences {
+    #[inline]
+    pub fn from_value(value: serde_json::Value) -> Self {
+        serde_json::from_value(value).unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn get(&self, category: NotificationCategory) -> NotificationDelivery {
+        match category {
+            NotificationCategory::Account => NotificationDelivery::default(),
+            NotificationCategory::Server => self.server,
+            NotificationCategory::Backup => self.backup,
+            NotificationCategory::Transfer => self.transfer,
+        }
+    }
+
+    pub async fn by_user_uuid(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let value: Option<serde_json::Value> = sqlx::query_scalar(
+            "SELECT users.notification_preferences FROM users WHERE users.uuid = $1",
+        )
+        .bind(user_uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(value.map(Self::from_value))
+    }
+
+    pub async fn save(
+        &self,
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE users SET notification_preferences = $2 WHERE users.uuid = $1")
+            .bind(user_uuid)
+            .bind(serde_json::to_value(self).unwrap())
+            .execute(database.write())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// A notification to deliver according to the preferences of the user.
+pub struct Notification {
+    pub category: NotificationCategory,
+    pub event: &'static str,
+    pub server_uuid: Option<uuid::Uuid>,
+
+    pub title: String,
+    pub body: String,
+    pub data: serde_json::Value,
+
+    /// Mailed when the user wants mails for the category. With a `server_uuid` the
+    /// `server_name` and `server_link` variables are filled in.
+    pub mail: Option<(MailTemplateKind, BTreeMap<&'static str, String>)>,
+}
+
+impl Notification {
+    /// Sent to the owner when a server gets suspended, by an admin or a bulk action.
+    pub fn server_suspended(server: &super::server::Server) -> Self {
+        Self {
+            category: NotificationCategory::Server,
+            event: "server:suspended",
+            server_uuid: Some(server.uuid),
+            title: "Server suspended".into(),
+            body: format!("Your server {} has been suspended.", server.name),
+            data: serde_json::json!({}),
+            mail: Some((MailTemplateKind::ServerSuspended, BTreeMap::new())),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UserNotification {
+    pub uuid: uuid::Uuid,
+    pub server_uuid: Option<uuid::Uuid>,
+
+    pub category: NotificationCategory,
+    pub event: String,
+    pub title: String,
+    pub body: String,
+    pub data: serde_json::Value,
+
+    pub read: Option<chrono::NaiveDateTime>,
+    pub created: chrono::NaiveDateTime,
+}
+
+impl BaseModel for UserNotification {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            ("user_notifications.uuid", format!("{prefix}uuid")),
+            (
+                "user_notifications.server_uuid",
+                format!("{prefix}server_uuid"),
+            ),
+            ("user_notifications.category", format!("{prefix}category")),
+            ("user_notifications.event", format!("{prefix}event")),
+            ("user_notifications.title", format!("{prefix}title")),
+            ("user_notifications.body", format!("{prefix}body")),
+            ("user_notifications.data", format!("{prefix}data")),
+            ("user_notifications.read", format!("{prefix}read")),
+            ("user_notifications.created", format!("{prefix}created")),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            uuid: row.get(format!("{prefix}uuid").as_str()),
+            server_uuid: row.get(format!("{prefix}server_uuid").as_str()),
+            category: row.get(format!("{prefix}category").as_str()),
+            event: row.get(format!("{prefix}event").as_str()),
+            title: row.get(format!("{prefix}title").as_str()),
+            body: row.get(format!("{prefix}body").as_str()),
+            data: row.get(format!("{prefix}data").as_str()),
+            read: row.get(format!("{prefix}read").as_str()),
+            created: row.get(format!("{prefix}created").as_str()),
+        }
+    }
+}
+
+impl UserNotification {
+    /// Stores and mails the notification as the user prefers, failures are only logged.
+    pub async fn notify(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+        notification: Notification,
+    ) {
+        if let Err(err) = Self::try_notify(database, user_uuid, notification).await {
+            tracing::error!(user = %user_uuid, "failed to notify user: {:#?}", err);
+        }
+    }
+
+    async fn try_notify(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+        notification: Notification,
+    ) -> Result<(), sqlx::Error> {
+        let delivery = match NotificationPreferences::by_user_uuid(database, user_uuid).await? {
+            Some(preferences) => preferences.get(notification.category),
+            None => return Ok(()),
+        };
+
+        if delivery.in_app {
+            sqlx::query(
+                r#"
+                INSERT INTO user_notifications (user_uuid, server_uuid, category, event, title, body, data)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(user_uuid)
+            .bind(notification.server_uuid)
+            .bind(notification.category)
+            .bind(notification.event)
+            .bind(&notification.title)
+            .bind(&notification.body)
+            .bind(&notification.data)
+            .execute(database.write())
+            .await?;
+        }
+
+        if delivery.email
+            && let Some((template, variables)) = notification.mail
+        {
+            crate::jobs::enqueue(
+                database,
+                &crate::jobs::mail::SendTemplateMail::new(
+                    database,
+                    user_uuid,
+                    notification.server_uuid,
+                    template,
+                    variables
+                        .into_iter()
+                        .map(|(key, value)| (key.to_string(), value))
+                        .collect(),
+                )?,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn by_user_uuid_uuid(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM user_notifications
+            WHERE user_notifications.user_uuid = $1 AND user_notifications.uuid = $2
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(user_uuid)
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn by_user_uuid_with_pagination(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+        page: i64,
+        per_page: i64,
+        unread: bool,
+    ) -> Result<super::Pagination<Self>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER() AS total_count
+            FROM user_notifications
+            WHERE user_notifications.user_uuid = $1 AND (NOT $2 OR user_notifications.read IS NULL)
+            ORDER BY user_notifications.created DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(user_uuid)
+        .bind(unread)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(super::Pagination {
+            total: rows.first().map_or(0, |row| row.get("total_count")),
+            per_page,
+            page,
+            data: rows.into_iter().map(|row| Self::map(None, &row)).collect(),
+        })
+    }
+
+    pub async fn count_unread_by_user_uuid(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM user_notifications
+            WHERE user_notifications.user_uuid = $1 AND user_notifications.read IS NULL
+            "#,
+        )
+        .bind(user_uuid)
+        .fetch_one(database.read())
+        .await
+    }
+
+    /// Marks the notification, or every notification of the user without one, as read.
+    pub async fn mark_read(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+        uuid: Option<uuid::Uuid>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_notifications
+            SET read = NOW()
+            WHERE user_notifications.user_uuid = $1
+                AND ($2::uuid IS NULL OR user_notifications.uuid = $2)
+                AND user_notifications.read IS NULL
+            "#,
+        )
+        .bind(user_uuid)
+        .bind(uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn delete_by_uuid(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM user_notifications WHERE user_notifications.uuid = $1")
+            .bind(uuid)
+            .execute(database.write())
+            .await?;
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn into_api_object(self) -> ApiUserNotification {
+        ApiUserNotification {
+            uuid: self.uuid,
+            server_uuid: self.server_uuid,
+            category: self.category,
+            event: self.event,
+            title: self.title,
+            body: self.body,
+            data: self.data,
+            read: self.read.map(|read| read.and_utc()),
+            created: self.created.and_utc(),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "UserNotification")]
+pub struct ApiUserNotification {
+    pub uuid: uuid::Uuid,
+    pub server_uuid: Option<uuid::Uuid>,
+
+    pub category: NotificationCategory,
+    pub event: String,
+    pub title: String,
+    pub body: String,
+    pub data: serde_json::Value,
+
+    pub read: Option<chrono::DateTime<chrono::Utc>>,
+    pub created: chrono::DateTime<chrono::Utc>,
+}
@@ -5,9 +5,78 @@ use rand::distr::SampleString;
 use serde::{Deserialize, Serialize};
 use sha2::Digest;
 use sqlx::{Row, postgres::PgRow};
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, HashSet},
+    net::IpAddr,
+};
 use utoipa::ToSchema;
 
+/// What a sign in is compared with earlier ones to tell whether it is a new device.
+#[derive(Clone)]
+pub struct DeviceFingerprint {
+    pub ip: IpAddr,
+    pub asn: Option<u32>,
+    pub user_agent: String,
+}
+
+impl DeviceFingerprint {
+    pub fn new(ip: IpAddr, headers: &axum::http::HeaderMap, asn_header: Option<&str>) -> Self {
+        Self {
+            ip,
+            asn: asn_header
+                .and_then(|header| headers.get(header))
+                .and_then(|asn| asn.to_str().ok())
+                .and_then(|asn| {
+                    let asn = asn.trim();
+                    asn.strip_prefix("AS")
+                        .or_else(|| asn.strip_prefix("as"))
+                        .unwrap_or(asn)
+                        .parse()
+                        .ok()
+                }),
+            user_agent: crate::utils::slice_up_to(
+                headers
+                    .get("User-Agent")
+                    .and_then(|ua| ua.to_str().ok())
+                    .unwrap_or("unknown"),
+                255,
+            )
+            .to_string(),
+        }
+    }
+
+    /// The network of an IP when its ASN is unknown, a /24 for IPv4 and a /48 for IPv6.
+    pub fn network(ip: IpAddr) -> IpAddr {
+        match ip {
+            IpAddr::V4(ip) => {
+                let [a, b, c, _] = ip.octets();
+                IpAddr::from([a, b, c, 0])
+            }
+            IpAddr::V6(ip) => {
+                let [a, b, c, ..] = ip.segments();
+                IpAddr::from([a, b, c, 0, 0, 0, 0, 0])
+            }
+        }
+    }
+
+    /// The user agent without version numbers, so browser updates are not new devices.
+    pub fn user_agent_family(user_agent: &str) -> String {
+        user_agent
+            .split(|c: char| c.is_ascii_digit() || c == '.' || c == '_')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("")
+    }
+}
+
+/// Which parts of a [`DeviceFingerprint`] were never seen for the user before.
+#[derive(Serialize)]
+pub struct NewDevice {
+    pub new_ip: bool,
+    pub new_network: bool,
+    pub new_user_agent: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct UserSession {
     pub uuid: uuid::Uuid,
@@ -78,6 +147,72 @@ impl UserSession {
         Ok(format!("{key_id}:{hash}"))
     }
 
+    /// Compares a sign in with the sessions and successful sign ins of the user. Returns
+    /// `None` for the first sign in of a user and for devices that were seen before.
+    pub async fn detect_new_device(
+        database: &crate::database::Database,
+        user_uuid: uuid::Uuid,
+        fingerprint: &DeviceFingerprint,
+    ) -> Result<Option<NewDevice>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT user_sessions.ip, user_sessions.user_agent, NULL::text AS asn
+            FROM user_sessions
+            WHERE user_sessions.user_uuid = $1
+            UNION ALL
+            (
+                SELECT user_activities.ip, user_activities.data->>'user_agent', user_activities.data->>'asn'
+                FROM user_activities
+                WHERE user_activities.user_uuid = $1 AND user_activities.event = 'auth:success'
+                ORDER BY user_activities.created DESC
+                LIMIT 1000
+            )
+            "#,
+        )
+        .bind(user_uuid)
+        .fetch_all(database.read())
+        .await?;
+
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ips = HashSet::new();
+        let mut networks = HashSet::new();
+        let mut asns = HashSet::new();
+        let mut user_agents = HashSet::new();
+        for row in rows {
+            let ip: sqlx::types::ipnetwork::IpNetwork = row.get("ip");
+            ips.insert(ip.ip());
+            networks.insert(DeviceFingerprint::network(ip.ip()));
+
+            if let Some(asn) = row.get::<Option<String>, _>("asn") {
+                asns.insert(asn);
+            }
+            if let Some(user_agent) = row.get::<Option<String>, _>("user_agent") {
+                user_agents.insert(DeviceFingerprint::user_agent_family(&user_agent));
+            }
+        }
+
+        let new_device = NewDevice {
+            new_ip: !ips.contains(&fingerprint.ip),
+            new_network: !networks.contains(&DeviceFingerprint::network(fingerprint.ip))
+                && !fingerprint
+                    .asn
+                    .is_some_and(|asn| asns.contains(&asn.to_string())),
+            new_user_agent: !user_agents.is_empty()
+                && !user_agents.contains(&DeviceFingerprint::user_agent_family(
+                    &fingerprint.user_agent,
+                )),
+        };
+
+        Ok(if new_device.new_network || new_device.new_user_agent {
+            Some(new_device)
+        } else {
+            None
+        })
+    }
+
     pub async fn by_user_uuid_uuid(
         database: &crate::database::Database,
         user_uuid: uuid::Uuid,
@@ -0,0 +1,272 @@
+use super::BaseModel;
+use rand::distr::SampleString;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, postgres::PgRow};
+use std::{collections::BTreeMap, sync::LazyLock};
+use utoipa::ToSchema;
+use validator::ValidationError;
+
+pub type GetWebhook = crate::extract::ConsumingExtension<Webhook>;
+
+pub static EVENT_FILTER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z0-9*:.-]+$").expect("Failed to compile event filter regex"));
+
+pub fn validate_event_filters(events: &[String]) -> Result<(), ValidationError> {
+    for event in events {
+        if event.is_empty() || event.len() > 255 || !EVENT_FILTER_REGEX.is_match(event) {
+            return Err(ValidationError::new("events")
+                .with_message(format!("invalid event filter: {event}").into()));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Webhook {
+    pub uuid: uuid::Uuid,
+
+    pub name: String,
+    pub description: Option<String>,
+
+    pub url: String,
+    pub secret: Vec<u8>,
+    pub events: Vec<String>,
+    pub enabled: bool,
+
+    pub created: chrono::NaiveDateTime,
+}
+
+impl BaseModel for Webhook {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            ("webhooks.uuid", format!("{prefix}uuid")),
+            ("webhooks.name", format!("{prefix}name")),
+            ("webhooks.description", format!("{prefix}description")),
+            ("webhooks.url", format!("{prefix}url")),
+            ("webhooks.secret", format!("{prefix}secret")),
+            ("webhooks.events", format!("{prefix}events")),
+            ("webhooks.enabled", format!("{prefix}enabled")),
+            ("webhooks.created", format!("{prefix}created")),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            uuid: row.get(format!("{prefix}uuid").as_str()),
+            name: row.get(format!("{prefix}name").as_str()),
+            description: row.get(format!("{prefix}description").as_str()),
+            url: row.get(format!("{prefix}url").as_str()),
+            secret: row.get(format!("{prefix}secret").as_str()),
+            events: row.get(format!("{prefix}events").as_str()),
+            enabled: row.get(format!("{prefix}enabled").as_str()),
+            created: row.get(format!("{prefix}created").as_str()),
+        }
+    }
+}
+
+impl Webhook {
+    pub async fn create(
+        database: &crate::database::Database,
+        name: &str,
+        description: Option<&str>,
+        url: &str,
+        secret: Option<&str>,
+        events: &[String],
+        enabled: bool,
+    ) -> Result<Self, sqlx::Error> {
+        let secret = match secret {
+            Some(secret) => secret.to_string(),
+            None => rand::distr::Alphanumeric.sample_string(&mut rand::rng(), 48),
+        };
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO webhooks (name, description, url, secret, events, enabled)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {}
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(name)
+        .bind(description)
+        .bind(url)
+        .bind(
+            database
+                .encrypt(secret)
+                .ok_or_else(|| sqlx::Error::Encode("failed to encrypt webhook secret".into()))?,
+        )
+        .bind(events)
+        .bind(enabled)
+        .fetch_one(database.write())
+        .await?;
+
+        Ok(Self::map(None, &row))
+    }
+
+    pub async fn by_uuid(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM webhooks
+            WHERE webhooks.uuid = $1
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn all_with_pagination(
+        database: &crate::database::Database,
+        page: i64,
+        per_page: i64,
+        search: Option<&str>,
+    ) -> Result<super::Pagination<Self>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER() AS total_count
+            FROM webhooks
+            WHERE ($1 IS NULL OR webhooks.name ILIKE '%' || $1 || '%' OR webhooks.url ILIKE '%' || $1 || '%')
+            ORDER BY webhooks.created
+            LIMIT $2 OFFSET $3
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(search)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(super::Pagination {
+            total: rows.first().map_or(0, |row| row.get("total_count")),
+            per_page,
+            page,
+            data: rows.into_iter().map(|row| Self::map(None, &row)).collect(),
+        })
+    }
+
+    pub async fn delete_by_uuid(
+        database: &crate::database::Database,
+        uuid: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM webhooks
+            WHERE webhooks.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .execute(database.write())
+        .await?;
+
+        Ok(())
+    }
+
+    pub fn decrypted_secret(
+        &self,
+        database: &crate::database::Database,
+    ) -> Result<String, sqlx::Error> {
+        database
+            .decrypt(&self.secret)
+            .ok_or_else(|| sqlx::Error::Decode("failed to decrypt webhook secret".into()))
+    }
+
+    /// Queues a delivery of `payload` for every enabled webhook with an event filter matching `event`.
+    /// Filters use `*` as a wildcard and may omit the scope, so `backup.*` matches `server:backup.create`.
+    ///
+    /// Failures are only logged, webhooks never prevent the activity they report on.
+    pub async fn trigger(
+        database: &crate::database::Database,
+        event: &str,
+        payload: serde_json::Value,
+    ) {
+        if let Err(err) = Self::queue_deliveries(database, event, payload).await {
+            tracing::error!(event, "failed to trigger webhooks: {:#?}", err);
+        }
+    }
+
+    async fn queue_deliveries(
+        database: &crate::database::Database,
+        event: &str,
+        payload: serde_json::Value,
+    ) -> Result<(), sqlx::Error> {
+        let mut transaction = database.write().begin().await?;
+
+        let delivery_uuids: Vec<uuid::Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO webhook_deliveries (webhook_uuid, event, payload)
+            SELECT webhooks.uuid, $1, $2
+            FROM webhooks
+            WHERE webhooks.enabled AND EXISTS (
+                SELECT 1 FROM UNNEST(webhooks.events) AS filter
+                WHERE $1 LIKE REPLACE(filter, '*', '%') OR SPLIT_PART($1, ':', 2) LIKE REPLACE(filter, '*', '%')
+            )
+            RETURNING webhook_deliveries.uuid
+            "#,
+        )
+        .bind(event)
+        .bind(payload)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        for delivery_uuid in delivery_uuids {
+            crate::jobs::enqueue_with(
+                &mut *transaction,
+                &crate::jobs::webhook::DeliverWebhook { delivery_uuid },
+                std::time::Duration::ZERO,
+            )
+            .await?;
+        }
+
+        transaction.commit().await
+    }
+
+    #[inline]
+    pub fn into_admin_api_object(self) -> AdminApiWebhook {
+        AdminApiWebhook {
+            uuid: self.uuid,
+            name: self.name,
+            description: self.description,
+            url: self.url,
+            secret: None,
+            events: self.events,
+            enabled: self.enabled,
+            created: self.created.and_utc(),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "Webhook")]
+pub struct AdminApiWebhook {
+    pub uuid: uuid::Uuid,
+
+    pub name: String,
+    pub description: Option<String>,
+
+    #[schema(format = "uri")]
+    pub url: String,
+    /// Only returned when the webhook is created, `null` afterwards.
+    pub secret: Option<String>,
+    pub events: Vec<String>,
+    pub enabled: bool,
+
+    pub created: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,377 @@
+use super::BaseModel;
+use hmac::Mac;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, postgres::PgRow};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// The maximum amount of attempts before a delivery is given up on.
+pub const MAX_ATTEMPTS: i32 = 8;
+/// Successful deliveries are pruned once they are older than this many days.
+pub const RETENTION_DAYS: i32 = 30;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WebhookDelivery {
+    pub uuid: uuid::Uuid,
+    pub webhook_uuid: uuid::Uuid,
+
+    pub event: String,
+    pub payload: serde_json::Value,
+
+    pub attempts: i32,
+    pub successful: bool,
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+
+    pub next_attempt: Option<chrono::NaiveDateTime>,
+    pub last_attempt: Option<chrono::NaiveDateTime>,
+    pub created: chrono::NaiveDateTime,
+}
+
+impl BaseModel for WebhookDelivery {
+    #[inline]
+    fn columns(prefix: Option<&str>) -> BTreeMap<&'static str, String> {
+        let prefix = prefix.unwrap_or_default();
+
+        BTreeMap::from([
+            ("webhook_deliveries.uuid", format!("{prefix}uuid")),
+            (
+                "webhook_deliveries.webhook_uuid",
+                format!("{prefix}webhook_uuid"),
+            ),
+            ("webhook_deliveries.event", format!("{prefix}event")),
+            ("webhook_deliveries.payload", format!("{prefix}payload")),
+            ("webhook_deliveries.attempts", format!("{prefix}attempts")),
+            (
+                "webhook_deliveries.successful",
+                format!("{prefix}successful"),
+            ),
+            (
+                "webhook_deliveries.response_status",
+                format!("{prefix}response_status"),
+            ),
+            (
+                "webhook_deliveries.response_body",
+                format!("{prefix}response_body"),
+            ),
+            ("webhook_deliveries.error", format!("{prefix}error")),
+            (
+                "webhook_deliveries.next_attempt",
+                format!("{prefix}next_attempt"),
+            ),
+            (
+                "webhook_deliveries.last_attempt",
+                format!("{prefix}last_attempt"),
+            ),
+            ("webhook_deliveries.created", format!("{prefix}created")),
+        ])
+    }
+
+    #[inline]
+    fn map(prefix: Option<&str>, row: &PgRow) -> Self {
+        let prefix = prefix.unwrap_or_default();
+
+        Self {
+            uuid: row.get(format!("{prefix}uuid").as_str()),
+            webhook_uuid: row.get(format!("{prefix}webhook_uuid").as_str()),
+            event: row.get(format!("{prefix}event").as_str()),
+            payload: row.get(format!("{prefix}payload").as_str()),
+            attempts: row.get(format!("{prefix}attempts").as_str()),
+            successful: row.get(format!("{prefix}successful").as_str()),
+            response_status: row.get(format!("{prefix}response_status").as_str()),
+            response_body: row.get(format!("{prefix}response_body").as_str()),
+            error: row.get(format!("{prefix}error").as_str()),
+            next_attempt: row.get(format!("{prefix}next_attempt").as_str()),
+            last_attempt: row.get(format!("{prefix}last_attempt").as_str()),
+            created: row.get(format!("{prefix}created").as_str()),
+        }
+    }
+}
+
+impl WebhookDelivery {
+    /// Creates a delivery and queues sending it in one transaction.
+    pub async fn create(
+        database: &crate::database::Database,
+        webhook_uuid: uuid::Uuid,
+        event: &str,
+        payload: serde_json::Value,
+    ) -> Result<Self, sqlx::Error> {
+        let mut transaction = database.write().begin().await?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO webhook_deliveries (webhook_uuid, event, payload)
+            VALUES ($1, $2, $3)
+            RETURNING {}
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(webhook_uuid)
+        .bind(event)
+        .bind(payload)
+        .fetch_one(&mut *transaction)
+        .await?;
+        let delivery = Self::map(None, &row);
+
+        crate::jobs::enqueue_with(
+            &mut *transaction,
+            &crate::jobs::webhook::DeliverWebhook {
+                delivery_uuid: delivery.uuid,
+            },
+            std::time::Duration::ZERO,
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(delivery)
+    }
+
+    pub async fn by_webhook_uuid_uuid(
+        database: &crate::database::Database,
+        webhook_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM webhook_deliveries
+            WHERE webhook_deliveries.webhook_uuid = $1 AND webhook_deliveries.uuid = $2
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(webhook_uuid)
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        Ok(row.map(|row| Self::map(None, &row)))
+    }
+
+    pub async fn by_webhook_uuid_with_pagination(
+        database: &crate::database::Database,
+        webhook_uuid: uuid::Uuid,
+        page: i64,
+        per_page: i64,
+        search: Option<&str>,
+    ) -> Result<super::Pagination<Self>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER() AS total_count
+            FROM webhook_deliveries
+            WHERE webhook_deliveries.webhook_uuid = $1 AND ($2 IS NULL OR webhook_deliveries.event ILIKE '%' || $2 || '%')
+            ORDER BY webhook_deliveries.created DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(webhook_uuid)
+        .bind(search)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(database.read())
+        .await?;
+
+        Ok(super::Pagination {
+            total: rows.first().map_or(0, |row| row.get("total_count")),
+            per_page,
+            page,
+            data: rows.into_iter().map(|row| Self::map(None, &row)).collect(),
+        })
+    }
+
+    /// Sends the delivery unless it is already completed or given up on. Failed attempts
+    /// queue the next one with an exponential backoff until [MAX_ATTEMPTS] is reached.
+    pub async fn deliver(
+        database: &crate::database::Database,
+        client: &reqwest::Client,
+        uuid: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}, webhooks.url AS webhook_url, webhooks.secret AS webhook_secret,
+                webhooks.enabled AS webhook_enabled
+            FROM webhook_deliveries
+            JOIN webhooks ON webhooks.uuid = webhook_deliveries.webhook_uuid
+            WHERE webhook_deliveries.uuid = $1 AND webhook_deliveries.next_attempt IS NOT NULL
+            "#,
+            Self::columns_sql(None)
+        ))
+        .bind(uuid)
+        .fetch_optional(database.read())
+        .await?;
+
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(()),
+        };
+
+        let delivery = Self::map(None, &row);
+        let url: String = row.get("webhook_url");
+        let secret: Vec<u8> = row.get("webhook_secret");
+        let enabled: bool = row.get("webhook_enabled");
+
+        let result = if !enabled {
+            Err("webhook is disabled".to_string())
+        } else {
+            match database.decrypt(secret) {
+                Some(secret) => delivery
+                    .send(client, &url, &secret)
+                    .await
+                    .map_err(|err| err.to_string()),
+                None => Err("failed to decrypt webhook secret".to_string()),
+            }
+        };
+
+        delivery.record_attempt(database, result).await
+    }
+
+    /// Deletes successful deliveries older than [RETENTION_DAYS], returning how many were deleted.
+    pub async fn prune(database: &crate::database::Database) -> Result<u64, sqlx::Error> {
+        let deleted = sqlx::query(
+            r#"
+            DELETE FROM webhook_deliveries
+            WHERE webhook_deliveries.successful
+                AND webhook_deliveries.created < NOW() - make_interval(days => $1)
+            "#,
+        )
+        .bind(RETENTION_DAYS)
+        .execute(database.write())
+        .await?
+        .rows_affected();
+
+        Ok(deleted)
+    }
+
+    async fn send(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        secret: &str,
+    ) -> Result<(u16, String), reqwest::Error> {
+        let body = serde_json::to_vec(&self.payload).unwrap_or_default();
+
+        let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(&body);
+        let signature = format!("{:x}", mac.finalize().into_bytes());
+
+        let response = client
+            .post(url)
+            .timeout(std::time::Duration::from_secs(10))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-Webhook-Event", &self.event)
+            .header("X-Webhook-Delivery", self.uuid.to_string())
+            .header("X-Webhook-Signature", format!("sha256={signature}"))
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status().as_u16();
+        let body = response.text().await.unwrap_or_default();
+
+        Ok((status, body))
+    }
+
+    async fn record_attempt(
+        &self,
+        database: &crate::database::Database,
+        result: Result<(u16, String), String>,
+    ) -> Result<(), sqlx::Error> {
+        let attempts = self.attempts + 1;
+
+        let (successful, status, body, error) = match result {
+            Ok((status, body)) => (
+                (200..300).contains(&status),
+                Some(status as i32),
+                Some(crate::utils::slice_up_to(&body, 2048).to_string()),
+                None,
+            ),
+            Err(err) => (false, None, None, Some(err)),
+        };
+
+        let backoff = if successful || attempts >= MAX_ATTEMPTS {
+            None
+        } else {
+            Some(std::time::Duration::from_secs(
+                30 * 2u64.pow(attempts as u32 - 1),
+            ))
+        };
+        let next_attempt = backoff.map(|backoff| {
+            chrono::Utc::now().naive_utc() + chrono::Duration::from_std(backoff).unwrap_or_default()
+        });
+
+        let mut transaction = database.write().begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET attempts = $2, successful = $3, response_status = $4, response_body = $5, error = $6,
+                next_attempt = $7, last_attempt = NOW()
+            WHERE webhook_deliveries.uuid = $1
+            "#,
+        )
+        .bind(self.uuid)
+        .bind(attempts)
+        .bind(successful)
+        .bind(status)
+        .bind(body)
+        .bind(error)
+        .bind(next_attempt)
+        .execute(&mut *transaction)
+        .await?;
+
+        if let Some(backoff) = backoff {
+            crate::jobs::enqueue_with(
+                &mut *transaction,
+                &crate::jobs::webhook::DeliverWebhook {
+                    delivery_uuid: self.uuid,
+                },
+                backoff,
+            )
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn into_admin_api_object(self) -> AdminApiWebhookDelivery {
+        AdminApiWebhookDelivery {
+            uuid: self.uuid,
+            event: self.event,
+            payload: self.payload,
+            attempts: self.attempts,
+            successful: self.successful,
+            response_status: self.response_status,
+            response_body: self.response_body,
+            error: self.error,
+            next_attempt: self.next_attempt.map(|dt| dt.and_utc()),
+            last_attempt: self.last_attempt.map(|dt| dt.and_utc()),
+            created: self.created.and_utc(),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+#[schema(title = "WebhookDelivery")]
+pub struct AdminApiWebhookDelivery {
+    pub uuid: uuid::Uuid,
+
+    pub event: String,
+    pub payload: serde_json::Value,
+
+    pub attempts: i32,
+    pub successful: bool,
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+
+    pub next_attempt: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_attempt: Option<chrono::DateTime<chrono::Utc>>,
+    pub created: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,316 @@
+use base64::Engine;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, jwk::JwkSet};
+use serde::Deserialize;
+use sha2::Digest;
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, RwLock},
+    time::{Duration, Instant},
+};
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .user_agent(format!("github.com/calagopus-rs/panel {}", crate::VERSION))
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .expect("Failed to create HTTP client")
+});
+
+/// A key set and the time it was fetched.
+type CachedJwks = (Instant, Arc<JwkSet>);
+
+/// Key sets by `jwks_uri`.
+static JWKS: LazyLock<RwLock<HashMap<String, CachedJwks>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+const JWKS_TTL: Duration = Duration::from_secs(60 * 60);
+/// Unknown key ids refetch the key set at most this often, to pick up rotated keys.
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// ID tokens have to be signed with one of these, `none` and shared secret algorithms are refused.
+const ALLOWED_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+#[derive(Deserialize)]
+pub struct OidcDiscovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub userinfo_endpoint: Option<String>,
+    pub jwks_uri: String,
+    #[serde(default)]
+    pub id_token_signing_alg_values_supported: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    id_token: String,
+}
+
+/// The merged claims of a validated ID token and the userinfo endpoint.
+pub struct OidcClaims(serde_json::Map<String, serde_json::Value>);
+
+impl OidcClaims {
+    /// Looks up a claim, nested claims can be addressed with dots (`realm_access.roles`).
+    pub fn get(&self, path: &str) -> Option<&serde_json::Value> {
+        let mut parts = path.split('.');
+        let mut value = self.0.get(parts.next()?)?;
+
+        for part in parts {
+            value = value.as_object()?.get(part)?;
+        }
+
+        Some(value)
+    }
+
+    #[inline]
+    pub fn get_str(&self, path: &str) -> Option<&str> {
+        self.get(path)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+    }
+
+    /// Returns a list claim, single strings are treated as a list of one.
+    pub fn get_strings(&self, path: &str) -> Vec<&str> {
+        match self.get(path) {
+            Some(serde_json::Value::Array(values)) => {
+                values.iter().filter_map(|v| v.as_str()).collect()
+            }
+            Some(serde_json::Value::String(value)) => vec![value.as_str()],
+            _ => Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn subject(&self) -> &str {
+        self.get_str("sub").unwrap_or_default()
+    }
+}
+
+pub struct OidcClient {
+    settings: crate::settings::AppSettingsOidc,
+    discovery: OidcDiscovery,
+}
+
+impl OidcClient {
+    pub async fn discover(
+        settings: crate::settings::AppSettingsOidc,
+    ) -> Result<Self, anyhow::Error> {
+        if !settings.enabled {
+            return Err(anyhow::anyhow!("oidc is not enabled"));
+        }
+
+        let issuer = settings.issuer.trim_end_matches('/');
+        let discovery: OidcDiscovery = CLIENT
+            .get(format!("{issuer}/.well-known/openid-configuration"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if discovery.issuer.trim_end_matches('/') != issuer {
+            return Err(anyhow::anyhow!(
+                "oidc discovery issuer mismatch: expected {issuer}, got {}",
+                discovery.issuer
+            ));
+        }
+
+        Ok(Self {
+            settings,
+            discovery,
+        })
+    }
+
+    #[inline]
+    pub fn settings(&self) -> &crate::settings::AppSettingsOidc {
+        &self.settings
+    }
+
+    pub fn authorization_url(
+        &self,
+        redirect_uri: &str,
+        state: &str,
+        nonce: &str,
+        code_verifier: &str,
+    ) -> Result<String, anyhow::Error> {
+        let code_challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(sha2::Sha256::digest(code_verifier.as_bytes()));
+
+        let mut url = reqwest::Url::parse(&self.discovery.authorization_endpoint)?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.settings.client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("scope", &self.settings.scopes)
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", &code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    /// Exchanges an authorization code and validates the returned ID token, its signature
+    /// against the keys of the provider as well as its issuer, audience, expiry and nonce.
+    pub async fn exchange_code(
+        &self,
+        redirect_uri: &str,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<OidcClaims, anyhow::Error> {
+        let response = CLIENT
+            .post(&self.discovery.token_endpoint)
+            .basic_auth(
+                urlencoding::encode(&self.settings.client_id),
+                Some(urlencoding::encode(&self.settings.client_secret)),
+            )
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", redirect_uri),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "oidc token endpoint returned {}: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            ));
+        }
+
+        let tokens: TokenResponse = response.json().await?;
+        let mut claims = self.validate_id_token(&tokens.id_token, nonce).await?;
+
+        if let Some(userinfo_endpoint) = &self.discovery.userinfo_endpoint {
+            let userinfo: serde_json::Map<String, serde_json::Value> = CLIENT
+                .get(userinfo_endpoint)
+                .bearer_auth(&tokens.access_token)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            if userinfo.get("sub").and_then(|s| s.as_str()) != Some(claims.subject()) {
+                return Err(anyhow::anyhow!("oidc userinfo subject mismatch"));
+            }
+
+            claims.0.extend(userinfo);
+        }
+
+        Ok(claims)
+    }
+
+    /// Returns the signing keys of the provider, refetching them when they are older than
+    /// `max_age`.
+    async fn jwks(&self, max_age: Duration) -> Result<Arc<JwkSet>, anyhow::Error> {
+        if let Some((fetched, jwks)) = JWKS.read().unwrap().get(&self.discovery.jwks_uri)
+            && fetched.elapsed() < max_age
+        {
+            return Ok(jwks.clone());
+        }
+
+        let jwks: Arc<JwkSet> = Arc::new(
+            CLIENT
+                .get(&self.discovery.jwks_uri)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?,
+        );
+
+        JWKS.write().unwrap().insert(
+            self.discovery.jwks_uri.clone(),
+            (Instant::now(), jwks.clone()),
+        );
+
+        Ok(jwks)
+    }
+
+    async fn validate_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<OidcClaims, anyhow::Error> {
+        let header = jsonwebtoken::decode_header(id_token)?;
+
+        // RS256 is the default of providers that do not advertise their algorithms
+        let advertised = |alg: &Algorithm| {
+            let name = format!("{alg:?}");
+
+            if self
+                .discovery
+                .id_token_signing_alg_values_supported
+                .is_empty()
+            {
+                name == "RS256"
+            } else {
+                self.discovery
+                    .id_token_signing_alg_values_supported
+                    .contains(&name)
+            }
+        };
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) || !advertised(&header.alg) {
+            return Err(anyhow::anyhow!(
+                "oidc id token is signed with an unsupported algorithm: {:?}",
+                header.alg
+            ));
+        }
+
+        let find_key = |jwks: &JwkSet| match &header.kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+        let jwk = match find_key(&*self.jwks(JWKS_TTL).await?) {
+            Some(jwk) => jwk,
+            None => find_key(&*self.jwks(JWKS_REFRESH_INTERVAL).await?)
+                .ok_or_else(|| anyhow::anyhow!("oidc id token is signed with an unknown key"))?,
+        };
+        if jwk
+            .common
+            .key_algorithm
+            .is_some_and(|alg| alg.to_string() != format!("{:?}", header.alg))
+        {
+            return Err(anyhow::anyhow!(
+                "oidc id token algorithm does not match its key"
+            ));
+        }
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = 60;
+        validation.set_issuer(&[&self.discovery.issuer]);
+        validation.set_audience(&[&self.settings.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = OidcClaims(
+            jsonwebtoken::decode(id_token, &DecodingKey::from_jwk(&jwk)?, &validation)?.claims,
+        );
+
+        if claims.get_str("nonce") != Some(nonce) {
+            return Err(anyhow::anyhow!("oidc id token nonce mismatch"));
+        }
+
+        if claims.subject().is_empty() {
+            return Err(anyhow::anyhow!("oidc id token has no subject"));
+        }
+
+        Ok(claims)
+    }
+}
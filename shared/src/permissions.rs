@@ -72,9 +72,23 @@ pub static USER_PERMISSIONS: LazyLock<PermissionMap> = LazyLock::new(|| {
                         "avatar",
                         "Allows to update and remove the avatar of the account.",
                     ),
+                    (
+                        "oidc",
+                        "Allows to link and unlink a single sign-on identity.",
+                    ),
                 ]),
             },
         ),
+        (
+            "servers",
+            PermissionGroup {
+                description: "Permissions that control the ability to create servers from deployable eggs.",
+                permissions: IndexMap::from([(
+                    "create",
+                    "Allows to create new servers within the quotas of the account.",
+                )]),
+            },
+        ),
         (
             "api-keys",
             PermissionGroup {
@@ -133,6 +147,23 @@ pub static USER_PERMISSIONS: LazyLock<PermissionMap> = LazyLock::new(|| {
                 ]),
             },
         ),
+        (
+            "notifications",
+            PermissionGroup {
+                description: "Permissions that control the ability to manage notifications on an account.",
+                permissions: IndexMap::from([
+                    (
+                        "read",
+                        "Allows to view notifications and notification preferences for the account.",
+                    ),
+                    (
+                        "update",
+                        "Allows to mark notifications as read and change notification preferences.",
+                    ),
+                    ("delete", "Allows to delete notifications from the account."),
+                ]),
+            },
+        ),
         (
             "activity",
             PermissionGroup {
@@ -166,6 +197,19 @@ pub static ADMIN_PERMISSIONS: LazyLock<PermissionMap> = LazyLock::new(|| {
                 ]),
             },
         ),
+        (
+            "mail-templates",
+            PermissionGroup {
+                description: "Permissions that control the ability to manage mail templates for the panel.",
+                permissions: IndexMap::from([
+                    ("read", "Allows to view mail templates and their overrides."),
+                    (
+                        "update",
+                        "Allows to create, modify and delete mail template overrides.",
+                    ),
+                ]),
+            },
+        ),
         (
             "users",
             PermissionGroup {
@@ -175,6 +219,10 @@ pub static ADMIN_PERMISSIONS: LazyLock<PermissionMap> = LazyLock::new(|| {
                     ("read", "Allows to view users for the panel."),
                     ("update", "Allows to modify users."),
                     ("disable-two-factor", "Allows to remove users' two-factor."),
+                    (
+                        "clear-lockout",
+                        "Allows to clear users' failed sign in attempts and lockouts.",
+                    ),
                     ("delete", "Allows to delete users from the panel."),
                 ]),
             },
@@ -266,6 +314,10 @@ pub static ADMIN_PERMISSIONS: LazyLock<PermissionMap> = LazyLock::new(|| {
                     ("update", "Allows to modify servers."),
                     ("delete", "Allows to delete servers from the panel."),
                     ("transfer", "Allows to transfer servers to other nodes."),
+                    (
+                        "clone",
+                        "Allows to clone servers, including their schedules and optionally subusers and databases.",
+                    ),
                     (
                         "allocations",
                         "Allows to view and manage a servers' allocations.",
@@ -275,6 +327,26 @@ pub static ADMIN_PERMISSIONS: LazyLock<PermissionMap> = LazyLock::new(|| {
                         "Allows to view and manage a servers' variables.",
                     ),
                     ("mounts", "Allows to view and manage a servers' mounts."),
+                    (
+                        "bulk",
+                        "Allows to run actions on many servers at once and view their progress.",
+                    ),
+                ]),
+            },
+        ),
+        (
+            "server-templates",
+            PermissionGroup {
+                description: "Permissions that control the ability to manage server templates for the panel.",
+                permissions: IndexMap::from([
+                    ("create", "Allows to create new server templates."),
+                    ("read", "Allows to view server templates."),
+                    ("update", "Allows to modify server templates."),
+                    ("delete", "Allows to delete server templates."),
+                    (
+                        "apply",
+                        "Allows to apply server templates to the servers created from them.",
+                    ),
                 ]),
             },
         ),
@@ -303,6 +375,10 @@ pub static ADMIN_PERMISSIONS: LazyLock<PermissionMap> = LazyLock::new(|| {
                     ("update", "Allows to modify eggs."),
                     ("delete", "Allows to delete eggs from the panel."),
                     ("mounts", "Allows to view and manage an eggs' mounts."),
+                    (
+                        "sources",
+                        "Allows to view, manage and sync the egg sources of nests.",
+                    ),
                 ]),
             },
         ),
@@ -334,6 +410,30 @@ pub static ADMIN_PERMISSIONS: LazyLock<PermissionMap> = LazyLock::new(|| {
                 ]),
             },
         ),
+        (
+            "webhooks",
+            PermissionGroup {
+                description: "Permissions that control the ability to manage webhooks for the panel.",
+                permissions: IndexMap::from([
+                    ("create", "Allows to create new webhooks for the panel."),
+                    ("read", "Allows to view webhooks and their deliveries."),
+                    ("update", "Allows to modify webhooks."),
+                    ("delete", "Allows to delete webhooks from the panel."),
+                    ("redeliver", "Allows to redeliver past webhook deliveries."),
+                ]),
+            },
+        ),
+        (
+            "jobs",
+            PermissionGroup {
+                description: "Permissions that control the ability to manage queued background jobs.",
+                permissions: IndexMap::from([
+                    ("read", "Allows to view queued and failed background jobs."),
+                    ("retry", "Allows to retry failed background jobs."),
+                    ("delete", "Allows to delete background jobs."),
+                ]),
+            },
+        ),
         (
             "activity",
             PermissionGroup {
@@ -422,6 +522,10 @@ pub static SERVER_PERMISSIONS: LazyLock<PermissionMap> = LazyLock::new(|| {
                     ("read", "Allows to view existing backups for the server."),
                     ("download", "Allows to download a backup for the server."),
                     ("restore", "Allows to restore a backup for the server."),
+                    (
+                        "verify",
+                        "Allows to verify the integrity of a backup for the server.",
+                    ),
                     (
                         "update",
                         "Allows to update an existing backup for the server.",
@@ -35,11 +35,51 @@ pub enum MailMode {
         password: Option<String>,
         use_tls: bool,
 
+        from_address: String,
+        from_name: Option<String>,
+    },
+    /// Pipes every mail into `MAIL_SENDMAIL_COMMAND` from the environment, `sendmail` if unset.
+    Sendmail {
+        from_address: String,
+        from_name: Option<String>,
+    },
+    /// Writes every mail as an `.eml` file into the `MAIL_FILE_DIRECTORY` from the environment,
+    /// meant for development.
+    File {
+        from_address: String,
+        from_name: Option<String>,
+    },
+    Http {
+        provider: MailHttpProvider,
+        endpoint: String,
+        api_key: String,
+
         from_address: String,
         from_name: Option<String>,
     },
 }
 
+#[derive(ToSchema, Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MailHttpProvider {
+    /// Posts a form to `{endpoint}/messages` using basic auth with the `api` user.
+    Mailgun,
+    /// Posts to `{endpoint}/email` using the `X-Postmark-Server-Token` header.
+    Postmark,
+    /// Posts `{from, to, subject, html}` as JSON to `endpoint` using a bearer token.
+    Json,
+}
+
+impl MailHttpProvider {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Mailgun => "mailgun",
+            Self::Postmark => "postmark",
+            Self::Json => "json",
+        }
+    }
+}
+
 #[derive(ToSchema, Serialize, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum CaptchaProvider {
@@ -162,6 +202,8 @@ pub struct AppSettingsServer {
 
     pub allow_overwriting_custom_docker_image: bool,
     pub allow_editing_startup_command: bool,
+
+    pub verify_backups_automatically: bool,
 }
 
 impl AppSettingsServer {
@@ -177,6 +219,8 @@ impl AppSettingsServer {
         values.push(self.allow_overwriting_custom_docker_image.to_string());
         keys.push("server::allow_editing_startup_command");
         values.push(self.allow_editing_startup_command.to_string());
+        keys.push("server::verify_backups_automatically");
+        values.push(self.verify_backups_automatically.to_string());
 
         (keys, values)
     }
@@ -200,6 +244,341 @@ impl AppSettingsServer {
                 .remove("server::allow_editing_startup_command")
                 .map(|s| s == "true")
                 .unwrap_or(false),
+
+            verify_backups_automatically: map
+                .remove("server::verify_backups_automatically")
+                .map(|s| s == "true")
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[schema(rename_all = "snake_case")]
+pub enum NewDeviceVerification {
+    None,
+    /// The new device has to enter a code mailed to the user.
+    Email,
+    /// Users with two-factor authentication pass by completing it, others confirm by email.
+    TwoFactor,
+}
+
+impl NewDeviceVerification {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Email => "email",
+            Self::TwoFactor => "two_factor",
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize, Deserialize)]
+pub struct AppSettingsSecurity {
+    pub new_device_notifications: bool,
+    /// Applies to password, passkey and OIDC sign ins.
+    pub new_device_verification: NewDeviceVerification,
+    /// Header set by a trusted reverse proxy with the ASN of the client, like `AS13335`.
+    /// A known ASN counts as a known network, so the proxy has to overwrite the header on
+    /// every request, otherwise clients can send a familiar ASN themselves to skip new device
+    /// checks. Leave it unset when the panel is reachable without that proxy.
+    pub asn_header: Option<String>,
+
+    /// Failed attempts of an account before further attempts are delayed, 0 disables delays.
+    pub login_delay_after: u32,
+    /// Upper bound of the delay in seconds, it doubles with every further failure.
+    pub login_max_delay: u64,
+    /// Failed attempts of an account before it is locked, 0 disables lockouts.
+    pub login_lockout_after: u32,
+    /// Seconds an account stays locked, and after which failed attempts are forgotten.
+    pub login_lockout_duration: u64,
+}
+
+impl AppSettingsSecurity {
+    pub fn serialize(&self) -> (Vec<&'static str>, Vec<String>) {
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+
+        keys.push("security::new_device_notifications");
+        values.push(self.new_device_notifications.to_string());
+        keys.push("security::new_device_verification");
+        values.push(self.new_device_verification.as_str().to_string());
+        keys.push("security::asn_header");
+        values.push(self.asn_header.clone().unwrap_or_default());
+        keys.push("security::login_delay_after");
+        values.push(self.login_delay_after.to_string());
+        keys.push("security::login_max_delay");
+        values.push(self.login_max_delay.to_string());
+        keys.push("security::login_lockout_after");
+        values.push(self.login_lockout_after.to_string());
+        keys.push("security::login_lockout_duration");
+        values.push(self.login_lockout_duration.to_string());
+
+        (keys, values)
+    }
+
+    pub fn deserialize(map: &mut HashMap<String, String>) -> Self {
+        AppSettingsSecurity {
+            new_device_notifications: map
+                .remove("security::new_device_notifications")
+                .map(|s| s == "true")
+                .unwrap_or(true),
+            new_device_verification: match map
+                .remove("security::new_device_verification")
+                .as_deref()
+            {
+                Some("email") => NewDeviceVerification::Email,
+                Some("two_factor") => NewDeviceVerification::TwoFactor,
+                _ => NewDeviceVerification::None,
+            },
+            asn_header: map.remove("security::asn_header").filter(|s| !s.is_empty()),
+
+            login_delay_after: map
+                .remove("security::login_delay_after")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+            login_max_delay: map
+                .remove("security::login_max_delay")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+            login_lockout_after: map
+                .remove("security::login_lockout_after")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(10),
+            login_lockout_duration: map
+                .remove("security::login_lockout_duration")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(15 * 60),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize, Deserialize, Clone)]
+pub struct RoleMapping {
+    pub group: String,
+    pub role_uuid: uuid::Uuid,
+}
+
+impl RoleMapping {
+    /// Returns the role of the first mapping whose group matches, if any.
+    pub fn resolve(mappings: &[Self], matches: impl Fn(&str) -> bool) -> Option<uuid::Uuid> {
+        mappings
+            .iter()
+            .find(|mapping| matches(&mapping.group))
+            .map(|mapping| mapping.role_uuid)
+    }
+}
+
+#[derive(ToSchema, Serialize, Deserialize, Clone)]
+pub struct AppSettingsOidc {
+    pub enabled: bool,
+    pub name: String,
+
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: String,
+
+    pub username_claim: String,
+    pub email_claim: String,
+    pub name_first_claim: String,
+    pub name_last_claim: String,
+    pub groups_claim: Option<String>,
+    pub role_mappings: Vec<RoleMapping>,
+
+    pub auto_provision: bool,
+}
+
+impl AppSettingsOidc {
+    /// The provider identities are linked under, subjects are only unique per issuer.
+    #[inline]
+    pub fn identity_provider(&self) -> &str {
+        self.issuer.trim_end_matches('/')
+    }
+
+    pub fn serialize(
+        &self,
+        database: &crate::database::Database,
+    ) -> (Vec<&'static str>, Vec<String>) {
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+
+        keys.push("oidc::enabled");
+        values.push(self.enabled.to_string());
+        keys.push("oidc::name");
+        values.push(self.name.clone());
+        keys.push("oidc::issuer");
+        values.push(self.issuer.clone());
+        keys.push("oidc::client_id");
+        values.push(self.client_id.clone());
+        keys.push("oidc::client_secret");
+        values.push(if self.client_secret.is_empty() {
+            String::new()
+        } else {
+            database
+                .encrypt(&self.client_secret)
+                .map(|b| base32::encode(base32::Alphabet::Z, &b))
+                .unwrap_or_default()
+        });
+        keys.push("oidc::scopes");
+        values.push(self.scopes.clone());
+        keys.push("oidc::username_claim");
+        values.push(self.username_claim.clone());
+        keys.push("oidc::email_claim");
+        values.push(self.email_claim.clone());
+        keys.push("oidc::name_first_claim");
+        values.push(self.name_first_claim.clone());
+        keys.push("oidc::name_last_claim");
+        values.push(self.name_last_claim.clone());
+        keys.push("oidc::groups_claim");
+        values.push(self.groups_claim.clone().unwrap_or_default());
+        keys.push("oidc::role_mappings");
+        values.push(serde_json::to_string(&self.role_mappings).unwrap_or_default());
+        keys.push("oidc::auto_provision");
+        values.push(self.auto_provision.to_string());
+
+        (keys, values)
+    }
+
+    pub fn deserialize(
+        map: &mut HashMap<String, String>,
+        database: &crate::database::Database,
+    ) -> Self {
+        AppSettingsOidc {
+            enabled: map
+                .remove("oidc::enabled")
+                .map(|s| s == "true")
+                .unwrap_or(false),
+            name: map
+                .remove("oidc::name")
+                .unwrap_or_else(|| "Single Sign-On".to_string()),
+            issuer: map.remove("oidc::issuer").unwrap_or_default(),
+            client_id: map.remove("oidc::client_id").unwrap_or_default(),
+            client_secret: map
+                .remove("oidc::client_secret")
+                .and_then(|s| {
+                    if s.is_empty() {
+                        None
+                    } else {
+                        base32::decode(base32::Alphabet::Z, &s).and_then(|b| database.decrypt(&b))
+                    }
+                })
+                .unwrap_or_default(),
+            scopes: map
+                .remove("oidc::scopes")
+                .unwrap_or_else(|| "openid profile email".to_string()),
+            username_claim: map
+                .remove("oidc::username_claim")
+                .unwrap_or_else(|| "preferred_username".to_string()),
+            email_claim: map
+                .remove("oidc::email_claim")
+                .unwrap_or_else(|| "email".to_string()),
+            name_first_claim: map
+                .remove("oidc::name_first_claim")
+                .unwrap_or_else(|| "given_name".to_string()),
+            name_last_claim: map
+                .remove("oidc::name_last_claim")
+                .unwrap_or_else(|| "family_name".to_string()),
+            groups_claim: map.remove("oidc::groups_claim").filter(|s| !s.is_empty()),
+            role_mappings: map
+                .remove("oidc::role_mappings")
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+            auto_provision: map
+                .remove("oidc::auto_provision")
+                .map(|s| s == "true")
+                .unwrap_or(true),
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize, Deserialize, Clone)]
+pub struct AppSettingsLdap {
+    pub enabled: bool,
+
+    pub url: String,
+    pub starttls: bool,
+    pub bind_dn: String,
+    pub search_base: String,
+    pub search_filter: String,
+
+    pub email_attribute: String,
+    pub name_first_attribute: String,
+    pub name_last_attribute: String,
+    pub groups_attribute: Option<String>,
+    pub role_mappings: Vec<RoleMapping>,
+}
+
+impl AppSettingsLdap {
+    pub fn serialize(&self) -> (Vec<&'static str>, Vec<String>) {
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+
+        keys.push("ldap::enabled");
+        values.push(self.enabled.to_string());
+        keys.push("ldap::url");
+        values.push(self.url.clone());
+        keys.push("ldap::starttls");
+        values.push(self.starttls.to_string());
+        keys.push("ldap::bind_dn");
+        values.push(self.bind_dn.clone());
+        keys.push("ldap::search_base");
+        values.push(self.search_base.clone());
+        keys.push("ldap::search_filter");
+        values.push(self.search_filter.clone());
+        keys.push("ldap::email_attribute");
+        values.push(self.email_attribute.clone());
+        keys.push("ldap::name_first_attribute");
+        values.push(self.name_first_attribute.clone());
+        keys.push("ldap::name_last_attribute");
+        values.push(self.name_last_attribute.clone());
+        keys.push("ldap::groups_attribute");
+        values.push(self.groups_attribute.clone().unwrap_or_default());
+        keys.push("ldap::role_mappings");
+        values.push(serde_json::to_string(&self.role_mappings).unwrap_or_default());
+
+        (keys, values)
+    }
+
+    pub fn deserialize(map: &mut HashMap<String, String>) -> Self {
+        AppSettingsLdap {
+            enabled: map
+                .remove("ldap::enabled")
+                .map(|s| s == "true")
+                .unwrap_or(false),
+            url: map
+                .remove("ldap::url")
+                .unwrap_or_else(|| "ldap://localhost:389".to_string()),
+            starttls: map
+                .remove("ldap::starttls")
+                .map(|s| s == "true")
+                .unwrap_or(false),
+            bind_dn: map
+                .remove("ldap::bind_dn")
+                .unwrap_or_else(|| "uid={username},ou=people,dc=example,dc=com".to_string()),
+            search_base: map
+                .remove("ldap::search_base")
+                .unwrap_or_else(|| "dc=example,dc=com".to_string()),
+            search_filter: map
+                .remove("ldap::search_filter")
+                .unwrap_or_else(|| "(uid={username})".to_string()),
+            email_attribute: map
+                .remove("ldap::email_attribute")
+                .unwrap_or_else(|| "mail".to_string()),
+            name_first_attribute: map
+                .remove("ldap::name_first_attribute")
+                .unwrap_or_else(|| "givenName".to_string()),
+            name_last_attribute: map
+                .remove("ldap::name_last_attribute")
+                .unwrap_or_else(|| "sn".to_string()),
+            groups_attribute: map
+                .remove("ldap::groups_attribute")
+                .filter(|s| !s.is_empty()),
+            role_mappings: map
+                .remove("ldap::role_mappings")
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
         }
     }
 }
@@ -216,6 +595,12 @@ pub struct AppSettings {
     pub webauthn: AppSettingsWebauthn,
     #[schema(inline)]
     pub server: AppSettingsServer,
+    #[schema(inline)]
+    pub security: AppSettingsSecurity,
+    #[schema(inline)]
+    pub oidc: AppSettingsOidc,
+    #[schema(inline)]
+    pub ldap: AppSettingsLdap,
 }
 
 impl AppSettings {
@@ -316,6 +701,53 @@ impl AppSettings {
                 keys.push("::mail_smtp_from_name");
                 values.push(from_name.clone().unwrap_or_else(|| "".to_string()));
             }
+            MailMode::Sendmail {
+                from_address,
+                from_name,
+            } => {
+                keys.push("::mail_mode");
+                values.push("sendmail".to_string());
+                keys.push("::mail_sendmail_from_address");
+                values.push(from_address.clone());
+                keys.push("::mail_sendmail_from_name");
+                values.push(from_name.clone().unwrap_or_default());
+            }
+            MailMode::File {
+                from_address,
+                from_name,
+            } => {
+                keys.push("::mail_mode");
+                values.push("file".to_string());
+                keys.push("::mail_file_from_address");
+                values.push(from_address.clone());
+                keys.push("::mail_file_from_name");
+                values.push(from_name.clone().unwrap_or_default());
+            }
+            MailMode::Http {
+                provider,
+                endpoint,
+                api_key,
+                from_address,
+                from_name,
+            } => {
+                keys.push("::mail_mode");
+                values.push("http".to_string());
+                keys.push("::mail_http_provider");
+                values.push(provider.as_str().to_string());
+                keys.push("::mail_http_endpoint");
+                values.push(endpoint.clone());
+                keys.push("::mail_http_api_key");
+                values.push(
+                    database
+                        .encrypt(api_key)
+                        .map(|b| base32::encode(base32::Alphabet::Z, &b))
+                        .unwrap_or_default(),
+                );
+                keys.push("::mail_http_from_address");
+                values.push(from_address.clone());
+                keys.push("::mail_http_from_name");
+                values.push(from_name.clone().unwrap_or_default());
+            }
         }
 
         match &self.captcha_provider {
@@ -359,6 +791,15 @@ impl AppSettings {
         let (keys_server, values_server) = self.server.serialize();
         keys.extend(keys_server);
         values.extend(values_server);
+        let (keys_security, values_security) = self.security.serialize();
+        keys.extend(keys_security);
+        values.extend(values_security);
+        let (keys_oidc, values_oidc) = self.oidc.serialize(database);
+        keys.extend(keys_oidc);
+        values.extend(values_oidc);
+        let (keys_ldap, values_ldap) = self.ldap.serialize();
+        keys.extend(keys_ldap);
+        values.extend(values_ldap);
 
         (keys, values)
     }
@@ -465,6 +906,43 @@ impl AppSettings {
                         .remove("::mail_smtp_from_name")
                         .filter(|s| !s.is_empty()),
                 },
+                Some("sendmail") => MailMode::Sendmail {
+                    from_address: map
+                        .remove("::mail_sendmail_from_address")
+                        .unwrap_or_else(|| "noreply@example.com".to_string()),
+                    from_name: map
+                        .remove("::mail_sendmail_from_name")
+                        .filter(|s| !s.is_empty()),
+                },
+                Some("file") => MailMode::File {
+                    from_address: map
+                        .remove("::mail_file_from_address")
+                        .unwrap_or_else(|| "noreply@example.com".to_string()),
+                    from_name: map
+                        .remove("::mail_file_from_name")
+                        .filter(|s| !s.is_empty()),
+                },
+                Some("http") => MailMode::Http {
+                    provider: match map.remove("::mail_http_provider").as_deref() {
+                        Some("postmark") => MailHttpProvider::Postmark,
+                        Some("json") => MailHttpProvider::Json,
+                        _ => MailHttpProvider::Mailgun,
+                    },
+                    endpoint: map
+                        .remove("::mail_http_endpoint")
+                        .unwrap_or_else(|| "https://api.mailgun.net/v3/example.com".to_string()),
+                    api_key: map
+                        .remove("::mail_http_api_key")
+                        .and_then(|s| base32::decode(base32::Alphabet::Z, &s))
+                        .and_then(|b| database.decrypt(&b))
+                        .unwrap_or_default(),
+                    from_address: map
+                        .remove("::mail_http_from_address")
+                        .unwrap_or_else(|| "noreply@example.com".to_string()),
+                    from_name: map
+                        .remove("::mail_http_from_name")
+                        .filter(|s| !s.is_empty()),
+                },
                 _ => MailMode::None,
             },
             captcha_provider: match map.remove("::captcha_provider").as_deref() {
@@ -495,6 +973,9 @@ impl AppSettings {
             app: AppSettingsApp::deserialize(map),
             webauthn: AppSettingsWebauthn::deserialize(map),
             server: AppSettingsServer::deserialize(map),
+            security: AppSettingsSecurity::deserialize(map),
+            oidc: AppSettingsOidc::deserialize(map, database),
+            ldap: AppSettingsLdap::deserialize(map),
         }
     }
 }
@@ -532,10 +1013,10 @@ impl<'a> SettingsGuard<'a> {
                         censor_values(k, v);
                     }
                 }
-                serde_json::Value::String(s) => {
-                    if key.contains("password") {
-                        *s = "*".repeat(s.len());
-                    }
+                serde_json::Value::String(s)
+                    if key.contains("password") || key.ends_with("secret") || key == "api_key" =>
+                {
+                    *s = "*".repeat(s.len());
                 }
                 _ => {}
             }
@@ -48,11 +48,15 @@ impl<'a> StorageUrlRetriever<'a> {
 
 pub struct Storage {
     settings: Arc<super::settings::Settings>,
+    database: Arc<super::database::Database>,
 }
 
 impl Storage {
-    pub fn new(settings: Arc<super::settings::Settings>) -> Self {
-        Self { settings }
+    pub fn new(
+        settings: Arc<super::settings::Settings>,
+        database: Arc<super::database::Database>,
+    ) -> Self {
+        Self { settings, database }
     }
 
     pub async fn retrieve_urls(&self) -> StorageUrlRetriever<'_> {
@@ -80,19 +84,15 @@ impl Storage {
                     return Err(err.into());
                 }
 
-                if let Some(parent) = path.parent().map(|p| p.to_path_buf()) {
-                    tokio::spawn(async move {
-                        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
-
-                        let mut directory = match tokio::fs::read_dir(&parent).await {
-                            Ok(directory) => directory,
-                            Err(_) => return,
-                        };
-
-                        if directory.next_entry().await.is_ok_and(|e| e.is_none()) {
-                            tokio::fs::remove_dir(parent).await.ok();
-                        }
-                    });
+                if let Some(parent) = path.parent().map(|p| p.to_path_buf())
+                    && let Err(err) = super::jobs::enqueue_in(
+                        &self.database,
+                        &super::jobs::storage::RemoveEmptyDirectory { path: parent },
+                        std::time::Duration::from_secs(10),
+                    )
+                    .await
+                {
+                    tracing::error!("failed to queue storage directory cleanup: {:#?}", err);
                 }
             }
             super::settings::StorageDriver::S3 {
@@ -2,7 +2,7 @@
 use super::*;
 use reqwest::{Client, Method, StatusCode};
 use serde::de::DeserializeOwned;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 
 static CLIENT: LazyLock<Client> = LazyLock::new(|| {
     Client::builder()
@@ -18,6 +18,24 @@ async fn request_impl<T: DeserializeOwned + 'static>(
     endpoint: impl AsRef<str>,
     body: Option<&impl Serialize>,
     body_raw: Option<String>,
+) -> Result<T, (StatusCode, super::ApiError)> {
+    let start = std::time::Instant::now();
+    let result = request_inner(client, method, endpoint, body, body_raw).await;
+
+    if let Some(observer) = &client.observer {
+        observer(start.elapsed(), result.is_err());
+    }
+
+    result
+}
+
+#[inline]
+async fn request_inner<T: DeserializeOwned + 'static>(
+    client: &WingsClient,
+    method: Method,
+    endpoint: impl AsRef<str>,
+    body: Option<&impl Serialize>,
+    body_raw: Option<String>,
 ) -> Result<T, (StatusCode, super::ApiError)> {
     let url = format!(
         "{}{}",
@@ -80,15 +98,29 @@ async fn request_impl<T: DeserializeOwned + 'static>(
     }
 }
 
+/// Called after every request with its duration and whether it failed.
+pub type RequestObserver = Arc<dyn Fn(std::time::Duration, bool) + Send + Sync>;
+
 pub struct WingsClient {
     base_url: String,
     token: String,
+    observer: Option<RequestObserver>,
 }
 
 impl WingsClient {
     #[inline]
     pub fn new(base_url: String, token: String) -> Self {
-        Self { base_url, token }
+        Self {
+            base_url,
+            token,
+            observer: None,
+        }
+    }
+
+    #[inline]
+    pub fn with_observer(mut self, observer: RequestObserver) -> Self {
+        self.observer = Some(observer);
+        self
     }
 
     pub async fn delete_backups_backup(